@@ -4,9 +4,13 @@
 // This software is released under the MIT License.
 // See the LICENSE file in the project root for full license information.
 
+mod repl;
+
+use anyhow::Context;
 use clap::{Parser, Subcommand};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::process::ExitCode;
 use std::str::FromStr;
@@ -14,7 +18,10 @@ use std::str::FromStr;
 use std::sync::{Arc, Mutex};
 use tracing::{error, info};
 
-use labwired_config::{load_test_script, LoadedTestScript, StopReason, TestAssertion, TestLimits};
+use labwired_config::{
+    load_test_script, LoadedTestScript, MemoryEqualsAssertion, MustReach, StopReason,
+    TestAssertion, TestLimits, TestPolicy,
+};
 
 const EXIT_PASS: u8 = 0;
 const EXIT_ASSERT_FAIL: u8 = 1;
@@ -35,6 +42,41 @@ fn parse_u32_addr(s: &str) -> Result<u32, String> {
     }
 }
 
+/// Loader selected by `--bin-base`: a flat binary at that address, ELF otherwise.
+fn firmware_format(bin_base: Option<u32>) -> labwired_loader::FirmwareFormat {
+    match bin_base {
+        Some(base) => labwired_loader::FirmwareFormat::Bin {
+            base_addr: base as u64,
+        },
+        None => labwired_loader::FirmwareFormat::Elf,
+    }
+}
+
+fn parse_fault_behavior(s: &str) -> Result<FaultBehavior, String> {
+    match s.trim().to_ascii_lowercase().as_str() {
+        "hardfault" => Ok(FaultBehavior::HardFault),
+        "propagate" => Ok(FaultBehavior::Propagate),
+        _ => Err(format!(
+            "Invalid fault behavior '{}': expected 'hardfault' or 'propagate'",
+            s
+        )),
+    }
+}
+
+/// Core names `--core` accepts, as advertised by `labwired capabilities`.
+const ARCHITECTURES: &[&str] = &["cortex-m", "riscv32"];
+
+fn parse_core(s: &str) -> Result<labwired_config::Arch, String> {
+    match s.trim().to_ascii_lowercase().as_str() {
+        "cortex-m" => Ok(labwired_config::Arch::Arm),
+        "riscv32" | "riscv" => Ok(labwired_config::Arch::RiscV),
+        _ => Err(format!(
+            "Invalid core '{}': expected 'cortex-m' or 'riscv32'",
+            s
+        )),
+    }
+}
+
 #[derive(Parser, Debug)]
 #[command(
     author,
@@ -64,14 +106,108 @@ struct Cli {
     #[arg(short, long, global = true)]
     trace: bool,
 
+    /// Only log warnings and errors
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// Increase log verbosity (-v: debug, -vv: trace); independent of --trace
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    verbose: u8,
+
     /// Maximum number of steps to execute (default: 20000)
     #[arg(long, default_value = "20000")]
     max_steps: usize,
 
+    /// Stop once the registers stay unchanged for STEPS steps with no
+    /// exception pending (a deliberate `loop {}`); 0 disables the check
+    #[arg(long, value_name = "STEPS", default_value = "1000")]
+    halt_window: u32,
+
+    /// Sleep between steps so the simulated clock runs at HZ cycles per
+    /// wall-clock second (for watching UART output at a human pace)
+    #[arg(long, value_name = "HZ", value_parser = clap::value_parser!(u64).range(1..))]
+    realtime: Option<u64>,
+
+    /// Core to simulate, 'cortex-m' or 'riscv32' (default: the chip's `arch`)
+    #[arg(long, value_parser = parse_core)]
+    core: Option<labwired_config::Arch>,
+
+    /// Print SEGGER RTT up-channel 0 output, from a control block found in RAM
+    #[arg(long)]
+    rtt: bool,
+
     /// Start a GDB server on the specified port
     #[arg(long)]
     gdb: Option<u16>,
 
+    /// Write a resumable machine checkpoint (JSON) to this path.
+    #[arg(long, requires = "checkpoint_every")]
+    checkpoint: Option<PathBuf>,
+
+    /// Rewrite the checkpoint every N steps (requires --checkpoint).
+    #[arg(long, requires = "checkpoint")]
+    checkpoint_every: Option<u64>,
+
+    /// Resume from a checkpoint written by --checkpoint instead of starting from reset.
+    #[arg(long)]
+    resume: Option<PathBuf>,
+
+    /// Bridge the UART to a TCP port on localhost (connect with `nc`/`telnet`).
+    #[arg(long, conflicts_with = "stdin_uart")]
+    uart_tcp: Option<u16>,
+
+    /// Feed stdin to the RX FIFO of the UART named NAME (e.g. uart1), for
+    /// driving firmware shells from a terminal or a pipe.
+    #[arg(long, value_name = "NAME")]
+    stdin_uart: Option<String>,
+
+    /// Keep the last N executed instructions (default: 32) and dump them if the run faults.
+    #[arg(long, value_name = "N", num_args = 0..=1, default_missing_value = "32")]
+    trace_on_fault: Option<usize>,
+
+    /// Write GPIO output pins and interrupts as a VCD waveform when the run ends
+    #[arg(long, value_name = "PATH")]
+    vcd: Option<PathBuf>,
+
+    /// Write every executed instruction to PATH as JSONL (pc, opcode, mnemonic, regs_changed)
+    #[arg(long, value_name = "PATH")]
+    trace_file: Option<PathBuf>,
+
+    /// Log NVIC enable/pend/active transitions as a timeline
+    #[arg(long)]
+    trace_nvic: bool,
+
+    /// Log how many instructions of each class (alu, load, store, branch, ...)
+    /// executed, most frequent first
+    #[arg(long)]
+    instruction_mix: bool,
+
+    /// Write per-function instruction and cycle counts (self-time) to PATH as JSON
+    #[arg(long, value_name = "PATH", conflicts_with = "bin_base")]
+    profile: Option<PathBuf>,
+
+    /// Write executed instruction addresses, basic-block hit counts and branch
+    /// outcomes (plus uncovered source lines, given debug info) to PATH as JSON
+    #[arg(long, value_name = "PATH")]
+    coverage: Option<PathBuf>,
+
+    /// Inject GPIO, UART and interrupt events at given steps from a YAML signals file
+    #[arg(long, value_name = "PATH")]
+    signals: Option<PathBuf>,
+
+    /// Clock cycles to advance (ticking peripherals) after reset before the first instruction
+    #[arg(long, default_value = "0")]
+    startup_cycles: u32,
+
+    /// Treat the firmware as a flat binary loaded at ADDR instead of an ELF
+    #[arg(long, value_name = "ADDR", value_parser = parse_u32_addr)]
+    bin_base: Option<u32>,
+
+    /// Reject an ELF whose machine type does not match the configured core
+    /// (otherwise a mismatch only warns)
+    #[arg(long)]
+    architecture_check: bool,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
@@ -79,7 +215,108 @@ struct Cli {
 #[derive(Subcommand, Debug)]
 enum Commands {
     /// Deterministic, CI-friendly runner mode driven by a test script (YAML).
-    Test(TestArgs),
+    Test(Box<TestArgs>),
+    /// Print supported architectures, peripherals, assertions and outputs as JSON.
+    Capabilities,
+    /// Interactive debugger reading commands from stdin (s, c N, r, m ADDR LEN, b ADDR, q).
+    Repl(ReplArgs),
+    /// Run for a fixed number of steps, writing a machine snapshot every N steps.
+    Record(RecordArgs),
+    /// Print which IRQ each peripheral raises and whether the NVIC enables it at start.
+    IrqMap(IrqMapArgs),
+    /// Disassemble the Thumb code of a firmware ELF.
+    Disasm(DisasmArgs),
+    /// Load a firmware ELF and serve it to a GDB client over TCP.
+    Gdb(GdbArgs),
+}
+
+#[derive(Parser, Debug)]
+struct GdbArgs {
+    /// Path to the firmware ELF file
+    #[arg(short = 'f', long)]
+    firmware: PathBuf,
+
+    /// Path to the system manifest (YAML)
+    #[arg(short = 's', long)]
+    system: Option<PathBuf>,
+
+    /// TCP port to listen on
+    #[arg(short = 'p', long, default_value = "3333")]
+    port: u16,
+
+    /// Serve the session over stdin/stdout instead of TCP, for
+    /// `target remote | labwired gdb ...`; logs go to stderr
+    #[arg(long, conflicts_with = "port")]
+    stdio: bool,
+
+    /// Core to simulate, 'cortex-m' or 'riscv32' (default: the chip's `arch`,
+    /// or the firmware ELF's machine type without a system manifest)
+    #[arg(long, value_parser = parse_core)]
+    core: Option<labwired_config::Arch>,
+}
+
+#[derive(Parser, Debug)]
+struct DisasmArgs {
+    /// Path to the firmware ELF file
+    #[arg(short = 'f', long)]
+    firmware: PathBuf,
+
+    /// First address to decode (default: the start of the segment holding the entry point)
+    #[arg(long, value_parser = parse_u32_addr)]
+    start: Option<u32>,
+
+    /// Number of instructions to print (default: up to the end of the segment)
+    #[arg(long)]
+    count: Option<usize>,
+}
+
+#[derive(Parser, Debug)]
+struct IrqMapArgs {
+    /// Path to the system manifest (YAML); the default bus is used without one
+    #[arg(short = 's', long)]
+    system: Option<PathBuf>,
+}
+
+#[derive(Parser, Debug)]
+struct RecordArgs {
+    /// Path to the firmware ELF file
+    #[arg(short = 'f', long)]
+    firmware: PathBuf,
+
+    /// Path to the system manifest (YAML)
+    #[arg(short = 's', long)]
+    system: Option<PathBuf>,
+
+    /// Total steps to run
+    #[arg(long)]
+    steps: u64,
+
+    /// Write a snapshot every N steps
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    interval: u64,
+
+    /// Directory for the numbered snapshot files (snapshot-000001.json, ...)
+    #[arg(short = 'o', long)]
+    output_dir: PathBuf,
+
+    /// Include the instructions executed since the previous snapshot
+    #[arg(long)]
+    include_trace: bool,
+}
+
+#[derive(Parser, Debug)]
+struct ReplArgs {
+    /// Path to the firmware ELF file
+    #[arg(short = 'f', long)]
+    firmware: PathBuf,
+
+    /// Path to the system manifest (YAML)
+    #[arg(short = 's', long)]
+    system: Option<PathBuf>,
+
+    /// Steps a bare `c` runs before stopping
+    #[arg(long, default_value = "20000")]
+    max_steps: u32,
 }
 
 #[derive(Parser, Debug)]
@@ -127,6 +364,59 @@ struct TestArgs {
     /// Number of steps with no PC change to detect stuck state (default: None)
     #[arg(long, alias = "no-progress")]
     detect_stuck: Option<u64>,
+
+    /// Stop with `halt` once the registers stay unchanged for STEPS steps with
+    /// no exception pending (a deliberate `loop {}`); checked before --detect-stuck
+    #[arg(long, value_name = "STEPS")]
+    halt_window: Option<u32>,
+
+    /// How Cortex-M memory violations are handled: 'hardfault' escalates to the
+    /// firmware's HardFault handler when one is installed, 'propagate' stops the
+    /// run with a memory violation
+    #[arg(long, value_parser = parse_fault_behavior, default_value = "hardfault")]
+    fault_behavior: FaultBehavior,
+
+    /// Clock cycles to advance (ticking peripherals) after reset before the first instruction
+    #[arg(long, default_value = "0")]
+    startup_cycles: u32,
+
+    /// Golden text file the captured UART output must match line by line; a
+    /// mismatch fails the test and prints a unified diff to stderr
+    #[arg(long)]
+    compare_uart: Option<PathBuf>,
+
+    /// Rewrite the --compare-uart file with the captured UART output, and
+    /// `registers_match_file` files with the final registers
+    #[arg(long)]
+    update_golden: bool,
+
+    /// Inject GPIO, UART and interrupt events at given steps from a YAML signals file
+    #[arg(long, value_name = "PATH")]
+    signals: Option<PathBuf>,
+
+    /// Treat the firmware as a flat binary loaded at ADDR instead of an ELF
+    #[arg(long, value_name = "ADDR", value_parser = parse_u32_addr)]
+    bin_base: Option<u32>,
+
+    /// Reject an ELF whose machine type does not match the configured core
+    /// (otherwise a mismatch only warns)
+    #[arg(long)]
+    architecture_check: bool,
+
+    /// Write the captured UART bytes with the step and cycle that emitted
+    /// each one as CSV (`step,cycle,byte,ascii`)
+    #[arg(long, value_name = "PATH")]
+    uart_csv: Option<PathBuf>,
+
+    /// Core to simulate, 'cortex-m' or 'riscv32' (default: the firmware ELF's
+    /// machine type)
+    #[arg(long, value_parser = parse_core)]
+    core: Option<labwired_config::Arch>,
+
+    /// Capture SEGGER RTT up-channel 0 output with the UART output, from a
+    /// control block found in RAM
+    #[arg(long)]
+    rtt: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -143,6 +433,9 @@ struct TestResult {
     message: Option<String>,
     assertions: Vec<AssertionResult>,
     firmware_hash: String,
+    /// GNU build-id of the firmware ELF, when it carries one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    firmware_build_id: Option<String>,
     config: TestConfig,
 }
 
@@ -172,7 +465,36 @@ struct TestConfig {
     script: PathBuf,
 }
 
-use labwired_core::snapshot::CpuSnapshot;
+use labwired_core::cpu::FaultBehavior;
+use labwired_core::snapshot::{CpuSnapshot, MachineSnapshot};
+use labwired_core::DebugControl;
+
+const CHECKPOINT_SCHEMA_VERSION: &str = "1.0";
+
+const RECORD_SCHEMA_VERSION: &str = "1.0";
+
+/// One sample written by `labwired record`.
+#[derive(Debug, Serialize)]
+struct RecordSnapshot {
+    record_schema_version: String,
+    /// 1-based sample number, matching the file name.
+    index: u64,
+    steps_executed: u64,
+    cycles: u64,
+    instructions: u64,
+    machine: MachineSnapshot,
+    /// Instructions executed since the previous sample (--include-trace).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    trace: Option<Vec<labwired_core::trace::TraceEntry>>,
+}
+
+/// Full machine state written by `--checkpoint` and consumed by `--resume`.
+#[derive(Debug, Serialize, Deserialize)]
+struct Checkpoint {
+    checkpoint_schema_version: String,
+    steps_executed: u64,
+    machine: MachineSnapshot,
+}
 
 #[derive(Debug, Serialize, Deserialize)]
 struct PeripheralSnapshot {
@@ -193,6 +515,7 @@ struct InteractiveSnapshotConfig {
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 enum Snapshot {
+    #[serde(rename = "cortex_m")]
     Standard {
         cpu: CpuSnapshot,
         steps_executed: u64,
@@ -210,6 +533,7 @@ enum Snapshot {
         limits: TestLimits,
         config: TestConfig,
     },
+    #[serde(rename = "interactive_cortex_m")]
     Interactive {
         snapshot_schema_version: String,
         status: String,
@@ -281,10 +605,7 @@ fn write_interactive_snapshot<C: labwired_core::Cpu>(
 
     let snapshot = Snapshot::Interactive {
         snapshot_schema_version: "1.0".to_string(),
-        status: if matches!(
-            inputs.stop_reason,
-            StopReason::MemoryViolation | StopReason::DecodeError
-        ) {
+        status: if is_error_stop(&inputs.stop_reason) {
             "error".to_string()
         } else {
             "ok".to_string()
@@ -313,26 +634,484 @@ fn write_interactive_snapshot<C: labwired_core::Cpu>(
         Err(e) => error!("Failed to create snapshot {:?}: {}", path, e),
     }
 }
+fn write_checkpoint<C: labwired_core::Cpu>(
+    path: &Path,
+    machine: &labwired_core::Machine<C>,
+    steps_executed: u64,
+) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let checkpoint = Checkpoint {
+        checkpoint_schema_version: CHECKPOINT_SCHEMA_VERSION.to_string(),
+        steps_executed,
+        machine: machine.snapshot(),
+    };
+    // Write to a sibling file first so an interrupted run never leaves a torn checkpoint.
+    let tmp_path = path.with_extension("tmp");
+    std::fs::write(&tmp_path, serde_json::to_vec(&checkpoint)?)?;
+    std::fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+/// Restore machine state from `path`, returning the number of steps already executed.
+fn resume_from_checkpoint<C: labwired_core::Cpu>(
+    path: &Path,
+    machine: &mut labwired_core::Machine<C>,
+) -> anyhow::Result<u64> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("Failed to read checkpoint {:?}: {}", path, e))?;
+    let checkpoint: Checkpoint = serde_json::from_str(&content)
+        .map_err(|e| anyhow::anyhow!("Failed to parse checkpoint {:?}: {}", path, e))?;
+    if checkpoint.checkpoint_schema_version != CHECKPOINT_SCHEMA_VERSION {
+        anyhow::bail!(
+            "Unsupported checkpoint_schema_version '{}' (expected '{}')",
+            checkpoint.checkpoint_schema_version,
+            CHECKPOINT_SCHEMA_VERSION
+        );
+    }
+    machine.restore(&checkpoint.machine)?;
+    Ok(checkpoint.steps_executed)
+}
+
 fn main() -> ExitCode {
     let cli = Cli::parse();
 
-    // Initialize tracing with appropriate level based on --trace flag
-    if cli.trace {
-        tracing_subscriber::fmt()
-            .with_max_level(tracing::Level::DEBUG)
-            .init();
-    } else {
-        tracing_subscriber::fmt()
-            .with_max_level(tracing::Level::INFO)
-            .init();
-    }
+    init_logging(&cli);
 
     match cli.command {
-        Some(Commands::Test(args)) => run_test(args),
+        Some(Commands::Test(args)) => run_test(*args),
+        Some(Commands::Capabilities) => run_capabilities(),
+        Some(Commands::Repl(args)) => run_repl(args),
+        Some(Commands::Record(args)) => run_record(args),
+        Some(Commands::IrqMap(args)) => run_irq_map(args),
+        Some(Commands::Disasm(args)) => run_disasm(args),
+        Some(Commands::Gdb(args)) => run_gdb(args),
         None => run_interactive(cli),
     }
 }
 
+/// Log level comes from --quiet/-v; per-instruction logs are shown only with --trace.
+fn init_logging(cli: &Cli) {
+    use tracing_subscriber::filter::{LevelFilter, Targets};
+    use tracing_subscriber::fmt::writer::BoxMakeWriter;
+    use tracing_subscriber::prelude::*;
+
+    let level = if cli.quiet {
+        LevelFilter::WARN
+    } else {
+        match cli.verbose {
+            0 => LevelFilter::INFO,
+            1 => LevelFilter::DEBUG,
+            _ => LevelFilter::TRACE,
+        }
+    };
+    let instruction_level = if cli.trace {
+        LevelFilter::TRACE
+    } else {
+        LevelFilter::OFF
+    };
+    let filter = Targets::new()
+        .with_default(level)
+        .with_target(labwired_core::INSTRUCTION_TRACE_TARGET, instruction_level);
+
+    // Stdout carries the GDB protocol with `gdb --stdio`
+    let stdio = matches!(&cli.command, Some(Commands::Gdb(args)) if args.stdio);
+    let writer = if stdio {
+        BoxMakeWriter::new(std::io::stderr)
+    } else {
+        BoxMakeWriter::new(std::io::stdout)
+    };
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::fmt::layer().with_writer(writer))
+        .with(filter)
+        .init();
+}
+
+#[derive(Debug, Serialize)]
+struct Capabilities {
+    version: &'static str,
+    result_schema_version: &'static str,
+    test_script_schema_versions: &'static [&'static str],
+    architectures: &'static [&'static str],
+    peripheral_types: &'static [&'static str],
+    assertion_kinds: &'static [&'static str],
+    output_formats: &'static [&'static str],
+}
+
+fn run_capabilities() -> ExitCode {
+    let capabilities = Capabilities {
+        version: env!("CARGO_PKG_VERSION"),
+        result_schema_version: RESULT_SCHEMA_VERSION,
+        test_script_schema_versions: &["1.0"],
+        architectures: ARCHITECTURES,
+        peripheral_types: labwired_core::bus::PERIPHERAL_TYPES,
+        assertion_kinds: labwired_config::TestAssertion::KINDS,
+        output_formats: &["result-json", "junit-xml", "uart-log", "snapshot-json"],
+    };
+    match serde_json::to_string_pretty(&capabilities) {
+        Ok(json) => {
+            println!("{}", json);
+            ExitCode::from(EXIT_PASS)
+        }
+        Err(e) => {
+            error!("Failed to serialize capabilities: {}", e);
+            ExitCode::from(EXIT_RUNTIME_ERROR)
+        }
+    }
+}
+
+fn run_irq_map(args: IrqMapArgs) -> ExitCode {
+    let mut bus = match build_bus(args.system) {
+        Ok(bus) => bus,
+        Err(e) => {
+            tracing::error!("{:#}", e);
+            return ExitCode::from(EXIT_CONFIG_ERROR);
+        }
+    };
+    // Attach the NVIC so enable state is reported as firmware would see it at reset.
+    let _ = labwired_core::system::cortex_m::configure_cortex_m(&mut bus);
+
+    for m in bus.irq_map() {
+        let irq = match m.irq {
+            Some(irq) => format!("IRQ {}", irq),
+            None => "no IRQ".to_string(),
+        };
+        let nvic = match (m.irq, m.nvic_enabled) {
+            (_, Some(true)) => "nvic enabled",
+            (_, Some(false)) => "nvic disabled",
+            (Some(_), None) => "core exception",
+            (None, None) => "-",
+        };
+        println!("{} -> {} (base {:#010x}, {})", m.name, irq, m.base, nvic);
+    }
+    ExitCode::from(EXIT_PASS)
+}
+
+fn run_disasm(args: DisasmArgs) -> ExitCode {
+    use labwired_core::decoder::arm::{self, Instruction};
+    use std::io::Write;
+
+    let program = match labwired_loader::load_elf(&args.firmware) {
+        Ok(program) => program,
+        Err(e) => {
+            tracing::error!("{:#}", e);
+            return ExitCode::from(EXIT_CONFIG_ERROR);
+        }
+    };
+    if program.arch != labwired_core::Arch::Arm {
+        error!(
+            "disasm decodes Thumb code only, the firmware is {:?}",
+            program.arch
+        );
+        return ExitCode::from(EXIT_CONFIG_ERROR);
+    }
+    let addr = args
+        .start
+        .map_or(program.entry_point & !1, |start| start as u64);
+    let Some(segment) = program
+        .segments
+        .iter()
+        .find(|s| (s.start_addr..s.start_addr + s.data.len() as u64).contains(&addr))
+    else {
+        error!("{:#x} is not in a loaded segment", addr);
+        return ExitCode::from(EXIT_CONFIG_ERROR);
+    };
+    let start = match args.start {
+        Some(start) => start as u64 & !1,
+        None => segment.start_addr,
+    };
+
+    let code = &segment.data[(start - segment.start_addr) as usize..];
+    let half = |at: usize| {
+        code.get(at..at + 2)
+            .map(|b| u16::from_le_bytes([b[0], b[1]]))
+    };
+    let mut out = std::io::stdout().lock();
+    let mut offset = 0;
+    for _ in 0..args.count.unwrap_or(usize::MAX) {
+        let Some(h1) = half(offset) else {
+            break;
+        };
+        let (len, text) = match arm::decode_thumb_16(h1) {
+            Instruction::Prefix32(h1) => match half(offset + 2) {
+                Some(h2) => {
+                    let text = match arm::decode_thumb_32(h1, h2) {
+                        Instruction::Unknown(_) if arm::is_coprocessor(h1) => {
+                            arm::decode_vfp(h1, h2).map(|vfp| vfp.to_string())
+                        }
+                        Instruction::Unknown(_) => None,
+                        instruction => Some(instruction.to_string()),
+                    };
+                    let text = text.unwrap_or_else(|| format!(".inst.w 0x{:04x}{:04x}", h1, h2));
+                    (4, format!("{:04x} {:04x}  {}", h1, h2, text))
+                }
+                None => (2, format!("{:04x}       .inst 0x{:04x}", h1, h1)),
+            },
+            instruction => (2, format!("{:04x}       {}", h1, instruction)),
+        };
+        // Stop quietly when the reader goes away (e.g. piped into `head`)
+        if writeln!(out, "{:#010x}: {}", start + offset as u64, text).is_err() {
+            break;
+        }
+        offset += len;
+    }
+    ExitCode::from(EXIT_PASS)
+}
+
+fn run_gdb(args: GdbArgs) -> ExitCode {
+    let mut bus = match build_bus(args.system.clone()) {
+        Ok(bus) => bus,
+        Err(e) => {
+            tracing::error!("{:#}", e);
+            return ExitCode::from(EXIT_CONFIG_ERROR);
+        }
+    };
+    let program = match labwired_loader::load_elf(&args.firmware) {
+        Ok(program) => program,
+        Err(e) => {
+            tracing::error!("{:#}", e);
+            return ExitCode::from(EXIT_CONFIG_ERROR);
+        }
+    };
+
+    let configured = match (args.core, &args.system) {
+        (Some(arch), _) => Ok(arch),
+        (None, Some(system)) => configured_arch(Some(system)),
+        (None, None) => Ok(match program.arch {
+            labwired_core::Arch::RiscV => labwired_config::Arch::RiscV,
+            _ => labwired_config::Arch::Arm,
+        }),
+    };
+    let arch = match configured {
+        Ok(arch) => arch,
+        Err(e) => {
+            tracing::error!("{:#}", e);
+            return ExitCode::from(EXIT_CONFIG_ERROR);
+        }
+    };
+    let format = labwired_loader::FirmwareFormat::Elf;
+    if let Err(msg) = check_architecture(&program, format, arch, false) {
+        tracing::error!("{}", msg);
+        return ExitCode::from(EXIT_CONFIG_ERROR);
+    }
+
+    let server = gdb_server(args.port, Some(&args.firmware));
+    let result = match arch {
+        labwired_config::Arch::RiscV => {
+            let cpu = labwired_core::system::riscv::configure_riscv(&mut bus);
+            match loaded_machine(cpu, bus, &program) {
+                Ok(machine) if args.stdio => server.run_stdio(machine),
+                Ok(machine) => server.run(machine),
+                Err(code) => return code,
+            }
+        }
+        _ => {
+            let (cpu, _nvic) = labwired_core::system::cortex_m::configure_cortex_m(&mut bus);
+            match loaded_machine(cpu, bus, &program) {
+                Ok(machine) if args.stdio => server.run_stdio(machine),
+                Ok(machine) => server.run(machine),
+                Err(code) => return code,
+            }
+        }
+    };
+    match result {
+        Ok(()) => ExitCode::from(EXIT_PASS),
+        Err(e) => {
+            error!("GDB server failed: {}", e);
+            ExitCode::from(EXIT_RUNTIME_ERROR)
+        }
+    }
+}
+
+/// A machine for `cpu` on `bus` with `program` loaded and reset into.
+fn loaded_machine<C: labwired_core::Cpu>(
+    cpu: C,
+    bus: labwired_core::bus::SystemBus,
+    program: &labwired_core::memory::ProgramImage,
+) -> Result<labwired_core::Machine<C>, ExitCode> {
+    let mut machine = labwired_core::Machine::new(cpu, bus);
+    if let Err(e) = machine.load_firmware(program) {
+        tracing::error!("Failed to load firmware into memory: {}", e);
+        return Err(ExitCode::from(EXIT_RUNTIME_ERROR));
+    }
+    Ok(machine)
+}
+
+fn run_repl(args: ReplArgs) -> ExitCode {
+    let mut bus = match build_bus(args.system.clone()) {
+        Ok(bus) => bus,
+        Err(e) => {
+            tracing::error!("{:#}", e);
+            return ExitCode::from(EXIT_CONFIG_ERROR);
+        }
+    };
+    let program = match labwired_loader::load_elf(&args.firmware) {
+        Ok(program) => program,
+        Err(e) => {
+            tracing::error!("{:#}", e);
+            return ExitCode::from(EXIT_CONFIG_ERROR);
+        }
+    };
+
+    let registers: Vec<String> = match program.arch {
+        labwired_core::Arch::RiscV => (0..32)
+            .map(|i| format!("x{}", i))
+            .chain(["pc".to_string()])
+            .collect(),
+        _ => (0..13)
+            .map(|i| format!("r{}", i))
+            .chain(["sp", "lr", "pc"].map(String::from))
+            .collect(),
+    };
+    match program.arch {
+        labwired_core::Arch::RiscV => {
+            let cpu = labwired_core::system::riscv::configure_riscv(&mut bus);
+            run_repl_machine(cpu, bus, &program, &registers, &args)
+        }
+        _ => {
+            let (cpu, _nvic) = labwired_core::system::cortex_m::configure_cortex_m(&mut bus);
+            run_repl_machine(cpu, bus, &program, &registers, &args)
+        }
+    }
+}
+
+fn run_repl_machine<C: labwired_core::Cpu>(
+    cpu: C,
+    bus: labwired_core::bus::SystemBus,
+    program: &labwired_core::memory::ProgramImage,
+    registers: &[String],
+    args: &ReplArgs,
+) -> ExitCode {
+    use std::io::IsTerminal;
+
+    let mut machine = match loaded_machine(cpu, bus, program) {
+        Ok(machine) => machine,
+        Err(code) => return code,
+    };
+
+    let stdin = std::io::stdin();
+    let prompt = stdin.is_terminal();
+    match repl::run(
+        &mut machine,
+        registers,
+        args.max_steps,
+        prompt,
+        stdin.lock(),
+        std::io::stdout(),
+    ) {
+        Ok(()) => ExitCode::from(EXIT_PASS),
+        Err(e) => {
+            error!("REPL I/O error: {}", e);
+            ExitCode::from(EXIT_RUNTIME_ERROR)
+        }
+    }
+}
+
+fn run_record(args: RecordArgs) -> ExitCode {
+    let mut bus = match build_bus(args.system.clone()) {
+        Ok(bus) => bus,
+        Err(e) => {
+            tracing::error!("{:#}", e);
+            return ExitCode::from(EXIT_CONFIG_ERROR);
+        }
+    };
+    let program = match labwired_loader::load_elf(&args.firmware) {
+        Ok(program) => program,
+        Err(e) => {
+            tracing::error!("{:#}", e);
+            return ExitCode::from(EXIT_CONFIG_ERROR);
+        }
+    };
+    if let Err(e) = std::fs::create_dir_all(&args.output_dir) {
+        tracing::error!("Failed to create {:?}: {}", args.output_dir, e);
+        return ExitCode::from(EXIT_CONFIG_ERROR);
+    }
+
+    match program.arch {
+        labwired_core::Arch::RiscV => {
+            let cpu = labwired_core::system::riscv::configure_riscv(&mut bus);
+            record_machine(
+                labwired_core::Machine::new(cpu, bus),
+                &program,
+                labwired_core::Arch::RiscV,
+                &args,
+            )
+        }
+        _ => {
+            let (cpu, _nvic) = labwired_core::system::cortex_m::configure_cortex_m(&mut bus);
+            record_machine(
+                labwired_core::Machine::new(cpu, bus),
+                &program,
+                labwired_core::Arch::Arm,
+                &args,
+            )
+        }
+    }
+}
+
+fn record_machine<C: labwired_core::Cpu>(
+    mut machine: labwired_core::Machine<C>,
+    program: &labwired_core::memory::ProgramImage,
+    arch: labwired_core::Arch,
+    args: &RecordArgs,
+) -> ExitCode {
+    let metrics = Arc::new(labwired_core::metrics::PerformanceMetrics::new());
+    machine.observers.push(metrics.clone());
+    let trace = args.include_trace.then(|| {
+        let trace = Arc::new(labwired_core::trace::InstructionTrace::new(
+            arch,
+            args.interval as usize,
+        ));
+        machine.observers.push(trace.clone());
+        trace
+    });
+
+    if let Err(e) = machine.load_firmware(program) {
+        tracing::error!("Failed to load firmware into memory: {}", e);
+        return ExitCode::from(EXIT_RUNTIME_ERROR);
+    }
+
+    info!(
+        "Recording {} steps, snapshot every {} into {:?}",
+        args.steps, args.interval, args.output_dir
+    );
+    for step in 1..=args.steps {
+        if let Err(e) = machine.step() {
+            error!("Simulation error at step {}: {}", step, e);
+            return ExitCode::from(EXIT_RUNTIME_ERROR);
+        }
+        if !step.is_multiple_of(args.interval) {
+            continue;
+        }
+
+        let index = step / args.interval;
+        let snapshot = RecordSnapshot {
+            record_schema_version: RECORD_SCHEMA_VERSION.to_string(),
+            index,
+            steps_executed: step,
+            cycles: metrics.get_cycles(),
+            instructions: metrics.get_instructions(),
+            machine: machine.snapshot(),
+            trace: trace.as_ref().map(|t| {
+                let entries = t.entries();
+                t.clear();
+                entries
+            }),
+        };
+        let path = args.output_dir.join(format!("snapshot-{:06}.json", index));
+        let written = serde_json::to_vec_pretty(&snapshot)
+            .map_err(anyhow::Error::from)
+            .and_then(|json| Ok(std::fs::write(&path, json)?));
+        if let Err(e) = written {
+            error!("Failed to write snapshot {:?}: {:#}", path, e);
+            return ExitCode::from(EXIT_RUNTIME_ERROR);
+        }
+    }
+
+    ExitCode::from(EXIT_PASS)
+}
+
 fn run_interactive(cli: Cli) -> ExitCode {
     info!("Starting LabWired Simulator");
 
@@ -342,7 +1121,7 @@ fn run_interactive(cli: Cli) -> ExitCode {
     };
 
     let system_path = cli.system.clone();
-    let bus = match build_bus(system_path.clone()) {
+    let mut bus = match build_bus(system_path.clone()) {
         Ok(bus) => bus,
         Err(e) => {
             tracing::error!("{:#}", e);
@@ -350,8 +1129,23 @@ fn run_interactive(cli: Cli) -> ExitCode {
         }
     };
 
+    if let Some(port) = cli.uart_tcp {
+        if let Err(e) = start_uart_tcp_bridge(port, &mut bus) {
+            tracing::error!("Failed to start UART bridge on port {}: {}", port, e);
+            return ExitCode::from(EXIT_CONFIG_ERROR);
+        }
+    }
+
+    if let Some(name) = &cli.stdin_uart {
+        if let Err(e) = attach_stdin_uart(name, &mut bus) {
+            tracing::error!("{:#}", e);
+            return ExitCode::from(EXIT_CONFIG_ERROR);
+        }
+    }
+
     info!("Loading firmware: {:?}", firmware);
-    let program = match labwired_loader::load_elf(firmware) {
+    let format = firmware_format(cli.bin_base);
+    let program = match labwired_loader::load_firmware(firmware, format) {
         Ok(program) => program,
         Err(e) => {
             tracing::error!("{:#}", e);
@@ -364,46 +1158,19 @@ fn run_interactive(cli: Cli) -> ExitCode {
 
     let metrics = std::sync::Arc::new(labwired_core::metrics::PerformanceMetrics::new());
 
-    let cpu_arch = if let Some(sys_path) = &system_path {
-        match labwired_config::SystemManifest::from_file(sys_path) {
-            Ok(manifest) => {
-                let chip_path = sys_path
-                    .parent()
-                    .unwrap_or_else(|| Path::new("."))
-                    .join(&manifest.chip);
-                match labwired_config::ChipDescriptor::from_file(&chip_path) {
-                    Ok(c) => c.arch,
-                    Err(e) => {
-                        tracing::error!("Failed to parse chip descriptor: {:#}", e);
-                        return ExitCode::from(EXIT_CONFIG_ERROR);
-                    }
-                }
-            }
-            Err(e) => {
-                tracing::error!("Failed to parse system manifest: {:#}", e);
-                return ExitCode::from(EXIT_CONFIG_ERROR);
-            }
+    let cpu_arch = match cli
+        .core
+        .map_or_else(|| configured_arch(system_path.as_deref()), Ok)
+    {
+        Ok(arch) => arch,
+        Err(e) => {
+            tracing::error!("{:#}", e);
+            return ExitCode::from(EXIT_CONFIG_ERROR);
         }
-    } else {
-        // Default to Arm if no system config provided (backward compatibility)
-        labwired_config::Arch::Arm
     };
-
-    if program.arch != labwired_core::Arch::Unknown {
-        // Map core::Arch to config::Arch for comparison
-        let prog_arch = match program.arch {
-            labwired_core::Arch::Arm => labwired_config::Arch::Arm,
-            labwired_core::Arch::RiscV => labwired_config::Arch::RiscV,
-            _ => labwired_config::Arch::Unknown,
-        };
-
-        if prog_arch != cpu_arch {
-            tracing::warn!(
-                "Architecture Mismatch! Config expects {:?}, but ELF is {:?}",
-                cpu_arch,
-                prog_arch
-            );
-        }
+    if let Err(msg) = check_architecture(&program, format, cpu_arch, cli.architecture_check) {
+        tracing::error!("{}", msg);
+        return ExitCode::from(EXIT_CONFIG_ERROR);
     }
 
     match cpu_arch {
@@ -425,12 +1192,49 @@ fn run_interactive_arm(
     let (cpu, _nvic) = labwired_core::system::cortex_m::configure_cortex_m(&mut bus);
     let mut machine = labwired_core::Machine::new(cpu, bus);
     machine.observers.push(metrics.clone());
+    machine.startup_cycles = cli.startup_cycles;
+    let fault_trace = attach_fault_trace(&cli, &mut machine, labwired_core::Arch::Arm);
+    let coverage = attach_coverage(&cli, &mut machine, labwired_core::Arch::Arm);
+    attach_vcd(&cli, &mut machine);
+    if let Err(code) = attach_trace_file(&cli, &mut machine, labwired_core::Arch::Arm) {
+        return code;
+    }
+    if cli.trace_nvic {
+        machine
+            .observers
+            .push(Arc::new(labwired_core::trace::NvicTimeline::new()));
+    }
+    if cli.instruction_mix {
+        machine
+            .observers
+            .push(Arc::new(labwired_core::metrics::InstructionMix::new()));
+    }
+    if let Err(code) = attach_profiler(&cli, &mut machine) {
+        return code;
+    }
+    if let Err(code) = attach_signals(&cli, &mut machine) {
+        return code;
+    }
 
     if let Err(e) = machine.load_firmware(&program) {
         tracing::error!("Failed to load firmware into memory: {}", e);
         return ExitCode::from(EXIT_RUNTIME_ERROR);
     }
 
+    let resumed_steps = match &cli.resume {
+        Some(path) => match resume_from_checkpoint(path, &mut machine) {
+            Ok(steps) => {
+                info!("Resumed from checkpoint {:?} at step {}", path, steps);
+                steps
+            }
+            Err(e) => {
+                tracing::error!("{:#}", e);
+                return ExitCode::from(EXIT_CONFIG_ERROR);
+            }
+        },
+        None => 0,
+    };
+
     info!("Starting Simulation (ARM Cortex-M)...");
     info!(
         "Initial PC: {:#x}, SP: {:#x}",
@@ -439,7 +1243,8 @@ fn run_interactive_arm(
 
     // Check if GDB server is requested
     if let Some(port) = cli.gdb {
-        let server = labwired_gdbstub::GdbServer::new(port);
+        let elf = cli.firmware.as_deref().filter(|_| cli.bin_base.is_none());
+        let server = gdb_server(port, elf);
         if let Err(e) = server.run(machine) {
             error!("GDB server failed: {}", e);
             return ExitCode::from(EXIT_RUNTIME_ERROR);
@@ -447,7 +1252,14 @@ fn run_interactive_arm(
         return ExitCode::from(EXIT_PASS);
     }
 
-    let result = run_simulation_loop(&cli, &mut machine, &metrics);
+    let result = run_simulation_loop(&cli, &mut machine, &metrics, resumed_steps);
+    let exit_code = interactive_exit_code(&result);
+    if let Some(trace) = &fault_trace {
+        dump_fault_trace(trace, &result);
+    }
+    if let Some(coverage) = &coverage {
+        write_coverage(&cli, coverage);
+    }
 
     if let Some(path) = &cli.snapshot {
         // Need to reconstruct full paths or pass them?
@@ -475,7 +1287,23 @@ fn run_interactive_arm(
     }
 
     report_metrics(&machine.cpu, &metrics);
-    ExitCode::from(EXIT_PASS)
+    exit_code
+}
+
+/// GDB server on `port`, with the symbols of the firmware ELF `elf` for
+/// `monitor break`.
+fn gdb_server(port: u16, elf: Option<&Path>) -> labwired_gdbstub::GdbServer {
+    let server = labwired_gdbstub::GdbServer::new(port);
+    let Some(path) = elf else {
+        return server;
+    };
+    match labwired_loader::SymbolProvider::new(path) {
+        Ok(symbols) => server.with_symbols(symbols),
+        Err(e) => {
+            tracing::warn!("No symbols for monitor break: {:#}", e);
+            server
+        }
+    }
 }
 
 fn run_interactive_riscv(
@@ -487,12 +1315,44 @@ fn run_interactive_riscv(
     let cpu = labwired_core::system::riscv::configure_riscv(&mut bus);
     let mut machine = labwired_core::Machine::new(cpu, bus);
     machine.observers.push(metrics.clone());
+    machine.startup_cycles = cli.startup_cycles;
+    let fault_trace = attach_fault_trace(&cli, &mut machine, labwired_core::Arch::RiscV);
+    let coverage = attach_coverage(&cli, &mut machine, labwired_core::Arch::RiscV);
+    attach_vcd(&cli, &mut machine);
+    if let Err(code) = attach_trace_file(&cli, &mut machine, labwired_core::Arch::RiscV) {
+        return code;
+    }
+    if cli.instruction_mix {
+        machine
+            .observers
+            .push(Arc::new(labwired_core::metrics::InstructionMix::new()));
+    }
+    if let Err(code) = attach_profiler(&cli, &mut machine) {
+        return code;
+    }
+    if let Err(code) = attach_signals(&cli, &mut machine) {
+        return code;
+    }
 
     if let Err(e) = machine.load_firmware(&program) {
         tracing::error!("Failed to load firmware into memory: {}", e);
         return ExitCode::from(EXIT_RUNTIME_ERROR);
     }
 
+    let resumed_steps = match &cli.resume {
+        Some(path) => match resume_from_checkpoint(path, &mut machine) {
+            Ok(steps) => {
+                info!("Resumed from checkpoint {:?} at step {}", path, steps);
+                steps
+            }
+            Err(e) => {
+                tracing::error!("{:#}", e);
+                return ExitCode::from(EXIT_CONFIG_ERROR);
+            }
+        },
+        None => 0,
+    };
+
     info!("Starting Simulation (RISC-V)...");
     info!(
         "Initial PC: {:#x}, SP: {:#x}",
@@ -502,7 +1362,8 @@ fn run_interactive_riscv(
 
     // Check if GDB server is requested
     if let Some(port) = cli.gdb {
-        let server = labwired_gdbstub::GdbServer::new(port);
+        let elf = cli.firmware.as_deref().filter(|_| cli.bin_base.is_none());
+        let server = gdb_server(port, elf);
         if let Err(e) = server.run(machine) {
             error!("GDB server failed: {}", e);
             return ExitCode::from(EXIT_RUNTIME_ERROR);
@@ -510,7 +1371,14 @@ fn run_interactive_riscv(
         return ExitCode::from(EXIT_PASS);
     }
 
-    let result = run_simulation_loop(&cli, &mut machine, &metrics);
+    let result = run_simulation_loop(&cli, &mut machine, &metrics, resumed_steps);
+    let exit_code = interactive_exit_code(&result);
+    if let Some(trace) = &fault_trace {
+        dump_fault_trace(trace, &result);
+    }
+    if let Some(coverage) = &coverage {
+        write_coverage(&cli, coverage);
+    }
 
     if let Some(path) = &cli.snapshot {
         let firmware_path = cli.firmware.as_ref().expect("Firmware path required");
@@ -530,9 +1398,200 @@ fn run_interactive_riscv(
             },
         );
     }
-
-    report_metrics(&machine.cpu, &metrics);
-    ExitCode::from(EXIT_PASS)
+
+    report_metrics(&machine.cpu, &metrics);
+    exit_code
+}
+
+fn attach_fault_trace<C: labwired_core::Cpu>(
+    cli: &Cli,
+    machine: &mut labwired_core::Machine<C>,
+    arch: labwired_core::Arch,
+) -> Option<Arc<labwired_core::trace::InstructionTrace>> {
+    let depth = cli.trace_on_fault?;
+    let trace = Arc::new(labwired_core::trace::InstructionTrace::new(arch, depth));
+    machine.observers.push(trace.clone());
+    Some(trace)
+}
+
+fn attach_coverage<C: labwired_core::Cpu>(
+    cli: &Cli,
+    machine: &mut labwired_core::Machine<C>,
+    arch: labwired_core::Arch,
+) -> Option<Arc<labwired_core::coverage::CoverageObserver>> {
+    cli.coverage.as_ref()?;
+    let coverage = Arc::new(labwired_core::coverage::CoverageObserver::new(arch));
+    machine.observers.push(coverage.clone());
+    Some(coverage)
+}
+
+/// Write the `--coverage` report, with a line summary when the firmware is
+/// an ELF with debug info.
+fn write_coverage(cli: &Cli, coverage: &labwired_core::coverage::CoverageObserver) {
+    use labwired_core::coverage::SourceLine;
+
+    let Some(path) = &cli.coverage else {
+        return;
+    };
+    let mut report = coverage.report();
+    let elf = cli.firmware.as_deref().filter(|_| cli.bin_base.is_none());
+    if let Some(symbols) = elf.and_then(|elf| labwired_loader::SymbolProvider::new(elf).ok()) {
+        let line_of = |addr: u64| {
+            let location = symbols.lookup(addr)?;
+            Some(SourceLine {
+                line: location.line?,
+                file: location.file,
+            })
+        };
+        let lines: Vec<SourceLine> = symbols
+            .lines()
+            .filter_map(|(_, _, addr)| line_of(addr))
+            .collect();
+        if !lines.is_empty() {
+            report = report.with_lines(lines, |addr| line_of(addr as u64));
+        }
+    }
+    if let Err(e) = report.write_json(path) {
+        tracing::warn!("Failed to write coverage {:?}: {}", path, e);
+    }
+}
+
+fn attach_vcd<C: labwired_core::Cpu>(cli: &Cli, machine: &mut labwired_core::Machine<C>) {
+    if let Some(path) = &cli.vcd {
+        let vcd = labwired_core::vcd::VcdRecorder::new(path);
+        machine.observers.push(Arc::new(vcd));
+    }
+}
+
+fn attach_trace_file<C: labwired_core::Cpu>(
+    cli: &Cli,
+    machine: &mut labwired_core::Machine<C>,
+    arch: labwired_core::Arch,
+) -> Result<(), ExitCode> {
+    let Some(path) = &cli.trace_file else {
+        return Ok(());
+    };
+    match labwired_core::trace::TraceObserver::create(arch, path) {
+        Ok(trace) => {
+            machine.observers.push(Arc::new(trace));
+            Ok(())
+        }
+        Err(e) => {
+            error!("Failed to create trace file {:?}: {}", path, e);
+            Err(ExitCode::from(EXIT_CONFIG_ERROR))
+        }
+    }
+}
+
+fn attach_profiler<C: labwired_core::Cpu>(
+    cli: &Cli,
+    machine: &mut labwired_core::Machine<C>,
+) -> Result<(), ExitCode> {
+    let (Some(path), Some(firmware)) = (&cli.profile, &cli.firmware) else {
+        return Ok(());
+    };
+    match labwired_loader::SymbolProvider::new(firmware) {
+        Ok(symbols) => {
+            let profiler = labwired_core::profile::Profiler::new(
+                symbols.functions().to_vec(),
+                Some(path.clone()),
+            );
+            machine.observers.push(Arc::new(profiler));
+            Ok(())
+        }
+        Err(e) => {
+            error!("Failed to read symbols for --profile: {:#}", e);
+            Err(ExitCode::from(EXIT_CONFIG_ERROR))
+        }
+    }
+}
+
+/// The stop reason recorded when `Machine::step` fails with `e`.
+fn error_stop_reason(e: &labwired_core::SimulationError) -> StopReason {
+    use labwired_core::SimulationError;
+    match e {
+        SimulationError::MemoryViolation(_) => StopReason::MemoryViolation,
+        SimulationError::DecodeError(_) => StopReason::DecodeError,
+        SimulationError::PolicyViolation(_) => StopReason::PolicyViolation,
+        SimulationError::UndefinedInstruction(_) => StopReason::UndefinedInstruction,
+        SimulationError::UnalignedAccess(_) => StopReason::UnalignedAccess,
+        SimulationError::StackOverflow(_) => StopReason::StackOverflow,
+        SimulationError::Breakpoint(..) => StopReason::Halt,
+        SimulationError::Config(_) => StopReason::ConfigError,
+    }
+}
+
+/// Whether `reason` is one `error_stop_reason` produces.
+fn is_error_stop(reason: &StopReason) -> bool {
+    matches!(
+        reason,
+        StopReason::MemoryViolation
+            | StopReason::DecodeError
+            | StopReason::PolicyViolation
+            | StopReason::UndefinedInstruction
+            | StopReason::UnalignedAccess
+            | StopReason::StackOverflow
+    )
+}
+
+fn attach_signals<C: labwired_core::Cpu>(
+    cli: &Cli,
+    machine: &mut labwired_core::Machine<C>,
+) -> Result<(), ExitCode> {
+    let Some(path) = &cli.signals else {
+        return Ok(());
+    };
+    match load_signals(path, &machine.bus) {
+        Ok(signals) => {
+            machine.signals = signals;
+            Ok(())
+        }
+        Err(e) => {
+            error!("{:#}", e);
+            Err(ExitCode::from(EXIT_CONFIG_ERROR))
+        }
+    }
+}
+
+fn load_signals(
+    path: &Path,
+    bus: &labwired_core::bus::SystemBus,
+) -> anyhow::Result<labwired_core::signals::SignalSchedule> {
+    let script = labwired_config::SignalScript::from_file(path)?;
+    let schedule = labwired_core::signals::SignalSchedule::from_config(&script);
+    schedule
+        .check_targets(bus)
+        .with_context(|| format!("Invalid signals file {:?}", path))?;
+    Ok(schedule)
+}
+
+fn dump_fault_trace(trace: &labwired_core::trace::InstructionTrace, result: &LoopResult) {
+    if !is_error_stop(&result.stop_reason) {
+        return;
+    }
+
+    let entries = trace.entries();
+    error!(
+        "Fault after {} steps; last {} instructions:",
+        result.steps_executed,
+        entries.len()
+    );
+    for entry in entries {
+        error!(
+            "  {:#010x}: {:#010x}  {}",
+            entry.pc, entry.opcode, entry.disasm
+        );
+    }
+}
+
+/// Say what kind of access faulted, which the error alone does not tell.
+fn log_bus_fault(bus: &labwired_core::bus::SystemBus, e: &labwired_core::SimulationError) {
+    if !matches!(e, labwired_core::SimulationError::MemoryViolation(_)) {
+        return;
+    }
+    if let Some(fault) = bus.last_fault() {
+        error!("Bus fault: {}", fault);
+    }
 }
 
 struct LoopResult {
@@ -541,15 +1600,32 @@ struct LoopResult {
     stop_message: Option<String>,
 }
 
+/// `EXIT_RUNTIME_ERROR` if an interactive run stopped on a fault.
+fn interactive_exit_code(result: &LoopResult) -> ExitCode {
+    if is_error_stop(&result.stop_reason) {
+        ExitCode::from(EXIT_RUNTIME_ERROR)
+    } else {
+        ExitCode::from(EXIT_PASS)
+    }
+}
+
 fn run_simulation_loop<C: labwired_core::Cpu>(
     cli: &Cli,
     machine: &mut labwired_core::Machine<C>,
     metrics: &labwired_core::metrics::PerformanceMetrics,
+    resumed_steps: u64,
 ) -> LoopResult {
     let mut stop_reason = StopReason::MaxSteps;
     let mut steps_executed: u64 = 0;
     let mut stop_message: Option<String> = None;
 
+    machine.halt_window = (cli.halt_window > 0).then_some(cli.halt_window);
+    if cli.rtt {
+        machine.rtt = Some(labwired_core::rtt::RttReader::new(None, true));
+    }
+    let mut throttle = cli
+        .realtime
+        .map(|hz| labwired_core::metrics::RealtimeThrottle::new(hz, metrics.get_cycles()));
     info!("Running for {} steps...", cli.max_steps);
     for step in 0..cli.max_steps {
         if !cli.breakpoint.is_empty() && cli.breakpoint.contains(&machine.cpu.get_pc()) {
@@ -565,6 +1641,22 @@ fn run_simulation_loop<C: labwired_core::Cpu>(
         match machine.step() {
             Ok(_) => {
                 steps_executed = (step + 1) as u64;
+                if let Some(throttle) = &mut throttle {
+                    throttle.pace(metrics.get_cycles());
+                }
+                if let (Some(path), Some(every)) = (&cli.checkpoint, cli.checkpoint_every) {
+                    let total_steps = resumed_steps + steps_executed;
+                    if every > 0 && total_steps.is_multiple_of(every) {
+                        if let Err(e) = write_checkpoint(path, machine, total_steps) {
+                            error!("Failed to write checkpoint {:?}: {:#}", path, e);
+                        }
+                    }
+                }
+                if machine.check_halt() {
+                    info!("Firmware halted at PC={:#x}", machine.cpu.get_pc());
+                    stop_reason = StopReason::Halt;
+                    break;
+                }
                 if !cli.trace && step > 0 && step % 10000 == 0 {
                     info!(
                         "Progress: {} steps, current IPS: {:.2}",
@@ -573,23 +1665,31 @@ fn run_simulation_loop<C: labwired_core::Cpu>(
                     );
                 }
             }
+            Err(labwired_core::SimulationError::Breakpoint(pc, imm)) => {
+                info!("Firmware halted at PC={:#x} (BKPT #{})", pc, imm);
+                stop_reason = StopReason::Halt;
+                break;
+            }
             Err(e) => {
                 info!("Simulation Error at step {}: {}", step, e);
-                stop_reason = match e {
-                    labwired_core::SimulationError::MemoryViolation(_) => {
-                        StopReason::MemoryViolation
-                    }
-                    labwired_core::SimulationError::DecodeError(_) => StopReason::DecodeError,
-                };
+                stop_reason = error_stop_reason(&e);
                 stop_message = Some(e.to_string());
+                log_bus_fault(&machine.bus, &e);
                 break;
             }
         }
     }
 
+    if let Some(rtt) = &mut machine.rtt {
+        rtt.finish(&mut machine.bus);
+    }
+    for observer in &machine.observers {
+        observer.on_simulation_stop();
+    }
+
     LoopResult {
         stop_reason,
-        steps_executed,
+        steps_executed: resumed_steps + steps_executed,
         stop_message,
     }
 }
@@ -665,8 +1765,19 @@ fn build_stop_reason_details(
                 value: duration.as_millis().min(u128::from(u64::MAX)) as u64,
             }),
         ),
+        StopReason::AssertionsPassed => (
+            None,
+            Some(NamedU64 {
+                name: "steps_executed".to_string(),
+                value: steps_executed,
+            }),
+        ),
         StopReason::MemoryViolation
         | StopReason::DecodeError
+        | StopReason::PolicyViolation
+        | StopReason::UndefinedInstruction
+        | StopReason::UnalignedAccess
+        | StopReason::StackOverflow
         | StopReason::Halt
         | StopReason::ConfigError => (None, None),
     };
@@ -698,7 +1809,11 @@ fn run_test(args: TestArgs) -> ExitCode {
         script_max_uart_bytes,
         script_no_progress_steps,
         script_wall_time_ms,
+        script_stop_when_assertions_pass,
+        script_must_reach,
+        script_exit_codes,
         assertions,
+        script_policy,
     ) = match loaded {
         LoadedTestScript::V1_0(script) => (
             Some(script.inputs.firmware),
@@ -708,7 +1823,11 @@ fn run_test(args: TestArgs) -> ExitCode {
             script.limits.max_uart_bytes,
             script.limits.no_progress_steps,
             script.limits.wall_time_ms,
+            script.limits.stop_when_assertions_pass,
+            script.limits.must_reach,
+            script.limits.exit_codes,
             script.assertions,
+            script.policy,
         ),
         LoadedTestScript::LegacyV1(script) => {
             tracing::warn!(
@@ -722,7 +1841,11 @@ fn run_test(args: TestArgs) -> ExitCode {
                 None,
                 None,
                 script.wall_time_ms,
+                false,
+                None,
+                HashMap::new(),
                 script.assertions,
+                TestPolicy::default(),
             )
         }
     };
@@ -737,6 +1860,9 @@ fn run_test(args: TestArgs) -> ExitCode {
         max_uart_bytes,
         no_progress_steps: detect_stuck,
         wall_time_ms: script_wall_time_ms,
+        stop_when_assertions_pass: script_stop_when_assertions_pass,
+        must_reach: script_must_reach,
+        exit_codes: script_exit_codes,
     };
 
     // Guard against accidentally huge runs from CI misconfiguration.
@@ -828,7 +1954,8 @@ fn run_test(args: TestArgs) -> ExitCode {
     let uart_tx = Arc::new(Mutex::new(Vec::new()));
     bus.attach_uart_tx_sink(uart_tx.clone(), !args.no_uart_stdout);
 
-    let program = match labwired_loader::load_elf(&firmware_path) {
+    let format = firmware_format(args.bin_base);
+    let program = match labwired_loader::load_firmware(&firmware_path, format) {
         Ok(program) => program,
         Err(e) => {
             let msg = format!("{:#}", e);
@@ -845,12 +1972,84 @@ fn run_test(args: TestArgs) -> ExitCode {
         }
     };
 
+    let arch_check = args
+        .core
+        .map_or_else(|| configured_arch(system_path.as_deref()), Ok)
+        .map_err(|e| format!("{:#}", e))
+        .and_then(|arch| check_architecture(&program, format, arch, args.architecture_check));
+    if let Err(msg) = arch_check {
+        error!("{}", msg);
+        write_config_error_outputs(
+            &args,
+            Some(&firmware_path),
+            system_path.as_ref(),
+            Some(&firmware_bytes),
+            Some(&resolved_limits),
+            msg,
+        );
+        return ExitCode::from(EXIT_CONFIG_ERROR);
+    }
+
+    let reach_targets = match resolve_reach_targets(
+        &firmware_path,
+        format,
+        &assertions,
+        resolved_limits.must_reach.as_ref(),
+    ) {
+        Ok(targets) => targets,
+        Err(e) => {
+            let msg = format!("{:#}", e);
+            error!("{}", msg);
+            write_config_error_outputs(
+                &args,
+                Some(&firmware_path),
+                system_path.as_ref(),
+                Some(&firmware_bytes),
+                Some(&resolved_limits),
+                msg,
+            );
+            return ExitCode::from(EXIT_CONFIG_ERROR);
+        }
+    };
+
+    let signals = match args
+        .signals
+        .as_deref()
+        .map(|path| load_signals(path, &bus))
+        .transpose()
+    {
+        Ok(signals) => signals.unwrap_or_default(),
+        Err(e) => {
+            let msg = format!("{:#}", e);
+            error!("{}", msg);
+            write_config_error_outputs(
+                &args,
+                Some(&firmware_path),
+                system_path.as_ref(),
+                Some(&firmware_bytes),
+                Some(&resolved_limits),
+                msg,
+            );
+            return ExitCode::from(EXIT_CONFIG_ERROR);
+        }
+    };
+
     let metrics = std::sync::Arc::new(labwired_core::metrics::PerformanceMetrics::new());
-    let (_cpu_configured, machine_arm, machine_riscv) = match program.arch {
+    // --core overrides the core the ELF was built for
+    let arch = match args.core {
+        Some(labwired_config::Arch::RiscV) => labwired_core::Arch::RiscV,
+        Some(_) => labwired_core::Arch::Arm,
+        None => program.arch,
+    };
+    let (_cpu_configured, machine_arm, machine_riscv) = match arch {
         labwired_core::Arch::Arm => {
-            let (cpu, _nvic) = labwired_core::system::cortex_m::configure_cortex_m(&mut bus);
+            let (mut cpu, _nvic) = labwired_core::system::cortex_m::configure_cortex_m(&mut bus);
+            cpu.fault_behavior = args.fault_behavior;
             let mut machine = labwired_core::Machine::new(cpu, bus);
             machine.observers.push(metrics.clone());
+            machine.startup_cycles = args.startup_cycles;
+            machine.policy = execution_policy(&script_policy, arch);
+            machine.signals = signals;
             if let Err(e) = machine.load_firmware(&program) {
                 return handle_load_error(&args, &metrics, &resolved_limits, &firmware_bytes, &uart_tx, &machine.cpu, &firmware_path, system_path.as_ref(), e);
             }
@@ -860,13 +2059,16 @@ fn run_test(args: TestArgs) -> ExitCode {
             let cpu = labwired_core::system::riscv::configure_riscv(&mut bus);
             let mut machine = labwired_core::Machine::new(cpu, bus);
             machine.observers.push(metrics.clone());
+            machine.startup_cycles = args.startup_cycles;
+            machine.policy = execution_policy(&script_policy, arch);
+            machine.signals = signals;
             if let Err(e) = machine.load_firmware(&program) {
                 return handle_load_error(&args, &metrics, &resolved_limits, &firmware_bytes, &uart_tx, &machine.cpu, &firmware_path, system_path.as_ref(), e);
             }
             (true, None, Some(machine))
         }
         _ => {
-            let msg = format!("Unsupported architecture: {:?}", program.arch);
+            let msg = format!("Unsupported architecture: {:?}", arch);
             error!("{}", msg);
             write_config_error_outputs(&args, Some(&firmware_path), system_path.as_ref(), Some(&firmware_bytes), Some(&resolved_limits), msg);
             return ExitCode::from(EXIT_CONFIG_ERROR);
@@ -874,14 +2076,29 @@ fn run_test(args: TestArgs) -> ExitCode {
     };
 
     if let Some(mut machine) = machine_arm {
-        execute_test_loop(&args, &mut machine, &resolved_limits, &assertions, &firmware_bytes, &uart_tx, &metrics, &firmware_path, system_path.as_ref())
+        execute_test_loop(&args, &mut machine, &resolved_limits, &assertions, &reach_targets, &firmware_bytes, &uart_tx, &metrics, &firmware_path, system_path.as_ref())
     } else if let Some(mut machine) = machine_riscv {
-        execute_test_loop(&args, &mut machine, &resolved_limits, &assertions, &firmware_bytes, &uart_tx, &metrics, &firmware_path, system_path.as_ref())
+        execute_test_loop(&args, &mut machine, &resolved_limits, &assertions, &reach_targets, &firmware_bytes, &uart_tx, &metrics, &firmware_path, system_path.as_ref())
     } else {
         unreachable!()
     }
 }
 
+/// The core's form of the script's `policy`, or `None` when it denies nothing.
+fn execution_policy(
+    policy: &TestPolicy,
+    arch: labwired_core::Arch,
+) -> Option<labwired_core::policy::ExecutionPolicy> {
+    let mut execution = labwired_core::policy::ExecutionPolicy::new(arch);
+    execution.deny_writes = policy
+        .deny_writes
+        .iter()
+        .map(|range| range.start..range.start + range.size)
+        .collect();
+    execution.deny_instructions = policy.deny_instructions.clone();
+    (!execution.is_empty()).then_some(execution)
+}
+
 #[allow(clippy::too_many_arguments)]
 fn handle_load_error<C: labwired_core::Cpu>(
     args: &TestArgs,
@@ -914,6 +2131,7 @@ fn handle_load_error<C: labwired_core::Cpu>(
         stop_reason_details,
         resolved_limits.clone(),
         vec![],
+        None,
         firmware_bytes,
         uart_tx,
         cpu,
@@ -924,12 +2142,191 @@ fn handle_load_error<C: labwired_core::Cpu>(
     ExitCode::from(EXIT_RUNTIME_ERROR)
 }
 
+/// Progress of the `must_reach` liveness gate.
+struct MustReachGate<'a> {
+    gate: &'a MustReach,
+    target: u32,
+    /// `Some(true)` once the target was reached in time, `Some(false)` once the
+    /// deadline passed without it. Still `None` at the end when the run
+    /// stopped early without reaching it, which fails the gate too.
+    outcome: Option<bool>,
+}
+
+impl MustReachGate<'_> {
+    /// Record the PC after `steps` steps.
+    fn observe(&mut self, steps: u64, pc: u32) {
+        if self.outcome.is_some() {
+            return;
+        }
+        if pc == self.target {
+            self.outcome = Some(true);
+        } else if steps >= self.gate.by_step {
+            self.outcome = Some(false);
+        }
+    }
+
+    fn failure_message(&self) -> Option<String> {
+        match self.outcome {
+            Some(true) => None,
+            Some(false) => Some(format!(
+                "must_reach: PC did not reach '{}' ({:#010x}) by step {}",
+                self.gate.symbol_or_addr, self.target, self.gate.by_step
+            )),
+            None => Some(format!(
+                "must_reach: PC did not reach '{}' ({:#010x}) before the run stopped",
+                self.gate.symbol_or_addr, self.target
+            )),
+        }
+    }
+}
+
+/// Compare memory at `a.address` with the expected bytes. A mismatch is
+/// described with the bytes actually read.
+fn check_memory_equals(
+    machine: &impl DebugControl,
+    a: &MemoryEqualsAssertion,
+) -> Result<(), String> {
+    let actual = u32::try_from(a.address)
+        .ok()
+        .and_then(|addr| machine.read_memory(addr, a.bytes.len()).ok());
+    match actual {
+        Some(actual) if actual == a.bytes => Ok(()),
+        Some(actual) => Err(format!(
+            "memory at {:#010x}: expected {}, read {}",
+            a.address,
+            hex_bytes(&a.bytes),
+            hex_bytes(&actual)
+        )),
+        None => Err(format!(
+            "memory at {:#010x} ({} bytes) is not mapped",
+            a.address,
+            a.bytes.len()
+        )),
+    }
+}
+
+fn hex_bytes(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Register names written by `--update-golden`, indexed by core register id.
+const GOLDEN_REGISTERS: [&str; 17] = [
+    "r0", "r1", "r2", "r3", "r4", "r5", "r6", "r7", "r8", "r9", "r10", "r11", "r12", "sp", "lr",
+    "pc", "xpsr",
+];
+
+/// Compare the final core registers (read by id through `read_reg`) with the
+/// JSON register file at `path`, or rewrite the file when `update` is set.
+/// Each mismatching register is described separately.
+fn check_registers_file(
+    path: &Path,
+    read_reg: impl Fn(u8) -> u32,
+    update: bool,
+) -> Result<(), Vec<String>> {
+    if update {
+        let registers: serde_json::Map<String, serde_json::Value> = GOLDEN_REGISTERS
+            .iter()
+            .enumerate()
+            .map(|(id, name)| {
+                (
+                    name.to_string(),
+                    format!("{:#010x}", read_reg(id as u8)).into(),
+                )
+            })
+            .collect();
+        let json = serde_json::to_string_pretty(&registers).unwrap_or_default() + "\n";
+        return match std::fs::write(path, json) {
+            Ok(()) => {
+                info!("Updated golden register file {:?}", path);
+                Ok(())
+            }
+            Err(e) => Err(vec![format!(
+                "failed to write register file {:?}: {}",
+                path, e
+            )]),
+        };
+    }
+
+    let expected: serde_json::Map<String, serde_json::Value> = std::fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|s| serde_json::from_str(&s).map_err(|e| e.to_string()))
+        .map_err(|e| vec![format!("failed to read register file {:?}: {}", path, e)])?;
+
+    let mut mismatches = Vec::new();
+    for (name, value) in &expected {
+        let Some(id) = labwired_config::core_register_id(name) else {
+            mismatches.push(format!(
+                "register file {:?}: unknown register '{}'",
+                path, name
+            ));
+            continue;
+        };
+        let Some(want) = register_file_value(value) else {
+            mismatches.push(format!(
+                "register file {:?}: invalid value {} for {}",
+                path, value, name
+            ));
+            continue;
+        };
+        let got = read_reg(id);
+        if got != want {
+            mismatches.push(format!(
+                "register {}: expected {:#010x}, read {:#010x}",
+                name, want, got
+            ));
+        }
+    }
+    if mismatches.is_empty() {
+        Ok(())
+    } else {
+        Err(mismatches)
+    }
+}
+
+/// A register file value: an integer or a `0x`-prefixed hex string.
+fn register_file_value(value: &serde_json::Value) -> Option<u32> {
+    match value {
+        serde_json::Value::Number(n) => n.as_u64().and_then(|v| u32::try_from(v).ok()),
+        serde_json::Value::String(s) => s
+            .strip_prefix("0x")
+            .or_else(|| s.strip_prefix("0X"))
+            .and_then(|hex| u32::from_str_radix(hex, 16).ok()),
+        _ => None,
+    }
+}
+
+/// Whether every assertion already holds mid-run. Assertions that can only be
+/// decided once the run is over (negated `reached_symbol`, an expected stop
+/// reason other than `assertions_passed`, or a register or memory value)
+/// never hold early.
+fn assertions_pass_early(
+    assertions: &[TestAssertion],
+    uart_text: &str,
+    reached_pcs: &HashSet<u32>,
+    reach_targets: &HashMap<String, u32>,
+) -> bool {
+    assertions.iter().all(|assertion| match assertion {
+        TestAssertion::UartContains(a) => uart_text.contains(&a.uart_contains),
+        TestAssertion::UartRegex(a) => simple_regex_is_match(&a.uart_regex, uart_text),
+        TestAssertion::ExpectedStopReason(a) => {
+            a.expected_stop_reason == StopReason::AssertionsPassed
+        }
+        TestAssertion::ReachedSymbol(a) => {
+            !a.negate && reached_pcs.contains(&reach_targets[&a.reached_symbol])
+        }
+        TestAssertion::RegisterEquals(_)
+        | TestAssertion::MemoryEquals(_)
+        | TestAssertion::RegistersMatchFile(_) => false,
+    })
+}
+
 #[allow(clippy::too_many_arguments)]
 fn execute_test_loop<C: labwired_core::Cpu>(
     args: &TestArgs,
     machine: &mut labwired_core::Machine<C>,
     resolved_limits: &TestLimits,
     assertions: &[TestAssertion],
+    reach_targets: &HashMap<String, u32>,
     firmware_bytes: &[u8],
     uart_tx: &Arc<Mutex<Vec<u8>>>,
     metrics: &Arc<labwired_core::metrics::PerformanceMetrics>,
@@ -948,8 +2345,59 @@ fn execute_test_loop<C: labwired_core::Cpu>(
     let mut sim_error_happened = false;
     let mut prev_pc = machine.cpu.get_pc();
     let mut stuck_counter: u64 = 0;
+    machine.halt_window = args.halt_window;
+    if args.rtt {
+        machine.rtt = Some(labwired_core::rtt::RttReader::new(
+            Some(uart_tx.clone()),
+            !args.no_uart_stdout,
+        ));
+    }
+    let uart_timeline = args.uart_csv.as_ref().map(|_| {
+        let timeline = Arc::new(labwired_core::trace::UartTimeline::new(uart_tx.clone()));
+        machine.observers.push(timeline.clone());
+        timeline
+    });
+    let watched_pcs: HashSet<u32> = reach_targets.values().copied().collect();
+    let mut reached_pcs = HashSet::new();
+    let mut must_reach = resolved_limits
+        .must_reach
+        .as_ref()
+        .map(|gate| MustReachGate {
+            gate,
+            target: reach_targets[&gate.symbol_or_addr],
+            outcome: None,
+        });
+    let check_early = resolved_limits.stop_when_assertions_pass && !assertions.is_empty();
+    // (UART length, reached PC count) at the last early check; assertions
+    // can only change outcome when one of them grows.
+    let mut last_checked = None;
 
     for step in 0..max_steps {
+        let pc = machine.cpu.get_pc() & !1;
+        if watched_pcs.contains(&pc) {
+            reached_pcs.insert(pc);
+        }
+        if let Some(gate) = &mut must_reach {
+            gate.observe(step, pc);
+        }
+        if check_early {
+            let state = (
+                uart_tx.lock().map(|g| g.len()).unwrap_or(0),
+                reached_pcs.len(),
+            );
+            if last_checked != Some(state) {
+                last_checked = Some(state);
+                let uart_text = {
+                    let bytes = uart_tx.lock().map(|g| g.clone()).unwrap_or_default();
+                    String::from_utf8_lossy(&bytes).to_string()
+                };
+                if assertions_pass_early(assertions, &uart_text, &reached_pcs, reach_targets) {
+                    stop_reason = StopReason::AssertionsPassed;
+                    steps_executed = step;
+                    break;
+                }
+            }
+        }
         if !args.breakpoint.is_empty() && args.breakpoint.contains(&machine.cpu.get_pc()) {
             stop_reason = StopReason::Halt;
             steps_executed = step;
@@ -980,13 +2428,26 @@ fn execute_test_loop<C: labwired_core::Cpu>(
         }
 
         steps_executed = step + 1;
-        if let Err(e) = machine.step() {
-            sim_error_happened = true;
-            stop_reason = match e {
-                labwired_core::SimulationError::MemoryViolation(_) => StopReason::MemoryViolation,
-                labwired_core::SimulationError::DecodeError(_) => StopReason::DecodeError,
-            };
-            error!("Simulation error at step {}: {}", step, e);
+        match machine.step() {
+            Ok(()) => {}
+            Err(labwired_core::SimulationError::Breakpoint(pc, imm)) => {
+                info!("Firmware halted at PC={:#x} (BKPT #{})", pc, imm);
+                stop_reason = StopReason::Halt;
+                steps_executed = step;
+                break;
+            }
+            Err(e) => {
+                sim_error_happened = true;
+                stop_reason = error_stop_reason(&e);
+                error!("Simulation error at step {}: {}", step, e);
+                log_bus_fault(&machine.bus, &e);
+                break;
+            }
+        }
+
+        if machine.check_halt() {
+            info!("Firmware halted at PC={:#x}", machine.cpu.get_pc());
+            stop_reason = StopReason::Halt;
             break;
         }
 
@@ -1010,6 +2471,17 @@ fn execute_test_loop<C: labwired_core::Cpu>(
         }
     }
 
+    if let Some(rtt) = &mut machine.rtt {
+        rtt.finish(&mut machine.bus);
+    }
+    let final_pc = machine.cpu.get_pc() & !1;
+    if watched_pcs.contains(&final_pc) {
+        reached_pcs.insert(final_pc);
+    }
+    if let Some(gate) = &mut must_reach {
+        gate.observe(steps_executed, final_pc);
+    }
+
     let uart_text = {
         let bytes = uart_tx.lock().map(|g| g.clone()).unwrap_or_default();
         String::from_utf8_lossy(&bytes).to_string()
@@ -1018,12 +2490,44 @@ fn execute_test_loop<C: labwired_core::Cpu>(
     let mut assertion_results = Vec::new();
     let mut all_passed = true;
     let mut expected_stop_reason_matched = false;
+    let mut failure_messages = Vec::new();
 
     for assertion in assertions {
         let passed = match &assertion {
             TestAssertion::UartContains(a) => uart_text.contains(&a.uart_contains),
             TestAssertion::UartRegex(a) => simple_regex_is_match(&a.uart_regex, &uart_text),
             TestAssertion::ExpectedStopReason(a) => a.expected_stop_reason == stop_reason,
+            TestAssertion::ReachedSymbol(a) => {
+                reached_pcs.contains(&reach_targets[&a.reached_symbol]) != a.negate
+            }
+            TestAssertion::RegisterEquals(a) => match a.register_id() {
+                // The PC is compared without the Thumb bit.
+                Some(15) => final_pc == a.value,
+                Some(id) => machine.read_core_reg(id) == a.value,
+                None => false,
+            },
+            TestAssertion::MemoryEquals(a) => match check_memory_equals(machine, a) {
+                Ok(()) => true,
+                Err(msg) => {
+                    failure_messages.push(msg);
+                    false
+                }
+            },
+            TestAssertion::RegistersMatchFile(a) => {
+                let path = resolve_script_path(&args.script, &a.registers_match_file);
+                // As for `register`, the PC is compared without the Thumb bit.
+                let read_reg = |id| match id {
+                    15 => final_pc,
+                    _ => machine.read_core_reg(id),
+                };
+                match check_registers_file(&path, read_reg, args.update_golden) {
+                    Ok(()) => true,
+                    Err(msgs) => {
+                        failure_messages.extend(msgs);
+                        false
+                    }
+                }
+            }
         };
 
         if matches!(assertion, TestAssertion::ExpectedStopReason(_)) && passed {
@@ -1045,6 +2549,19 @@ fn execute_test_loop<C: labwired_core::Cpu>(
         });
     }
 
+    if let Some(golden) = &args.compare_uart {
+        if !check_golden_uart(golden, &uart_text, args.update_golden) {
+            all_passed = false;
+        }
+    }
+
+    if let Some(msg) = must_reach.as_ref().and_then(MustReachGate::failure_message) {
+        error!("{}", msg);
+        all_passed = false;
+        failure_messages.push(msg);
+    }
+    let failure_message = (!failure_messages.is_empty()).then(|| failure_messages.join("; "));
+
     let stop_requires_assertion = matches!(
         stop_reason,
         StopReason::WallTime | StopReason::MaxUartBytes | StopReason::NoProgress
@@ -1078,6 +2595,7 @@ fn execute_test_loop<C: labwired_core::Cpu>(
         stop_reason_details,
         resolved_limits.clone(),
         assertion_results,
+        failure_message,
         firmware_bytes,
         uart_tx,
         &machine.cpu,
@@ -1085,8 +2603,17 @@ fn execute_test_loop<C: labwired_core::Cpu>(
         system_path,
         duration,
     );
+    if let (Some(path), Some(timeline)) = (&args.uart_csv, &uart_timeline) {
+        if let Err(e) = write_uart_csv(path, timeline) {
+            error!("Failed to write UART CSV {:?}: {}", path, e);
+        }
+    }
 
-    if !all_passed || (stop_requires_assertion && !expected_stop_reason_matched) {
+    if !all_passed {
+        ExitCode::from(EXIT_ASSERT_FAIL)
+    } else if let Some(&code) = resolved_limits.exit_codes.get(&stop_reason) {
+        ExitCode::from(code)
+    } else if stop_requires_assertion && !expected_stop_reason_matched {
         ExitCode::from(EXIT_ASSERT_FAIL)
     } else if sim_error_happened && !expected_stop_reason_matched {
         ExitCode::from(EXIT_RUNTIME_ERROR)
@@ -1095,6 +2622,18 @@ fn execute_test_loop<C: labwired_core::Cpu>(
     }
 }
 
+fn write_uart_csv(
+    path: &Path,
+    timeline: &labwired_core::trace::UartTimeline,
+) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut out = std::io::BufWriter::new(std::fs::File::create(path)?);
+    timeline.write_csv(&mut out)?;
+    std::io::Write::flush(&mut out)
+}
+
 #[allow(clippy::too_many_arguments, clippy::if_same_then_else)]
 fn write_outputs<C: labwired_core::Cpu>(
     args: &TestArgs,
@@ -1105,6 +2644,7 @@ fn write_outputs<C: labwired_core::Cpu>(
     stop_reason_details: StopReasonDetails,
     limits: TestLimits,
     assertions: Vec<AssertionResult>,
+    message: Option<String>,
     firmware_bytes: &[u8],
     uart_tx: &Arc<Mutex<Vec<u8>>>,
     cpu: &C,
@@ -1115,6 +2655,7 @@ fn write_outputs<C: labwired_core::Cpu>(
     let mut hasher = Sha256::new();
     hasher.update(firmware_bytes);
     let firmware_hash = format!("{:x}", hasher.finalize());
+    let firmware_build_id = firmware_build_id(firmware_path);
 
     let assertions_for_junit = assertions.clone();
     let result = TestResult {
@@ -1126,9 +2667,10 @@ fn write_outputs<C: labwired_core::Cpu>(
         stop_reason,
         stop_reason_details: stop_reason_details.clone(),
         limits: limits.clone(),
-        message: None,
+        message,
         assertions,
         firmware_hash,
+        firmware_build_id,
         config: TestConfig {
             firmware: firmware_path.to_path_buf(),
             system: system_path.cloned(),
@@ -1230,6 +2772,11 @@ fn write_outputs<C: labwired_core::Cpu>(
     }
 }
 
+/// Build-id of the firmware at `path`; `None` for raw binaries or ELFs without one.
+fn firmware_build_id(path: &Path) -> Option<String> {
+    labwired_loader::load_build_id(path).ok().flatten()
+}
+
 fn write_config_error_outputs(
     args: &TestArgs,
     firmware_path: Option<&PathBuf>,
@@ -1254,6 +2801,9 @@ fn write_config_error_outputs(
         max_uart_bytes: None,
         no_progress_steps: None,
         wall_time_ms: None,
+        stop_when_assertions_pass: false,
+        must_reach: None,
+        exit_codes: HashMap::new(),
     });
 
     let stop_reason = StopReason::ConfigError;
@@ -1279,6 +2829,7 @@ fn write_config_error_outputs(
         message: Some(message.clone()),
         assertions: vec![],
         firmware_hash,
+        firmware_build_id: firmware_path.and_then(|p| firmware_build_id(p)),
         config: TestConfig {
             firmware: firmware_path.cloned().unwrap_or_default(),
             system: system_path.cloned(),
@@ -1370,6 +2921,98 @@ fn write_config_error_outputs(
     }
 }
 
+/// Feed stdin into the RX FIFO of UART `name` from a background thread, so
+/// bytes become available to the firmware as they arrive. At EOF feeding
+/// stops; the simulation keeps running.
+fn attach_stdin_uart(name: &str, bus: &mut labwired_core::bus::SystemBus) -> anyhow::Result<()> {
+    use std::io::Read;
+
+    let uart = bus
+        .peripheral_mut::<labwired_core::peripherals::uart::Uart>(name)
+        .ok_or_else(|| anyhow::anyhow!("--stdin-uart: no UART named '{}'", name))?;
+    let rx = Arc::new(Mutex::new(std::collections::VecDeque::new()));
+    uart.set_rx_source(Some(rx.clone()));
+
+    std::thread::spawn(move || {
+        let mut stdin = std::io::stdin();
+        let mut buf = [0u8; 256];
+        while let Ok(n) = stdin.read(&mut buf) {
+            if n == 0 {
+                break;
+            }
+            if let Ok(mut fifo) = rx.lock() {
+                fifo.extend(&buf[..n]);
+            }
+        }
+        tracing::debug!("stdin closed, no more UART input");
+    });
+    Ok(())
+}
+
+/// Bridge the bus UARTs to a TCP listener on localhost. Bytes received from the
+/// client feed the UART RX FIFO; TX output is streamed back to the client. Output
+/// produced before a client connects is buffered and sent on connection.
+fn start_uart_tcp_bridge(
+    port: u16,
+    bus: &mut labwired_core::bus::SystemBus,
+) -> std::io::Result<()> {
+    use std::io::{Read, Write};
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    let listener = std::net::TcpListener::bind(("127.0.0.1", port))?;
+    let tx = Arc::new(Mutex::new(Vec::new()));
+    let rx = Arc::new(Mutex::new(std::collections::VecDeque::new()));
+    bus.attach_uart_tx_sink(tx.clone(), true);
+    bus.attach_uart_rx_source(rx.clone());
+    info!("UART bridged to tcp://127.0.0.1:{}", port);
+
+    std::thread::spawn(move || {
+        let mut sent = 0;
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else {
+                continue;
+            };
+            let Ok(mut reader) = stream.try_clone() else {
+                continue;
+            };
+            info!("UART bridge: client connected");
+
+            let connected = Arc::new(AtomicBool::new(true));
+            let reader_connected = connected.clone();
+            let rx = rx.clone();
+            std::thread::spawn(move || {
+                let mut buf = [0u8; 256];
+                while let Ok(n) = reader.read(&mut buf) {
+                    if n == 0 {
+                        break;
+                    }
+                    if let Ok(mut fifo) = rx.lock() {
+                        fifo.extend(&buf[..n]);
+                    }
+                }
+                reader_connected.store(false, Ordering::SeqCst);
+            });
+
+            while connected.load(Ordering::SeqCst) {
+                let pending = tx
+                    .lock()
+                    .map(|guard| guard[sent..].to_vec())
+                    .unwrap_or_default();
+                if !pending.is_empty() {
+                    if stream.write_all(&pending).is_err() {
+                        break;
+                    }
+                    sent += pending.len();
+                }
+                std::thread::sleep(std::time::Duration::from_millis(5));
+            }
+            info!("UART bridge: client disconnected");
+        }
+    });
+
+    Ok(())
+}
+
 fn build_bus(system_path: Option<PathBuf>) -> anyhow::Result<labwired_core::bus::SystemBus> {
     let bus = if let Some(sys_path) = system_path {
         info!("Loading system manifest: {:?}", sys_path);
@@ -1380,7 +3023,14 @@ fn build_bus(system_path: Option<PathBuf>) -> anyhow::Result<labwired_core::bus:
             .join(&manifest.chip);
         info!("Loading chip descriptor: {:?}", chip_path);
         let chip = labwired_config::ChipDescriptor::from_file(&chip_path)?;
-        labwired_core::bus::SystemBus::from_config(&chip, &manifest)?
+        let mut bus = labwired_core::bus::SystemBus::from_config(&chip, &manifest)?;
+        let manifest_dir = sys_path.parent().unwrap_or_else(|| Path::new("."));
+        for preload in &manifest.preload {
+            let data = preload.read(manifest_dir)?;
+            info!("Preloading {} bytes at {:#x}", data.len(), preload.addr);
+            bus.preload(preload.addr, &data)?;
+        }
+        bus
     } else {
         info!("Using default hardware configuration");
         labwired_core::bus::SystemBus::new()
@@ -1389,6 +3039,97 @@ fn build_bus(system_path: Option<PathBuf>) -> anyhow::Result<labwired_core::bus:
     Ok(bus)
 }
 
+/// Core architecture selected by the system manifest's chip; Arm when no
+/// manifest is given.
+fn configured_arch(system_path: Option<&Path>) -> anyhow::Result<labwired_config::Arch> {
+    let Some(sys_path) = system_path else {
+        return Ok(labwired_config::Arch::Arm);
+    };
+    let manifest = labwired_config::SystemManifest::from_file(sys_path)
+        .context("Failed to parse system manifest")?;
+    let chip_path = sys_path
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join(&manifest.chip);
+    let chip = labwired_config::ChipDescriptor::from_file(&chip_path)
+        .context("Failed to parse chip descriptor")?;
+    Ok(chip.arch)
+}
+
+/// Compare the ELF machine type (`e_machine`) with the configured core. A
+/// mismatch is an error with `strict` (`--architecture-check`) and a warning
+/// otherwise. Flat binaries and unrecognised machine types are not checked.
+fn check_architecture(
+    program: &labwired_core::memory::ProgramImage,
+    format: labwired_loader::FirmwareFormat,
+    configured: labwired_config::Arch,
+    strict: bool,
+) -> Result<(), String> {
+    let firmware = match (format, program.arch) {
+        (labwired_loader::FirmwareFormat::Elf, labwired_core::Arch::Arm) => {
+            labwired_config::Arch::Arm
+        }
+        (labwired_loader::FirmwareFormat::Elf, labwired_core::Arch::RiscV) => {
+            labwired_config::Arch::RiscV
+        }
+        _ => return Ok(()),
+    };
+    if firmware == configured {
+        return Ok(());
+    }
+
+    let msg = format!(
+        "Architecture mismatch: firmware ELF targets {:?} but the system is configured for {:?}",
+        firmware, configured
+    );
+    if strict {
+        return Err(msg);
+    }
+    tracing::warn!("{}", msg);
+    Ok(())
+}
+
+/// Resolve every `reached_symbol` assertion and the `must_reach` target to a
+/// PC, keyed by the name used in the script. Flat binaries have no symbol
+/// table, so only addresses work.
+fn resolve_reach_targets(
+    firmware_path: &Path,
+    format: labwired_loader::FirmwareFormat,
+    assertions: &[TestAssertion],
+    must_reach: Option<&MustReach>,
+) -> anyhow::Result<HashMap<String, u32>> {
+    // (script key, name)
+    let names: Vec<(&str, &str)> = assertions
+        .iter()
+        .filter_map(|a| match a {
+            TestAssertion::ReachedSymbol(a) => Some(("reached_symbol", a.reached_symbol.as_str())),
+            _ => None,
+        })
+        .chain(must_reach.map(|m| ("must_reach", m.symbol_or_addr.as_str())))
+        .collect();
+    if names.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let symbols = match format {
+        labwired_loader::FirmwareFormat::Elf => labwired_loader::load_symbols(firmware_path)?,
+        labwired_loader::FirmwareFormat::Bin { .. } => HashMap::new(),
+    };
+    let mut targets = HashMap::new();
+    for (key, name) in names {
+        let addr = symbols
+            .get(name)
+            .map(|&addr| addr as u32)
+            .or_else(|| {
+                let hex = name.strip_prefix("0x")?;
+                u32::from_str_radix(hex, 16).ok()
+            })
+            .ok_or_else(|| anyhow::anyhow!("{} '{}' not found in firmware", key, name))?;
+        targets.insert(name.to_string(), addr & !1);
+    }
+    Ok(targets)
+}
+
 fn resolve_script_path(script_path: &Path, value: &str) -> PathBuf {
     let p = PathBuf::from(value);
     if p.is_absolute() {
@@ -1475,6 +3216,12 @@ fn write_junit_xml(
     if let Some(v) = limits.wall_time_ms {
         details.push_str(&format!("  - wall_time_ms={}\n", v));
     }
+    if let Some(gate) = &limits.must_reach {
+        details.push_str(&format!(
+            "  - must_reach={}@{}\n",
+            gate.symbol_or_addr, gate.by_step
+        ));
+    }
     details.push_str(&format!("firmware_hash={}\n", firmware_hash));
     details.push_str(&format!("firmware={}\n", config.firmware.display()));
     if let Some(sys) = &config.system {
@@ -1529,7 +3276,7 @@ fn write_junit_xml(
         failures += 1;
         testcases.push_str(&format!(
             "    <failure message=\"{}\">{}</failure>\n",
-            xml_escape("failure"),
+            xml_escape(message.unwrap_or("failure")),
             xml_escape(&details)
         ));
     }
@@ -1590,6 +3337,17 @@ fn assertion_short_name(assertion: &TestAssertion) -> String {
         TestAssertion::ExpectedStopReason(a) => {
             format!("expected_stop_reason: {:?}", a.expected_stop_reason)
         }
+        TestAssertion::ReachedSymbol(a) if a.negate => {
+            format!("reached_symbol: {} (negate)", a.reached_symbol)
+        }
+        TestAssertion::ReachedSymbol(a) => format!("reached_symbol: {}", a.reached_symbol),
+        TestAssertion::RegisterEquals(a) => format!("register: {} == {:#x}", a.register, a.value),
+        TestAssertion::MemoryEquals(a) => {
+            format!("memory: {:#010x} == {}", a.address, hex_bytes(&a.bytes))
+        }
+        TestAssertion::RegistersMatchFile(a) => {
+            format!("registers_match_file: {}", a.registers_match_file)
+        }
     };
 
     if s.len() <= MAX_LEN {
@@ -1601,6 +3359,132 @@ fn assertion_short_name(assertion: &TestAssertion) -> String {
     truncated
 }
 
+/// Compare captured UART text with a golden file, or rewrite the file when
+/// `update` is set. Logs the mismatch and prints a unified diff to stderr.
+fn check_golden_uart(path: &Path, uart_text: &str, update: bool) -> bool {
+    if update {
+        return match std::fs::write(path, uart_text) {
+            Ok(()) => {
+                info!("Updated golden UART output {:?}", path);
+                true
+            }
+            Err(e) => {
+                error!("Failed to write golden UART output {:?}: {}", path, e);
+                false
+            }
+        };
+    }
+
+    let expected = match std::fs::read_to_string(path) {
+        Ok(s) => s,
+        Err(e) => {
+            error!("Failed to read golden UART output {:?}: {}", path, e);
+            return false;
+        }
+    };
+    if expected.lines().eq(uart_text.lines()) {
+        return true;
+    }
+
+    error!("UART output does not match golden file {:?}", path);
+    eprint!(
+        "{}",
+        unified_diff(&expected, uart_text, &path.display().to_string(), "uart")
+    );
+    false
+}
+
+/// Line-based unified diff from `old` to `new`, with three lines of context.
+///
+/// Lines shared at the start and end are matched directly; the LCS table is
+/// only built for what lies between, and only up to `MAX_LCS_CELLS` entries.
+/// A larger middle is reported as removed and re-added in one block.
+fn unified_diff(old: &str, new: &str, old_name: &str, new_name: &str) -> String {
+    const CONTEXT: usize = 3;
+    const MAX_LCS_CELLS: usize = 1 << 22;
+    let a: Vec<&str> = old.lines().collect();
+    let b: Vec<&str> = new.lines().collect();
+
+    let prefix = a.iter().zip(&b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let (a_end, b_end) = (a.len() - suffix, b.len() - suffix);
+
+    // Edit script of (tag, index into a, index into b)
+    let mut ops: Vec<(char, usize, usize)> = (0..prefix).map(|i| (' ', i, i)).collect();
+    let (n, m) = (a_end - prefix, b_end - prefix);
+    if (n + 1).saturating_mul(m + 1) > MAX_LCS_CELLS {
+        ops.extend((prefix..a_end).map(|i| ('-', i, prefix)));
+        ops.extend((prefix..b_end).map(|j| ('+', a_end, j)));
+    } else {
+        // lcs[i][j]: longest common subsequence of the middle of a[i..] and b[j..]
+        let mut lcs = vec![vec![0u32; m + 1]; n + 1];
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                lcs[i][j] = if a[prefix + i] == b[prefix + j] {
+                    lcs[i + 1][j + 1] + 1
+                } else {
+                    lcs[i + 1][j].max(lcs[i][j + 1])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < n || j < m {
+            if i < n && j < m && a[prefix + i] == b[prefix + j] {
+                ops.push((' ', prefix + i, prefix + j));
+                i += 1;
+                j += 1;
+            } else if i < n && (j == m || lcs[i + 1][j] >= lcs[i][j + 1]) {
+                ops.push(('-', prefix + i, prefix + j));
+                i += 1;
+            } else {
+                ops.push(('+', prefix + i, prefix + j));
+                j += 1;
+            }
+        }
+    }
+    ops.extend((0..suffix).map(|k| (' ', a_end + k, b_end + k)));
+
+    let mut out = format!("--- {}\n+++ {}\n", old_name, new_name);
+    let mut next = 0;
+    while let Some(first) = ops[next..].iter().position(|op| op.0 != ' ') {
+        let first = next + first;
+        // Changes closer than 2 * CONTEXT lines share a hunk.
+        let mut last = first;
+        for (k, op) in ops.iter().enumerate().skip(first) {
+            if op.0 != ' ' {
+                last = k;
+            } else if k - last > 2 * CONTEXT {
+                break;
+            }
+        }
+        let start = first.saturating_sub(CONTEXT).max(next);
+        let end = (last + CONTEXT + 1).min(ops.len());
+        let hunk = &ops[start..end];
+
+        let old_len = hunk.iter().filter(|op| op.0 != '+').count();
+        let new_len = hunk.iter().filter(|op| op.0 != '-').count();
+        let old_start = hunk[0].1 + usize::from(old_len > 0);
+        let new_start = hunk[0].2 + usize::from(new_len > 0);
+        out.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            old_start, old_len, new_start, new_len
+        ));
+        for &(tag, i, j) in hunk {
+            let line = if tag == '+' { b[j] } else { a[i] };
+            out.push(tag);
+            out.push_str(line);
+            out.push('\n');
+        }
+        next = end;
+    }
+    out
+}
+
 // Minimal regex matcher supporting: '^' anchor, '$' anchor, '.' and '*' (Kleene star).
 // This is intentionally small to avoid introducing new deps; it does not implement full PCRE/Rust regex.
 fn simple_regex_is_match(pattern: &str, text: &str) -> bool {
@@ -0,0 +1,168 @@
+// LabWired - Firmware Simulation Platform
+// Copyright (C) 2026 Andrii Shylenko
+//
+// This software is released under the MIT License.
+// See the LICENSE file in the project root for full license information.
+
+//! Line-oriented debugger over stdin for `labwired repl`.
+//!
+//! Commands:
+//! - `s`: step one instruction
+//! - `c [N]`: continue for up to N steps (default: --max-steps) or until a breakpoint
+//! - `r`: print core registers
+//! - `m ADDR LEN`: hex dump LEN (at most 64 KiB) bytes starting at ADDR
+//! - `b ADDR`: set a breakpoint at ADDR
+//! - `q`: quit (end of input quits too)
+
+use crate::parse_u32_addr;
+use labwired_core::{DebugControl, StopReason};
+use std::collections::HashSet;
+use std::io::{self, BufRead, Write};
+
+/// Bytes per hex dump line.
+const DUMP_WIDTH: usize = 16;
+/// Longest hex dump `m` prints.
+const MAX_DUMP_LEN: u32 = 64 * 1024;
+
+/// Run the REPL until `q` or end of input.
+///
+/// `registers` names the core registers by id; the last entry must be the PC,
+/// which is always shown without the Thumb bit.
+/// With `prompt` set, `(labwired) ` is printed before reading each command.
+pub fn run<M: DebugControl>(
+    machine: &mut M,
+    registers: &[String],
+    default_steps: u32,
+    prompt: bool,
+    input: impl BufRead,
+    mut output: impl Write,
+) -> io::Result<()> {
+    let pc_id = (registers.len() - 1) as u8;
+    let mut breakpoints = HashSet::new();
+    let mut lines = input.lines();
+    loop {
+        if prompt {
+            write!(output, "(labwired) ")?;
+            output.flush()?;
+        }
+        let Some(line) = lines.next().transpose()? else {
+            return Ok(());
+        };
+        let args: Vec<&str> = line.split_whitespace().collect();
+        let Some((&command, args)) = args.split_first() else {
+            continue;
+        };
+
+        match (command, args) {
+            ("q", []) => return Ok(()),
+            ("s", []) => {
+                let result = machine.step_single();
+                report_stop(&mut output, result, machine.read_core_reg(pc_id))?;
+            }
+            ("c", [] | [_]) => {
+                let steps = match args.first() {
+                    Some(n) => match n.parse::<u32>() {
+                        Ok(n) if n > 0 => n,
+                        _ => {
+                            writeln!(output, "error: invalid step count '{}'", n)?;
+                            continue;
+                        }
+                    },
+                    None => default_steps,
+                };
+                let result = continue_from(machine, &breakpoints, pc_id, steps);
+                report_stop(&mut output, result, machine.read_core_reg(pc_id))?;
+            }
+            ("r", []) => {
+                for (id, name) in registers.iter().enumerate() {
+                    let mut value = machine.read_core_reg(id as u8);
+                    if id as u8 == pc_id {
+                        value &= !1;
+                    }
+                    writeln!(output, "{:<4}= {:#010x}", name, value)?;
+                }
+            }
+            ("m", [addr, len]) => {
+                let (Ok(addr), Ok(len)) = (parse_u32_addr(addr), parse_u32_addr(len)) else {
+                    writeln!(output, "error: usage: m ADDR LEN")?;
+                    continue;
+                };
+                if len > MAX_DUMP_LEN {
+                    writeln!(output, "error: LEN is at most {:#x}", MAX_DUMP_LEN)?;
+                    continue;
+                }
+                match machine.read_memory(addr, len as usize) {
+                    Ok(data) => dump(&mut output, addr, &data)?,
+                    Err(e) => writeln!(output, "error: {}", e)?,
+                }
+            }
+            ("b", [addr]) => match parse_u32_addr(addr) {
+                Ok(addr) => {
+                    let addr = addr & !1;
+                    machine.add_breakpoint(addr);
+                    breakpoints.insert(addr);
+                    writeln!(output, "breakpoint at {:#010x}", addr)?;
+                }
+                Err(e) => writeln!(output, "error: {}", e)?,
+            },
+            _ => writeln!(output, "error: unknown command '{}'", line.trim())?,
+        }
+    }
+}
+
+/// Continue for up to `steps` instructions, first stepping off a breakpoint
+/// at the current PC so that `c` after a breakpoint stop makes progress.
+fn continue_from<M: DebugControl>(
+    machine: &mut M,
+    breakpoints: &HashSet<u32>,
+    pc_id: u8,
+    steps: u32,
+) -> labwired_core::SimResult<StopReason> {
+    let pc = machine.read_core_reg(pc_id) & !1;
+    let first = if breakpoints.contains(&pc) {
+        machine.remove_breakpoint(pc);
+        let result = machine.step_single();
+        machine.add_breakpoint(pc);
+        result
+    } else {
+        machine.step_single()
+    };
+    match first? {
+        StopReason::StepDone if steps > 1 => machine.run(Some(steps - 1)),
+        StopReason::StepDone => Ok(StopReason::MaxStepsReached),
+        other => Ok(other),
+    }
+}
+
+fn report_stop(
+    output: &mut impl Write,
+    result: labwired_core::SimResult<StopReason>,
+    pc: u32,
+) -> io::Result<()> {
+    let reason = match result {
+        Ok(StopReason::Breakpoint(_)) => "breakpoint".to_string(),
+        Ok(StopReason::Watchpoint { addr, kind }) => {
+            format!("watchpoint ({:?} at {:#010x})", kind, addr)
+        }
+        Ok(StopReason::StepDone) => "step".to_string(),
+        Ok(StopReason::MaxStepsReached) => "step limit".to_string(),
+        Ok(StopReason::ManualStop) => "stopped".to_string(),
+        Ok(StopReason::Halted) => "halted".to_string(),
+        Ok(StopReason::PredicateMet) => "condition met".to_string(),
+        Err(e) => format!("error ({})", e),
+    };
+    writeln!(output, "{}, pc = {:#010x}", reason, pc & !1)
+}
+
+fn dump(output: &mut impl Write, addr: u32, data: &[u8]) -> io::Result<()> {
+    for (i, chunk) in data.chunks(DUMP_WIDTH).enumerate() {
+        let bytes: Vec<String> = chunk.iter().map(|b| format!("{:02x}", b)).collect();
+        writeln!(
+            output,
+            "{:#010x}: {}",
+            addr as usize + i * DUMP_WIDTH,
+            bytes.join(" ")
+        )?;
+    }
+    Ok(())
+}
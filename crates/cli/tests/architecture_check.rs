@@ -0,0 +1,112 @@
+// LabWired - Firmware Simulation Platform
+// Copyright (C) 2026 Andrii Shylenko
+//
+// This software is released under the MIT License.
+// See the LICENSE file in the project root for full license information.
+
+mod common;
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const MISMATCH: &str =
+    "Architecture mismatch: firmware ELF targets Arm but the system is configured for RiscV";
+
+/// An ARM (Thumb) ELF next to a system whose chip is a RISC-V core.
+fn arm_elf_on_riscv_system(name: &str) -> PathBuf {
+    let dir = common::temp_dir(name);
+    common::write_thumb_elf(&dir.join("fw.elf"), &[0xE7FE]); // b .
+    std::fs::write(
+        dir.join("chip.yaml"),
+        r#"
+name: "rv-chip"
+arch: "riscv32"
+flash:
+  base: 0x0
+  size: "64KB"
+ram:
+  base: 0x20000000
+  size: "16KB"
+peripherals: []
+"#,
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("system.yaml"),
+        "name: \"rv-system\"\nchip: \"chip.yaml\"\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("script.yaml"),
+        r#"
+schema_version: "1.0"
+inputs:
+  firmware: "fw.elf"
+  system: "system.yaml"
+limits:
+  max_steps: 10
+assertions: []
+"#,
+    )
+    .unwrap();
+    dir
+}
+
+fn run_test_mode(dir: &Path, extra: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_labwired"))
+        .args([
+            "test",
+            "--script",
+            dir.join("script.yaml").to_str().unwrap(),
+            "--no-uart-stdout",
+            "--output-dir",
+            dir.join("out").to_str().unwrap(),
+        ])
+        .args(extra)
+        .output()
+        .expect("Failed to execute labwired")
+}
+
+#[test]
+fn test_architecture_check_rejects_mismatched_elf() {
+    let dir = arm_elf_on_riscv_system("arch-check-test");
+    let output = run_test_mode(&dir, &["--architecture-check"]);
+    assert_eq!(output.status.code(), Some(2));
+
+    let result: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(dir.join("out/result.json")).unwrap())
+            .unwrap();
+    assert_eq!(result["status"], "error");
+    assert_eq!(result["message"], MISMATCH);
+}
+
+#[test]
+fn test_architecture_mismatch_only_warns_without_check() {
+    let dir = arm_elf_on_riscv_system("arch-check-warn");
+    let output = run_test_mode(&dir, &[]);
+    assert_eq!(output.status.code(), Some(0));
+}
+
+#[test]
+fn test_architecture_check_in_interactive_mode() {
+    let dir = arm_elf_on_riscv_system("arch-check-run");
+    let output = Command::new(env!("CARGO_BIN_EXE_labwired"))
+        .args([
+            "--firmware",
+            dir.join("fw.elf").to_str().unwrap(),
+            "--system",
+            dir.join("system.yaml").to_str().unwrap(),
+            "--max-steps",
+            "10",
+            "--architecture-check",
+        ])
+        .output()
+        .expect("Failed to execute labwired");
+    assert_eq!(output.status.code(), Some(2));
+    let combined = format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(combined.contains(MISMATCH), "{}", combined);
+}
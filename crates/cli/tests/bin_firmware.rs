@@ -0,0 +1,93 @@
+// LabWired - Firmware Simulation Platform
+// Copyright (C) 2026 Andrii Shylenko
+//
+// This software is released under the MIT License.
+// See the LICENSE file in the project root for full license information.
+
+mod common;
+
+use std::process::Command;
+
+/// Raw image without a vector table: print "B" on the UART, then spin.
+const BIN_FIRMWARE: &[u16] = &[
+    0x2140, // 0x0800_0000: movs r1, #0x40
+    0x0609, // 0x0800_0002: lsls r1, r1, #24
+    0x22C0, // 0x0800_0004: movs r2, #0xC0
+    0x0212, // 0x0800_0006: lsls r2, r2, #8
+    0x1889, // 0x0800_0008: adds r1, r1, r2   ; r1 = 0x4000_C000
+    0x2042, // 0x0800_000A: movs r0, #'B'
+    0x7108, // 0x0800_000C: strb r0, [r1, #4] ; DR
+    0xE7FE, // 0x0800_000E: b .
+];
+
+#[test]
+fn test_bin_firmware_starts_at_bin_base_without_vector_table() {
+    let dir = common::temp_dir("bin-firmware");
+    std::fs::write(
+        dir.join("chip.yaml"),
+        r#"
+name: "bin-chip"
+arch: "cortex-m3"
+flash:
+  base: 0x08000000
+  size: "64KB"
+ram:
+  base: 0x20000000
+  size: "16KB"
+peripherals:
+  - id: "uart1"
+    type: "uart"
+    base_address: 0x4000C000
+"#,
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("system.yaml"),
+        "name: \"bin-system\"\nchip: \"chip.yaml\"\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("script.yaml"),
+        r#"
+schema_version: "1.0"
+inputs:
+  firmware: "fw.bin"
+  system: "system.yaml"
+limits:
+  max_steps: 50
+assertions:
+  - uart_contains: "B"
+  - reached_symbol: "0x0800000e"
+"#,
+    )
+    .unwrap();
+    let image: Vec<u8> = BIN_FIRMWARE
+        .iter()
+        .flat_map(|op| op.to_le_bytes())
+        .collect();
+    std::fs::write(dir.join("fw.bin"), image).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_labwired"))
+        .args([
+            "test",
+            "--script",
+            dir.join("script.yaml").to_str().unwrap(),
+            "--bin-base",
+            "0x08000000",
+            "--no-uart-stdout",
+            "--output-dir",
+            dir.join("out").to_str().unwrap(),
+        ])
+        .output()
+        .expect("Failed to execute labwired");
+    let uart = std::fs::read_to_string(dir.join("out").join("uart.log")).unwrap_or_default();
+    let _ = std::fs::remove_dir_all(&dir);
+
+    assert_eq!(
+        output.status.code(),
+        Some(0),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(uart, "B");
+}
@@ -0,0 +1,47 @@
+// LabWired - Firmware Simulation Platform
+// Copyright (C) 2026 Andrii Shylenko
+//
+// This software is released under the MIT License.
+// See the LICENSE file in the project root for full license information.
+
+use std::process::Command;
+
+#[test]
+fn test_cli_capabilities_prints_json() {
+    let output = Command::new(env!("CARGO_BIN_EXE_labwired"))
+        .arg("capabilities")
+        .output()
+        .expect("Failed to execute labwired");
+    assert!(output.status.success());
+
+    let caps: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(caps["version"], env!("CARGO_PKG_VERSION"));
+    assert!(caps["architectures"]
+        .as_array()
+        .unwrap()
+        .contains(&serde_json::json!("cortex-m")));
+    let peripherals = caps["peripheral_types"].as_array().unwrap();
+    assert!(!peripherals.is_empty());
+    assert!(peripherals.contains(&serde_json::json!("uart")));
+    assert!(caps["assertion_kinds"]
+        .as_array()
+        .unwrap()
+        .contains(&serde_json::json!("uart_contains")));
+}
+
+#[test]
+fn test_cli_core_accepts_every_advertised_architecture() {
+    let output = Command::new(env!("CARGO_BIN_EXE_labwired"))
+        .arg("capabilities")
+        .output()
+        .expect("Failed to execute labwired");
+    let caps: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    for arch in caps["architectures"].as_array().unwrap() {
+        let arch = arch.as_str().unwrap();
+        let output = Command::new(env!("CARGO_BIN_EXE_labwired"))
+            .args(["--core", arch, "capabilities"])
+            .output()
+            .expect("Failed to execute labwired");
+        assert!(output.status.success(), "--core {}: {:?}", arch, output);
+    }
+}
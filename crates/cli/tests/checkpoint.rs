@@ -0,0 +1,111 @@
+// LabWired - Firmware Simulation Platform
+// Copyright (C) 2026 Andrii Shylenko
+//
+// This software is released under the MIT License.
+// See the LICENSE file in the project root for full license information.
+
+use std::path::Path;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn run_labwired(args: &[&str]) {
+    let output = Command::new(env!("CARGO_BIN_EXE_labwired"))
+        .args(args)
+        .output()
+        .expect("Failed to execute labwired");
+    assert!(
+        output.status.success(),
+        "labwired {:?} failed: {}",
+        args,
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+fn read_json(path: &Path) -> serde_json::Value {
+    let content = std::fs::read_to_string(path).unwrap();
+    serde_json::from_str(&content).unwrap()
+}
+
+#[test]
+fn test_cli_resume_from_checkpoint_matches_uninterrupted_run() {
+    let firmware = std::fs::canonicalize("../../tests/fixtures/uart-ok-thumbv7m.elf").unwrap();
+    let firmware = firmware.to_str().unwrap();
+
+    let nonce = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+    let dir = std::env::temp_dir().join(format!("labwired-tests-checkpoint-{}", nonce));
+    std::fs::create_dir_all(&dir).unwrap();
+    let full = dir.join("full.json");
+    let half = dir.join("half.json");
+    let resumed = dir.join("resumed.json");
+
+    // Reference: 200 uninterrupted steps, checkpointed at the end.
+    run_labwired(&[
+        "--firmware",
+        firmware,
+        "--max-steps",
+        "200",
+        "--checkpoint",
+        full.to_str().unwrap(),
+        "--checkpoint-every",
+        "200",
+    ]);
+
+    // Stop after 100 steps with a checkpoint, then resume for the remaining 100.
+    run_labwired(&[
+        "--firmware",
+        firmware,
+        "--max-steps",
+        "100",
+        "--checkpoint",
+        half.to_str().unwrap(),
+        "--checkpoint-every",
+        "100",
+    ]);
+    assert_eq!(read_json(&half)["steps_executed"], 100);
+
+    run_labwired(&[
+        "--firmware",
+        firmware,
+        "--resume",
+        half.to_str().unwrap(),
+        "--max-steps",
+        "100",
+        "--checkpoint",
+        resumed.to_str().unwrap(),
+        "--checkpoint-every",
+        "100",
+    ]);
+
+    let full = read_json(&full);
+    let resumed = read_json(&resumed);
+    assert_eq!(full["steps_executed"], 200);
+    assert_eq!(resumed["steps_executed"], 200);
+    assert_eq!(full["machine"]["cpu"], resumed["machine"]["cpu"]);
+    assert_eq!(full["machine"]["ram"], resumed["machine"]["ram"]);
+    assert_eq!(
+        full["machine"]["peripherals"],
+        resumed["machine"]["peripherals"]
+    );
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_cli_resume_rejects_missing_checkpoint() {
+    let firmware = std::fs::canonicalize("../../tests/fixtures/uart-ok-thumbv7m.elf").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_labwired"))
+        .args([
+            "--firmware",
+            firmware.to_str().unwrap(),
+            "--resume",
+            "/nonexistent/labwired-checkpoint.json",
+        ])
+        .output()
+        .expect("Failed to execute labwired");
+
+    assert_eq!(output.status.code(), Some(2));
+}
@@ -0,0 +1,134 @@
+// LabWired - Firmware Simulation Platform
+// Copyright (C) 2026 Andrii Shylenko
+//
+// This software is released under the MIT License.
+// See the LICENSE file in the project root for full license information.
+
+//! Helpers shared by the CLI integration tests.
+
+#![allow(dead_code)]
+
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Address of the first instruction in images built by [`write_thumb_elf`].
+pub const CODE_BASE: u32 = 0x40;
+
+/// Create a unique, empty temp directory for a test.
+pub fn temp_dir(prefix: &str) -> PathBuf {
+    let nonce = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+    let dir = std::env::temp_dir().join(format!("labwired-tests-{}-{}", prefix, nonce));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Write a minimal Cortex-M ELF for the default bus: a vector table at address 0
+/// (SP = 0x2000_1000, Reset = `CODE_BASE`, all other vectors empty) followed by
+/// `code` as 16-bit Thumb instructions, in a single PT_LOAD segment.
+pub fn write_thumb_elf(path: &Path, code: &[u16]) {
+    std::fs::write(path, thumb_elf(code, None)).unwrap();
+}
+
+/// Like [`write_thumb_elf`], plus a `.note.gnu.build-id` section holding `build_id`.
+pub fn write_thumb_elf_with_build_id(path: &Path, code: &[u16], build_id: &[u8]) {
+    std::fs::write(path, thumb_elf(code, Some(build_id))).unwrap();
+}
+
+fn thumb_elf(code: &[u16], build_id: Option<&[u8]>) -> Vec<u8> {
+    let mut image = Vec::new();
+    image.extend_from_slice(&0x2000_1000u32.to_le_bytes()); // Initial SP
+    image.extend_from_slice(&(CODE_BASE | 1).to_le_bytes()); // Reset vector (Thumb)
+    image.resize(CODE_BASE as usize, 0);
+    for op in code {
+        image.extend_from_slice(&op.to_le_bytes());
+    }
+    // EM_ARM, EABI version 5
+    elf32(&image, 40, CODE_BASE | 1, 0x0500_0000, build_id)
+}
+
+/// Write a minimal RV32 ELF for the default bus: `code` (32-bit instructions)
+/// at `CODE_BASE`, which is also the entry point, in a single PT_LOAD segment.
+pub fn write_riscv_elf(path: &Path, code: &[u32]) {
+    let mut image = vec![0; CODE_BASE as usize];
+    for op in code {
+        image.extend_from_slice(&op.to_le_bytes());
+    }
+    std::fs::write(path, elf32(&image, 243, CODE_BASE, 0, None)).unwrap(); // EM_RISCV
+}
+
+/// An ELF32 executable loading `image` at address 0.
+fn elf32(image: &[u8], machine: u16, entry: u32, flags: u32, build_id: Option<&[u8]>) -> Vec<u8> {
+    let mut image = image.to_vec();
+    if build_id.is_some() {
+        image.resize(image.len().next_multiple_of(4), 0);
+    }
+
+    let header_size = 52u32;
+    let phdr_size = 32u32;
+    let shdr_size = 40u32;
+    let data_offset = header_size + phdr_size;
+    let len = image.len() as u32;
+
+    // Sections: null, .note.gnu.build-id, .shstrtab
+    let mut sections = Vec::new();
+    let mut shdrs = Vec::new();
+    if let Some(id) = build_id {
+        let shstrtab = b"\0.note.gnu.build-id\0.shstrtab\0";
+        let note_offset = data_offset + len;
+        sections.extend_from_slice(&4u32.to_le_bytes()); // namesz
+        sections.extend_from_slice(&(id.len() as u32).to_le_bytes()); // descsz
+        sections.extend_from_slice(&3u32.to_le_bytes()); // NT_GNU_BUILD_ID
+        sections.extend_from_slice(b"GNU\0");
+        sections.extend_from_slice(id);
+        sections.resize(sections.len().next_multiple_of(4), 0);
+        let note_size = 16 + id.len() as u32;
+        let strtab_offset = note_offset + sections.len() as u32;
+        let strtab_size = shstrtab.len() as u32;
+        sections.extend_from_slice(shstrtab);
+        sections.resize(sections.len().next_multiple_of(4), 0);
+
+        shdrs.extend_from_slice(&[0; 40]);
+        // name, type, flags, addr, offset, size, link, info, addralign, entsize
+        for field in [1, 7, 0, 0, note_offset, note_size, 0, 0, 4, 0] {
+            shdrs.extend_from_slice(&field.to_le_bytes());
+        }
+        for field in [20, 3, 0, 0, strtab_offset, strtab_size, 0, 0, 1, 0] {
+            shdrs.extend_from_slice(&field.to_le_bytes());
+        }
+    }
+    let shoff = if shdrs.is_empty() {
+        0
+    } else {
+        data_offset + len + sections.len() as u32
+    };
+    let shnum = (shdrs.len() as u32 / shdr_size) as u16;
+
+    let mut elf = Vec::new();
+    elf.extend_from_slice(&[0x7F, b'E', b'L', b'F', 1, 1, 1, 0]);
+    elf.extend_from_slice(&[0; 8]);
+    elf.extend_from_slice(&2u16.to_le_bytes()); // e_type: EXEC
+    elf.extend_from_slice(&machine.to_le_bytes()); // e_machine
+    elf.extend_from_slice(&1u32.to_le_bytes()); // e_version
+    elf.extend_from_slice(&entry.to_le_bytes()); // e_entry
+    elf.extend_from_slice(&header_size.to_le_bytes()); // e_phoff
+    elf.extend_from_slice(&shoff.to_le_bytes()); // e_shoff
+    elf.extend_from_slice(&flags.to_le_bytes()); // e_flags
+    elf.extend_from_slice(&(header_size as u16).to_le_bytes()); // e_ehsize
+    elf.extend_from_slice(&(phdr_size as u16).to_le_bytes()); // e_phentsize
+    elf.extend_from_slice(&1u16.to_le_bytes()); // e_phnum
+    elf.extend_from_slice(&(shdr_size as u16).to_le_bytes()); // e_shentsize
+    elf.extend_from_slice(&shnum.to_le_bytes()); // e_shnum
+    elf.extend_from_slice(&(shnum.saturating_sub(1)).to_le_bytes()); // e_shstrndx
+
+    for field in [1, data_offset, 0, 0, len, len, 5, 4] {
+        // PT_LOAD, offset, vaddr, paddr, filesz, memsz, R+X, align
+        elf.extend_from_slice(&field.to_le_bytes());
+    }
+    elf.extend_from_slice(&image);
+    elf.extend_from_slice(&sections);
+    elf.extend_from_slice(&shdrs);
+    elf
+}
@@ -0,0 +1,48 @@
+// LabWired - Firmware Simulation Platform
+// Copyright (C) 2026 Andrii Shylenko
+//
+// This software is released under the MIT License.
+// See the LICENSE file in the project root for full license information.
+
+mod common;
+
+use std::process::Command;
+
+#[test]
+fn test_core_riscv_runs_riscv_elf() {
+    let dir = common::temp_dir("core-riscv");
+    let firmware = dir.join("fw.elf");
+    common::write_riscv_elf(
+        &firmware,
+        &[
+            0x0050_0093, // 0x40: addi x1, x0, 5
+            0x0010_8093, // 0x44: addi x1, x1, 1
+            0xFFDF_F06F, // 0x48: jal x0, 0x44
+        ],
+    );
+    let snapshot = dir.join("snapshot.json");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_labwired"))
+        .args([
+            "--firmware",
+            firmware.to_str().unwrap(),
+            "--core",
+            "riscv",
+            "--max-steps",
+            "5",
+            "--snapshot",
+            snapshot.to_str().unwrap(),
+        ])
+        .output()
+        .expect("Failed to execute labwired");
+    assert!(output.status.success(), "{:?}", output);
+
+    let snapshot: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&snapshot).unwrap()).unwrap();
+    assert_eq!(snapshot["status"], "ok");
+    assert_eq!(snapshot["steps_executed"], 5);
+    assert_eq!(snapshot["cpu"]["type"], "risc_v");
+    // addi, addi, jal, addi, jal
+    assert_eq!(snapshot["cpu"]["registers"][1], 7);
+    assert_eq!(snapshot["cpu"]["pc"], 0x44);
+}
@@ -0,0 +1,102 @@
+// LabWired - Firmware Simulation Platform
+// Copyright (C) 2026 Andrii Shylenko
+//
+// This software is released under the MIT License.
+// See the LICENSE file in the project root for full license information.
+
+mod common;
+
+use std::process::Command;
+
+#[test]
+fn test_cli_coverage_reports_untaken_if_body() {
+    let dir = common::temp_dir("coverage");
+    let firmware = dir.join("fw.elf");
+    // R0 is 0 out of reset, so the branch over the `if` body is always taken
+    common::write_thumb_elf(
+        &firmware,
+        &[
+            0x2800, // 0x40: cmp r0, #0
+            0xD000, // 0x42: beq 0x46
+            0x2101, // 0x44: movs r1, #1
+            0x2202, // 0x46: movs r2, #2
+            0xE7FE, // 0x48: b .
+        ],
+    );
+    let report_path = dir.join("coverage.json");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_labwired"))
+        .args([
+            "--firmware",
+            firmware.to_str().unwrap(),
+            "--max-steps",
+            "10",
+            "--coverage",
+            report_path.to_str().unwrap(),
+        ])
+        .output()
+        .expect("Failed to execute labwired");
+    assert!(output.status.success(), "{:?}", output);
+
+    let report: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&report_path).unwrap()).unwrap();
+    let _ = std::fs::remove_dir_all(&dir);
+
+    assert_eq!(
+        report["executed"],
+        serde_json::json!([0x40, 0x42, 0x46, 0x48])
+    );
+    let branches = report["branches"].as_array().unwrap();
+    assert_eq!(branches.len(), 1);
+    assert_eq!(branches[0]["addr"], 0x42);
+    assert_eq!(branches[0]["taken"], 1);
+    assert_eq!(branches[0]["not_taken"], 0);
+
+    let blocks = report["basic_blocks"].as_array().unwrap();
+    let starts: Vec<u64> = blocks
+        .iter()
+        .map(|b| b["start"].as_u64().unwrap())
+        .collect();
+    assert_eq!(starts, [0x40, 0x46, 0x48]);
+    assert_eq!(blocks[2]["hits"], 7);
+}
+
+#[test]
+fn test_cli_coverage_summarises_source_lines() {
+    let dir = common::temp_dir("coverage-lines");
+    let firmware = std::fs::canonicalize("../../tests/fixtures/dap-step-thumbv7m.elf").unwrap();
+    let report_path = dir.join("coverage.json");
+
+    // Three steps run `movs r0`, `bl helper` and helper's `push` (source
+    // lines 19, 20 and 26 of tests/fixtures/dap-step-thumbv7m.s)
+    let output = Command::new(env!("CARGO_BIN_EXE_labwired"))
+        .args([
+            "--firmware",
+            firmware.to_str().unwrap(),
+            "--max-steps",
+            "3",
+            "--coverage",
+            report_path.to_str().unwrap(),
+        ])
+        .output()
+        .expect("Failed to execute labwired");
+    assert!(output.status.success(), "{:?}", output);
+
+    let report: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&report_path).unwrap()).unwrap();
+    let _ = std::fs::remove_dir_all(&dir);
+
+    let lines = &report["lines"];
+    assert_eq!(lines["covered"], 3);
+    assert_eq!(lines["total"], 11);
+    let uncovered: Vec<u64> = lines["uncovered"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|l| {
+            assert!(l["file"].as_str().unwrap().ends_with("dap-step-thumbv7m.s"));
+            l["line"].as_u64().unwrap()
+        })
+        .collect();
+    assert_eq!(uncovered, [21, 22, 24, 27, 28, 29, 31, 32]);
+}
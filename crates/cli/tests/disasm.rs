@@ -0,0 +1,45 @@
+// LabWired - Firmware Simulation Platform
+// Copyright (C) 2026 Andrii Shylenko
+//
+// This software is released under the MIT License.
+// See the LICENSE file in the project root for full license information.
+
+use std::process::Command;
+
+fn disasm(args: &[&str]) -> String {
+    let firmware = std::fs::canonicalize("../../tests/fixtures/uart-ok-thumbv7m.elf").unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_labwired"))
+        .args(["-q", "disasm", "--firmware", firmware.to_str().unwrap()])
+        .args(args)
+        .output()
+        .expect("Failed to execute labwired");
+    assert!(output.status.success(), "{:?}", output);
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_disasm_fixture_range() {
+    // main() of the fixture: the UART address is built with MOVW/MOVT
+    let text = disasm(&["--start", "0x440", "--count", "3"]);
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(
+        lines,
+        [
+            "0x00000440: f24c 0000  movw r0, #0xc000",
+            "0x00000444: 214f       movs r1, #79",
+            "0x00000446: f2c4 0000  movt r0, #0x4000",
+        ]
+    );
+}
+
+#[test]
+fn test_disasm_defaults_to_the_entry_segment() {
+    let text = disasm(&[]);
+    // Reset handler at the start of .text
+    assert!(
+        text.starts_with("0x00000400: f000 f82c  bl #88\n"),
+        "{}",
+        text
+    );
+    assert!(text.contains("strb r1, [r0, #0]"), "{}", text);
+}
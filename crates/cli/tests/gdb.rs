@@ -0,0 +1,170 @@
+// LabWired - Firmware Simulation Platform
+// Copyright (C) 2026 Andrii Shylenko
+//
+// This software is released under the MIT License.
+// See the LICENSE file in the project root for full license information.
+
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+fn connect_with_retry(port: u16, timeout: Duration) -> TcpStream {
+    let deadline = Instant::now() + timeout;
+    loop {
+        match TcpStream::connect(("127.0.0.1", port)) {
+            Ok(stream) => return stream,
+            Err(e) if Instant::now() >= deadline => {
+                panic!("Failed to connect to GDB server: {}", e)
+            }
+            Err(_) => std::thread::sleep(Duration::from_millis(50)),
+        }
+    }
+}
+
+/// Read one `$...#xx` reply, with any leading `+` ack.
+fn read_reply(stream: &mut TcpStream) -> String {
+    let mut reply = Vec::new();
+    let mut buf = [0u8; 64];
+    while !reply.contains(&b'#') {
+        let n = stream.read(&mut buf).unwrap();
+        assert!(n > 0, "connection closed early");
+        reply.extend_from_slice(&buf[..n]);
+    }
+    String::from_utf8_lossy(&reply).to_string()
+}
+
+/// Length of a reply payload once its `X*N` run-length encoding is expanded.
+fn expanded_len(payload: &str) -> usize {
+    let bytes = payload.as_bytes();
+    let mut len = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'*' && i + 1 < bytes.len() {
+            len += (bytes[i + 1] - 29) as usize;
+            i += 2;
+        } else {
+            len += 1;
+            i += 1;
+        }
+    }
+    len
+}
+
+#[test]
+fn test_gdb_subcommand_serves_a_session() {
+    let firmware = std::fs::canonicalize("../../tests/fixtures/uart-ok-thumbv7m.elf").unwrap();
+    let port = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_labwired"))
+        .args([
+            "gdb",
+            "--firmware",
+            firmware.to_str().unwrap(),
+            "--port",
+            &port.to_string(),
+        ])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("Failed to execute labwired");
+
+    let mut stream = connect_with_retry(port, Duration::from_secs(10));
+    stream
+        .set_read_timeout(Some(Duration::from_secs(10)))
+        .unwrap();
+    // Ask for the stop reason: the target is halted at reset
+    stream.write_all(b"$?#3f").unwrap();
+    let reply = read_reply(&mut stream);
+    assert!(reply.starts_with("+$"), "{}", reply);
+
+    // Dropping the connection ends the session and the process
+    drop(stream);
+    let deadline = Instant::now() + Duration::from_secs(10);
+    let status = loop {
+        if let Some(status) = child.try_wait().unwrap() {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            panic!("labwired gdb did not exit after the client disconnected");
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    };
+    assert!(status.success());
+}
+
+#[test]
+fn test_gdb_core_flag_overrides_the_elf_machine_type() {
+    let firmware = std::fs::canonicalize("../../tests/fixtures/uart-ok-thumbv7m.elf").unwrap();
+    let port = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_labwired"))
+        .args([
+            "gdb",
+            "--firmware",
+            firmware.to_str().unwrap(),
+            "--port",
+            &port.to_string(),
+            "--core",
+            "riscv",
+        ])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("Failed to execute labwired");
+
+    let mut stream = connect_with_retry(port, Duration::from_secs(10));
+    stream
+        .set_read_timeout(Some(Duration::from_secs(10)))
+        .unwrap();
+    // x0-x31 and pc, 8 hex digits each, where Cortex-M sends 17 registers
+    stream.write_all(b"$g#67").unwrap();
+    let reply = read_reply(&mut stream);
+    let payload = reply.trim_start_matches('+').trim_start_matches('$');
+    let payload = payload.split('#').next().unwrap();
+    assert_eq!(expanded_len(payload), 33 * 8, "{}", reply);
+
+    drop(stream);
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+#[test]
+fn test_gdb_stdio_keeps_stdout_for_the_protocol() {
+    let firmware = std::fs::canonicalize("../../tests/fixtures/uart-ok-thumbv7m.elf").unwrap();
+    let mut child = Command::new(env!("CARGO_BIN_EXE_labwired"))
+        .args(["gdb", "--stdio", "--firmware", firmware.to_str().unwrap()])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to execute labwired");
+
+    // Continue long enough for the firmware to print "OK", interrupt, detach
+    let mut stdin = child.stdin.take().unwrap();
+    stdin.write_all(b"+$c#63").unwrap();
+    std::thread::sleep(Duration::from_millis(500));
+    stdin.write_all(b"\x03").unwrap();
+    std::thread::sleep(Duration::from_millis(200));
+    stdin.write_all(b"+$D#44+").unwrap();
+    drop(stdin);
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success(), "{:?}", output);
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stdout.contains("$OK#9a"), "no detach reply in {:?}", stdout);
+    // Neither the UART echo nor log lines may corrupt the packet stream
+    assert!(!stdout.replace("$OK#9a", "").contains("OK"), "{:?}", stdout);
+    assert!(!stdout.contains("INFO"), "{:?}", stdout);
+    assert!(stderr.contains("GDB session ended"), "{:?}", stderr);
+}
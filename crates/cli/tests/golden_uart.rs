@@ -0,0 +1,144 @@
+// LabWired - Firmware Simulation Platform
+// Copyright (C) 2026 Andrii Shylenko
+//
+// This software is released under the MIT License.
+// See the LICENSE file in the project root for full license information.
+
+mod common;
+
+use std::path::Path;
+use std::process::Command;
+
+const UART_TEXT: &str = "boot\nself-test ok\nready\n";
+
+/// Print the NUL-terminated string at 0x60 to the UART at 0x4000_C000, then spin.
+fn print_firmware(text: &str) -> Vec<u16> {
+    let mut code = vec![
+        0x2140, // 0x40: movs r1, #0x40
+        0x0609, // 0x42: lsls r1, r1, #24
+        0x22C0, // 0x44: movs r2, #0xC0
+        0x0212, // 0x46: lsls r2, r2, #8
+        0x1889, // 0x48: adds r1, r1, r2      ; r1 = 0x4000_C000
+        0x2060, // 0x4A: movs r0, #0x60       ; string
+        0x7802, // 0x4C: ldrb r2, [r0, #0]
+        0x2A00, // 0x4E: cmp r2, #0
+        0xD002, // 0x50: beq 0x58
+        0x710A, // 0x52: strb r2, [r1, #4]    ; DR
+        0x3001, // 0x54: adds r0, #1
+        0xE7F9, // 0x56: b 0x4C
+        0xE7FE, // 0x58: b 0x58
+        0x0000, 0x0000, 0x0000,
+    ];
+    let mut bytes = text.as_bytes().to_vec();
+    bytes.resize(bytes.len() / 2 * 2 + 2, 0);
+    code.extend(bytes.chunks(2).map(|b| u16::from_le_bytes([b[0], b[1]])));
+    code
+}
+
+fn setup(dir: &Path) {
+    common::write_thumb_elf(&dir.join("fw.elf"), &print_firmware(UART_TEXT));
+    std::fs::write(
+        dir.join("script.yaml"),
+        r#"
+schema_version: "1.0"
+inputs:
+  firmware: "fw.elf"
+limits:
+  max_steps: 500
+assertions: []
+"#,
+    )
+    .unwrap();
+}
+
+fn run_test(dir: &Path, golden: &Path, update: bool) -> std::process::Output {
+    let script = dir.join("script.yaml");
+    let mut args = vec![
+        "test",
+        "--script",
+        script.to_str().unwrap(),
+        "--no-uart-stdout",
+        "--compare-uart",
+        golden.to_str().unwrap(),
+    ];
+    if update {
+        args.push("--update-golden");
+    }
+    Command::new(env!("CARGO_BIN_EXE_labwired"))
+        .args(args)
+        .output()
+        .expect("Failed to execute labwired")
+}
+
+#[test]
+fn test_cli_compare_uart_matching_golden_passes() {
+    let dir = common::temp_dir("golden-match");
+    setup(&dir);
+    let golden = dir.join("expected.txt");
+    std::fs::write(&golden, UART_TEXT).unwrap();
+
+    let output = run_test(&dir, &golden, false);
+    let _ = std::fs::remove_dir_all(&dir);
+
+    assert_eq!(
+        output.status.code(),
+        Some(0),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn test_cli_compare_uart_mismatch_fails_with_diff() {
+    let dir = common::temp_dir("golden-mismatch");
+    setup(&dir);
+    let golden = dir.join("expected.txt");
+    std::fs::write(&golden, "boot\nself-test FAILED\nready\n").unwrap();
+
+    let output = run_test(&dir, &golden, false);
+    let _ = std::fs::remove_dir_all(&dir);
+
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("@@ -1,3 +1,3 @@\n boot\n-self-test FAILED\n+self-test ok\n ready\n"),
+        "stderr: {}",
+        stderr
+    );
+}
+
+#[test]
+fn test_cli_compare_uart_diffs_lines_between_shared_ends() {
+    let dir = common::temp_dir("golden-long");
+    setup(&dir);
+    let golden = dir.join("expected.txt");
+    let noise: String = (0..2000).map(|i| format!("noise {}\n", i)).collect();
+    std::fs::write(&golden, format!("boot\n{}self-test ok\nready\n", noise)).unwrap();
+
+    let output = run_test(&dir, &golden, false);
+    let _ = std::fs::remove_dir_all(&dir);
+
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("@@ -1,2003 +1,3 @@\n boot\n-noise 0\n"),
+        "stderr: {}",
+        stderr
+    );
+    assert!(stderr.contains("-noise 1999\n self-test ok\n ready\n"));
+}
+
+#[test]
+fn test_cli_update_golden_rewrites_file() {
+    let dir = common::temp_dir("golden-update");
+    setup(&dir);
+    let golden = dir.join("expected.txt");
+    std::fs::write(&golden, "stale\n").unwrap();
+
+    let output = run_test(&dir, &golden, true);
+    let updated = std::fs::read_to_string(&golden).unwrap();
+    let _ = std::fs::remove_dir_all(&dir);
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(updated, UART_TEXT);
+}
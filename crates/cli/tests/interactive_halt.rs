@@ -0,0 +1,147 @@
+// LabWired - Firmware Simulation Platform
+// Copyright (C) 2026 Andrii Shylenko
+//
+// This software is released under the MIT License.
+// See the LICENSE file in the project root for full license information.
+
+mod common;
+
+use std::path::Path;
+use std::process::{Command, Output};
+
+fn run_interactive(firmware: &Path, snapshot: &Path, extra: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_labwired"))
+        .args([
+            "--firmware",
+            firmware.to_str().unwrap(),
+            "--max-steps",
+            "100000",
+            "--snapshot",
+            snapshot.to_str().unwrap(),
+        ])
+        .args(extra)
+        .output()
+        .expect("Failed to execute labwired")
+}
+
+fn read_json(path: &Path) -> serde_json::Value {
+    serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+}
+
+#[test]
+fn test_interactive_stops_when_firmware_halts() {
+    let firmware = std::fs::canonicalize("../../tests/fixtures/uart-ok-thumbv7m.elf").unwrap();
+    let dir = common::temp_dir("interactive-halt");
+    let snapshot = dir.join("snapshot.json");
+
+    let output = run_interactive(&firmware, &snapshot, &["--halt-window", "16"]);
+    let log = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(log.contains("Firmware halted at PC="), "{}", log);
+
+    let snapshot = read_json(&snapshot);
+    assert_eq!(snapshot["stop_reason"], "halt");
+    assert!(snapshot["steps_executed"].as_u64().unwrap() < 1000);
+}
+
+#[test]
+fn test_interactive_halt_detection_is_on_by_default() {
+    let firmware = std::fs::canonicalize("../../tests/fixtures/uart-ok-thumbv7m.elf").unwrap();
+    let dir = common::temp_dir("interactive-halt-default");
+    let snapshot = dir.join("snapshot.json");
+
+    let output = run_interactive(&firmware, &snapshot, &[]);
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(read_json(&snapshot)["stop_reason"], "halt");
+
+    let output = run_interactive(&firmware, &snapshot, &["--halt-window", "0"]);
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(read_json(&snapshot)["stop_reason"], "max_steps");
+}
+
+#[test]
+fn test_interactive_stops_at_bkpt() {
+    let dir = common::temp_dir("interactive-bkpt");
+    let firmware = dir.join("fw.elf");
+    common::write_thumb_elf(
+        &firmware,
+        &[
+            0x2001, // 0x40: movs r0, #1
+            0xBE03, // 0x42: bkpt #3
+        ],
+    );
+    let snapshot = dir.join("snapshot.json");
+
+    let output = run_interactive(&firmware, &snapshot, &[]);
+    let log = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(
+        log.contains("Firmware halted at PC=0x42 (BKPT #3)"),
+        "{}",
+        log
+    );
+
+    let snapshot = read_json(&snapshot);
+    assert_eq!(snapshot["stop_reason"], "halt");
+    assert_eq!(snapshot["steps_executed"], 1);
+}
+
+#[test]
+fn test_semihosting_bkpt_is_not_a_clean_halt() {
+    let dir = common::temp_dir("interactive-semihosting");
+    let firmware = dir.join("fw.elf");
+    common::write_thumb_elf(&firmware, &[0xBEAB]); // bkpt #0xab
+    let snapshot = dir.join("snapshot.json");
+
+    let output = run_interactive(&firmware, &snapshot, &[]);
+    assert_eq!(output.status.code(), Some(3), "{:?}", output);
+    assert_eq!(read_json(&snapshot)["stop_reason"], "undefined_instruction");
+}
+
+#[test]
+fn test_test_runner_stops_at_bkpt() {
+    let dir = common::temp_dir("runner-bkpt");
+    common::write_thumb_elf(&dir.join("fw.elf"), &[0x2001, 0xBE00]); // movs r0, #1; bkpt #0
+    std::fs::write(
+        dir.join("script.yaml"),
+        r#"
+schema_version: "1.0"
+inputs:
+  firmware: "fw.elf"
+limits:
+  max_steps: 100
+assertions:
+  - expected_stop_reason: halt
+"#,
+    )
+    .unwrap();
+
+    let out_dir = dir.join("out");
+    let output = Command::new(env!("CARGO_BIN_EXE_labwired"))
+        .args([
+            "test",
+            "--script",
+            dir.join("script.yaml").to_str().unwrap(),
+            "--output-dir",
+            out_dir.to_str().unwrap(),
+        ])
+        .output()
+        .expect("Failed to execute labwired");
+    assert_eq!(output.status.code(), Some(0), "{:?}", output);
+
+    let result = read_json(&out_dir.join("result.json"));
+    assert_eq!(result["stop_reason"], "halt");
+    assert_eq!(result["steps_executed"], 1);
+}
+
+#[test]
+fn test_interactive_fault_exits_with_runtime_error() {
+    let dir = common::temp_dir("interactive-fault");
+    let firmware = dir.join("fw.elf");
+    common::write_thumb_elf(&firmware, &[0xFFFF, 0xFFFF]); // undefined
+    let snapshot = dir.join("snapshot.json");
+
+    let output = run_interactive(&firmware, &snapshot, &[]);
+    assert_eq!(output.status.code(), Some(3), "{:?}", output);
+    assert_eq!(read_json(&snapshot)["status"], "error");
+}
@@ -0,0 +1,30 @@
+// LabWired - Firmware Simulation Platform
+// Copyright (C) 2026 Andrii Shylenko
+//
+// This software is released under the MIT License.
+// See the LICENSE file in the project root for full license information.
+
+use std::process::Command;
+
+#[test]
+fn test_irq_map_lists_default_bus_wiring() {
+    let output = Command::new(env!("CARGO_BIN_EXE_labwired"))
+        .args(["-q", "irq-map"])
+        .output()
+        .expect("Failed to execute labwired");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert!(
+        lines.contains(&"tim2 -> IRQ 28 (base 0x40000000, nvic disabled)"),
+        "{}",
+        stdout
+    );
+    assert!(
+        lines.contains(&"systick -> IRQ 15 (base 0xe000e010, core exception)"),
+        "{}",
+        stdout
+    );
+    assert!(lines.contains(&"uart1 -> no IRQ (base 0x4000c000, -)"));
+}
@@ -0,0 +1,61 @@
+// LabWired - Firmware Simulation Platform
+// Copyright (C) 2026 Andrii Shylenko
+//
+// This software is released under the MIT License.
+// See the LICENSE file in the project root for full license information.
+
+mod common;
+
+use std::process::Command;
+
+/// Write "ITM" to stimulus port 0, then spin.
+const ITM_FIRMWARE: &[u16] = &[
+    0x20E0, // 0x40: movs r0, #0xE0
+    0x0600, // 0x42: lsls r0, r0, #24    ; r0 = 0xE000_0000
+    0x2149, // 0x44: movs r1, #'I'
+    0x7001, // 0x46: strb r1, [r0, #0]
+    0x2154, // 0x48: movs r1, #'T'
+    0x7001, // 0x4A: strb r1, [r0, #0]
+    0x214D, // 0x4C: movs r1, #'M'
+    0x7001, // 0x4E: strb r1, [r0, #0]
+    0xE7FE, // 0x50: b 0x50
+];
+
+#[test]
+fn test_itm_output_satisfies_uart_assertions() {
+    let dir = common::temp_dir("itm");
+    common::write_thumb_elf(&dir.join("fw.elf"), ITM_FIRMWARE);
+    std::fs::write(
+        dir.join("script.yaml"),
+        r#"
+schema_version: "1.0"
+inputs:
+  firmware: "fw.elf"
+limits:
+  max_steps: 20
+assertions:
+  - uart_contains: "ITM"
+"#,
+    )
+    .unwrap();
+
+    let out_dir = dir.join("out");
+    let output = Command::new(env!("CARGO_BIN_EXE_labwired"))
+        .args([
+            "test",
+            "--script",
+            dir.join("script.yaml").to_str().unwrap(),
+            "--no-uart-stdout",
+            "--output-dir",
+            out_dir.to_str().unwrap(),
+        ])
+        .output()
+        .expect("Failed to execute labwired");
+    let uart = std::fs::read_to_string(out_dir.join("uart.log")).unwrap_or_default();
+    let _ = std::fs::remove_dir_all(&dir);
+
+    assert_eq!(output.status.code(), Some(0), "{:?}", output);
+    assert_eq!(uart, "ITM");
+    // --no-uart-stdout silences the ITM echo too
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("ITM"));
+}
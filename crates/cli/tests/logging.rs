@@ -0,0 +1,44 @@
+// LabWired - Firmware Simulation Platform
+// Copyright (C) 2026 Andrii Shylenko
+//
+// This software is released under the MIT License.
+// See the LICENSE file in the project root for full license information.
+
+use std::process::Command;
+
+fn run_labwired(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_labwired"))
+        .args(args)
+        .output()
+        .expect("Failed to execute labwired");
+    format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    )
+}
+
+#[test]
+fn test_cli_quiet_suppresses_banner_but_reports_errors() {
+    let args = ["--firmware", "/nonexistent/labwired-logging.elf"];
+
+    let normal = run_labwired(&args);
+    assert!(normal.contains("Starting LabWired Simulator"), "{}", normal);
+
+    let quiet = run_labwired(&["--quiet", args[0], args[1]]);
+    assert!(!quiet.contains("Starting LabWired Simulator"), "{}", quiet);
+    assert!(quiet.contains("ERROR"), "{}", quiet);
+}
+
+#[test]
+fn test_cli_verbose_does_not_enable_instruction_trace() {
+    let firmware = std::fs::canonicalize("../../tests/fixtures/uart-ok-thumbv7m.elf").unwrap();
+    let firmware = firmware.to_str().unwrap();
+
+    let verbose = run_labwired(&["-v", "--firmware", firmware, "--max-steps", "20"]);
+    assert!(verbose.contains("DEBUG"), "{}", verbose);
+    assert!(!verbose.contains("Opcode="), "{}", verbose);
+
+    let traced = run_labwired(&["--trace", "--firmware", firmware, "--max-steps", "20"]);
+    assert!(traced.contains("Opcode="), "{}", traced);
+}
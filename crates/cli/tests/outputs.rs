@@ -4,6 +4,8 @@
 // This software is released under the MIT License.
 // See the LICENSE file in the project root for full license information.
 
+mod common;
+
 use std::path::PathBuf;
 use std::process::Command;
 use std::time::{SystemTime, UNIX_EPOCH};
@@ -93,6 +95,7 @@ assertions:
     assert!(result["stop_reason_details"]["observed"]["value"].is_number());
     assert_eq!(result["limits"]["max_steps"], 10);
     assert!(result["firmware_hash"].as_str().is_some());
+    assert!(result.get("firmware_build_id").is_none());
     assert!(result["config"]["firmware"]
         .as_str()
         .unwrap()
@@ -320,6 +323,45 @@ assertions:
     assert!(output.status.success());
 }
 
+#[test]
+fn test_cli_test_mode_undefined_instruction() {
+    let dir = common::temp_dir("undefined-instruction");
+    common::write_thumb_elf(
+        &dir.join("fw.elf"),
+        &[
+            0x2001, // 0x40: movs r0, #1
+            0xFBF0, // 0x42: undefined (long multiply, op1 = 0b111)
+            0x0000,
+        ],
+    );
+    let script = dir.join("script.yaml");
+    std::fs::write(
+        &script,
+        "schema_version: \"1.0\"\ninputs:\n  firmware: \"fw.elf\"\nlimits:\n  max_steps: 10\n",
+    )
+    .unwrap();
+
+    let out_dir = dir.join("out");
+    let output = Command::new(env!("CARGO_BIN_EXE_labwired"))
+        .args([
+            "test",
+            "--script",
+            script.to_str().unwrap(),
+            "--no-uart-stdout",
+            "--output-dir",
+            out_dir.to_str().unwrap(),
+        ])
+        .output()
+        .expect("Failed to execute command");
+
+    assert_eq!(output.status.code(), Some(3));
+    let result: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(out_dir.join("result.json")).unwrap())
+            .unwrap();
+    assert_eq!(result["stop_reason"], "undefined_instruction");
+    assert_eq!(result["steps_executed"], 2);
+}
+
 #[test]
 fn test_cli_test_mode_max_steps_guard() {
     let fw_abs = std::fs::canonicalize("../../tests/fixtures/uart-ok-thumbv7m.elf").unwrap();
@@ -429,3 +471,53 @@ assertions:
         2
     );
 }
+
+#[test]
+fn test_cli_test_mode_result_includes_build_id() {
+    let dir = common::temp_dir("build-id");
+    let fw_path = dir.join("fw.elf");
+    // B . (loop forever)
+    let build_id = [0xde, 0xad, 0xbe, 0xef, 0x01, 0x23, 0x45, 0x67];
+    common::write_thumb_elf_with_build_id(&fw_path, &[0xE7FE], &build_id);
+
+    let script_path = dir.join("script.yaml");
+    std::fs::write(
+        &script_path,
+        r#"
+schema_version: "1.0"
+inputs:
+  firmware: "fw.elf"
+limits:
+  max_steps: 10
+assertions:
+  - expected_stop_reason: max_steps
+"#,
+    )
+    .unwrap();
+
+    let output_dir = dir.join("artifacts");
+    let output = Command::new(env!("CARGO_BIN_EXE_labwired"))
+        .args([
+            "test",
+            "--script",
+            script_path.to_str().unwrap(),
+            "--no-uart-stdout",
+            "--output-dir",
+            output_dir.to_str().unwrap(),
+        ])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let result: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(output_dir.join("result.json")).unwrap())
+            .unwrap();
+    assert_eq!(result["firmware_build_id"], "deadbeef01234567");
+    assert_eq!(result["firmware_hash"].as_str().unwrap().len(), 64);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,83 @@
+// LabWired - Firmware Simulation Platform
+// Copyright (C) 2026 Andrii Shylenko
+//
+// This software is released under the MIT License.
+// See the LICENSE file in the project root for full license information.
+
+mod common;
+
+use std::process::Command;
+
+/// Store r1 to 0x100 in flash, then spin.
+const FLASH_WRITE: &[u16] = &[
+    0x2001, // 0x40: movs r0, #1
+    0x0200, // 0x42: lsls r0, r0, #8     ; r0 = 0x100
+    0x6001, // 0x44: str r1, [r0, #0]
+    0xE7FE, // 0x46: b 0x46
+];
+
+fn run_with_policy(name: &str, policy: &str, assertions: &str) -> (i32, serde_json::Value) {
+    let dir = common::temp_dir(name);
+    common::write_thumb_elf(&dir.join("fw.elf"), FLASH_WRITE);
+    std::fs::write(
+        dir.join("script.yaml"),
+        format!(
+            "schema_version: \"1.0\"\ninputs:\n  firmware: \"fw.elf\"\nlimits:\n  max_steps: 10\n{}{}",
+            policy, assertions
+        ),
+    )
+    .unwrap();
+
+    let out_dir = dir.join("out");
+    let output = Command::new(env!("CARGO_BIN_EXE_labwired"))
+        .args([
+            "test",
+            "--script",
+            dir.join("script.yaml").to_str().unwrap(),
+            "--no-uart-stdout",
+            "--output-dir",
+            out_dir.to_str().unwrap(),
+        ])
+        .output()
+        .expect("Failed to execute labwired");
+    let result = std::fs::read_to_string(out_dir.join("result.json")).unwrap();
+    (
+        output.status.code().unwrap(),
+        serde_json::from_str(&result).unwrap(),
+    )
+}
+
+#[test]
+fn test_denied_flash_write_stops_with_policy_violation() {
+    let (code, result) = run_with_policy(
+        "policy-flash",
+        "policy:\n  deny_writes:\n    - start: 0x0\n      size: 0x10000\n",
+        "",
+    );
+    assert_eq!(code, 3);
+    assert_eq!(result["status"], "error");
+    assert_eq!(result["stop_reason"], "policy_violation");
+    assert_eq!(result["steps_executed"], 3);
+}
+
+#[test]
+fn test_expected_policy_violation_passes() {
+    let (code, result) = run_with_policy(
+        "policy-expected",
+        "policy:\n  deny_writes:\n    - start: 0x100\n      size: 4\n",
+        "assertions:\n  - expected_stop_reason: policy_violation\n",
+    );
+    assert_eq!(code, 0);
+    assert_eq!(result["status"], "pass");
+}
+
+#[test]
+fn test_write_outside_denied_ranges_runs_on() {
+    let (code, result) = run_with_policy(
+        "policy-other",
+        "policy:\n  deny_writes:\n    - start: 0x104\n      size: 4\n",
+        "",
+    );
+    assert_eq!(code, 0);
+    assert_eq!(result["stop_reason"], "max_steps");
+}
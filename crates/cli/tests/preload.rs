@@ -0,0 +1,122 @@
+// LabWired - Firmware Simulation Platform
+// Copyright (C) 2026 Andrii Shylenko
+//
+// This software is released under the MIT License.
+// See the LICENSE file in the project root for full license information.
+
+mod common;
+
+use std::path::Path;
+use std::process::Command;
+
+/// Copy 16 bytes from 0x2000_0000 to the UART at 0x4000_C000, then spin.
+const DUMP_RAM_FIRMWARE: &[u16] = &[
+    0x2020, // 0x40: movs r0, #0x20
+    0x0600, // 0x42: lsls r0, r0, #24     ; r0 = 0x2000_0000
+    0x2140, // 0x44: movs r1, #0x40
+    0x0609, // 0x46: lsls r1, r1, #24
+    0x22C0, // 0x48: movs r2, #0xC0
+    0x0212, // 0x4A: lsls r2, r2, #8
+    0x1889, // 0x4C: adds r1, r1, r2      ; r1 = 0x4000_C000
+    0x2310, // 0x4E: movs r3, #16
+    0x7802, // 0x50: ldrb r2, [r0, #0]
+    0x710A, // 0x52: strb r2, [r1, #4]    ; DR
+    0x3001, // 0x54: adds r0, #1
+    0x3B01, // 0x56: subs r3, #1
+    0xD1FA, // 0x58: bne 0x50
+    0xE7FE, // 0x5A: b 0x5A
+];
+
+fn write_system(dir: &Path, preload: &str) {
+    std::fs::write(
+        dir.join("chip.yaml"),
+        r#"
+name: "preload-chip"
+arch: "cortex-m3"
+flash:
+  base: 0x0
+  size: "64KB"
+ram:
+  base: 0x20000000
+  size: "16KB"
+peripherals:
+  - id: "uart1"
+    type: "uart"
+    base_address: 0x4000C000
+"#,
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("system.yaml"),
+        format!(
+            "name: \"preload-system\"\nchip: \"chip.yaml\"\npreload:\n{}",
+            preload
+        ),
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("script.yaml"),
+        r#"
+schema_version: "1.0"
+inputs:
+  firmware: "fw.elf"
+  system: "system.yaml"
+limits:
+  max_steps: 200
+assertions:
+  - uart_contains: "preloaded-window"
+"#,
+    )
+    .unwrap();
+    common::write_thumb_elf(&dir.join("fw.elf"), DUMP_RAM_FIRMWARE);
+    std::fs::write(dir.join("image.bin"), b"HEADER:preloaded-windowTRAILER").unwrap();
+}
+
+fn run_script(dir: &Path) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_labwired"))
+        .args([
+            "test",
+            "--script",
+            dir.join("script.yaml").to_str().unwrap(),
+            "--no-uart-stdout",
+            "--output-dir",
+            dir.join("out").to_str().unwrap(),
+        ])
+        .output()
+        .expect("Failed to execute labwired")
+}
+
+#[test]
+fn test_cli_preload_file_window_into_ram() {
+    let dir = common::temp_dir("preload");
+    write_system(
+        &dir,
+        "  - addr: 0x20000000\n    file: \"image.bin\"\n    file_offset: 7\n    len: 16\n",
+    );
+
+    let output = run_script(&dir);
+    let uart = std::fs::read_to_string(dir.join("out").join("uart.log")).unwrap_or_default();
+    let _ = std::fs::remove_dir_all(&dir);
+
+    assert_eq!(
+        output.status.code(),
+        Some(0),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(uart, "preloaded-window");
+}
+
+#[test]
+fn test_cli_preload_window_past_end_of_file_is_config_error() {
+    let dir = common::temp_dir("preload-bounds");
+    write_system(
+        &dir,
+        "  - addr: 0x20000000\n    file: \"image.bin\"\n    file_offset: 24\n    len: 16\n",
+    );
+
+    let output = run_script(&dir);
+    let _ = std::fs::remove_dir_all(&dir);
+
+    assert_eq!(output.status.code(), Some(2));
+}
@@ -0,0 +1,42 @@
+// LabWired - Firmware Simulation Platform
+// Copyright (C) 2026 Andrii Shylenko
+//
+// This software is released under the MIT License.
+// See the LICENSE file in the project root for full license information.
+
+mod common;
+
+use std::process::Command;
+
+#[test]
+fn test_cli_profile_attributes_hot_loop_to_its_label() {
+    // reset: movs r0, #0; count: adds r0, #1; b count
+    let firmware = std::fs::canonicalize("../../tests/fixtures/dap-count-thumbv7m.elf").unwrap();
+    let dir = common::temp_dir("profile");
+    let report_path = dir.join("profile.json");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_labwired"))
+        .args([
+            "--firmware",
+            firmware.to_str().unwrap(),
+            "--max-steps",
+            "101",
+            "--profile",
+            report_path.to_str().unwrap(),
+        ])
+        .output()
+        .expect("Failed to execute labwired");
+    assert!(output.status.success(), "{:?}", output);
+
+    let report: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&report_path).unwrap()).unwrap();
+    let _ = std::fs::remove_dir_all(&dir);
+
+    assert_eq!(report["total_instructions"], 101);
+    let functions = report["functions"].as_array().unwrap();
+    assert_eq!(functions[0]["function"], "count");
+    assert_eq!(functions[0]["instructions"], 100);
+    assert_eq!(functions[1]["function"], "reset");
+    assert_eq!(functions[1]["instructions"], 1);
+    assert!(functions[0]["cycles"].as_u64().unwrap() > 100);
+}
@@ -0,0 +1,32 @@
+// LabWired - Firmware Simulation Platform
+// Copyright (C) 2026 Andrii Shylenko
+//
+// This software is released under the MIT License.
+// See the LICENSE file in the project root for full license information.
+
+mod common;
+
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+#[test]
+fn test_realtime_throttles_to_simulated_clock() {
+    let dir = common::temp_dir("realtime");
+    let firmware = dir.join("fw.elf");
+    common::write_thumb_elf(&firmware, &[0xE7FE]); // B .
+
+    // Every instruction costs at least one cycle, so 1000 steps at 4 kHz
+    // cover at least a quarter of a second of simulated time. `B .` costs
+    // three, so the run should take about 0.75 s; the upper bound is loose
+    // but catches a throttle that oversleeps.
+    let start = Instant::now();
+    let output = Command::new(env!("CARGO_BIN_EXE_labwired"))
+        .args(["-q", "--realtime", "4000", "--max-steps", "1000"])
+        .args(["--firmware", firmware.to_str().unwrap()])
+        .output()
+        .expect("Failed to execute labwired");
+    let elapsed = start.elapsed();
+    assert!(output.status.success(), "{:?}", output);
+    assert!(elapsed >= Duration::from_millis(250), "{:?}", elapsed);
+    assert!(elapsed < Duration::from_secs(5), "{:?}", elapsed);
+}
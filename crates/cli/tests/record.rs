@@ -0,0 +1,72 @@
+// LabWired - Firmware Simulation Platform
+// Copyright (C) 2026 Andrii Shylenko
+//
+// This software is released under the MIT License.
+// See the LICENSE file in the project root for full license information.
+
+mod common;
+
+use std::process::Command;
+
+/// Increments R0 forever.
+const FIRMWARE: &[u16] = &[
+    0x2000, // 0x40: movs r0, #0
+    0x3001, // 0x42: adds r0, #1
+    0xE7FD, // 0x44: b 0x42
+];
+
+#[test]
+fn test_record_writes_numbered_snapshots() {
+    let dir = common::temp_dir("record");
+    let firmware = dir.join("fw.elf");
+    let out_dir = dir.join("snapshots");
+    common::write_thumb_elf(&firmware, FIRMWARE);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_labwired"))
+        .args([
+            "-q",
+            "record",
+            "--firmware",
+            firmware.to_str().unwrap(),
+            "--steps",
+            "30",
+            "--interval",
+            "10",
+            "--include-trace",
+            "--output-dir",
+            out_dir.to_str().unwrap(),
+        ])
+        .output()
+        .expect("Failed to execute labwired");
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let mut files: Vec<_> = std::fs::read_dir(&out_dir)
+        .unwrap()
+        .map(|e| e.unwrap().file_name().into_string().unwrap())
+        .collect();
+    files.sort();
+    assert_eq!(
+        files,
+        [
+            "snapshot-000001.json",
+            "snapshot-000002.json",
+            "snapshot-000003.json"
+        ]
+    );
+
+    let steps: Vec<u64> = files
+        .iter()
+        .map(|name| {
+            let json: serde_json::Value =
+                serde_json::from_slice(&std::fs::read(out_dir.join(name)).unwrap()).unwrap();
+            assert_eq!(json["trace"].as_array().unwrap().len(), 10);
+            json["steps_executed"].as_u64().unwrap()
+        })
+        .collect();
+    let _ = std::fs::remove_dir_all(&dir);
+    assert_eq!(steps, [10, 20, 30]);
+}
@@ -0,0 +1,91 @@
+// LabWired - Firmware Simulation Platform
+// Copyright (C) 2026 Andrii Shylenko
+//
+// This software is released under the MIT License.
+// See the LICENSE file in the project root for full license information.
+
+mod common;
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Stores 0xAB to RAM, then sets R3 and spins.
+const FIRMWARE: &[u16] = &[
+    0x2001, // 0x40: movs r0, #1
+    0x2120, // 0x42: movs r1, #0x20
+    0x0609, // 0x44: lsls r1, r1, #24     ; r1 = 0x2000_0000
+    0x22AB, // 0x46: movs r2, #0xAB
+    0x700A, // 0x48: strb r2, [r1, #0]
+    0x2303, // 0x4A: movs r3, #3
+    0xE7FE, // 0x4C: b .
+];
+
+fn run_repl(commands: &str) -> (bool, Vec<String>) {
+    let dir = common::temp_dir("repl");
+    let firmware = dir.join("fw.elf");
+    common::write_thumb_elf(&firmware, FIRMWARE);
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_labwired"))
+        .args(["-q", "repl", "--firmware", firmware.to_str().unwrap()])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("Failed to execute labwired");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(commands.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    let _ = std::fs::remove_dir_all(&dir);
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    (
+        output.status.success(),
+        stdout.lines().map(str::to_string).collect(),
+    )
+}
+
+#[test]
+fn test_repl_step_break_continue_and_inspect() {
+    let (success, lines) = run_repl("s\nr\nb 0x4a\nc 100\nm 0x20000000 4\nc 5\nr\nq\n");
+    assert!(success);
+
+    let expected_in_order = [
+        "step, pc = 0x00000042",
+        "r0  = 0x00000001",
+        "pc  = 0x00000042",
+        "breakpoint at 0x0000004a",
+        "breakpoint, pc = 0x0000004a",
+        "0x20000000: ab 00 00 00",
+        "step limit, pc = 0x0000004c",
+        "r3  = 0x00000003",
+    ];
+    let mut rest = lines.iter();
+    for expected in expected_in_order {
+        assert!(
+            rest.any(|line| line == expected),
+            "missing '{}' in order; output: {:#?}",
+            expected,
+            lines
+        );
+    }
+}
+
+#[test]
+fn test_repl_reports_unknown_commands_and_quits_at_eof() {
+    let (success, lines) = run_repl("x 1\nm nowhere 4\n");
+    assert!(success);
+    assert!(lines.contains(&"error: unknown command 'x 1'".to_string()));
+    assert!(lines.contains(&"error: usage: m ADDR LEN".to_string()));
+}
+
+#[test]
+fn test_repl_caps_memory_dump_length() {
+    let (success, lines) = run_repl("m 0x20000000 0x10001\nm 0x20000000 0x10000\n");
+    assert!(success);
+    assert!(lines.contains(&"error: LEN is at most 0x10000".to_string()));
+    assert!(lines.iter().any(|line| line.starts_with("0x2000fff0: ")));
+}
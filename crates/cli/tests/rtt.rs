@@ -0,0 +1,73 @@
+// LabWired - Firmware Simulation Platform
+// Copyright (C) 2026 Andrii Shylenko
+//
+// This software is released under the MIT License.
+// See the LICENSE file in the project root for full license information.
+
+mod common;
+
+use std::process::Command;
+
+/// Firmware that sets up an RTT control block at 0x2000_0000 with a 16-byte
+/// up buffer at 0x2000_0100, writes "OK" through it, then idles for a while
+/// (giving the host time to find the block) and stops.
+fn rtt_firmware() -> Vec<u16> {
+    let mut code = vec![
+        0x2020, // MOVS R0, #0x20
+        0x0600, // LSLS R0, R0, #24
+    ];
+    // acID, written byte by byte; the rest of the block is zeroed RAM
+    for (i, byte) in b"SEGGER RTT".iter().enumerate() {
+        code.push(0x2100 | *byte as u16); // MOVS R1, #byte
+        code.push(0x7001 | (i as u16) << 6); // STRB R1, [R0, #i]
+    }
+    code.extend([
+        0x2101, // MOVS R1, #1
+        0x6101, // STR R1, [R0, #16] (MaxNumUpBuffers)
+        0x2201, // MOVS R2, #1
+        0x0212, // LSLS R2, R2, #8
+        0x1812, // ADDS R2, R2, R0
+        0x61C2, // STR R2, [R0, #28] (aUp[0].pBuffer)
+        0x2110, // MOVS R1, #16
+        0x6201, // STR R1, [R0, #32] (aUp[0].SizeOfBuffer)
+        0x214F, // MOVS R1, #'O'
+        0x7011, // STRB R1, [R2, #0]
+        0x214B, // MOVS R1, #'K'
+        0x7051, // STRB R1, [R2, #1]
+        0x2102, // MOVS R1, #2
+        0x6241, // STR R1, [R0, #36] (aUp[0].WrOff)
+        0x2364, // MOVS R3, #100
+        0x3B01, // SUBS R3, #1
+        0xD1FD, // BNE the SUBS
+        0xBE00, // BKPT #0
+    ]);
+    code
+}
+
+#[test]
+fn test_rtt_output_is_printed() {
+    let dir = common::temp_dir("rtt");
+    let firmware = dir.join("fw.elf");
+    common::write_thumb_elf(&firmware, &rtt_firmware());
+
+    let output = Command::new(env!("CARGO_BIN_EXE_labwired"))
+        .args(["-q", "--rtt", "--firmware", firmware.to_str().unwrap()])
+        .output()
+        .expect("Failed to execute labwired");
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "OK");
+}
+
+#[test]
+fn test_rtt_output_is_silent_without_flag() {
+    let dir = common::temp_dir("rtt-off");
+    let firmware = dir.join("fw.elf");
+    common::write_thumb_elf(&firmware, &rtt_firmware());
+
+    let output = Command::new(env!("CARGO_BIN_EXE_labwired"))
+        .args(["-q", "--firmware", firmware.to_str().unwrap()])
+        .output()
+        .expect("Failed to execute labwired");
+    assert!(output.status.success(), "{:?}", output);
+    assert!(output.stdout.is_empty(), "{:?}", output);
+}
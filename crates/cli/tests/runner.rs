@@ -167,3 +167,74 @@ assertions: []
 
     assert_eq!(output.status.code(), Some(3));
 }
+
+fn run_test_script(prefix: &str, script: &str) -> std::process::Output {
+    let script = write_temp_file(prefix, script);
+    Command::new(env!("CARGO_BIN_EXE_labwired"))
+        .args([
+            "test",
+            "--firmware",
+            "../../tests/fixtures/uart-ok-thumbv7m.elf",
+            "--script",
+            script.to_str().unwrap(),
+            "--no-uart-stdout",
+        ])
+        .output()
+        .expect("Failed to execute command")
+}
+
+#[test]
+fn test_cli_test_mode_reached_symbol() {
+    let output = run_test_script(
+        "script-reached",
+        r#"
+schema_version: "1.0"
+inputs:
+  firmware: "../../tests/fixtures/uart-ok-thumbv7m.elf"
+limits:
+  max_steps: 1000
+assertions:
+  - reached_symbol: main
+  - reached_symbol: HardFault
+    negate: true
+"#,
+    );
+    assert_eq!(
+        output.status.code(),
+        Some(0),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let output = run_test_script(
+        "script-not-reached",
+        r#"
+schema_version: "1.0"
+inputs:
+  firmware: "../../tests/fixtures/uart-ok-thumbv7m.elf"
+limits:
+  max_steps: 1000
+assertions:
+  - reached_symbol: main
+    negate: true
+"#,
+    );
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn test_cli_test_mode_unknown_reached_symbol_is_config_error() {
+    let output = run_test_script(
+        "script-unknown-symbol",
+        r#"
+schema_version: "1.0"
+inputs:
+  firmware: "../../tests/fixtures/uart-ok-thumbv7m.elf"
+limits:
+  max_steps: 10
+assertions:
+  - reached_symbol: no_such_function
+"#,
+    );
+    assert_eq!(output.status.code(), Some(2));
+}
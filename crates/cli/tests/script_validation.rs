@@ -150,3 +150,35 @@ assertions: []
 
     assert_eq!(output.status.code(), Some(1));
 }
+
+#[test]
+fn test_exit_codes_override_maps_wall_time_to_success() {
+    let script = write_temp_file(
+        "script-wall-time-exit-code",
+        r#"
+schema_version: "1.0"
+inputs:
+  firmware: "../../tests/fixtures/uart-ok-thumbv7m.elf"
+limits:
+  max_steps: 1
+  wall_time_ms: 0
+  exit_codes:
+    wall_time: 0
+assertions: []
+"#,
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_labwired"))
+        .args([
+            "test",
+            "--firmware",
+            "../../tests/fixtures/uart-ok-thumbv7m.elf",
+            "--script",
+            script.to_str().unwrap(),
+            "--no-uart-stdout",
+        ])
+        .output()
+        .expect("Failed to execute command");
+
+    assert_eq!(output.status.code(), Some(0));
+}
@@ -0,0 +1,110 @@
+// LabWired - Firmware Simulation Platform
+// Copyright (C) 2026 Andrii Shylenko
+//
+// This software is released under the MIT License.
+// See the LICENSE file in the project root for full license information.
+
+mod common;
+
+use std::process::Command;
+
+/// Poll GPIOA pin 0 until it reads high, then leave 1 in R2 and spin.
+const POLL_GPIO: &[u16] = &[
+    0x4802, // 0x40: ldr r0, [pc, #8]     ; r0 = [0x4C]
+    0x6881, // 0x42: ldr r1, [r0, #8]     ; IDR
+    0x2900, // 0x44: cmp r1, #0
+    0xD0FC, // 0x46: beq 0x42
+    0x2201, // 0x48: movs r2, #1
+    0xE7FE, // 0x4A: b 0x4A
+    0x0800, // 0x4C: .word 0x40010800 (GPIOA)
+    0x4001,
+];
+
+fn run_with_signals(name: &str, signals: &str) -> (i32, serde_json::Value) {
+    let dir = common::temp_dir(name);
+    common::write_thumb_elf(&dir.join("fw.elf"), POLL_GPIO);
+    std::fs::write(
+        dir.join("script.yaml"),
+        "schema_version: \"1.0\"\ninputs:\n  firmware: \"fw.elf\"\nlimits:\n  max_steps: 40\nassertions:\n  - register: r2\n    value: 1\n",
+    )
+    .unwrap();
+    std::fs::write(dir.join("signals.yaml"), signals).unwrap();
+
+    let out_dir = dir.join("out");
+    let output = Command::new(env!("CARGO_BIN_EXE_labwired"))
+        .args([
+            "test",
+            "--script",
+            dir.join("script.yaml").to_str().unwrap(),
+            "--signals",
+            dir.join("signals.yaml").to_str().unwrap(),
+            "--no-uart-stdout",
+            "--output-dir",
+            out_dir.to_str().unwrap(),
+        ])
+        .output()
+        .expect("Failed to execute labwired");
+    let result = std::fs::read_to_string(out_dir.join("result.json")).unwrap();
+    (
+        output.status.code().unwrap(),
+        serde_json::from_str(&result).unwrap(),
+    )
+}
+
+#[test]
+fn test_scheduled_gpio_input_releases_polling_loop() {
+    let (code, result) = run_with_signals(
+        "signals-gpio",
+        "signals:\n  - at_step: 10\n    gpio_input: { port: gpioa, pin: 0, level: true }\n",
+    );
+    assert_eq!(code, 0);
+    assert_eq!(result["status"], "pass");
+}
+
+#[test]
+fn test_without_due_signal_firmware_keeps_polling() {
+    let (code, result) = run_with_signals(
+        "signals-late",
+        "signals:\n  - at_step: 1000\n    gpio_input: { port: gpioa, pin: 0, level: true }\n",
+    );
+    assert_eq!(code, 1);
+    assert_eq!(result["status"], "fail");
+}
+
+#[test]
+fn test_invalid_signals_file_is_config_error() {
+    let (code, result) = run_with_signals(
+        "signals-invalid",
+        "signals:\n  - at_step: 1\n    gpio_input: { port: gpioa, pin: 16, level: true }\n",
+    );
+    assert_eq!(code, 2);
+    assert_eq!(result["status"], "error");
+}
+
+#[test]
+fn test_signal_for_unknown_port_is_config_error() {
+    let (code, result) = run_with_signals(
+        "signals-unknown-port",
+        "signals:\n  - at_step: 1\n    gpio_input: { port: gpioz, pin: 0, level: true }\n",
+    );
+    assert_eq!(code, 2);
+    assert_eq!(result["status"], "error");
+    assert!(
+        result["message"]
+            .as_str()
+            .unwrap_or_default()
+            .contains("gpioz"),
+        "{}",
+        result
+    );
+}
+
+#[test]
+fn test_signal_for_unknown_uart_is_config_error() {
+    let (code, result) = run_with_signals(
+        "signals-unknown-uart",
+        "signals:\n  - at_step: 1\n    uart_rx: { name: uart9, data: \"x\" }\n",
+    );
+    assert_eq!(code, 2);
+    assert_eq!(result["status"], "error");
+}
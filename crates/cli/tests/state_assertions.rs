@@ -0,0 +1,172 @@
+// LabWired - Firmware Simulation Platform
+// Copyright (C) 2026 Andrii Shylenko
+//
+// This software is released under the MIT License.
+// See the LICENSE file in the project root for full license information.
+
+mod common;
+
+use std::process::Command;
+
+/// Store 0xEFBEADDE (bytes DE AD BE EF) at 0x2000_0000, leave 42 in R0,
+/// then spin.
+const FIRMWARE: &[u16] = &[
+    0x2020, // 0x40: movs r0, #0x20
+    0x0600, // 0x42: lsls r0, r0, #24     ; r0 = 0x2000_0000
+    0x4902, // 0x44: ldr r1, [pc, #8]     ; r1 = [0x50]
+    0x6001, // 0x46: str r1, [r0, #0]
+    0x202A, // 0x48: movs r0, #42
+    0xE7FE, // 0x4A: b 0x4A
+    0x0000, // 0x4C: padding
+    0x0000, // 0x4E: padding
+    0xADDE, // 0x50: .word 0xEFBEADDE
+    0xEFBE,
+];
+
+fn run_with_assertions(name: &str, assertions: &str) -> (std::process::Output, serde_json::Value) {
+    run_in_dir(&common::temp_dir(name), assertions, &[])
+}
+
+fn run_in_dir(
+    dir: &std::path::Path,
+    assertions: &str,
+    extra_args: &[&str],
+) -> (std::process::Output, serde_json::Value) {
+    common::write_thumb_elf(&dir.join("fw.elf"), FIRMWARE);
+    std::fs::write(
+        dir.join("script.yaml"),
+        format!(
+            "schema_version: \"1.0\"\ninputs:\n  firmware: \"fw.elf\"\nlimits:\n  max_steps: 10\nassertions:\n{}",
+            assertions
+        ),
+    )
+    .unwrap();
+
+    let out_dir = dir.join("out");
+    let output = Command::new(env!("CARGO_BIN_EXE_labwired"))
+        .args([
+            "test",
+            "--script",
+            dir.join("script.yaml").to_str().unwrap(),
+            "--no-uart-stdout",
+            "--output-dir",
+            out_dir.to_str().unwrap(),
+        ])
+        .args(extra_args)
+        .output()
+        .expect("Failed to execute labwired");
+    let result = std::fs::read_to_string(out_dir.join("result.json"))
+        .map(|s| serde_json::from_str(&s).unwrap())
+        .unwrap_or(serde_json::Value::Null);
+    (output, result)
+}
+
+#[test]
+fn test_register_equals_passes_on_final_value() {
+    let (output, result) = run_with_assertions(
+        "register-pass",
+        "  - register: \"r0\"\n    value: 42\n  - register: pc\n    value: 0x4A\n",
+    );
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(result["status"], "pass");
+    assert_eq!(result["assertions"][0]["passed"], true);
+    assert_eq!(result["assertions"][1]["passed"], true);
+}
+
+#[test]
+fn test_register_equals_mismatch_fails() {
+    let (output, result) =
+        run_with_assertions("register-fail", "  - register: R0\n    value: 41\n");
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(result["status"], "fail");
+    assert_eq!(result["assertions"][0]["passed"], false);
+}
+
+#[test]
+fn test_register_equals_unknown_register_is_config_error() {
+    let (output, _) = run_with_assertions("register-unknown", "  - register: r99\n    value: 0\n");
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn test_memory_equals_passes_on_written_buffer() {
+    let (output, result) = run_with_assertions(
+        "memory-pass",
+        "  - address: 0x20000000\n    bytes: \"DEADBEEF\"\n  - address: 0x20000002\n    bytes: [0xbe, 0xef]\n",
+    );
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(result["status"], "pass");
+    assert_eq!(result["message"], serde_json::Value::Null);
+}
+
+#[test]
+fn test_memory_equals_mismatch_reports_bytes_read() {
+    let (output, result) = run_with_assertions(
+        "memory-fail",
+        "  - address: 0x20000000\n    bytes: \"0xdeadc0de\"\n",
+    );
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(result["assertions"][0]["passed"], false);
+    assert_eq!(
+        result["message"],
+        "memory at 0x20000000: expected deadc0de, read deadbeef"
+    );
+}
+
+#[test]
+fn test_memory_equals_unmapped_address_fails() {
+    let (output, result) = run_with_assertions(
+        "memory-unmapped",
+        "  - address: 0x90000000\n    bytes: \"00\"\n",
+    );
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        result["message"],
+        "memory at 0x90000000 (1 bytes) is not mapped"
+    );
+}
+
+#[test]
+fn test_registers_match_file_passes_on_matching_dump() {
+    let dir = common::temp_dir("registers-file-pass");
+    std::fs::write(dir.join("regs.json"), r#"{"r0": 42, "pc": "0x0000004a"}"#).unwrap();
+    let (output, result) = run_in_dir(&dir, "  - registers_match_file: \"regs.json\"\n", &[]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(result["status"], "pass");
+    assert_eq!(result["assertions"][0]["passed"], true);
+}
+
+#[test]
+fn test_registers_match_file_reports_each_mismatch() {
+    let dir = common::temp_dir("registers-file-fail");
+    std::fs::write(dir.join("regs.json"), r#"{"r0": "0x2b", "r1": 0}"#).unwrap();
+    let (output, result) = run_in_dir(&dir, "  - registers_match_file: \"regs.json\"\n", &[]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(result["assertions"][0]["passed"], false);
+    let message = result["message"].as_str().unwrap();
+    assert!(
+        message.contains("register r0: expected 0x0000002b, read 0x0000002a"),
+        "{}",
+        message
+    );
+    assert!(message.contains("register r1: expected"), "{}", message);
+}
+
+#[test]
+fn test_registers_match_file_update_golden_rewrites_dump() {
+    let dir = common::temp_dir("registers-file-update");
+    std::fs::write(dir.join("regs.json"), r#"{"r0": 0}"#).unwrap();
+    let (output, _) = run_in_dir(
+        &dir,
+        "  - registers_match_file: \"regs.json\"\n",
+        &["--update-golden"],
+    );
+    assert_eq!(output.status.code(), Some(0));
+
+    let (output, result) = run_in_dir(&dir, "  - registers_match_file: \"regs.json\"\n", &[]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(result["status"], "pass");
+    let regs: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(dir.join("regs.json")).unwrap()).unwrap();
+    assert_eq!(regs["r0"], "0x0000002a");
+}
@@ -0,0 +1,97 @@
+// LabWired - Firmware Simulation Platform
+// Copyright (C) 2026 Andrii Shylenko
+//
+// This software is released under the MIT License.
+// See the LICENSE file in the project root for full license information.
+
+mod common;
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Echo UART RX to TX until a newline, then BKPT.
+const ECHO_FIRMWARE: &[u16] = &[
+    0x2140, // 0x40: movs r1, #0x40
+    0x0609, // 0x42: lsls r1, r1, #24
+    0x22C0, // 0x44: movs r2, #0xC0
+    0x0212, // 0x46: lsls r2, r2, #8
+    0x1889, // 0x48: adds r1, r1, r2     ; r1 = 0x4000_C000
+    0x7808, // 0x4A: ldrb r0, [r1, #0]   ; SR
+    0x0680, // 0x4C: lsls r0, r0, #26    ; RXNE into N
+    0xD5FC, // 0x4E: bpl 0x4A
+    0x7908, // 0x50: ldrb r0, [r1, #4]   ; DR
+    0x7108, // 0x52: strb r0, [r1, #4]
+    0x280A, // 0x54: cmp r0, #'\n'
+    0xD1F8, // 0x56: bne 0x4A
+    0xBE00, // 0x58: bkpt #0
+];
+
+#[test]
+fn test_stdin_is_fed_to_uart_rx() {
+    let dir = common::temp_dir("stdin-uart");
+    let firmware = dir.join("fw.elf");
+    common::write_thumb_elf(&firmware, ECHO_FIRMWARE);
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_labwired"))
+        .args([
+            "-q",
+            "--firmware",
+            firmware.to_str().unwrap(),
+            "--stdin-uart",
+            "uart1",
+            "--max-steps",
+            "50000000",
+        ])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to execute labwired");
+    // Closing stdin afterwards is an EOF the run must survive
+    child.stdin.take().unwrap().write_all(b"hello\n").unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "hello\n");
+}
+
+#[test]
+fn test_stdin_uart_rejects_unknown_uart() {
+    let dir = common::temp_dir("stdin-uart-unknown");
+    let firmware = dir.join("fw.elf");
+    common::write_thumb_elf(&firmware, ECHO_FIRMWARE);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_labwired"))
+        .args([
+            "--firmware",
+            firmware.to_str().unwrap(),
+            "--stdin-uart",
+            "uart9",
+        ])
+        .stdin(Stdio::null())
+        .output()
+        .expect("Failed to execute labwired");
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn test_stdin_uart_conflicts_with_uart_tcp() {
+    let dir = common::temp_dir("stdin-uart-tcp");
+    let firmware = dir.join("fw.elf");
+    common::write_thumb_elf(&firmware, ECHO_FIRMWARE);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_labwired"))
+        .args([
+            "--firmware",
+            firmware.to_str().unwrap(),
+            "--stdin-uart",
+            "uart1",
+            "--uart-tcp",
+            "0",
+        ])
+        .stdin(Stdio::null())
+        .output()
+        .expect("Failed to execute labwired");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("cannot be used with"));
+}
@@ -13,6 +13,10 @@ fn get_labwired_bin() -> PathBuf {
 }
 
 fn run_test(name: &str, yaml_content: &str) -> Value {
+    run_test_with_args(name, yaml_content, &[])
+}
+
+fn run_test_with_args(name: &str, yaml_content: &str, extra_args: &[&str]) -> Value {
     let temp_dir = std::env::temp_dir().join(format!("labwired-stop-{}", name));
     let _ = std::fs::remove_dir_all(&temp_dir);
     std::fs::create_dir_all(&temp_dir).unwrap();
@@ -46,6 +50,7 @@ fn run_test(name: &str, yaml_content: &str) -> Value {
         .arg("--output-dir")
         .arg(&temp_dir)
         .arg("--no-uart-stdout")
+        .args(extra_args)
         .output()
         .expect("Failed to run labwired");
 
@@ -120,6 +125,13 @@ assertions: []
 "#;
     let result = run_test("max_cycles", script);
     assert_eq!(result["stop_reason"], "max_cycles");
+    // The cycle budget, not the step limit, ended the run.
+    assert!(result["cycles"].as_u64().unwrap() >= 10);
+    assert!(result["steps_executed"].as_u64().unwrap() <= 10);
+    assert_eq!(
+        result["stop_reason_details"]["triggered_stop_condition"],
+        "max_cycles"
+    );
 }
 
 #[test]
@@ -232,3 +244,96 @@ assertions:
     assert_eq!(result["stop_reason"], "no_progress");
     assert_eq!(result["status"], "pass");
 }
+
+#[test]
+fn test_halt_window_stops_final_loop_as_halt() {
+    let script = r#"
+schema_version: "1.0"
+inputs:
+  firmware: "__FIRMWARE__"
+  system: "__SYSTEM__"
+limits:
+  max_steps: 100000
+  no_progress_steps: 100
+assertions:
+  - uart_contains: "OK"
+  - expected_stop_reason: halt
+"#;
+    let result = run_test_with_args("halt_window", script, &["--halt-window", "50"]);
+    assert_eq!(result["stop_reason"], "halt");
+    assert_eq!(result["status"], "pass");
+}
+
+#[test]
+fn test_stop_when_assertions_pass() {
+    let script = r#"
+schema_version: "1.0"
+inputs:
+  firmware: "__FIRMWARE__"
+  system: "__SYSTEM__"
+limits:
+  max_steps: 20000
+  stop_when_assertions_pass: true
+assertions:
+  - uart_contains: "OK"
+  - expected_stop_reason: assertions_passed
+"#;
+    let result = run_test("assertions_passed", script);
+    assert_eq!(result["status"], "pass");
+    assert_eq!(result["stop_reason"], "assertions_passed");
+    assert_eq!(result["limits"]["stop_when_assertions_pass"], true);
+    let steps = result["steps_executed"].as_u64().unwrap();
+    assert!(steps > 0 && steps < 1000, "steps_executed = {}", steps);
+    assert_eq!(
+        result["stop_reason_details"]["observed"]["value"].as_u64(),
+        Some(steps)
+    );
+}
+
+#[test]
+fn test_must_reach_gate() {
+    let script = r#"
+schema_version: "1.0"
+inputs:
+  firmware: "__FIRMWARE__"
+  system: "__SYSTEM__"
+limits:
+  max_steps: 200
+  must_reach:
+    symbol_or_addr: __TARGET__
+    by_step: 100
+assertions:
+  - expected_stop_reason: max_steps
+"#;
+    let result = run_test("must_reach_ok", &script.replace("__TARGET__", "main"));
+    assert_eq!(result["status"], "pass");
+    assert!(result.get("message").is_none());
+
+    // HardFault is never reached: the gate trips but the run continues
+    let result = run_test(
+        "must_reach_miss",
+        &script.replace("__TARGET__", "HardFault"),
+    );
+    assert_eq!(result["status"], "fail");
+    assert_eq!(result["stop_reason"], "max_steps");
+    assert_eq!(result["steps_executed"], 200);
+    assert_eq!(
+        result["message"],
+        "must_reach: PC did not reach 'HardFault' (0x00000462) by step 100"
+    );
+    assert_eq!(result["limits"]["must_reach"]["by_step"], 100);
+
+    // A run that stops before the deadline misses the gate as well
+    let result = run_test(
+        "must_reach_short_run",
+        &script
+            .replace("__TARGET__", "HardFault")
+            .replace("max_steps: 200", "max_steps: 50"),
+    );
+    assert_eq!(result["status"], "fail");
+    assert_eq!(result["steps_executed"], 50);
+    assert_eq!(
+        result["message"],
+        "must_reach: PC did not reach 'HardFault' (0x00000462) before the run stopped"
+    );
+}
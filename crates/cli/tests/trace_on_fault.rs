@@ -0,0 +1,74 @@
+// LabWired - Firmware Simulation Platform
+// Copyright (C) 2026 Andrii Shylenko
+//
+// This software is released under the MIT License.
+// See the LICENSE file in the project root for full license information.
+
+mod common;
+
+use std::process::Command;
+
+/// Branches to unmapped memory; the next fetch raises a memory violation.
+const FAULTING_FIRMWARE: &[u16] = &[
+    0x2060, // 0x40: movs r0, #0x60
+    0x0600, // 0x42: lsls r0, r0, #24
+    0x2101, // 0x44: movs r1, #1
+    0x1840, // 0x46: adds r0, r0, r1      ; r0 = 0x6000_0001
+    0x4700, // 0x48: bx r0
+];
+
+#[test]
+fn test_cli_trace_on_fault_dumps_last_instructions() {
+    let dir = common::temp_dir("trace-on-fault");
+    let firmware = dir.join("fault.elf");
+    common::write_thumb_elf(&firmware, FAULTING_FIRMWARE);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_labwired"))
+        .args([
+            "--firmware",
+            firmware.to_str().unwrap(),
+            "--max-steps",
+            "100",
+            "--trace-on-fault",
+            "4",
+        ])
+        .output()
+        .expect("Failed to execute labwired");
+    let _ = std::fs::remove_dir_all(&dir);
+
+    let log = format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(log.contains("last 4 instructions"), "{}", log);
+    assert!(
+        log.contains("0x00000048: 0x00004700  Bx { rm: 0 }"),
+        "{}",
+        log
+    );
+    assert!(log.contains("0x00000046: 0x00001840"), "{}", log);
+    assert!(log.contains("0x00000042: 0x00000600"), "{}", log);
+    // Only the last 4 instructions are kept
+    assert!(!log.contains("0x00000040: 0x00002060"), "{}", log);
+}
+
+#[test]
+fn test_cli_trace_on_fault_silent_without_fault() {
+    let firmware = std::fs::canonicalize("../../tests/fixtures/uart-ok-thumbv7m.elf").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_labwired"))
+        .args([
+            "--firmware",
+            firmware.to_str().unwrap(),
+            "--max-steps",
+            "50",
+            "--trace-on-fault",
+        ])
+        .output()
+        .expect("Failed to execute labwired");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!stdout.contains("instructions:"), "{}", stdout);
+}
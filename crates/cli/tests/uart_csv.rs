@@ -0,0 +1,86 @@
+// LabWired - Firmware Simulation Platform
+// Copyright (C) 2026 Andrii Shylenko
+//
+// This software is released under the MIT License.
+// See the LICENSE file in the project root for full license information.
+
+use std::path::PathBuf;
+use std::process::Command;
+
+#[test]
+fn test_uart_csv_has_a_row_per_emitted_byte() {
+    let temp_dir = std::env::temp_dir().join("labwired-uart-csv");
+    let _ = std::fs::remove_dir_all(&temp_dir);
+    std::fs::create_dir_all(&temp_dir).unwrap();
+
+    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let workspace_root = manifest_dir.parent().unwrap().parent().unwrap();
+    let firmware_path = workspace_root
+        .join("tests/fixtures/uart-ok-thumbv7m.elf")
+        .canonicalize()
+        .unwrap();
+    let system_path = workspace_root
+        .join("configs/systems/ci-fixture-uart1.yaml")
+        .canonicalize()
+        .unwrap();
+
+    let script_path = temp_dir.join("script.yaml");
+    std::fs::write(
+        &script_path,
+        format!(
+            r#"
+schema_version: "1.0"
+inputs:
+  firmware: "{}"
+  system: "{}"
+limits:
+  max_steps: 1000
+assertions: []
+"#,
+            firmware_path.display(),
+            system_path.display()
+        ),
+    )
+    .unwrap();
+
+    let output_dir = temp_dir.join("out");
+    let csv_path = temp_dir.join("uart.csv");
+    let output = Command::new(env!("CARGO_BIN_EXE_labwired"))
+        .arg("test")
+        .arg("--script")
+        .arg(&script_path)
+        .arg("--output-dir")
+        .arg(&output_dir)
+        .arg("--uart-csv")
+        .arg(&csv_path)
+        .arg("--no-uart-stdout")
+        .output()
+        .expect("Failed to run labwired");
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let uart = std::fs::read(output_dir.join("uart.log")).unwrap();
+    assert!(!uart.is_empty(), "fixture should print to the UART");
+    let csv = std::fs::read_to_string(&csv_path).unwrap();
+    let mut lines = csv.lines();
+    assert_eq!(lines.next(), Some("step,cycle,byte,ascii"));
+
+    let rows: Vec<&str> = lines.collect();
+    assert_eq!(rows.len(), uart.len());
+    let mut last_step = 0;
+    for (row, &byte) in rows.iter().zip(&uart) {
+        let fields: Vec<&str> = row.splitn(4, ',').collect();
+        let step: u64 = fields[0].parse().unwrap();
+        assert!(step >= last_step, "steps must not go backwards: {}", row);
+        last_step = step;
+        assert!(
+            fields[1].parse::<u64>().is_ok(),
+            "bad cycle column: {}",
+            row
+        );
+        assert_eq!(fields[2], format!("0x{:02x}", byte));
+    }
+}
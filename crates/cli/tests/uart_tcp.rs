@@ -0,0 +1,88 @@
+// LabWired - Firmware Simulation Platform
+// Copyright (C) 2026 Andrii Shylenko
+//
+// This software is released under the MIT License.
+// See the LICENSE file in the project root for full license information.
+
+mod common;
+
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// Thumb echo loop for the default bus (UART at 0x4000_C000): wait for RXNE,
+/// read DR, write it back to DR.
+const ECHO_FIRMWARE: &[u16] = &[
+    0x2040, // 0x40: movs r0, #0x40
+    0x0600, // 0x42: lsls r0, r0, #24
+    0x21C0, // 0x44: movs r1, #0xC0
+    0x0209, // 0x46: lsls r1, r1, #8
+    0x1840, // 0x48: adds r0, r0, r1      ; r0 = 0x4000_C000
+    0x7801, // 0x4A: ldrb r1, [r0, #0]    ; SR
+    0x2220, // 0x4C: movs r2, #0x20       ; RXNE
+    0x4011, // 0x4E: ands r1, r2
+    0xD0FB, // 0x50: beq 0x4A
+    0x7901, // 0x52: ldrb r1, [r0, #4]    ; DR
+    0x7101, // 0x54: strb r1, [r0, #4]
+    0xE7F8, // 0x56: b 0x4A
+];
+
+fn connect_with_retry(port: u16, timeout: Duration) -> TcpStream {
+    let deadline = Instant::now() + timeout;
+    loop {
+        match TcpStream::connect(("127.0.0.1", port)) {
+            Ok(stream) => return stream,
+            Err(e) if Instant::now() >= deadline => panic!("Failed to connect to bridge: {}", e),
+            Err(_) => std::thread::sleep(Duration::from_millis(50)),
+        }
+    }
+}
+
+#[test]
+fn test_cli_uart_tcp_bridge_echoes_input() {
+    let dir = common::temp_dir("uart-tcp");
+    let firmware = dir.join("echo.elf");
+    common::write_thumb_elf(&firmware, ECHO_FIRMWARE);
+
+    let port = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_labwired"))
+        .args([
+            "--firmware",
+            firmware.to_str().unwrap(),
+            "--max-steps",
+            "1000000000",
+            "--uart-tcp",
+            &port.to_string(),
+        ])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("Failed to execute labwired");
+
+    let mut stream = connect_with_retry(port, Duration::from_secs(10));
+    stream
+        .set_read_timeout(Some(Duration::from_millis(200)))
+        .unwrap();
+    stream.write_all(b"ping").unwrap();
+
+    let mut received = Vec::new();
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while !received.ends_with(b"ping") && Instant::now() < deadline {
+        let mut buf = [0u8; 64];
+        if let Ok(n) = stream.read(&mut buf) {
+            received.extend_from_slice(&buf[..n]);
+        }
+    }
+
+    let _ = child.kill();
+    let _ = child.wait();
+    let _ = std::fs::remove_dir_all(&dir);
+
+    assert_eq!(String::from_utf8_lossy(&received), "ping");
+}
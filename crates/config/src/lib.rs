@@ -45,6 +45,9 @@ pub struct ChipDescriptor {
     pub flash: MemoryRange,
     pub ram: MemoryRange,
     pub peripherals: Vec<PeripheralConfig>,
+    /// The core has a single-precision FPU (Cortex-M4F/M7).
+    #[serde(default)]
+    pub fpu: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -56,6 +59,47 @@ pub struct ExternalDevice {
     pub config: HashMap<String, serde_yaml::Value>,
 }
 
+/// Initializes memory at `addr` from `len` bytes of `file` starting at
+/// `file_offset` (the rest of the file when `len` is omitted), e.g. a
+/// filesystem image in external flash.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct MemoryPreload {
+    pub addr: u64,
+    pub file: String, // Relative to the system manifest
+    #[serde(default)]
+    pub file_offset: u64,
+    #[serde(default)]
+    pub len: Option<u64>,
+}
+
+impl MemoryPreload {
+    /// Read the configured window of `file`, resolved against `base_dir`.
+    pub fn read(&self, base_dir: &Path) -> Result<Vec<u8>> {
+        let path = base_dir.join(&self.file);
+        let data = std::fs::read(&path)
+            .with_context(|| format!("Failed to read preload file {:?}", path))?;
+        let file_len = data.len() as u64;
+        let len = self
+            .len
+            .unwrap_or_else(|| file_len.saturating_sub(self.file_offset));
+        let end = self
+            .file_offset
+            .checked_add(len)
+            .filter(|&end| end <= file_len);
+        let Some(end) = end else {
+            anyhow::bail!(
+                "Preload window {:#x}+{:#x} is out of bounds for {:?} ({} bytes)",
+                self.file_offset,
+                len,
+                path,
+                file_len
+            );
+        };
+        Ok(data[self.file_offset as usize..end as usize].to_vec())
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct SystemManifest {
     pub name: String,
@@ -64,6 +108,8 @@ pub struct SystemManifest {
     pub memory_overrides: HashMap<String, String>,
     #[serde(default)]
     pub external_devices: Vec<ExternalDevice>,
+    #[serde(default)]
+    pub preload: Vec<MemoryPreload>,
 }
 
 impl ChipDescriptor {
@@ -99,9 +145,31 @@ pub struct TestLimits {
     pub no_progress_steps: Option<u64>,
     #[serde(default)]
     pub wall_time_ms: Option<u64>,
+    /// Check assertions during the run and stop as soon as all of them pass.
+    #[serde(default)]
+    pub stop_when_assertions_pass: bool,
+    /// Fail the run if the PC has not reached a target by a deadline step.
+    #[serde(default)]
+    pub must_reach: Option<MustReach>,
+    /// Process exit code to use for a stop reason instead of the default.
+    /// Failed assertions still exit with 1.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub exit_codes: HashMap<StopReason, u8>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+/// Liveness gate: the PC must equal `symbol_or_addr` at some point within the
+/// first `by_step` steps. It does not stop the run; a miss fails it at the end,
+/// as does a run that stops early without reaching the target.
+/// As with `reached_symbol`, a `0x`-prefixed value that is not a symbol name is
+/// taken as a raw address.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct MustReach {
+    pub symbol_or_addr: String,
+    pub by_step: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
 #[serde(rename_all = "snake_case")]
 pub enum StopReason {
     /// Runner failed before simulation started (e.g. script parse/validation error).
@@ -113,7 +181,14 @@ pub enum StopReason {
     WallTime,
     MemoryViolation,
     DecodeError,
+    /// The firmware broke the script's `policy`.
+    PolicyViolation,
+    UndefinedInstruction,
+    UnalignedAccess,
+    StackOverflow,
     Halt,
+    /// Every assertion passed before a limit was hit (`stop_when_assertions_pass`).
+    AssertionsPassed,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -134,12 +209,152 @@ pub struct StopReasonAssertion {
     pub expected_stop_reason: StopReason,
 }
 
+/// Passes if the PC ever equals the address of `reached_symbol` during the run
+/// (or never does, with `negate: true`). A `0x`-prefixed value that is not a
+/// symbol name is taken as a raw address.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ReachedSymbolAssertion {
+    pub reached_symbol: String,
+    #[serde(default)]
+    pub negate: bool,
+}
+
+/// Passes if core register `register` holds `value` once the run is over.
+/// Names are `r0`-`r15`, `sp`, `lr`, `pc` and `xpsr`, in any case.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct RegisterEqualsAssertion {
+    pub register: String,
+    pub value: u32,
+}
+
+impl RegisterEqualsAssertion {
+    /// Core register id of `register`; see [`core_register_id`].
+    pub fn register_id(&self) -> Option<u8> {
+        core_register_id(&self.register)
+    }
+}
+
+/// Core register id of `name` (0-12 = R0-R12, 13 = SP, 14 = LR, 15 = PC,
+/// 16 = xPSR), or `None` for an unknown name. Case-insensitive.
+pub fn core_register_id(name: &str) -> Option<u8> {
+    let name = name.trim().to_ascii_lowercase();
+    match name.as_str() {
+        "sp" => Some(13),
+        "lr" => Some(14),
+        "pc" => Some(15),
+        "xpsr" => Some(16),
+        _ => (0..=15).find(|n| name == format!("r{}", n)),
+    }
+}
+
+/// Passes if the final core registers match a JSON file of expected values,
+/// such as a reference implementation's dump: an object mapping register
+/// names (as for `register`) to integers or `0x`-prefixed hex strings.
+/// Registers missing from the file are not checked. A relative path is
+/// resolved against the script's directory.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct RegistersMatchFileAssertion {
+    pub registers_match_file: String,
+}
+
+/// Passes if memory starting at `address` holds `bytes` once the run is over.
+/// `bytes` is either a list of byte values or a hex string such as
+/// `"DEADBEEF"` (optional `0x` prefix; whitespace is ignored).
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct MemoryEqualsAssertion {
+    pub address: u64,
+    #[serde(deserialize_with = "deserialize_bytes")]
+    pub bytes: Vec<u8>,
+}
+
+fn deserialize_bytes<'de, D>(deserializer: D) -> std::result::Result<Vec<u8>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Bytes {
+        List(Vec<u8>),
+        Hex(String),
+    }
+
+    match Bytes::deserialize(deserializer)? {
+        Bytes::List(bytes) => Ok(bytes),
+        Bytes::Hex(hex) => parse_hex_bytes(&hex).map_err(serde::de::Error::custom),
+    }
+}
+
+/// Decode a hex byte string such as `"DEADBEEF"` or `"0xde ad be ef"`.
+pub fn parse_hex_bytes(hex: &str) -> Result<Vec<u8>> {
+    let hex = hex.trim();
+    let hex = hex
+        .strip_prefix("0x")
+        .or_else(|| hex.strip_prefix("0X"))
+        .unwrap_or(hex);
+    let digits: Vec<u8> = hex.bytes().filter(|b| !b.is_ascii_whitespace()).collect();
+    if !digits.len().is_multiple_of(2) {
+        anyhow::bail!("Hex string '{}' has an odd number of digits", hex);
+    }
+    digits
+        .chunks(2)
+        .map(|pair| {
+            std::str::from_utf8(pair)
+                .ok()
+                .and_then(|p| u8::from_str_radix(p, 16).ok())
+                .with_context(|| format!("Invalid hex string '{}'", hex))
+        })
+        .collect()
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(untagged)]
 pub enum TestAssertion {
     UartContains(UartContainsAssertion),
     UartRegex(UartRegexAssertion),
     ExpectedStopReason(StopReasonAssertion),
+    ReachedSymbol(ReachedSymbolAssertion),
+    RegisterEquals(RegisterEqualsAssertion),
+    MemoryEquals(MemoryEqualsAssertion),
+    RegistersMatchFile(RegistersMatchFileAssertion),
+}
+
+impl TestAssertion {
+    /// Assertion keys accepted in test scripts.
+    pub const KINDS: &'static [&'static str] = &[
+        "uart_contains",
+        "uart_regex",
+        "expected_stop_reason",
+        "reached_symbol",
+        "register",
+        "address",
+        "registers_match_file",
+    ];
+}
+
+/// Operations the firmware under test may not perform. The run stops with
+/// `policy_violation` at the first one.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct TestPolicy {
+    /// Address ranges the CPU may not write, e.g. flash.
+    #[serde(default)]
+    pub deny_writes: Vec<AddressRange>,
+    /// Mnemonics, as in `--trace-file` records (e.g. `cpsid`), that may not
+    /// execute.
+    #[serde(default)]
+    pub deny_instructions: Vec<String>,
+}
+
+/// `size` bytes starting at `start`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct AddressRange {
+    pub start: u64,
+    pub size: u64,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -150,6 +365,8 @@ pub struct TestScript {
     pub limits: TestLimits,
     #[serde(default)]
     pub assertions: Vec<TestAssertion>,
+    #[serde(default)]
+    pub policy: TestPolicy,
 }
 
 impl TestScript {
@@ -178,6 +395,115 @@ impl TestScript {
             anyhow::bail!("Limit 'max_steps' must be greater than zero");
         }
 
+        for assertion in &self.assertions {
+            match assertion {
+                TestAssertion::RegisterEquals(a) if a.register_id().is_none() => anyhow::bail!(
+                    "Unknown register '{}' in assertion (expected r0-r15, sp, lr, pc or xpsr)",
+                    a.register
+                ),
+                TestAssertion::MemoryEquals(a) if a.bytes.is_empty() => anyhow::bail!(
+                    "Memory assertion at {:#x} must list at least one byte",
+                    a.address
+                ),
+                TestAssertion::RegistersMatchFile(a)
+                    if a.registers_match_file.trim().is_empty() =>
+                {
+                    anyhow::bail!("Assertion 'registers_match_file' path cannot be empty")
+                }
+                _ => {}
+            }
+        }
+
+        if self
+            .limits
+            .exit_codes
+            .contains_key(&StopReason::ConfigError)
+        {
+            anyhow::bail!("Limit 'exit_codes' cannot override 'config_error'");
+        }
+
+        if let Some(gate) = &self.limits.must_reach {
+            if gate.symbol_or_addr.trim().is_empty() {
+                anyhow::bail!("Limit 'must_reach.symbol_or_addr' cannot be empty");
+            }
+            if gate.by_step == 0 {
+                anyhow::bail!("Limit 'must_reach.by_step' must be greater than zero");
+            }
+        }
+
+        for range in &self.policy.deny_writes {
+            if range.size == 0 || range.start.checked_add(range.size).is_none() {
+                anyhow::bail!(
+                    "Policy 'deny_writes' range at {:#x} must have a non-zero size that fits the address space",
+                    range.start
+                );
+            }
+        }
+        if self
+            .policy
+            .deny_instructions
+            .iter()
+            .any(|m| m.trim().is_empty())
+        {
+            anyhow::bail!("Policy 'deny_instructions' entries cannot be empty");
+        }
+
+        Ok(())
+    }
+}
+
+/// External events applied to a running machine at given steps
+/// (`--signals`).
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct SignalScript {
+    #[serde(default)]
+    pub signals: Vec<ScheduledSignal>,
+}
+
+/// `event`, applied once `at_step` steps have executed.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ScheduledSignal {
+    pub at_step: u64,
+    #[serde(flatten)]
+    pub event: SignalEventConfig,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SignalEventConfig {
+    /// Drive input `pin` of the GPIO port with peripheral id `port`.
+    GpioInput { port: String, pin: u8, level: bool },
+    /// Queue the text `data` on the receive side of UART `name`.
+    UartRx { name: String, data: String },
+    /// Pend exception `num` (external IRQs start at 16).
+    PendIrq { num: u32 },
+}
+
+impl SignalScript {
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let f = std::fs::File::open(&path)
+            .with_context(|| format!("Failed to open signals file at {:?}", path.as_ref()))?;
+        let script: Self = serde_yaml::from_reader(f).context("Failed to parse signals YAML")?;
+        script.validate()?;
+        Ok(script)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        for signal in &self.signals {
+            match &signal.event {
+                SignalEventConfig::GpioInput { pin, .. } if *pin >= 16 => anyhow::bail!(
+                    "Signal at step {}: GPIO pin {} out of range (0-15)",
+                    signal.at_step,
+                    pin
+                ),
+                SignalEventConfig::UartRx { data, .. } if data.is_empty() => anyhow::bail!(
+                    "Signal at step {}: 'uart_rx' data cannot be empty",
+                    signal.at_step
+                ),
+                _ => {}
+            }
+        }
         Ok(())
     }
 }
@@ -322,6 +648,76 @@ limits:
         assert!(err.to_string().contains("Unsupported schema_version"));
     }
 
+    #[test]
+    fn test_policy_parses_and_rejects_empty_range() {
+        let yaml = r#"
+schema_version: "1.0"
+inputs:
+  firmware: "fw.elf"
+limits:
+  max_steps: 100
+policy:
+  deny_writes:
+    - start: 0x08000000
+      size: 0x10000
+  deny_instructions: [cpsid]
+"#;
+        let mut script: TestScript = serde_yaml::from_str(yaml).unwrap();
+        script.validate().unwrap();
+        assert_eq!(
+            script.policy.deny_writes,
+            vec![AddressRange {
+                start: 0x0800_0000,
+                size: 0x1_0000
+            }]
+        );
+        assert_eq!(script.policy.deny_instructions, vec!["cpsid"]);
+
+        script.policy.deny_writes[0].size = 0;
+        let err = script.validate().unwrap_err();
+        assert!(err.to_string().contains("deny_writes"));
+    }
+
+    #[test]
+    fn test_signal_script_parses_and_rejects_bad_pin() {
+        let yaml = r#"
+signals:
+  - at_step: 500
+    gpio_input: { port: gpioa, pin: 0, level: true }
+  - at_step: 1000
+    uart_rx: { name: uart1, data: "ping\n" }
+  - at_step: 1200
+    pend_irq: { num: 37 }
+"#;
+        let mut script: SignalScript = serde_yaml::from_str(yaml).unwrap();
+        script.validate().unwrap();
+        assert_eq!(script.signals.len(), 3);
+        assert_eq!(
+            script.signals[0].event,
+            SignalEventConfig::GpioInput {
+                port: "gpioa".to_string(),
+                pin: 0,
+                level: true
+            }
+        );
+        assert_eq!(
+            script.signals[1].event,
+            SignalEventConfig::UartRx {
+                name: "uart1".to_string(),
+                data: "ping\n".to_string()
+            }
+        );
+        assert_eq!(script.signals[2].at_step, 1200);
+
+        script.signals[0].event = SignalEventConfig::GpioInput {
+            port: "gpioa".to_string(),
+            pin: 16,
+            level: true,
+        };
+        let err = script.validate().unwrap_err();
+        assert!(err.to_string().contains("pin 16"));
+    }
+
     #[test]
     fn test_invalid_max_steps() {
         let yaml = r#"
@@ -350,6 +746,178 @@ limits:
         assert!(err.to_string().contains("firmware"));
     }
 
+    #[test]
+    fn test_reached_symbol_assertion() {
+        let yaml = r#"
+schema_version: "1.0"
+inputs:
+  firmware: "fw.elf"
+limits:
+  max_steps: 100
+assertions:
+  - reached_symbol: main
+  - reached_symbol: HardFault
+    negate: true
+"#;
+        let script: TestScript = serde_yaml::from_str(yaml).unwrap();
+        match &script.assertions[..] {
+            [TestAssertion::ReachedSymbol(main), TestAssertion::ReachedSymbol(fault)] => {
+                assert_eq!(main.reached_symbol, "main");
+                assert!(!main.negate);
+                assert_eq!(fault.reached_symbol, "HardFault");
+                assert!(fault.negate);
+            }
+            other => panic!("unexpected assertions: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_must_reach_limit() {
+        let yaml = r#"
+schema_version: "1.0"
+inputs:
+  firmware: "fw.elf"
+limits:
+  max_steps: 1000
+  must_reach:
+    symbol_or_addr: main
+    by_step: 500
+"#;
+        let script: TestScript = serde_yaml::from_str(yaml).unwrap();
+        assert!(script.validate().is_ok());
+        let gate = script.limits.must_reach.unwrap();
+        assert_eq!(gate.symbol_or_addr, "main");
+        assert_eq!(gate.by_step, 500);
+
+        let zero = yaml.replace("by_step: 500", "by_step: 0");
+        let script: TestScript = serde_yaml::from_str(&zero).unwrap();
+        let err = script.validate().unwrap_err();
+        assert!(err.to_string().contains("must_reach.by_step"));
+
+        let missing = yaml.replace("    by_step: 500\n", "");
+        assert!(serde_yaml::from_str::<TestScript>(&missing).is_err());
+    }
+
+    #[test]
+    fn test_exit_codes_limit() {
+        let yaml = r#"
+schema_version: "1.0"
+inputs:
+  firmware: "fw.elf"
+limits:
+  max_steps: 1000
+  exit_codes:
+    wall_time: 0
+    max_steps: 7
+"#;
+        let script: TestScript = serde_yaml::from_str(yaml).unwrap();
+        assert!(script.validate().is_ok());
+        assert_eq!(script.limits.exit_codes[&StopReason::WallTime], 0);
+        assert_eq!(script.limits.exit_codes[&StopReason::MaxSteps], 7);
+
+        let unknown = yaml.replace("wall_time: 0", "wall_clock: 0");
+        assert!(serde_yaml::from_str::<TestScript>(&unknown).is_err());
+
+        let out_of_range = yaml.replace("max_steps: 7", "max_steps: 256");
+        assert!(serde_yaml::from_str::<TestScript>(&out_of_range).is_err());
+
+        let config = yaml.replace("max_steps: 7", "config_error: 0");
+        let script: TestScript = serde_yaml::from_str(&config).unwrap();
+        let err = script.validate().unwrap_err();
+        assert!(err.to_string().contains("config_error"));
+    }
+
+    #[test]
+    fn test_register_equals_assertion() {
+        let yaml = r#"
+schema_version: "1.0"
+inputs:
+  firmware: "fw.elf"
+limits:
+  max_steps: 1000
+assertions:
+  - register: "r0"
+    value: 42
+  - register: xPSR
+    value: 0x01000000
+"#;
+        let script: TestScript = serde_yaml::from_str(yaml).unwrap();
+        assert!(script.validate().is_ok());
+        match script.assertions.as_slice() {
+            [TestAssertion::RegisterEquals(r0), TestAssertion::RegisterEquals(xpsr)] => {
+                assert_eq!((r0.register_id(), r0.value), (Some(0), 42));
+                assert_eq!((xpsr.register_id(), xpsr.value), (Some(16), 0x0100_0000));
+            }
+            other => panic!("unexpected assertions: {:?}", other),
+        }
+
+        let unknown = yaml.replace("\"r0\"", "r16");
+        let script: TestScript = serde_yaml::from_str(&unknown).unwrap();
+        let err = script.validate().unwrap_err();
+        assert!(err.to_string().contains("Unknown register 'r16'"));
+    }
+
+    #[test]
+    fn test_registers_match_file_assertion() {
+        let yaml = r#"
+schema_version: "1.0"
+inputs:
+  firmware: "fw.elf"
+limits:
+  max_steps: 1000
+assertions:
+  - registers_match_file: "golden/regs.json"
+"#;
+        let script: TestScript = serde_yaml::from_str(yaml).unwrap();
+        assert!(script.validate().is_ok());
+        match script.assertions.as_slice() {
+            [TestAssertion::RegistersMatchFile(a)] => {
+                assert_eq!(a.registers_match_file, "golden/regs.json")
+            }
+            other => panic!("unexpected assertions: {:?}", other),
+        }
+
+        let empty = yaml.replace("\"golden/regs.json\"", "\"\"");
+        let script: TestScript = serde_yaml::from_str(&empty).unwrap();
+        assert!(script.validate().is_err());
+    }
+
+    #[test]
+    fn test_memory_equals_assertion_bytes() {
+        let yaml = r#"
+schema_version: "1.0"
+inputs:
+  firmware: "fw.elf"
+limits:
+  max_steps: 1000
+assertions:
+  - address: 0x20000000
+    bytes: "0xDE AD be ef"
+  - address: 0x20000010
+    bytes: [1, 0x02]
+"#;
+        let script: TestScript = serde_yaml::from_str(yaml).unwrap();
+        assert!(script.validate().is_ok());
+        match script.assertions.as_slice() {
+            [TestAssertion::MemoryEquals(hex), TestAssertion::MemoryEquals(list)] => {
+                assert_eq!(hex.address, 0x2000_0000);
+                assert_eq!(hex.bytes, [0xDE, 0xAD, 0xBE, 0xEF]);
+                assert_eq!(list.bytes, [1, 2]);
+            }
+            other => panic!("unexpected assertions: {:?}", other),
+        }
+
+        assert!(parse_hex_bytes("abc").is_err());
+        assert!(parse_hex_bytes("zz").is_err());
+        let odd = yaml.replace("\"0xDE AD be ef\"", "\"DEA\"");
+        assert!(serde_yaml::from_str::<TestScript>(&odd).is_err());
+
+        let empty = yaml.replace("[1, 0x02]", "[]");
+        let script: TestScript = serde_yaml::from_str(&empty).unwrap();
+        let err = script.validate().unwrap_err();
+        assert!(err.to_string().contains("at least one byte"));
+    }
+
     fn write_temp_file(prefix: &str, contents: &str) -> std::path::PathBuf {
         let mut dir = std::env::temp_dir();
         dir.push("labwired-config-tests");
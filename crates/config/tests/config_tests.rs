@@ -4,7 +4,7 @@
 // This software is released under the MIT License.
 // See the LICENSE file in the project root for full license information.
 
-use labwired_config::ChipDescriptor;
+use labwired_config::{ChipDescriptor, MemoryPreload, SystemManifest};
 
 #[test]
 fn test_old_yaml_still_parses() {
@@ -53,3 +53,56 @@ peripherals:
     assert_eq!(desc.peripherals[0].size, Some("1KB".to_string()));
     assert_eq!(desc.peripherals[0].irq, Some(37));
 }
+
+#[test]
+fn test_system_manifest_preload_parses() {
+    let yaml = r#"
+name: "test-system"
+chip: "chip.yaml"
+preload:
+  - addr: 0x90000000
+    file: "fs.img"
+    file_offset: 0x200
+    len: 16
+  - addr: 0x20000000
+    file: "table.bin"
+"#;
+    let manifest: SystemManifest = serde_yaml::from_str(yaml).unwrap();
+    assert_eq!(
+        manifest.preload,
+        vec![
+            MemoryPreload {
+                addr: 0x9000_0000,
+                file: "fs.img".to_string(),
+                file_offset: 0x200,
+                len: Some(16),
+            },
+            MemoryPreload {
+                addr: 0x2000_0000,
+                file: "table.bin".to_string(),
+                file_offset: 0,
+                len: None,
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_preload_read_validates_window() {
+    let dir = std::env::temp_dir().join(format!("labwired-preload-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("data.bin"), b"0123456789").unwrap();
+
+    let preload = |file_offset, len| MemoryPreload {
+        addr: 0,
+        file: "data.bin".to_string(),
+        file_offset,
+        len,
+    };
+    assert_eq!(preload(2, Some(4)).read(&dir).unwrap(), b"2345");
+    assert_eq!(preload(7, None).read(&dir).unwrap(), b"789");
+    assert!(preload(8, Some(4)).read(&dir).is_err());
+    assert!(preload(11, None).read(&dir).is_err());
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
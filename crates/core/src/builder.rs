@@ -0,0 +1,139 @@
+// LabWired - Firmware Simulation Platform
+// Copyright (C) 2026 Andrii Shylenko
+//
+// This software is released under the MIT License.
+// See the LICENSE file in the project root for full license information.
+
+use crate::bus::SystemBus;
+use crate::cpu::{CortexM, RiscV};
+use crate::memory::ProgramImage;
+use crate::{Cpu, DebugControl, Machine, SimResult, SimulationError, SimulationObserver};
+use labwired_config::{ChipDescriptor, SystemManifest};
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::sync::{Arc, Mutex};
+
+/// Cores a `MachineBuilder` can wire up to a bus, mapping the core
+/// peripherals they need (see `configure_cortex_m` and `configure_riscv`).
+pub trait ConfigureCpu: Cpu + Sized {
+    fn configure(bus: &mut SystemBus) -> Self;
+}
+
+impl ConfigureCpu for CortexM {
+    fn configure(bus: &mut SystemBus) -> Self {
+        crate::system::cortex_m::configure_cortex_m(bus).0
+    }
+}
+
+impl ConfigureCpu for RiscV {
+    fn configure(bus: &mut SystemBus) -> Self {
+        crate::system::riscv::configure_riscv(bus)
+    }
+}
+
+/// Step-by-step construction of a `Machine`.
+///
+/// ```
+/// use labwired_core::cpu::CortexM;
+/// use labwired_core::{Arch, MachineBuilder, ProgramImage};
+///
+/// let image = ProgramImage::new(0x40, Arch::Arm)
+///     .with_segment(0x0, [0x00, 0x10, 0x00, 0x20, 0x41, 0x00, 0x00, 0x00])
+///     .with_segment(0x40, [0xFE, 0xE7]); // b .
+/// let machine = MachineBuilder::<CortexM>::new()
+///     .firmware(image)
+///     .breakpoint(0x40)
+///     .build()
+///     .unwrap();
+/// assert_eq!(machine.cpu.pc & !1, 0x40);
+/// ```
+///
+/// Without a chip the machine gets the default `SystemBus::new` memory
+/// map. Observers are attached before the firmware is loaded, so they see
+/// `on_simulation_start`.
+pub struct MachineBuilder<C: ConfigureCpu> {
+    chip: Option<ChipDescriptor>,
+    firmware: Option<ProgramImage>,
+    observers: Vec<Arc<dyn SimulationObserver>>,
+    uart_sink: Option<(Arc<Mutex<Vec<u8>>>, bool)>,
+    breakpoints: Vec<u32>,
+    cpu: PhantomData<C>,
+}
+
+impl<C: ConfigureCpu> Default for MachineBuilder<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C: ConfigureCpu> MachineBuilder<C> {
+    pub fn new() -> Self {
+        Self {
+            chip: None,
+            firmware: None,
+            observers: Vec::new(),
+            uart_sink: None,
+            breakpoints: Vec::new(),
+            cpu: PhantomData,
+        }
+    }
+
+    /// Build the bus from `chip` instead of the default memory map.
+    pub fn chip(mut self, chip: ChipDescriptor) -> Self {
+        self.chip = Some(chip);
+        self
+    }
+
+    /// Load `image` and reset into it as the last step of `build`.
+    pub fn firmware(mut self, image: ProgramImage) -> Self {
+        self.firmware = Some(image);
+        self
+    }
+
+    pub fn observer(mut self, observer: Arc<dyn SimulationObserver>) -> Self {
+        self.observers.push(observer);
+        self
+    }
+
+    /// Capture UART output in `sink`, as `SystemBus::attach_uart_tx_sink`.
+    pub fn uart_sink(mut self, sink: Arc<Mutex<Vec<u8>>>, echo_stdout: bool) -> Self {
+        self.uart_sink = Some((sink, echo_stdout));
+        self
+    }
+
+    pub fn breakpoint(mut self, addr: u32) -> Self {
+        self.breakpoints.push(addr);
+        self
+    }
+
+    pub fn build(self) -> SimResult<Machine<C>> {
+        let mut bus = match &self.chip {
+            Some(chip) => {
+                let manifest = SystemManifest {
+                    name: chip.name.clone(),
+                    chip: chip.name.clone(),
+                    memory_overrides: HashMap::new(),
+                    external_devices: Vec::new(),
+                    preload: Vec::new(),
+                };
+                SystemBus::from_config(chip, &manifest)
+                    .map_err(|e| SimulationError::Config(format!("{:#}", e)))?
+            }
+            None => SystemBus::new(),
+        };
+        if let Some((sink, echo_stdout)) = self.uart_sink {
+            bus.attach_uart_tx_sink(sink, echo_stdout);
+        }
+
+        let cpu = C::configure(&mut bus);
+        let mut machine = Machine::new(cpu, bus);
+        machine.observers = self.observers;
+        for addr in self.breakpoints {
+            machine.add_breakpoint(addr);
+        }
+        if let Some(image) = &self.firmware {
+            machine.load_program(image)?;
+        }
+        Ok(machine)
+    }
+}
@@ -5,10 +5,13 @@
 // See the LICENSE file in the project root for full license information.
 
 use crate::memory::LinearMemory;
+use crate::peripherals::itm::Itm;
 use crate::peripherals::nvic::NvicState;
 use crate::peripherals::uart::Uart;
 use crate::{Bus, DmaRequest, Peripheral, SimResult, SimulationError};
 use labwired_config::{parse_size, ChipDescriptor, SystemManifest};
+use std::cell::Cell;
+use std::collections::{HashMap, VecDeque};
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use std::sync::Mutex;
@@ -21,11 +24,84 @@ pub struct PeripheralEntry {
     pub dev: Box<dyn Peripheral>,
 }
 
+/// What backs a region of the address space.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemoryRegionKind {
+    Flash,
+    Ram,
+    Mmio,
+}
+
+/// Geometry of one mapped region, as reported by `SystemBus::memory_regions`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryRegion {
+    pub name: String,
+    pub base: u64,
+    pub size: u64,
+    pub kind: MemoryRegionKind,
+}
+
+/// IRQ wiring of one peripheral, as reported by `SystemBus::irq_map`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IrqMapping {
+    pub name: String,
+    pub base: u64,
+    /// Exception number raised by the peripheral (external IRQs start at 16).
+    pub irq: Option<u32>,
+    /// NVIC enable bit for external IRQs; `None` for core exceptions,
+    /// peripherals without an IRQ, or a bus without an NVIC.
+    pub nvic_enabled: Option<bool>,
+}
+
+/// What the CPU was doing when an access faulted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FaultKind {
+    /// Reading the instruction at the PC.
+    InstructionFetch,
+    /// Any other access: loads and stores, vector reads, exception stacking.
+    DataAccess,
+}
+
+/// The most recent faulting bus access, as reported by `SystemBus::last_fault`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FaultInfo {
+    pub addr: u64,
+    pub kind: FaultKind,
+    pub is_write: bool,
+}
+
+impl std::fmt::Display for FaultInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let access = match (self.kind, self.is_write) {
+            (FaultKind::InstructionFetch, _) => "instruction fetch",
+            (FaultKind::DataAccess, false) => "data read",
+            (FaultKind::DataAccess, true) => "data write",
+        };
+        write!(f, "{} at {:#x}", access, self.addr)
+    }
+}
+
+/// Peripheral `type` values accepted in chip descriptors by `SystemBus::from_config`.
+pub const PERIPHERAL_TYPES: &[&str] = &[
+    "uart", "systick", "gpio", "rcc", "timer", "i2c", "spi", "exti", "afio", "dma", "file",
+];
+
 pub struct SystemBus {
     pub flash: LinearMemory,
     pub ram: LinearMemory,
     pub peripherals: Vec<PeripheralEntry>,
     pub nvic: Option<Arc<NvicState>>,
+    /// The chip's core has an FPU; `configure_cortex_m` fits one.
+    pub fpu: bool,
+    /// Data copied in by `preload`, kept so a clearing reset can re-apply it.
+    pub(crate) preloads: Vec<crate::memory::Segment>,
+    /// Sink given to `attach_uart_tx_sink`, and whether it echoes, so that
+    /// peripherals fitted afterwards (the ITM) capture into it too.
+    pub(crate) tx_sink: Option<(Arc<Mutex<Vec<u8>>>, bool)>,
+    last_fault: Cell<Option<FaultInfo>>,
+    /// Address of the instruction being executed, set by `Machine::step` so
+    /// faults on it are classified as fetches.
+    fetch_addr: Cell<Option<u64>>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -154,13 +230,111 @@ impl SystemBus {
                 },
             ],
             nvic: None,
+            fpu: false,
+            preloads: Vec::new(),
+            tx_sink: None,
+            last_fault: Cell::new(None),
+            fetch_addr: Cell::new(None),
         }
     }
 
-    /// Attach a UART TX capture sink to any UART peripherals on this bus.
+    /// Peripheral to IRQ wiring, in bus order, with the current NVIC enable state.
+    pub fn irq_map(&self) -> Vec<IrqMapping> {
+        self.peripherals
+            .iter()
+            .map(|p| {
+                let nvic_enabled = match (p.irq, &self.nvic) {
+                    (Some(irq), Some(nvic)) if irq >= 16 => {
+                        let n = irq - 16;
+                        nvic.iser
+                            .get((n / 32) as usize)
+                            .map(|iser| iser.load(Ordering::SeqCst) & (1 << (n % 32)) != 0)
+                    }
+                    _ => None,
+                };
+                IrqMapping {
+                    name: p.name.clone(),
+                    base: p.base,
+                    irq: p.irq,
+                    nvic_enabled,
+                }
+            })
+            .collect()
+    }
+
+    /// Flash, RAM and peripheral regions, in bus decode order.
+    pub fn memory_regions(&self) -> Vec<MemoryRegion> {
+        let mut regions = vec![
+            MemoryRegion {
+                name: "flash".to_string(),
+                base: self.flash.base_addr,
+                size: self.flash.data.len() as u64,
+                kind: MemoryRegionKind::Flash,
+            },
+            MemoryRegion {
+                name: "ram".to_string(),
+                base: self.ram.base_addr,
+                size: self.ram.data.len() as u64,
+                kind: MemoryRegionKind::Ram,
+            },
+        ];
+        regions.extend(self.peripherals.iter().map(|p| MemoryRegion {
+            name: p.name.clone(),
+            base: p.base,
+            size: p.size,
+            kind: MemoryRegionKind::Mmio,
+        }));
+        regions
+    }
+
+    /// The peripheral named `name`, if it is a `T`.
+    pub fn peripheral_mut<T: 'static>(&mut self, name: &str) -> Option<&mut T> {
+        self.peripherals
+            .iter_mut()
+            .find(|p| p.name == name)?
+            .dev
+            .as_any_mut()?
+            .downcast_mut::<T>()
+    }
+
+    /// Attach a UART TX capture sink to any UART peripherals on this bus,
+    /// and to the ITM, whose stimulus-port output is captured alongside.
     ///
     /// When `echo_stdout` is false, UART writes will no longer be printed to stdout.
     pub fn attach_uart_tx_sink(&mut self, sink: Arc<Mutex<Vec<u8>>>, echo_stdout: bool) {
+        self.tx_sink = Some((sink.clone(), echo_stdout));
+        for p in &mut self.peripherals {
+            let Some(any) = p.dev.as_any_mut() else {
+                continue;
+            };
+            if let Some(uart) = any.downcast_mut::<Uart>() {
+                uart.set_sink(Some(sink.clone()), echo_stdout);
+            } else if let Some(itm) = any.downcast_mut::<Itm>() {
+                itm.set_sink(Some(sink.clone()), echo_stdout);
+            }
+        }
+    }
+
+    /// Stop UART and ITM output from being echoed to stdout, keeping any
+    /// attached capture sink, e.g. when stdout carries a debugger protocol.
+    pub fn disable_stdout_echo(&mut self) {
+        if let Some((_, echo_stdout)) = &mut self.tx_sink {
+            *echo_stdout = false;
+        }
+        for p in &mut self.peripherals {
+            let Some(any) = p.dev.as_any_mut() else {
+                continue;
+            };
+            if let Some(uart) = any.downcast_mut::<Uart>() {
+                uart.set_echo_stdout(false);
+            } else if let Some(itm) = any.downcast_mut::<Itm>() {
+                itm.set_echo_stdout(false);
+            }
+        }
+    }
+
+    /// Attach a host-fed RX FIFO to any UART peripherals on this bus.
+    pub fn attach_uart_rx_source(&mut self, rx: Arc<Mutex<VecDeque<u8>>>) {
         for p in &mut self.peripherals {
             let Some(any) = p.dev.as_any_mut() else {
                 continue;
@@ -168,33 +342,148 @@ impl SystemBus {
             let Some(uart) = any.downcast_mut::<Uart>() else {
                 continue;
             };
-            uart.set_sink(Some(sink.clone()), echo_stdout);
+            uart.set_rx_source(Some(rx.clone()));
+        }
+    }
+
+    /// Map `dev` at `base..base + size` under `name`, optionally wired to
+    /// `irq`. Names must be unique, since lookups such as `peripheral_mut`
+    /// and `remove_peripheral` go by name, and the range must not overlap
+    /// RAM, flash or another peripheral.
+    pub fn add_peripheral(
+        &mut self,
+        name: &str,
+        base: u64,
+        size: u64,
+        irq: Option<u32>,
+        dev: Box<dyn Peripheral>,
+    ) -> anyhow::Result<()> {
+        if self.peripherals.iter().any(|p| p.name == name) {
+            anyhow::bail!("a peripheral named '{}' is already mapped", name);
+        }
+        let overlaps = |start: u64, len: u64| {
+            start < base.saturating_add(size) && base < start.saturating_add(len)
+        };
+        let memories = [("RAM", &self.ram), ("flash", &self.flash)];
+        if let Some((memory, _)) = memories
+            .iter()
+            .find(|(_, m)| overlaps(m.base_addr, m.data.len() as u64))
+        {
+            anyhow::bail!("'{}' at {:#x}+{:#x} overlaps {}", name, base, size, memory);
         }
+        if let Some(other) = self.peripherals.iter().find(|p| overlaps(p.base, p.size)) {
+            anyhow::bail!(
+                "'{}' at {:#x}+{:#x} overlaps peripheral '{}'",
+                name,
+                base,
+                size,
+                other.name
+            );
+        }
+        self.peripherals.push(PeripheralEntry {
+            name: name.to_string(),
+            base,
+            size,
+            irq,
+            dev,
+        });
+        Ok(())
+    }
+
+    /// Unmap the peripheral named `name`, returning its device.
+    pub fn remove_peripheral(&mut self, name: &str) -> Option<Box<dyn Peripheral>> {
+        let index = self.peripherals.iter().position(|p| p.name == name)?;
+        Some(self.peripherals.remove(index).dev)
     }
 
-    pub fn from_config(chip: &ChipDescriptor, _manifest: &SystemManifest) -> anyhow::Result<Self> {
-        let flash_size = parse_size(&chip.flash.size)?;
-        let ram_size = parse_size(&chip.ram.size)?;
+    /// Apply `config` to the peripheral named `name` without restarting the machine.
+    pub fn reconfigure_peripheral(
+        &mut self,
+        name: &str,
+        config: &HashMap<String, serde_json::Value>,
+    ) -> anyhow::Result<()> {
+        let entry = self
+            .peripherals
+            .iter_mut()
+            .find(|p| p.name == name)
+            .ok_or_else(|| anyhow::anyhow!("no peripheral named '{}'", name))?;
+        entry
+            .dev
+            .reconfigure(config)
+            .map_err(|e| e.context(format!("failed to reconfigure '{}'", name)))
+    }
+
+    pub fn from_config(chip: &ChipDescriptor, manifest: &SystemManifest) -> anyhow::Result<Self> {
+        let mut flash_size = parse_size(&chip.flash.size)?;
+        let mut ram_size = parse_size(&chip.ram.size)?;
+
+        // The system manifest can resize the chip's memories (e.g. a part
+        // variant with more RAM); bases stay as the chip defines them.
+        for (region, size) in &manifest.memory_overrides {
+            let size = parse_size(size)
+                .map_err(|e| e.context(format!("Invalid memory_overrides.{}", region)))?;
+            match region.as_str() {
+                "flash" => flash_size = size,
+                "ram" => ram_size = size,
+                other => anyhow::bail!(
+                    "Unknown memory_overrides key '{}' (expected 'flash' or 'ram')",
+                    other
+                ),
+            }
+        }
 
         let mut bus = Self {
             flash: LinearMemory::new(flash_size as usize, chip.flash.base),
             ram: LinearMemory::new(ram_size as usize, chip.ram.base),
             peripherals: Vec::new(),
             nvic: None,
+            fpu: chip.fpu,
+            preloads: Vec::new(),
+            tx_sink: None,
+            last_fault: Cell::new(None),
+            fetch_addr: Cell::new(None),
         };
 
         for p_cfg in &chip.peripherals {
+            // Window size when the descriptor gives none
+            let mut default_size = 0x1000; // 4KB page
             let dev: Box<dyn Peripheral> = match p_cfg.r#type.as_str() {
-                "uart" => Box::new(crate::peripherals::uart::Uart::new()),
+                "uart" => Box::new(crate::peripherals::uart::Uart::with_config(&p_cfg.config)),
                 "systick" => Box::new(crate::peripherals::systick::Systick::new()),
                 "gpio" => Box::new(crate::peripherals::gpio::GpioPort::new()),
-                "rcc" => Box::new(crate::peripherals::rcc::Rcc::new()),
-                "timer" => Box::new(crate::peripherals::timer::Timer::new()),
+                "rcc" => {
+                    let ticks = |key: &str| {
+                        p_cfg
+                            .config
+                            .get(key)
+                            .and_then(|v| v.as_u64())
+                            .map(|v| v.min(u32::MAX as u64) as u32)
+                    };
+                    let mut timing = crate::peripherals::rcc::RccTiming::default();
+                    if let Some(t) = ticks("hsi_startup_ticks") {
+                        timing.hsi_startup_ticks = t;
+                    }
+                    if let Some(t) = ticks("hse_startup_ticks") {
+                        timing.hse_startup_ticks = t;
+                    }
+                    if let Some(t) = ticks("pll_lock_ticks") {
+                        timing.pll_lock_ticks = t;
+                    }
+                    Box::new(crate::peripherals::rcc::Rcc::with_timing(timing))
+                }
+                "timer" => Box::new(crate::peripherals::timer::Timer::with_config(&p_cfg.config)),
                 "i2c" => Box::new(crate::peripherals::i2c::I2c::new()),
                 "spi" => Box::new(crate::peripherals::spi::Spi::new()),
                 "exti" => Box::new(crate::peripherals::exti::Exti::new()),
                 "afio" => Box::new(crate::peripherals::afio::Afio::new()),
                 "dma" => Box::new(crate::peripherals::dma::Dma1::new()),
+                "file" => {
+                    let mem =
+                        crate::peripherals::file_memory::FileMemory::with_config(&p_cfg.config)
+                            .map_err(|e| e.context(format!("Invalid peripheral '{}'", p_cfg.id)))?;
+                    default_size = mem.len() as u64;
+                    Box::new(mem)
+                }
                 other => {
                     tracing::warn!(
                         "Unsupported peripheral type '{}' for id '{}'; skipping",
@@ -206,7 +495,7 @@ impl SystemBus {
             };
 
             let mut dev = dev;
-            for ext in &_manifest.external_devices {
+            for ext in &manifest.external_devices {
                 if ext.connection == p_cfg.id {
                     tracing::info!("Stubbing {} on {}", ext.id, p_cfg.id);
                     // For now, if it's a stub, we replace it or wrap it?
@@ -220,16 +509,13 @@ impl SystemBus {
             let size = if let Some(size) = &p_cfg.size {
                 parse_size(size)?
             } else {
-                0x1000 // Default 4KB page
+                default_size
             };
 
-            let irq = if let Some(irq) = p_cfg.irq {
-                Some(irq)
-            } else if p_cfg.id == "systick" {
-                Some(15)
-            } else {
-                None
-            };
+            // SysTick is always exception 15, whatever the descriptor calls it
+            let irq = p_cfg
+                .irq
+                .or_else(|| (p_cfg.r#type == "systick").then_some(15));
 
             bus.peripherals.push(PeripheralEntry {
                 name: p_cfg.id.clone(),
@@ -243,6 +529,25 @@ impl SystemBus {
         Ok(bus)
     }
 
+    /// Copy `data` into flash or RAM at `addr`, before firmware is loaded.
+    /// The whole range must fit in one region.
+    pub fn preload(&mut self, addr: u64, data: &[u8]) -> anyhow::Result<()> {
+        let segment = crate::memory::Segment {
+            start_addr: addr,
+            data: data.to_vec(),
+            mem_size: data.len() as u64,
+        };
+        if self.flash.load_from_segment(&segment) || self.ram.load_from_segment(&segment) {
+            self.preloads.push(segment);
+            return Ok(());
+        }
+        anyhow::bail!(
+            "Preload {:#x}+{:#x} does not fit in flash or RAM",
+            addr,
+            data.len()
+        )
+    }
+
     pub fn signal_nvic_irq(&self, irq: u32) {
         if let Some(nvic) = &self.nvic {
             if irq >= 16 {
@@ -267,7 +572,114 @@ impl SystemBus {
         Ok(b0 | (b1 << 8) | (b2 << 16) | (b3 << 24))
     }
 
+    /// Fill `buf` from `addr` for a debugger, copying straight out of RAM or
+    /// flash when the range lies in one of them and otherwise reading byte by
+    /// byte through `Peripheral::peek`, so no peripheral state changes.
+    pub fn read_bytes(&self, addr: u64, buf: &mut [u8]) -> SimResult<()> {
+        if self.ram.read_slice(addr, buf) || self.flash.read_slice(addr, buf) {
+            return Ok(());
+        }
+        for (i, byte) in buf.iter_mut().enumerate() {
+            *byte = self.peek_u8(addr + i as u64)?;
+        }
+        Ok(())
+    }
+
+    /// The byte at `addr` as `Peripheral::peek` sees it.
+    pub fn peek_u8(&self, addr: u64) -> SimResult<u8> {
+        if let Some(val) = self.ram.read_u8(addr) {
+            return Ok(val);
+        }
+        if let Some(val) = self.flash.read_u8(addr) {
+            return Ok(val);
+        }
+        for p in &self.peripherals {
+            if addr >= p.base && addr < p.base + p.size {
+                return p.dev.peek(addr - p.base);
+            }
+        }
+        Err(SimulationError::MemoryViolation(addr))
+    }
+
+    /// Write `data` to `addr`, the counterpart of `read_bytes`. Outside RAM
+    /// and flash, aligned words go out as word writes so that a debugger
+    /// writing a register acts on it once, as a CPU store would.
+    pub fn write_bytes(&mut self, addr: u64, data: &[u8]) -> SimResult<()> {
+        if self.ram.write_slice(addr, data) || self.flash.write_slice(addr, data) {
+            return Ok(());
+        }
+        let mut i = 0;
+        while i < data.len() {
+            let at = addr + i as u64;
+            match data.get(i..i + 4) {
+                Some(word) if at.is_multiple_of(4) => {
+                    let word = u32::from_le_bytes([word[0], word[1], word[2], word[3]]);
+                    self.write_u32(at, word)?;
+                    i += 4;
+                }
+                _ => {
+                    self.write_u8(at, data[i])?;
+                    i += 1;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Peripheral mapping all of `[addr, addr + len)`, for accesses that are
+    /// aligned to `len` and not backed by RAM or flash.
+    fn wide_access_target(&mut self, addr: u64, len: u64) -> Option<&mut PeripheralEntry> {
+        if !addr.is_multiple_of(len)
+            || self.ram.read_u8(addr).is_some()
+            || self.flash.read_u8(addr).is_some()
+        {
+            return None;
+        }
+        self.peripherals
+            .iter_mut()
+            .find(|p| addr >= p.base && addr + len <= p.base + p.size)
+    }
+
+    /// The most recent access that failed with `MemoryViolation`, kept until
+    /// the next fault or `clear_last_fault`.
+    pub fn last_fault(&self) -> Option<FaultInfo> {
+        self.last_fault.get()
+    }
+
+    pub fn clear_last_fault(&self) {
+        self.last_fault.set(None);
+    }
+
+    pub(crate) fn set_fetch_addr(&self, addr: Option<u64>) {
+        self.fetch_addr.set(addr);
+    }
+
+    /// Record `res` as the last fault if it is a memory violation.
+    fn track<T>(&self, res: SimResult<T>, addr: u64, is_write: bool) -> SimResult<T> {
+        if let Err(SimulationError::MemoryViolation(_)) = res {
+            let fetch = self
+                .fetch_addr
+                .get()
+                .is_some_and(|pc| !is_write && (pc..pc + 4).contains(&addr));
+            let kind = if fetch {
+                FaultKind::InstructionFetch
+            } else {
+                FaultKind::DataAccess
+            };
+            self.last_fault.set(Some(FaultInfo {
+                addr,
+                kind,
+                is_write,
+            }));
+        }
+        res
+    }
+
     pub fn write_u32(&mut self, addr: u64, value: u32) -> SimResult<()> {
+        if let Some(p) = self.wide_access_target(addr, 4) {
+            let res = p.dev.write_u32(addr - p.base, value);
+            return self.track(res, addr, true);
+        }
         self.write_u8(addr, (value & 0xFF) as u8)?;
         self.write_u8(addr + 1, ((value >> 8) & 0xFF) as u8)?;
         self.write_u8(addr + 2, ((value >> 16) & 0xFF) as u8)?;
@@ -282,6 +694,10 @@ impl SystemBus {
     }
 
     pub fn write_u16(&mut self, addr: u64, value: u16) -> SimResult<()> {
+        if let Some(p) = self.wide_access_target(addr, 2) {
+            let res = p.dev.write_u16(addr - p.base, value);
+            return self.track(res, addr, true);
+        }
         self.write_u8(addr, (value & 0xFF) as u8)?;
         self.write_u8(addr + 1, ((value >> 8) & 0xFF) as u8)?;
         Ok(())
@@ -304,46 +720,14 @@ impl SystemBus {
                 });
             }
 
+            for irq in res.irqs(p.irq) {
+                pend_irq(self.nvic.as_deref(), irq, &mut interrupts);
+            }
+
             // Collect DMA requests
             if !res.dma_requests.is_empty() {
                 dma_requests.extend(res.dma_requests);
             }
-
-            if res.irq {
-                if let Some(irq) = p.irq {
-                    if irq >= 16 {
-                        if let Some(nvic) = &self.nvic {
-                            let idx = ((irq - 16) / 32) as usize;
-                            let bit = (irq - 16) % 32;
-                            if idx < 8 {
-                                nvic.ispr[idx].fetch_or(1 << bit, Ordering::SeqCst);
-                            }
-                        } else {
-                            // No NVIC, pend legacy style
-                            interrupts.push(irq);
-                        }
-                    } else {
-                        // Core exceptions bypass NVIC ISPR/ISER
-                        interrupts.push(irq);
-                    }
-                }
-            }
-
-            for irq in res.explicit_irqs {
-                if let Some(nvic) = &self.nvic {
-                    if irq >= 16 {
-                        let idx = ((irq - 16) / 32) as usize;
-                        let bit = (irq - 16) % 32;
-                        if idx < 8 {
-                            nvic.ispr[idx].fetch_or(1 << bit, Ordering::SeqCst);
-                        }
-                    } else {
-                        interrupts.push(irq);
-                    }
-                } else {
-                    interrupts.push(irq);
-                }
-            }
         }
 
         // 2. Scan NVIC for all Pending & Enabled interrupts
@@ -363,6 +747,7 @@ impl SystemBus {
             }
         }
 
+        dedup_interrupts(&mut interrupts);
         (interrupts, costs, dma_requests)
     }
 
@@ -383,43 +768,12 @@ impl SystemBus {
                     cycles: res.cycles,
                 });
             }
+            for irq in res.irqs(p.irq) {
+                pend_irq(self.nvic.as_deref(), irq, &mut interrupts);
+            }
             if !res.dma_requests.is_empty() {
                 pending_dma.extend(res.dma_requests);
             }
-
-            if res.irq {
-                if let Some(irq) = p.irq {
-                    if irq >= 16 {
-                        if let Some(nvic) = &self.nvic {
-                            let idx = ((irq - 16) / 32) as usize;
-                            let bit = (irq - 16) % 32;
-                            if idx < 8 {
-                                nvic.ispr[idx].fetch_or(1 << bit, Ordering::SeqCst);
-                            }
-                        } else {
-                            interrupts.push(irq);
-                        }
-                    } else {
-                        interrupts.push(irq);
-                    }
-                }
-            }
-
-            for irq in res.explicit_irqs {
-                if let Some(nvic) = &self.nvic {
-                    if irq >= 16 {
-                        let idx = ((irq - 16) / 32) as usize;
-                        let bit = (irq - 16) % 32;
-                        if idx < 8 {
-                            nvic.ispr[idx].fetch_or(1 << bit, Ordering::SeqCst);
-                        }
-                    } else {
-                        interrupts.push(irq);
-                    }
-                } else {
-                    interrupts.push(irq);
-                }
-            }
         }
 
         // Phase 2: Execute DMA requests (this now has access to self.flash/ram via write_u8)
@@ -460,10 +814,34 @@ impl SystemBus {
             }
         }
 
+        dedup_interrupts(&mut interrupts);
         (interrupts, costs)
     }
 }
 
+/// Sort the interrupts raised in one tick and drop repeats, so each is pended
+/// once and in the same order on every run. Several peripherals (or
+/// a peripheral's line and explicit IRQs) may raise the same exception.
+fn dedup_interrupts(interrupts: &mut Vec<u32>) {
+    interrupts.sort_unstable();
+    interrupts.dedup();
+}
+
+/// Pend `irq` in the NVIC when one is attached. Core exceptions (< 16), and all
+/// IRQs on buses without an NVIC, are returned for the CPU to pend directly.
+fn pend_irq(nvic: Option<&NvicState>, irq: u32, interrupts: &mut Vec<u32>) {
+    match nvic {
+        Some(nvic) if irq >= 16 => {
+            let idx = ((irq - 16) / 32) as usize;
+            let bit = (irq - 16) % 32;
+            if idx < 8 {
+                nvic.ispr[idx].fetch_or(1 << bit, Ordering::SeqCst);
+            }
+        }
+        _ => interrupts.push(irq),
+    }
+}
+
 impl crate::Bus for SystemBus {
     fn read_u8(&self, addr: u64) -> SimResult<u8> {
         if let Some(val) = self.ram.read_u8(addr) {
@@ -476,11 +854,15 @@ impl crate::Bus for SystemBus {
         // Dynamic Peripherals
         for p in &self.peripherals {
             if addr >= p.base && addr < p.base + p.size {
-                return p.dev.read(addr - p.base);
+                return self.track(p.dev.read(addr - p.base), addr, false);
             }
         }
 
-        Err(SimulationError::MemoryViolation(addr))
+        self.track(Err(SimulationError::MemoryViolation(addr)), addr, false)
+    }
+
+    fn peek_u8(&self, addr: u64) -> SimResult<u8> {
+        SystemBus::peek_u8(self, addr)
     }
 
     fn write_u8(&mut self, addr: u64, value: u8) -> SimResult<()> {
@@ -492,13 +874,22 @@ impl crate::Bus for SystemBus {
         }
 
         // Dynamic Peripherals
+        let mut res = Err(SimulationError::MemoryViolation(addr));
         for p in &mut self.peripherals {
             if addr >= p.base && addr < p.base + p.size {
-                return p.dev.write(addr - p.base, value);
+                res = p.dev.write(addr - p.base, value);
+                break;
             }
         }
+        self.track(res, addr, true)
+    }
 
-        Err(SimulationError::MemoryViolation(addr))
+    fn write_u16(&mut self, addr: u64, value: u16) -> SimResult<()> {
+        SystemBus::write_u16(self, addr, value)
+    }
+
+    fn write_u32(&mut self, addr: u64, value: u32) -> SimResult<()> {
+        SystemBus::write_u32(self, addr, value)
     }
 
     fn tick_peripherals(&mut self) -> Vec<u32> {
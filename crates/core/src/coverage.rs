@@ -0,0 +1,230 @@
+// LabWired - Firmware Simulation Platform
+// Copyright (C) 2026 Andrii Shylenko
+//
+// This software is released under the MIT License.
+// See the LICENSE file in the project root for full license information.
+
+use crate::decoder::arm::{self, Instruction};
+use crate::{Arch, SimulationObserver};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Hit count of one basic block in a [`CoverageReport`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BlockHits {
+    pub start: u32,
+    /// Address of the block's last instruction.
+    pub end: u32,
+    pub hits: u64,
+}
+
+/// Outcomes of one conditional branch; an outcome with a zero count was
+/// never exercised.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BranchOutcomes {
+    pub addr: u32,
+    pub taken: u64,
+    pub not_taken: u64,
+}
+
+/// A source line in a [`LineCoverage`] summary.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct SourceLine {
+    pub file: String,
+    pub line: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LineCoverage {
+    pub covered: usize,
+    pub total: usize,
+    /// Lines with code that never ran, by file and line.
+    pub uncovered: Vec<SourceLine>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CoverageReport {
+    /// Addresses of every executed instruction, ascending.
+    pub executed: Vec<u32>,
+    pub basic_blocks: Vec<BlockHits>,
+    pub branches: Vec<BranchOutcomes>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lines: Option<LineCoverage>,
+}
+
+impl CoverageReport {
+    /// Summarise line coverage: `lines` are all source lines with code and
+    /// `line_of` maps an instruction address to its line.
+    pub fn with_lines(
+        mut self,
+        lines: impl IntoIterator<Item = SourceLine>,
+        line_of: impl Fn(u32) -> Option<SourceLine>,
+    ) -> Self {
+        let executed: BTreeSet<SourceLine> = self
+            .executed
+            .iter()
+            .filter_map(|&addr| line_of(addr))
+            .collect();
+        let mut all: BTreeSet<SourceLine> = lines.into_iter().collect();
+        all.extend(executed.iter().cloned());
+        let uncovered: Vec<SourceLine> = all.difference(&executed).cloned().collect();
+        self.lines = Some(LineCoverage {
+            covered: executed.len(),
+            total: all.len(),
+            uncovered,
+        });
+        self
+    }
+
+    pub fn write_json(&self, path: &Path) -> std::io::Result<()> {
+        let json = serde_json::to_string_pretty(self).unwrap_or_default();
+        std::fs::write(path, json + "\n")
+    }
+}
+
+#[derive(Debug, Default)]
+struct InstructionStats {
+    hits: u64,
+    size: u32,
+    conditional: bool,
+    taken: u64,
+    not_taken: u64,
+    /// Execution continued somewhere other than the next instruction.
+    left_by_jump: bool,
+    /// Execution arrived here from somewhere other than the previous instruction.
+    entered_by_jump: bool,
+}
+
+#[derive(Debug, Default)]
+struct CoverageState {
+    instructions: BTreeMap<u32, InstructionStats>,
+    last: Option<u32>,
+}
+
+/// Records which instructions executed, how often, and which way every
+/// conditional branch went.
+///
+/// A branch counts as taken when the next executed instruction is not the
+/// one after it, so an exception taken right after an untaken branch is
+/// recorded as the branch being taken. Conditional branches are recognised
+/// in Thumb (`B<cond>`, `B<cond>.W`, `CBZ`, `CBNZ`) and RISC-V (`BEQ`... and
+/// `C.BEQZ`/`C.BNEZ`) code.
+#[derive(Debug)]
+pub struct CoverageObserver {
+    arch: Arch,
+    state: Mutex<CoverageState>,
+}
+
+impl CoverageObserver {
+    pub fn new(arch: Arch) -> Self {
+        Self {
+            arch,
+            state: Mutex::new(CoverageState::default()),
+        }
+    }
+
+    /// Size in bytes of the instruction `opcode`, and whether it is a
+    /// conditional branch.
+    fn classify(&self, opcode: u32) -> (u32, bool) {
+        match self.arch {
+            Arch::RiscV if opcode & 3 == 3 => (4, opcode & 0x7F == 0x63),
+            // C.BEQZ and C.BNEZ: quadrant 1, funct3 110 and 111
+            Arch::RiscV => (2, opcode & 3 == 1 && (opcode >> 13) & 7 >= 6),
+            _ => match arm::decode_thumb_16(opcode as u16) {
+                // B<cond>.W (T3); cond 111x is the misc control space
+                Instruction::Prefix32(h1) => {
+                    let h2 = opcode >> 16;
+                    let t3 = h1 & 0xF800 == 0xF000 && h2 & 0xD000 == 0x8000;
+                    (4, t3 && (h1 >> 7) & 7 != 7)
+                }
+                Instruction::BranchCond { .. }
+                | Instruction::Cbz { .. }
+                | Instruction::Cbnz { .. } => (2, true),
+                _ => (2, false),
+            },
+        }
+    }
+
+    pub fn report(&self) -> CoverageReport {
+        let Ok(state) = self.state.lock() else {
+            return CoverageReport {
+                executed: Vec::new(),
+                basic_blocks: Vec::new(),
+                branches: Vec::new(),
+                lines: None,
+            };
+        };
+        let instructions = &state.instructions;
+
+        let mut basic_blocks: Vec<BlockHits> = Vec::new();
+        let mut next_addr = None;
+        let mut block_ended = true;
+        for (&addr, stats) in instructions {
+            let leader = block_ended || stats.entered_by_jump || next_addr != Some(addr);
+            match basic_blocks.last_mut() {
+                Some(block) if !leader => block.end = addr,
+                _ => basic_blocks.push(BlockHits {
+                    start: addr,
+                    end: addr,
+                    hits: stats.hits,
+                }),
+            }
+            block_ended = stats.conditional || stats.left_by_jump;
+            next_addr = Some(addr + stats.size);
+        }
+
+        CoverageReport {
+            executed: instructions.keys().copied().collect(),
+            basic_blocks,
+            branches: instructions
+                .iter()
+                .filter(|(_, stats)| stats.conditional)
+                .map(|(&addr, stats)| BranchOutcomes {
+                    addr,
+                    taken: stats.taken,
+                    not_taken: stats.not_taken,
+                })
+                .collect(),
+            lines: None,
+        }
+    }
+}
+
+impl SimulationObserver for CoverageObserver {
+    fn on_step_start(&self, pc: u32, opcode: u32) {
+        // The Thumb bit is not part of the instruction address
+        let pc = match self.arch {
+            Arch::RiscV => pc,
+            _ => pc & !1,
+        };
+        let (size, conditional) = self.classify(opcode);
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+        let mut jumped = false;
+        if let Some(last) = state.last {
+            if let Some(prev) = state.instructions.get_mut(&last) {
+                let sequential = pc == last.wrapping_add(prev.size);
+                if prev.conditional {
+                    if sequential {
+                        prev.not_taken += 1;
+                    } else {
+                        prev.taken += 1;
+                    }
+                }
+                if !sequential {
+                    prev.left_by_jump = true;
+                    jumped = true;
+                }
+            }
+        }
+        let stats = state.instructions.entry(pc).or_default();
+        stats.hits += 1;
+        stats.size = size;
+        stats.conditional = conditional;
+        stats.entered_by_jump |= jumped;
+        state.last = Some(pc);
+    }
+}
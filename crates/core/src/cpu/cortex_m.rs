@@ -4,15 +4,168 @@
 // This software is released under the MIT License.
 // See the LICENSE file in the project root for full license information.
 
-use crate::decoder::arm::{decode_thumb_16, Instruction};
-use crate::{Bus, Cpu, SimResult, SimulationObserver};
+use crate::cpu::fpu::{Fpu, FPCCR_ASPEN, FPCCR_LSPACT, FPCCR_LSPEN, FPCCR_RESET, FPSCR_NZCV};
+use crate::decoder::arm::{decode_thumb_16, Instruction, VfpInstruction};
+use crate::decoder::InstructionCategory;
+use crate::peripherals::nvic::NvicEvent;
+use crate::{Bus, Cpu, SimResult, SimulationError, SimulationObserver};
 use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Arc;
 
 // PSR Bits (Internal usage) - Omitted if unused
 const PSR_C: u32 = 1 << 29;
+/// EPSR Thumb bit. Cortex-M only executes Thumb code, so it is set out of
+/// reset and carried through the xPSR stacked on exception entry.
+pub const PSR_T: u32 = 1 << 24;
+
+const EXC_HARDFAULT: u32 = 3;
+const EXC_USAGEFAULT: u32 = 6;
+
+/// SHCSR.USGFAULTENA: UsageFault is taken as itself rather than escalated
+/// to HardFault.
+const SHCSR: u64 = 0xE000_ED24;
+/// CCR.UNALIGN_TRP: unaligned halfword and word accesses fault.
+const CCR: u64 = 0xE000_ED14;
+const CCR_UNALIGN_TRP: u32 = 1 << 3;
+const SHCSR_USGFAULTENA: u32 = 1 << 18;
+/// CFSR UsageFault status bit: a coprocessor instruction with no
+/// coprocessor to execute it.
+pub const CFSR_NOCP: u32 = 1 << 19;
+
+// IPSR field of xPSR: number of the currently active exception (0 = Thread mode)
+const IPSR_MASK: u32 = 0x1FF;
+
+/// EXC_RETURN for a return to Thread mode on the main stack, with a basic
+/// frame. Clearing `EXC_RETURN_BASIC_FRAME` selects the extended frame.
+const EXC_RETURN_THREAD: u32 = 0xFFFF_FFF9;
+const EXC_RETURN_BASIC_FRAME: u32 = 1 << 4;
+/// Exception frame sizes: R0-R3, R12, LR, PC and xPSR, and with the
+/// extended frame S0-S15, FPSCR and a reserved word after them.
+const BASIC_FRAME_SIZE: u32 = 0x20;
+const EXTENDED_FRAME_SIZE: u32 = 0x68;
+
+/// System handler priority bytes (SHPR1-3) for exceptions 4-15
+const SHPR_BASE: u64 = 0xE000_ED18;
+/// NVIC priority bytes (IPR0-59) for external interrupts (exception 16+)
+const NVIC_IPR_BASE: u64 = 0xE000_E400;
+
+/// Priority of `exception_num`; lower values are more urgent. Reset, NMI
+/// and HardFault have fixed negative priorities, the rest are read from
+/// SHPR/IPR (0 when the register is not mapped).
+fn exception_priority(bus: &dyn Bus, exception_num: u32) -> i16 {
+    let read = |addr: u64| bus.peek_u8(addr).map_or(0, i16::from);
+    match exception_num {
+        1 => -3,
+        2 => -2,
+        EXC_HARDFAULT => -1,
+        4..=15 => read(SHPR_BASE + (exception_num as u64 - 4)),
+        _ => read(NVIC_IPR_BASE + (exception_num as u64 - 16)),
+    }
+}
+
+/// What the core does when an instruction raises a bus fault.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum FaultBehavior {
+    /// Pend HardFault when the vector table provides a handler; propagate
+    /// the `MemoryViolation` otherwise.
+    #[default]
+    HardFault,
+    /// Always propagate the `MemoryViolation` to the caller.
+    Propagate,
+}
+
+/// Extra cycles an unaligned halfword/word access costs by default: the bus
+/// splits it into two transfers.
+pub const DEFAULT_UNALIGNED_ACCESS_CYCLES: u32 = 1;
+
+/// Approximate cycle cost per instruction class, reported to observers
+/// through `on_step_end`. The defaults follow the Cortex-M3 timing tables
+/// loosely and can be tuned per core via `CortexM::cycle_costs`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CycleCosts {
+    /// Data processing, moves, compares and not-taken branches.
+    pub alu: u32,
+    /// Single register loads.
+    pub load: u32,
+    /// Single register stores.
+    pub store: u32,
+    /// Per transferred register of LDM/STM/PUSH/POP, on top of one base cycle.
+    pub per_register: u32,
+    /// MUL/MLA/MLS and the long multiplies.
+    pub multiply: u32,
+    /// SDIV/UDIV (worst case).
+    pub divide: u32,
+    /// Extra cycles when an instruction writes the PC: pipeline refill.
+    pub branch_taken: u32,
+    /// Taking an exception: stacking and vector fetch.
+    pub exception_entry: u32,
+}
+
+impl Default for CycleCosts {
+    fn default() -> Self {
+        Self {
+            alu: 1,
+            load: 2,
+            store: 2,
+            per_register: 1,
+            multiply: 1,
+            divide: 12,
+            branch_taken: 2,
+            exception_entry: 12,
+        }
+    }
+}
+
+impl CycleCosts {
+    /// Base cost of a 16-bit instruction, before branch and alignment penalties.
+    fn thumb16(&self, instruction: &Instruction) -> u32 {
+        let transfer = |registers: u8, extra: bool| {
+            1 + (registers.count_ones() + extra as u32) * self.per_register
+        };
+        match *instruction {
+            Instruction::LdrImm { .. }
+            | Instruction::LdrReg { .. }
+            | Instruction::LdrLit { .. }
+            | Instruction::LdrSp { .. }
+            | Instruction::LdrbImm { .. }
+            | Instruction::LdrhImm { .. } => self.load,
+            Instruction::StrImm { .. }
+            | Instruction::StrSp { .. }
+            | Instruction::StrbImm { .. }
+            | Instruction::StrhImm { .. } => self.store,
+            Instruction::Push { registers, m } => transfer(registers, m),
+            Instruction::Pop { registers, p } => transfer(registers, p),
+            Instruction::Ldm { registers, .. } | Instruction::Stm { registers, .. } => {
+                transfer(registers, false)
+            }
+            Instruction::Mul { .. } => self.multiply,
+            _ => self.alu,
+        }
+    }
 
-#[derive(Debug, Default)]
+    /// Base cost of a 32-bit instruction, classified from its halfwords.
+    fn thumb32(&self, h1: u16, h2: u16) -> u32 {
+        if (h1 & 0xFE40) == 0xE800 {
+            // LDM/STM (T2), including PUSH.W/POP.W
+            1 + h2.count_ones() * self.per_register
+        } else if (h1 & 0xFE40) == 0xE840 || (h1 & 0xFE00) == 0xF800 {
+            // LDREX/STREX/LDRD/STRD/TBB and the single register transfers
+            if h1 & 0x0010 != 0 {
+                self.load
+            } else {
+                self.store
+            }
+        } else if (h1 & 0xFFD0) == 0xFB90 {
+            self.divide
+        } else if (h1 & 0xFF00) == 0xFB00 {
+            self.multiply
+        } else {
+            self.alu
+        }
+    }
+}
+
+#[derive(Debug)]
 pub struct CortexM {
     pub r0: u32,
     pub r1: u32,
@@ -31,9 +184,69 @@ pub struct CortexM {
     pub lr: u32, // R14
     pub pc: u32, // R15
     pub xpsr: u32,
+    /// xPSR loaded by `reset`; IPSR is cleared regardless. Defaults to `PSR_T`.
+    pub reset_xpsr: u32,
     pub pending_exceptions: u32, // Bitmask
     pub primask: bool,           // Interrupt mask (true = disabled)
     pub vtor: Arc<AtomicU32>,    // Shared Vector Table Offset Register
+    pub vectactive: Arc<AtomicU32>, // Active exception, mirrored into SCB ICSR
+    pub cfsr: Arc<AtomicU32>,       // Fault status, shared with SCB CFSR
+    pub fault_behavior: FaultBehavior,
+    /// Cycles added to a load/store whose address is not aligned to its size.
+    pub unaligned_access_cycles: u32,
+    /// Fail unaligned halfword/word loads and stores with `UnalignedAccess`
+    /// instead of performing them, whatever CCR.UNALIGN_TRP says. Off by
+    /// default, leaving it to firmware setting the CCR bit.
+    pub unaligned_trap: bool,
+    /// Raise a NOCP UsageFault on coprocessor (VFP) instructions, as an
+    /// FPU-less core does. When off they stop the run with
+    /// `UndefinedInstruction` like other unimplemented opcodes.
+    pub nocp_trap: bool,
+    /// FPU of an M4F/M7 core (chip descriptor `fpu: true`). Coprocessor
+    /// instructions execute on it; without one `nocp_trap` applies.
+    pub fpu: Option<Fpu>,
+    /// FPCCR and FPCAR, shared with the FP context registers at 0xE000_EF34.
+    pub fpccr: Arc<AtomicU32>,
+    pub fpcar: Arc<AtomicU32>,
+    pub cycle_costs: CycleCosts,
+}
+
+impl Default for CortexM {
+    fn default() -> Self {
+        Self {
+            r0: 0,
+            r1: 0,
+            r2: 0,
+            r3: 0,
+            r4: 0,
+            r5: 0,
+            r6: 0,
+            r7: 0,
+            r8: 0,
+            r9: 0,
+            r10: 0,
+            r11: 0,
+            r12: 0,
+            sp: 0,
+            lr: 0,
+            pc: 0,
+            xpsr: PSR_T,
+            reset_xpsr: PSR_T,
+            pending_exceptions: 0,
+            primask: false,
+            vtor: Arc::default(),
+            vectactive: Arc::default(),
+            cfsr: Arc::default(),
+            fault_behavior: FaultBehavior::default(),
+            unaligned_access_cycles: DEFAULT_UNALIGNED_ACCESS_CYCLES,
+            unaligned_trap: false,
+            nocp_trap: true,
+            fpu: None,
+            fpccr: Arc::new(AtomicU32::new(FPCCR_RESET)),
+            fpcar: Arc::default(),
+            cycle_costs: CycleCosts::default(),
+        }
+    }
 }
 
 impl CortexM {
@@ -53,6 +266,67 @@ impl CortexM {
         self.vtor = vtor;
     }
 
+    pub fn set_shared_vectactive(&mut self, vectactive: Arc<AtomicU32>) {
+        self.vectactive = vectactive;
+    }
+
+    pub fn set_shared_cfsr(&mut self, cfsr: Arc<AtomicU32>) {
+        self.cfsr = cfsr;
+    }
+
+    pub fn set_shared_fpccr(&mut self, fpccr: Arc<AtomicU32>) {
+        self.fpccr = fpccr;
+    }
+
+    pub fn set_shared_fpcar(&mut self, fpcar: Arc<AtomicU32>) {
+        self.fpcar = fpcar;
+    }
+
+    fn set_active_exception(&mut self, exception_num: u32) {
+        self.xpsr = (self.xpsr & !IPSR_MASK) | (exception_num & IPSR_MASK);
+        self.vectactive.store(exception_num & IPSR_MASK, Ordering::SeqCst);
+    }
+
+    /// The PC as an instruction operand: the address of the current
+    /// instruction plus 4, for 16- and 32-bit instructions alike. Branch
+    /// offsets are relative to this value.
+    fn read_pc_for_operand(&self) -> u32 {
+        (self.pc & !1).wrapping_add(4)
+    }
+
+    /// Base address of PC-relative literal loads, ADR and TBB/TBH:
+    /// `read_pc_for_operand` rounded down to a word boundary.
+    fn literal_base(&self) -> u32 {
+        self.read_pc_for_operand() & !3
+    }
+
+    /// Register `n` as a data operand, reading R15 as `read_pc_for_operand`.
+    fn read_operand(&self, n: u8) -> u32 {
+        if n == 15 {
+            self.read_pc_for_operand()
+        } else {
+            self.read_reg(n)
+        }
+    }
+
+    /// Extra cycles for a `size`-byte access at `addr`, or the
+    /// `UnalignedAccess` error when it is unaligned and trapping is on
+    /// (`unaligned_trap`, or CCR.UNALIGN_TRP set by firmware).
+    fn unaligned_penalty(&self, bus: &dyn Bus, addr: u32, size: u32) -> SimResult<u32> {
+        if addr.is_multiple_of(size) {
+            Ok(0)
+        } else if self.unaligned_trap
+            || bus
+                .peek_u32(CCR)
+                .is_ok_and(|ccr| ccr & CCR_UNALIGN_TRP != 0)
+        {
+            tracing::error!("Unaligned {}-byte access at {:#x}", size, addr);
+            Err(SimulationError::UnalignedAccess(addr as u64))
+        } else {
+            Ok(self.unaligned_access_cycles)
+        }
+    }
+
     fn read_reg(&self, n: u8) -> u32 {
         match n {
             0 => self.r0,
@@ -146,16 +420,321 @@ impl CortexM {
     fn branch_to(&mut self, addr: u32, bus: &mut dyn Bus) -> SimResult<()> {
         if (addr & 0xF000_0000) == 0xF000_0000 {
             // EXC_RETURN logic
-            self.exception_return(bus)?;
+            self.exception_return(bus, addr)?;
         } else {
             self.pc = addr & !1;
         }
         Ok(())
     }
 
-    fn exception_return(&mut self, bus: &mut dyn Bus) -> SimResult<()> {
+    /// Escalate a bus fault (or the failed stack push of a `StackOverflow`)
+    /// raised during `step` to HardFault when configured and a handler is
+    /// installed; otherwise hand the error back to the caller.
+    fn handle_fault(&mut self, bus: &dyn Bus, err: SimulationError) -> SimResult<()> {
+        let (SimulationError::MemoryViolation(addr) | SimulationError::StackOverflow(addr)) = err
+        else {
+            return Err(err);
+        };
+        if self.fault_behavior == FaultBehavior::Propagate {
+            return Err(err);
+        }
+
+        let vector_addr = self.vtor.load(Ordering::SeqCst) + EXC_HARDFAULT * 4;
+        let handler = bus.read_u32(vector_addr as u64).unwrap_or(0) & !1;
+        // No handler, or the handler is the faulting code itself (lockup).
+        if handler == 0 || handler == self.pc & !1 {
+            return Err(err);
+        }
+
+        tracing::warn!(
+            "{} at {:#x} (PC={:#x}), pending HardFault",
+            if matches!(err, SimulationError::StackOverflow(_)) {
+                "Stack overflow"
+            } else {
+                "Memory violation"
+            },
+            addr,
+            self.pc
+        );
+        self.set_exception_pending(EXC_HARDFAULT);
+        Ok(())
+    }
+
+    /// Record `status` in CFSR and pend UsageFault, or HardFault when
+    /// SHCSR does not enable it. Without a handler to take it the
+    /// instruction at the PC is reported as undefined.
+    fn usage_fault(&mut self, bus: &dyn Bus, status: u32) -> SimResult<()> {
+        self.cfsr.fetch_or(status, Ordering::SeqCst);
+        let enabled = bus
+            .peek_u32(SHCSR)
+            .is_ok_and(|shcsr| shcsr & SHCSR_USGFAULTENA != 0);
+        let exception_num = if enabled {
+            EXC_USAGEFAULT
+        } else {
+            EXC_HARDFAULT
+        };
+
+        let vector_addr = self.vtor.load(Ordering::SeqCst) + exception_num * 4;
+        let handler = bus.read_u32(vector_addr as u64).unwrap_or(0) & !1;
+        if handler == 0 || handler == self.pc & !1 {
+            return Err(SimulationError::UndefinedInstruction((self.pc & !1) as u64));
+        }
+
+        tracing::warn!(
+            "UsageFault (CFSR {:#x}) at PC={:#x}, pending exception {}",
+            status,
+            self.pc,
+            exception_num
+        );
+        self.set_exception_pending(exception_num);
+        Ok(())
+    }
+
+    fn fpu_mut(&mut self) -> &mut Fpu {
+        self.fpu.get_or_insert_with(Fpu::default)
+    }
+
+    /// Write S0-S15 and FPSCR to the FP part of an extended frame at `addr`.
+    fn save_fp_context(&mut self, bus: &mut dyn Bus, addr: u32) -> SimResult<()> {
+        let fpu = self.fpu_mut().clone();
+        for (i, val) in fpu.s[..16].iter().enumerate() {
+            push_word(bus, addr.wrapping_add(i as u32 * 4), *val)?;
+        }
+        push_word(bus, addr.wrapping_add(0x40), fpu.fpscr)
+    }
+
+    fn restore_fp_context(&mut self, bus: &mut dyn Bus, addr: u32) -> SimResult<()> {
+        for i in 0..16 {
+            let val = bus.read_u32(addr.wrapping_add(i * 4) as u64)?;
+            self.fpu_mut().s[i as usize] = val;
+        }
+        self.fpu_mut().fpscr = bus.read_u32(addr.wrapping_add(0x40) as u64)?;
+        Ok(())
+    }
+
+    /// Execute a VFP instruction on the FPU, returning its cycle cost.
+    /// Arithmetic is IEEE 754 single precision rounding to nearest; the
+    /// FPSCR rounding mode, flush-to-zero and exception flags are not
+    /// modelled.
+    fn execute_vfp(&mut self, bus: &mut dyn Bus, instruction: VfpInstruction) -> SimResult<u32> {
+        use VfpInstruction::*;
+        let mut cycles = self.cycle_costs.alu;
+        let fpccr = self.fpccr.load(Ordering::SeqCst);
+        if fpccr & FPCCR_LSPACT != 0 {
+            // First FP instruction since a lazily stacked exception entry:
+            // save the interrupted context into the space reserved for it.
+            let addr = self.fpcar.load(Ordering::SeqCst);
+            self.save_fp_context(bus, addr)?;
+            self.fpccr.fetch_and(!FPCCR_LSPACT, Ordering::SeqCst);
+        }
+        if fpccr & FPCCR_ASPEN != 0 {
+            self.fpu_mut().fpca = true;
+        }
+        match instruction {
+            Vadd { sd, sn, sm } => {
+                let fpu = self.fpu_mut();
+                fpu.write_s(sd, fpu.read_s(sn) + fpu.read_s(sm));
+            }
+            Vsub { sd, sn, sm } => {
+                let fpu = self.fpu_mut();
+                fpu.write_s(sd, fpu.read_s(sn) - fpu.read_s(sm));
+            }
+            Vmul { sd, sn, sm } => {
+                let fpu = self.fpu_mut();
+                fpu.write_s(sd, fpu.read_s(sn) * fpu.read_s(sm));
+            }
+            Vdiv { sd, sn, sm } => {
+                let fpu = self.fpu_mut();
+                fpu.write_s(sd, fpu.read_s(sn) / fpu.read_s(sm));
+            }
+            Vabs { sd, sm } => {
+                let fpu = self.fpu_mut();
+                fpu.s[sd as usize] = fpu.s[sm as usize] & !(1 << 31);
+            }
+            Vneg { sd, sm } => {
+                let fpu = self.fpu_mut();
+                fpu.s[sd as usize] = fpu.s[sm as usize] ^ (1 << 31);
+            }
+            Vsqrt { sd, sm } => {
+                let fpu = self.fpu_mut();
+                fpu.write_s(sd, fpu.read_s(sm).sqrt());
+            }
+            VmovReg { sd, sm } => {
+                let fpu = self.fpu_mut();
+                fpu.s[sd as usize] = fpu.s[sm as usize];
+            }
+            VmovImm { sd, imm } => self.fpu_mut().s[sd as usize] = imm,
+            VmovToCore { rt, sn } => {
+                let val = self.fpu_mut().s[sn as usize];
+                self.write_reg(rt, val);
+            }
+            VmovFromCore { sn, rt } => {
+                let val = self.read_reg(rt);
+                self.fpu_mut().s[sn as usize] = val;
+            }
+            Vldr {
+                vd,
+                rn,
+                offset,
+                double,
+            }
+            | Vstr {
+                vd,
+                rn,
+                offset,
+                double,
+            } => {
+                let base = if rn == 15 {
+                    self.literal_base()
+                } else {
+                    self.read_reg(rn)
+                };
+                let addr = base.wrapping_add(offset as u32);
+                let extra = self.unaligned_penalty(bus, addr, 4)?;
+                let words = if double { 2 } else { 1 };
+                let first = if double { vd as usize * 2 } else { vd as usize };
+                if matches!(instruction, Vldr { .. }) {
+                    for i in 0..words {
+                        let val = bus.read_u32(addr.wrapping_add(i * 4) as u64)?;
+                        self.fpu_mut().s[first + i as usize] = val;
+                    }
+                    cycles = self.cycle_costs.load + extra;
+                } else {
+                    for i in 0..words {
+                        let val = self.fpu_mut().s[first + i as usize];
+                        bus.write_u32(addr.wrapping_add(i * 4) as u64, val)?;
+                    }
+                    cycles = self.cycle_costs.store + extra;
+                }
+                if double {
+                    cycles += self.cycle_costs.per_register;
+                }
+            }
+            Vldm {
+                vd,
+                rn,
+                regs,
+                double,
+                db,
+                writeback,
+            }
+            | Vstm {
+                vd,
+                rn,
+                regs,
+                double,
+                db,
+                writeback,
+            } => {
+                let words = if double { regs as u32 * 2 } else { regs as u32 };
+                let first = if double { vd as usize * 2 } else { vd as usize };
+                let base = self.read_reg(rn);
+                let start = if db {
+                    base.wrapping_sub(words * 4)
+                } else {
+                    base
+                };
+                let load = matches!(instruction, Vldm { .. });
+                for i in 0..words {
+                    let addr = start.wrapping_add(i * 4);
+                    if load {
+                        let val = bus.read_u32(addr as u64)?;
+                        self.fpu_mut().s[first + i as usize] = val;
+                    } else {
+                        let val = self.fpu_mut().s[first + i as usize];
+                        if rn == 13 {
+                            push_word(bus, addr, val)?;
+                        } else {
+                            bus.write_u32(addr as u64, val)?;
+                        }
+                    }
+                }
+                if writeback {
+                    let end = if db {
+                        start
+                    } else {
+                        start.wrapping_add(words * 4)
+                    };
+                    self.write_reg(rn, end);
+                }
+                cycles = if load {
+                    self.cycle_costs.load
+                } else {
+                    self.cycle_costs.store
+                } + (words - 1) * self.cycle_costs.per_register;
+            }
+            VcvtToFloat { sd, sm, signed } => {
+                let fpu = self.fpu_mut();
+                let bits = fpu.s[sm as usize];
+                fpu.write_s(
+                    sd,
+                    if signed {
+                        bits as i32 as f32
+                    } else {
+                        bits as f32
+                    },
+                );
+            }
+            VcvtToInt {
+                sd,
+                sm,
+                signed,
+                round_zero,
+            } => {
+                let fpu = self.fpu_mut();
+                let val = fpu.read_s(sm);
+                let val = if round_zero {
+                    val
+                } else {
+                    val.round_ties_even()
+                };
+                // Out-of-range values saturate and NaN converts to 0
+                fpu.s[sd as usize] = if signed {
+                    val as i32 as u32
+                } else {
+                    val as u32
+                };
+            }
+            Vcmp { sd, sm } => {
+                let fpu = self.fpu_mut();
+                let b = sm.map_or(0.0, |sm| fpu.read_s(sm));
+                fpu.compare(fpu.read_s(sd), b);
+            }
+            Vmrs { rt } => {
+                let fpscr = self.fpu_mut().fpscr;
+                if rt == 15 {
+                    self.xpsr = (self.xpsr & !FPSCR_NZCV) | (fpscr & FPSCR_NZCV);
+                } else {
+                    self.write_reg(rt, fpscr);
+                }
+            }
+            Vmsr { rt } => {
+                let val = self.read_reg(rt);
+                self.fpu_mut().fpscr = val;
+            }
+        }
+        Ok(cycles)
+    }
+
+    fn exception_return(&mut self, bus: &mut dyn Bus, exc_return: u32) -> SimResult<()> {
         // Perform Unstacking
         let frame_ptr = self.sp;
+        let mut frame_size = BASIC_FRAME_SIZE;
+        if self.fpu.is_some() {
+            let extended = exc_return & EXC_RETURN_BASIC_FRAME == 0;
+            if extended {
+                let fpccr = self.fpccr.load(Ordering::SeqCst);
+                if fpccr & FPCCR_LSPACT != 0 {
+                    // The handler never used the FPU, so the registers
+                    // still hold the context the frame was reserved for.
+                    self.fpccr.store(fpccr & !FPCCR_LSPACT, Ordering::SeqCst);
+                } else {
+                    self.restore_fp_context(bus, frame_ptr + BASIC_FRAME_SIZE)?;
+                }
+                frame_size = EXTENDED_FRAME_SIZE;
+            }
+            self.fpu_mut().fpca = extended;
+        }
 
         self.r0 = bus.read_u32(frame_ptr as u64)?;
         self.r1 = bus.read_u32((frame_ptr + 4) as u64)?;
@@ -165,8 +744,9 @@ impl CortexM {
         self.lr = bus.read_u32((frame_ptr + 20) as u64)?;
         self.pc = bus.read_u32((frame_ptr + 24) as u64)?;
         self.xpsr = bus.read_u32((frame_ptr + 28) as u64)?;
+        self.vectactive.store(self.xpsr & IPSR_MASK, Ordering::SeqCst);
 
-        self.sp = frame_ptr + 32;
+        self.sp = frame_ptr + frame_size;
 
         tracing::info!("Exception return to {:#x}", self.pc);
         Ok(())
@@ -178,6 +758,13 @@ impl Cpu for CortexM {
         self.pc = 0x0000_0000;
         self.sp = 0x2000_0000;
         self.pending_exceptions = 0;
+        self.xpsr = self.reset_xpsr;
+        self.set_active_exception(0);
+        if let Some(fpu) = &mut self.fpu {
+            *fpu = Fpu::default();
+            self.fpccr.store(FPCCR_RESET, Ordering::SeqCst);
+            self.fpcar.store(0, Ordering::SeqCst);
+        }
 
         let vtor = self.vtor.load(Ordering::SeqCst) as u64;
         if let Ok(sp) = bus.read_u32(vtor) {
@@ -204,6 +791,13 @@ impl Cpu for CortexM {
             self.pending_exceptions |= 1 << exception_num;
         }
     }
+    fn exception_pending(&self) -> bool {
+        self.pending_exceptions != 0
+    }
+
+    fn register_count(&self) -> u8 {
+        17
+    }
 
     fn get_register(&self, id: u8) -> u32 {
         self.read_reg(id)
@@ -213,6 +807,20 @@ impl Cpu for CortexM {
         self.write_reg(id, val);
     }
 
+    fn registers(&self) -> [u32; 17] {
+        [
+            self.r0, self.r1, self.r2, self.r3, self.r4, self.r5, self.r6, self.r7, self.r8,
+            self.r9, self.r10, self.r11, self.r12, self.sp, self.lr, self.pc, self.xpsr,
+        ]
+    }
+
+    fn set_registers(&mut self, regs: &[u32; 17]) {
+        [
+            self.r0, self.r1, self.r2, self.r3, self.r4, self.r5, self.r6, self.r7, self.r8,
+            self.r9, self.r10, self.r11, self.r12, self.sp, self.lr, self.pc, self.xpsr,
+        ] = *regs;
+    }
+
     fn snapshot(&self) -> crate::snapshot::CpuSnapshot {
         crate::snapshot::CpuSnapshot::Arm(crate::snapshot::ArmCpuSnapshot {
             registers: vec![
@@ -223,9 +831,32 @@ impl Cpu for CortexM {
             primask: self.primask,
             pending_exceptions: self.pending_exceptions,
             vtor: self.vtor.load(Ordering::Relaxed),
+            fpu: self.fpu.clone(),
         })
     }
 
+    fn restore(&mut self, snapshot: &crate::snapshot::CpuSnapshot) -> anyhow::Result<()> {
+        let crate::snapshot::CpuSnapshot::Arm(s) = snapshot else {
+            anyhow::bail!("cannot restore a non-ARM CPU snapshot into a Cortex-M core");
+        };
+        if s.registers.len() != 16 {
+            anyhow::bail!("expected 16 ARM registers, got {}", s.registers.len());
+        }
+        for (id, val) in s.registers.iter().enumerate() {
+            self.write_reg(id as u8, *val);
+        }
+        self.xpsr = s.xpsr;
+        self.vectactive.store(s.xpsr & IPSR_MASK, Ordering::SeqCst);
+        self.primask = s.primask;
+        self.pending_exceptions = s.pending_exceptions;
+        self.vtor.store(s.vtor, Ordering::SeqCst);
+        // Whether there is an FPU is part of the core, not its state
+        if let Some(fpu) = &mut self.fpu {
+            *fpu = s.fpu.clone().unwrap_or_default();
+        }
+        Ok(())
+    }
+
     fn step(
         &mut self,
         bus: &mut dyn Bus,
@@ -234,28 +865,62 @@ impl Cpu for CortexM {
         static STEP_COUNT: AtomicU32 = AtomicU32::new(0);
         // Check for pending exceptions before executing instruction
         if self.pending_exceptions != 0 {
-            // Find highest priority exception (Simplified: highest bit)
-            let exception_num = 31 - self.pending_exceptions.leading_zeros();
+            // Most urgent priority first; equal priorities go to the lowest
+            // exception number.
+            let exception_num = (0..32)
+                .filter(|n| self.pending_exceptions & (1 << n) != 0)
+                .min_by_key(|&n| (exception_priority(bus, n), n))
+                .unwrap_or_default();
             self.pending_exceptions &= !(1 << exception_num);
 
             // Perform Stacking (Simplified)
+            let fp_frame = self.fpu.as_ref().is_some_and(|fpu| fpu.fpca);
+            let frame_size = if fp_frame {
+                EXTENDED_FRAME_SIZE
+            } else {
+                BASIC_FRAME_SIZE
+            };
             let sp = self.sp;
-            let frame_ptr = sp.wrapping_sub(32);
+            let frame_ptr = sp.wrapping_sub(frame_size);
 
             // Stack: R0, R1, R2, R3, R12, LR, PC, xPSR
-            let _ = bus.write_u32(frame_ptr as u64, self.r0);
-            let _ = bus.write_u32((frame_ptr + 4) as u64, self.r1);
-            let _ = bus.write_u32((frame_ptr + 8) as u64, self.r2);
-            let _ = bus.write_u32((frame_ptr + 12) as u64, self.r3);
-            let _ = bus.write_u32((frame_ptr + 16) as u64, self.r12);
-            let _ = bus.write_u32((frame_ptr + 20) as u64, self.lr);
-            let _ = bus.write_u32((frame_ptr + 24) as u64, self.pc);
-            let _ = bus.write_u32((frame_ptr + 28) as u64, self.xpsr);
+            let frame = [
+                self.r0, self.r1, self.r2, self.r3, self.r12, self.lr, self.pc, self.xpsr,
+            ];
+            let stacked = frame
+                .iter()
+                .zip((frame_ptr..).step_by(4))
+                .try_for_each(|(&val, addr)| push_word(bus, addr, val));
+            if let Err(e) = stacked {
+                // A fault while stacking for HardFault itself is a lockup;
+                // otherwise the HardFault is taken once this entry completes.
+                if exception_num == EXC_HARDFAULT {
+                    return Err(e);
+                }
+                self.handle_fault(bus, e)?;
+            }
 
             self.sp = frame_ptr;
 
             // EXC_RETURN: Thread Mode, MSP
-            self.lr = 0xFFFF_FFF9;
+            self.lr = EXC_RETURN_THREAD;
+            if fp_frame {
+                let fp_area = frame_ptr + BASIC_FRAME_SIZE;
+                let fpccr = self.fpccr.load(Ordering::SeqCst);
+                if fpccr & FPCCR_LSPEN != 0 {
+                    // Reserve the space; the first FP instruction fills it
+                    self.fpcar.store(fp_area, Ordering::SeqCst);
+                    self.fpccr.store(fpccr | FPCCR_LSPACT, Ordering::SeqCst);
+                } else if let Err(e) = self.save_fp_context(bus, fp_area) {
+                    if exception_num == EXC_HARDFAULT {
+                        return Err(e);
+                    }
+                    self.handle_fault(bus, e)?;
+                }
+                self.fpu_mut().fpca = false;
+                self.lr &= !EXC_RETURN_BASIC_FRAME;
+            }
+            self.set_active_exception(exception_num);
 
             // Jump to ISR handler
             let vtor = self.vtor.load(Ordering::SeqCst);
@@ -270,20 +935,46 @@ impl Cpu for CortexM {
                 );
             }
 
+            for observer in observers {
+                observer.on_nvic_change(exception_num, NvicEvent::Activate);
+                observer.on_step_end(self.cycle_costs.exception_entry);
+            }
             return Ok(());
         }
 
         // ... (existing logic)
+        // An exception return changes IPSR
+        let active = self.xpsr & IPSR_MASK;
+
         // Fetch 16-bit thumb instruction
         let fetch_pc = self.pc & !1;
-        let opcode = bus.read_u16(fetch_pc as u64)?;
-
-        for observer in observers {
-            observer.on_step_start(self.pc, opcode as u32);
-        }
+        let opcode = match bus.read_u16(fetch_pc as u64) {
+            Ok(opcode) => opcode,
+            Err(e) => return self.handle_fault(bus, e),
+        };
 
         // Decode
         let instruction = decode_thumb_16(opcode);
+        if !observers.is_empty() {
+            // 32-bit instructions are reported whole, first halfword low
+            let h2 = match instruction {
+                Instruction::Prefix32(_) => bus.read_u16((fetch_pc + 2) as u64).ok(),
+                _ => None,
+            };
+            let word = opcode as u32 | (h2.unwrap_or(0) as u32) << 16;
+            for observer in observers {
+                observer.on_step_start(self.pc, word);
+            }
+            let category = match instruction {
+                Instruction::Prefix32(h1) => h2.map(|h2| InstructionCategory::thumb_32(h1, h2)),
+                ref instruction => Some(InstructionCategory::thumb_16(instruction, opcode)),
+            };
+            if let Some(category) = category {
+                for observer in observers {
+                    observer.on_instruction(category);
+                }
+            }
+        }
 
         let count = STEP_COUNT.fetch_add(1, Ordering::SeqCst);
         if count.is_multiple_of(100000) {
@@ -291,6 +982,7 @@ impl Cpu for CortexM {
         }
 
         tracing::debug!(
+            target: crate::INSTRUCTION_TRACE_TARGET,
             "PC={:#x}, Opcode={:#04x}, Instr={:?}",
             self.pc,
             opcode,
@@ -299,7 +991,7 @@ impl Cpu for CortexM {
 
         // Execute
         let mut pc_increment = 2; // Default for 16-bit instruction
-        let mut cycles = 1;
+        let mut cycles = self.cycle_costs.thumb16(&instruction);
 
         match instruction {
             Instruction::Bfi { .. }
@@ -325,18 +1017,18 @@ impl Cpu for CortexM {
             // Control Flow
             Instruction::Cbz { rn, imm } => {
                 if self.read_reg(rn) == 0 {
-                    self.pc = self.pc.wrapping_add(4).wrapping_add(imm as u32);
+                    self.pc = self.read_pc_for_operand().wrapping_add(imm as u32);
                     pc_increment = 0;
                 }
             }
             Instruction::Cbnz { rn, imm } => {
                 if self.read_reg(rn) != 0 {
-                    self.pc = self.pc.wrapping_add(4).wrapping_add(imm as u32);
+                    self.pc = self.read_pc_for_operand().wrapping_add(imm as u32);
                     pc_increment = 0;
                 }
             }
             Instruction::Branch { offset } => {
-                let target = (self.pc as i32 + 4 + offset) as u32;
+                let target = self.read_pc_for_operand().wrapping_add(offset as u32);
                 self.pc = target;
                 pc_increment = 0;
             }
@@ -388,8 +1080,8 @@ impl Cpu for CortexM {
                 self.write_reg(13, sp);
             }
             Instruction::AddRegHigh { rd, rm } => {
-                let val1 = self.read_reg(rd);
-                let val2 = self.read_reg(rm);
+                let val1 = self.read_operand(rd);
+                let val2 = self.read_operand(rm);
                 self.write_reg(rd, val1.wrapping_add(val2));
             }
             Instruction::CmpImm { rn, imm } => {
@@ -398,13 +1090,13 @@ impl Cpu for CortexM {
                 self.update_nzcv(res, c, v);
             }
             Instruction::CmpReg { rn, rm } => {
-                let op1 = self.read_reg(rn);
-                let op2 = self.read_reg(rm);
+                let op1 = self.read_operand(rn);
+                let op2 = self.read_operand(rm);
                 let (res, c, v) = sub_with_flags(op1, op2);
                 self.update_nzcv(res, c, v);
             }
             Instruction::MovReg { rd, rm } => {
-                let val = self.read_reg(rm);
+                let val = self.read_operand(rm);
                 self.write_reg(rd, val);
             }
             // Logic
@@ -500,53 +1192,52 @@ impl Cpu for CortexM {
             Instruction::LdrImm { rt, rn, imm } => {
                 let base = self.read_reg(rn);
                 let addr = base.wrapping_add(imm as u32);
-                if let Ok(val) = bus.read_u32(addr as u64) {
-                    self.write_reg(rt, val);
-                } else {
-                    tracing::error!("Bus Read Fault at {:#x}", addr);
+                cycles += self.unaligned_penalty(bus, addr, 4)?;
+                match bus.read_u32(addr as u64) {
+                    Ok(val) => self.write_reg(rt, val),
+                    Err(e) => return self.handle_fault(bus, e),
                 }
             }
             Instruction::StrImm { rt, rn, imm } => {
                 let base = self.read_reg(rn);
                 let addr = base.wrapping_add(imm as u32);
+                cycles += self.unaligned_penalty(bus, addr, 4)?;
                 let val = self.read_reg(rt);
-                if bus.write_u32(addr as u64, val).is_err() {
-                    tracing::error!("Bus Write Fault at {:#x}", addr);
+                if let Err(e) = bus.write_u32(addr as u64, val) {
+                    return self.handle_fault(bus, e);
                 }
             }
             Instruction::LdrReg { rt, rn, rm } => {
                 let addr = self.read_reg(rn).wrapping_add(self.read_reg(rm));
-                if let Ok(val) = bus.read_u32(addr as u64) {
-                    self.write_reg(rt, val);
-                } else {
-                    tracing::error!("Bus Read Fault (LDR reg) at {:#x}", addr);
+                cycles += self.unaligned_penalty(bus, addr, 4)?;
+                match bus.read_u32(addr as u64) {
+                    Ok(val) => self.write_reg(rt, val),
+                    Err(e) => return self.handle_fault(bus, e),
                 }
             }
 
             Instruction::LdrLit { rt, imm } => {
-                // ... (existing)
-                let pc_val = (self.pc & !3) + 4;
-                let addr = pc_val.wrapping_add(imm as u32);
-                if let Ok(val) = bus.read_u32(addr as u64) {
-                    self.write_reg(rt, val);
-                } else {
-                    tracing::error!("Bus Read Fault (LdrLit) at {:#x}", addr);
+                let addr = self.literal_base().wrapping_add(imm as u32);
+                match bus.read_u32(addr as u64) {
+                    Ok(val) => self.write_reg(rt, val),
+                    Err(e) => return self.handle_fault(bus, e),
                 }
             }
 
             Instruction::LdrSp { rt, imm } => {
                 let addr = self.sp.wrapping_add(imm as u32);
-                if let Ok(val) = bus.read_u32(addr as u64) {
-                    self.write_reg(rt, val);
-                } else {
-                    tracing::error!("Bus Read Fault (LdrSp) at {:#x}", addr);
+                cycles += self.unaligned_penalty(bus, addr, 4)?;
+                match bus.read_u32(addr as u64) {
+                    Ok(val) => self.write_reg(rt, val),
+                    Err(e) => return self.handle_fault(bus, e),
                 }
             }
             Instruction::StrSp { rt, imm } => {
                 let addr = self.sp.wrapping_add(imm as u32);
+                cycles += self.unaligned_penalty(bus, addr, 4)?;
                 let val = self.read_reg(rt);
-                if bus.write_u32(addr as u64, val).is_err() {
-                    tracing::error!("Bus Write Fault (StrSp) at {:#x}", addr);
+                if let Err(e) = bus.write_u32(addr as u64, val) {
+                    return self.handle_fault(bus, e);
                 }
             }
             Instruction::AddSpReg { rd, imm } => {
@@ -554,8 +1245,7 @@ impl Cpu for CortexM {
                 self.write_reg(rd, res);
             }
             Instruction::Adr { rd, imm } => {
-                let pc_val = (self.pc & !3) + 4;
-                let res = pc_val.wrapping_add(imm as u32);
+                let res = self.literal_base().wrapping_add(imm as u32);
                 self.write_reg(rd, res);
             }
             Instruction::Uxtb { rd, rm } => {
@@ -567,35 +1257,35 @@ impl Cpu for CortexM {
             Instruction::LdrbImm { rt, rn, imm } => {
                 let base = self.read_reg(rn);
                 let addr = base.wrapping_add(imm as u32);
-                if let Ok(val) = bus.read_u8(addr as u64) {
-                    self.write_reg(rt, val as u32);
-                } else {
-                    tracing::error!("Bus Read Fault (LDRB) at {:#x}", addr);
+                match bus.read_u8(addr as u64) {
+                    Ok(val) => self.write_reg(rt, val as u32),
+                    Err(e) => return self.handle_fault(bus, e),
                 }
             }
             Instruction::StrbImm { rt, rn, imm } => {
                 let base = self.read_reg(rn);
                 let addr = base.wrapping_add(imm as u32);
                 let val = (self.read_reg(rt) & 0xFF) as u8;
-                if bus.write_u8(addr as u64, val).is_err() {
-                    tracing::error!("Bus Write Fault (STRB) at {:#x}", addr);
+                if let Err(e) = bus.write_u8(addr as u64, val) {
+                    return self.handle_fault(bus, e);
                 }
             }
             Instruction::LdrhImm { rt, rn, imm } => {
                 let base = self.read_reg(rn);
                 let addr = base.wrapping_add(imm as u32);
-                if let Ok(val) = bus.read_u16(addr as u64) {
-                    self.write_reg(rt, val as u32);
-                } else {
-                    tracing::error!("Bus Read Fault (LDRH) at {:#x}", addr);
+                cycles += self.unaligned_penalty(bus, addr, 2)?;
+                match bus.read_u16(addr as u64) {
+                    Ok(val) => self.write_reg(rt, val as u32),
+                    Err(e) => return self.handle_fault(bus, e),
                 }
             }
             Instruction::StrhImm { rt, rn, imm } => {
                 let base = self.read_reg(rn);
                 let addr = base.wrapping_add(imm as u32);
+                cycles += self.unaligned_penalty(bus, addr, 2)?;
                 let val = (self.read_reg(rt) & 0xFFFF) as u16;
-                if bus.write_u16(addr as u64, val).is_err() {
-                    tracing::error!("Bus Write Fault (STRH) at {:#x}", addr);
+                if let Err(e) = bus.write_u16(addr as u64, val) {
+                    return self.handle_fault(bus, e);
                 }
             }
 
@@ -608,8 +1298,9 @@ impl Cpu for CortexM {
                 if m {
                     sp = sp.wrapping_sub(4);
                     let val = self.read_reg(14);
-                    if bus.write_u32(sp as u64, val).is_err() {
+                    if let Err(e) = push_word(bus, sp, val) {
                         tracing::error!("Stack Overflow (PUSH LR)");
+                        return self.handle_fault(bus, e);
                     }
                 }
 
@@ -618,8 +1309,9 @@ impl Cpu for CortexM {
                     if (registers & (1 << i)) != 0 {
                         sp = sp.wrapping_sub(4);
                         let val = self.read_reg(i);
-                        if bus.write_u32(sp as u64, val).is_err() {
+                        if let Err(e) = push_word(bus, sp, val) {
                             tracing::error!("Stack Overflow (PUSH R{})", i);
+                            return self.handle_fault(bus, e);
                         }
                     }
                 }
@@ -632,8 +1324,9 @@ impl Cpu for CortexM {
                 // Registers R0 up to R7
                 for i in 0..=7 {
                     if (registers & (1 << i)) != 0 {
-                        if let Ok(val) = bus.read_u32(sp as u64) {
-                            self.write_reg(i, val);
+                        match bus.read_u32(sp as u64) {
+                            Ok(val) => self.write_reg(i, val),
+                            Err(e) => return self.handle_fault(bus, e),
                         }
                         sp = sp.wrapping_add(4);
                     }
@@ -657,34 +1350,49 @@ impl Cpu for CortexM {
                 // 2. If PC, read, add 4.
 
                 if p {
-                    if let Ok(val) = bus.read_u32(sp as u64) {
-                        self.branch_to(val, bus)?;
-                        pc_increment = 0; // Branch taken
+                    let val = match bus.read_u32(sp as u64) {
+                        Ok(val) => val,
+                        Err(e) => return self.handle_fault(bus, e),
+                    };
+                    if let Err(e) = self.branch_to(val, bus) {
+                        return self.handle_fault(bus, e);
                     }
+                    pc_increment = 0; // Branch taken
                     sp = sp.wrapping_add(4);
                 }
 
                 self.write_reg(13, sp);
             }
+            Instruction::Ldm { registers: 0, .. } | Instruction::Stm { registers: 0, .. } => {
+                // An empty register list is UNPREDICTABLE
+                tracing::error!("LDM/STM with an empty register list at {:#x}", self.pc);
+                return Err(SimulationError::DecodeError((self.pc & !1) as u64));
+            }
             Instruction::Ldm { rn, registers } => {
                 let mut base = self.read_reg(rn);
                 for i in 0..=7 {
                     if (registers & (1 << i)) != 0 {
-                        if let Ok(val) = bus.read_u32(base as u64) {
-                            self.write_reg(i, val);
+                        match bus.read_u32(base as u64) {
+                            Ok(val) => self.write_reg(i, val),
+                            Err(e) => return self.handle_fault(bus, e),
                         }
                         base = base.wrapping_add(4);
                     }
                 }
-                self.write_reg(rn, base);
+                // LDMIA writes back only when the base is not loaded itself
+                if registers & (1 << rn) == 0 {
+                    self.write_reg(rn, base);
+                }
             }
             Instruction::Stm { rn, registers } => {
+                // A base in the list stores its original value; writeback
+                // always happens.
                 let mut base = self.read_reg(rn);
                 for i in 0..=7 {
                     if (registers & (1 << i)) != 0 {
                         let val = self.read_reg(i);
-                        if bus.write_u32(base as u64, val).is_err() {
-                            tracing::error!("Bus Write Fault (STM) at {:#x}", base);
+                        if let Err(e) = bus.write_u32(base as u64, val) {
+                            return self.handle_fault(bus, e);
                         }
                         base = base.wrapping_add(4);
                     }
@@ -696,54 +1404,68 @@ impl Cpu for CortexM {
             Instruction::Bl { offset } => {
                 // BL: Branch with Link.
                 // LR = Next Instruction Address | 1 (Thumb bit)
-                let _next_pc = self.pc + 4; // 32-bit instruction size for BL?
-                                            // Wait. BL is decoded as 32-bit.
-                                            // If we assume decode_thumb_16 handled a 32-bit stream, then PC increment should be adjusted?
-                                            // Or does `decode_thumb_16` return `BlPrefix` and then we handle it?
-                                            // The current `decoder` returns `Bl` with full offset if it sees the pair??
-                                            // NO. My decoder implementation for BL (in previous turn) was:
-                                            // `Instruction::Bl { offset: offset << 1 }`
-                                            // But `decode_thumb_16` ONLY sees 16 bits. It cannot see the second half!
-                                            // Real decoding of BL requires fetching 32 bits.
-
-                // CRITICAL CORRECTION: `decode_thumb_16` is 16-bit.
-                // BL is 32-bit (encoded as two 16-bit halves).
-                // Fetch loop fetches 16 bits.
-                // 1. Fetch High Half (0xF0xx). Returns BlPrefix?
-                // 2. Fetch Low Half (0xF8xx). Combine?
-
-                // My logic in decoder needs revisit. I put `Bl { offset }` thinking T1/T2 but BL is always 32-bit in Thumb-2.
-                // T1 encoding of BL doesn't exist as single 16-bit.
-
-                // For now, let's just implement the execution stub assuming the decoder *somehow* gave us the full BL.
-                // But since the decoder only sees 16 bits, we need to handle the prefix state in the CPU loop!
-
-                self.lr = (self.pc + 4) | 1;
-                let target = (self.pc as i32 + 4 + offset) as u32;
+                // BL is a 32-bit instruction, so the return address is
+                // also the PC operand value.
+                self.lr = self.read_pc_for_operand() | 1;
+                let target = self.read_pc_for_operand().wrapping_add(offset as u32);
                 self.pc = target;
                 pc_increment = 0;
             }
             Instruction::BranchCond { cond, offset } => {
                 if self.check_condition(cond) {
-                    let target = (self.pc as i32 + 4 + offset) as u32;
+                    let target = self.read_pc_for_operand().wrapping_add(offset as u32);
                     self.pc = target;
                     pc_increment = 0;
                 }
             }
             Instruction::Bx { rm } => {
                 let target = self.read_reg(rm);
-                self.branch_to(target, bus)?;
+                if let Err(e) = self.branch_to(target, bus) {
+                    return self.handle_fault(bus, e);
+                }
                 pc_increment = 0;
             }
 
+            Instruction::Prefix32(h1)
+                if self.fpu.is_some() && crate::decoder::arm::is_coprocessor(h1) =>
+            {
+                let next_pc = (self.pc & !1) + 2;
+                let h2 = match bus.read_u16(next_pc as u64) {
+                    Ok(h2) => h2,
+                    Err(e) => return self.handle_fault(bus, e),
+                };
+                let Some(vfp) = crate::decoder::arm::decode_vfp(h1, h2) else {
+                    tracing::error!("Unsupported VFP instruction: {:04x} {:04x}", h1, h2);
+                    return Err(SimulationError::UndefinedInstruction((self.pc & !1) as u64));
+                };
+                tracing::debug!(
+                    target: crate::INSTRUCTION_TRACE_TARGET,
+                    " decoded VFP: {}",
+                    vfp
+                );
+                cycles = match self.execute_vfp(bus, vfp) {
+                    Ok(cycles) => cycles,
+                    Err(e) => return self.handle_fault(bus, e),
+                };
+                pc_increment = 4;
+            }
+
             Instruction::Prefix32(h1) => {
-                cycles = 2;
                 let next_pc = (self.pc & !1) + 2;
                 if let Ok(h2) = bus.read_u16(next_pc as u64) {
+                    if self.nocp_trap && crate::decoder::arm::is_coprocessor(h1) {
+                        tracing::error!("Coprocessor instruction {:04x} {:04x} without an FPU", h1, h2);
+                        return self.usage_fault(bus, CFSR_NOCP);
+                    }
+                    cycles = self.cycle_costs.thumb32(h1, h2);
                     // Use the new modular decoder
                     let instruction32 = crate::decoder::arm::decode_thumb_32(h1, h2);
 
-                    tracing::debug!(" decoded 32-bit: {:?}", instruction32);
+                    tracing::debug!(
+                        target: crate::INSTRUCTION_TRACE_TARGET,
+                        " decoded 32-bit: {:?}",
+                        instruction32
+                    );
 
                     match instruction32 {
                         Instruction::Bfi { rd, rn, lsb, width } => {
@@ -831,11 +1553,11 @@ impl Cpu for CortexM {
                                         ((op2 as i32) >> imm5) as u32
                                     };
                                 }
-                                3 => if imm5 != 0 { op2 = op2.rotate_right(imm5 as u32) }, // ROR
+                                3 if imm5 != 0 => op2 = op2.rotate_right(imm5 as u32), // ROR
                                 _ => {}
                             }
 
-                            let mut result = 0u32;
+                            let result: u32;
                             match op {
                                 0x0 => { result = op1 & op2; self.write_reg(rd, result); } // AND
                                 0x1 => { result = op1 & !op2; self.write_reg(rd, result); } // BIC
@@ -851,7 +1573,8 @@ impl Cpu for CortexM {
                                 0x8 => { result = op1.wrapping_add(op2); self.write_reg(rd, result); } // ADD
                                 0xD => { result = op1.wrapping_sub(op2); self.write_reg(rd, result); } // SUB
                                 _ => {
-                                    tracing::warn!("Unknown DataProc32 op {:#x}", op);
+                                    tracing::error!("Unknown DataProc32 op {:#x}", op);
+                                    return Err(SimulationError::UndefinedInstruction((self.pc & !1) as u64));
                                 }
                             }
 
@@ -862,7 +1585,43 @@ impl Cpu for CortexM {
                         }
                         _ => {
                             // Fallback to legacy decoding
-                            if (h1 & 0xFE00) == 0xE800 {
+                            if (h1 & 0xFE40) == 0xE800 && matches!((h1 >> 7) & 3, 1 | 2) {
+                                // LDM/STM (T2): IA or DB, including POP.W (LDMIA SP!)
+                                // and PUSH.W (STMDB SP!)
+                                let increment = (h1 >> 7) & 3 == 1;
+                                let wback = h1 & 0x20 != 0;
+                                let is_load = h1 & 0x10 != 0;
+                                let rn = (h1 & 0xF) as u8;
+                                let registers = h2;
+                                let size = registers.count_ones() * 4;
+                                let base = self.read_reg(rn);
+                                let start = if increment { base } else { base.wrapping_sub(size) };
+                                let mut addr = start;
+                                let mut new_pc = None;
+                                for i in (0..16u8).filter(|i| registers & (1 << i) != 0) {
+                                    if is_load {
+                                        match bus.read_u32(addr as u64) {
+                                            Ok(val) if i == 15 => new_pc = Some(val),
+                                            Ok(val) => self.write_reg(i, val),
+                                            Err(e) => return self.handle_fault(bus, e),
+                                        }
+                                    } else {
+                                        let val = self.read_reg(i);
+                                        let stored = if rn == 13 { push_word(bus, addr, val) } else { bus.write_u32(addr as u64, val) };
+                                        if let Err(e) = stored { return self.handle_fault(bus, e); }
+                                    }
+                                    addr = addr.wrapping_add(4);
+                                }
+                                // A loaded base keeps the loaded value
+                                if wback && !(is_load && registers & (1 << rn) != 0) {
+                                    self.write_reg(rn, if increment { base.wrapping_add(size) } else { start });
+                                }
+                                pc_increment = 4;
+                                if let Some(target) = new_pc {
+                                    if let Err(e) = self.branch_to(target, bus) { return self.handle_fault(bus, e); }
+                                    pc_increment = 0;
+                                }
+                            } else if (h1 & 0xFE00) == 0xE800 {
                                 // Load/store dual, load/store exclusive, table branch
                                 let op = ((h1 >> 7) & 3) as u8;
                                 let rn = (h1 & 0xF) as u8;
@@ -875,26 +1634,31 @@ impl Cpu for CortexM {
                                     let rm = (h2 & 0xF) as u8;
                                     let is_tbh = (h2 & 0x0010) != 0;
 
-                                    let mut base = self.read_reg(rn);
-                                    if rn == 15 {
-                                        base = (self.pc & !3).wrapping_add(4);
-                                    }
+                                    let base = if rn == 15 {
+                                        self.literal_base()
+                                    } else {
+                                        self.read_reg(rn)
+                                    };
                                     let index = self.read_reg(rm);
 
                                     if is_tbh {
                                         let addr = base.wrapping_add(index << 1);
-                                        if let Ok(halfword) = bus.read_u16(addr as u64) {
-                                            let offset = (halfword as u32) << 1;
-                                            self.pc = self.pc.wrapping_add(4).wrapping_add(offset);
-                                            pc_increment = 0;
-                                        }
+                                        let halfword = match bus.read_u16(addr as u64) {
+                                            Ok(halfword) => halfword,
+                                            Err(e) => return self.handle_fault(bus, e),
+                                        };
+                                        let offset = (halfword as u32) << 1;
+                                        self.pc = self.read_pc_for_operand().wrapping_add(offset);
+                                        pc_increment = 0;
                                     } else {
                                         let addr = base.wrapping_add(index);
-                                        if let Ok(byte) = bus.read_u8(addr as u64) {
-                                            let offset = (byte as u32) << 1;
-                                            self.pc = self.pc.wrapping_add(4).wrapping_add(offset);
-                                            pc_increment = 0;
-                                        }
+                                        let byte = match bus.read_u8(addr as u64) {
+                                            Ok(byte) => byte,
+                                            Err(e) => return self.handle_fault(bus, e),
+                                        };
+                                        let offset = (byte as u32) << 1;
+                                        self.pc = self.read_pc_for_operand().wrapping_add(offset);
+                                        pc_increment = 0;
                                     }
                                 } else if op == 2 || op == 3 {
                                     // STRD / LDRD (immediate) - simplified
@@ -903,23 +1667,57 @@ impl Cpu for CortexM {
                                     let addr = base.wrapping_add(imm8 << 2);
 
                                     if is_load {
-                                        if let Ok(v1) = bus.read_u32(addr as u64) {
-                                            self.write_reg(rt, v1);
-                                        }
-                                        if let Ok(v2) = bus.read_u32((addr + 4) as u64) {
-                                            self.write_reg(rt2, v2);
+                                        let loaded = bus
+                                            .read_u32(addr as u64)
+                                            .and_then(|v1| Ok((v1, bus.read_u32((addr + 4) as u64)?)));
+                                        match loaded {
+                                            Ok((v1, v2)) => {
+                                                self.write_reg(rt, v1);
+                                                self.write_reg(rt2, v2);
+                                            }
+                                            Err(e) => return self.handle_fault(bus, e),
                                         }
                                     } else {
                                         let v1 = self.read_reg(rt);
                                         let v2 = self.read_reg(rt2);
-                                        let _ = bus.write_u32(addr as u64, v1);
-                                        let _ = bus.write_u32((addr + 4) as u64, v2);
+                                        let stored = bus
+                                            .write_u32(addr as u64, v1)
+                                            .and_then(|_| bus.write_u32((addr + 4) as u64, v2));
+                                        if let Err(e) = stored {
+                                            return self.handle_fault(bus, e);
+                                        }
                                     }
                                     pc_increment = 4;
                                 } else {
                                     // ...
                                     pc_increment = 4;
                                 }
+                            } else if (h1 & 0xF800) == 0xF000
+                                && (h2 & 0xD000) == 0x8000
+                                && (h1 >> 7) & 0x7 != 0x7
+                            {
+                                // B<cond>.W (T3): S:J2:J1:imm6:imm11:0
+                                let cond = ((h1 >> 6) & 0xF) as u8;
+                                let s = ((h1 >> 10) & 0x1) as i32;
+                                let j1 = ((h2 >> 13) & 0x1) as i32;
+                                let j2 = ((h2 >> 11) & 0x1) as i32;
+                                let imm6 = (h1 & 0x3F) as i32;
+                                let imm11 = (h2 & 0x7FF) as i32;
+                                let mut offset = (s << 20)
+                                    | (j2 << 19)
+                                    | (j1 << 18)
+                                    | (imm6 << 12)
+                                    | (imm11 << 1);
+                                if s != 0 {
+                                    offset |= !0x001F_FFFF;
+                                }
+                                if self.check_condition(cond) {
+                                    self.pc =
+                                        self.read_pc_for_operand().wrapping_add(offset as u32);
+                                    pc_increment = 0;
+                                } else {
+                                    pc_increment = 4;
+                                }
                             } else if (h1 & 0xF800) == 0xF000 && (h2 & 0x8000) == 0x8000 {
                                 // B.W / BL
                                 let s = ((h1 >> 10) & 0x1) as i32;
@@ -948,9 +1746,9 @@ impl Cpu for CortexM {
                                 }
 
                                 if is_bl {
-                                    self.lr = (self.pc + 4) | 1;
+                                    self.lr = self.read_pc_for_operand() | 1;
                                 }
-                                self.pc = (self.pc as i32 + 4 + offset) as u32;
+                                self.pc = self.read_pc_for_operand().wrapping_add(offset as u32);
                                 pc_increment = 0;
                             } else if (h1 & 0xFBF0) == 0xF240 {
                                 // MOVW (T1)
@@ -974,7 +1772,7 @@ impl Cpu for CortexM {
                                 let new_val = (old_val & 0x0000FFFF) | ((imm16 as u32) << 16);
                                 self.write_reg(rd, new_val);
                                 pc_increment = 4;
-                            } else if (h1 & 0xFB00) == 0xF000 && (h2 & 0x8000) == 0 {
+                            } else if (h1 & 0xFA00) == 0xF000 && (h2 & 0x8000) == 0 {
                                 // Data-processing (modified immediate)
                                 let i = (h1 >> 10) & 0x1;
                                 let op = ((h1 >> 5) & 0xF) as u8;
@@ -984,50 +1782,51 @@ impl Cpu for CortexM {
                                 let rd = ((h2 >> 8) & 0xF) as u8;
                                 let imm8 = h2 & 0xFF;
                                 let imm12 = (i << 11) | (imm3 << 8) | imm8;
-                                let imm32 = thumb_expand_imm(imm12 as u32); // restored usage
+                                let carry_in = self.xpsr & PSR_C != 0;
+                                let (imm32, imm_carry) = thumb_expand_imm_c(imm12 as u32, carry_in);
                                 let op1 = self.read_reg(rn);
-                                let mut result = 0u32;
-                                let mut update_pc = true;
-
-                                match op {
-                                    0x0 => { result = op1 & imm32; self.write_reg(rd, result); } // AND
-                                    0x1 => { result = op1 & !imm32; self.write_reg(rd, result); } // BIC
-                                    0x2 => { // ORR / MOV
-                                        result = if rn == 0xF { imm32 } else { op1 | imm32 };
-                                        self.write_reg(rd, result);
-                                    }
-                                    0x3 => { // ORN / MVN
-                                        result = if rn == 0xF { !imm32 } else { op1 | !imm32 };
-                                        self.write_reg(rd, result);
-                                    }
-                                    0x4 => { result = op1 ^ imm32; self.write_reg(rd, result); } // EOR
-                                    0x8 => { result = op1.wrapping_add(imm32); self.write_reg(rd, result); } // ADD
-                                    0xA => { // ADC
-                                        let carry = if self.xpsr & PSR_C != 0 { 1 } else { 0 };
-                                        result = op1.wrapping_add(imm32).wrapping_add(carry);
-                                        self.write_reg(rd, result);
-                                    }
-                                    0xB => { // SBC
-                                        let carry = if self.xpsr & PSR_C != 0 { 1 } else { 0 };
-                                        result = op1.wrapping_sub(imm32).wrapping_sub(1 - carry);
-                                        self.write_reg(rd, result);
+                                // The result, plus carry and overflow from arithmetic ops
+                                let arith = |(r, c, v)| Some((r, Some((c, v))));
+                                let outcome = match op {
+                                    0x0 => Some((op1 & imm32, None)), // AND / TST
+                                    0x1 => Some((op1 & !imm32, None)), // BIC
+                                    0x2 => Some((if rn == 0xF { imm32 } else { op1 | imm32 }, None)), // ORR / MOV
+                                    0x3 => Some((if rn == 0xF { !imm32 } else { op1 | !imm32 }, None)), // ORN / MVN
+                                    0x4 => Some((op1 ^ imm32, None)), // EOR / TEQ
+                                    0x8 => arith(add_with_carry(op1, imm32, false)), // ADD / CMN
+                                    0xA => arith(add_with_carry(op1, imm32, carry_in)), // ADC
+                                    0xB => arith(add_with_carry(op1, !imm32, carry_in)), // SBC
+                                    0xD => arith(add_with_carry(op1, !imm32, true)), // SUB / CMP
+                                    0xE => arith(add_with_carry(!op1, imm32, true)), // RSB
+                                    _ => None,
+                                };
+                                if let Some((result, arith_flags)) = outcome {
+                                    // TST, TEQ, CMN and CMP only set flags
+                                    let compare = s && rd == 0xF && matches!(op, 0x0 | 0x4 | 0x8 | 0xD);
+                                    if !compare { self.write_reg(rd, result); }
+                                    if s {
+                                        match arith_flags {
+                                            Some((c, v)) => self.update_nzcv(result, c, v),
+                                            None => {
+                                                // Logical ops take C from the immediate expansion
+                                                self.update_nz(result);
+                                                if imm_carry { self.xpsr |= PSR_C; } else { self.xpsr &= !PSR_C; }
+                                            }
+                                        }
                                     }
-                                    0xD => { result = op1.wrapping_sub(imm32); self.write_reg(rd, result); } // SUB
-                                    0xE => { result = imm32.wrapping_sub(op1); self.write_reg(rd, result); } // RSB
-                                    _ => { update_pc = false; }
+                                    pc_increment = 4;
                                 }
-                                if s && update_pc { self.update_nz(result); }
-                                if update_pc { pc_increment = 4; }
-                            } else if (h1 & 0xFB00) == 0xF100 && (h2 & 0x8000) == 0 {
-                                // Data-processing (plain binary immediate)
+                            } else if (h1 & 0xFB00) == 0xF200 && (h2 & 0x8000) == 0 {
+                                // Data-processing (plain binary immediate): ADDW, SUBW
                                 let i = (h1 >> 10) & 0x1;
-                                let op = ((h1 >> 5) & 0xF) as u8;
+                                let op = ((h1 >> 4) & 0x1F) as u8;
                                 let rn = (h1 & 0xF) as u8;
                                 let imm3 = (h2 >> 12) & 0x7;
                                 let rd = ((h2 >> 8) & 0xF) as u8;
                                 let imm8 = h2 & 0xFF;
                                 let imm12 = (i << 11) | (imm3 << 8) | imm8;
-                                let op1 = self.read_reg(rn);
+                                // With Rn = PC these are ADR.W
+                                let op1 = if rn == 15 { self.literal_base() } else { self.read_reg(rn) };
                                 match op {
                                     0x0 => { self.write_reg(rd, op1.wrapping_add(imm12 as u32)); pc_increment = 4; } // ADD
                                     0xA => { self.write_reg(rd, op1.wrapping_sub(imm12 as u32)); pc_increment = 4; } // SUB
@@ -1038,7 +1837,9 @@ impl Cpu for CortexM {
                                 let op1 = (h1 >> 4) & 0xF;
                                 let rn = (h1 & 0xF) as u8;
                                 let rt = ((h2 >> 12) & 0xF) as u8;
-                                let is_t4 = (op1 & 0x8) == 0;
+                                // Literal loads (Rn = PC) take U from bit 7 of the first halfword
+                                let is_literal = rn == 15;
+                                let is_t4 = !is_literal && (op1 & 0x8) == 0;
                                 let is_reg_offset = is_t4 && (h2 & 0x0800) == 0;
 
                                 if !is_reg_offset {
@@ -1047,7 +1848,14 @@ impl Cpu for CortexM {
                                     let mut wb = false;
                                     let mut wb_val = 0u32;
 
-                                    if !is_t4 { // T3
+                                    if is_literal {
+                                        let offset = (h2 & 0xFFF) as u32;
+                                        addr = if op1 & 0x8 != 0 {
+                                            self.literal_base().wrapping_add(offset)
+                                        } else {
+                                            self.literal_base().wrapping_sub(offset)
+                                        };
+                                    } else if !is_t4 { // T3
                                         let offset = (h2 & 0xFFF) as i32;
                                         addr = self.read_reg(rn).wrapping_add(offset as u32);
                                     } else { // T4
@@ -1066,30 +1874,34 @@ impl Cpu for CortexM {
                                         }
                                     }
 
-                                    match op1 & 0x7 {
-                                        0 => { let val = (self.read_reg(rt) & 0xFF) as u8; let _ = bus.write_u8(addr as u64, val); }
-                                        1 => { if let Ok(v) = bus.read_u8(addr as u64) { self.write_reg(rt, v as u32); } }
-                                        2 => { let val = (self.read_reg(rt) & 0xFFFF) as u16; let _ = bus.write_u16(addr as u64, val); }
-                                        3 => { if let Ok(v) = bus.read_u16(addr as u64) { self.write_reg(rt, v as u32); } }
-                                        4 => { let val = self.read_reg(rt); let _ = bus.write_u32(addr as u64, val); }
-                                        5 => { if let Ok(v) = bus.read_u32(addr as u64) { self.write_reg(rt, v); } }
-                                        _ => { supported = false; }
-                                    }
+                                    if op1 & 0x7 < 6 { cycles += self.unaligned_penalty(bus, addr, 1 << ((op1 & 0x7) >> 1))?; }
+                                    let access = match op1 & 0x7 {
+                                        0 => { let val = (self.read_reg(rt) & 0xFF) as u8; bus.write_u8(addr as u64, val) }
+                                        1 => bus.read_u8(addr as u64).map(|v| self.write_reg(rt, v as u32)),
+                                        2 => { let val = (self.read_reg(rt) & 0xFFFF) as u16; bus.write_u16(addr as u64, val) }
+                                        3 => bus.read_u16(addr as u64).map(|v| self.write_reg(rt, v as u32)),
+                                        4 => { let val = self.read_reg(rt); bus.write_u32(addr as u64, val) }
+                                        5 => bus.read_u32(addr as u64).map(|v| self.write_reg(rt, v)),
+                                        _ => { supported = false; Ok(()) }
+                                    };
+                                    if let Err(e) = access { return self.handle_fault(bus, e); }
                                     if supported { if wb { self.write_reg(rn, wb_val); } pc_increment = 4; }
                                 } else {
                                     // Reg offset
                                     let rm = (h2 & 0xF) as u8;
                                     let imm2 = ((h2 >> 4) & 0x3) as u32;
                                     let addr = self.read_reg(rn).wrapping_add(self.read_reg(rm) << imm2);
-                                     match op1 & 0x7 {
-                                        0 => { let val = (self.read_reg(rt) & 0xFF) as u8; let _ = bus.write_u8(addr as u64, val); }
-                                        1 => { if let Ok(v) = bus.read_u8(addr as u64) { self.write_reg(rt, v as u32); } }
-                                        2 => { let val = (self.read_reg(rt) & 0xFFFF) as u16; let _ = bus.write_u16(addr as u64, val); }
-                                        3 => { if let Ok(v) = bus.read_u16(addr as u64) { self.write_reg(rt, v as u32); } }
-                                        4 => { let val = self.read_reg(rt); let _ = bus.write_u32(addr as u64, val); }
-                                        5 => { if let Ok(v) = bus.read_u32(addr as u64) { self.write_reg(rt, v); } }
-                                        _ => { }
-                                    }
+                                    if op1 & 0x7 < 6 { cycles += self.unaligned_penalty(bus, addr, 1 << ((op1 & 0x7) >> 1))?; }
+                                    let access = match op1 & 0x7 {
+                                        0 => { let val = (self.read_reg(rt) & 0xFF) as u8; bus.write_u8(addr as u64, val) }
+                                        1 => bus.read_u8(addr as u64).map(|v| self.write_reg(rt, v as u32)),
+                                        2 => { let val = (self.read_reg(rt) & 0xFFFF) as u16; bus.write_u16(addr as u64, val) }
+                                        3 => bus.read_u16(addr as u64).map(|v| self.write_reg(rt, v as u32)),
+                                        4 => { let val = self.read_reg(rt); bus.write_u32(addr as u64, val) }
+                                        5 => bus.read_u32(addr as u64).map(|v| self.write_reg(rt, v)),
+                                        _ => Ok(()),
+                                    };
+                                    if let Err(e) = access { return self.handle_fault(bus, e); }
                                     pc_increment = 4;
                                 }
                             } else if (h1 & 0xFFF0) == 0xFB90 { // SDIV
@@ -1107,30 +1919,45 @@ impl Cpu for CortexM {
                                 let rm = (h2 & 0xF) as u8;
                                 let dividend = self.read_reg(rn);
                                 let divisor = self.read_reg(rm);
-                                let result = if divisor == 0 { 0 } else { dividend / divisor };
+                                let result = dividend.checked_div(divisor).unwrap_or(0);
                                 self.write_reg(rd, result);
                                 pc_increment = 4;
                             } else {
-                                tracing::warn!("Internal: Unhandled 32-bit: {:04x} {:04x}", h1, h2);
-                                pc_increment = 4;
+                                tracing::error!("Undefined 32-bit instruction: {:04x} {:04x}", h1, h2);
+                                return Err(SimulationError::UndefinedInstruction((self.pc & !1) as u64));
                             }
                         }
                     }
 
                 } else {
-                    tracing::error!("Bus Read Fault (32-bit suffix) at {:#x}", next_pc);
+                    return self.handle_fault(bus, SimulationError::MemoryViolation(next_pc as u64));
                 }
             }
 
+            Instruction::Bkpt { imm } => {
+                // BKPT #0xAB is an ARM semihosting call, which is not implemented
+                if imm == 0xAB {
+                    tracing::error!("Semihosting BKPT at {:#x} is not supported", self.pc);
+                    return Err(SimulationError::UndefinedInstruction((self.pc & !1) as u64));
+                }
+                return Err(SimulationError::Breakpoint((self.pc & !1) as u64, imm));
+            }
             Instruction::Unknown(op) => {
-                tracing::warn!("Unknown instruction at {:#x}: Opcode {:#06x}", self.pc, op);
-                pc_increment = 2; // Skip 16-bit
+                tracing::error!("Unknown instruction at {:#x}: Opcode {:#06x}", self.pc, op);
+                return Err(SimulationError::UndefinedInstruction((self.pc & !1) as u64));
             }
         }
 
+        if pc_increment == 0 {
+            cycles += self.cycle_costs.branch_taken;
+        }
         self.pc = self.pc.wrapping_add(pc_increment);
 
+        let returned = active != 0 && self.xpsr & IPSR_MASK != active;
         for observer in observers {
+            if returned {
+                observer.on_nvic_change(active, NvicEvent::Deactivate);
+            }
             observer.on_step_end(cycles);
         }
 
@@ -1138,6 +1965,15 @@ impl Cpu for CortexM {
     }
 }
 
+/// Store `val` at `addr` as part of a stack push, reporting a failed write
+/// as `StackOverflow`.
+fn push_word(bus: &mut dyn Bus, addr: u32, val: u32) -> SimResult<()> {
+    bus.write_u32(addr as u64, val).map_err(|e| match e {
+        SimulationError::MemoryViolation(_) => SimulationError::StackOverflow(addr as u64),
+        e => e,
+    })
+}
+
 fn add_with_flags(op1: u32, op2: u32) -> (u32, bool, bool) {
     let (res, overflow1) = op1.overflowing_add(op2);
     let carry = overflow1;
@@ -1148,6 +1984,14 @@ fn add_with_flags(op1: u32, op2: u32) -> (u32, bool, bool) {
     (res, carry, overflow)
 }
 
+/// ARM `AddWithCarry`: the sum, carry out and signed overflow.
+fn add_with_carry(op1: u32, op2: u32, carry_in: bool) -> (u32, bool, bool) {
+    let sum = op1 as u64 + op2 as u64 + carry_in as u64;
+    let res = sum as u32;
+    let signed_sum = op1 as i32 as i64 + op2 as i32 as i64 + carry_in as i64;
+    (res, sum > u32::MAX as u64, signed_sum != res as i32 as i64)
+}
+
 fn sub_with_flags(op1: u32, op2: u32) -> (u32, bool, bool) {
     let (res, borrow) = op1.overflowing_sub(op2);
     let carry = !borrow;
@@ -1158,8 +2002,10 @@ fn sub_with_flags(op1: u32, op2: u32) -> (u32, bool, bool) {
     (res, carry, overflow)
 }
 
-// Thumb expand immediate - implements ARM's modified immediate constant expansion
-fn thumb_expand_imm(imm12: u32) -> u32 {
+// Thumb expand immediate - implements ARM's modified immediate constant expansion.
+// Also returns the carry out: repeated byte patterns leave the carry unchanged,
+// rotated constants carry out bit 31 of the result.
+fn thumb_expand_imm_c(imm12: u32, carry_in: bool) -> (u32, bool) {
     let i = (imm12 >> 11) & 1;
     let imm3 = (imm12 >> 8) & 7;
     let imm8 = imm12 & 0xFF;
@@ -1167,19 +2013,21 @@ fn thumb_expand_imm(imm12: u32) -> u32 {
     if i == 0 && (imm3 >> 2) == 0 {
         // i:imm3 is 0000, 0001, 0010, 0011.
         // Match repetition patterns:
-        match imm3 {
+        let value = match imm3 {
             0 => imm8,                       // 00000000 00000000 00000000 abcdefgh
             1 => (imm8 << 16) | imm8,        // 00000000 abcdefgh 00000000 abcdefgh
             2 => (imm8 << 24) | (imm8 << 8), // abcdefgh 00000000 abcdefgh 00000000
             3 => (imm8 << 24) | (imm8 << 16) | (imm8 << 8) | imm8, // abcdefgh abcdefgh abcdefgh abcdefgh
             _ => unreachable!(),
-        }
+        };
+        (value, carry_in)
     } else {
         // Rotated immediate
         // The value to rotate is '1' concatenated with bits 6:0 of imm8.
         let val = 0x80 | (imm8 & 0x7F);
         // The rotation amount 'n' is i:imm3:imm8[7]
         let n = (i << 4) | (imm3 << 1) | (imm8 >> 7);
-        val.rotate_right(n)
+        let value = val.rotate_right(n);
+        (value, value & 0x8000_0000 != 0)
     }
 }
@@ -0,0 +1,77 @@
+// LabWired - Firmware Simulation Platform
+// Copyright (C) 2026 Andrii Shylenko
+//
+// This software is released under the MIT License.
+// See the LICENSE file in the project root for full license information.
+
+use serde::{Deserialize, Serialize};
+
+/// FPSCR condition flags, set by VCMP and copied to the APSR by
+/// `VMRS APSR_nzcv, FPSCR`.
+pub const FPSCR_N: u32 = 1 << 31;
+pub const FPSCR_Z: u32 = 1 << 30;
+pub const FPSCR_C: u32 = 1 << 29;
+pub const FPSCR_V: u32 = 1 << 28;
+pub const FPSCR_NZCV: u32 = FPSCR_N | FPSCR_Z | FPSCR_C | FPSCR_V;
+
+/// FPCCR.ASPEN: executing an FP instruction sets CONTROL.FPCA, so exception
+/// entry stacks FP context.
+pub const FPCCR_ASPEN: u32 = 1 << 31;
+/// FPCCR.LSPEN: FP context is stacked lazily, on the handler's first FP
+/// instruction, into space reserved on entry.
+pub const FPCCR_LSPEN: u32 = 1 << 30;
+/// FPCCR.LSPACT: lazy stacking is pending at FPCAR.
+pub const FPCCR_LSPACT: u32 = 1 << 0;
+/// FPCCR out of reset: automatic, lazy stacking.
+pub const FPCCR_RESET: u32 = FPCCR_ASPEN | FPCCR_LSPEN;
+
+/// Floating-point extension of a Cortex-M4F/M7 core.
+///
+/// `s0`-`s31` hold raw single-precision bits; the double-precision view
+/// `d0`-`d15` pairs them up, `d<n>` being `s<2n+1>:s<2n>`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Fpu {
+    pub s: [u32; 32],
+    pub fpscr: u32,
+    /// CONTROL.FPCA: the current context uses the FPU, so an exception
+    /// taken now stacks an extended frame.
+    #[serde(default)]
+    pub fpca: bool,
+}
+
+impl Fpu {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn read_s(&self, n: u8) -> f32 {
+        f32::from_bits(self.s[n as usize])
+    }
+
+    pub fn write_s(&mut self, n: u8, val: f32) {
+        self.s[n as usize] = val.to_bits();
+    }
+
+    pub fn read_d(&self, n: u8) -> u64 {
+        let n = n as usize * 2;
+        ((self.s[n + 1] as u64) << 32) | self.s[n] as u64
+    }
+
+    pub fn write_d(&mut self, n: u8, val: u64) {
+        let n = n as usize * 2;
+        self.s[n] = val as u32;
+        self.s[n + 1] = (val >> 32) as u32;
+    }
+
+    /// Set the FPSCR flags from comparing `a` with `b`, as VCMP does.
+    pub fn compare(&mut self, a: f32, b: f32) {
+        let flags = match a.partial_cmp(&b) {
+            Some(std::cmp::Ordering::Equal) => FPSCR_Z | FPSCR_C,
+            Some(std::cmp::Ordering::Less) => FPSCR_N,
+            Some(std::cmp::Ordering::Greater) => FPSCR_C,
+            // Unordered: either operand is a NaN
+            None => FPSCR_C | FPSCR_V,
+        };
+        self.fpscr = (self.fpscr & !FPSCR_NZCV) | flags;
+    }
+}
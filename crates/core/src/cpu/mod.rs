@@ -5,7 +5,9 @@
 // See the LICENSE file in the project root for full license information.
 
 pub mod cortex_m;
+pub mod fpu;
 pub mod riscv;
 
-pub use cortex_m::CortexM;
+pub use cortex_m::{CortexM, CycleCosts, FaultBehavior};
+pub use fpu::Fpu;
 pub use riscv::RiscV;
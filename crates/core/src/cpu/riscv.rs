@@ -4,14 +4,123 @@
 // This software is released under the MIT License.
 // See the LICENSE file in the project root for full license information.
 
-use crate::decoder::riscv::{decode_rv32, Instruction};
+use crate::decoder::riscv::{decode_rv32, decode_rv32c, is_compressed, Instruction};
+use crate::decoder::InstructionCategory;
 use crate::{Bus, Cpu, SimResult, SimulationObserver};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Arc;
 
-#[derive(Debug, Default)]
+pub const CSR_MSTATUS: u16 = 0x300;
+pub const CSR_MISA: u16 = 0x301;
+pub const CSR_MIE: u16 = 0x304;
+pub const CSR_MTVEC: u16 = 0x305;
+pub const CSR_MSCRATCH: u16 = 0x340;
+pub const CSR_MEPC: u16 = 0x341;
+pub const CSR_MCAUSE: u16 = 0x342;
+pub const CSR_MTVAL: u16 = 0x343;
+pub const CSR_MIP: u16 = 0x344;
+pub const CSR_MHARTID: u16 = 0xF14;
+
+/// `mcause` exception codes.
+pub const CAUSE_BREAKPOINT: u32 = 3;
+pub const CAUSE_ECALL_FROM_M: u32 = 11;
+/// Set in `mcause` when the trap is an interrupt.
+pub const CAUSE_INTERRUPT: u32 = 1 << 31;
+/// `mcause` interrupt codes, which are also their `mip`/`mie` bit numbers.
+pub const IRQ_MSI: u32 = 3;
+pub const IRQ_MTI: u32 = 7;
+pub const IRQ_MEI: u32 = 11;
+
+/// `mip` bits set by devices rather than software.
+const MIP_EXTERNAL: u32 = (1 << IRQ_MSI) | (1 << IRQ_MTI) | (1 << IRQ_MEI);
+
+const MSTATUS_MIE: u32 = 1 << 3;
+const MSTATUS_MPIE: u32 = 1 << 7;
+/// MPP = 3: the trap was taken from machine mode, the only mode modelled.
+const MSTATUS_MPP_M: u32 = 3 << 11;
+
+/// `misa` for RV32IC: MXL = 1 (32-bit), extensions I and C.
+const MISA_RV32IC: u32 = (1 << 30) | (1 << 8) | (1 << 2);
+
+/// Machine-mode CSRs backed by the core. Any other CSR number is illegal.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Csrs {
+    pub mstatus: u32,
+    pub mie: u32,
+    pub mtvec: u32,
+    pub mscratch: u32,
+    pub mepc: u32,
+    pub mcause: u32,
+    pub mtval: u32,
+    pub mip: u32,
+}
+
+impl Csrs {
+    /// Value of CSR `csr`, or `None` if it is not implemented.
+    pub fn read(&self, csr: u16) -> Option<u32> {
+        Some(match csr {
+            CSR_MSTATUS => self.mstatus,
+            CSR_MISA => MISA_RV32IC,
+            CSR_MIE => self.mie,
+            CSR_MTVEC => self.mtvec,
+            CSR_MSCRATCH => self.mscratch,
+            CSR_MEPC => self.mepc,
+            CSR_MCAUSE => self.mcause,
+            CSR_MTVAL => self.mtval,
+            CSR_MIP => self.mip,
+            CSR_MHARTID => 0,
+            _ => return None,
+        })
+    }
+
+    /// Write CSR `csr`. Returns false if it is not implemented or read-only
+    /// (CSR numbers 0xC00-0xFFF). Writes to `misa` are ignored, which the
+    /// spec permits for a fixed ISA.
+    pub fn write(&mut self, csr: u16, val: u32) -> bool {
+        match csr {
+            CSR_MSTATUS => self.mstatus = val,
+            CSR_MISA => {}
+            CSR_MIE => self.mie = val,
+            CSR_MTVEC => self.mtvec = val,
+            CSR_MSCRATCH => self.mscratch = val,
+            // IALIGN = 16 with the C extension: bit 0 is always zero
+            CSR_MEPC => self.mepc = val & !1,
+            CSR_MCAUSE => self.mcause = val,
+            CSR_MTVAL => self.mtval = val,
+            // Pending machine interrupts are read-only; devices drive them
+            CSR_MIP => self.mip = (self.mip & MIP_EXTERNAL) | (val & !MIP_EXTERNAL),
+            _ => return false,
+        }
+        true
+    }
+}
+
+/// PC after reset unless configured otherwise, as on many RV32 platforms.
+pub const DEFAULT_RESET_VECTOR: u32 = 0x8000_0000;
+
+#[derive(Debug)]
 pub struct RiscV {
     pub x: [u32; 32], // x0..x31. x0 is correctly hardwired to 0 in logic.
     pub pc: u32,
+    pub csrs: Csrs,
+    /// Where `reset` starts execution. Chip-specific; firmware loaded through
+    /// `Machine::load_firmware` starts at its entry point instead.
+    pub reset_vector: u32,
+    /// Interrupt lines driven by devices such as the CLINT, as `mip` bits.
+    mip_lines: Arc<AtomicU32>,
+}
+
+impl Default for RiscV {
+    fn default() -> Self {
+        Self {
+            x: [0; 32],
+            pc: 0,
+            csrs: Csrs::default(),
+            reset_vector: DEFAULT_RESET_VECTOR,
+            mip_lines: Arc::new(AtomicU32::new(0)),
+        }
+    }
 }
 
 impl RiscV {
@@ -19,6 +128,11 @@ impl RiscV {
         Self::default()
     }
 
+    /// Connect the device-driven `mip` bits (MSIP, MTIP, MEIP).
+    pub fn set_shared_mip(&mut self, lines: Arc<AtomicU32>) {
+        self.mip_lines = lines;
+    }
+
     fn read_reg(&self, n: u8) -> u32 {
         if n == 0 {
             0
@@ -32,12 +146,81 @@ impl RiscV {
             self.x[n as usize] = val;
         }
     }
+
+    fn csr_read(&self, csr: u16) -> SimResult<u32> {
+        self.csrs.read(csr).ok_or_else(|| self.illegal_csr(csr))
+    }
+
+    /// Read CSR `csr` into `rd`, then write `new` to it if set. An access
+    /// to an unimplemented CSR, or a write to a read-only one, is illegal.
+    fn csr_access(&mut self, rd: u8, csr: u16, new: Option<u32>) -> SimResult<()> {
+        let old = self.csr_read(csr)?;
+        if let Some(val) = new {
+            if !self.csrs.write(csr, val) {
+                return Err(self.illegal_csr(csr));
+            }
+        }
+        self.write_reg(rd, old);
+        Ok(())
+    }
+
+    /// Take a trap: save the PC of the trapping (or, for an interrupt, the
+    /// next) instruction in `mepc`, record `cause`, mask interrupts and
+    /// continue at the `mtvec` base. Exceptions always use the base address,
+    /// even in vectored mode.
+    fn trap(&mut self, cause: u32, tval: u32) -> u32 {
+        let csrs = &mut self.csrs;
+        csrs.mepc = self.pc;
+        csrs.mcause = cause;
+        csrs.mtval = tval;
+        let mie = csrs.mstatus & MSTATUS_MIE != 0;
+        csrs.mstatus &= !(MSTATUS_MIE | MSTATUS_MPIE);
+        csrs.mstatus |= MSTATUS_MPP_M | if mie { MSTATUS_MPIE } else { 0 };
+        csrs.mtvec & !3
+    }
+
+    /// Highest-priority interrupt that is pending, enabled in `mie` and not
+    /// masked by `mstatus.MIE`.
+    fn pending_interrupt(&self) -> Option<u32> {
+        if self.csrs.mstatus & MSTATUS_MIE == 0 {
+            return None;
+        }
+        let pending = self.csrs.mip & self.csrs.mie;
+        [IRQ_MEI, IRQ_MSI, IRQ_MTI]
+            .into_iter()
+            .find(|&code| pending & (1 << code) != 0)
+    }
+
+    /// Take interrupt `code`. Vectored mode (`mtvec` MODE = 1) jumps to
+    /// base + 4 * code.
+    fn interrupt(&mut self, code: u32) -> u32 {
+        let base = self.trap(CAUSE_INTERRUPT | code, 0);
+        if self.csrs.mtvec & 3 == 1 {
+            base.wrapping_add(4 * code)
+        } else {
+            base
+        }
+    }
+
+    /// Return from a trap: resume at `mepc` and restore the interrupt
+    /// enable saved on entry.
+    fn mret(&mut self) -> u32 {
+        let csrs = &mut self.csrs;
+        let mpie = csrs.mstatus & MSTATUS_MPIE != 0;
+        csrs.mstatus &= !MSTATUS_MIE;
+        csrs.mstatus |= MSTATUS_MPIE | if mpie { MSTATUS_MIE } else { 0 };
+        csrs.mepc
+    }
+
+    fn illegal_csr(&self, csr: u16) -> crate::SimulationError {
+        tracing::error!("Illegal access to CSR {:#05x} at {:#x}", csr, self.pc);
+        crate::SimulationError::DecodeError(self.pc as u64)
+    }
 }
 
 impl Cpu for RiscV {
     fn reset(&mut self, _bus: &mut dyn Bus) -> SimResult<()> {
-        self.pc = 0x8000_0000; // Typical RISC-V Reset Vector (varies by platform)
-                               // x0..x31 are 0 by Default
+        self.pc = self.reset_vector;
         Ok(())
     }
 
@@ -46,21 +229,37 @@ impl Cpu for RiscV {
         bus: &mut dyn Bus,
         observers: &[Arc<dyn SimulationObserver>],
     ) -> SimResult<()> {
-        let opcode = bus.read_u32(self.pc as u64)?;
+        let lines = self.mip_lines.load(Ordering::SeqCst) & MIP_EXTERNAL;
+        self.csrs.mip = (self.csrs.mip & !MIP_EXTERNAL) | lines;
+        if let Some(code) = self.pending_interrupt() {
+            tracing::debug!("Taking interrupt {} at {:#x}", code, self.pc);
+            self.pc = self.interrupt(code);
+            return Ok(());
+        }
 
+        let half = bus.read_u16(self.pc as u64)?;
+        let (opcode, instruction, len) = if is_compressed(half) {
+            (half as u32, decode_rv32c(half), 2)
+        } else {
+            let opcode = bus.read_u32(self.pc as u64)?;
+            (opcode, decode_rv32(opcode), 4)
+        };
+
+        let category = InstructionCategory::riscv(&instruction);
         for observer in observers {
             observer.on_step_start(self.pc, opcode);
+            observer.on_instruction(category);
         }
 
-        let instruction = decode_rv32(opcode);
         tracing::debug!(
+            target: crate::INSTRUCTION_TRACE_TARGET,
             "PC={:#x}, Op={:#08x}, Instr={:?}",
             self.pc,
             opcode,
             instruction
         );
 
-        let mut next_pc = self.pc.wrapping_add(4);
+        let mut next_pc = self.pc.wrapping_add(len);
 
         match instruction {
             Instruction::Lui { rd, imm } => {
@@ -72,13 +271,13 @@ impl Cpu for RiscV {
             }
             Instruction::Jal { rd, imm } => {
                 let target = self.pc.wrapping_add(imm as u32);
-                self.write_reg(rd, self.pc.wrapping_add(4));
+                self.write_reg(rd, self.pc.wrapping_add(len));
                 next_pc = target;
             }
             Instruction::Jalr { rd, rs1, imm } => {
                 let base = self.read_reg(rs1);
                 let target = base.wrapping_add(imm as u32) & !1;
-                self.write_reg(rd, self.pc.wrapping_add(4));
+                self.write_reg(rd, self.pc.wrapping_add(len));
                 next_pc = target;
             }
             Instruction::Beq { rs1, rs2, imm } => {
@@ -249,14 +448,45 @@ impl Cpu for RiscV {
             Instruction::Fence => {
                 // No-op in single threaded core model
             }
-            Instruction::Ecall | Instruction::Ebreak => {
-                // Should trap. For now, we can just log or halt.
-                // Minimal implementation: no-op but maybe set a flag?
-                tracing::warn!("ECALL/EBREAK encountered at {:#x}", self.pc);
+            Instruction::Ecall => {
+                next_pc = self.trap(CAUSE_ECALL_FROM_M, 0);
+            }
+            Instruction::Ebreak => {
+                next_pc = self.trap(CAUSE_BREAKPOINT, self.pc);
+            }
+            Instruction::Mret => {
+                next_pc = self.mret();
+            }
+            Instruction::Csrrw { rd, rs1, csr } => {
+                let val = self.read_reg(rs1);
+                self.csr_access(rd, csr, Some(val))?;
+            }
+            Instruction::Csrrs { rd, rs1, csr } => {
+                let old = self.csr_read(csr)?;
+                let new = (rs1 != 0).then(|| old | self.read_reg(rs1));
+                self.csr_access(rd, csr, new)?;
+            }
+            Instruction::Csrrc { rd, rs1, csr } => {
+                let old = self.csr_read(csr)?;
+                let new = (rs1 != 0).then(|| old & !self.read_reg(rs1));
+                self.csr_access(rd, csr, new)?;
+            }
+            Instruction::Csrrwi { rd, uimm, csr } => {
+                self.csr_access(rd, csr, Some(uimm as u32))?;
+            }
+            Instruction::Csrrsi { rd, uimm, csr } => {
+                let old = self.csr_read(csr)?;
+                let new = (uimm != 0).then_some(old | uimm as u32);
+                self.csr_access(rd, csr, new)?;
+            }
+            Instruction::Csrrci { rd, uimm, csr } => {
+                let old = self.csr_read(csr)?;
+                let new = (uimm != 0).then_some(old & !(uimm as u32));
+                self.csr_access(rd, csr, new)?;
             }
             Instruction::Unknown(inst) => {
                 tracing::error!("Unknown instruction {:#x} at {:#x}", inst, self.pc);
-                return Err(crate::SimulationError::DecodeError(self.pc as u64));
+                return Err(crate::SimulationError::UndefinedInstruction(self.pc as u64));
             }
         }
 
@@ -277,6 +507,14 @@ impl Cpu for RiscV {
         // TODO: RISC-V Interrupts
     }
 
+    fn exception_pending(&self) -> bool {
+        self.pending_interrupt().is_some()
+    }
+
+    fn register_count(&self) -> u8 {
+        33
+    }
+
     fn get_register(&self, id: u8) -> u32 {
         if id < 32 {
             self.read_reg(id)
@@ -294,12 +532,33 @@ impl Cpu for RiscV {
         }
     }
 
+    // There is no xPSR; register 16 is x16
+    fn xpsr(&self) -> u32 {
+        0
+    }
+    fn set_xpsr(&mut self, _val: u32) {}
+
     fn snapshot(&self) -> crate::snapshot::CpuSnapshot {
         crate::snapshot::CpuSnapshot::RiscV(crate::snapshot::RiscVCpuSnapshot {
             registers: self.x.to_vec(),
             pc: self.pc,
+            csrs: self.csrs,
         })
     }
+
+    fn restore(&mut self, snapshot: &crate::snapshot::CpuSnapshot) -> anyhow::Result<()> {
+        let crate::snapshot::CpuSnapshot::RiscV(s) = snapshot else {
+            anyhow::bail!("cannot restore a non-RISC-V CPU snapshot into a RISC-V core");
+        };
+        if s.registers.len() != 32 {
+            anyhow::bail!("expected 32 RISC-V registers, got {}", s.registers.len());
+        }
+        self.x.copy_from_slice(&s.registers);
+        self.x[0] = 0;
+        self.pc = s.pc;
+        self.csrs = s.csrs;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -327,6 +586,237 @@ mod tests {
         assert_eq!(machine.cpu.pc, 4);
     }
 
+    fn program(words: &[u32]) -> SystemBus {
+        let mut bus = SystemBus::new();
+        bus.flash.data = words.iter().flat_map(|w| w.to_le_bytes()).collect();
+        bus
+    }
+
+    #[test]
+    fn test_riscv_compressed_instructions_advance_pc_by_two() {
+        let code: [u16; 9] = [
+            0x4505, // 0x00: c.li a0, 1
+            0x0515, // 0x02: c.addi a0, 5
+            0xA019, // 0x04: c.j 0x0a
+            0x4501, // 0x06: c.li a0, 0 (skipped)
+            0x0001, // 0x08: c.nop (skipped)
+            0x41D0, // 0x0a: c.lw a2, 4(a1)
+            0x0693, // 0x0c: addi a3, zero, 7 (32-bit, 2-byte aligned)
+            0x0070, 0x2011, // 0x10: c.jal 0x14
+        ];
+        let mut bus = SystemBus::new();
+        bus.flash.data = code.iter().flat_map(|h| h.to_le_bytes()).collect();
+        bus.write_u32(0x2000_0004, 0xCAFE_F00D).unwrap();
+        let mut cpu = RiscV::new();
+        cpu.x[11] = 0x2000_0000;
+        let mut machine = Machine::new(cpu, bus);
+
+        machine.step().unwrap();
+        assert_eq!(machine.cpu.pc, 0x02);
+        machine.step().unwrap();
+        assert_eq!(machine.cpu.read_reg(10), 6);
+        machine.step().unwrap();
+        assert_eq!(machine.cpu.pc, 0x0A);
+        machine.step().unwrap();
+        assert_eq!(machine.cpu.read_reg(12), 0xCAFE_F00D);
+        assert_eq!(machine.cpu.pc, 0x0C);
+        machine.step().unwrap();
+        assert_eq!(machine.cpu.read_reg(13), 7);
+        assert_eq!(machine.cpu.pc, 0x10);
+        machine.step().unwrap();
+        assert_eq!(machine.cpu.read_reg(1), 0x12, "link is pc + 2");
+        assert_eq!(machine.cpu.pc, 0x14);
+        assert_eq!(machine.cpu.read_reg(10), 6);
+    }
+
+    #[test]
+    fn test_riscv_load_firmware_starts_at_entry_point() {
+        let mut image = crate::memory::ProgramImage::new(0x2000_0100, crate::Arch::RiscV);
+        let code = 0x0050_0093u32.to_le_bytes().to_vec(); // addi x1, x0, 5
+        image.add_segment(0x2000_0100, code, 4);
+        let mut machine = Machine::new(RiscV::new(), SystemBus::new());
+
+        machine.load_firmware(&image).unwrap();
+        assert_eq!(machine.cpu.pc, 0x2000_0100);
+        machine.step().unwrap();
+        assert_eq!(machine.cpu.read_reg(1), 5);
+
+        machine.reset().unwrap();
+        assert_eq!(machine.cpu.pc, 0x2000_0100);
+    }
+
+    #[test]
+    fn test_riscv_reset_uses_reset_vector() {
+        let mut cpu = RiscV::new();
+        assert_eq!(cpu.reset_vector, DEFAULT_RESET_VECTOR);
+        cpu.reset_vector = 0x1000;
+        let mut machine = Machine::new(cpu, SystemBus::new());
+
+        machine.reset().unwrap();
+        assert_eq!(machine.cpu.pc, 0x1000);
+    }
+
+    #[test]
+    fn test_riscv_csr_write_read_back_mtvec() {
+        let bus = program(&[
+            0x3055_12F3, // csrrw t0, mtvec, a0
+            0x3050_2373, // csrrs t1, mtvec, zero
+            0x3050_2073, // csrrs zero, mtvec, zero
+            0x3004_63F3, // csrrsi t2, mstatus, 8
+            0xF140_2073, // csrrs zero, mhartid, zero (read-only, not written)
+        ]);
+        let mut cpu = RiscV::new();
+        cpu.x[5] = 0xDEAD;
+        cpu.x[10] = 0x8000_0101;
+        let mut machine = Machine::new(cpu, bus);
+        for _ in 0..5 {
+            machine.step().unwrap();
+        }
+
+        assert_eq!(machine.cpu.read_reg(5), 0); // old mtvec
+        assert_eq!(machine.cpu.read_reg(6), 0x8000_0101);
+        assert_eq!(machine.cpu.csrs.mtvec, 0x8000_0101);
+        assert_eq!(machine.cpu.read_reg(7), 0);
+        assert_eq!(machine.cpu.csrs.mstatus, 8);
+        assert_eq!(machine.cpu.pc, 20);
+    }
+
+    #[test]
+    fn test_riscv_ecall_traps_to_mtvec_and_mret_returns() {
+        let mut words = vec![
+            0x0200_0293, // 0x00: addi t0, zero, 0x20
+            0x3052_9073, // 0x04: csrrw zero, mtvec, t0
+            0x0000_0073, // 0x08: ecall
+            0x0000_0073, // 0x0c: ecall
+            0x0010_0593, // 0x10: addi a1, zero, 1
+        ];
+        words.resize(8, 0x0000_0013); // nop up to the handler
+        words.extend([
+            0x0015_0513, // 0x20: addi a0, a0, 1
+            0x3410_2373, // 0x24: csrrs t1, mepc, zero
+            0x0043_0313, // 0x28: addi t1, t1, 4
+            0x3413_1073, // 0x2c: csrrw zero, mepc, t1
+            0x3020_0073, // 0x30: mret
+        ]);
+        let mut machine = Machine::new(RiscV::new(), program(&words));
+
+        for _ in 0..3 {
+            machine.step().unwrap();
+        }
+        assert_eq!(machine.cpu.pc, 0x20);
+        assert_eq!(machine.cpu.csrs.mepc, 0x08);
+        assert_eq!(machine.cpu.csrs.mcause, CAUSE_ECALL_FROM_M);
+
+        for _ in 0..12 {
+            machine.step().unwrap();
+        }
+        assert_eq!(machine.cpu.read_reg(10), 2); // handler ran twice
+        assert_eq!(machine.cpu.read_reg(11), 1);
+        assert_eq!(machine.cpu.pc, 0x14);
+    }
+
+    #[test]
+    fn test_riscv_ebreak_masks_interrupts_until_mret() {
+        let bus = program(&[
+            0x0010_0073, // 0x00: ebreak
+            0x3020_0073, // 0x04: mret
+        ]);
+        let mut cpu = RiscV::new();
+        cpu.csrs.mtvec = 0x04;
+        cpu.csrs.mstatus = MSTATUS_MIE;
+        let mut machine = Machine::new(cpu, bus);
+
+        machine.step().unwrap();
+        assert_eq!(machine.cpu.pc, 0x04);
+        assert_eq!(machine.cpu.csrs.mcause, CAUSE_BREAKPOINT);
+        assert_eq!(machine.cpu.csrs.mtval, 0x00);
+        assert_eq!(
+            machine.cpu.csrs.mstatus,
+            MSTATUS_MPIE | MSTATUS_MPP_M,
+            "MIE saved in MPIE and cleared"
+        );
+
+        machine.step().unwrap();
+        assert_eq!(machine.cpu.pc, 0x00);
+        assert_eq!(machine.cpu.csrs.mstatus & MSTATUS_MIE, MSTATUS_MIE);
+    }
+
+    #[test]
+    fn test_riscv_clint_timer_interrupt_runs_handler() {
+        let mut words = vec![
+            0x0400_0293, // 0x00: addi t0, zero, 0x40
+            0x3052_9073, // 0x04: csrrw zero, mtvec, t0
+            0x0200_4337, // 0x08: lui t1, 0x02004     ; t1 = mtimecmp
+            0x00A0_0393, // 0x0c: addi t2, zero, 10
+            0x0073_2023, // 0x10: sw t2, 0(t1)
+            0x0003_2223, // 0x14: sw zero, 4(t1)     ; mtimecmp = 10
+            0x0800_0293, // 0x18: addi t0, zero, 0x80
+            0x3042_A073, // 0x1c: csrrs zero, mie, t0 ; MTIE
+            0x3004_6073, // 0x20: csrrsi zero, mstatus, 8 ; MIE
+            0x0000_006F, // 0x24: j 0x24
+        ];
+        words.resize(16, 0x0000_0013); // nop up to the handler
+        words.extend([
+            0x0015_0513, // 0x40: addi a0, a0, 1
+            0xFFF0_0393, // 0x44: addi t2, zero, -1
+            0x0073_2223, // 0x48: sw t2, 4(t1)       ; push mtimecmp out
+            0x3020_0073, // 0x4c: mret
+        ]);
+        let mut bus = program(&words);
+        let cpu = crate::system::riscv::configure_riscv(&mut bus);
+        let mut machine = Machine::new(cpu, bus);
+
+        // mtime reaches 10 as the loop is entered
+        for _ in 0..10 {
+            machine.step().unwrap();
+        }
+        assert_eq!(machine.cpu.pc, 0x24);
+        assert_eq!(machine.cpu.read_reg(10), 0);
+
+        machine.step().unwrap();
+        assert_eq!(machine.cpu.pc, 0x40);
+        assert_eq!(machine.cpu.csrs.mcause, CAUSE_INTERRUPT | IRQ_MTI);
+        assert_eq!(machine.cpu.csrs.mepc, 0x24);
+        assert_eq!(machine.cpu.csrs.mstatus & MSTATUS_MIE, 0);
+
+        for _ in 0..4 {
+            machine.step().unwrap();
+        }
+        assert_eq!(machine.cpu.pc, 0x24);
+        assert_eq!(machine.cpu.read_reg(10), 1);
+
+        // The interrupt is level-triggered and now deasserted
+        for _ in 0..20 {
+            machine.step().unwrap();
+        }
+        assert_eq!(machine.cpu.read_reg(10), 1);
+        assert_eq!(machine.cpu.csrs.mip & (1 << IRQ_MTI), 0);
+    }
+
+    #[test]
+    fn test_riscv_unknown_opcode_is_undefined_instruction() {
+        let mut machine = Machine::new(RiscV::new(), program(&[0xFFFF_FFFF]));
+        assert!(matches!(
+            machine.step(),
+            Err(crate::SimulationError::UndefinedInstruction(0))
+        ));
+    }
+
+    #[test]
+    fn test_riscv_illegal_csr_access_is_decode_error() {
+        // csrrw zero, mhartid, a0: write to a read-only CSR
+        let mut machine = Machine::new(RiscV::new(), program(&[0xF145_1073]));
+        assert!(matches!(
+            machine.step(),
+            Err(crate::SimulationError::DecodeError(0))
+        ));
+
+        // csrrs t0, 0x7c0, zero: unimplemented CSR
+        let mut machine = Machine::new(RiscV::new(), program(&[0x7C00_22F3]));
+        assert!(machine.step().is_err());
+        assert_eq!(machine.cpu.pc, 0);
+    }
+
     #[test]
     fn test_riscv_beq_taken() {
         let mut bus = SystemBus::new();
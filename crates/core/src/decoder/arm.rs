@@ -4,6 +4,8 @@
 // This software is released under the MIT License.
 // See the LICENSE file in the project root for full license information.
 
+use std::fmt;
+
 #[derive(Debug, PartialEq, Eq)]
 pub enum Instruction {
     Nop,
@@ -31,6 +33,7 @@ pub enum Instruction {
     AddRegHigh { rd: u8, rm: u8 }, // ADD Rd, Rm (at least one high register)
     Cpsie,                         // CPSIE i
     Cpsid,                         // CPSID i
+    Bkpt { imm: u8 },              // BKPT #imm
 
     And { rd: u8, rm: u8 }, // AND Rd, Rm
     Orr { rd: u8, rm: u8 }, // ORR Rd, Rm
@@ -104,6 +107,169 @@ pub enum Instruction {
     Prefix32(u16),
 }
 
+/// UAL-style register names (`sp`, `lr`, `pc` for R13-R15).
+fn reg(r: u8) -> String {
+    match r {
+        13 => "sp".to_string(),
+        14 => "lr".to_string(),
+        15 => "pc".to_string(),
+        _ => format!("r{}", r),
+    }
+}
+
+/// Register list for PUSH/POP/LDM/STM: low registers from the bitmask, plus
+/// `extra` (LR or PC) when present.
+fn reg_list(registers: u8, extra: Option<u8>) -> String {
+    let names: Vec<String> = (0..8)
+        .filter(|i| registers & (1 << i) != 0)
+        .chain(extra)
+        .map(reg)
+        .collect();
+    format!("{{{}}}", names.join(", "))
+}
+
+fn cond_suffix(cond: u8) -> &'static str {
+    const CONDS: [&str; 15] = [
+        "eq", "ne", "cs", "cc", "mi", "pl", "vs", "vc", "hi", "ls", "ge", "lt", "gt", "le", "",
+    ];
+    CONDS.get(cond as usize).copied().unwrap_or("")
+}
+
+/// Formats the instruction as UAL assembly, e.g. `movs r0, #1`.
+///
+/// Branch targets are printed as offsets from the PC value the instruction
+/// observes (its address + 4), since the instruction alone has no address.
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use Instruction::*;
+        match *self {
+            Nop => write!(f, "nop"),
+            MovImm { rd, imm } => write!(f, "movs {}, #{}", reg(rd), imm),
+            Branch { offset } => write!(f, "b #{}", offset),
+            BranchCond { cond, offset } => write!(f, "b{} #{}", cond_suffix(cond), offset),
+
+            AddReg { rd, rn, rm } => write!(f, "adds {}, {}, {}", reg(rd), reg(rn), reg(rm)),
+            AddImm3 { rd, rn, imm } => write!(f, "adds {}, {}, #{}", reg(rd), reg(rn), imm),
+            AddImm8 { rd, imm } => write!(f, "adds {}, #{}", reg(rd), imm),
+            SubReg { rd, rn, rm } => write!(f, "subs {}, {}, {}", reg(rd), reg(rn), reg(rm)),
+            SubImm3 { rd, rn, imm } => write!(f, "subs {}, {}, #{}", reg(rd), reg(rn), imm),
+            SubImm8 { rd, imm } => write!(f, "subs {}, #{}", reg(rd), imm),
+
+            CmpImm { rn, imm } => write!(f, "cmp {}, #{}", reg(rn), imm),
+            CmpReg { rn, rm } => write!(f, "cmp {}, {}", reg(rn), reg(rm)),
+            MovReg { rd, rm } => write!(f, "mov {}, {}", reg(rd), reg(rm)),
+            Movw { rd, imm } => write!(f, "movw {}, #{:#x}", reg(rd), imm),
+            Movt { rd, imm } => write!(f, "movt {}, #{:#x}", reg(rd), imm),
+
+            AddSp { imm } => write!(f, "add sp, #{}", imm),
+            SubSp { imm } => write!(f, "sub sp, #{}", imm),
+            AddRegHigh { rd, rm } => write!(f, "add {}, {}", reg(rd), reg(rm)),
+            Cpsie => write!(f, "cpsie i"),
+            Cpsid => write!(f, "cpsid i"),
+            Bkpt { imm } => write!(f, "bkpt #{}", imm),
+
+            And { rd, rm } => write!(f, "ands {}, {}", reg(rd), reg(rm)),
+            Orr { rd, rm } => write!(f, "orrs {}, {}", reg(rd), reg(rm)),
+            Eor { rd, rm } => write!(f, "eors {}, {}", reg(rd), reg(rm)),
+            Mvn { rd, rm } => write!(f, "mvns {}, {}", reg(rd), reg(rm)),
+
+            Lsl { rd, rm, imm } => write!(f, "lsls {}, {}, #{}", reg(rd), reg(rm), imm),
+            Lsr { rd, rm, imm } => write!(f, "lsrs {}, {}, #{}", reg(rd), reg(rm), imm),
+            Asr { rd, rm, imm } => write!(f, "asrs {}, {}, #{}", reg(rd), reg(rm), imm),
+
+            LdrImm { rt, rn, imm } => write!(f, "ldr {}, [{}, #{}]", reg(rt), reg(rn), imm),
+            StrImm { rt, rn, imm } => write!(f, "str {}, [{}, #{}]", reg(rt), reg(rn), imm),
+            LdrLit { rt, imm } => write!(f, "ldr {}, [pc, #{}]", reg(rt), imm),
+            LdrbImm { rt, rn, imm } => write!(f, "ldrb {}, [{}, #{}]", reg(rt), reg(rn), imm),
+            StrbImm { rt, rn, imm } => write!(f, "strb {}, [{}, #{}]", reg(rt), reg(rn), imm),
+            LdrhImm { rt, rn, imm } => write!(f, "ldrh {}, [{}, #{}]", reg(rt), reg(rn), imm),
+            StrhImm { rt, rn, imm } => write!(f, "strh {}, [{}, #{}]", reg(rt), reg(rn), imm),
+
+            Push { registers, m } => write!(f, "push {}", reg_list(registers, m.then_some(14))),
+            Pop { registers, p } => write!(f, "pop {}", reg_list(registers, p.then_some(15))),
+            Ldm { rn, registers } => write!(f, "ldm {}!, {}", reg(rn), reg_list(registers, None)),
+            Stm { rn, registers } => write!(f, "stm {}!, {}", reg(rn), reg_list(registers, None)),
+
+            Cbz { rn, imm } => write!(f, "cbz {}, #{}", reg(rn), imm),
+            Cbnz { rn, imm } => write!(f, "cbnz {}, #{}", reg(rn), imm),
+            Bl { offset } => write!(f, "bl #{}", offset),
+            Bx { rm } => write!(f, "bx {}", reg(rm)),
+            Mul { rd, rn } => write!(f, "muls {}, {}, {}", reg(rd), reg(rn), reg(rd)),
+
+            LdrSp { rt, imm } => write!(f, "ldr {}, [sp, #{}]", reg(rt), imm),
+            StrSp { rt, imm } => write!(f, "str {}, [sp, #{}]", reg(rt), imm),
+            AddSpReg { rd, imm } => write!(f, "add {}, sp, #{}", reg(rd), imm),
+
+            Uxtb { rd, rm } => write!(f, "uxtb {}, {}", reg(rd), reg(rm)),
+            Adr { rd, imm } => write!(f, "adr {}, #{}", reg(rd), imm),
+            AsrReg { rd, rm } => write!(f, "asrs {}, {}", reg(rd), reg(rm)),
+            LdrReg { rt, rn, rm } => write!(f, "ldr {}, [{}, {}]", reg(rt), reg(rn), reg(rm)),
+            Rsbs { rd, rn } => write!(f, "rsbs {}, {}, #0", reg(rd), reg(rn)),
+
+            Bfi { rd, rn, lsb, width } => {
+                write!(f, "bfi {}, {}, #{}, #{}", reg(rd), reg(rn), lsb, width)
+            }
+            Bfc { rd, lsb, width } => write!(f, "bfc {}, #{}, #{}", reg(rd), lsb, width),
+            Sbfx { rd, rn, lsb, width } => {
+                write!(f, "sbfx {}, {}, #{}, #{}", reg(rd), reg(rn), lsb, width)
+            }
+            Ubfx { rd, rn, lsb, width } => {
+                write!(f, "ubfx {}, {}, #{}, #{}", reg(rd), reg(rn), lsb, width)
+            }
+
+            Clz { rd, rm } => write!(f, "clz {}, {}", reg(rd), reg(rm)),
+            Rbit { rd, rm } => write!(f, "rbit {}, {}", reg(rd), reg(rm)),
+            Rev { rd, rm } => write!(f, "rev {}, {}", reg(rd), reg(rm)),
+            Rev16 { rd, rm } => write!(f, "rev16 {}, {}", reg(rd), reg(rm)),
+            RevSh { rd, rm } => write!(f, "revsh {}, {}", reg(rd), reg(rm)),
+
+            DataProc32 {
+                op,
+                rn,
+                rd,
+                rm,
+                imm5,
+                shift_type,
+                set_flags,
+            } => {
+                let s = if set_flags { "s" } else { "" };
+                let shift = match (shift_type, imm5) {
+                    (0, 0) => String::new(),
+                    (3, 0) => ", rrx".to_string(),
+                    _ => {
+                        let kinds = ["lsl", "lsr", "asr", "ror"];
+                        let amount = if imm5 == 0 { 32 } else { imm5 };
+                        format!(", {} #{}", kinds[shift_type as usize & 3], amount)
+                    }
+                };
+                match (op, rn) {
+                    (0x2, 15) => write!(f, "mov{}.w {}, {}{}", s, reg(rd), reg(rm), shift),
+                    (0x3, 15) => write!(f, "mvn{}.w {}, {}{}", s, reg(rd), reg(rm), shift),
+                    _ => {
+                        let name = match op {
+                            0x0 => "and",
+                            0x1 => "bic",
+                            0x2 => "orr",
+                            0x3 => "orn",
+                            0x4 => "eor",
+                            0x8 => "add",
+                            0xA => "adc",
+                            0xB => "sbc",
+                            0xD => "sub",
+                            0xE => "rsb",
+                            _ => return write!(f, "<dp32 op {:#x}>", op),
+                        };
+                        let (rd, rn, rm) = (reg(rd), reg(rn), reg(rm));
+                        write!(f, "{}{}.w {}, {}, {}{}", name, s, rd, rn, rm, shift)
+                    }
+                }
+            }
+
+            Unknown(opcode) | Prefix32(opcode) => write!(f, ".inst 0x{:04x}", opcode),
+        }
+    }
+}
+
 /// Decodes a 16-bit Thumb instruction
 pub fn decode_thumb_16(opcode: u16) -> Instruction {
     // 0. Shift (immediate), add, subtract, move, and compare
@@ -383,6 +549,13 @@ pub fn decode_thumb_16(opcode: u16) -> Instruction {
             }
         }
 
+        // BKPT (T1): 1011 1110 iiii iiii
+        if (opcode & 0xFF00) == 0xBE00 {
+            return Instruction::Bkpt {
+                imm: (opcode & 0xFF) as u8,
+            };
+        }
+
         // HINT/IT (T1): 1011 1111 ...
         if (opcode & 0xFF00) == 0xBF00 {
             return Instruction::Nop;
@@ -428,6 +601,272 @@ pub fn decode_thumb_16(opcode: u16) -> Instruction {
 
 
 
+/// Whether a 32-bit Thumb instruction starting with `h1` is in the
+/// coprocessor space (`111x 11xx`: 0xECxx-0xEFxx and 0xFCxx-0xFFxx), which
+/// holds the VFP instructions.
+pub fn is_coprocessor(h1: u16) -> bool {
+    (h1 & 0xEC00) == 0xEC00
+}
+
+/// Single-precision VFP instructions in the coprocessor space. `s*`
+/// operands are S-register numbers; `vd` in the loads and stores is a
+/// D-register number when `double` is set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VfpInstruction {
+    Vadd { sd: u8, sn: u8, sm: u8 }, // VADD.F32 Sd, Sn, Sm
+    Vsub { sd: u8, sn: u8, sm: u8 }, // VSUB.F32 Sd, Sn, Sm
+    Vmul { sd: u8, sn: u8, sm: u8 }, // VMUL.F32 Sd, Sn, Sm
+    Vdiv { sd: u8, sn: u8, sm: u8 }, // VDIV.F32 Sd, Sn, Sm
+    Vabs { sd: u8, sm: u8 },         // VABS.F32 Sd, Sm
+    Vneg { sd: u8, sm: u8 },         // VNEG.F32 Sd, Sm
+    Vsqrt { sd: u8, sm: u8 },        // VSQRT.F32 Sd, Sm
+    VmovReg { sd: u8, sm: u8 },      // VMOV.F32 Sd, Sm
+    VmovImm { sd: u8, imm: u32 },    // VMOV.F32 Sd, #imm (expanded to f32 bits)
+    VmovToCore { rt: u8, sn: u8 },   // VMOV Rt, Sn
+    VmovFromCore { sn: u8, rt: u8 }, // VMOV Sn, Rt
+    Vldr { vd: u8, rn: u8, offset: i32, double: bool }, // VLDR Sd/Dd, [Rn, #offset]
+    Vstr { vd: u8, rn: u8, offset: i32, double: bool }, // VSTR Sd/Dd, [Rn, #offset]
+    Vldm { vd: u8, rn: u8, regs: u8, double: bool, db: bool, writeback: bool }, // VLDM{IA,DB} Rn{!}, {list}; VPOP is VLDMIA SP!
+    Vstm { vd: u8, rn: u8, regs: u8, double: bool, db: bool, writeback: bool }, // VSTM{IA,DB} Rn{!}, {list}; VPUSH is VSTMDB SP!
+    VcvtToFloat { sd: u8, sm: u8, signed: bool }, // VCVT.F32.S32/U32 Sd, Sm
+    VcvtToInt { sd: u8, sm: u8, signed: bool, round_zero: bool }, // VCVT{R}.S32/U32.F32 Sd, Sm
+    Vcmp { sd: u8, sm: Option<u8> }, // VCMP.F32 Sd, Sm (None: #0.0)
+    Vmrs { rt: u8 },                 // VMRS Rt, FPSCR (Rt 15: APSR_nzcv)
+    Vmsr { rt: u8 },                 // VMSR FPSCR, Rt
+}
+
+impl fmt::Display for VfpInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use VfpInstruction::*;
+        let vreg = |double: bool, n: u8| format!("{}{}", if double { 'd' } else { 's' }, n);
+        match *self {
+            Vadd { sd, sn, sm } => write!(f, "vadd.f32 s{}, s{}, s{}", sd, sn, sm),
+            Vsub { sd, sn, sm } => write!(f, "vsub.f32 s{}, s{}, s{}", sd, sn, sm),
+            Vmul { sd, sn, sm } => write!(f, "vmul.f32 s{}, s{}, s{}", sd, sn, sm),
+            Vdiv { sd, sn, sm } => write!(f, "vdiv.f32 s{}, s{}, s{}", sd, sn, sm),
+            Vabs { sd, sm } => write!(f, "vabs.f32 s{}, s{}", sd, sm),
+            Vneg { sd, sm } => write!(f, "vneg.f32 s{}, s{}", sd, sm),
+            Vsqrt { sd, sm } => write!(f, "vsqrt.f32 s{}, s{}", sd, sm),
+            VmovReg { sd, sm } => write!(f, "vmov.f32 s{}, s{}", sd, sm),
+            VmovImm { sd, imm } => write!(f, "vmov.f32 s{}, #{}", sd, f32::from_bits(imm)),
+            VmovToCore { rt, sn } => write!(f, "vmov {}, s{}", reg(rt), sn),
+            VmovFromCore { sn, rt } => write!(f, "vmov s{}, {}", sn, reg(rt)),
+            Vldr {
+                vd,
+                rn,
+                offset,
+                double,
+            } => {
+                write!(f, "vldr {}, [{}, #{}]", vreg(double, vd), reg(rn), offset)
+            }
+            Vstr {
+                vd,
+                rn,
+                offset,
+                double,
+            } => {
+                write!(f, "vstr {}, [{}, #{}]", vreg(double, vd), reg(rn), offset)
+            }
+            Vldm {
+                vd,
+                rn,
+                regs,
+                double,
+                db,
+                writeback,
+            }
+            | Vstm {
+                vd,
+                rn,
+                regs,
+                double,
+                db,
+                writeback,
+            } => {
+                let load = matches!(self, Vldm { .. });
+                let mut list = vreg(double, vd);
+                if regs > 1 {
+                    list = format!("{}-{}", list, vreg(double, vd + regs - 1));
+                }
+                if rn == 13 && writeback && db != load {
+                    let op = if load { "vpop" } else { "vpush" };
+                    return write!(f, "{} {{{}}}", op, list);
+                }
+                let op = if load { "vldm" } else { "vstm" };
+                let mode = if db { "db" } else { "ia" };
+                let wb = if writeback { "!" } else { "" };
+                write!(f, "{}{} {}{}, {{{}}}", op, mode, reg(rn), wb, list)
+            }
+            VcvtToFloat { sd, sm, signed } => {
+                let from = if signed { "s32" } else { "u32" };
+                write!(f, "vcvt.f32.{} s{}, s{}", from, sd, sm)
+            }
+            VcvtToInt {
+                sd,
+                sm,
+                signed,
+                round_zero,
+            } => {
+                let r = if round_zero { "" } else { "r" };
+                let to = if signed { "s32" } else { "u32" };
+                write!(f, "vcvt{}.{}.f32 s{}, s{}", r, to, sd, sm)
+            }
+            Vcmp { sd, sm: Some(sm) } => write!(f, "vcmp.f32 s{}, s{}", sd, sm),
+            Vcmp { sd, sm: None } => write!(f, "vcmp.f32 s{}, #0.0", sd),
+            Vmrs { rt: 15 } => write!(f, "vmrs APSR_nzcv, fpscr"),
+            Vmrs { rt } => write!(f, "vmrs {}, fpscr", reg(rt)),
+            Vmsr { rt } => write!(f, "vmsr fpscr, {}", reg(rt)),
+        }
+    }
+}
+
+/// VFPExpandImm for single precision: `abcdefgh` becomes
+/// `a:NOT(b):bbbbb:cd:efgh:0...0`.
+fn vfp_expand_imm(imm8: u32) -> u32 {
+    let sign = (imm8 >> 7) & 1;
+    let b = (imm8 >> 6) & 1;
+    let exponent = ((b ^ 1) << 7) | (if b != 0 { 0x7C } else { 0 }) | ((imm8 >> 4) & 3);
+    (sign << 31) | (exponent << 23) | ((imm8 & 0xF) << 19)
+}
+
+/// Decodes the VFP instructions `VfpInstruction` covers from a coprocessor
+/// space instruction (see `is_coprocessor`); anything else, including
+/// double-precision arithmetic, is `None`.
+pub fn decode_vfp(h1: u16, h2: u16) -> Option<VfpInstruction> {
+    use VfpInstruction::*;
+    // cp10 is single precision, cp11 double
+    let coproc = (h2 >> 8) & 0xF;
+    if coproc & 0xE != 0xA {
+        return None;
+    }
+    let double = coproc == 0xB;
+    let d = ((h1 >> 6) & 1) as u8;
+    let vd = ((h2 >> 12) & 0xF) as u8;
+    let sd = (vd << 1) | d;
+    let sn = (((h1 & 0xF) as u8) << 1) | ((h2 >> 7) & 1) as u8;
+    let sm = (((h2 & 0xF) as u8) << 1) | ((h2 >> 5) & 1) as u8;
+
+    // VLDM / VSTM: 1110 110P UDWL Rn with P:U:W 010 or 011 (IA), 101 (DB)
+    let (p, u, w) = (h1 & 0x0100 != 0, h1 & 0x0080 != 0, h1 & 0x0020 != 0);
+    if (h1 & 0xFE00) == 0xEC00 && ((!p && u) || (p && !u && w)) {
+        let imm8 = (h2 & 0xFF) as u8;
+        // An odd count in the double form is the obsolete FLDMX/FSTMX
+        if double && imm8 & 1 != 0 {
+            return None;
+        }
+        let (vd, regs, bank) = if double {
+            ((d << 4) | vd, imm8 / 2, 16)
+        } else {
+            (sd, imm8, 32)
+        };
+        if regs == 0 || vd + regs > bank {
+            return None;
+        }
+        let rn = (h1 & 0xF) as u8;
+        let (db, writeback) = (p, w);
+        return Some(if (h1 & 0x0010) != 0 {
+            Vldm {
+                vd,
+                rn,
+                regs,
+                double,
+                db,
+                writeback,
+            }
+        } else {
+            Vstm {
+                vd,
+                rn,
+                regs,
+                double,
+                db,
+                writeback,
+            }
+        });
+    }
+
+    // VLDR / VSTR: 1110 1101 UD0L Rn
+    if (h1 & 0xFF20) == 0xED00 {
+        let imm = ((h2 & 0xFF) as i32) << 2;
+        let offset = if (h1 & 0x0080) != 0 { imm } else { -imm };
+        let rn = (h1 & 0xF) as u8;
+        let vd = if double { (d << 4) | vd } else { sd };
+        // FPv4-SP has only D0-D15
+        if double && vd >= 16 {
+            return None;
+        }
+        return Some(if (h1 & 0x0010) != 0 {
+            Vldr {
+                vd,
+                rn,
+                offset,
+                double,
+            }
+        } else {
+            Vstr {
+                vd,
+                rn,
+                offset,
+                double,
+            }
+        });
+    }
+
+    if (h1 & 0xFF00) != 0xEE00 || double {
+        return None;
+    }
+
+    // Transfers between core and extension registers: Rt 1010 N001 0000
+    if (h2 & 0x0010) != 0 {
+        if (h2 & 0x006F) != 0 {
+            return None;
+        }
+        let rt = ((h2 >> 12) & 0xF) as u8;
+        return match h1 & 0x00FF {
+            0xE1 => Some(Vmsr { rt }),
+            0xF1 => Some(Vmrs { rt }),
+            op if op & 0xF0 == 0x00 => Some(VmovFromCore { sn, rt }),
+            op if op & 0xF0 == 0x10 => Some(VmovToCore { rt, sn }),
+            _ => None,
+        };
+    }
+
+    // Data processing: opc1 is h1 bits 7, 5:4; opc3 h2 bits 7:6
+    let op = (h2 & 0x0040) != 0;
+    match (h1 & 0x00B0, op) {
+        (0x20, false) => Some(Vmul { sd, sn, sm }),
+        (0x30, false) => Some(Vadd { sd, sn, sm }),
+        (0x30, true) => Some(Vsub { sd, sn, sm }),
+        (0x80, false) => Some(Vdiv { sd, sn, sm }),
+        (0xB0, false) => {
+            let imm8 = (((h1 & 0xF) << 4) | (h2 & 0xF)) as u32;
+            Some(VmovImm {
+                sd,
+                imm: vfp_expand_imm(imm8),
+            })
+        }
+        (0xB0, true) => {
+            let bit7 = (h2 & 0x0080) != 0;
+            match (h1 & 0xF, bit7) {
+                (0x0, false) => Some(VmovReg { sd, sm }),
+                (0x0, true) => Some(Vabs { sd, sm }),
+                (0x1, false) => Some(Vneg { sd, sm }),
+                (0x1, true) => Some(Vsqrt { sd, sm }),
+                (0x4, _) => Some(Vcmp { sd, sm: Some(sm) }),
+                (0x5, _) => Some(Vcmp { sd, sm: None }),
+                (0x8, signed) => Some(VcvtToFloat { sd, sm, signed }),
+                (opc2 @ (0xC | 0xD), round_zero) => Some(VcvtToInt {
+                    sd,
+                    sm,
+                    signed: opc2 == 0xD,
+                    round_zero,
+                }),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
 /// Decodes a 32-bit Thumb instruction (requires two 16-bit halfwords)
 pub fn decode_thumb_32(h1: u16, h2: u16) -> Instruction {
     // 32-bit Thumb instruction encoding:
@@ -523,9 +962,43 @@ pub fn decode_thumb_32(h1: u16, h2: u16) -> Instruction {
                 0x9 => return Instruction::Rev16 { rd, rm },
                 0xA => return Instruction::Rbit { rd, rm },
                 0xB => return Instruction::RevSh { rd, rm },
-                0xC => return Instruction::Clz { rd, rm },
                 _ => {}
             }
+        } else if (h1 & 0xFFF0) == 0xFAB0 && ((h2 >> 4) & 0xF) == 0x8 {
+            // FABm: CLZ
+            return Instruction::Clz { rd, rm };
+        }
+    }
+
+    // B.W (T4) / BL (T1): offset relative to PC + 4
+    if (h1 & 0xF800) == 0xF000 && (h2 & 0x9000) == 0x9000 {
+        let s = ((h1 >> 10) & 0x1) as i32;
+        let i1 = !(((h2 >> 13) & 0x1) as i32 ^ s) & 0x1;
+        let i2 = !(((h2 >> 11) & 0x1) as i32 ^ s) & 0x1;
+        let imm10 = (h1 & 0x3FF) as i32;
+        let imm11 = (h2 & 0x7FF) as i32;
+        let mut offset = (s << 24) | (i1 << 23) | (i2 << 22) | (imm10 << 12) | (imm11 << 1);
+        if s != 0 {
+            offset |= !0x01FF_FFFF;
+        }
+        if (h2 & 0x1000) != 0 {
+            return Instruction::Bl { offset };
+        } else {
+            return Instruction::Branch { offset };
+        }
+    }
+
+    // MOVW / MOVT (T1)
+    if (h1 & 0xFBF0) == 0xF240 || (h1 & 0xFBF0) == 0xF2C0 {
+        let i = (h1 >> 10) & 0x1;
+        let imm4 = h1 & 0xF;
+        let imm3 = (h2 >> 12) & 0x7;
+        let rd = ((h2 >> 8) & 0xF) as u8;
+        let imm = (imm4 << 12) | (i << 11) | (imm3 << 8) | (h2 & 0xFF);
+        if (h1 & 0x0080) != 0 {
+            return Instruction::Movt { rd, imm };
+        } else {
+            return Instruction::Movw { rd, imm };
         }
     }
 
@@ -620,6 +1093,20 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_decode_clz() {
+        // CLZ R1, R0 is in the FABx group, not beside REV
+        assert_eq!(
+            decode_thumb_32(0xFAB0, 0xF180),
+            Instruction::Clz { rd: 1, rm: 0 }
+        );
+        // The old placement, op2 0b1100 among REV/RBIT, is not CLZ
+        assert!(!matches!(
+            decode_thumb_32(0xFA90, 0xF1C0),
+            Instruction::Clz { .. }
+        ));
+    }
+
 
 
     #[test]
@@ -797,9 +1284,25 @@ mod tests {
         assert_eq!(decode_thumb_16(0xBF00), Instruction::Nop);
     }
 
+    #[test]
+    fn test_decode_bkpt() {
+        assert_eq!(decode_thumb_16(0xBEAB), Instruction::Bkpt { imm: 0xAB });
+        assert_eq!(decode_thumb_16(0xBE00).to_string(), "bkpt #0");
+    }
+
     #[test]
     fn test_decode_branch() {
         assert_eq!(decode_thumb_16(0xE002), Instruction::Branch { offset: 4 });
+        // imm11 extremes: +1023 and -1024 halfwords
+        assert_eq!(
+            decode_thumb_16(0xE3FF),
+            Instruction::Branch { offset: 2046 }
+        );
+        assert_eq!(
+            decode_thumb_16(0xE400),
+            Instruction::Branch { offset: -2048 }
+        );
+        assert_eq!(decode_thumb_16(0xE7FE), Instruction::Branch { offset: -4 });
     }
 
     #[test]
@@ -883,4 +1386,72 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn test_decode_bl_and_movw() {
+        // BL +6 (0x42 -> 0x4C): F000 F803
+        assert_eq!(decode_thumb_32(0xF000, 0xF803), Instruction::Bl { offset: 6 });
+        // BL -8: F7FF FFFC
+        assert_eq!(decode_thumb_32(0xF7FF, 0xFFFC), Instruction::Bl { offset: -8 });
+        // MOVW R0, #0x1234 / MOVT R0, #0x1234
+        assert_eq!(
+            decode_thumb_32(0xF241, 0x2034),
+            Instruction::Movw { rd: 0, imm: 0x1234 }
+        );
+        assert_eq!(
+            decode_thumb_32(0xF2C1, 0x2034),
+            Instruction::Movt { rd: 0, imm: 0x1234 }
+        );
+    }
+
+    #[test]
+    fn test_display_mnemonics() {
+        let cases = [
+            (decode_thumb_16(0x2001), "movs r0, #1"),
+            (decode_thumb_16(0xB500), "push {lr}"),
+            (decode_thumb_16(0xBD10), "pop {r4, pc}"),
+            (decode_thumb_16(0x4770), "bx lr"),
+            (decode_thumb_16(0xE7FE), "b #-4"),
+            (decode_thumb_16(0xD0FB), "beq #-10"),
+            (decode_thumb_16(0x7901), "ldrb r1, [r0, #4]"),
+            (decode_thumb_16(0x466F), "mov r7, sp"),
+            (decode_thumb_32(0xF000, 0xF803), "bl #6"),
+            (decode_thumb_32(0xF241, 0x2034), "movw r0, #0x1234"),
+            (decode_thumb_16(0x4080), ".inst 0x4080"),
+        ];
+        for (instruction, text) in cases {
+            assert_eq!(instruction.to_string(), text);
+        }
+    }
+
+    #[test]
+    fn test_decode_vfp() {
+        let cases = [
+            ((0xEE30, 0x1A20), "vadd.f32 s2, s0, s1"),
+            ((0xEE20, 0x0A81), "vmul.f32 s0, s1, s2"),
+            ((0xED90, 0x0A01), "vldr s0, [r0, #4]"),
+            ((0xED01, 0x1B02), "vstr d1, [r1, #-8]"),
+            ((0xEE11, 0x1A10), "vmov r1, s2"),
+            ((0xEEB7, 0x0A00), "vmov.f32 s0, #1"),
+            ((0xEEFD, 0x1AC1), "vcvt.s32.f32 s3, s2"),
+            ((0xEEB8, 0x0AC0), "vcvt.f32.s32 s0, s0"),
+            ((0xEEF1, 0xFA10), "vmrs APSR_nzcv, fpscr"),
+            ((0xED2D, 0x8A10), "vpush {s16-s31}"),
+            ((0xECBD, 0x8A10), "vpop {s16-s31}"),
+            ((0xED20, 0x8A10), "vstmdb r0!, {s16-s31}"),
+            ((0xEC90, 0x0A01), "vldmia r0, {s0}"),
+            ((0xECA1, 0x4B08), "vstmia r1!, {d4-d7}"),
+        ];
+        for ((h1, h2), text) in cases {
+            assert_eq!(decode_vfp(h1, h2).unwrap().to_string(), text);
+        }
+        // Double-precision arithmetic is not covered
+        assert_eq!(decode_vfp(0xEE30, 0x0B01), None);
+        // FSTMX, and a register list running past s31
+        assert_eq!(decode_vfp(0xED2D, 0x8B03), None);
+        assert_eq!(decode_vfp(0xED6D, 0xFA02), None);
+        // VLDR and VSTR of d16, past the FPv4-SP register bank
+        assert_eq!(decode_vfp(0xEDD0, 0x0B00), None);
+        assert_eq!(decode_vfp(0xEDC0, 0x0B00), None);
+    }
 }
@@ -9,3 +9,141 @@ pub mod riscv;
 
 pub use arm::decode_thumb_16;
 pub use arm::Instruction as ArmInstruction;
+
+/// Broad class of an executed instruction, reported through
+/// `SimulationObserver::on_instruction`.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, serde::Serialize, serde::Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum InstructionCategory {
+    /// Arithmetic, logic, shifts, moves, compares and bit-field operations.
+    Alu,
+    Multiply,
+    /// Loads, including `POP` and `LDM`.
+    Load,
+    /// Stores, including `PUSH` and `STM`.
+    Store,
+    Branch,
+    /// `SVC`, `BKPT`, `ECALL`, `EBREAK` and `MRET`.
+    Exception,
+    /// Hints, barriers, `CPS`, `IT`, `MSR`/`MRS` and CSR accesses.
+    System,
+    /// Floating-point and other coprocessor instructions.
+    Float,
+    /// Anything not decoded.
+    Other,
+}
+
+impl InstructionCategory {
+    /// Category of a 16-bit Thumb instruction (not `Prefix32`) decoded
+    /// from `opcode`.
+    pub fn thumb_16(instruction: &ArmInstruction, opcode: u16) -> Self {
+        use ArmInstruction::*;
+        match instruction {
+            Branch { .. }
+            | BranchCond { .. }
+            | Cbz { .. }
+            | Cbnz { .. }
+            | Bl { .. }
+            | Bx { .. } => Self::Branch,
+            LdrImm { .. }
+            | LdrLit { .. }
+            | LdrbImm { .. }
+            | LdrhImm { .. }
+            | Pop { .. }
+            | Ldm { .. }
+            | LdrSp { .. }
+            | LdrReg { .. } => Self::Load,
+            StrImm { .. }
+            | StrbImm { .. }
+            | StrhImm { .. }
+            | Push { .. }
+            | Stm { .. }
+            | StrSp { .. } => Self::Store,
+            Mul { .. } => Self::Multiply,
+            Nop | Cpsie | Cpsid => Self::System,
+            Bkpt { .. } => Self::Exception,
+            Unknown(_) | Prefix32(_) => match opcode >> 8 {
+                0xDF => Self::Exception, // SVC
+                0xBF => Self::System,    // IT and hints
+                _ => Self::Other,
+            },
+            _ => Self::Alu,
+        }
+    }
+
+    /// Category of the 32-bit Thumb instruction `h1:h2`, from its encoding
+    /// group (ARMv7-M ARM A5.3).
+    pub fn thumb_32(h1: u16, h2: u16) -> Self {
+        let op1 = (h1 >> 11) & 3;
+        let op2 = (h1 >> 4) & 0x7F;
+        let load = h1 & (1 << 4) != 0;
+        match op1 {
+            1 if op2 & 0x64 == 0x00 || op2 & 0x64 == 0x04 => {
+                // TBB/TBH live among the load/store dual and exclusive encodings
+                if h1 & 0xFFF0 == 0xE8D0 && h2 & 0xFFE0 == 0xF000 {
+                    Self::Branch
+                } else if load {
+                    Self::Load
+                } else {
+                    Self::Store
+                }
+            }
+            1 if op2 & 0x60 == 0x20 => Self::Alu,
+            2 if h2 & 0x8000 == 0 => Self::Alu,
+            // Conditional branch encoding space with cond 111x: misc control
+            2 if h2 & 0x5000 == 0 && (h1 >> 7) & 7 == 7 => Self::System,
+            2 => Self::Branch,
+            3 if op2 & 0x71 == 0x00 => Self::Store,
+            3 if op2 & 0x61 == 0x01 => Self::Load,
+            3 if op2 & 0x70 == 0x20 => Self::Alu,
+            3 if op2 & 0x70 == 0x30 => Self::Multiply,
+            1 | 3 => Self::Float,
+            _ => Self::Other,
+        }
+    }
+
+    pub fn riscv(instruction: &riscv::Instruction) -> Self {
+        use riscv::Instruction::*;
+        match instruction {
+            Jal { .. }
+            | Jalr { .. }
+            | Beq { .. }
+            | Bne { .. }
+            | Blt { .. }
+            | Bge { .. }
+            | Bltu { .. }
+            | Bgeu { .. } => Self::Branch,
+            Lb { .. } | Lh { .. } | Lw { .. } | Lbu { .. } | Lhu { .. } => Self::Load,
+            Sb { .. } | Sh { .. } | Sw { .. } => Self::Store,
+            Ecall | Ebreak | Mret => Self::Exception,
+            Fence
+            | Csrrw { .. }
+            | Csrrs { .. }
+            | Csrrc { .. }
+            | Csrrwi { .. }
+            | Csrrsi { .. }
+            | Csrrci { .. } => Self::System,
+            Unknown(_) => Self::Other,
+            _ => Self::Alu,
+        }
+    }
+}
+
+impl std::fmt::Display for InstructionCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Alu => "alu",
+            Self::Multiply => "multiply",
+            Self::Load => "load",
+            Self::Store => "store",
+            Self::Branch => "branch",
+            Self::Exception => "exception",
+            Self::System => "system",
+            Self::Float => "float",
+            Self::Other => "other",
+        };
+        f.pad(name)
+    }
+}
@@ -4,49 +4,57 @@
 // This software is released under the MIT License.
 // See the LICENSE file in the project root for full license information.
 
-/// RISC-V RV32I Base Integer Instruction Set
+/// RISC-V RV32I Base Integer Instruction Set. Compressed (RV32C)
+/// instructions decode to the base instruction they expand to.
 #[derive(Debug, PartialEq, Eq)]
 pub enum Instruction {
-    Lui { rd: u8, imm: u32 },            // LUI rd, imm
-    Auipc { rd: u8, imm: u32 },          // AUIPC rd, imm
-    Jal { rd: u8, imm: i32 },            // JAL rd, offset
-    Jalr { rd: u8, rs1: u8, imm: i32 },  // JALR rd, rs1, offset
-    Beq { rs1: u8, rs2: u8, imm: i32 },  // BEQ rs1, rs2, offset
-    Bne { rs1: u8, rs2: u8, imm: i32 },  // BNE rs1, rs2, offset
-    Blt { rs1: u8, rs2: u8, imm: i32 },  // BLT rs1, rs2, offset
-    Bge { rs1: u8, rs2: u8, imm: i32 },  // BGE rs1, rs2, offset
-    Bltu { rs1: u8, rs2: u8, imm: i32 }, // BLTU rs1, rs2, offset
-    Bgeu { rs1: u8, rs2: u8, imm: i32 }, // BGEU rs1, rs2, offset
-    Lb { rd: u8, rs1: u8, imm: i32 },    // LB rd, offset(rs1)
-    Lh { rd: u8, rs1: u8, imm: i32 },    // LH rd, offset(rs1)
-    Lw { rd: u8, rs1: u8, imm: i32 },    // LW rd, offset(rs1)
-    Lbu { rd: u8, rs1: u8, imm: i32 },   // LBU rd, offset(rs1)
-    Lhu { rd: u8, rs1: u8, imm: i32 },   // LHU rd, offset(rs1)
-    Sb { rs1: u8, rs2: u8, imm: i32 },   // SB rs2, offset(rs1)
-    Sh { rs1: u8, rs2: u8, imm: i32 },   // SH rs2, offset(rs1)
-    Sw { rs1: u8, rs2: u8, imm: i32 },   // SW rs2, offset(rs1)
-    Addi { rd: u8, rs1: u8, imm: i32 },  // ADDI rd, rs1, imm
-    Slti { rd: u8, rs1: u8, imm: i32 },  // SLTI rd, rs1, imm
-    Sltiu { rd: u8, rs1: u8, imm: i32 }, // SLTIU rd, rs1, imm
-    Xori { rd: u8, rs1: u8, imm: i32 },  // XORI rd, rs1, imm
-    Ori { rd: u8, rs1: u8, imm: i32 },   // ORI rd, rs1, imm
-    Andi { rd: u8, rs1: u8, imm: i32 },  // ANDI rd, rs1, imm
-    Slli { rd: u8, rs1: u8, shamt: u8 }, // SLLI rd, rs1, shamt
-    Srli { rd: u8, rs1: u8, shamt: u8 }, // SRLI rd, rs1, shamt
-    Srai { rd: u8, rs1: u8, shamt: u8 }, // SRAI rd, rs1, shamt
-    Add { rd: u8, rs1: u8, rs2: u8 },    // ADD rd, rs1, rs2
-    Sub { rd: u8, rs1: u8, rs2: u8 },    // SUB rd, rs1, rs2
-    Sll { rd: u8, rs1: u8, rs2: u8 },    // SLL rd, rs1, rs2
-    Slt { rd: u8, rs1: u8, rs2: u8 },    // SLT rd, rs1, rs2
-    Sltu { rd: u8, rs1: u8, rs2: u8 },   // SLTU rd, rs1, rs2
-    Xor { rd: u8, rs1: u8, rs2: u8 },    // XOR rd, rs1, rs2
-    Srl { rd: u8, rs1: u8, rs2: u8 },    // SRL rd, rs1, rs2
-    Sra { rd: u8, rs1: u8, rs2: u8 },    // SRA rd, rs1, rs2
-    Or { rd: u8, rs1: u8, rs2: u8 },     // OR rd, rs1, rs2
-    And { rd: u8, rs1: u8, rs2: u8 },    // AND rd, rs1, rs2
-    Fence,                               // FENCE
-    Ecall,                               // ECALL
-    Ebreak,                              // EBREAK
+    Lui { rd: u8, imm: u32 },              // LUI rd, imm
+    Auipc { rd: u8, imm: u32 },            // AUIPC rd, imm
+    Jal { rd: u8, imm: i32 },              // JAL rd, offset
+    Jalr { rd: u8, rs1: u8, imm: i32 },    // JALR rd, rs1, offset
+    Beq { rs1: u8, rs2: u8, imm: i32 },    // BEQ rs1, rs2, offset
+    Bne { rs1: u8, rs2: u8, imm: i32 },    // BNE rs1, rs2, offset
+    Blt { rs1: u8, rs2: u8, imm: i32 },    // BLT rs1, rs2, offset
+    Bge { rs1: u8, rs2: u8, imm: i32 },    // BGE rs1, rs2, offset
+    Bltu { rs1: u8, rs2: u8, imm: i32 },   // BLTU rs1, rs2, offset
+    Bgeu { rs1: u8, rs2: u8, imm: i32 },   // BGEU rs1, rs2, offset
+    Lb { rd: u8, rs1: u8, imm: i32 },      // LB rd, offset(rs1)
+    Lh { rd: u8, rs1: u8, imm: i32 },      // LH rd, offset(rs1)
+    Lw { rd: u8, rs1: u8, imm: i32 },      // LW rd, offset(rs1)
+    Lbu { rd: u8, rs1: u8, imm: i32 },     // LBU rd, offset(rs1)
+    Lhu { rd: u8, rs1: u8, imm: i32 },     // LHU rd, offset(rs1)
+    Sb { rs1: u8, rs2: u8, imm: i32 },     // SB rs2, offset(rs1)
+    Sh { rs1: u8, rs2: u8, imm: i32 },     // SH rs2, offset(rs1)
+    Sw { rs1: u8, rs2: u8, imm: i32 },     // SW rs2, offset(rs1)
+    Addi { rd: u8, rs1: u8, imm: i32 },    // ADDI rd, rs1, imm
+    Slti { rd: u8, rs1: u8, imm: i32 },    // SLTI rd, rs1, imm
+    Sltiu { rd: u8, rs1: u8, imm: i32 },   // SLTIU rd, rs1, imm
+    Xori { rd: u8, rs1: u8, imm: i32 },    // XORI rd, rs1, imm
+    Ori { rd: u8, rs1: u8, imm: i32 },     // ORI rd, rs1, imm
+    Andi { rd: u8, rs1: u8, imm: i32 },    // ANDI rd, rs1, imm
+    Slli { rd: u8, rs1: u8, shamt: u8 },   // SLLI rd, rs1, shamt
+    Srli { rd: u8, rs1: u8, shamt: u8 },   // SRLI rd, rs1, shamt
+    Srai { rd: u8, rs1: u8, shamt: u8 },   // SRAI rd, rs1, shamt
+    Add { rd: u8, rs1: u8, rs2: u8 },      // ADD rd, rs1, rs2
+    Sub { rd: u8, rs1: u8, rs2: u8 },      // SUB rd, rs1, rs2
+    Sll { rd: u8, rs1: u8, rs2: u8 },      // SLL rd, rs1, rs2
+    Slt { rd: u8, rs1: u8, rs2: u8 },      // SLT rd, rs1, rs2
+    Sltu { rd: u8, rs1: u8, rs2: u8 },     // SLTU rd, rs1, rs2
+    Xor { rd: u8, rs1: u8, rs2: u8 },      // XOR rd, rs1, rs2
+    Srl { rd: u8, rs1: u8, rs2: u8 },      // SRL rd, rs1, rs2
+    Sra { rd: u8, rs1: u8, rs2: u8 },      // SRA rd, rs1, rs2
+    Or { rd: u8, rs1: u8, rs2: u8 },       // OR rd, rs1, rs2
+    And { rd: u8, rs1: u8, rs2: u8 },      // AND rd, rs1, rs2
+    Fence,                                 // FENCE
+    Ecall,                                 // ECALL
+    Ebreak,                                // EBREAK
+    Mret,                                  // MRET
+    Csrrw { rd: u8, rs1: u8, csr: u16 },   // CSRRW rd, csr, rs1
+    Csrrs { rd: u8, rs1: u8, csr: u16 },   // CSRRS rd, csr, rs1
+    Csrrc { rd: u8, rs1: u8, csr: u16 },   // CSRRC rd, csr, rs1
+    Csrrwi { rd: u8, uimm: u8, csr: u16 }, // CSRRWI rd, csr, uimm
+    Csrrsi { rd: u8, uimm: u8, csr: u16 }, // CSRRSI rd, csr, uimm
+    Csrrci { rd: u8, uimm: u8, csr: u16 }, // CSRRCI rd, csr, uimm
     Unknown(u32),
 }
 
@@ -193,20 +201,23 @@ pub fn decode_rv32(inst: u32) -> Instruction {
                 4 => Instruction::Xori { rd, rs1, imm },
                 6 => Instruction::Ori { rd, rs1, imm },
                 7 => Instruction::Andi { rd, rs1, imm },
-                1 => {
-                    // SLLI
-                    let shamt = (imm & 0x1F) as u8;
-                    Instruction::Slli { rd, rs1, shamt }
-                }
-                5 => {
-                    // SRLI/SRAI
-                    let shamt = (imm & 0x1F) as u8;
-                    if (imm & 0x400) != 0 {
-                        Instruction::Srai { rd, rs1, shamt }
-                    } else {
-                        Instruction::Srli { rd, rs1, shamt }
-                    }
-                }
+                // Shifts: imm[4:0] is shamt, imm[11:5] is funct7. On RV32 a
+                // set shamt[5] (bit 25) is reserved, as is any other funct7.
+                1 if funct7 == 0x00 => Instruction::Slli {
+                    rd,
+                    rs1,
+                    shamt: rs2,
+                },
+                5 if funct7 == 0x00 => Instruction::Srli {
+                    rd,
+                    rs1,
+                    shamt: rs2,
+                },
+                5 if funct7 == 0x20 => Instruction::Srai {
+                    rd,
+                    rs1,
+                    shamt: rs2,
+                },
                 _ => Instruction::Unknown(inst),
             }
         }
@@ -232,15 +243,606 @@ pub fn decode_rv32(inst: u32) -> Instruction {
         }
         0x73 => {
             // SYSTEM
+            let csr = (inst >> 20) as u16;
+            // The immediate forms encode a 5-bit zero-extended value in rs1
+            let uimm = rs1;
             match funct3 {
                 0 => match inst >> 20 {
                     0x000 => Instruction::Ecall,
                     0x001 => Instruction::Ebreak,
+                    0x302 => Instruction::Mret,
                     _ => Instruction::Unknown(inst),
                 },
-                _ => Instruction::Unknown(inst), // CSR instructions not implemented yet
+                1 => Instruction::Csrrw { rd, rs1, csr },
+                2 => Instruction::Csrrs { rd, rs1, csr },
+                3 => Instruction::Csrrc { rd, rs1, csr },
+                5 => Instruction::Csrrwi { rd, uimm, csr },
+                6 => Instruction::Csrrsi { rd, uimm, csr },
+                7 => Instruction::Csrrci { rd, uimm, csr },
+                _ => Instruction::Unknown(inst),
             }
         }
         _ => Instruction::Unknown(inst),
     }
 }
+
+/// Whether the instruction whose low halfword is `half` is a 16-bit
+/// compressed (RVC) instruction. 32-bit encodings have both low bits set.
+pub fn is_compressed(half: u16) -> bool {
+    half & 3 != 3
+}
+
+fn sign_extend(value: u32, bits: u32) -> i32 {
+    ((value << (32 - bits)) as i32) >> (32 - bits)
+}
+
+/// Decode a 16-bit RV32C instruction into the 32-bit instruction it
+/// expands to. Reserved encodings and those of extensions not modelled
+/// (floating point) are `Unknown`, holding the halfword.
+pub fn decode_rv32c(inst: u16) -> Instruction {
+    let inst = inst as u32;
+    let unknown = Instruction::Unknown(inst);
+    let funct3 = (inst >> 13) & 0x7;
+    // Full register fields (CR/CI/CSS formats)
+    let rd = ((inst >> 7) & 0x1F) as u8;
+    let rs2 = ((inst >> 2) & 0x1F) as u8;
+    // Compact register fields x8..x15 (CIW/CL/CS/CB formats)
+    let rd_c = 8 + ((inst >> 2) & 0x7) as u8;
+    let rs1_c = 8 + ((inst >> 7) & 0x7) as u8;
+    // imm[5|4:0] of CI: bit 12, bits 6:2
+    let imm6 = sign_extend(((inst >> 7) & 0x20) | ((inst >> 2) & 0x1F), 6);
+    // C.LW / C.SW: uimm[5:3|2|6] in bits 12:10, 6, 5
+    let uimm_w = ((inst >> 7) & 0x38) | ((inst >> 4) & 0x4) | ((inst << 1) & 0x40);
+
+    match (inst & 3, funct3) {
+        (0, 0) => {
+            // C.ADDI4SPN: nzuimm[5:4|9:6|2|3] in bits 12:5
+            let imm = ((inst >> 7) & 0x30)
+                | ((inst >> 1) & 0x3C0)
+                | ((inst >> 4) & 0x4)
+                | ((inst >> 2) & 0x8);
+            if imm == 0 {
+                return unknown;
+            }
+            Instruction::Addi {
+                rd: rd_c,
+                rs1: 2,
+                imm: imm as i32,
+            }
+        }
+        (0, 2) => Instruction::Lw {
+            rd: rd_c,
+            rs1: rs1_c,
+            imm: uimm_w as i32,
+        },
+        (0, 6) => Instruction::Sw {
+            rs1: rs1_c,
+            rs2: rd_c,
+            imm: uimm_w as i32,
+        },
+        // C.ADDI (C.NOP when rd = 0)
+        (1, 0) => Instruction::Addi {
+            rd,
+            rs1: rd,
+            imm: imm6,
+        },
+        (1, 1) => Instruction::Jal {
+            rd: 1,
+            imm: cj_offset(inst),
+        },
+        // C.LI
+        (1, 2) => Instruction::Addi {
+            rd,
+            rs1: 0,
+            imm: imm6,
+        },
+        (1, 3) if rd == 2 => {
+            // C.ADDI16SP: nzimm[9|4|6|8:7|5] in bits 12, 6, 5, 4:3, 2
+            let imm = ((inst >> 3) & 0x200)
+                | ((inst >> 2) & 0x10)
+                | ((inst << 1) & 0x40)
+                | ((inst << 4) & 0x180)
+                | ((inst << 3) & 0x20);
+            if imm == 0 {
+                return unknown;
+            }
+            Instruction::Addi {
+                rd: 2,
+                rs1: 2,
+                imm: sign_extend(imm, 10),
+            }
+        }
+        // C.LUI: nzimm[17|16:12]
+        (1, 3) if imm6 != 0 => Instruction::Lui {
+            rd,
+            imm: (imm6 << 12) as u32,
+        },
+        (1, 4) => {
+            let rd = rs1_c;
+            let rs2 = rd_c;
+            // RV32: shamt[5] (bit 12) must be zero
+            let shamt = imm6 as u8 & 0x1F;
+            let rv32_shamt = inst & (1 << 12) == 0;
+            match (inst >> 10) & 0x3 {
+                0 if rv32_shamt => Instruction::Srli { rd, rs1: rd, shamt },
+                1 if rv32_shamt => Instruction::Srai { rd, rs1: rd, shamt },
+                2 => Instruction::Andi {
+                    rd,
+                    rs1: rd,
+                    imm: imm6,
+                },
+                3 if inst & (1 << 12) == 0 => match (inst >> 5) & 0x3 {
+                    0 => Instruction::Sub { rd, rs1: rd, rs2 },
+                    1 => Instruction::Xor { rd, rs1: rd, rs2 },
+                    2 => Instruction::Or { rd, rs1: rd, rs2 },
+                    _ => Instruction::And { rd, rs1: rd, rs2 },
+                },
+                _ => unknown,
+            }
+        }
+        (1, 5) => Instruction::Jal {
+            rd: 0,
+            imm: cj_offset(inst),
+        },
+        (1, 6) => Instruction::Beq {
+            rs1: rs1_c,
+            rs2: 0,
+            imm: cb_offset(inst),
+        },
+        (1, 7) => Instruction::Bne {
+            rs1: rs1_c,
+            rs2: 0,
+            imm: cb_offset(inst),
+        },
+        (2, 0) if inst & (1 << 12) == 0 => Instruction::Slli {
+            rd,
+            rs1: rd,
+            shamt: rs2,
+        },
+        (2, 2) if rd != 0 => {
+            // C.LWSP: uimm[5|4:2|7:6] in bits 12, 6:4, 3:2
+            let imm = ((inst >> 7) & 0x20) | ((inst >> 2) & 0x1C) | ((inst << 4) & 0xC0);
+            Instruction::Lw {
+                rd,
+                rs1: 2,
+                imm: imm as i32,
+            }
+        }
+        (2, 4) => match (inst & (1 << 12) != 0, rd, rs2) {
+            (false, 0, 0) => unknown,
+            // C.JR
+            (false, rs1, 0) => Instruction::Jalr { rd: 0, rs1, imm: 0 },
+            // C.MV
+            (false, rd, rs2) => Instruction::Add { rd, rs1: 0, rs2 },
+            (true, 0, 0) => Instruction::Ebreak,
+            // C.JALR
+            (true, rs1, 0) => Instruction::Jalr { rd: 1, rs1, imm: 0 },
+            // C.ADD
+            (true, rd, rs2) => Instruction::Add { rd, rs1: rd, rs2 },
+        },
+        (2, 6) => {
+            // C.SWSP: uimm[5:2|7:6] in bits 12:9, 8:7
+            let imm = ((inst >> 7) & 0x3C) | ((inst >> 1) & 0xC0);
+            Instruction::Sw {
+                rs1: 2,
+                rs2,
+                imm: imm as i32,
+            }
+        }
+        _ => unknown,
+    }
+}
+
+/// C.J / C.JAL offset: imm[11|4|9:8|10|6|7|3:1|5] in bits 12:2.
+fn cj_offset(inst: u32) -> i32 {
+    let imm = ((inst >> 1) & 0x800)
+        | ((inst >> 7) & 0x10)
+        | ((inst >> 1) & 0x300)
+        | ((inst << 2) & 0x400)
+        | ((inst >> 1) & 0x40)
+        | ((inst << 1) & 0x80)
+        | ((inst >> 2) & 0xE)
+        | ((inst << 3) & 0x20);
+    sign_extend(imm, 12)
+}
+
+/// C.BEQZ / C.BNEZ offset: imm[8|4:3] in bits 12:10, imm[7:6|2:1|5] in bits 6:2.
+fn cb_offset(inst: u32) -> i32 {
+    let imm = ((inst >> 4) & 0x100)
+        | ((inst >> 7) & 0x18)
+        | ((inst << 1) & 0xC0)
+        | ((inst >> 2) & 0x6)
+        | ((inst << 3) & 0x20);
+    sign_extend(imm, 9)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Encodings are hand-assembled from the RV32I immediate layouts, covering
+    // each sign and the extremes of every format.
+
+    #[test]
+    fn test_decode_u_type() {
+        // lui a0, 0x12345
+        assert_eq!(
+            decode_rv32(0x1234_5537),
+            Instruction::Lui {
+                rd: 10,
+                imm: 0x1234_5000
+            }
+        );
+        // lui t0, 0xfffff
+        assert_eq!(
+            decode_rv32(0xFFFF_F2B7),
+            Instruction::Lui {
+                rd: 5,
+                imm: 0xFFFF_F000
+            }
+        );
+        // auipc ra, 0x1
+        assert_eq!(
+            decode_rv32(0x0000_1097),
+            Instruction::Auipc { rd: 1, imm: 0x1000 }
+        );
+    }
+
+    #[test]
+    fn test_decode_jal_immediate() {
+        let cases = [
+            (0x0000_006F, 0, 0),          // j .
+            (0xFFDF_F06F, 0, -4),         // j .-4
+            (0x0080_00EF, 1, 8),          // jal ra, .+8
+            (0x0010_02EF, 5, 0x800),      // jal t0, .+0x800 (imm[11])
+            (0x3441_256F, 10, 0x12344),   // jal a0, .+0x12344 (imm[19:12])
+            (0x80FF_F06F, 0, -2034),      // j .-2034
+            (0x7FFF_F0EF, 1, 0xF_FFFE),   // jal ra, max
+            (0x8000_006F, 0, -0x10_0000), // j min
+        ];
+        for (inst, rd, imm) in cases {
+            assert_eq!(
+                decode_rv32(inst),
+                Instruction::Jal { rd, imm },
+                "{:#010x}",
+                inst
+            );
+        }
+    }
+
+    #[test]
+    fn test_decode_branch_immediate() {
+        assert_eq!(
+            decode_rv32(0xFE00_0EE3), // beq zero, zero, .-4
+            Instruction::Beq {
+                rs1: 0,
+                rs2: 0,
+                imm: -4
+            }
+        );
+        assert_eq!(
+            decode_rv32(0x00B5_1463), // bne a0, a1, .+8
+            Instruction::Bne {
+                rs1: 10,
+                rs2: 11,
+                imm: 8
+            }
+        );
+        assert_eq!(
+            decode_rv32(0x7E62_CFE3), // blt t0, t1, max
+            Instruction::Blt {
+                rs1: 5,
+                rs2: 6,
+                imm: 4094
+            }
+        );
+        assert_eq!(
+            decode_rv32(0x8083_D063), // bge t2, s0, min
+            Instruction::Bge {
+                rs1: 7,
+                rs2: 8,
+                imm: -4096
+            }
+        );
+        assert_eq!(
+            decode_rv32(0x0020_E0E3), // bltu ra, sp, .+0x800 (imm[11])
+            Instruction::Bltu {
+                rs1: 1,
+                rs2: 2,
+                imm: 0x800
+            }
+        );
+        assert_eq!(
+            decode_rv32(0xFE41_FFE3), // bgeu gp, tp, .-2
+            Instruction::Bgeu {
+                rs1: 3,
+                rs2: 4,
+                imm: -2
+            }
+        );
+    }
+
+    #[test]
+    fn test_decode_s_type_immediate() {
+        assert_eq!(
+            decode_rv32(0x0011_2623), // sw ra, 12(sp)
+            Instruction::Sw {
+                rs1: 2,
+                rs2: 1,
+                imm: 12
+            }
+        );
+        assert_eq!(
+            decode_rv32(0xFE81_2E23), // sw s0, -4(sp)
+            Instruction::Sw {
+                rs1: 2,
+                rs2: 8,
+                imm: -4
+            }
+        );
+        assert_eq!(
+            decode_rv32(0x7EB5_0FA3), // sb a1, 2047(a0)
+            Instruction::Sb {
+                rs1: 10,
+                rs2: 11,
+                imm: 2047
+            }
+        );
+        assert_eq!(
+            decode_rv32(0x80D6_1023), // sh a3, -2048(a2)
+            Instruction::Sh {
+                rs1: 12,
+                rs2: 13,
+                imm: -2048
+            }
+        );
+    }
+
+    #[test]
+    fn test_decode_i_type_immediate() {
+        assert_eq!(
+            decode_rv32(0xFF01_0113), // addi sp, sp, -16
+            Instruction::Addi {
+                rd: 2,
+                rs1: 2,
+                imm: -16
+            }
+        );
+        assert_eq!(
+            decode_rv32(0x8005_A503), // lw a0, -2048(a1)
+            Instruction::Lw {
+                rd: 10,
+                rs1: 11,
+                imm: -2048
+            }
+        );
+        assert_eq!(
+            decode_rv32(0x7FF3_C303), // lbu t1, 2047(t2)
+            Instruction::Lbu {
+                rd: 6,
+                rs1: 7,
+                imm: 2047
+            }
+        );
+        assert_eq!(
+            decode_rv32(0x0000_8067), // ret
+            Instruction::Jalr {
+                rd: 0,
+                rs1: 1,
+                imm: 0
+            }
+        );
+        assert_eq!(
+            decode_rv32(0xFFF2_80E7), // jalr ra, -1(t0)
+            Instruction::Jalr {
+                rd: 1,
+                rs1: 5,
+                imm: -1
+            }
+        );
+    }
+
+    #[test]
+    fn test_decode_shift_immediate() {
+        assert_eq!(
+            decode_rv32(0x01F5_1513), // slli a0, a0, 31
+            Instruction::Slli {
+                rd: 10,
+                rs1: 10,
+                shamt: 31
+            }
+        );
+        assert_eq!(
+            decode_rv32(0x01F5_5513), // srli a0, a0, 31
+            Instruction::Srli {
+                rd: 10,
+                rs1: 10,
+                shamt: 31
+            }
+        );
+        assert_eq!(
+            decode_rv32(0x41F5_5513), // srai a0, a0, 31
+            Instruction::Srai {
+                rd: 10,
+                rs1: 10,
+                shamt: 31
+            }
+        );
+        // shamt[5] set: reserved on RV32
+        assert_eq!(decode_rv32(0x0205_1513), Instruction::Unknown(0x0205_1513));
+        assert_eq!(decode_rv32(0x4205_5513), Instruction::Unknown(0x4205_5513));
+        // Unassigned funct7
+        assert_eq!(decode_rv32(0x2005_5513), Instruction::Unknown(0x2005_5513));
+    }
+
+    #[test]
+    fn test_decode_csr_instructions() {
+        assert_eq!(
+            decode_rv32(0x3055_12F3), // csrrw t0, mtvec, a0
+            Instruction::Csrrw {
+                rd: 5,
+                rs1: 10,
+                csr: 0x305
+            }
+        );
+        assert_eq!(
+            decode_rv32(0x3000_2073), // csrrs zero, mstatus, zero
+            Instruction::Csrrs {
+                rd: 0,
+                rs1: 0,
+                csr: 0x300
+            }
+        );
+        assert_eq!(
+            decode_rv32(0x341F_F5F3), // csrrci a1, mepc, 31
+            Instruction::Csrrci {
+                rd: 11,
+                uimm: 31,
+                csr: 0x341
+            }
+        );
+        // funct3 = 4 is reserved
+        assert_eq!(decode_rv32(0x3000_4073), Instruction::Unknown(0x3000_4073));
+    }
+
+    #[test]
+    fn test_decode_trap_instructions() {
+        assert_eq!(decode_rv32(0x0000_0073), Instruction::Ecall);
+        assert_eq!(decode_rv32(0x0010_0073), Instruction::Ebreak);
+        assert_eq!(decode_rv32(0x3020_0073), Instruction::Mret);
+        // SRET is not implemented (M-mode only)
+        assert_eq!(decode_rv32(0x1020_0073), Instruction::Unknown(0x1020_0073));
+    }
+
+    #[test]
+    fn test_decode_compressed() {
+        assert!(is_compressed(0x4505));
+        assert!(!is_compressed(0x0693));
+        // c.addi a0, 5 / c.addi a0, -1
+        assert_eq!(
+            decode_rv32c(0x0515),
+            Instruction::Addi {
+                rd: 10,
+                rs1: 10,
+                imm: 5
+            }
+        );
+        assert_eq!(
+            decode_rv32c(0x157D),
+            Instruction::Addi {
+                rd: 10,
+                rs1: 10,
+                imm: -1
+            }
+        );
+        // c.li a1, 1
+        assert_eq!(
+            decode_rv32c(0x4585),
+            Instruction::Addi {
+                rd: 11,
+                rs1: 0,
+                imm: 1
+            }
+        );
+        // c.addi16sp sp, -16 / c.addi4spn s0, sp, 16
+        assert_eq!(
+            decode_rv32c(0x717D),
+            Instruction::Addi {
+                rd: 2,
+                rs1: 2,
+                imm: -16
+            }
+        );
+        assert_eq!(
+            decode_rv32c(0x0800),
+            Instruction::Addi {
+                rd: 8,
+                rs1: 2,
+                imm: 16
+            }
+        );
+        // c.lw a2, 4(a0) / c.sw a2, 8(a0)
+        assert_eq!(
+            decode_rv32c(0x4150),
+            Instruction::Lw {
+                rd: 12,
+                rs1: 10,
+                imm: 4
+            }
+        );
+        assert_eq!(
+            decode_rv32c(0xC510),
+            Instruction::Sw {
+                rs1: 10,
+                rs2: 12,
+                imm: 8
+            }
+        );
+        // c.lwsp ra, 12(sp) / c.swsp ra, 12(sp)
+        assert_eq!(
+            decode_rv32c(0x40B2),
+            Instruction::Lw {
+                rd: 1,
+                rs1: 2,
+                imm: 12
+            }
+        );
+        assert_eq!(
+            decode_rv32c(0xC606),
+            Instruction::Sw {
+                rs1: 2,
+                rs2: 1,
+                imm: 12
+            }
+        );
+        // c.j +6 / c.j -2 / c.jal +4 / c.beqz a0, +4
+        assert_eq!(decode_rv32c(0xA019), Instruction::Jal { rd: 0, imm: 6 });
+        assert_eq!(decode_rv32c(0xBFFD), Instruction::Jal { rd: 0, imm: -2 });
+        assert_eq!(decode_rv32c(0x2011), Instruction::Jal { rd: 1, imm: 4 });
+        assert_eq!(
+            decode_rv32c(0xC111),
+            Instruction::Beq {
+                rs1: 10,
+                rs2: 0,
+                imm: 4
+            }
+        );
+        // c.mv a0, a1 / c.jr ra / c.ebreak
+        assert_eq!(
+            decode_rv32c(0x852E),
+            Instruction::Add {
+                rd: 10,
+                rs1: 0,
+                rs2: 11
+            }
+        );
+        assert_eq!(
+            decode_rv32c(0x8082),
+            Instruction::Jalr {
+                rd: 0,
+                rs1: 1,
+                imm: 0
+            }
+        );
+        assert_eq!(decode_rv32c(0x9002), Instruction::Ebreak);
+        // c.srli a0, 1 / c.and a0, a1
+        assert_eq!(
+            decode_rv32c(0x8105),
+            Instruction::Srli {
+                rd: 10,
+                rs1: 10,
+                shamt: 1
+            }
+        );
+        assert_eq!(
+            decode_rv32c(0x8D6D),
+            Instruction::And {
+                rd: 10,
+                rs1: 10,
+                rs2: 11
+            }
+        );
+        // The all-zero halfword is defined to be illegal
+        assert_eq!(decode_rv32c(0x0000), Instruction::Unknown(0));
+    }
+}
@@ -4,7 +4,9 @@
 // This software is released under the MIT License.
 // See the LICENSE file in the project root for full license information.
 
+pub mod builder;
 pub mod bus;
+pub mod coverage;
 pub mod cpu;
 pub mod decoder;
 pub mod interrupt;
@@ -12,15 +14,28 @@ pub mod memory;
 pub mod metrics;
 pub mod multi_core;
 pub mod peripherals;
+pub mod policy;
+pub mod profile;
+pub mod rtt;
 pub mod signals;
 pub mod snapshot;
 pub mod system;
+pub mod trace;
+pub mod vcd;
+pub mod watchpoint;
+
+pub use builder::MachineBuilder;
+pub use memory::ProgramImage;
+pub use watchpoint::{WatchKind, Watchpoint};
 
 use std::any::Any;
 use std::sync::Arc;
 
 mod tests;
 
+/// `tracing` target of the per-instruction execution log.
+pub const INSTRUCTION_TRACE_TARGET: &str = "labwired::instructions";
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub enum Arch {
     Arm,
@@ -34,6 +49,25 @@ pub enum SimulationError {
     MemoryViolation(u64),
     #[error("Instruction decoding error at {0:#x}")]
     DecodeError(u64),
+    /// The firmware broke the machine's `ExecutionPolicy`: a denied write to
+    /// the address, or a denied instruction at it.
+    #[error("Execution policy violation at {0:#x}")]
+    PolicyViolation(u64),
+    /// An opcode the core does not implement, at the address.
+    #[error("Undefined instruction at {0:#x}")]
+    UndefinedInstruction(u64),
+    /// A trapped unaligned load or store to the address.
+    #[error("Unaligned access at {0:#x}")]
+    UnalignedAccess(u64),
+    /// A stack push to the address failed.
+    #[error("Stack overflow at {0:#x}")]
+    StackOverflow(u64),
+    /// A `BKPT #imm` at the address with no debugger attached to take it.
+    #[error("BKPT #{1} at {0:#x}")]
+    Breakpoint(u64, u8),
+    /// The machine could not be built, e.g. from an invalid chip descriptor.
+    #[error("Configuration error: {0}")]
+    Config(String),
 }
 
 pub type SimResult<T> = Result<T, SimulationError>;
@@ -51,21 +85,64 @@ pub struct DmaRequest {
     pub direction: DmaDirection,
 }
 
+/// Result of a single `Peripheral::tick`.
 #[derive(Debug, Clone, Default)]
 pub struct PeripheralTickResult {
+    /// Raise the IRQ line assigned to the peripheral on the bus.
     pub irq: bool,
     pub cycles: u32,
     pub dma_requests: Vec<DmaRequest>,
+    /// Additional IRQ numbers to pend this tick, independent of the bus line.
+    /// Lets one peripheral (e.g. a multi-channel DMA or EXTI) raise several
+    /// interrupts at once.
     pub explicit_irqs: Vec<u32>,
 }
 
+impl PeripheralTickResult {
+    /// All IRQ numbers raised by this tick, given the peripheral's bus IRQ line.
+    pub fn irqs(&self, line: Option<u32>) -> impl Iterator<Item = u32> + '_ {
+        line.filter(|_| self.irq)
+            .into_iter()
+            .chain(self.explicit_irqs.iter().copied())
+    }
+}
+
 /// Trait for observing simulation events in a modular way.
 pub trait SimulationObserver: std::fmt::Debug + Send + Sync {
     fn on_simulation_start(&self) {}
     fn on_simulation_stop(&self) {}
+    /// `opcode` is the fetched instruction; a 32-bit Thumb instruction has
+    /// its first halfword in the low 16 bits.
     fn on_step_start(&self, _pc: u32, _opcode: u32) {}
+    /// The instruction fetched at the start of the step decoded as
+    /// `category`; called before it executes.
+    fn on_instruction(&self, _category: decoder::InstructionCategory) {}
     fn on_step_end(&self, _cycles: u32) {}
     fn on_peripheral_tick(&self, _name: &str, _cycles: u32) {}
+    /// A GPIO port's output data register changed (or was first observed)
+    /// during the last step.
+    fn on_gpio_change(&self, _port: &str, _odr: u32) {}
+    /// Exception `irq` was raised by a peripheral or injected.
+    fn on_interrupt(&self, _irq: u32) {}
+    /// Exception `irq` (numbered as in `on_interrupt`) changed NVIC state:
+    /// enabled, disabled, pended or cleared through the NVIC registers
+    /// during the last step, or activated and deactivated by the core on
+    /// exception entry and return.
+    fn on_nvic_change(&self, _irq: u32, _event: peripherals::nvic::NvicEvent) {}
+    /// Whether to receive `on_registers_changed`. Tracking register changes
+    /// snapshots the core before every step, so it is only done while an
+    /// attached observer asks for it.
+    fn wants_register_changes(&self) -> bool {
+        false
+    }
+    /// Core registers (by `Cpu::get_register` id) whose value differs after
+    /// the last step, with their new values. Called after every step, with
+    /// an empty list if nothing changed, on observers that return true from
+    /// `wants_register_changes`.
+    fn on_registers_changed(&self, _changed: &[(u8, u32)]) {}
+    /// The last `Machine::step` finished: peripherals have ticked and every
+    /// other per-step callback has been made.
+    fn on_step_complete(&self) {}
 }
 
 /// Trait representing a CPU architecture
@@ -80,17 +157,87 @@ pub trait Cpu {
     fn get_pc(&self) -> u32;
     fn set_sp(&mut self, val: u32);
     fn set_exception_pending(&mut self, exception_num: u32);
+    /// Whether an exception or interrupt will be taken on the next step.
+    fn exception_pending(&self) -> bool {
+        false
+    }
 
     // Debug Access
+    /// Number of registers reachable through `get_register`, ids `0..count`.
+    fn register_count(&self) -> u8;
     fn get_register(&self, id: u8) -> u32;
     fn set_register(&mut self, id: u8, val: u32);
+    /// Registers `0..17` in one call; on Cortex-M r0-r12, SP, LR, PC and
+    /// xPSR, the order GDB uses.
+    fn registers(&self) -> [u32; 17] {
+        std::array::from_fn(|id| self.get_register(id as u8))
+    }
+    fn set_registers(&mut self, regs: &[u32; 17]) {
+        for (id, &val) in regs.iter().enumerate() {
+            self.set_register(id as u8, val);
+        }
+    }
+    /// Program status register (register 16 on Cortex-M).
+    fn xpsr(&self) -> u32 {
+        self.get_register(16)
+    }
+    fn set_xpsr(&mut self, val: u32) {
+        self.set_register(16, val);
+    }
     fn snapshot(&self) -> snapshot::CpuSnapshot;
+    fn restore(&mut self, snapshot: &snapshot::CpuSnapshot) -> anyhow::Result<()>;
+}
+
+/// A named peripheral register, as listed by [`Peripheral::registers`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PeripheralRegister {
+    pub name: &'static str,
+    /// Offset from the peripheral base.
+    pub offset: u64,
+    pub value: u32,
+}
+
+impl PeripheralRegister {
+    /// Build the register list for `layout` (name, offset) by reading each
+    /// offset through `read`.
+    pub fn from_layout(
+        layout: &[(&'static str, u64)],
+        read: impl Fn(u64) -> u32,
+    ) -> Vec<PeripheralRegister> {
+        layout
+            .iter()
+            .map(|&(name, offset)| PeripheralRegister {
+                name,
+                offset,
+                value: read(offset),
+            })
+            .collect()
+    }
 }
 
 /// Trait representing a memory-mapped peripheral
 pub trait Peripheral: std::fmt::Debug + Send {
     fn read(&self, offset: u64) -> SimResult<u8>;
+    /// Read the way a debugger does, without the side effects a CPU read
+    /// may have (such as popping a FIFO). Defaults to `read`.
+    fn peek(&self, offset: u64) -> SimResult<u8> {
+        self.read(offset)
+    }
     fn write(&mut self, offset: u64, value: u8) -> SimResult<()>;
+    /// Write an aligned halfword as a single access. The default splits it
+    /// into byte writes, low byte first.
+    fn write_u16(&mut self, offset: u64, value: u16) -> SimResult<()> {
+        self.write(offset, value as u8)?;
+        self.write(offset + 1, (value >> 8) as u8)
+    }
+    /// Write an aligned word as a single access. The default splits it into
+    /// byte writes, low byte first.
+    fn write_u32(&mut self, offset: u64, value: u32) -> SimResult<()> {
+        for (i, byte) in value.to_le_bytes().into_iter().enumerate() {
+            self.write(offset + i as u64, byte)?;
+        }
+        Ok(())
+    }
     fn tick(&mut self) -> PeripheralTickResult {
         PeripheralTickResult::default()
     }
@@ -103,6 +250,26 @@ pub trait Peripheral: std::fmt::Debug + Send {
     fn snapshot(&self) -> serde_json::Value {
         serde_json::Value::Null
     }
+    /// Restore state previously produced by `snapshot`.
+    fn restore(&mut self, _state: &serde_json::Value) -> anyhow::Result<()> {
+        Ok(())
+    }
+    /// Change parameters at runtime, without resetting register state.
+    /// Keys are peripheral-specific; unknown keys are an error. A request
+    /// that fails leaves the peripheral as it was.
+    fn reconfigure(
+        &mut self,
+        _config: &std::collections::HashMap<String, serde_json::Value>,
+    ) -> anyhow::Result<()> {
+        anyhow::bail!("peripheral does not support reconfiguration")
+    }
+    /// Current register values for debugger views. Unlike `read`, this must
+    /// not have side effects such as popping a FIFO. Peripherals usually
+    /// keep a `REGISTERS` table of names and offsets for
+    /// `PeripheralRegister::from_layout`.
+    fn registers(&self) -> Vec<PeripheralRegister> {
+        Vec::new()
+    }
 }
 
 /// Trait representing the system bus
@@ -127,6 +294,20 @@ pub trait Bus {
         Ok(b0 | (b1 << 8) | (b2 << 16) | (b3 << 24))
     }
 
+    /// `read_u8` without side effects, watchpoint hits or fault tracking,
+    /// for the core's own probes of system registers such as CCR.
+    fn peek_u8(&self, addr: u64) -> SimResult<u8> {
+        self.read_u8(addr)
+    }
+
+    fn peek_u32(&self, addr: u64) -> SimResult<u32> {
+        let b0 = self.peek_u8(addr)? as u32;
+        let b1 = self.peek_u8(addr + 1)? as u32;
+        let b2 = self.peek_u8(addr + 2)? as u32;
+        let b3 = self.peek_u8(addr + 3)? as u32;
+        Ok(b0 | (b1 << 8) | (b2 << 16) | (b3 << 24))
+    }
+
     fn write_u32(&mut self, addr: u64, value: u32) -> SimResult<()> {
         self.write_u8(addr, (value & 0xFF) as u8)?;
         self.write_u8(addr + 1, ((value >> 8) & 0xFF) as u8)?;
@@ -150,6 +331,10 @@ pub trait DebugControl {
     fn remove_breakpoint(&mut self, addr: u32);
     fn clear_breakpoints(&mut self);
 
+    fn add_watchpoint(&mut self, addr: u32, len: u32, kind: WatchKind);
+    /// Returns false if no matching watchpoint was set.
+    fn remove_watchpoint(&mut self, addr: u32, len: u32, kind: WatchKind) -> bool;
+
     /// Run until breakpoint or steps limit
     fn run(&mut self, max_steps: Option<u32>) -> SimResult<StopReason>;
 
@@ -161,14 +346,33 @@ pub trait DebugControl {
 
     fn read_memory(&self, addr: u32, len: usize) -> SimResult<Vec<u8>>;
     fn write_memory(&mut self, addr: u32, data: &[u8]) -> SimResult<()>;
+    /// Fill `buf` from `addr`. Ranges within RAM or flash are copied in one
+    /// go rather than byte by byte; peripherals are read through
+    /// `Peripheral::peek`, so inspecting them has no side effects.
+    fn read_memory_into(&self, addr: u32, buf: &mut [u8]) -> SimResult<()>;
+    /// Write `data` to `addr`, the counterpart of `read_memory_into`.
+    fn write_memory_from(&mut self, addr: u32, data: &[u8]) -> SimResult<()>;
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum StopReason {
+    /// Stopped before executing the breakpoint at the address, which never
+    /// carries the Thumb bit.
     Breakpoint(u32),
+    /// A CPU access to `addr` matched a watchpoint of `kind`.
+    Watchpoint {
+        addr: u32,
+        kind: WatchKind,
+    },
     StepDone,
     MaxStepsReached,
     ManualStop,
+    /// The core registers (PC included) stayed the same for
+    /// `Machine::halt_window` steps with no exception pending: the firmware
+    /// is spinning in a loop such as `b .`.
+    Halted,
+    /// The predicate passed to `Machine::run_until` returned true.
+    PredicateMet,
 }
 
 pub struct Machine<C: Cpu> {
@@ -176,8 +380,64 @@ pub struct Machine<C: Cpu> {
     pub bus: bus::SystemBus,
     pub observers: Vec<Arc<dyn SimulationObserver>>,
 
+    /// Cycles the clock advances after reset before the first instruction
+    /// (oscillator/HSE startup). Peripherals are ticked once per cycle.
+    pub startup_cycles: u32,
+
+    /// Re-initialise RAM on every reset: fill it with `ram_init_pattern`, then
+    /// re-apply preloads and the RAM-resident parts of the loaded firmware
+    /// (`.data`, zeroed `.bss`). Flash is left as loaded.
+    pub reset_clears_ram: bool,
+    /// Fill byte for `reset_clears_ram`: 0 zeroes RAM, anything else (e.g.
+    /// 0xA5) poisons it so reads of uninitialised memory stand out.
+    pub ram_init_pattern: u8,
+    /// Segments of the last loaded image that did not land in flash.
+    ram_segments: Vec<memory::Segment>,
+    /// PC every reset starts at instead of the core's own reset PC: the
+    /// entry point of a loaded RISC-V image, which has no vector table.
+    reset_entry: Option<u32>,
+    /// Last ODR reported through `on_gpio_change`, by peripheral index.
+    gpio_outputs: Vec<Option<u32>>,
+    /// NVIC ISER and ISPR words last reported through `on_nvic_change`.
+    nvic_words: [u32; 16],
+    /// Core registers before the current step, for `on_registers_changed`.
+    registers_before: Vec<u32>,
+
     // Debug state
     pub breakpoints: HashSet<u32>,
+    pub watchpoints: Vec<Watchpoint>,
+    watch_hit: Option<(u32, WatchKind)>,
+
+    /// Operations the firmware is not allowed to perform.
+    pub policy: Option<policy::ExecutionPolicy>,
+
+    /// External events applied at the start of each step.
+    pub signals: signals::SignalSchedule,
+
+    /// Steps `run` waits with unchanged registers before stopping with
+    /// `StopReason::Halted`; `None` disables halt detection.
+    pub halt_window: Option<u32>,
+    /// Register fingerprint of the last step and how many steps in a row
+    /// it has been seen, for `check_halt`.
+    halt_state: (u64, u32),
+
+    /// Drains SEGGER RTT up-channel 0 after every step when set.
+    pub rtt: Option<rtt::RttReader>,
+}
+
+/// Step `cpu` on `bus`, returning the first watchpoint hit, if any.
+fn step_watched<C: Cpu>(
+    cpu: &mut C,
+    bus: &mut dyn Bus,
+    watchpoints: &[Watchpoint],
+    observers: &[Arc<dyn SimulationObserver>],
+) -> (SimResult<()>, Option<(u32, WatchKind)>) {
+    if watchpoints.is_empty() {
+        return (cpu.step(bus, observers), None);
+    }
+    let mut bus = watchpoint::WatchedBus::new(bus, watchpoints);
+    let res = cpu.step(&mut bus, observers);
+    (res, bus.hit())
 }
 
 impl<C: Cpu> Machine<C> {
@@ -186,16 +446,43 @@ impl<C: Cpu> Machine<C> {
             cpu,
             bus,
             observers: Vec::new(),
+            startup_cycles: 0,
+            reset_clears_ram: false,
+            ram_init_pattern: 0,
+            ram_segments: Vec::new(),
+            reset_entry: None,
+            gpio_outputs: Vec::new(),
+            nvic_words: [0; 16],
+            registers_before: Vec::new(),
             breakpoints: HashSet::new(),
+            watchpoints: Vec::new(),
+            watch_hit: None,
+            policy: None,
+            signals: signals::SignalSchedule::default(),
+            halt_window: None,
+            halt_state: (0, 0),
+            rtt: None,
         }
     }
 }
 
 impl<C: Cpu> Machine<C> {
-    pub fn load_firmware(&mut self, image: &memory::ProgramImage) -> SimResult<()> {
+    /// Load `image` and reset into it.
+    ///
+    /// Segments inside flash are written there; anything else goes to RAM
+    /// and is re-applied when `reset_clears_ram` is set. Zero-filled tails
+    /// are cleared in RAM only. Cortex-M cores then boot from the vector
+    /// table (falling back to the entry point if it is blank) and RISC-V
+    /// cores start at the entry point. Observers see `on_simulation_start`.
+    ///
+    /// The image can come from the ELF loader or be built directly with
+    /// `ProgramImage::with_segment`.
+    pub fn load_program(&mut self, image: &memory::ProgramImage) -> SimResult<()> {
+        self.ram_segments.clear();
         for segment in &image.segments {
             // Try loading into Flash first
             if !self.bus.flash.load_from_segment(segment) {
+                self.ram_segments.push(segment.clone());
                 // If not flash, try RAM? Or just warn?
                 // For now, let's assume everything goes to Flash or RAM mapped spaces
                 if !self.bus.ram.load_from_segment(segment) {
@@ -205,8 +492,18 @@ impl<C: Cpu> Machine<C> {
                     );
                 }
             }
+            // .bss: only RAM is zeroed; a tail in flash is left as is
+            if let Some((addr, len)) = segment.zero_fill() {
+                if !self.bus.ram.zero_range(addr, len) {
+                    tracing::debug!("Zero-fill {:#x}+{:#x} is not in RAM, skipped", addr, len);
+                }
+            }
         }
 
+        self.reset_entry = (image.arch == Arch::RiscV).then_some(image.entry_point as u32);
+        if let Some(rtt) = &mut self.rtt {
+            rtt.scan(&self.bus);
+        }
         for observer in &self.observers {
             observer.on_simulation_start();
         }
@@ -220,14 +517,284 @@ impl<C: Cpu> Machine<C> {
         Ok(())
     }
 
+    /// Same as `load_program`.
+    pub fn load_firmware(&mut self, image: &memory::ProgramImage) -> SimResult<()> {
+        self.load_program(image)
+    }
+
     pub fn reset(&mut self) -> SimResult<()> {
-        self.cpu.reset(&mut self.bus)
+        if self.reset_clears_ram {
+            self.init_ram();
+        }
+        self.cpu.reset(&mut self.bus)?;
+        if let Some(entry) = self.reset_entry {
+            self.cpu.set_pc(entry);
+        }
+
+        if self.startup_cycles > 0 {
+            for _ in 0..self.startup_cycles {
+                self.tick_peripherals();
+            }
+            for observer in &self.observers {
+                observer.on_step_end(self.startup_cycles);
+            }
+        }
+        Ok(())
+    }
+
+    /// Fill RAM with `ram_init_pattern`, then re-apply preloads and the
+    /// RAM-resident segments of the loaded firmware, in their load order.
+    fn init_ram(&mut self) {
+        self.bus.ram.data.fill(self.ram_init_pattern);
+        for segment in &self.bus.preloads {
+            self.bus.ram.load_from_segment(segment);
+        }
+        for segment in &self.ram_segments {
+            self.bus.ram.load_from_segment(segment);
+            if let Some((addr, len)) = segment.zero_fill() {
+                self.bus.ram.zero_range(addr, len);
+            }
+        }
     }
 
     pub fn step(&mut self) -> SimResult<()> {
-        let res = self.cpu.step(&mut self.bus, &self.observers);
+        for event in self.signals.take_due() {
+            self.apply_signal(event);
+        }
+        if let Some(policy) = &self.policy {
+            let pc = self.cpu.get_pc();
+            if policy.denies_instruction(&self.bus, pc) {
+                tracing::error!("Execution policy denies the instruction at {:#x}", pc);
+                return Err(SimulationError::PolicyViolation(pc as u64));
+            }
+        }
+        let track_registers = self.observers.iter().any(|o| o.wants_register_changes());
+        if track_registers {
+            let cpu = &self.cpu;
+            self.registers_before.clear();
+            self.registers_before
+                .extend((0..cpu.register_count()).map(|id| cpu.get_register(id)));
+        }
+        self.bus
+            .set_fetch_addr(Some((self.cpu.get_pc() & !1) as u64));
+        let (res, violation) = match &self.policy {
+            Some(policy) => {
+                let mut bus = policy::PolicyBus::new(&mut self.bus, policy);
+                let (res, hit) =
+                    step_watched(&mut self.cpu, &mut bus, &self.watchpoints, &self.observers);
+                self.watch_hit = hit;
+                (res, bus.violation())
+            }
+            None => {
+                let (res, hit) = step_watched(
+                    &mut self.cpu,
+                    &mut self.bus,
+                    &self.watchpoints,
+                    &self.observers,
+                );
+                self.watch_hit = hit;
+                (res, None)
+            }
+        };
+        self.bus.set_fetch_addr(None);
+        self.tick_peripherals();
+        if let Some(rtt) = &mut self.rtt {
+            rtt.poll(&mut self.bus);
+        }
+        if !self.observers.is_empty() {
+            self.report_gpio_changes();
+            self.report_nvic_changes();
+            if track_registers {
+                self.report_register_changes();
+            }
+            for observer in &self.observers {
+                observer.on_step_complete();
+            }
+        }
+        if let Some(addr) = violation {
+            tracing::error!("Execution policy denies the write to {:#x}", addr);
+            return Err(SimulationError::PolicyViolation(addr));
+        }
+        res
+    }
 
-        // Propagate peripherals
+    fn apply_signal(&mut self, event: signals::SignalEvent) {
+        tracing::debug!("Applying signal {:?}", event);
+        match event {
+            signals::SignalEvent::GpioInput { port, pin, level } => {
+                let gpio = self
+                    .bus
+                    .peripheral_mut::<peripherals::gpio::GpioPort>(&port);
+                match gpio {
+                    Some(gpio) => gpio.set_input(pin, level.into()),
+                    None => tracing::warn!("Signal: no GPIO port named '{}'", port),
+                }
+            }
+            signals::SignalEvent::UartRx { name, bytes } => {
+                let uart = self.bus.peripheral_mut::<peripherals::uart::Uart>(&name);
+                match uart {
+                    Some(uart) => uart.push_rx(&bytes),
+                    None => tracing::warn!("Signal: no UART named '{}'", name),
+                }
+            }
+            signals::SignalEvent::PendIrq { num } => self.inject_interrupt(num),
+        }
+    }
+
+    /// Track the register file after a step for halt detection. Returns
+    /// true once it has not changed for `halt_window` consecutive steps
+    /// without an exception pending; always false if `halt_window` is unset.
+    pub fn check_halt(&mut self) -> bool {
+        use std::hash::{Hash, Hasher};
+        let Some(window) = self.halt_window else {
+            return false;
+        };
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        for id in 0..self.cpu.register_count() {
+            self.cpu.get_register(id).hash(&mut hasher);
+        }
+        let fingerprint = hasher.finish();
+        let (last, count) = self.halt_state;
+        self.halt_state = if fingerprint == last && !self.cpu.exception_pending() {
+            (fingerprint, count.saturating_add(1))
+        } else {
+            (fingerprint, 0)
+        };
+        self.halt_state.1 >= window
+    }
+
+    fn report_nvic_changes(&mut self) {
+        use peripherals::nvic::{Nvic, NvicEvent};
+        let Some(nvic) = self
+            .bus
+            .peripherals
+            .iter()
+            .find_map(|p| p.dev.as_any()?.downcast_ref::<Nvic>())
+        else {
+            return;
+        };
+        let words = nvic.enable_pend_words();
+        if words == self.nvic_words {
+            return;
+        }
+        // Per IRQ, an enable change is reported before a pending change
+        for n in 0..256 {
+            let (word, bit) = (n / 32, 1 << (n % 32));
+            let changes = [
+                (word, NvicEvent::Enable, NvicEvent::Disable),
+                (word + 8, NvicEvent::Pend, NvicEvent::Clear),
+            ];
+            for (i, set, cleared) in changes {
+                let after = words[i] & bit;
+                if self.nvic_words[i] & bit == after {
+                    continue;
+                }
+                let event = if after != 0 { set } else { cleared };
+                for observer in &self.observers {
+                    observer.on_nvic_change(16 + n as u32, event);
+                }
+            }
+        }
+        self.nvic_words = words;
+    }
+
+    fn report_register_changes(&self) {
+        let changed: Vec<(u8, u32)> = (0..self.cpu.register_count())
+            .zip(&self.registers_before)
+            .map(|(id, &before)| (id, before, self.cpu.get_register(id)))
+            .filter(|&(_, before, after)| before != after)
+            .map(|(id, _, after)| (id, after))
+            .collect();
+        for observer in self.observers.iter().filter(|o| o.wants_register_changes()) {
+            observer.on_registers_changed(&changed);
+        }
+    }
+
+    fn report_gpio_changes(&mut self) {
+        self.gpio_outputs.resize(self.bus.peripherals.len(), None);
+        for (p, last) in self.bus.peripherals.iter().zip(&mut self.gpio_outputs) {
+            let Some(gpio) = p
+                .dev
+                .as_any()
+                .and_then(|dev| dev.downcast_ref::<peripherals::gpio::GpioPort>())
+            else {
+                continue;
+            };
+            let odr = gpio.odr();
+            if *last != Some(odr) {
+                *last = Some(odr);
+                for observer in &self.observers {
+                    observer.on_gpio_change(&p.name, odr);
+                }
+            }
+        }
+    }
+
+    /// Step until `pred` returns true for the machine after a step, giving
+    /// `StopReason::PredicateMet`.
+    ///
+    /// The run also stops at a breakpoint, before stepping onto it, and after
+    /// a step that hit a watchpoint (reported ahead of the predicate), was
+    /// detected as a halt, or reached `max_steps`. `DebugControl::run` is
+    /// this with a predicate that never holds.
+    pub fn run_until<F: FnMut(&Self) -> bool>(
+        &mut self,
+        mut pred: F,
+        max_steps: Option<u32>,
+    ) -> SimResult<StopReason> {
+        let mut steps = 0;
+        self.halt_state = (0, 0);
+        loop {
+            let pc = self.cpu.get_pc() & !1;
+            if self.breakpoints.contains(&pc) {
+                return Ok(StopReason::Breakpoint(pc));
+            }
+
+            self.step()?;
+            steps += 1;
+
+            if let Some((addr, kind)) = self.watch_hit.take() {
+                return Ok(StopReason::Watchpoint { addr, kind });
+            }
+            if pred(self) {
+                return Ok(StopReason::PredicateMet);
+            }
+            if self.check_halt() {
+                return Ok(StopReason::Halted);
+            }
+            if max_steps.is_some_and(|max| steps >= max) {
+                return Ok(StopReason::MaxStepsReached);
+            }
+        }
+    }
+
+    /// Pend exception `irq` as if a peripheral had raised it, without one
+    /// backing it.
+    ///
+    /// Core exceptions (< 16) are pended on the CPU directly. External IRQs
+    /// (>= 16) go through the NVIC when the bus has one: they are set in ISPR
+    /// and taken on the next step only if enabled in ISER, staying pending
+    /// otherwise.
+    pub fn inject_interrupt(&mut self, irq: u32) {
+        if let Some(nvic) = self.bus.nvic.as_deref().filter(|_| irq >= 16) {
+            let idx = ((irq - 16) / 32) as usize;
+            let bit = 1 << ((irq - 16) % 32);
+            if idx >= nvic.ispr.len() {
+                return;
+            }
+            nvic.ispr[idx].fetch_or(bit, std::sync::atomic::Ordering::SeqCst);
+            if nvic.iser[idx].load(std::sync::atomic::Ordering::SeqCst) & bit == 0 {
+                return;
+            }
+        }
+        self.cpu.set_exception_pending(irq);
+        for observer in &self.observers {
+            observer.on_interrupt(irq);
+        }
+        tracing::debug!("Exception {} Pend (injected)", irq);
+    }
+
+    fn tick_peripherals(&mut self) {
         let (interrupts, costs) = self.bus.tick_peripherals_fully();
         for c in costs {
             if let Some(p) = self.bus.peripherals.get(c.index) {
@@ -238,10 +805,11 @@ impl<C: Cpu> Machine<C> {
         }
         for irq in interrupts {
             self.cpu.set_exception_pending(irq);
+            for observer in &self.observers {
+                observer.on_interrupt(irq);
+            }
             tracing::debug!("Exception {} Pend", irq);
         }
-
-        res
     }
 
     pub fn snapshot(&self) -> snapshot::MachineSnapshot {
@@ -253,8 +821,34 @@ impl<C: Cpu> Machine<C> {
                 .iter()
                 .map(|p| (p.name.clone(), p.dev.snapshot()))
                 .collect(),
+            flash: Some(self.bus.flash.snapshot()),
+            ram: Some(self.bus.ram.snapshot()),
         }
     }
+
+    /// Restore CPU, memory and peripheral state from a snapshot taken on a
+    /// machine with the same memory map.
+    pub fn restore(&mut self, snap: &snapshot::MachineSnapshot) -> anyhow::Result<()> {
+        self.cpu.restore(&snap.cpu)?;
+        if let Some(flash) = &snap.flash {
+            self.bus.flash.restore(flash)?;
+        }
+        if let Some(ram) = &snap.ram {
+            self.bus.ram.restore(ram)?;
+        }
+        for (name, state) in &snap.peripherals {
+            match self.bus.peripherals.iter_mut().find(|p| &p.name == name) {
+                Some(p) => p.dev.restore(state).map_err(|e| {
+                    anyhow::anyhow!("failed to restore peripheral '{}': {}", name, e)
+                })?,
+                None => tracing::warn!(
+                    "Snapshot peripheral '{}' not present on bus; skipping",
+                    name
+                ),
+            }
+        }
+        Ok(())
+    }
 }
 
 impl<C: Cpu> DebugControl for Machine<C> {
@@ -270,36 +864,31 @@ impl<C: Cpu> DebugControl for Machine<C> {
         self.breakpoints.clear();
     }
 
-    fn run(&mut self, max_steps: Option<u32>) -> SimResult<StopReason> {
-        let mut steps = 0;
-        loop {
-            // Check breakpoints BEFORE stepping
-            let pc = self.cpu.get_pc();
-            // Note: breakpoints typically match the exact PC.
-            // Thumb instructions are at even addresses, usually.
-            // If the user sets a BP at an odd address (Thumb function pointer), we should mask it?
-            // Usually DAP clients send the symbol address.
-            // Let's assume exact match for now, but mask LSB.
-            let pc_aligned = pc & !1;
-
-            if self.breakpoints.contains(&pc_aligned) {
-                return Ok(StopReason::Breakpoint(pc));
-            }
-
-            self.step()?;
-            steps += 1;
+    fn add_watchpoint(&mut self, addr: u32, len: u32, kind: WatchKind) {
+        self.watchpoints.push(Watchpoint { addr, len, kind });
+    }
 
-            if let Some(max) = max_steps {
-                if steps >= max {
-                    return Ok(StopReason::MaxStepsReached);
-                }
+    fn remove_watchpoint(&mut self, addr: u32, len: u32, kind: WatchKind) -> bool {
+        let wp = Watchpoint { addr, len, kind };
+        match self.watchpoints.iter().position(|w| *w == wp) {
+            Some(index) => {
+                self.watchpoints.remove(index);
+                true
             }
+            None => false,
         }
     }
 
+    fn run(&mut self, max_steps: Option<u32>) -> SimResult<StopReason> {
+        self.run_until(|_| false, max_steps)
+    }
+
     fn step_single(&mut self) -> SimResult<StopReason> {
         self.step()?;
-        Ok(StopReason::StepDone)
+        match self.watch_hit.take() {
+            Some((addr, kind)) => Ok(StopReason::Watchpoint { addr, kind }),
+            None => Ok(StopReason::StepDone),
+        }
     }
 
     fn read_core_reg(&self, id: u8) -> u32 {
@@ -311,18 +900,20 @@ impl<C: Cpu> DebugControl for Machine<C> {
     }
 
     fn read_memory(&self, addr: u32, len: usize) -> SimResult<Vec<u8>> {
-        let mut data = Vec::with_capacity(len);
-        for i in 0..len {
-            let byte = self.bus.read_u8((addr as u64) + (i as u64))?;
-            data.push(byte);
-        }
+        let mut data = vec![0; len];
+        self.read_memory_into(addr, &mut data)?;
         Ok(data)
     }
 
     fn write_memory(&mut self, addr: u32, data: &[u8]) -> SimResult<()> {
-        for (i, byte) in data.iter().enumerate() {
-            self.bus.write_u8((addr as u64) + (i as u64), *byte)?;
-        }
-        Ok(())
+        self.write_memory_from(addr, data)
+    }
+
+    fn read_memory_into(&self, addr: u32, buf: &mut [u8]) -> SimResult<()> {
+        self.bus.read_bytes(addr as u64, buf)
+    }
+
+    fn write_memory_from(&mut self, addr: u32, data: &[u8]) -> SimResult<()> {
+        self.bus.write_bytes(addr as u64, data)
     }
 }
@@ -10,6 +10,19 @@ use serde::{Deserialize, Serialize};
 pub struct Segment {
     pub start_addr: u64,
     pub data: Vec<u8>,
+    /// Size in memory (ELF `p_memsz`). Bytes past `data` up to this size are
+    /// zero-filled on load, as for `.bss`.
+    #[serde(default)]
+    pub mem_size: u64,
+}
+
+impl Segment {
+    /// Start and length of the zero-filled tail, if any.
+    pub fn zero_fill(&self) -> Option<(u64, u64)> {
+        let file_size = self.data.len() as u64;
+        (self.mem_size > file_size)
+            .then(|| (self.start_addr + file_size, self.mem_size - file_size))
+    }
 }
 
 use crate::Arch;
@@ -30,8 +43,38 @@ impl ProgramImage {
         }
     }
 
-    pub fn add_segment(&mut self, start_addr: u64, data: Vec<u8>) {
-        self.segments.push(Segment { start_addr, data });
+    pub fn add_segment(&mut self, start_addr: u64, data: Vec<u8>, mem_size: u64) {
+        self.segments.push(Segment {
+            start_addr,
+            data,
+            mem_size,
+        });
+    }
+
+    /// Builder form of `add_segment` for `data` loaded at `start_addr`, with
+    /// no zero-filled tail:
+    ///
+    /// ```
+    /// use labwired_core::{Arch, ProgramImage};
+    ///
+    /// let image = ProgramImage::new(0x40, Arch::Arm)
+    ///     .with_segment(0x0, [0x00, 0x10, 0x00, 0x20, 0x41, 0x00, 0x00, 0x00])
+    ///     .with_segment(0x40, [0xFE, 0xE7]) // b .
+    ///     .with_bss(0x2000_0000, 0x100);
+    /// assert_eq!(image.segments.len(), 3);
+    /// ```
+    pub fn with_segment(mut self, start_addr: u64, data: impl Into<Vec<u8>>) -> Self {
+        let data = data.into();
+        let mem_size = data.len() as u64;
+        self.add_segment(start_addr, data, mem_size);
+        self
+    }
+
+    /// Builder form of `add_segment` for `size` zeroed bytes at `start_addr`,
+    /// as for `.bss`.
+    pub fn with_bss(mut self, start_addr: u64, size: u64) -> Self {
+        self.add_segment(start_addr, Vec::new(), size);
+        self
     }
 }
 
@@ -66,6 +109,38 @@ impl LinearMemory {
         }
     }
 
+    /// Byte range of `data` backing `[addr, addr + len)`, if all of it is
+    /// in this region.
+    fn slice_range(&self, addr: u64, len: usize) -> Option<std::ops::Range<usize>> {
+        let start = addr.checked_sub(self.base_addr)? as usize;
+        let end = start.checked_add(len)?;
+        (end <= self.data.len()).then_some(start..end)
+    }
+
+    /// Fill `buf` from `addr`; false (leaving `buf` untouched) unless the
+    /// whole range is in this region.
+    pub fn read_slice(&self, addr: u64, buf: &mut [u8]) -> bool {
+        match self.slice_range(addr, buf.len()) {
+            Some(range) => {
+                buf.copy_from_slice(&self.data[range]);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Copy `data` to `addr`; false (writing nothing) unless the whole
+    /// range is in this region.
+    pub fn write_slice(&mut self, addr: u64, data: &[u8]) -> bool {
+        match self.slice_range(addr, data.len()) {
+            Some(range) => {
+                self.data[range].copy_from_slice(data);
+                true
+            }
+            None => false,
+        }
+    }
+
     pub fn load_from_segment(&mut self, segment: &Segment) -> bool {
         // Simple overlap check
         let end_addr = segment.start_addr + segment.data.len() as u64;
@@ -78,6 +153,36 @@ impl LinearMemory {
         }
         false
     }
+
+    /// Zero `len` bytes from `addr`. Returns false, changing nothing, unless
+    /// the whole range lies in this region.
+    pub fn zero_range(&mut self, addr: u64, len: u64) -> bool {
+        let mem_end = self.base_addr + self.data.len() as u64;
+        if addr >= self.base_addr && addr + len <= mem_end {
+            let offset = (addr - self.base_addr) as usize;
+            self.data[offset..offset + len as usize].fill(0);
+            return true;
+        }
+        false
+    }
+
+    pub fn snapshot(&self) -> crate::snapshot::MemorySnapshot {
+        crate::snapshot::MemorySnapshot::capture(self.base_addr, &self.data)
+    }
+
+    pub fn restore(&mut self, snapshot: &crate::snapshot::MemorySnapshot) -> anyhow::Result<()> {
+        if snapshot.base != self.base_addr || snapshot.size != self.data.len() as u64 {
+            anyhow::bail!(
+                "memory snapshot {:#x}+{:#x} does not match region {:#x}+{:#x}",
+                snapshot.base,
+                snapshot.size,
+                self.base_addr,
+                self.data.len()
+            );
+        }
+        self.data = snapshot.to_bytes()?;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -113,6 +218,7 @@ mod tests {
         let seg1 = Segment {
             start_addr: 0x1000,
             data: vec![1, 2, 3],
+            mem_size: 3,
         };
         assert!(mem.load_from_segment(&seg1));
         assert_eq!(mem.read_u8(0x1000), Some(1));
@@ -121,10 +227,31 @@ mod tests {
         let seg2 = Segment {
             start_addr: 0x13FE,
             data: vec![10, 20, 30], // 3 bytes: 13FE, 13FF, 1400 (out)
+            mem_size: 3,
         };
         assert!(!mem.load_from_segment(&seg2));
 
         // Verify partial write didn't happen (atomic load not guaranteed but check logic)
         assert_eq!(mem.read_u8(0x13FF), Some(0)); // Still 0
     }
+
+    #[test]
+    fn test_snapshot_restore_roundtrip() {
+        let mut mem = LinearMemory::new(1024, 0x1000);
+        mem.write_u8(0x1000, 0xAA);
+        mem.write_u8(0x1001, 0xBB);
+        mem.write_u8(0x1008, 0xCC); // Short gap, merged into the first chunk
+        mem.write_u8(0x1200, 0xDD); // Long gap, separate chunk
+
+        let snap = mem.snapshot();
+        assert_eq!(snap.chunks.len(), 2);
+
+        let mut restored = LinearMemory::new(1024, 0x1000);
+        restored.write_u8(0x1100, 0x11); // Stale data must be cleared
+        restored.restore(&snap).unwrap();
+        assert_eq!(restored.data, mem.data);
+
+        let mut mismatched = LinearMemory::new(512, 0x1000);
+        assert!(mismatched.restore(&snap).is_err());
+    }
 }
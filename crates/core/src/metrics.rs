@@ -4,19 +4,25 @@
 // This software is released under the MIT License.
 // See the LICENSE file in the project root for full license information.
 
+use crate::decoder::InstructionCategory;
 use crate::SimulationObserver;
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Mutex;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
+/// Instruction, cycle and IPS counters for the current run.
+///
+/// A run starts when firmware is loaded: `on_simulation_start` clears the
+/// counters and restarts the IPS timer, so reloading a machine measures the
+/// new run on its own.
 #[derive(Debug)]
 pub struct PerformanceMetrics {
     instruction_count: AtomicU64,
     cycle_count: AtomicU64,
     peripheral_cycle_count: AtomicU64,
     peripheral_cycles_by_name: Mutex<HashMap<String, u64>>,
-    start_time: Instant,
+    start_time: Mutex<Instant>,
 }
 
 impl Default for PerformanceMetrics {
@@ -32,7 +38,7 @@ impl PerformanceMetrics {
             cycle_count: AtomicU64::new(0),
             peripheral_cycle_count: AtomicU64::new(0),
             peripheral_cycles_by_name: Mutex::new(HashMap::new()),
-            start_time: Instant::now(),
+            start_time: Mutex::new(Instant::now()),
         }
     }
 
@@ -45,6 +51,13 @@ impl PerformanceMetrics {
         }
     }
 
+    /// Measure IPS from now on.
+    pub fn restart_timer(&self) {
+        if let Ok(mut start) = self.start_time.lock() {
+            *start = Instant::now();
+        }
+    }
+
     pub fn get_instructions(&self) -> u64 {
         self.instruction_count.load(Ordering::SeqCst)
     }
@@ -66,7 +79,10 @@ impl PerformanceMetrics {
     }
 
     pub fn get_ips(&self) -> f64 {
-        let elapsed = self.start_time.elapsed().as_secs_f64();
+        let elapsed = self
+            .start_time
+            .lock()
+            .map_or(0.0, |start| start.elapsed().as_secs_f64());
         if elapsed > 0.0 {
             self.get_instructions() as f64 / elapsed
         } else {
@@ -77,7 +93,8 @@ impl PerformanceMetrics {
 
 impl SimulationObserver for PerformanceMetrics {
     fn on_simulation_start(&self) {
-        // Reset counters on each start if needed, or just keep them cumulative
+        self.reset();
+        self.restart_timer();
     }
 
     fn on_step_start(&self, _pc: u32, _opcode: u32) {
@@ -100,3 +117,120 @@ impl SimulationObserver for PerformanceMetrics {
         }
     }
 }
+
+/// Executed instruction counts per [`InstructionCategory`].
+///
+/// Like [`PerformanceMetrics`], the counts cover the current run and are
+/// cleared by `on_simulation_start`. `on_simulation_stop` logs the
+/// histogram, most frequent category first.
+#[derive(Debug, Default)]
+pub struct InstructionMix {
+    counts: Mutex<HashMap<InstructionCategory, u64>>,
+}
+
+impl InstructionMix {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn count(&self, category: InstructionCategory) -> u64 {
+        self.counts
+            .lock()
+            .ok()
+            .and_then(|m| m.get(&category).copied())
+            .unwrap_or(0)
+    }
+
+    /// Categories that executed at least once with their counts, most
+    /// frequent first (ties in declaration order).
+    pub fn histogram(&self) -> Vec<(InstructionCategory, u64)> {
+        let mut histogram: Vec<(InstructionCategory, u64)> = self
+            .counts
+            .lock()
+            .map(|m| m.iter().map(|(&c, &n)| (c, n)).collect())
+            .unwrap_or_default();
+        histogram.sort_by(|a, b| (b.1, a.0).cmp(&(a.1, b.0)));
+        histogram
+    }
+}
+
+impl SimulationObserver for InstructionMix {
+    fn on_simulation_start(&self) {
+        if let Ok(mut m) = self.counts.lock() {
+            m.clear();
+        }
+    }
+
+    fn on_simulation_stop(&self) {
+        let histogram = self.histogram();
+        let total: u64 = histogram.iter().map(|(_, n)| n).sum();
+        tracing::info!("Instruction mix ({} instructions):", total);
+        for (category, n) in histogram {
+            let share = n as f64 * 100.0 / total as f64;
+            tracing::info!("  {:<10} {:>10} {:>5.1}%", category, n, share);
+        }
+    }
+
+    fn on_instruction(&self, category: InstructionCategory) {
+        if let Ok(mut m) = self.counts.lock() {
+            *m.entry(category).or_insert(0) += 1;
+        }
+    }
+}
+
+/// Sleeps the run loop so simulated time (`cycles / hz`) tracks wall-clock
+/// time.
+///
+/// Clocks are compared every `hz / 100` cycles, against a fixed anchor so
+/// rounding in one check does not carry into the next. A simulation that falls
+/// more than [`RealtimeThrottle::MAX_LAG`] behind (a host too slow for `hz`,
+/// or a run paused in a debugger) is re-anchored instead of racing to catch
+/// up.
+#[derive(Debug)]
+pub struct RealtimeThrottle {
+    hz: u64,
+    anchor: Instant,
+    anchor_cycles: u64,
+    next_check: u64,
+}
+
+impl RealtimeThrottle {
+    pub const MAX_LAG: Duration = Duration::from_millis(100);
+
+    /// Throttle to a simulated clock of `hz` (clamped to at least 1),
+    /// starting from `cycles` at the current instant.
+    pub fn new(hz: u64, cycles: u64) -> Self {
+        let mut throttle = Self {
+            hz: hz.max(1),
+            anchor: Instant::now(),
+            anchor_cycles: 0,
+            next_check: 0,
+        };
+        throttle.recalibrate(cycles);
+        throttle
+    }
+
+    fn recalibrate(&mut self, cycles: u64) {
+        self.anchor = Instant::now();
+        self.anchor_cycles = cycles;
+        self.next_check = cycles + (self.hz / 100).max(1);
+    }
+
+    /// Account for the run having reached `cycles`, sleeping if it is ahead
+    /// of the wall clock.
+    pub fn pace(&mut self, cycles: u64) {
+        if cycles < self.next_check {
+            return;
+        }
+        let elapsed_cycles = cycles.saturating_sub(self.anchor_cycles);
+        let simulated = Duration::from_secs_f64(elapsed_cycles as f64 / self.hz as f64);
+        let wall = self.anchor.elapsed();
+        if let Some(ahead) = simulated.checked_sub(wall) {
+            std::thread::sleep(ahead);
+        } else if wall - simulated > Self::MAX_LAG {
+            self.recalibrate(cycles);
+            return;
+        }
+        self.next_check = cycles + (self.hz / 100).max(1);
+    }
+}
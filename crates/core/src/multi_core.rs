@@ -4,8 +4,11 @@
 // This software is released under the MIT License.
 // See the LICENSE file in the project root for full license information.
 
-use crate::{bus::SystemBus, Bus, Cpu, SimulationObserver};
-use std::sync::Arc;
+use crate::bus::{PeripheralEntry, SystemBus};
+use crate::memory::LinearMemory;
+use crate::peripherals::reg32::{Reg32Peripheral, WriteLatch};
+use crate::{Bus, Cpu, Machine, SimResult, SimulationObserver};
+use std::sync::{Arc, Mutex};
 
 /// A machine that supports multiple CPU cores.
 ///
@@ -45,3 +48,194 @@ impl MultiCoreMachine {
         results
     }
 }
+
+/// RAM mapped into the buses of several machines at once, such as the SRAM
+/// both cores of a dual-core MCU see. Clones share the same storage.
+#[derive(Clone)]
+pub struct SharedMemory {
+    mem: Arc<Mutex<LinearMemory>>,
+}
+
+impl SharedMemory {
+    pub fn new(size: usize) -> Self {
+        Self {
+            mem: Arc::new(Mutex::new(LinearMemory::new(size, 0))),
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.mem.lock().map_or(0, |mem| mem.data.len())
+    }
+
+    /// Copy of `len` bytes from `offset`, truncated at the end of the region.
+    pub fn read_bytes(&self, offset: usize, len: usize) -> Vec<u8> {
+        let Ok(mem) = self.mem.lock() else {
+            return Vec::new();
+        };
+        let end = offset.saturating_add(len).min(mem.data.len());
+        mem.data.get(offset..end).unwrap_or_default().to_vec()
+    }
+}
+
+impl std::fmt::Debug for SharedMemory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SharedMemory")
+            .field("size", &self.size())
+            .finish()
+    }
+}
+
+impl crate::Peripheral for SharedMemory {
+    fn read(&self, offset: u64) -> SimResult<u8> {
+        let mem = self
+            .mem
+            .lock()
+            .map_err(|_| crate::SimulationError::MemoryViolation(offset))?;
+        Ok(mem.read_u8(offset).unwrap_or(0))
+    }
+
+    fn write(&mut self, offset: u64, value: u8) -> SimResult<()> {
+        if let Ok(mut mem) = self.mem.lock() {
+            mem.write_u8(offset, value);
+        }
+        Ok(())
+    }
+}
+
+/// Interrupt requests posted through a `Mailbox`: (target core, exception).
+type SignalQueue = Arc<Mutex<Vec<(usize, u32)>>>;
+
+/// Doorbell through which firmware on one core interrupts another.
+///
+/// A word written to offset 0 requests exception `value & 0xFFFF` on core
+/// `value >> 16`; `MultiCore::step_all` delivers it with `signal_core` as
+/// soon as the writing core's step is done. Reads return zero.
+#[derive(Debug, Clone, Default)]
+pub struct Mailbox {
+    requests: SignalQueue,
+    latch: WriteLatch,
+}
+
+impl Reg32Peripheral for Mailbox {
+    fn read_reg(&self, _offset: u64) -> u32 {
+        0
+    }
+
+    fn on_reg_write(&mut self, offset: u64, value: u32) {
+        if offset != 0 {
+            return;
+        }
+        if let Ok(mut requests) = self.requests.lock() {
+            requests.push(((value >> 16) as usize, value & 0xFFFF));
+        }
+    }
+
+    fn write_latch(&mut self) -> Option<&mut WriteLatch> {
+        Some(&mut self.latch)
+    }
+}
+
+impl crate::Peripheral for Mailbox {
+    fn read(&self, offset: u64) -> SimResult<u8> {
+        Ok(self.read_byte(offset))
+    }
+
+    fn write(&mut self, offset: u64, value: u8) -> SimResult<()> {
+        self.write_byte(offset, value);
+        Ok(())
+    }
+
+    fn write_u16(&mut self, offset: u64, value: u16) -> SimResult<()> {
+        self.write_half(offset, value);
+        Ok(())
+    }
+
+    fn write_u32(&mut self, offset: u64, value: u32) -> SimResult<()> {
+        self.write_word(offset, value);
+        Ok(())
+    }
+
+    fn tick(&mut self) -> crate::PeripheralTickResult {
+        self.flush_write();
+        crate::PeripheralTickResult::default()
+    }
+}
+
+/// Several complete machines, each with its own core and bus, run in
+/// lockstep. Unlike `MultiCoreMachine` the buses are separate: cores only
+/// share what is mapped with `map_shared_memory`, and interrupt each other
+/// through `signal_core` or a mapped `Mailbox`.
+pub struct MultiCore<C: Cpu> {
+    pub machines: Vec<Machine<C>>,
+    mailbox: Mailbox,
+}
+
+impl<C: Cpu> MultiCore<C> {
+    pub fn new(machines: Vec<Machine<C>>) -> Self {
+        Self {
+            machines,
+            mailbox: Mailbox::default(),
+        }
+    }
+
+    /// Map `memory` at `base` on every core's bus.
+    pub fn map_shared_memory(&mut self, name: &str, base: u64, memory: &SharedMemory) {
+        let size = memory.size() as u64;
+        for machine in &mut self.machines {
+            machine.bus.peripherals.push(PeripheralEntry {
+                name: name.to_string(),
+                base,
+                size,
+                irq: None,
+                dev: Box::new(memory.clone()),
+            });
+        }
+    }
+
+    /// Map the mailbox doorbell at `base` on every core's bus.
+    pub fn map_mailbox(&mut self, base: u64) {
+        for machine in &mut self.machines {
+            machine.bus.peripherals.push(PeripheralEntry {
+                name: "mailbox".to_string(),
+                base,
+                size: 4,
+                irq: None,
+                dev: Box::new(self.mailbox.clone()),
+            });
+        }
+    }
+
+    /// Pend exception `irq` on core `target`, as a peripheral interrupt
+    /// would (external interrupts also go through its NVIC). Returns false
+    /// if there is no such core.
+    pub fn signal_core(&mut self, target: usize, irq: u32) -> bool {
+        let Some(machine) = self.machines.get_mut(target) else {
+            return false;
+        };
+        machine.inject_interrupt(irq);
+        true
+    }
+
+    /// Step every core once, in order, delivering mailbox requests after
+    /// each step so later cores see them in the same round.
+    pub fn step_all(&mut self) -> Vec<SimResult<()>> {
+        let mut results = Vec::with_capacity(self.machines.len());
+        for i in 0..self.machines.len() {
+            results.push(self.machines[i].step());
+            self.deliver_signals();
+        }
+        results
+    }
+
+    fn deliver_signals(&mut self) {
+        let requests = match self.mailbox.requests.lock() {
+            Ok(mut requests) => std::mem::take(&mut *requests),
+            Err(_) => return,
+        };
+        for (target, irq) in requests {
+            if !self.signal_core(target, irq) {
+                tracing::warn!("Mailbox: no core {} to signal (exception {})", target, irq);
+            }
+        }
+    }
+}
@@ -4,10 +4,10 @@
 // This software is released under the MIT License.
 // See the LICENSE file in the project root for full license information.
 
-use crate::{Peripheral, PeripheralTickResult, SimResult};
+use crate::{Peripheral, PeripheralRegister, PeripheralTickResult, SimResult};
 use std::any::Any;
 
-#[derive(Debug, serde::Serialize)]
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub struct Adc {
     // Registers
     pub sr: u32,  // 0x00 - Status Register
@@ -48,18 +48,23 @@ impl Adc {
         // Clear EOC bit on start
         self.sr &= !0x2;
     }
+
+    fn read_reg(&self, offset: u64) -> u32 {
+        match offset {
+            0x00 => self.sr,
+            0x04 => self.cr1,
+            0x08 => self.cr2,
+            0x4C => self.dr,
+            _ => 0,
+        }
+    }
 }
 
+const REGISTERS: &[(&str, u64)] = &[("SR", 0x00), ("CR1", 0x04), ("CR2", 0x08), ("DR", 0x4C)];
+
 impl Peripheral for Adc {
     fn read(&self, offset: u64) -> SimResult<u8> {
-        let val = match offset {
-            0x00..=0x03 => self.sr,
-            0x04..=0x07 => self.cr1,
-            0x08..=0x0B => self.cr2,
-            0x4C..=0x4F => self.dr,
-            _ => 0,
-        };
-
+        let val = self.read_reg(offset & !3);
         let shift = (offset % 4) * 8;
         Ok(((val >> shift) & 0xFF) as u8)
     }
@@ -156,4 +161,13 @@ impl Peripheral for Adc {
     fn snapshot(&self) -> serde_json::Value {
         serde_json::to_value(self).unwrap_or(serde_json::Value::Null)
     }
+
+    fn restore(&mut self, state: &serde_json::Value) -> anyhow::Result<()> {
+        *self = serde_json::from_value(state.clone())?;
+        Ok(())
+    }
+
+    fn registers(&self) -> Vec<PeripheralRegister> {
+        PeripheralRegister::from_layout(REGISTERS, |offset| self.read_reg(offset))
+    }
 }
@@ -4,11 +4,11 @@
 // This software is released under the MIT License.
 // See the LICENSE file in the project root for full license information.
 
-use crate::{Peripheral, SimResult};
+use crate::{Peripheral, PeripheralRegister, SimResult};
 use std::any::Any;
 
 /// STM32F1 Alternate Function I/O (AFIO)
-#[derive(Debug, Default, serde::Serialize)]
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
 pub struct Afio {
     pub evcr: u32,
     pub mapr: u32,
@@ -58,6 +58,16 @@ impl Afio {
     }
 }
 
+const REGISTERS: &[(&str, u64)] = &[
+    ("EVCR", 0x00),
+    ("MAPR", 0x04),
+    ("EXTICR1", 0x08),
+    ("EXTICR2", 0x0C),
+    ("EXTICR3", 0x10),
+    ("EXTICR4", 0x14),
+    ("MAPR2", 0x1C),
+];
+
 impl Peripheral for Afio {
     fn read(&self, offset: u64) -> SimResult<u8> {
         let reg_offset = offset & !3;
@@ -89,4 +99,13 @@ impl Peripheral for Afio {
     fn snapshot(&self) -> serde_json::Value {
         serde_json::to_value(self).unwrap_or(serde_json::Value::Null)
     }
+
+    fn restore(&mut self, state: &serde_json::Value) -> anyhow::Result<()> {
+        *self = serde_json::from_value(state.clone())?;
+        Ok(())
+    }
+
+    fn registers(&self) -> Vec<PeripheralRegister> {
+        PeripheralRegister::from_layout(REGISTERS, |offset| self.read_reg(offset))
+    }
 }
@@ -0,0 +1,175 @@
+// LabWired - Firmware Simulation Platform
+// Copyright (C) 2026 Andrii Shylenko
+//
+// This software is released under the MIT License.
+// See the LICENSE file in the project root for full license information.
+
+use crate::cpu::riscv::{IRQ_MSI, IRQ_MTI};
+use crate::peripherals::reg32::{Reg32Peripheral, WriteLatch};
+use crate::SimResult;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+/// Standard CLINT base address on SiFive-style RV32 platforms.
+pub const CLINT_BASE: u64 = 0x0200_0000;
+pub const CLINT_SIZE: u64 = 0x1_0000;
+
+const MSIP: u64 = 0x0000;
+const MTIMECMP_LO: u64 = 0x4000;
+const MTIMECMP_HI: u64 = 0x4004;
+const MTIME_LO: u64 = 0xBFF8;
+const MTIME_HI: u64 = 0xBFFC;
+
+/// Core-Local Interruptor for a single hart.
+///
+/// `mtime` counts peripheral ticks and the machine timer interrupt is
+/// pending while `mtime >= mtimecmp`. Pending interrupts are level signals
+/// published to the core through the shared `mip` lines (see
+/// `RiscV::set_shared_mip`), so they clear as soon as firmware moves
+/// `mtimecmp` ahead or clears `msip`.
+#[derive(Debug)]
+pub struct Clint {
+    msip: u32,
+    mtime: u64,
+    mtimecmp: u64,
+    mip: Arc<AtomicU32>,
+    latch: WriteLatch,
+}
+
+/// State saved by `snapshot`; the `mip` lines are rebuilt from it.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct ClintSnapshot {
+    msip: u32,
+    mtime: u64,
+    mtimecmp: u64,
+}
+
+impl Clint {
+    pub fn new(mip: Arc<AtomicU32>) -> Self {
+        let clint = Self {
+            msip: 0,
+            mtime: 0,
+            // Not pending out of reset
+            mtimecmp: u64::MAX,
+            mip,
+            latch: WriteLatch::default(),
+        };
+        clint.update_lines();
+        clint
+    }
+
+    pub fn mtime(&self) -> u64 {
+        self.mtime
+    }
+
+    pub fn mtimecmp(&self) -> u64 {
+        self.mtimecmp
+    }
+
+    fn update_lines(&self) {
+        let mut lines = 0;
+        if self.msip & 1 != 0 {
+            lines |= 1 << IRQ_MSI;
+        }
+        if self.mtime >= self.mtimecmp {
+            lines |= 1 << IRQ_MTI;
+        }
+        self.mip.store(lines, Ordering::SeqCst);
+    }
+}
+
+fn set_half(value: u64, high: bool, half: u32) -> u64 {
+    if high {
+        (value & 0xFFFF_FFFF) | ((half as u64) << 32)
+    } else {
+        (value & !0xFFFF_FFFF) | half as u64
+    }
+}
+
+impl Reg32Peripheral for Clint {
+    fn read_reg(&self, offset: u64) -> u32 {
+        match offset {
+            MSIP => self.msip,
+            MTIMECMP_LO => self.mtimecmp as u32,
+            MTIMECMP_HI => (self.mtimecmp >> 32) as u32,
+            MTIME_LO => self.mtime as u32,
+            MTIME_HI => (self.mtime >> 32) as u32,
+            _ => 0,
+        }
+    }
+
+    fn on_reg_write(&mut self, offset: u64, value: u32) {
+        match offset {
+            MSIP => self.msip = value & 1,
+            MTIMECMP_LO | MTIMECMP_HI => {
+                self.mtimecmp = set_half(self.mtimecmp, offset == MTIMECMP_HI, value)
+            }
+            MTIME_LO | MTIME_HI => self.mtime = set_half(self.mtime, offset == MTIME_HI, value),
+            _ => return,
+        }
+        self.update_lines();
+    }
+
+    fn write_latch(&mut self) -> Option<&mut WriteLatch> {
+        Some(&mut self.latch)
+    }
+}
+
+const REGISTERS: &[(&str, u64)] = &[
+    ("MSIP", MSIP),
+    ("MTIMECMP_LO", MTIMECMP_LO),
+    ("MTIMECMP_HI", MTIMECMP_HI),
+    ("MTIME_LO", MTIME_LO),
+    ("MTIME_HI", MTIME_HI),
+];
+
+impl crate::Peripheral for Clint {
+    fn read(&self, offset: u64) -> SimResult<u8> {
+        Ok(self.read_byte(offset))
+    }
+
+    fn write(&mut self, offset: u64, value: u8) -> SimResult<()> {
+        self.write_byte(offset, value);
+        Ok(())
+    }
+
+    fn write_u16(&mut self, offset: u64, value: u16) -> SimResult<()> {
+        self.write_half(offset, value);
+        Ok(())
+    }
+
+    fn write_u32(&mut self, offset: u64, value: u32) -> SimResult<()> {
+        self.write_word(offset, value);
+        Ok(())
+    }
+
+    fn tick(&mut self) -> crate::PeripheralTickResult {
+        self.flush_write();
+        self.mtime = self.mtime.wrapping_add(1);
+        self.update_lines();
+        crate::PeripheralTickResult::default()
+    }
+
+    fn snapshot(&self) -> serde_json::Value {
+        let state = ClintSnapshot {
+            msip: self.msip,
+            mtime: self.mtime,
+            mtimecmp: self.mtimecmp,
+        };
+        serde_json::to_value(state).unwrap_or(serde_json::Value::Null)
+    }
+
+    fn restore(&mut self, state: &serde_json::Value) -> anyhow::Result<()> {
+        let state: ClintSnapshot = serde_json::from_value(state.clone())?;
+        self.msip = state.msip;
+        self.mtime = state.mtime;
+        self.mtimecmp = state.mtimecmp;
+        self.latch = WriteLatch::default();
+        self.update_lines();
+        Ok(())
+    }
+
+    fn registers(&self) -> Vec<crate::PeripheralRegister> {
+        crate::PeripheralRegister::from_layout(REGISTERS, |offset| self.read_reg(offset))
+    }
+}
@@ -4,10 +4,12 @@
 // This software is released under the MIT License.
 // See the LICENSE file in the project root for full license information.
 
-use crate::{DmaDirection, DmaRequest, Peripheral, PeripheralTickResult, SimResult};
+use crate::{
+    DmaDirection, DmaRequest, Peripheral, PeripheralRegister, PeripheralTickResult, SimResult,
+};
 use std::any::Any;
 
-#[derive(Debug, Default, serde::Serialize)]
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
 struct DmaChannel {
     ccr: u32,
     cndtr: u32,
@@ -17,7 +19,7 @@ struct DmaChannel {
 }
 
 /// STM32F1 DMA1 Controller (7 channels)
-#[derive(Debug, Default, serde::Serialize)]
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
 pub struct Dma1 {
     isr: u32,
     ifcr: u32,
@@ -80,6 +82,38 @@ impl Dma1 {
     }
 }
 
+const REGISTERS: &[(&str, u64)] = &[
+    ("ISR", 0x00),
+    ("CCR1", 0x08),
+    ("CNDTR1", 0x0C),
+    ("CPAR1", 0x10),
+    ("CMAR1", 0x14),
+    ("CCR2", 0x1C),
+    ("CNDTR2", 0x20),
+    ("CPAR2", 0x24),
+    ("CMAR2", 0x28),
+    ("CCR3", 0x30),
+    ("CNDTR3", 0x34),
+    ("CPAR3", 0x38),
+    ("CMAR3", 0x3C),
+    ("CCR4", 0x44),
+    ("CNDTR4", 0x48),
+    ("CPAR4", 0x4C),
+    ("CMAR4", 0x50),
+    ("CCR5", 0x58),
+    ("CNDTR5", 0x5C),
+    ("CPAR5", 0x60),
+    ("CMAR5", 0x64),
+    ("CCR6", 0x6C),
+    ("CNDTR6", 0x70),
+    ("CPAR6", 0x74),
+    ("CMAR6", 0x78),
+    ("CCR7", 0x80),
+    ("CNDTR7", 0x84),
+    ("CPAR7", 0x88),
+    ("CMAR7", 0x8C),
+];
+
 impl Peripheral for Dma1 {
     fn read(&self, offset: u64) -> SimResult<u8> {
         let reg_offset = offset & !3;
@@ -190,4 +224,13 @@ impl Peripheral for Dma1 {
     fn snapshot(&self) -> serde_json::Value {
         serde_json::to_value(self).unwrap_or(serde_json::Value::Null)
     }
+
+    fn restore(&mut self, state: &serde_json::Value) -> anyhow::Result<()> {
+        *self = serde_json::from_value(state.clone())?;
+        Ok(())
+    }
+
+    fn registers(&self) -> Vec<PeripheralRegister> {
+        PeripheralRegister::from_layout(REGISTERS, |offset| self.read_reg(offset))
+    }
 }
@@ -4,11 +4,11 @@
 // This software is released under the MIT License.
 // See the LICENSE file in the project root for full license information.
 
-use crate::{Peripheral, PeripheralTickResult, SimResult};
+use crate::{Peripheral, PeripheralRegister, PeripheralTickResult, SimResult};
 use std::any::Any;
 
 /// STM32F1 External Interrupt/Event Controller (EXTI)
-#[derive(Debug, Default, serde::Serialize)]
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
 pub struct Exti {
     pub imr: u32,   // 0x00 - Interrupt mask register
     pub emr: u32,   // 0x04 - Event mask register
@@ -63,6 +63,15 @@ impl Exti {
     }
 }
 
+const REGISTERS: &[(&str, u64)] = &[
+    ("IMR", 0x00),
+    ("EMR", 0x04),
+    ("RTSR", 0x08),
+    ("FTSR", 0x0C),
+    ("SWIER", 0x10),
+    ("PR", 0x14),
+];
+
 impl Peripheral for Exti {
     fn read(&self, offset: u64) -> SimResult<u8> {
         let reg_offset = offset & !3;
@@ -122,4 +131,13 @@ impl Peripheral for Exti {
     fn snapshot(&self) -> serde_json::Value {
         serde_json::to_value(self).unwrap_or(serde_json::Value::Null)
     }
+
+    fn restore(&mut self, state: &serde_json::Value) -> anyhow::Result<()> {
+        *self = serde_json::from_value(state.clone())?;
+        Ok(())
+    }
+
+    fn registers(&self) -> Vec<PeripheralRegister> {
+        PeripheralRegister::from_layout(REGISTERS, |offset| self.read_reg(offset))
+    }
 }
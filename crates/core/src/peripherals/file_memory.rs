@@ -0,0 +1,113 @@
+// LabWired - Firmware Simulation Platform
+// Copyright (C) 2026 Andrii Shylenko
+//
+// This software is released under the MIT License.
+// See the LICENSE file in the project root for full license information.
+
+use crate::SimResult;
+use anyhow::Context;
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{Seek, SeekFrom, Write};
+use std::path::PathBuf;
+
+/// Memory window backed by a host file, e.g. external QSPI flash holding a
+/// filesystem image.
+///
+/// The file is read once when the peripheral is created and reads are served
+/// from that copy. When writable, every write also goes through to the file,
+/// so the image on disk reflects what the firmware stored; otherwise writes
+/// are ignored, like a ROM.
+#[derive(Debug)]
+pub struct FileMemory {
+    path: PathBuf,
+    data: Vec<u8>,
+    /// Open for writing when the window is writable.
+    file: Option<File>,
+}
+
+impl FileMemory {
+    pub fn open(path: impl Into<PathBuf>, writable: bool) -> anyhow::Result<Self> {
+        let path = path.into();
+        let data = std::fs::read(&path)
+            .with_context(|| format!("Failed to read memory file {:?}", path))?;
+        let file = if writable {
+            let file = OpenOptions::new()
+                .write(true)
+                .open(&path)
+                .with_context(|| format!("Failed to open memory file {:?} for writing", path))?;
+            Some(file)
+        } else {
+            None
+        };
+        Ok(Self { path, data, file })
+    }
+
+    /// Build from a peripheral's `config` map: `path` (required) and
+    /// `writable` (bool, default false). A relative `path` is resolved
+    /// against the working directory.
+    pub fn with_config(config: &HashMap<String, serde_yaml::Value>) -> anyhow::Result<Self> {
+        let mut path = None;
+        let mut writable = false;
+        for (key, value) in config {
+            match (key.as_str(), value) {
+                ("path", serde_yaml::Value::String(p)) => path = Some(p.clone()),
+                ("writable", serde_yaml::Value::Bool(w)) => writable = *w,
+                ("path" | "writable", v) => {
+                    anyhow::bail!("file: invalid value {:?} for '{}'", v, key)
+                }
+                (other, _) => tracing::warn!("file: ignoring unknown config key '{}'", other),
+            }
+        }
+        let path = path.context("file: missing 'path'")?;
+        Self::open(path, writable)
+    }
+
+    /// Size of the backing file when it was opened.
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    fn write_through(&mut self, offset: u64, bytes: &[u8]) {
+        let end = offset as usize + bytes.len();
+        let Some(file) = &mut self.file else {
+            tracing::debug!("file: ignoring write to read-only {:?}", self.path);
+            return;
+        };
+        let Some(window) = self.data.get_mut(offset as usize..end) else {
+            return;
+        };
+        window.copy_from_slice(bytes);
+        let res = file
+            .seek(SeekFrom::Start(offset))
+            .and_then(|_| file.write_all(bytes));
+        if let Err(e) = res {
+            tracing::warn!("file: failed to write {:?}: {}", self.path, e);
+        }
+    }
+}
+
+impl crate::Peripheral for FileMemory {
+    fn read(&self, offset: u64) -> SimResult<u8> {
+        Ok(self.data.get(offset as usize).copied().unwrap_or(0))
+    }
+
+    fn write(&mut self, offset: u64, value: u8) -> SimResult<()> {
+        self.write_through(offset, &[value]);
+        Ok(())
+    }
+
+    fn write_u16(&mut self, offset: u64, value: u16) -> SimResult<()> {
+        self.write_through(offset, &value.to_le_bytes());
+        Ok(())
+    }
+
+    fn write_u32(&mut self, offset: u64, value: u32) -> SimResult<()> {
+        self.write_through(offset, &value.to_le_bytes());
+        Ok(())
+    }
+}
@@ -0,0 +1,107 @@
+// LabWired - Firmware Simulation Platform
+// Copyright (C) 2026 Andrii Shylenko
+//
+// This software is released under the MIT License.
+// See the LICENSE file in the project root for full license information.
+
+use crate::SimResult;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+/// Base of the floating-point context registers (FPCCR at +4).
+pub const FPU_CONTROL_BASE: u64 = 0xE000_EF30;
+pub const FPU_CONTROL_SIZE: u64 = 0x10;
+
+const FPCCR: u64 = 0x04;
+const FPCAR: u64 = 0x08;
+const FPDSCR: u64 = 0x0C;
+
+/// FPCCR and FPCAR, which control (and record) how the core stacks FP
+/// context on exception entry, plus FPDSCR. FPCCR and FPCAR are shared
+/// with the core, which updates them during lazy stacking.
+#[derive(Debug)]
+pub struct FpuControl {
+    fpccr: Arc<AtomicU32>,
+    fpcar: Arc<AtomicU32>,
+    fpdscr: u32,
+}
+
+/// State saved by `snapshot`.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct FpuControlSnapshot {
+    fpccr: u32,
+    fpcar: u32,
+    fpdscr: u32,
+}
+
+impl FpuControl {
+    pub fn new(fpccr: Arc<AtomicU32>, fpcar: Arc<AtomicU32>) -> Self {
+        Self {
+            fpccr,
+            fpcar,
+            fpdscr: 0,
+        }
+    }
+
+    fn read_reg(&self, offset: u64) -> u32 {
+        match offset {
+            FPCCR => self.fpccr.load(Ordering::SeqCst),
+            FPCAR => self.fpcar.load(Ordering::SeqCst),
+            FPDSCR => self.fpdscr,
+            _ => 0,
+        }
+    }
+
+    fn write_reg(&mut self, offset: u64, value: u32) {
+        match offset {
+            FPCCR => self.fpccr.store(value, Ordering::SeqCst),
+            // Frame addresses are doubleword aligned; bits [2:0] are reserved
+            FPCAR => self.fpcar.store(value & !7, Ordering::SeqCst),
+            FPDSCR => self.fpdscr = value,
+            _ => {}
+        }
+    }
+}
+
+const REGISTERS: &[(&str, u64)] = &[("FPCCR", FPCCR), ("FPCAR", FPCAR), ("FPDSCR", FPDSCR)];
+
+impl crate::Peripheral for FpuControl {
+    fn read(&self, offset: u64) -> SimResult<u8> {
+        let reg_val = self.read_reg(offset & !3);
+        Ok((reg_val >> ((offset % 4) * 8)) as u8)
+    }
+
+    fn write(&mut self, offset: u64, value: u8) -> SimResult<()> {
+        let reg_offset = offset & !3;
+        let shift = (offset % 4) * 8;
+        let reg_val = (self.read_reg(reg_offset) & !(0xFF << shift)) | ((value as u32) << shift);
+        self.write_reg(reg_offset, reg_val);
+        Ok(())
+    }
+
+    fn write_u32(&mut self, offset: u64, value: u32) -> SimResult<()> {
+        self.write_reg(offset & !3, value);
+        Ok(())
+    }
+
+    fn snapshot(&self) -> serde_json::Value {
+        let state = FpuControlSnapshot {
+            fpccr: self.fpccr.load(Ordering::SeqCst),
+            fpcar: self.fpcar.load(Ordering::SeqCst),
+            fpdscr: self.fpdscr,
+        };
+        serde_json::to_value(state).unwrap_or(serde_json::Value::Null)
+    }
+
+    fn restore(&mut self, state: &serde_json::Value) -> anyhow::Result<()> {
+        let state: FpuControlSnapshot = serde_json::from_value(state.clone())?;
+        self.fpccr.store(state.fpccr, Ordering::SeqCst);
+        self.fpcar.store(state.fpcar, Ordering::SeqCst);
+        self.fpdscr = state.fpdscr;
+        Ok(())
+    }
+
+    fn registers(&self) -> Vec<crate::PeripheralRegister> {
+        crate::PeripheralRegister::from_layout(REGISTERS, |offset| self.read_reg(offset))
+    }
+}
@@ -4,20 +4,17 @@
 // This software is released under the MIT License.
 // See the LICENSE file in the project root for full license information.
 
+use crate::peripherals::reg32::Reg32Peripheral;
 use crate::SimResult;
 
 /// STM32F1-compatible GPIO peripheral
-#[derive(Debug, Default, serde::Serialize)]
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
 pub struct GpioPort {
     crl: u32,  // 0x00: configuration register low
     crh: u32,  // 0x04: configuration register high
     idr: u32,  // 0x08: input data register
     odr: u32,  // 0x0C: output data register
     lckr: u32, // 0x18: configuration lock register
-    bsrr_buf: u32,
-    bsrr_mask: u8,
-    brr_buf: u32,
-    brr_mask: u8,
 }
 
 impl GpioPort {
@@ -29,6 +26,25 @@ impl GpioPort {
         }
     }
 
+    /// Output data register.
+    pub fn odr(&self) -> u32 {
+        self.odr
+    }
+
+    /// Drive input `pin` (0-15) as seen in IDR. Other pins are ignored.
+    pub fn set_input(&mut self, pin: u8, high: bool) {
+        if pin >= 16 {
+            return;
+        }
+        if high {
+            self.idr |= 1 << pin;
+        } else {
+            self.idr &= !(1 << pin);
+        }
+    }
+}
+
+impl Reg32Peripheral for GpioPort {
     fn read_reg(&self, offset: u64) -> u32 {
         match offset {
             0x00 => self.crl,
@@ -40,17 +56,16 @@ impl GpioPort {
         }
     }
 
-    fn write_reg(&mut self, offset: u64, value: u32) {
+    fn on_reg_write(&mut self, offset: u64, value: u32) {
         match offset {
             0x00 => self.crl = value,
             0x04 => self.crh = value,
             0x0C => self.odr = value & 0xFFFF,
             0x10 => {
-                // BSRR: Bit Set/Reset Register
+                // BSRR: Bit Set/Reset Register. Set wins when both bits are written.
                 let set = value & 0xFFFF;
                 let reset = (value >> 16) & 0xFFFF;
-                self.odr |= set;
-                self.odr &= !reset;
+                self.odr = (self.odr & !reset) | set;
             }
             0x14 => {
                 // BRR: Bit Reset Register
@@ -61,77 +76,54 @@ impl GpioPort {
             _ => {}
         }
     }
-
-    fn handle_write_only_buffer(&mut self, reg_offset: u64, byte_offset: u32, value: u8) -> bool {
-        let (buf, mask) = if reg_offset == 0x10 {
-            (&mut self.bsrr_buf, &mut self.bsrr_mask)
-        } else {
-            (&mut self.brr_buf, &mut self.brr_mask)
-        };
-
-        let shift = byte_offset * 8;
-        let byte_mask = 1u8 << byte_offset;
-        *buf &= !(0xFF << shift);
-        *buf |= (value as u32) << shift;
-        *mask |= byte_mask;
-
-        if *mask == 0x0F {
-            let val = *buf;
-            *buf = 0;
-            *mask = 0;
-            self.write_reg(reg_offset, val);
-            return true;
-        }
-
-        if *mask == 0x03 {
-            let val = *buf & 0x0000_FFFF;
-            *buf = 0;
-            *mask = 0;
-            self.write_reg(reg_offset, val);
-            return true;
-        }
-
-        if *mask == 0x0C {
-            let val = *buf & 0xFFFF_0000;
-            *buf = 0;
-            *mask = 0;
-            self.write_reg(reg_offset, val);
-            return true;
-        }
-
-        false
-    }
 }
 
+const REGISTERS: &[(&str, u64)] = &[
+    ("CRL", 0x00),
+    ("CRH", 0x04),
+    ("IDR", 0x08),
+    ("ODR", 0x0C),
+    ("LCKR", 0x18),
+];
+
 impl crate::Peripheral for GpioPort {
     fn read(&self, offset: u64) -> SimResult<u8> {
-        let reg_offset = offset & !3;
-        let byte_offset = (offset % 4) as u32;
-        let reg_val = self.read_reg(reg_offset);
-        Ok(((reg_val >> (byte_offset * 8)) & 0xFF) as u8)
+        Ok(self.read_byte(offset))
     }
 
     fn write(&mut self, offset: u64, value: u8) -> SimResult<()> {
-        let reg_offset = offset & !3;
-        let byte_offset = (offset % 4) as u32;
+        self.write_byte(offset, value);
+        Ok(())
+    }
 
-        if (reg_offset == 0x10 || reg_offset == 0x14)
-            && self.handle_write_only_buffer(reg_offset, byte_offset, value)
-        {
-            return Ok(());
-        }
+    fn write_u16(&mut self, offset: u64, value: u16) -> SimResult<()> {
+        self.write_half(offset, value);
+        Ok(())
+    }
 
-        let mut reg_val = self.read_reg(reg_offset);
+    fn write_u32(&mut self, offset: u64, value: u32) -> SimResult<()> {
+        self.write_word(offset, value);
+        Ok(())
+    }
 
-        let mask = 0xFF << (byte_offset * 8);
-        reg_val &= !mask;
-        reg_val |= (value as u32) << (byte_offset * 8);
+    fn as_any(&self) -> Option<&dyn std::any::Any> {
+        Some(self)
+    }
 
-        self.write_reg(reg_offset, reg_val);
-        Ok(())
+    fn as_any_mut(&mut self) -> Option<&mut dyn std::any::Any> {
+        Some(self)
     }
 
     fn snapshot(&self) -> serde_json::Value {
         serde_json::to_value(self).unwrap_or(serde_json::Value::Null)
     }
+
+    fn restore(&mut self, state: &serde_json::Value) -> anyhow::Result<()> {
+        *self = serde_json::from_value(state.clone())?;
+        Ok(())
+    }
+
+    fn registers(&self) -> Vec<crate::PeripheralRegister> {
+        crate::PeripheralRegister::from_layout(REGISTERS, |offset| self.read_reg(offset))
+    }
 }
@@ -7,7 +7,7 @@
 use crate::SimResult;
 
 /// STM32F1 compatible I2C peripheral (Master mode only)
-#[derive(Debug, Default, serde::Serialize)]
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
 pub struct I2c {
     cr1: u16,
     cr2: u16,
@@ -74,6 +74,18 @@ impl I2c {
     }
 }
 
+const REGISTERS: &[(&str, u64)] = &[
+    ("CR1", 0x00),
+    ("CR2", 0x04),
+    ("OAR1", 0x08),
+    ("OAR2", 0x0C),
+    ("DR", 0x10),
+    ("SR1", 0x14),
+    ("SR2", 0x18),
+    ("CCR", 0x1C),
+    ("TRISE", 0x20),
+];
+
 impl crate::Peripheral for I2c {
     fn read(&self, offset: u64) -> SimResult<u8> {
         let reg_offset = offset & !3;
@@ -99,4 +111,13 @@ impl crate::Peripheral for I2c {
     fn snapshot(&self) -> serde_json::Value {
         serde_json::to_value(self).unwrap_or(serde_json::Value::Null)
     }
+
+    fn restore(&mut self, state: &serde_json::Value) -> anyhow::Result<()> {
+        *self = serde_json::from_value(state.clone())?;
+        Ok(())
+    }
+
+    fn registers(&self) -> Vec<crate::PeripheralRegister> {
+        crate::PeripheralRegister::from_layout(REGISTERS, |offset| self.read_reg(offset) as u32)
+    }
 }
@@ -4,21 +4,21 @@
 // This software is released under the MIT License.
 // See the LICENSE file in the project root for full license information.
 
-use crate::{Peripheral, PeripheralTickResult, SimResult};
+use crate::{Peripheral, PeripheralRegister, PeripheralTickResult, SimResult};
 use std::any::Any;
 
 /// Mock TMP102 I2C Temperature Sensor.
 ///
 /// In a real system, this would be on a separate bus. For simulation,
 /// we provide a memory-mapped version to demonstrate register modeling.
-#[derive(Debug, serde::Serialize)]
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub struct Tmp102 {
     pub temp: i16,   // 0x00 - Temperature (12-bit)
     pub config: u16, // 0x01 - Configuration
     pub t_low: i16,  // 0x02 - T_LOW
     pub t_high: i16, // 0x03 - T_HIGH
 
-    #[serde(skip)]
+    #[serde(default)]
     ticks: u32,
 }
 
@@ -59,6 +59,13 @@ impl Default for Tmp102 {
     }
 }
 
+const REGISTERS: &[(&str, u64)] = &[
+    ("TEMP", 0x00),
+    ("CONFIG", 0x04),
+    ("T_LOW", 0x08),
+    ("T_HIGH", 0x0C),
+];
+
 impl Peripheral for Tmp102 {
     fn read(&self, offset: u64) -> SimResult<u8> {
         let reg_offset = offset & !3;
@@ -99,6 +106,11 @@ impl Peripheral for Tmp102 {
         serde_json::to_value(self).unwrap_or(serde_json::Value::Null)
     }
 
+    fn restore(&mut self, state: &serde_json::Value) -> anyhow::Result<()> {
+        *self = serde_json::from_value(state.clone())?;
+        Ok(())
+    }
+
     fn as_any(&self) -> Option<&dyn Any> {
         Some(self)
     }
@@ -106,4 +118,8 @@ impl Peripheral for Tmp102 {
     fn as_any_mut(&mut self) -> Option<&mut dyn Any> {
         Some(self)
     }
+
+    fn registers(&self) -> Vec<PeripheralRegister> {
+        PeripheralRegister::from_layout(REGISTERS, |offset| self.read_reg(offset) as u32)
+    }
 }
@@ -0,0 +1,162 @@
+// LabWired - Firmware Simulation Platform
+// Copyright (C) 2026 Andrii Shylenko
+//
+// This software is released under the MIT License.
+// See the LICENSE file in the project root for full license information.
+
+use crate::SimResult;
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+
+/// Base of the Instrumentation Trace Macrocell.
+pub const ITM_BASE: u64 = 0xE000_0000;
+pub const ITM_SIZE: u64 = 0x1000;
+
+/// Stimulus ports 0-31 are consecutive words from the base.
+const STIM_PORTS: u64 = 32;
+const TER: u64 = 0xE00;
+const TPR: u64 = 0xE40;
+const TCR: u64 = 0xE80;
+
+/// TCR.ITMENA: the ITM as a whole is enabled.
+pub const TCR_ITMENA: u32 = 1 << 0;
+
+/// ITM stimulus ports, as used for `printf` over SWO.
+///
+/// Bytes written to an enabled port go to the same capture sink as UART
+/// output (see `SystemBus::attach_uart_tx_sink`); writes to a port whose
+/// TER bit is clear are dropped. Out of reset every port is enabled, as if
+/// a trace probe had configured the ITM, so CMSIS `ITM_SendChar` works
+/// without debugger setup. Reading a port returns 1 (FIFO ready).
+#[derive(Debug)]
+pub struct Itm {
+    sink: Option<Arc<Mutex<Vec<u8>>>>,
+    echo_stdout: bool,
+    ter: u32,
+    tpr: u32,
+    tcr: u32,
+}
+
+/// State saved by `snapshot`.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct ItmSnapshot {
+    ter: u32,
+    tpr: u32,
+    tcr: u32,
+}
+
+impl Default for Itm {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Itm {
+    pub fn new() -> Self {
+        Self {
+            sink: None,
+            echo_stdout: true,
+            ter: u32::MAX,
+            tpr: 0,
+            tcr: TCR_ITMENA,
+        }
+    }
+
+    pub fn set_sink(&mut self, sink: Option<Arc<Mutex<Vec<u8>>>>, echo_stdout: bool) {
+        self.sink = sink;
+        self.echo_stdout = echo_stdout;
+    }
+
+    pub fn set_echo_stdout(&mut self, echo_stdout: bool) {
+        self.echo_stdout = echo_stdout;
+    }
+
+    fn port_enabled(&self, port: u64) -> bool {
+        self.tcr & TCR_ITMENA != 0 && self.ter & (1 << port) != 0
+    }
+
+    fn emit(&self, value: u8) {
+        if let Some(sink) = &self.sink {
+            if let Ok(mut guard) = sink.lock() {
+                guard.push(value);
+            }
+        }
+        if self.echo_stdout {
+            print!("{}", value as char);
+            let _ = io::stdout().flush();
+        }
+    }
+
+    fn read_reg(&self, offset: u64) -> u32 {
+        match offset {
+            o if o < STIM_PORTS * 4 => 1,
+            TER => self.ter,
+            TPR => self.tpr,
+            TCR => self.tcr,
+            _ => 0,
+        }
+    }
+
+    fn write_reg(&mut self, offset: u64, value: u32) {
+        match offset {
+            TER => self.ter = value,
+            TPR => self.tpr = value & 0xF,
+            TCR => self.tcr = value,
+            _ => {}
+        }
+    }
+}
+
+const REGISTERS: &[(&str, u64)] = &[("TER", TER), ("TPR", TPR), ("TCR", TCR)];
+
+impl crate::Peripheral for Itm {
+    fn read(&self, offset: u64) -> SimResult<u8> {
+        let reg_val = self.read_reg(offset & !3);
+        Ok((reg_val >> ((offset % 4) * 8)) as u8)
+    }
+
+    /// A byte, halfword or word write to a stimulus port sends that many
+    /// bytes (wider writes arrive here split, low byte first).
+    fn write(&mut self, offset: u64, value: u8) -> SimResult<()> {
+        let reg_offset = offset & !3;
+        if reg_offset < STIM_PORTS * 4 {
+            if self.port_enabled(reg_offset / 4) {
+                self.emit(value);
+            }
+            return Ok(());
+        }
+        let shift = (offset % 4) * 8;
+        let reg_val = (self.read_reg(reg_offset) & !(0xFF << shift)) | ((value as u32) << shift);
+        self.write_reg(reg_offset, reg_val);
+        Ok(())
+    }
+
+    fn as_any(&self) -> Option<&dyn std::any::Any> {
+        Some(self)
+    }
+
+    fn as_any_mut(&mut self) -> Option<&mut dyn std::any::Any> {
+        Some(self)
+    }
+
+    fn snapshot(&self) -> serde_json::Value {
+        let state = ItmSnapshot {
+            ter: self.ter,
+            tpr: self.tpr,
+            tcr: self.tcr,
+        };
+        serde_json::to_value(state).unwrap_or(serde_json::Value::Null)
+    }
+
+    fn restore(&mut self, state: &serde_json::Value) -> anyhow::Result<()> {
+        let state: ItmSnapshot = serde_json::from_value(state.clone())?;
+        self.ter = state.ter;
+        self.tpr = state.tpr;
+        self.tcr = state.tcr;
+        Ok(())
+    }
+
+    fn registers(&self) -> Vec<crate::PeripheralRegister> {
+        crate::PeripheralRegister::from_layout(REGISTERS, |offset| self.read_reg(offset))
+    }
+}
@@ -6,13 +6,18 @@
 
 pub mod adc;
 pub mod afio;
+pub mod clint;
 pub mod dma;
 pub mod exti;
+pub mod file_memory;
+pub mod fpu;
 pub mod gpio;
 pub mod i2c;
 pub mod i2c_temp_sensor;
+pub mod itm;
 pub mod nvic;
 pub mod rcc;
+pub mod reg32;
 pub mod scb;
 pub mod spi;
 pub mod stub;
@@ -4,7 +4,8 @@
 // This software is released under the MIT License.
 // See the LICENSE file in the project root for full license information.
 
-use crate::{Peripheral, SimResult};
+use crate::peripherals::reg32::Reg32Peripheral;
+use crate::{Peripheral, PeripheralRegister, SimResult};
 use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Arc;
 
@@ -13,6 +14,8 @@ use std::sync::Arc;
 pub struct NvicState {
     pub iser: [AtomicU32; 8],
     pub ispr: [AtomicU32; 8],
+    /// IPR0-59: one priority byte per IRQ, four IRQs per word
+    pub ipr: [AtomicU32; 60],
 }
 
 impl Default for NvicState {
@@ -38,10 +41,43 @@ impl Default for NvicState {
                 AtomicU32::new(0),
                 AtomicU32::new(0),
             ],
+            ipr: std::array::from_fn(|_| AtomicU32::new(0)),
         }
     }
 }
 
+/// NVIC state transition reported through
+/// [`SimulationObserver::on_nvic_change`](crate::SimulationObserver::on_nvic_change).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NvicEvent {
+    /// Set in ISER.
+    Enable,
+    /// Cleared through ICER.
+    Disable,
+    /// Set in ISPR, by firmware or a peripheral.
+    Pend,
+    /// Cleared through ICPR.
+    Clear,
+    /// Taken by the core on exception entry.
+    Activate,
+    /// Left by the core on exception return.
+    Deactivate,
+}
+
+impl std::fmt::Display for NvicEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            NvicEvent::Enable => "enable",
+            NvicEvent::Disable => "disable",
+            NvicEvent::Pend => "pend",
+            NvicEvent::Clear => "clear",
+            NvicEvent::Activate => "activate",
+            NvicEvent::Deactivate => "deactivate",
+        };
+        f.write_str(name)
+    }
+}
+
 /// Nested Vectored Interrupt Controller (NVIC) mock.
 #[derive(Debug, Clone)]
 pub struct Nvic {
@@ -65,49 +101,98 @@ impl Nvic {
             false
         }
     }
+
+    /// ISER0-7 followed by ISPR0-7.
+    pub(crate) fn enable_pend_words(&self) -> [u32; 16] {
+        std::array::from_fn(|i| {
+            let regs = if i < 8 {
+                &self.state.iser
+            } else {
+                &self.state.ispr
+            };
+            regs[i % 8].load(Ordering::SeqCst)
+        })
+    }
 }
 
-impl Peripheral for Nvic {
-    fn read(&self, offset: u64) -> SimResult<u8> {
-        let reg_idx = (offset / 4) as usize;
-        let byte_offset = (offset % 4) as usize;
+/// IPR is left out to keep debugger views short.
+const REGISTERS: &[(&str, u64)] = &[
+    ("ISER0", 0x00),
+    ("ISER1", 0x04),
+    ("ISER2", 0x08),
+    ("ISER3", 0x0C),
+    ("ISER4", 0x10),
+    ("ISER5", 0x14),
+    ("ISER6", 0x18),
+    ("ISER7", 0x1C),
+    ("ISPR0", 0x100),
+    ("ISPR1", 0x104),
+    ("ISPR2", 0x108),
+    ("ISPR3", 0x10C),
+    ("ISPR4", 0x110),
+    ("ISPR5", 0x114),
+    ("ISPR6", 0x118),
+    ("ISPR7", 0x11C),
+];
 
-        let val = if offset < 0x20 {
+/// ISER/ICER/ISPR/ICPR bits act independently and IPR holds byte-wide
+/// fields, so every byte write takes effect as it arrives.
+impl Reg32Peripheral for Nvic {
+    fn read_reg(&self, offset: u64) -> u32 {
+        if offset < 0x20 {
             // ISER0-7
-            self.state.iser[reg_idx].load(Ordering::SeqCst)
+            self.state.iser[(offset / 4) as usize].load(Ordering::SeqCst)
         } else if (0x100..0x120).contains(&offset) {
             // ISPR0-7
-            let real_idx = (offset - 0x100) / 4;
-            self.state.ispr[real_idx as usize].load(Ordering::SeqCst)
+            self.state.ispr[((offset - 0x100) / 4) as usize].load(Ordering::SeqCst)
+        } else if (0x300..0x3F0).contains(&offset) {
+            // IPR0-59
+            self.state.ipr[((offset - 0x300) / 4) as usize].load(Ordering::SeqCst)
         } else {
             0
-        };
-
-        Ok(((val >> (byte_offset * 8)) & 0xFF) as u8)
+        }
     }
 
-    fn write(&mut self, offset: u64, value: u8) -> SimResult<()> {
-        let reg_idx = (offset / 4) as usize;
-        let byte_offset = (offset % 4) as usize;
-        let mask = (value as u32) << (byte_offset * 8);
-
+    fn on_reg_write(&mut self, offset: u64, value: u32) {
         if offset < 0x20 {
             // ISER: Writing 1 sets the enable bit
-            self.state.iser[reg_idx].fetch_or(mask, Ordering::SeqCst);
+            self.state.iser[(offset / 4) as usize].fetch_or(value, Ordering::SeqCst);
         } else if (0x80..0xA0).contains(&offset) {
             // ICER: Writing 1 clears the enable bit
-            let real_idx = reg_idx - 0x80 / 4;
-            self.state.iser[real_idx].fetch_and(!mask, Ordering::SeqCst);
+            let idx = ((offset - 0x80) / 4) as usize;
+            self.state.iser[idx].fetch_and(!value, Ordering::SeqCst);
         } else if (0x100..0x120).contains(&offset) {
             // ISPR: Writing 1 sets the pending bit
-            let real_idx = reg_idx - 0x100 / 4;
-            self.state.ispr[real_idx].fetch_or(mask, Ordering::SeqCst);
+            let idx = ((offset - 0x100) / 4) as usize;
+            self.state.ispr[idx].fetch_or(value, Ordering::SeqCst);
         } else if (0x180..0x1A0).contains(&offset) {
             // ICPR: Writing 1 clears the pending bit
-            let real_idx = reg_idx - 0x180 / 4;
-            self.state.ispr[real_idx].fetch_and(!mask, Ordering::SeqCst);
+            let idx = ((offset - 0x180) / 4) as usize;
+            self.state.ispr[idx].fetch_and(!value, Ordering::SeqCst);
+        } else if (0x300..0x3F0).contains(&offset) {
+            // IPR: byte-wide priority fields
+            self.state.ipr[((offset - 0x300) / 4) as usize].store(value, Ordering::SeqCst);
         }
+    }
+}
+
+impl Peripheral for Nvic {
+    fn read(&self, offset: u64) -> SimResult<u8> {
+        Ok(self.read_byte(offset))
+    }
+
+    fn write(&mut self, offset: u64, value: u8) -> SimResult<()> {
+        self.write_byte(offset, value);
+        Ok(())
+    }
 
+    fn write_u16(&mut self, offset: u64, value: u16) -> SimResult<()> {
+        self.write_half(offset, value);
+        Ok(())
+    }
+
+    fn write_u32(&mut self, offset: u64, value: u32) -> SimResult<()> {
+        self.write_word(offset, value);
         Ok(())
     }
 
@@ -124,9 +209,60 @@ impl Peripheral for Nvic {
             .iter()
             .map(|a| a.load(Ordering::Relaxed))
             .collect();
+        let ipr: Vec<u32> = self
+            .state
+            .ipr
+            .iter()
+            .map(|a| a.load(Ordering::Relaxed))
+            .collect();
         serde_json::json!({
             "iser": iser,
             "ispr": ispr,
+            "ipr": ipr,
         })
     }
+
+    fn restore(&mut self, state: &serde_json::Value) -> anyhow::Result<()> {
+        let iser: Vec<u32> = serde_json::from_value(state["iser"].clone())?;
+        let ispr: Vec<u32> = serde_json::from_value(state["ispr"].clone())?;
+        for (reg, val) in self.state.iser.iter().zip(iser) {
+            reg.store(val, Ordering::SeqCst);
+        }
+        for (reg, val) in self.state.ispr.iter().zip(ispr) {
+            reg.store(val, Ordering::SeqCst);
+        }
+        // Snapshots from before priorities were modelled have no IPR.
+        let ipr: Vec<u32> = match state.get("ipr") {
+            Some(ipr) => serde_json::from_value(ipr.clone())?,
+            None => Vec::new(),
+        };
+        for (reg, val) in self
+            .state
+            .ipr
+            .iter()
+            .zip(ipr.into_iter().chain(std::iter::repeat(0)))
+        {
+            reg.store(val, Ordering::SeqCst);
+        }
+        Ok(())
+    }
+
+    fn registers(&self) -> Vec<PeripheralRegister> {
+        PeripheralRegister::from_layout(REGISTERS, |offset| {
+            let regs = if offset < 0x100 {
+                &self.state.iser
+            } else {
+                &self.state.ispr
+            };
+            regs[((offset & 0xFF) / 4) as usize].load(Ordering::SeqCst)
+        })
+    }
+
+    fn as_any(&self) -> Option<&dyn std::any::Any> {
+        Some(self)
+    }
+
+    fn as_any_mut(&mut self) -> Option<&mut dyn std::any::Any> {
+        Some(self)
+    }
 }
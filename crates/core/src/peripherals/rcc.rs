@@ -4,23 +4,108 @@
 // This software is released under the MIT License.
 // See the LICENSE file in the project root for full license information.
 
-use crate::SimResult;
+use crate::{PeripheralTickResult, SimResult};
+
+const CR_HSION: u32 = 1 << 0;
+const CR_HSIRDY: u32 = 1 << 1;
+const CR_HSEON: u32 = 1 << 16;
+const CR_HSERDY: u32 = 1 << 17;
+const CR_PLLON: u32 = 1 << 24;
+const CR_PLLRDY: u32 = 1 << 25;
+const CR_RDY_MASK: u32 = CR_HSIRDY | CR_HSERDY | CR_PLLRDY;
+/// HSION set, HSITRIM = 16 (ready flags are derived on read)
+const CR_RESET: u32 = 0x0000_0081;
+
+const CFGR_SW_MASK: u32 = 0b11;
+const CFGR_SWS_SHIFT: u32 = 2;
+const CFGR_SWS_MASK: u32 = 0b11 << CFGR_SWS_SHIFT;
+
+/// Oscillator startup delays, in peripheral ticks, between setting an `xxON`
+/// bit in RCC_CR and the matching `xxRDY` flag reading as set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct RccTiming {
+    pub hsi_startup_ticks: u32,
+    pub hse_startup_ticks: u32,
+    pub pll_lock_ticks: u32,
+}
+
+impl Default for RccTiming {
+    fn default() -> Self {
+        Self {
+            hsi_startup_ticks: 0,
+            hse_startup_ticks: 100,
+            pll_lock_ticks: 50,
+        }
+    }
+}
 
 /// Minimal RCC (Reset and Clock Control) peripheral
 /// Base address: 0x4002_1000
-#[derive(Debug, Default, serde::Serialize)]
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 pub struct Rcc {
+    cr: u32,
+    cfgr: u32,
     apb1enr: u32,
     apb2enr: u32,
+    timing: RccTiming,
+    /// Ticks left until HSI, HSE and PLL report ready.
+    hsi_pending: u32,
+    hse_pending: u32,
+    pll_pending: u32,
+}
+
+impl Default for Rcc {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Rcc {
     pub fn new() -> Self {
-        Self::default()
+        Self::with_timing(RccTiming::default())
+    }
+
+    pub fn with_timing(timing: RccTiming) -> Self {
+        Self {
+            cr: CR_RESET,
+            cfgr: 0,
+            apb1enr: 0,
+            apb2enr: 0,
+            timing,
+            hsi_pending: timing.hsi_startup_ticks,
+            hse_pending: 0,
+            pll_pending: 0,
+        }
+    }
+
+    /// RCC whose HSIRDY flag only reads as set after `ticks` peripheral ticks.
+    pub fn with_hsi_startup(ticks: u32) -> Self {
+        Self::with_timing(RccTiming {
+            hsi_startup_ticks: ticks,
+            ..RccTiming::default()
+        })
+    }
+
+    fn ready_flags(&self) -> u32 {
+        let mut flags = 0;
+        if self.cr & CR_HSION != 0 && self.hsi_pending == 0 {
+            flags |= CR_HSIRDY;
+        }
+        if self.cr & CR_HSEON != 0 && self.hse_pending == 0 {
+            flags |= CR_HSERDY;
+        }
+        if self.cr & CR_PLLON != 0 && self.pll_pending == 0 {
+            flags |= CR_PLLRDY;
+        }
+        flags
     }
 
     fn read_reg(&self, offset: u64) -> u32 {
         match offset {
+            0x00 => self.cr | self.ready_flags(),
+            0x04 => self.cfgr,
             0x18 => self.apb2enr,
             0x1C => self.apb1enr,
             _ => 0,
@@ -29,6 +114,25 @@ impl Rcc {
 
     fn write_reg(&mut self, offset: u64, value: u32) {
         match offset {
+            0x00 => {
+                let value = value & !CR_RDY_MASK;
+                let turned_on = value & !self.cr;
+                if turned_on & CR_HSION != 0 {
+                    self.hsi_pending = self.timing.hsi_startup_ticks;
+                }
+                if turned_on & CR_HSEON != 0 {
+                    self.hse_pending = self.timing.hse_startup_ticks;
+                }
+                if turned_on & CR_PLLON != 0 {
+                    self.pll_pending = self.timing.pll_lock_ticks;
+                }
+                self.cr = value;
+            }
+            // The clock switch completes immediately: SWS mirrors SW
+            0x04 => {
+                let sw = value & CFGR_SW_MASK;
+                self.cfgr = (value & !CFGR_SWS_MASK) | (sw << CFGR_SWS_SHIFT);
+            }
             0x18 => self.apb2enr = value,
             0x1C => self.apb1enr = value,
             _ => {}
@@ -36,6 +140,13 @@ impl Rcc {
     }
 }
 
+const REGISTERS: &[(&str, u64)] = &[
+    ("CR", 0x00),
+    ("CFGR", 0x04),
+    ("APB2ENR", 0x18),
+    ("APB1ENR", 0x1C),
+];
+
 impl crate::Peripheral for Rcc {
     fn read(&self, offset: u64) -> SimResult<u8> {
         let reg_offset = offset & !3;
@@ -57,7 +168,29 @@ impl crate::Peripheral for Rcc {
         Ok(())
     }
 
+    fn tick(&mut self) -> PeripheralTickResult {
+        if self.cr & CR_HSION != 0 {
+            self.hsi_pending = self.hsi_pending.saturating_sub(1);
+        }
+        if self.cr & CR_HSEON != 0 {
+            self.hse_pending = self.hse_pending.saturating_sub(1);
+        }
+        if self.cr & CR_PLLON != 0 {
+            self.pll_pending = self.pll_pending.saturating_sub(1);
+        }
+        PeripheralTickResult::default()
+    }
+
     fn snapshot(&self) -> serde_json::Value {
         serde_json::to_value(self).unwrap_or(serde_json::Value::Null)
     }
+
+    fn restore(&mut self, state: &serde_json::Value) -> anyhow::Result<()> {
+        *self = serde_json::from_value(state.clone())?;
+        Ok(())
+    }
+
+    fn registers(&self) -> Vec<crate::PeripheralRegister> {
+        crate::PeripheralRegister::from_layout(REGISTERS, |offset| self.read_reg(offset))
+    }
 }
@@ -0,0 +1,105 @@
+// LabWired - Firmware Simulation Platform
+// Copyright (C) 2026 Andrii Shylenko
+//
+// This software is released under the MIT License.
+// See the LICENSE file in the project root for full license information.
+
+/// Bytes of a word register written so far through the byte path.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct WriteLatch {
+    offset: u64,
+    value: u32,
+    /// One bit per byte lane that holds written data.
+    lanes: u8,
+}
+
+fn replace_lane(word: u32, lane: u32, byte: u8) -> u32 {
+    let shift = lane * 8;
+    (word & !(0xFF << shift)) | ((byte as u32) << shift)
+}
+
+/// Peripheral whose registers are 32 bits wide.
+///
+/// Halfword and word accesses from the bus dispatch to `on_reg_write` as one
+/// write, so a word store to GPIO BSRR acts once. A byte write is merged
+/// into the register's current value (`read_reg`) and takes effect as it
+/// arrives.
+///
+/// Peripherals where a word that arrives byte by byte must still act once
+/// (a mailbox doorbell) return a `WriteLatch` from `write_latch`: byte writes
+/// are then collected and dispatched once the whole word has arrived. A
+/// partial word is completed from `read_reg` and dispatched by
+/// `flush_write`, which runs before any other write and should be called
+/// from `tick`.
+pub trait Reg32Peripheral {
+    /// Current value of the register at the word-aligned `offset`.
+    /// Write-only registers read as zero.
+    fn read_reg(&self, offset: u64) -> u32;
+
+    /// Apply a write of `value` to the register at the word-aligned `offset`.
+    fn on_reg_write(&mut self, offset: u64, value: u32);
+
+    /// Latch collecting byte writes into word writes, if the peripheral
+    /// wants them collected.
+    fn write_latch(&mut self) -> Option<&mut WriteLatch> {
+        None
+    }
+
+    fn read_byte(&self, offset: u64) -> u8 {
+        (self.read_reg(offset & !3) >> ((offset % 4) * 8)) as u8
+    }
+
+    fn write_byte(&mut self, offset: u64, value: u8) {
+        let reg = offset & !3;
+        let lane = (offset % 4) as u32;
+        let Some(&mut latch) = self.write_latch() else {
+            let word = replace_lane(self.read_reg(reg), lane, value);
+            self.on_reg_write(reg, word);
+            return;
+        };
+
+        if latch.lanes != 0 && (latch.offset != reg || latch.lanes & (1 << lane) != 0) {
+            self.flush_write();
+        }
+        let Some(latch) = self.write_latch() else {
+            return;
+        };
+        latch.offset = reg;
+        latch.value = replace_lane(latch.value, lane, value);
+        latch.lanes |= 1 << lane;
+        if latch.lanes == 0x0F {
+            let word = std::mem::take(latch).value;
+            self.on_reg_write(reg, word);
+        }
+    }
+
+    fn write_half(&mut self, offset: u64, value: u16) {
+        self.flush_write();
+        let reg = offset & !3;
+        let lane = (offset % 4) as u32;
+        let word = replace_lane(self.read_reg(reg), lane, value as u8);
+        let word = replace_lane(word, lane + 1, (value >> 8) as u8);
+        self.on_reg_write(reg, word);
+    }
+
+    fn write_word(&mut self, offset: u64, value: u32) {
+        self.flush_write();
+        self.on_reg_write(offset & !3, value);
+    }
+
+    /// Dispatch a partially written word, taking the bytes that were not
+    /// written from `read_reg`.
+    fn flush_write(&mut self) {
+        let Some(latch) = self.write_latch().map(std::mem::take) else {
+            return;
+        };
+        if latch.lanes == 0 {
+            return;
+        }
+        let mut word = self.read_reg(latch.offset);
+        for lane in (0..4).filter(|lane| latch.lanes & (1 << lane) != 0) {
+            word = replace_lane(word, lane, (latch.value >> (lane * 8)) as u8);
+        }
+        self.on_reg_write(latch.offset, word);
+    }
+}
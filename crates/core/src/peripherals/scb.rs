@@ -8,40 +8,62 @@ use crate::SimResult;
 use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Arc;
 
+// ICSR write-one-to-set/clear bits
+const ICSR_NMIPENDSET: u32 = 1 << 31;
+const ICSR_PENDSVSET: u32 = 1 << 28;
+const ICSR_PENDSVCLR: u32 = 1 << 27;
+const ICSR_PENDSTSET: u32 = 1 << 26;
+const ICSR_PENDSTCLR: u32 = 1 << 25;
+const ICSR_VECTACTIVE_MASK: u32 = 0x1FF;
+
+const EXC_NMI: u32 = 2;
+const EXC_PENDSV: u32 = 14;
+const EXC_SYSTICK: u32 = 15;
+
 /// System Control Block (SCB)
 #[derive(Debug, serde::Serialize)]
 pub struct Scb {
     pub cpuid: u32,
+    /// Latched NMIPENDSET/PENDSVSET/PENDSTSET bits, delivered to the core on the next tick
     pub icsr: u32,
     #[serde(skip)]
     pub vtor: Arc<AtomicU32>, // Shared with CPU
+    #[serde(skip)]
+    pub vectactive: Arc<AtomicU32>, // Shared with CPU
     pub aircr: u32,
     pub scr: u32,
     pub ccr: u32,
     pub shpr1: u32,
     pub shpr2: u32,
     pub shpr3: u32,
+    /// System handler control and state; only the fault enable bits matter
+    pub shcsr: u32,
+    #[serde(skip)]
+    pub cfsr: Arc<AtomicU32>, // Shared with CPU, write-one-to-clear
 }
 
 impl Scb {
-    pub fn new(vtor: Arc<AtomicU32>) -> Self {
+    pub fn new(vtor: Arc<AtomicU32>, vectactive: Arc<AtomicU32>, cfsr: Arc<AtomicU32>) -> Self {
         Self {
             cpuid: 0x410F_C241, // Cortex-M4 r0p1
             icsr: 0,
             vtor,
+            vectactive,
             aircr: 0,
             scr: 0,
             ccr: 0,
             shpr1: 0,
             shpr2: 0,
             shpr3: 0,
+            shcsr: 0,
+            cfsr,
         }
     }
 
     fn read_reg(&self, offset: u64) -> u32 {
         match offset {
             0x00 => self.cpuid,
-            0x04 => self.icsr,
+            0x04 => self.icsr | (self.vectactive.load(Ordering::SeqCst) & ICSR_VECTACTIVE_MASK),
             0x08 => self.vtor.load(Ordering::Relaxed),
             0x0C => self.aircr,
             0x10 => self.scr,
@@ -49,13 +71,23 @@ impl Scb {
             0x18 => self.shpr1,
             0x1C => self.shpr2,
             0x20 => self.shpr3,
+            0x24 => self.shcsr,
+            0x28 => self.cfsr.load(Ordering::SeqCst),
             _ => 0,
         }
     }
 
     fn write_reg(&mut self, offset: u64, value: u32) {
         match offset {
-            0x04 => self.icsr = value, // Simplified
+            0x04 => {
+                self.icsr |= value & (ICSR_NMIPENDSET | ICSR_PENDSVSET | ICSR_PENDSTSET);
+                if value & ICSR_PENDSVCLR != 0 {
+                    self.icsr &= !ICSR_PENDSVSET;
+                }
+                if value & ICSR_PENDSTCLR != 0 {
+                    self.icsr &= !ICSR_PENDSTSET;
+                }
+            }
             0x08 => self.vtor.store(value, Ordering::Relaxed),
             0x0C => self.aircr = value,
             0x10 => self.scr = value,
@@ -63,11 +95,29 @@ impl Scb {
             0x18 => self.shpr1 = value,
             0x1C => self.shpr2 = value,
             0x20 => self.shpr3 = value,
+            0x24 => self.shcsr = value,
+            0x28 => {
+                self.cfsr.fetch_and(!value, Ordering::SeqCst);
+            }
             _ => {}
         }
     }
 }
 
+const REGISTERS: &[(&str, u64)] = &[
+    ("CPUID", 0x00),
+    ("ICSR", 0x04),
+    ("VTOR", 0x08),
+    ("AIRCR", 0x0C),
+    ("SCR", 0x10),
+    ("CCR", 0x14),
+    ("SHPR1", 0x18),
+    ("SHPR2", 0x1C),
+    ("SHPR3", 0x20),
+    ("SHCSR", 0x24),
+    ("CFSR", 0x28),
+];
+
 impl crate::Peripheral for Scb {
     fn read(&self, offset: u64) -> SimResult<u8> {
         let reg_offset = offset & !3;
@@ -89,15 +139,66 @@ impl crate::Peripheral for Scb {
         Ok(())
     }
 
+    fn tick(&mut self) -> crate::PeripheralTickResult {
+        let mut explicit_irqs = Vec::new();
+        if self.icsr & ICSR_NMIPENDSET != 0 {
+            explicit_irqs.push(EXC_NMI);
+        }
+        if self.icsr & ICSR_PENDSVSET != 0 {
+            explicit_irqs.push(EXC_PENDSV);
+        }
+        if self.icsr & ICSR_PENDSTSET != 0 {
+            explicit_irqs.push(EXC_SYSTICK);
+        }
+        self.icsr = 0;
+
+        crate::PeripheralTickResult {
+            explicit_irqs,
+            ..Default::default()
+        }
+    }
+
     fn snapshot(&self) -> serde_json::Value {
         let mut value = serde_json::to_value(self).unwrap_or(serde_json::Value::Null);
-        // Inject VTOR value manually since we skip the Arc
+        // Inject VTOR and CFSR values manually since we skip the Arcs
         if let Some(obj) = value.as_object_mut() {
             obj.insert(
                 "vtor".to_string(),
                 serde_json::Value::Number(self.vtor.load(Ordering::Relaxed).into()),
             );
+            obj.insert(
+                "cfsr".to_string(),
+                serde_json::Value::Number(self.cfsr.load(Ordering::Relaxed).into()),
+            );
         }
         value
     }
+
+    fn restore(&mut self, state: &serde_json::Value) -> anyhow::Result<()> {
+        let field = |name: &str| -> anyhow::Result<u32> {
+            state
+                .get(name)
+                .and_then(|v| v.as_u64())
+                .map(|v| v as u32)
+                .ok_or_else(|| anyhow::anyhow!("SCB snapshot missing '{}'", name))
+        };
+        self.cpuid = field("cpuid")?;
+        self.icsr = field("icsr")?;
+        self.vtor.store(field("vtor")?, Ordering::Relaxed);
+        self.aircr = field("aircr")?;
+        self.scr = field("scr")?;
+        self.ccr = field("ccr")?;
+        self.shpr1 = field("shpr1")?;
+        self.shpr2 = field("shpr2")?;
+        self.shpr3 = field("shpr3")?;
+        // Snapshots from before fault status was modelled have neither
+        self.shcsr = field("shcsr").unwrap_or(0);
+        self.cfsr
+            .store(field("cfsr").unwrap_or(0), Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn registers(&self) -> Vec<crate::PeripheralRegister> {
+        crate::PeripheralRegister::from_layout(REGISTERS, |offset| self.read_reg(offset))
+    }
 }
@@ -7,7 +7,7 @@
 use crate::SimResult;
 
 /// STM32F1 compatible SPI peripheral
-#[derive(Debug, Default, serde::Serialize)]
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
 pub struct Spi {
     cr1: u16,
     cr2: u16,
@@ -61,6 +61,18 @@ impl Spi {
     }
 }
 
+const REGISTERS: &[(&str, u64)] = &[
+    ("CR1", 0x00),
+    ("CR2", 0x04),
+    ("SR", 0x08),
+    ("DR", 0x0C),
+    ("CRCPR", 0x10),
+    ("RXCRCR", 0x14),
+    ("TXCRCR", 0x18),
+    ("I2SCFGR", 0x1C),
+    ("I2SPR", 0x20),
+];
+
 impl crate::Peripheral for Spi {
     fn read(&self, offset: u64) -> SimResult<u8> {
         let reg_offset = offset & !3;
@@ -85,4 +97,13 @@ impl crate::Peripheral for Spi {
     fn snapshot(&self) -> serde_json::Value {
         serde_json::to_value(self).unwrap_or(serde_json::Value::Null)
     }
+
+    fn restore(&mut self, state: &serde_json::Value) -> anyhow::Result<()> {
+        *self = serde_json::from_value(state.clone())?;
+        Ok(())
+    }
+
+    fn registers(&self) -> Vec<crate::PeripheralRegister> {
+        crate::PeripheralRegister::from_layout(REGISTERS, |offset| self.read_reg(offset) as u32)
+    }
 }
@@ -8,7 +8,7 @@ use crate::SimResult;
 use std::collections::HashMap;
 
 /// A simple stub peripheral that returns fixed values on read.
-#[derive(Debug, serde::Serialize)]
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub struct StubPeripheral {
     pub values: HashMap<u64, u32>, // mapping offset to value
     pub default_val: u32,
@@ -44,4 +44,38 @@ impl crate::Peripheral for StubPeripheral {
     fn snapshot(&self) -> serde_json::Value {
         serde_json::to_value(self).unwrap_or(serde_json::Value::Null)
     }
+
+    fn restore(&mut self, state: &serde_json::Value) -> anyhow::Result<()> {
+        *self = serde_json::from_value(state.clone())?;
+        Ok(())
+    }
+
+    /// Accepts `default_value` (read back at unmapped offsets) and `values`
+    /// (offset -> value, offsets as `0x`-prefixed hex or decimal strings).
+    fn reconfigure(&mut self, config: &HashMap<String, serde_json::Value>) -> anyhow::Result<()> {
+        // Applied to a copy and swapped in once every key is valid
+        let mut default_val = self.default_val;
+        let mut values = self.values.clone();
+        for (key, value) in config {
+            match key.as_str() {
+                "default_value" => default_val = serde_json::from_value(value.clone())?,
+                "values" => {
+                    let table: HashMap<String, u32> = serde_json::from_value(value.clone())?;
+                    for (offset, val) in table {
+                        let parsed = match offset.strip_prefix("0x") {
+                            Some(hex) => u64::from_str_radix(hex, 16),
+                            None => offset.parse(),
+                        };
+                        let offset = parsed
+                            .map_err(|_| anyhow::anyhow!("invalid stub offset '{}'", offset))?;
+                        values.insert(offset & !3, val);
+                    }
+                }
+                other => anyhow::bail!("unknown stub config key '{}'", other),
+            }
+        }
+        self.default_val = default_val;
+        self.values = values;
+        Ok(())
+    }
 }
@@ -4,11 +4,12 @@
 // This software is released under the MIT License.
 // See the LICENSE file in the project root for full license information.
 
+use crate::peripherals::reg32::Reg32Peripheral;
 use crate::SimResult;
 
 /// Mocked SysTick Timer peripheral
 /// Standard address: 0xE000_E010
-#[derive(Debug, Default, serde::Serialize)]
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
 pub struct Systick {
     csr: u32,
     rvr: u32,
@@ -25,7 +26,9 @@ impl Systick {
             calib: 0x4000_0000, // No reference clock, no skew
         }
     }
+}
 
+impl Reg32Peripheral for Systick {
     fn read_reg(&self, offset: u64) -> u32 {
         match offset {
             0x00 => self.csr,
@@ -36,7 +39,7 @@ impl Systick {
         }
     }
 
-    fn write_reg(&mut self, offset: u64, value: u32) {
+    fn on_reg_write(&mut self, offset: u64, value: u32) {
         match offset {
             0x00 => {
                 self.csr = value & 0x7;
@@ -53,25 +56,25 @@ impl Systick {
     }
 }
 
+const REGISTERS: &[(&str, u64)] = &[("CSR", 0x00), ("RVR", 0x04), ("CVR", 0x08), ("CALIB", 0x0C)];
+
 impl crate::Peripheral for Systick {
     fn read(&self, offset: u64) -> SimResult<u8> {
-        let reg_offset = offset & !3;
-        let byte_offset = (offset % 4) as u32;
-        let reg_val = self.read_reg(reg_offset);
-        Ok(((reg_val >> (byte_offset * 8)) & 0xFF) as u8)
+        Ok(self.read_byte(offset))
     }
 
     fn write(&mut self, offset: u64, value: u8) -> SimResult<()> {
-        let reg_offset = offset & !3;
-        let byte_offset = (offset % 4) as u32;
-        let mut reg_val = self.read_reg(reg_offset);
+        self.write_byte(offset, value);
+        Ok(())
+    }
 
-        // Modify byte
-        let mask = 0xFF << (byte_offset * 8);
-        reg_val &= !mask;
-        reg_val |= (value as u32) << (byte_offset * 8);
+    fn write_u16(&mut self, offset: u64, value: u16) -> SimResult<()> {
+        self.write_half(offset, value);
+        Ok(())
+    }
 
-        self.write_reg(reg_offset, reg_val);
+    fn write_u32(&mut self, offset: u64, value: u32) -> SimResult<()> {
+        self.write_word(offset, value);
         Ok(())
     }
 
@@ -105,4 +108,13 @@ impl crate::Peripheral for Systick {
     fn snapshot(&self) -> serde_json::Value {
         serde_json::to_value(self).unwrap_or(serde_json::Value::Null)
     }
+
+    fn restore(&mut self, state: &serde_json::Value) -> anyhow::Result<()> {
+        *self = serde_json::from_value(state.clone())?;
+        Ok(())
+    }
+
+    fn registers(&self) -> Vec<crate::PeripheralRegister> {
+        crate::PeripheralRegister::from_layout(REGISTERS, |offset| self.read_reg(offset))
+    }
 }
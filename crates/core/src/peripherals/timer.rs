@@ -5,9 +5,10 @@
 // See the LICENSE file in the project root for full license information.
 
 use crate::SimResult;
+use std::collections::HashMap;
 
 /// Basic STM32 General Purpose Timer (TIM2-TIM5 compatible)
-#[derive(Debug, Default, serde::Serialize)]
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
 pub struct Timer {
     cr1: u32,
     dier: u32,
@@ -28,6 +29,24 @@ impl Timer {
         }
     }
 
+    /// Build a timer from a peripheral's `config` map.
+    ///
+    /// `prescaler` sets the initial PSC value. Unknown keys and values of the
+    /// wrong type are ignored with a warning.
+    pub fn with_config(config: &HashMap<String, serde_yaml::Value>) -> Self {
+        let mut timer = Self::new();
+        for (key, value) in config {
+            match key.as_str() {
+                "prescaler" => match value.as_u64() {
+                    Some(psc) if psc <= 0xFFFF => timer.psc = psc as u32,
+                    _ => tracing::warn!("timer: ignoring invalid prescaler {:?}", value),
+                },
+                other => tracing::warn!("timer: ignoring unknown config key '{}'", other),
+            }
+        }
+        timer
+    }
+
     fn read_reg(&self, offset: u64) -> u32 {
         match offset {
             0x00 => self.cr1,
@@ -53,6 +72,15 @@ impl Timer {
     }
 }
 
+const REGISTERS: &[(&str, u64)] = &[
+    ("CR1", 0x00),
+    ("DIER", 0x0C),
+    ("SR", 0x10),
+    ("CNT", 0x24),
+    ("PSC", 0x28),
+    ("ARR", 0x2C),
+];
+
 impl crate::Peripheral for Timer {
     fn read(&self, offset: u64) -> SimResult<u8> {
         let reg_offset = offset & !3;
@@ -112,4 +140,13 @@ impl crate::Peripheral for Timer {
     fn snapshot(&self) -> serde_json::Value {
         serde_json::to_value(self).unwrap_or(serde_json::Value::Null)
     }
+
+    fn restore(&mut self, state: &serde_json::Value) -> anyhow::Result<()> {
+        *self = serde_json::from_value(state.clone())?;
+        Ok(())
+    }
+
+    fn registers(&self) -> Vec<crate::PeripheralRegister> {
+        crate::PeripheralRegister::from_layout(REGISTERS, |offset| self.read_reg(offset))
+    }
 }
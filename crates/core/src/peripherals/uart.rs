@@ -4,42 +4,146 @@
 // This software is released under the MIT License.
 // See the LICENSE file in the project root for full license information.
 
-use crate::SimResult;
+use crate::{PeripheralRegister, SimResult};
+use std::collections::{HashMap, VecDeque};
 use std::io::{self, Write};
 use std::sync::{Arc, Mutex};
 
+const SR_RXNE: u8 = 0x20;
+
 /// Simple UART mock.
 /// Writes to Data Register (offset 0x0) correspond to stdout writes.
-#[derive(Debug, Default, serde::Serialize)]
+/// Reads from the Data Register pop bytes from the RX FIFO, if one is attached;
+/// `peek` leaves the FIFO alone.
+#[derive(Debug, serde::Serialize)]
 pub struct Uart {
     #[serde(skip)]
     sink: Option<Arc<Mutex<Vec<u8>>>>,
+    #[serde(skip)]
+    rx: Option<Arc<Mutex<VecDeque<u8>>>>,
     echo_stdout: bool,
+    /// `echo_stdout` from the chip descriptor; when false, `set_sink`
+    /// cannot turn echoing back on for this instance.
+    #[serde(skip)]
+    echo_allowed: bool,
+    /// Configured baud rate. Informational only: TX/RX are not timed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    baud: Option<u32>,
+}
+
+impl Default for Uart {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Uart {
     pub fn new() -> Self {
         Self {
             sink: None,
+            rx: None,
             echo_stdout: true,
+            echo_allowed: true,
+            baud: None,
+        }
+    }
+
+    /// Build a UART from a peripheral's `config` map.
+    ///
+    /// Recognised keys are `echo_stdout` (bool) and `baud` (integer).
+    /// Unknown keys and values of the wrong type are ignored with a warning.
+    pub fn with_config(config: &HashMap<String, serde_yaml::Value>) -> Self {
+        let mut uart = Self::new();
+        for (key, value) in config {
+            match (key.as_str(), value) {
+                ("echo_stdout", serde_yaml::Value::Bool(echo)) => {
+                    uart.echo_stdout = *echo;
+                    uart.echo_allowed = *echo;
+                }
+                ("baud", v) if v.as_u64().is_some_and(|b| b <= u32::MAX as u64) => {
+                    uart.baud = v.as_u64().map(|b| b as u32);
+                }
+                ("echo_stdout" | "baud", v) => {
+                    tracing::warn!("uart: ignoring invalid value {:?} for '{}'", v, key);
+                }
+                (other, _) => tracing::warn!("uart: ignoring unknown config key '{}'", other),
+            }
         }
+        uart
     }
 
     pub fn set_sink(&mut self, sink: Option<Arc<Mutex<Vec<u8>>>>, echo_stdout: bool) {
         self.sink = sink;
-        self.echo_stdout = echo_stdout;
+        self.echo_stdout = echo_stdout && self.echo_allowed;
+    }
+
+    /// Echo transmitted bytes to the host's stdout, as far as the chip
+    /// descriptor's `echo_stdout` allows.
+    pub fn set_echo_stdout(&mut self, echo_stdout: bool) {
+        self.echo_stdout = echo_stdout && self.echo_allowed;
+    }
+
+    /// Whether transmitted bytes are echoed to the host's stdout.
+    pub fn echoes_stdout(&self) -> bool {
+        self.echo_stdout
+    }
+
+    /// Attach an RX FIFO. Bytes pushed into it by the host are received by the firmware.
+    pub fn set_rx_source(&mut self, rx: Option<Arc<Mutex<VecDeque<u8>>>>) {
+        self.rx = rx;
+    }
+
+    /// Queue `bytes` for the firmware to receive, attaching an RX FIFO if
+    /// there is none yet.
+    pub fn push_rx(&mut self, bytes: &[u8]) {
+        let rx = self.rx.get_or_insert_with(Arc::default);
+        if let Ok(mut fifo) = rx.lock() {
+            fifo.extend(bytes);
+        }
+    }
+
+    fn rx_pending(&self) -> bool {
+        self.rx
+            .as_ref()
+            .and_then(|rx| rx.lock().ok().map(|guard| !guard.is_empty()))
+            .unwrap_or(false)
+    }
+
+    /// Next received byte without consuming it.
+    fn rx_peek(&self) -> u8 {
+        self.rx
+            .as_ref()
+            .and_then(|rx| rx.lock().ok().and_then(|guard| guard.front().copied()))
+            .unwrap_or(0)
+    }
+
+    fn rx_pop(&self) -> u8 {
+        self.rx
+            .as_ref()
+            .and_then(|rx| rx.lock().ok().and_then(|mut guard| guard.pop_front()))
+            .unwrap_or(0)
     }
 }
 
+const REGISTERS: &[(&str, u64)] = &[("SR", 0x00), ("DR", 0x04)];
+
 impl crate::Peripheral for Uart {
     fn read(&self, offset: u64) -> SimResult<u8> {
         match offset {
-            0x00 => Ok(0xC0), // SR: TXE=1, TC=1 (Ready)
-            0x04 => Ok(0x00), // DR: Always return 0 for reads
+            // SR: TXE=1, TC=1 (Ready), RXNE when the RX FIFO has data
+            0x00 => Ok(0xC0 | if self.rx_pending() { SR_RXNE } else { 0 }),
+            0x04 => Ok(self.rx_pop()), // DR: Next received byte (0 when empty)
             _ => Ok(0),
         }
     }
 
+    fn peek(&self, offset: u64) -> SimResult<u8> {
+        match offset {
+            0x04 => Ok(self.rx_peek()),
+            _ => self.read(offset),
+        }
+    }
+
     fn write(&mut self, offset: u64, value: u8) -> SimResult<()> {
         // STM32 USART DR is at offset 0x04
         if offset == 0x04 || offset == 0x00 {
@@ -75,4 +179,8 @@ impl crate::Peripheral for Uart {
     fn snapshot(&self) -> serde_json::Value {
         serde_json::to_value(self).unwrap_or(serde_json::Value::Null)
     }
+
+    fn registers(&self) -> Vec<PeripheralRegister> {
+        PeripheralRegister::from_layout(REGISTERS, |offset| self.peek(offset).unwrap_or(0) as u32)
+    }
 }
@@ -0,0 +1,138 @@
+// LabWired - Firmware Simulation Platform
+// Copyright (C) 2026 Andrii Shylenko
+//
+// This software is released under the MIT License.
+// See the LICENSE file in the project root for full license information.
+
+use crate::{Arch, Bus, DmaRequest, SimResult, SimulationError};
+use std::cell::Cell;
+use std::ops::Range;
+
+/// Operations untrusted firmware may not perform.
+///
+/// A denied instruction is not executed and a denied write is dropped; in
+/// both cases `Machine::step` fails with `SimulationError::PolicyViolation`.
+/// As with watchpoints, only CPU accesses are checked: debugger writes and
+/// DMA are not restricted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecutionPolicy {
+    pub arch: Arch,
+    /// Address ranges the CPU may not write.
+    pub deny_writes: Vec<Range<u64>>,
+    /// Mnemonics, as in instruction trace records (e.g. `cpsid`, `ecall`),
+    /// that may not execute. Compared case-insensitively. 32-bit Thumb
+    /// instructions have no mnemonic of their own and are never denied.
+    pub deny_instructions: Vec<String>,
+}
+
+impl ExecutionPolicy {
+    pub fn new(arch: Arch) -> Self {
+        Self {
+            arch,
+            deny_writes: Vec::new(),
+            deny_instructions: Vec::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.deny_writes.is_empty() && self.deny_instructions.is_empty()
+    }
+
+    fn denies_write(&self, addr: u64) -> bool {
+        self.deny_writes.iter().any(|range| range.contains(&addr))
+    }
+
+    /// Whether the instruction at `pc` may not execute.
+    pub(crate) fn denies_instruction(&self, bus: &dyn Bus, pc: u32) -> bool {
+        if self.deny_instructions.is_empty() {
+            return false;
+        }
+        let opcode = match self.arch {
+            Arch::Arm => bus.read_u16((pc & !1) as u64).map(u32::from),
+            Arch::RiscV => bus.read_u16(pc as u64).and_then(|half| {
+                if crate::decoder::riscv::is_compressed(half) {
+                    Ok(half as u32)
+                } else {
+                    bus.read_u32(pc as u64)
+                }
+            }),
+            Arch::Unknown => return false,
+        };
+        // An unreadable PC faults in the CPU as usual
+        let Ok(opcode) = opcode else {
+            return false;
+        };
+        let mnemonic = crate::trace::mnemonic(self.arch, opcode);
+        self.deny_instructions
+            .iter()
+            .any(|denied| denied.eq_ignore_ascii_case(&mnemonic))
+    }
+}
+
+/// Bus wrapper that drops writes the policy denies and records the first.
+///
+/// The error is also returned to the CPU, but cores may swallow store
+/// faults, so the recorded violation is what stops the step.
+pub(crate) struct PolicyBus<'a> {
+    inner: &'a mut dyn Bus,
+    policy: &'a ExecutionPolicy,
+    violation: Cell<Option<u64>>,
+}
+
+impl<'a> PolicyBus<'a> {
+    pub(crate) fn new(inner: &'a mut dyn Bus, policy: &'a ExecutionPolicy) -> Self {
+        Self {
+            inner,
+            policy,
+            violation: Cell::new(None),
+        }
+    }
+
+    /// First denied address written, if any.
+    pub(crate) fn violation(&self) -> Option<u64> {
+        self.violation.get()
+    }
+
+    fn check_write(&self, addr: u64, len: u64) -> SimResult<()> {
+        let Some(denied) = (addr..addr + len).find(|&a| self.policy.denies_write(a)) else {
+            return Ok(());
+        };
+        if self.violation.get().is_none() {
+            self.violation.set(Some(denied));
+        }
+        Err(SimulationError::PolicyViolation(denied))
+    }
+}
+
+impl Bus for PolicyBus<'_> {
+    fn read_u8(&self, addr: u64) -> SimResult<u8> {
+        self.inner.read_u8(addr)
+    }
+
+    fn peek_u8(&self, addr: u64) -> SimResult<u8> {
+        self.inner.peek_u8(addr)
+    }
+
+    fn write_u8(&mut self, addr: u64, value: u8) -> SimResult<()> {
+        self.check_write(addr, 1)?;
+        self.inner.write_u8(addr, value)
+    }
+
+    fn write_u16(&mut self, addr: u64, value: u16) -> SimResult<()> {
+        self.check_write(addr, 2)?;
+        self.inner.write_u16(addr, value)
+    }
+
+    fn write_u32(&mut self, addr: u64, value: u32) -> SimResult<()> {
+        self.check_write(addr, 4)?;
+        self.inner.write_u32(addr, value)
+    }
+
+    fn tick_peripherals(&mut self) -> Vec<u32> {
+        self.inner.tick_peripherals()
+    }
+
+    fn execute_dma(&mut self, requests: &[DmaRequest]) -> SimResult<()> {
+        self.inner.execute_dma(requests)
+    }
+}
@@ -0,0 +1,167 @@
+// LabWired - Firmware Simulation Platform
+// Copyright (C) 2026 Andrii Shylenko
+//
+// This software is released under the MIT License.
+// See the LICENSE file in the project root for full license information.
+
+use crate::SimulationObserver;
+use serde::Serialize;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// Name given to PCs outside every known function.
+pub const UNKNOWN_FUNCTION: &str = "[unknown]";
+
+/// A function's address range, as found in the firmware's symbol table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionSymbol {
+    pub name: String,
+    pub addr: u64,
+    /// Size in bytes; 0 when the symbol has no size (e.g. an assembly label).
+    pub size: u64,
+}
+
+/// Self-time of one function in a [`ProfileReport`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FunctionProfile {
+    pub function: String,
+    pub instructions: u64,
+    pub cycles: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ProfileReport {
+    pub total_instructions: u64,
+    pub total_cycles: u64,
+    /// Functions that executed at least one instruction, most cycles first.
+    pub functions: Vec<FunctionProfile>,
+}
+
+#[derive(Debug)]
+struct Range {
+    start: u64,
+    end: u64,
+    name: String,
+}
+
+#[derive(Debug)]
+struct ProfileState {
+    /// (instructions, cycles) per range; the extra last slot is `UNKNOWN_FUNCTION`.
+    counts: Vec<(u64, u64)>,
+    /// Slot of the instruction being executed, until its `on_step_end`.
+    current: Option<usize>,
+}
+
+/// Attributes every executed instruction, and the cycles it took, to the
+/// function containing its PC (self-time: callees are counted separately).
+///
+/// The report is written as JSON on `on_simulation_stop` when a path is set.
+/// Symbols without a size extend to the next symbol. Cycles not preceded by
+/// an instruction (exception entry) go to `UNKNOWN_FUNCTION`.
+#[derive(Debug)]
+pub struct Profiler {
+    ranges: Vec<Range>,
+    path: Option<PathBuf>,
+    state: Mutex<ProfileState>,
+}
+
+impl Profiler {
+    pub fn new(mut functions: Vec<FunctionSymbol>, path: Option<PathBuf>) -> Self {
+        // At a shared address, keep the symbol that has a size
+        functions
+            .sort_by(|a, b| (a.addr, a.size == 0, &a.name).cmp(&(b.addr, b.size == 0, &b.name)));
+        functions.dedup_by_key(|f| f.addr);
+
+        let ranges: Vec<Range> = functions
+            .iter()
+            .enumerate()
+            .map(|(i, f)| Range {
+                start: f.addr,
+                end: match (f.size, functions.get(i + 1)) {
+                    (0, Some(next)) => next.addr,
+                    (0, None) => u64::MAX,
+                    (size, _) => f.addr.saturating_add(size),
+                },
+                name: f.name.clone(),
+            })
+            .collect();
+        let state = ProfileState {
+            counts: vec![(0, 0); ranges.len() + 1],
+            current: None,
+        };
+        Self {
+            ranges,
+            path,
+            state: Mutex::new(state),
+        }
+    }
+
+    fn slot(&self, pc: u64) -> usize {
+        let i = self.ranges.partition_point(|r| r.start <= pc);
+        match i.checked_sub(1) {
+            Some(i) if pc < self.ranges[i].end => i,
+            _ => self.ranges.len(),
+        }
+    }
+
+    pub fn report(&self) -> ProfileReport {
+        let Ok(state) = self.state.lock() else {
+            return ProfileReport {
+                total_instructions: 0,
+                total_cycles: 0,
+                functions: Vec::new(),
+            };
+        };
+        let mut functions: Vec<FunctionProfile> = state
+            .counts
+            .iter()
+            .enumerate()
+            .filter(|(_, &(instructions, cycles))| instructions > 0 || cycles > 0)
+            .map(|(i, &(instructions, cycles))| FunctionProfile {
+                function: self
+                    .ranges
+                    .get(i)
+                    .map_or(UNKNOWN_FUNCTION, |r| r.name.as_str())
+                    .to_string(),
+                instructions,
+                cycles,
+            })
+            .collect();
+        functions.sort_by(|a, b| {
+            (b.cycles, b.instructions, &a.function).cmp(&(a.cycles, a.instructions, &b.function))
+        });
+        ProfileReport {
+            total_instructions: functions.iter().map(|f| f.instructions).sum(),
+            total_cycles: functions.iter().map(|f| f.cycles).sum(),
+            functions,
+        }
+    }
+}
+
+impl SimulationObserver for Profiler {
+    fn on_simulation_stop(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let json = serde_json::to_string_pretty(&self.report()).unwrap_or_default();
+        if let Err(e) = std::fs::write(path, json + "\n") {
+            tracing::warn!("Failed to write profile {:?}: {}", path, e);
+        }
+    }
+
+    fn on_step_start(&self, pc: u32, _opcode: u32) {
+        // The Thumb bit is not part of the instruction address
+        let slot = self.slot((pc & !1) as u64);
+        if let Ok(mut state) = self.state.lock() {
+            state.counts[slot].0 += 1;
+            state.current = Some(slot);
+        }
+    }
+
+    fn on_step_end(&self, cycles: u32) {
+        if let Ok(mut state) = self.state.lock() {
+            let slot = state.current.take().unwrap_or(self.ranges.len());
+            state.counts[slot].1 += cycles as u64;
+        }
+    }
+}
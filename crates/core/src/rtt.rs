@@ -0,0 +1,179 @@
+// LabWired - Firmware Simulation Platform
+// Copyright (C) 2026 Andrii Shylenko
+//
+// This software is released under the MIT License.
+// See the LICENSE file in the project root for full license information.
+
+use crate::bus::SystemBus;
+use crate::Bus;
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+
+/// Start of the `acID` field identifying a SEGGER RTT control block.
+pub const RTT_SIGNATURE: &[u8; 11] = b"SEGGER RTT\0";
+
+/// Offset of `aUp[0]` in the control block, after `acID[16]`,
+/// `MaxNumUpBuffers` and `MaxNumDownBuffers`.
+const UP_CHANNELS: u64 = 24;
+/// Size of one `SEGGER_RTT_BUFFER_UP`.
+const CHANNEL_SIZE: u64 = 24;
+const BUFFER: u64 = 4;
+const SIZE: u64 = 8;
+const WR_OFF: u64 = 12;
+const RD_OFF: u64 = 16;
+
+/// Longest wait, in steps, between searches for a control block that is
+/// not there yet. The wait starts at one step and doubles after each miss:
+/// the block is usually set up by `SEGGER_RTT_Init` shortly after boot.
+const MAX_RESCAN_INTERVAL: u32 = 1 << 16;
+
+/// An RTT up-channel (target to host) ring buffer, as read from RAM.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RttUpChannel {
+    /// Address of `aUp[n]` in the control block.
+    pub addr: u64,
+    pub buffer: u32,
+    pub size: u32,
+    pub wr_off: u32,
+    pub rd_off: u32,
+}
+
+impl RttUpChannel {
+    /// Read up-channel `n` of the control block at `control_block`, or
+    /// `None` if it does not exist or looks uninitialised.
+    pub fn read(bus: &SystemBus, control_block: u64, n: u32) -> Option<Self> {
+        let max_up = bus.read_u32(control_block + 16).ok()?;
+        if n >= max_up {
+            return None;
+        }
+        let addr = control_block + UP_CHANNELS + n as u64 * CHANNEL_SIZE;
+        let channel = Self {
+            addr,
+            buffer: bus.read_u32(addr + BUFFER).ok()?,
+            size: bus.read_u32(addr + SIZE).ok()?,
+            wr_off: bus.read_u32(addr + WR_OFF).ok()?,
+            rd_off: bus.read_u32(addr + RD_OFF).ok()?,
+        };
+        let valid =
+            channel.size > 0 && channel.wr_off < channel.size && channel.rd_off < channel.size;
+        valid.then_some(channel)
+    }
+}
+
+/// Host side of SEGGER RTT: finds the control block in RAM and drains
+/// up-channel 0 into a capture sink after every step.
+///
+/// Bytes are consumed the way a debug probe does: the reader copies
+/// `RdOff..WrOff` out of the ring buffer and writes `RdOff` back, so
+/// firmware using a blocking or skipping mode never sees the buffer fill.
+/// No peripheral is involved; the reader only touches the control block.
+#[derive(Debug)]
+pub struct RttReader {
+    sink: Option<Arc<Mutex<Vec<u8>>>>,
+    echo_stdout: bool,
+    control_block: Option<u64>,
+    /// Polls left before the next search while no control block is known.
+    until_scan: u32,
+    /// Polls between that search and the one after it.
+    rescan_interval: u32,
+}
+
+impl RttReader {
+    /// Drain into `sink` and, when `echo_stdout` is set, the host's stdout.
+    pub fn new(sink: Option<Arc<Mutex<Vec<u8>>>>, echo_stdout: bool) -> Self {
+        Self {
+            sink,
+            echo_stdout,
+            control_block: None,
+            until_scan: 0,
+            rescan_interval: 1,
+        }
+    }
+
+    /// Address of the control block, once found.
+    pub fn control_block(&self) -> Option<u64> {
+        self.control_block
+    }
+
+    /// Search RAM for the control block signature (it is word aligned).
+    pub fn find_control_block(bus: &SystemBus) -> Option<u64> {
+        let data = &bus.ram.data;
+        (0..data.len().saturating_sub(RTT_SIGNATURE.len() - 1))
+            .step_by(4)
+            .find(|&i| data[i..i + RTT_SIGNATURE.len()] == RTT_SIGNATURE[..])
+            .map(|i| bus.ram.base_addr + i as u64)
+    }
+
+    /// Search for the control block now, e.g. after loading firmware that
+    /// places it in `.data`.
+    pub fn scan(&mut self, bus: &SystemBus) -> Option<u64> {
+        self.control_block = Self::find_control_block(bus);
+        if let Some(addr) = self.control_block {
+            tracing::info!("RTT control block at {:#x}", addr);
+        }
+        self.until_scan = self.rescan_interval;
+        self.rescan_interval = (self.rescan_interval * 2).min(MAX_RESCAN_INTERVAL);
+        self.control_block
+    }
+
+    /// Copy bytes written to up-channel 0 since the last poll to the sink
+    /// and mark them read. Returns the number of bytes drained.
+    pub fn poll(&mut self, bus: &mut SystemBus) -> usize {
+        let control_block = match self.control_block {
+            Some(addr) => addr,
+            None => {
+                if self.until_scan > 0 {
+                    self.until_scan -= 1;
+                    return 0;
+                }
+                match self.scan(bus) {
+                    Some(addr) => addr,
+                    None => return 0,
+                }
+            }
+        };
+        let Some(channel) = RttUpChannel::read(bus, control_block, 0) else {
+            return 0;
+        };
+        if channel.wr_off == channel.rd_off {
+            return 0;
+        }
+
+        let mut bytes = Vec::new();
+        let mut off = channel.rd_off;
+        while off != channel.wr_off {
+            match bus.read_u8(channel.buffer as u64 + off as u64) {
+                Ok(byte) => bytes.push(byte),
+                Err(_) => return 0,
+            }
+            off = (off + 1) % channel.size;
+        }
+        if bus
+            .write_u32(channel.addr + RD_OFF, channel.wr_off)
+            .is_err()
+        {
+            return 0;
+        }
+
+        if let Some(sink) = &self.sink {
+            if let Ok(mut guard) = sink.lock() {
+                guard.extend_from_slice(&bytes);
+            }
+        }
+        if self.echo_stdout {
+            let mut stdout = io::stdout();
+            let _ = stdout.write_all(&bytes);
+            let _ = stdout.flush();
+        }
+        bytes.len()
+    }
+
+    /// Drain what is left when the run stops, searching for the control
+    /// block first if the rescan backoff had not got round to it yet.
+    pub fn finish(&mut self, bus: &mut SystemBus) -> usize {
+        if self.control_block.is_none() {
+            self.until_scan = 0;
+        }
+        self.poll(bus)
+    }
+}
@@ -75,6 +75,114 @@ impl InterruptLine {
     }
 }
 
+/// An external event applied to a running machine by a `SignalSchedule`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignalEvent {
+    /// Drive input `pin` of the GPIO port peripheral named `port`.
+    GpioInput {
+        port: String,
+        pin: u8,
+        level: DigitalLevel,
+    },
+    /// Queue `bytes` on the receive FIFO of the UART peripheral `name`.
+    UartRx { name: String, bytes: Vec<u8> },
+    /// Pend exception `num`, as `Machine::inject_interrupt` does.
+    PendIrq { num: u32 },
+}
+
+/// External events to apply at given steps, set as `Machine::signals`.
+///
+/// An event scheduled `at_step` N is applied at the start of the step that
+/// follows N executed steps, so the instruction in that step is the first
+/// to observe it. Steps are counted from when the schedule is installed.
+#[derive(Debug, Clone, Default)]
+pub struct SignalSchedule {
+    /// Pending events ordered by step; events at the same step keep their
+    /// insertion order.
+    events: Vec<(u64, SignalEvent)>,
+    steps: u64,
+}
+
+impl SignalSchedule {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Schedule from a `--signals` file.
+    pub fn from_config(script: &labwired_config::SignalScript) -> Self {
+        use labwired_config::SignalEventConfig;
+        let mut schedule = Self::new();
+        for signal in &script.signals {
+            let event = match &signal.event {
+                SignalEventConfig::GpioInput { port, pin, level } => SignalEvent::GpioInput {
+                    port: port.clone(),
+                    pin: *pin,
+                    level: DigitalLevel::from(*level),
+                },
+                SignalEventConfig::UartRx { name, data } => SignalEvent::UartRx {
+                    name: name.clone(),
+                    bytes: data.as_bytes().to_vec(),
+                },
+                SignalEventConfig::PendIrq { num } => SignalEvent::PendIrq { num: *num },
+            };
+            schedule.push(signal.at_step, event);
+        }
+        schedule
+    }
+
+    /// Check that every GPIO and UART event names a peripheral of that kind
+    /// on `bus`, so a misspelt name fails before the run rather than only
+    /// warning when the event comes due.
+    pub fn check_targets(&self, bus: &crate::bus::SystemBus) -> anyhow::Result<()> {
+        use crate::peripherals::{gpio::GpioPort, uart::Uart};
+        let is = |name: &str, kind: std::any::TypeId| {
+            bus.peripherals
+                .iter()
+                .find(|p| p.name == name)
+                .and_then(|p| p.dev.as_any())
+                .is_some_and(|dev| dev.type_id() == kind)
+        };
+        for (step, event) in &self.events {
+            match event {
+                SignalEvent::GpioInput { port, .. }
+                    if !is(port, std::any::TypeId::of::<GpioPort>()) =>
+                {
+                    anyhow::bail!("Signal at step {}: no GPIO port named '{}'", step, port)
+                }
+                SignalEvent::UartRx { name, .. } if !is(name, std::any::TypeId::of::<Uart>()) => {
+                    anyhow::bail!("Signal at step {}: no UART named '{}'", step, name)
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Add `event` at `at_step`. A step already passed applies it on the
+    /// next step.
+    pub fn push(&mut self, at_step: u64, event: SignalEvent) {
+        let index = self.events.partition_point(|(step, _)| *step <= at_step);
+        self.events.insert(index, (at_step, event));
+    }
+
+    /// Whether no events remain to be applied.
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Steps counted so far.
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    /// Events due before the current step runs; advances the step count.
+    pub(crate) fn take_due(&mut self) -> Vec<SignalEvent> {
+        let due = self.events.partition_point(|(step, _)| *step <= self.steps);
+        self.steps += 1;
+        self.events.drain(..due).map(|(_, event)| event).collect()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -90,6 +198,31 @@ mod tests {
         assert!(b);
     }
 
+    #[test]
+    fn test_check_targets_rejects_unknown_names() {
+        let bus = crate::bus::SystemBus::new();
+        let gpio = |port: &str| SignalEvent::GpioInput {
+            port: port.to_string(),
+            pin: 0,
+            level: DigitalLevel::High,
+        };
+        let mut schedule = SignalSchedule::new();
+        schedule.push(1, gpio("gpioa"));
+        schedule.push(
+            2,
+            SignalEvent::UartRx {
+                name: "uart1".to_string(),
+                bytes: b"x".to_vec(),
+            },
+        );
+        schedule.check_targets(&bus).unwrap();
+
+        // uart1 exists, but it is not a GPIO port
+        schedule.push(3, gpio("uart1"));
+        let err = schedule.check_targets(&bus).unwrap_err();
+        assert!(err.to_string().contains("step 3"), "{}", err);
+    }
+
     #[test]
     fn test_interrupt_line() {
         let mut irq = InterruptLine::new();
@@ -11,6 +11,10 @@ use std::collections::HashMap;
 pub struct MachineSnapshot {
     pub cpu: CpuSnapshot,
     pub peripherals: HashMap<String, serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub flash: Option<MemorySnapshot>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ram: Option<MemorySnapshot>,
     // Future: metrics
 }
 
@@ -28,10 +32,104 @@ pub struct ArmCpuSnapshot {
     pub primask: bool,
     pub pending_exceptions: u32,
     pub vtor: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fpu: Option<crate::cpu::fpu::Fpu>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct RiscVCpuSnapshot {
     pub registers: Vec<u32>,
     pub pc: u32,
+    #[serde(default)]
+    pub csrs: crate::cpu::riscv::Csrs,
+}
+
+/// Sparse image of a `LinearMemory` region.
+///
+/// Only runs of non-zero bytes are stored (hex encoded), which keeps
+/// mostly-empty 1MB regions small enough for JSON checkpoints.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct MemorySnapshot {
+    pub base: u64,
+    pub size: u64,
+    pub chunks: Vec<MemoryChunk>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct MemoryChunk {
+    /// Offset from the region base
+    pub offset: u64,
+    /// Hex-encoded bytes
+    pub data: String,
+}
+
+// Zero runs shorter than this are kept inside a chunk instead of splitting it.
+const CHUNK_MERGE_GAP: usize = 16;
+
+impl MemorySnapshot {
+    pub fn capture(base: u64, data: &[u8]) -> Self {
+        let mut chunks = Vec::new();
+        let mut i = 0;
+        while i < data.len() {
+            if data[i] == 0 {
+                i += 1;
+                continue;
+            }
+            let start = i;
+            let mut end = i;
+            while i < data.len() {
+                if data[i] != 0 {
+                    end = i + 1;
+                } else if i - end >= CHUNK_MERGE_GAP {
+                    break;
+                }
+                i += 1;
+            }
+            chunks.push(MemoryChunk {
+                offset: start as u64,
+                data: encode_hex(&data[start..end]),
+            });
+            i = end;
+        }
+
+        Self {
+            base,
+            size: data.len() as u64,
+            chunks,
+        }
+    }
+
+    /// Rebuild the full contents of the region.
+    pub fn to_bytes(&self) -> anyhow::Result<Vec<u8>> {
+        let mut data = vec![0u8; self.size as usize];
+        for chunk in &self.chunks {
+            let bytes = decode_hex(&chunk.data)?;
+            let start = chunk.offset as usize;
+            let end = start + bytes.len();
+            if end > data.len() {
+                anyhow::bail!(
+                    "memory chunk at offset {:#x} ({} bytes) exceeds region size {:#x}",
+                    chunk.offset,
+                    bytes.len(),
+                    self.size
+                );
+            }
+            data[start..end].copy_from_slice(&bytes);
+        }
+        Ok(data)
+    }
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn decode_hex(s: &str) -> anyhow::Result<Vec<u8>> {
+    if !s.len().is_multiple_of(2) || !s.bytes().all(|b| b.is_ascii_hexdigit()) {
+        anyhow::bail!("invalid hex data in memory chunk");
+    }
+    Ok((0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap_or(0))
+        .collect())
 }
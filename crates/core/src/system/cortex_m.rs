@@ -5,7 +5,10 @@
 // See the LICENSE file in the project root for full license information.
 
 use crate::bus::{PeripheralEntry, SystemBus};
-use crate::cpu::CortexM;
+use crate::cpu::fpu::FPCCR_RESET;
+use crate::cpu::{CortexM, Fpu};
+use crate::peripherals::fpu::{FpuControl, FPU_CONTROL_BASE, FPU_CONTROL_SIZE};
+use crate::peripherals::itm::{Itm, ITM_BASE, ITM_SIZE};
 use crate::peripherals::nvic::{Nvic, NvicState};
 use crate::peripherals::scb::Scb;
 use std::sync::atomic::AtomicU32;
@@ -13,15 +16,19 @@ use std::sync::Arc;
 
 pub fn configure_cortex_m(bus: &mut SystemBus) -> (CortexM, Arc<NvicState>) {
     let vtor = Arc::new(AtomicU32::new(0));
+    let vectactive = Arc::new(AtomicU32::new(0));
+    let cfsr = Arc::new(AtomicU32::new(0));
     let nvic_state = Arc::new(NvicState::default());
 
     let mut cpu = CortexM::default();
     cpu.set_shared_vtor(vtor.clone());
+    cpu.set_shared_vectactive(vectactive.clone());
+    cpu.set_shared_cfsr(cfsr.clone());
 
     bus.nvic = Some(nvic_state.clone());
 
     // Ensure SCB exists (VTOR relocation)
-    let scb = Scb::new(vtor);
+    let scb = Scb::new(vtor, vectactive, cfsr);
     if let Some(p) = bus
         .peripherals
         .iter_mut()
@@ -64,5 +71,47 @@ pub fn configure_cortex_m(bus: &mut SystemBus) -> (CortexM, Arc<NvicState>) {
         });
     }
 
+    // ITM stimulus ports (trace output), captured with the UART output
+    let mut itm = Itm::new();
+    if let Some((sink, echo_stdout)) = &bus.tx_sink {
+        itm.set_sink(Some(sink.clone()), *echo_stdout);
+    }
+    if let Some(p) = bus
+        .peripherals
+        .iter_mut()
+        .find(|p| p.name == "itm" || p.base == ITM_BASE)
+    {
+        p.name = "itm".to_string();
+        p.base = ITM_BASE;
+        p.size = ITM_SIZE;
+        p.irq = None;
+        p.dev = Box::new(itm);
+    } else {
+        bus.peripherals.push(PeripheralEntry {
+            name: "itm".to_string(),
+            base: ITM_BASE,
+            size: ITM_SIZE,
+            irq: None,
+            dev: Box::new(itm),
+        });
+    }
+
+    // FP context control (lazy stacking), on cores with an FPU
+    if bus.fpu {
+        cpu.fpu = Some(Fpu::default());
+        let fpccr = Arc::new(AtomicU32::new(FPCCR_RESET));
+        let fpcar = Arc::new(AtomicU32::new(0));
+        cpu.set_shared_fpccr(fpccr.clone());
+        cpu.set_shared_fpcar(fpcar.clone());
+        let _ = bus.remove_peripheral("fpu");
+        bus.peripherals.push(PeripheralEntry {
+            name: "fpu".to_string(),
+            base: FPU_CONTROL_BASE,
+            size: FPU_CONTROL_SIZE,
+            irq: None,
+            dev: Box::new(FpuControl::new(fpccr, fpcar)),
+        });
+    }
+
     (cpu, nvic_state)
 }
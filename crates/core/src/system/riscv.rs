@@ -4,11 +4,40 @@
 // This software is released under the MIT License.
 // See the LICENSE file in the project root for full license information.
 
-use crate::bus::SystemBus;
+use crate::bus::{PeripheralEntry, SystemBus};
 use crate::cpu::RiscV;
+use crate::peripherals::clint::{Clint, CLINT_BASE, CLINT_SIZE};
+use std::sync::atomic::AtomicU32;
+use std::sync::Arc;
 
-pub fn configure_riscv(_bus: &mut SystemBus) -> RiscV {
-    // For now, no specific peripherals (PLIC/CLINT) are mandated for the basic RV32I simulation loop.
-    // Future iterations will add them here.
-    RiscV::new()
+pub fn configure_riscv(bus: &mut SystemBus) -> RiscV {
+    let mip = Arc::new(AtomicU32::new(0));
+
+    let mut cpu = RiscV::new();
+    cpu.set_shared_mip(mip.clone());
+
+    // Ensure the CLINT exists (machine timer and software interrupts).
+    // No PLIC is modelled yet.
+    let clint = Clint::new(mip);
+    if let Some(p) = bus
+        .peripherals
+        .iter_mut()
+        .find(|p| p.name == "clint" || p.base == CLINT_BASE)
+    {
+        p.name = "clint".to_string();
+        p.base = CLINT_BASE;
+        p.size = CLINT_SIZE;
+        p.irq = None;
+        p.dev = Box::new(clint);
+    } else {
+        bus.peripherals.push(PeripheralEntry {
+            name: "clint".to_string(),
+            base: CLINT_BASE,
+            size: CLINT_SIZE,
+            irq: None,
+            dev: Box::new(clint),
+        });
+    }
+
+    cpu
 }
@@ -5,15 +5,16 @@
 // See the LICENSE file in the project root for full license information.
 
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod tests {
     use crate::cpu::CortexM;
     use crate::decoder::arm::{self as decoder, Instruction};
     use crate::peripherals::nvic::NvicState;
-    use crate::{Bus, Cpu, Machine, Peripheral, SimResult};
+    use crate::{Bus, Cpu, Machine, Peripheral, SimResult, SimulationError};
     use labwired_config::{Arch, ChipDescriptor, MemoryRange, PeripheralConfig, SystemManifest};
     use std::collections::HashMap;
     use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
-    use std::sync::Arc;
+    use std::sync::{Arc, Mutex};
 
     fn create_machine() -> VariableMachine {
         // Placeholder name collision? No.
@@ -23,6 +24,12 @@ mod tests {
     }
     type VariableMachine = Machine<CortexM>;
 
+    fn load_thumb(machine: &mut VariableMachine, addr: u64, code: &[u16]) {
+        for (i, op) in code.iter().enumerate() {
+            machine.bus.write_u16(addr + i as u64 * 2, *op).unwrap();
+        }
+    }
+
     #[derive(Debug)]
     struct RecordingPeripheral {
         regs: [u8; 16],
@@ -78,6 +85,35 @@ mod tests {
         }
     }
 
+    /// Two-channel peripheral: each channel completes on the first tick and
+    /// raises its own IRQ.
+    #[derive(Debug)]
+    struct DualChannelPeripheral {
+        channel_irqs: [u32; 2],
+        done: bool,
+    }
+
+    impl Peripheral for DualChannelPeripheral {
+        fn read(&self, _offset: u64) -> SimResult<u8> {
+            Ok(0)
+        }
+
+        fn write(&mut self, _offset: u64, _value: u8) -> SimResult<()> {
+            Ok(())
+        }
+
+        fn tick(&mut self) -> crate::PeripheralTickResult {
+            if self.done {
+                return crate::PeripheralTickResult::default();
+            }
+            self.done = true;
+            crate::PeripheralTickResult {
+                explicit_irqs: self.channel_irqs.to_vec(),
+                ..Default::default()
+            }
+        }
+    }
+
     #[test]
     fn test_decoder_mov() {
         // 0x202A => MOV R0, #42
@@ -195,6 +231,136 @@ mod tests {
         machine.step().unwrap();
     }
 
+    #[test]
+    fn test_uart_rx_fifo() {
+        let mut machine = create_machine();
+        let rx = Arc::new(std::sync::Mutex::new(std::collections::VecDeque::new()));
+        machine.bus.attach_uart_rx_source(rx.clone());
+
+        // SR: RXNE clear while the FIFO is empty
+        assert_eq!(machine.bus.read_u8(0x4000_C000).unwrap() & 0x20, 0);
+
+        rx.lock().unwrap().extend(b"ok");
+        assert_eq!(machine.bus.read_u8(0x4000_C000).unwrap() & 0x20, 0x20);
+        assert_eq!(machine.bus.read_u8(0x4000_C004).unwrap(), b'o');
+        assert_eq!(machine.bus.read_u8(0x4000_C004).unwrap(), b'k');
+        assert_eq!(machine.bus.read_u8(0x4000_C000).unwrap() & 0x20, 0);
+    }
+
+    #[test]
+    fn test_uart_registers_do_not_consume_rx() {
+        let mut machine = create_machine();
+        let rx = Arc::new(std::sync::Mutex::new(std::collections::VecDeque::new()));
+        machine.bus.attach_uart_rx_source(rx.clone());
+        rx.lock().unwrap().extend(b"x");
+
+        let uart = machine
+            .bus
+            .peripherals
+            .iter()
+            .find(|p| p.name == "uart1")
+            .unwrap();
+        let registers: Vec<(&str, u32)> = uart
+            .dev
+            .registers()
+            .iter()
+            .map(|r| (r.name, r.value))
+            .collect();
+        assert_eq!(registers, [("SR", 0xE0), ("DR", b'x' as u32)]);
+        assert_eq!(machine.bus.read_u8(0x4000_C004).unwrap(), b'x');
+    }
+
+    #[test]
+    fn test_debugger_memory_read_does_not_consume_rx() {
+        use crate::DebugControl;
+        let mut machine = create_machine();
+        let rx = Arc::new(std::sync::Mutex::new(std::collections::VecDeque::new()));
+        machine.bus.attach_uart_rx_source(rx.clone());
+        rx.lock().unwrap().extend(b"x");
+
+        assert_eq!(machine.read_memory(0x4000_C000, 8).unwrap()[4], b'x');
+        assert_eq!(machine.read_memory(0x4000_C004, 1).unwrap(), [b'x']);
+        assert_eq!(machine.bus.read_u8(0x4000_C004).unwrap(), b'x');
+        assert_eq!(machine.bus.read_u8(0x4000_C000).unwrap() & 0x20, 0);
+    }
+
+    #[test]
+    fn test_reconfigure_stub_peripheral_mid_run() {
+        let mut machine = create_machine();
+        machine.bus.peripherals.push(crate::bus::PeripheralEntry {
+            name: "sensor".to_string(),
+            base: 0x5000_0000,
+            size: 0x100,
+            irq: None,
+            dev: Box::new(crate::peripherals::stub::StubPeripheral::new(0x42)),
+        });
+        machine.bus.write_u16(0x0, 0xE7FE).unwrap(); // b .
+        machine.step().unwrap();
+        assert_eq!(machine.bus.read_u32(0x5000_0000).unwrap(), 0x42);
+
+        let config = HashMap::from([
+            ("default_value".to_string(), serde_json::json!(0x99)),
+            ("values".to_string(), serde_json::json!({"0x8": 0x1234})),
+        ]);
+        machine
+            .bus
+            .reconfigure_peripheral("sensor", &config)
+            .unwrap();
+        machine.step().unwrap();
+        assert_eq!(machine.bus.read_u32(0x5000_0000).unwrap(), 0x99);
+        assert_eq!(machine.bus.read_u32(0x5000_0008).unwrap(), 0x1234);
+
+        let bad = HashMap::from([("speed".to_string(), serde_json::json!(1))]);
+        assert!(machine.bus.reconfigure_peripheral("sensor", &bad).is_err());
+        // A request with one bad key changes nothing, whatever the key order
+        let partly_bad = HashMap::from([
+            ("default_value".to_string(), serde_json::json!(0x77)),
+            (
+                "values".to_string(),
+                serde_json::json!({"0x8": 1, "nope": 2}),
+            ),
+        ]);
+        assert!(machine
+            .bus
+            .reconfigure_peripheral("sensor", &partly_bad)
+            .is_err());
+        assert_eq!(machine.bus.read_u32(0x5000_0000).unwrap(), 0x99);
+        assert_eq!(machine.bus.read_u32(0x5000_0008).unwrap(), 0x1234);
+        assert!(machine
+            .bus
+            .reconfigure_peripheral("uart1", &config)
+            .is_err());
+        assert!(machine
+            .bus
+            .reconfigure_peripheral("missing", &config)
+            .is_err());
+    }
+
+    #[test]
+    fn test_irq_map_reports_nvic_enable_state() {
+        let mut machine = create_machine();
+        let tim2 = |m: &VariableMachine| {
+            m.bus
+                .irq_map()
+                .into_iter()
+                .find(|e| e.name == "tim2")
+                .unwrap()
+        };
+        assert_eq!(tim2(&machine).irq, Some(28));
+        assert_eq!(tim2(&machine).nvic_enabled, Some(false));
+
+        // NVIC_ISER0 bit 12 enables exception 28
+        machine.bus.write_u32(0xE000_E100, 1 << 12).unwrap();
+        assert_eq!(tim2(&machine).nvic_enabled, Some(true));
+
+        let systick = machine
+            .bus
+            .irq_map()
+            .into_iter()
+            .find(|e| e.name == "systick");
+        assert_eq!(systick.unwrap().nvic_enabled, None);
+    }
+
     #[test]
     fn test_bus_routes_peripheral_reads_writes() {
         let mut bus = crate::bus::SystemBus::new();
@@ -254,6 +420,60 @@ mod tests {
         assert_eq!(ispr0 & 0x1, 0x1, "NVIC ISPR0 bit 0 should be set");
     }
 
+    #[test]
+    fn test_tick_peripheral_pends_multiple_irqs() {
+        let mut bus = crate::bus::SystemBus::new();
+        let nvic_state = Arc::new(NvicState::default());
+        bus.nvic = Some(nvic_state.clone());
+        // Enable IRQ 17 and 18 (NVIC lines 1 and 2)
+        nvic_state.iser[0].store(0b110, Ordering::SeqCst);
+
+        bus.peripherals.push(crate::bus::PeripheralEntry {
+            name: "dual_dma".to_string(),
+            base: 0x5000_3000,
+            size: 0x10,
+            irq: None,
+            dev: Box::new(DualChannelPeripheral {
+                channel_irqs: [17, 18],
+                done: false,
+            }),
+        });
+
+        let irqs = bus.tick_peripherals();
+        assert_eq!(irqs, vec![17, 18]);
+        assert_eq!(nvic_state.ispr[0].load(Ordering::SeqCst) & 0b110, 0b110);
+    }
+
+    #[test]
+    fn test_tick_peripherals_pends_each_irq_once_in_order() {
+        let mut bus = crate::bus::SystemBus::new();
+        let nvic_state = Arc::new(NvicState::default());
+        bus.nvic = Some(nvic_state.clone());
+        nvic_state.iser[0].store(0b10, Ordering::SeqCst);
+
+        // Both raise IRQ 17 (pended in the NVIC, then found by its scan)
+        // and SysTick (returned directly)
+        for (i, base) in [0x5000_3000, 0x5000_4000].into_iter().enumerate() {
+            bus.peripherals.push(crate::bus::PeripheralEntry {
+                name: format!("shared{}", i),
+                base,
+                size: 0x10,
+                irq: None,
+                dev: Box::new(DualChannelPeripheral {
+                    channel_irqs: [17, 15],
+                    done: false,
+                }),
+            });
+        }
+        let (irqs, _costs) = bus.tick_peripherals_fully();
+        assert_eq!(irqs, vec![15, 17]);
+        assert_eq!(nvic_state.ispr[0].load(Ordering::SeqCst), 0b10);
+
+        // Still pending in the NVIC on the next tick, but raised once
+        let irqs = bus.tick_peripherals();
+        assert_eq!(irqs, vec![17]);
+    }
+
     #[test]
     fn test_tick_peripheral_without_nvic_returns_irq() {
         let mut bus = crate::bus::SystemBus::new();
@@ -302,6 +522,7 @@ mod tests {
                     config: HashMap::new(),
                 },
             ],
+            fpu: false,
         };
 
         let manifest = SystemManifest {
@@ -309,6 +530,7 @@ mod tests {
             chip: "test-chip".to_string(),
             memory_overrides: HashMap::new(),
             external_devices: Vec::new(),
+            preload: Vec::new(),
         };
 
         let bus = crate::bus::SystemBus::from_config(&chip, &manifest).unwrap();
@@ -337,6 +559,27 @@ mod tests {
         assert_eq!(bus.read_u32(odr).unwrap() & 0xFFFF, 0x0000);
     }
 
+    #[test]
+    fn test_gpio_bytewise_bsrr_write_is_one_register_action() {
+        let mut bus = crate::bus::SystemBus::new();
+        let gpioa_base = 0x4001_0800;
+        let odr = gpioa_base + 0x0C;
+        let bsrr = gpioa_base + 0x10;
+
+        // Set and reset pin 0 in one write: as a single BSRR action set wins.
+        // Acting on each halfword separately would leave the pin low. A
+        // debugger writing the bytes of the word sends it as one access.
+        bus.write_bytes(bsrr, &0x0001_0001u32.to_le_bytes())
+            .unwrap();
+        assert_eq!(bus.read_u32(odr).unwrap() & 0xFFFF, 0x0001);
+
+        // A lone byte write takes effect at once.
+        bus.write_u8(bsrr, 0x02).unwrap();
+        assert_eq!(bus.read_u32(odr).unwrap() & 0xFFFF, 0x0003);
+        bus.write_u8(odr + 1, 0x01).unwrap();
+        assert_eq!(bus.read_u32(odr).unwrap() & 0xFFFF, 0x0103);
+    }
+
     #[test]
     fn test_from_config_defaults_size_irq_and_base() {
         let chip = ChipDescriptor {
@@ -368,6 +611,7 @@ mod tests {
                     config: HashMap::new(),
                 },
             ],
+            fpu: false,
         };
 
         let manifest = SystemManifest {
@@ -375,6 +619,7 @@ mod tests {
             chip: "test-chip-2".to_string(),
             memory_overrides: HashMap::new(),
             external_devices: Vec::new(),
+            preload: Vec::new(),
         };
 
         let bus = crate::bus::SystemBus::from_config(&chip, &manifest).unwrap();
@@ -416,6 +661,7 @@ mod tests {
                 irq: Some(37),
                 config: HashMap::new(),
             }],
+            fpu: false,
         };
 
         let manifest = SystemManifest {
@@ -423,6 +669,7 @@ mod tests {
             chip: "test-chip-3".to_string(),
             memory_overrides: HashMap::new(),
             external_devices: Vec::new(),
+            preload: Vec::new(),
         };
 
         let bus = crate::bus::SystemBus::from_config(&chip, &manifest).unwrap();
@@ -435,6 +682,239 @@ mod tests {
         assert_eq!(uart1.irq, Some(37));
     }
 
+    #[test]
+    fn test_from_config_applies_memory_overrides() {
+        let chip = ChipDescriptor {
+            name: "test-chip-4".to_string(),
+            arch: Arch::Arm,
+            flash: MemoryRange {
+                base: 0x0,
+                size: "128KB".to_string(),
+            },
+            ram: MemoryRange {
+                base: 0x2000_0000,
+                size: "20KB".to_string(),
+            },
+            peripherals: vec![PeripheralConfig {
+                id: "console".to_string(),
+                r#type: "uart".to_string(),
+                base_address: 0x4000_C000,
+                size: None,
+                irq: Some(53),
+                config: HashMap::new(),
+            }],
+            fpu: false,
+        };
+        let mut manifest = SystemManifest {
+            name: "test-system-4".to_string(),
+            chip: "test-chip-4".to_string(),
+            memory_overrides: HashMap::from([("ram".to_string(), "64KB".to_string())]),
+            external_devices: Vec::new(),
+            preload: Vec::new(),
+        };
+
+        let mut bus = crate::bus::SystemBus::from_config(&chip, &manifest).unwrap();
+        assert_eq!(bus.ram.data.len(), 64 * 1024);
+        assert_eq!(bus.ram.base_addr, 0x2000_0000);
+        assert_eq!(bus.flash.data.len(), 128 * 1024);
+        assert!(bus.write_u32(0x2000_FFFC, 0x1234_5678).is_ok());
+        assert_eq!(bus.peripherals[0].irq, Some(53));
+
+        manifest.memory_overrides = HashMap::from([("sram2".to_string(), "16KB".to_string())]);
+        let err = crate::bus::SystemBus::from_config(&chip, &manifest)
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("memory_overrides"));
+    }
+
+    #[test]
+    fn test_from_config_passes_peripheral_config() {
+        let uart_config: HashMap<String, serde_yaml::Value> =
+            serde_yaml::from_str("echo_stdout: false\nbaud: 115200\nparity: none").unwrap();
+        let timer_config: HashMap<String, serde_yaml::Value> =
+            serde_yaml::from_str("prescaler: 7").unwrap();
+        let chip = ChipDescriptor {
+            name: "test-chip-5".to_string(),
+            arch: Arch::Arm,
+            flash: MemoryRange {
+                base: 0x0,
+                size: "128KB".to_string(),
+            },
+            ram: MemoryRange {
+                base: 0x2000_0000,
+                size: "20KB".to_string(),
+            },
+            peripherals: vec![
+                PeripheralConfig {
+                    id: "uart1".to_string(),
+                    r#type: "uart".to_string(),
+                    base_address: 0x4000_C000,
+                    size: None,
+                    irq: None,
+                    config: uart_config,
+                },
+                PeripheralConfig {
+                    id: "tim2".to_string(),
+                    r#type: "timer".to_string(),
+                    base_address: 0x4000_0000,
+                    size: None,
+                    irq: None,
+                    config: timer_config,
+                },
+            ],
+            fpu: false,
+        };
+        let manifest = SystemManifest {
+            name: "test-system-5".to_string(),
+            chip: "test-chip-5".to_string(),
+            memory_overrides: HashMap::new(),
+            external_devices: Vec::new(),
+            preload: Vec::new(),
+        };
+
+        let mut bus = crate::bus::SystemBus::from_config(&chip, &manifest).unwrap();
+        let sink = Arc::new(Mutex::new(Vec::new()));
+        bus.attach_uart_tx_sink(sink.clone(), true);
+
+        let uart = bus.peripherals[0]
+            .dev
+            .as_any()
+            .and_then(|any| any.downcast_ref::<crate::peripherals::uart::Uart>())
+            .unwrap();
+        assert!(!uart.echoes_stdout());
+
+        bus.write_u8(0x4000_C004, b'A').unwrap();
+        assert_eq!(*sink.lock().unwrap(), b"A");
+        assert_eq!(bus.read_u32(0x4000_0028).unwrap(), 7);
+    }
+
+    #[test]
+    fn test_from_config_accepts_all_listed_peripheral_types() {
+        let backing = std::env::temp_dir().join("labwired-all-types.bin");
+        std::fs::write(&backing, [0u8; 16]).unwrap();
+        let peripherals = crate::bus::PERIPHERAL_TYPES
+            .iter()
+            .enumerate()
+            .map(|(i, ty)| PeripheralConfig {
+                id: format!("{}{}", ty, i),
+                r#type: ty.to_string(),
+                base_address: 0x4000_0000 + i as u64 * 0x1000,
+                size: None,
+                irq: None,
+                // `file` needs a backing file; every other type has defaults
+                config: match *ty {
+                    "file" => HashMap::from([(
+                        "path".to_string(),
+                        serde_yaml::Value::from(backing.to_str().unwrap()),
+                    )]),
+                    _ => HashMap::new(),
+                },
+            })
+            .collect();
+        let chip = ChipDescriptor {
+            name: "test-chip-all".to_string(),
+            arch: Arch::Arm,
+            flash: MemoryRange {
+                base: 0x0,
+                size: "64KB".to_string(),
+            },
+            ram: MemoryRange {
+                base: 0x2000_0000,
+                size: "20KB".to_string(),
+            },
+            peripherals,
+            fpu: false,
+        };
+        let manifest = SystemManifest {
+            name: "test-system-all".to_string(),
+            chip: "test-chip-all".to_string(),
+            memory_overrides: HashMap::new(),
+            external_devices: Vec::new(),
+            preload: Vec::new(),
+        };
+
+        let bus = crate::bus::SystemBus::from_config(&chip, &manifest).unwrap();
+        assert_eq!(bus.peripherals.len(), crate::bus::PERIPHERAL_TYPES.len());
+    }
+
+    fn file_memory_bus(path: &std::path::Path, writable: bool) -> crate::bus::SystemBus {
+        let chip = ChipDescriptor {
+            name: "test-chip-qspi".to_string(),
+            arch: Arch::Arm,
+            flash: MemoryRange {
+                base: 0x0,
+                size: "64KB".to_string(),
+            },
+            ram: MemoryRange {
+                base: 0x2000_0000,
+                size: "20KB".to_string(),
+            },
+            peripherals: vec![PeripheralConfig {
+                id: "qspi".to_string(),
+                r#type: "file".to_string(),
+                base_address: 0x9000_0000,
+                size: None,
+                irq: None,
+                config: HashMap::from([
+                    (
+                        "path".to_string(),
+                        serde_yaml::Value::from(path.to_str().unwrap()),
+                    ),
+                    ("writable".to_string(), serde_yaml::Value::from(writable)),
+                ]),
+            }],
+            fpu: false,
+        };
+        let manifest = SystemManifest {
+            name: "test-system-qspi".to_string(),
+            chip: "test-chip-qspi".to_string(),
+            memory_overrides: HashMap::new(),
+            external_devices: Vec::new(),
+            preload: Vec::new(),
+        };
+        crate::bus::SystemBus::from_config(&chip, &manifest).unwrap()
+    }
+
+    #[test]
+    fn test_file_memory_reads_file_and_persists_writes() {
+        let path = std::env::temp_dir().join("labwired-qspi-rw.bin");
+        std::fs::write(&path, [0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88]).unwrap();
+        let mut bus = file_memory_bus(&path, true);
+        assert_eq!(bus.peripherals[0].size, 8);
+
+        let (cpu, _nvic) = crate::system::cortex_m::configure_cortex_m(&mut bus);
+        let mut machine = Machine::new(cpu, bus);
+        // LDR R1, [R0]; STR R2, [R0, #4]
+        machine.bus.write_u16(0, 0x6801).unwrap();
+        machine.bus.write_u16(2, 0x6042).unwrap();
+        machine.cpu.r0 = 0x9000_0000;
+        machine.cpu.r2 = 0xCAFE_F00D;
+        machine.cpu.pc = 0;
+        machine.step().unwrap();
+        machine.step().unwrap();
+
+        assert_eq!(machine.cpu.r1, 0x4433_2211);
+        assert_eq!(machine.bus.read_u32(0x9000_0004).unwrap(), 0xCAFE_F00D);
+        assert_eq!(
+            std::fs::read(&path).unwrap(),
+            [0x11, 0x22, 0x33, 0x44, 0x0D, 0xF0, 0xFE, 0xCA]
+        );
+        // Past the end of the file is not mapped
+        assert!(machine.bus.read_u8(0x9000_0008).is_err());
+    }
+
+    #[test]
+    fn test_file_memory_read_only_ignores_writes() {
+        let path = std::env::temp_dir().join("labwired-qspi-ro.bin");
+        std::fs::write(&path, [0xAA; 4]).unwrap();
+        let mut bus = file_memory_bus(&path, false);
+
+        bus.write_u32(0x9000_0000, 0).unwrap();
+
+        assert_eq!(bus.read_u32(0x9000_0000).unwrap(), 0xAAAA_AAAA);
+        assert_eq!(std::fs::read(&path).unwrap(), [0xAA; 4]);
+    }
+
     #[test]
     fn test_cpu_execute_sp_rel() {
         let mut machine = create_machine();
@@ -707,6 +1187,37 @@ mod tests {
         assert_eq!(machine.cpu.r7, 20); // R7 was untouched
     }
 
+    #[test]
+    fn test_xpsr_thumb_bit_set_on_reset_and_kept_through_exception() {
+        use crate::cpu::cortex_m::PSR_T;
+        let mut machine = create_machine();
+        machine.bus.write_u32(0x0, 0x2002_0000).unwrap(); // Initial SP
+        machine.bus.write_u32(0x4, 0x0000_0041).unwrap(); // Reset vector
+        machine.bus.write_u32(0x3C, 0x0000_1001).unwrap(); // SysTick vector
+        machine.bus.write_u16(0x40, 0xE7FE).unwrap(); // B .
+        machine.bus.write_u16(0x1000, 0x4770).unwrap(); // BX LR
+
+        machine.cpu.xpsr = 0;
+        machine.reset().unwrap();
+        assert_eq!(machine.cpu.xpsr, PSR_T);
+
+        machine.cpu.set_exception_pending(15);
+        machine.step().unwrap(); // Entry
+        assert_eq!(machine.cpu.pc, 0x1000);
+        let stacked = machine.bus.read_u32(machine.cpu.sp as u64 + 28).unwrap();
+        assert_eq!(stacked, PSR_T);
+        assert_eq!(machine.cpu.xpsr, PSR_T | 15);
+
+        machine.step().unwrap(); // BX LR
+        assert_eq!(machine.cpu.pc & !1, 0x40);
+        assert_eq!(machine.cpu.xpsr, PSR_T);
+
+        // A chip may override the reset value
+        machine.cpu.reset_xpsr = PSR_T | 0x4000_0000;
+        machine.reset().unwrap();
+        assert_eq!(machine.cpu.xpsr, PSR_T | 0x4000_0000);
+    }
+
     #[test]
     fn test_iteration_7_instructions() {
         let mut machine: Machine<CortexM> = create_machine();
@@ -793,6 +1304,48 @@ mod tests {
         assert_eq!(machine.cpu.r0, 200);
     }
 
+    #[test]
+    fn test_ldm_stm_empty_register_list_is_decode_error() {
+        let mut machine = create_machine();
+        machine.cpu.r0 = 0x2000_0000;
+
+        // LDM R0!, {} -> 0xC800
+        machine.bus.write_u16(0, 0xC800).unwrap();
+        machine.cpu.pc = 0;
+        assert!(matches!(
+            machine.step(),
+            Err(SimulationError::DecodeError(0))
+        ));
+        assert_eq!(machine.cpu.pc, 0);
+
+        // STM R0!, {} -> 0xC000
+        machine.bus.write_u16(2, 0xC000).unwrap();
+        machine.cpu.pc = 2;
+        assert!(matches!(
+            machine.step(),
+            Err(SimulationError::DecodeError(2))
+        ));
+        assert_eq!(machine.cpu.r0, 0x2000_0000);
+    }
+
+    #[test]
+    fn test_ldm_loading_its_base_register_suppresses_writeback() {
+        let mut machine = create_machine();
+        machine.bus.write_u32(0x2000_0000, 0x1111_1111).unwrap();
+        machine.bus.write_u32(0x2000_0004, 0x2000_0100).unwrap();
+        machine.bus.write_u32(0x2000_0008, 0x3333_3333).unwrap();
+
+        // LDM R1!, {R0, R1, R2} -> 0xC907
+        machine.cpu.r1 = 0x2000_0000;
+        machine.bus.write_u16(0, 0xC907).unwrap();
+        machine.cpu.pc = 0;
+        machine.step().unwrap();
+
+        assert_eq!(machine.cpu.r0, 0x1111_1111);
+        assert_eq!(machine.cpu.r1, 0x2000_0100); // loaded, not 0x2000_000C
+        assert_eq!(machine.cpu.r2, 0x3333_3333);
+    }
+
     #[test]
     fn test_nvic_external_interrupt() {
         let mut machine: Machine<CortexM> = create_machine();
@@ -814,6 +1367,9 @@ mod tests {
         });
         // (Note: StubPeripheral::tick returns false. I should use a more active one or just pend manually)
 
+        // Exception entry stacks onto RAM
+        machine.cpu.sp = 0x2002_0000;
+
         // Manually pend it in NVIC ISPR
         machine.bus.write_u8(0xE000E100 + 0x100, 1).unwrap(); // ISPR0 bit 0
         machine.step().unwrap();
@@ -826,6 +1382,31 @@ mod tests {
         assert_eq!(machine.cpu.pc, isr_addr); // Should JUMP now
     }
 
+    #[test]
+    fn test_inject_interrupt_enters_vector_table_handler() {
+        let mut machine = machine_with_two_irq_handlers();
+        machine.bus.write_u32(0x3C, 0x3001).unwrap(); // SysTick
+        machine.bus.write_u16(0x3000, 0x4770).unwrap();
+
+        // Core exception: pended on the CPU, taken on the next step.
+        machine.inject_interrupt(15);
+        machine.step().unwrap();
+        assert_eq!(machine.cpu.pc, 0x3000);
+        machine.step().unwrap(); // BX LR
+
+        // IRQ1 while disabled in the NVIC: pending, not taken.
+        machine.inject_interrupt(17);
+        assert_eq!(machine.bus.read_u32(0xE000_E200).unwrap() & 0b10, 0b10); // ISPR0
+        machine.step().unwrap();
+        assert_eq!(machine.cpu.pc, 0x2000_0002);
+
+        machine.bus.write_u32(0xE000_E280, 0b10).unwrap(); // ICPR0
+        machine.bus.write_u32(0xE000_E100, 0b10).unwrap(); // ISER0
+        machine.inject_interrupt(17);
+        machine.step().unwrap();
+        assert_eq!(machine.cpu.pc, 0x1000);
+    }
+
     #[test]
     fn test_vtor_relocation() {
         let mut machine: Machine<CortexM> = create_machine();
@@ -851,23 +1432,350 @@ mod tests {
     }
 
     #[test]
-    fn test_mov_w_instruction() {
-        let mut machine: Machine<CortexM> = create_machine();
-        machine.cpu.pc = 0;
-        machine.cpu.sp = 0x2000_1000;
+    fn test_icsr_pendsvset_runs_pendsv_handler() {
+        let mut machine = create_machine();
 
-        // Test MOV.W R0, #0x55
-        // Encoding: 0xF04F 0x0055
-        // imm12 = 0x055 (i=0, imm3=0, imm8=0x55)
-        // Pattern 0, no rotation -> result = 0x55
-        machine.bus.write_u16(0, 0xF04F).unwrap();
-        machine.bus.write_u16(2, 0x0055).unwrap();
-        machine.step().unwrap();
-        assert_eq!(machine.cpu.r0, 0x55, "MOV.W R0, #0x55 failed");
-        assert_eq!(machine.cpu.pc, 4, "PC should advance by 4");
+        // PendSV (Exception 14) vector at 0x38
+        let handler: u32 = 0x0000_1000;
+        machine.bus.write_u32(0x38, handler | 1).unwrap();
+        // BX LR in the handler
+        machine.bus.write_u16(0x1000, 0x4770).unwrap();
 
-        // Test MOV.W R1, #0x42
-        // Encoding: 0xF04F 0x0142
+        machine.cpu.pc = 0x2000_0000;
+        machine.cpu.sp = 0x2002_0000;
+        assert_eq!(machine.bus.read_u32(0xE000_ED04).unwrap() & 0x1FF, 0);
+
+        // ICSR.PENDSVSET
+        machine.bus.write_u32(0xE000_ED04, 1 << 28).unwrap();
+
+        machine.step().unwrap(); // SCB delivers PendSV
+        machine.step().unwrap(); // Exception entry
+        assert_eq!(machine.cpu.pc, handler);
+        assert_eq!(machine.bus.read_u32(0xE000_ED04).unwrap() & 0x1FF, 14); // VECTACTIVE
+        assert_eq!(machine.bus.read_u32(0xE000_ED04).unwrap() & (1 << 28), 0);
+
+        machine.step().unwrap(); // BX LR (Exception Return)
+        assert_eq!(machine.cpu.pc, 0x2000_0002);
+        assert_eq!(machine.bus.read_u32(0xE000_ED04).unwrap() & 0x1FF, 0);
+    }
+
+    /// Vectors for IRQ1 (exception 17) and IRQ2 (exception 18), each
+    /// handler a single BX LR; the core idles in RAM.
+    fn machine_with_two_irq_handlers() -> VariableMachine {
+        let mut machine = create_machine();
+        machine.bus.write_u32(0x44, 0x1001).unwrap();
+        machine.bus.write_u32(0x48, 0x2001).unwrap();
+        machine.bus.write_u16(0x1000, 0x4770).unwrap();
+        machine.bus.write_u16(0x2000, 0x4770).unwrap();
+        machine.cpu.pc = 0x2000_0000;
+        machine.cpu.sp = 0x2002_0000;
+        machine
+    }
+
+    #[test]
+    fn test_equal_priority_irqs_taken_lowest_number_first() {
+        let mut machine = machine_with_two_irq_handlers();
+        machine.cpu.set_exception_pending(18);
+        machine.cpu.set_exception_pending(17);
+
+        machine.step().unwrap();
+        assert_eq!(machine.cpu.pc, 0x1000);
+        assert_eq!(machine.cpu.pending_exceptions, 1 << 18);
+    }
+
+    #[test]
+    fn test_pending_irq_with_lower_priority_value_taken_first() {
+        let mut machine = machine_with_two_irq_handlers();
+        machine.bus.write_u8(0xE000_E401, 0x80).unwrap(); // IPR: IRQ1
+        machine.bus.write_u8(0xE000_E402, 0x40).unwrap(); // IPR: IRQ2
+        assert_eq!(machine.bus.read_u32(0xE000_E400).unwrap(), 0x0040_8000);
+
+        machine.cpu.set_exception_pending(17);
+        machine.cpu.set_exception_pending(18);
+        machine.step().unwrap();
+        assert_eq!(machine.cpu.pc, 0x2000);
+    }
+
+    #[test]
+    fn test_icsr_pendsvclr_cancels_pendsv() {
+        let mut machine = create_machine();
+        machine.bus.write_u32(0x38, 0x1001).unwrap();
+
+        machine.cpu.pc = 0x2000_0000;
+        machine.cpu.sp = 0x2002_0000;
+
+        machine.bus.write_u32(0xE000_ED04, 1 << 28).unwrap(); // PENDSVSET
+        assert_ne!(machine.bus.read_u32(0xE000_ED04).unwrap() & (1 << 28), 0);
+        machine.bus.write_u32(0xE000_ED04, 1 << 27).unwrap(); // PENDSVCLR
+        assert_eq!(machine.bus.read_u32(0xE000_ED04).unwrap() & (1 << 28), 0);
+
+        machine.step().unwrap();
+        assert_eq!(machine.cpu.pending_exceptions, 0);
+    }
+
+    #[test]
+    fn test_icsr_pendstset_pends_systick() {
+        let mut machine = create_machine();
+        machine.bus.write_u32(0x3C, 0x1001).unwrap();
+
+        machine.cpu.pc = 0x2000_0000;
+        machine.cpu.sp = 0x2002_0000;
+
+        machine.bus.write_u32(0xE000_ED04, 1 << 26).unwrap(); // PENDSTSET
+        machine.step().unwrap();
+        machine.step().unwrap();
+        assert_eq!(machine.cpu.pc, 0x1000);
+        assert_eq!(machine.bus.read_u32(0xE000_ED04).unwrap() & 0x1FF, 15);
+    }
+
+    /// Boot a machine whose RCC needs 100 ticks for HSIRDY, running a loop
+    /// that polls RCC_CR.HSIRDY.
+    fn boot_hsi_poll_loop(
+        startup_cycles: u32,
+    ) -> (VariableMachine, Arc<crate::metrics::PerformanceMetrics>) {
+        let mut machine = create_machine();
+        let rcc = machine
+            .bus
+            .peripherals
+            .iter_mut()
+            .find(|p| p.name == "rcc")
+            .unwrap();
+        rcc.dev = Box::new(crate::peripherals::rcc::Rcc::with_hsi_startup(100));
+
+        let metrics = Arc::new(crate::metrics::PerformanceMetrics::new());
+        machine.observers.push(metrics.clone());
+        machine.startup_cycles = startup_cycles;
+
+        machine.bus.write_u32(0x0, 0x2000_1000).unwrap();
+        machine.bus.write_u32(0x4, 0x41).unwrap();
+        let program: [u16; 6] = [
+            0x4802, // 0x40: ldr r0, [pc, #8]    ; RCC base
+            0x6801, // 0x42: ldr r1, [r0, #0]    ; RCC_CR
+            0x2202, // 0x44: movs r2, #2         ; HSIRDY
+            0x4011, // 0x46: ands r1, r2
+            0xD0FB, // 0x48: beq 0x42
+            0xE7FE, // 0x4A: b .
+        ];
+        load_thumb(&mut machine, 0x40, &program);
+        machine.bus.write_u32(0x4C, 0x4002_1000).unwrap();
+
+        machine.reset().unwrap();
+        (machine, metrics)
+    }
+
+    #[test]
+    fn test_startup_cycles_let_rcc_ready_poll_exit() {
+        let (mut machine, metrics) = boot_hsi_poll_loop(100);
+        assert_eq!(metrics.get_cycles(), 100);
+        assert_eq!(metrics.get_instructions(), 0);
+
+        for _ in 0..5 {
+            machine.step().unwrap();
+        }
+        assert_eq!(machine.cpu.pc & !1, 0x4A);
+    }
+
+    #[test]
+    fn test_rcc_ready_poll_spins_without_startup_cycles() {
+        let (mut machine, metrics) = boot_hsi_poll_loop(0);
+        assert_eq!(metrics.get_cycles(), 0);
+
+        for _ in 0..5 {
+            machine.step().unwrap();
+        }
+        assert_eq!(machine.cpu.pc & !1, 0x42);
+    }
+
+    #[test]
+    fn test_rcc_hse_ready_after_startup_delay() {
+        use crate::peripherals::rcc::{Rcc, RccTiming};
+
+        let mut machine = create_machine();
+        let rcc = machine
+            .bus
+            .peripherals
+            .iter_mut()
+            .find(|p| p.name == "rcc")
+            .unwrap();
+        rcc.dev = Box::new(Rcc::with_timing(RccTiming {
+            hse_startup_ticks: 20,
+            ..RccTiming::default()
+        }));
+
+        machine.bus.write_u32(0x0, 0x2000_1000).unwrap();
+        machine.bus.write_u32(0x4, 0x41).unwrap();
+        let program: [u16; 8] = [
+            0x4803, // 0x40: ldr r0, [pc, #12]   ; RCC base
+            0x4904, // 0x42: ldr r1, [pc, #16]   ; HSEON
+            0x6001, // 0x44: str r1, [r0, #0]    ; RCC_CR.HSEON = 1
+            0x004A, // 0x46: lsls r2, r1, #1     ; HSERDY
+            0x6803, // 0x48: ldr r3, [r0, #0]
+            0x4013, // 0x4A: ands r3, r2
+            0xD0FC, // 0x4C: beq 0x48
+            0xE7FE, // 0x4E: b .
+        ];
+        load_thumb(&mut machine, 0x40, &program);
+        machine.bus.write_u32(0x50, 0x4002_1000).unwrap();
+        machine.bus.write_u32(0x54, 1 << 16).unwrap();
+        machine.reset().unwrap();
+
+        // HSEON written, oscillator still starting up
+        for _ in 0..12 {
+            machine.step().unwrap();
+        }
+        assert_ne!(machine.cpu.pc & !1, 0x4E);
+        assert_eq!(machine.bus.read_u32(0x4002_1000).unwrap() & (1 << 17), 0);
+
+        // Delay elapsed: HSERDY set and the polling loop exits
+        for _ in 0..30 {
+            machine.step().unwrap();
+        }
+        assert_eq!(machine.cpu.pc & !1, 0x4E);
+        assert_ne!(machine.bus.read_u32(0x4002_1000).unwrap() & (1 << 17), 0);
+
+        // Clock switch to PLL is reflected in SWS immediately
+        machine.bus.write_u32(0x4002_1004, 0b10).unwrap();
+        assert_eq!(machine.bus.read_u32(0x4002_1004).unwrap() & 0b1111, 0b1010);
+    }
+
+    #[test]
+    fn test_read_watchpoint_stops_run_after_load() {
+        use crate::{DebugControl, StopReason, WatchKind};
+
+        let mut machine = create_machine();
+        machine.cpu.pc = 0x0;
+        machine.cpu.r0 = 0x2000_0040;
+        machine.bus.write_u16(0x0, 0x2105).unwrap(); // movs r1, #5
+        machine.bus.write_u16(0x2, 0x6001).unwrap(); // str r1, [r0, #0]
+        machine.bus.write_u16(0x4, 0x6802).unwrap(); // ldr r2, [r0, #0]
+        machine.bus.write_u16(0x6, 0xE7FE).unwrap(); // b .
+
+        machine.add_watchpoint(0x2000_0042, 2, WatchKind::Read);
+        let reason = machine.run(Some(100)).unwrap();
+        assert_eq!(
+            reason,
+            StopReason::Watchpoint {
+                addr: 0x2000_0042,
+                kind: WatchKind::Read
+            }
+        );
+        assert_eq!(machine.cpu.pc, 0x6);
+        assert_eq!(machine.cpu.r2, 5);
+
+        assert!(machine.remove_watchpoint(0x2000_0042, 2, WatchKind::Read));
+        assert!(!machine.remove_watchpoint(0x2000_0042, 2, WatchKind::Read));
+        assert_eq!(machine.run(Some(10)).unwrap(), StopReason::MaxStepsReached);
+    }
+
+    #[test]
+    fn test_memory_violation_escalates_to_hardfault() {
+        let mut machine = create_machine();
+
+        // HardFault (Exception 3) vector at 0x0C
+        let handler: u32 = 0x0000_2000;
+        machine.bus.write_u32(0x0C, handler | 1).unwrap();
+
+        // Fetch from unmapped memory
+        machine.cpu.pc = 0x6000_0000;
+        machine.cpu.sp = 0x2002_0000;
+
+        machine.step().unwrap(); // Fault is pended, not propagated
+        machine.step().unwrap(); // Exception entry
+        assert_eq!(machine.cpu.pc, handler);
+        assert_eq!(machine.cpu.lr, 0xFFFF_FFF9);
+    }
+
+    #[test]
+    fn test_data_abort_escalates_to_hardfault() {
+        let mut machine = create_machine();
+        let handler: u32 = 0x0000_2000;
+        machine.bus.write_u32(0x0C, handler | 1).unwrap();
+        machine.bus.write_u16(0x40, 0x6801).unwrap(); // LDR R1, [R0]
+        machine.bus.write_u16(0x42, 0x6001).unwrap(); // STR R1, [R0]
+        machine.cpu.sp = 0x2002_0000;
+        machine.cpu.r0 = 0x6000_0000;
+        machine.cpu.r1 = 7;
+
+        for pc in [0x40, 0x42] {
+            machine.cpu.pc = pc;
+            machine.step().unwrap(); // Fault is pended
+            assert_eq!(machine.cpu.pc, pc, "faulting instruction is not retired");
+            machine.step().unwrap(); // Exception entry
+            assert_eq!(machine.cpu.pc, handler);
+            let stacked_pc = machine.bus.read_u32((machine.cpu.sp + 24) as u64).unwrap();
+            assert_eq!(stacked_pc, pc);
+            assert_eq!(machine.cpu.r1, 7);
+
+            machine.cpu.sp = 0x2002_0000;
+            machine.cpu.xpsr &= !0x1FF;
+        }
+    }
+
+    #[test]
+    fn test_memory_violation_propagates_without_handler() {
+        let mut machine = create_machine();
+
+        machine.cpu.pc = 0x6000_0000;
+        machine.cpu.sp = 0x2002_0000;
+
+        assert!(matches!(
+            machine.step(),
+            Err(crate::SimulationError::MemoryViolation(0x6000_0000))
+        ));
+    }
+
+    #[test]
+    fn test_memory_violation_propagate_behavior() {
+        let mut machine = create_machine();
+        machine.cpu.fault_behavior = crate::cpu::FaultBehavior::Propagate;
+        machine.bus.write_u32(0x0C, 0x2001).unwrap();
+
+        machine.cpu.pc = 0x6000_0000;
+        machine.cpu.sp = 0x2002_0000;
+
+        assert!(matches!(
+            machine.step(),
+            Err(crate::SimulationError::MemoryViolation(0x6000_0000))
+        ));
+        assert_eq!(machine.cpu.pc, 0x6000_0000);
+    }
+
+    #[test]
+    fn test_branch_offset_extremes_wrap() {
+        let mut machine: Machine<CortexM> = create_machine();
+
+        // B #-2048 from 0x0: target wraps below zero
+        machine.cpu.pc = 0;
+        machine.bus.write_u16(0, 0xE400).unwrap();
+        machine.step().unwrap();
+        assert_eq!(machine.cpu.pc, 0xFFFF_F804);
+
+        // B #+2046 from just below 0x8000_0000: crosses the i32 sign boundary
+        machine.bus.flash = crate::memory::LinearMemory::new(0x1000, 0x7FFF_F000);
+        machine.cpu.pc = 0x7FFF_FFF0;
+        machine.bus.write_u16(0x7FFF_FFF0, 0xE3FF).unwrap();
+        machine.step().unwrap();
+        assert_eq!(machine.cpu.pc, 0x8000_07F2);
+    }
+
+    #[test]
+    fn test_mov_w_instruction() {
+        let mut machine: Machine<CortexM> = create_machine();
+        machine.cpu.pc = 0;
+        machine.cpu.sp = 0x2000_1000;
+
+        // Test MOV.W R0, #0x55
+        // Encoding: 0xF04F 0x0055
+        // imm12 = 0x055 (i=0, imm3=0, imm8=0x55)
+        // Pattern 0, no rotation -> result = 0x55
+        machine.bus.write_u16(0, 0xF04F).unwrap();
+        machine.bus.write_u16(2, 0x0055).unwrap();
+        machine.step().unwrap();
+        assert_eq!(machine.cpu.r0, 0x55, "MOV.W R0, #0x55 failed");
+        assert_eq!(machine.cpu.pc, 4, "PC should advance by 4");
+
+        // Test MOV.W R1, #0x42
+        // Encoding: 0xF04F 0x0142
         // imm12 = 0x042 (i=0, imm3=0, imm8=0x42)
         // Pattern 0, no rotation -> result = 0x42
         machine.cpu.pc = 4;
@@ -878,6 +1786,34 @@ mod tests {
         assert_eq!(machine.cpu.pc, 8, "PC should advance by 4");
     }
 
+    #[test]
+    fn test_movs_w_modified_immediate() {
+        let mut machine: Machine<CortexM> = create_machine();
+        machine.cpu.pc = 0;
+        machine.cpu.sp = 0x2000_1000;
+        machine.cpu.xpsr = 0x4000_0000; // Z set beforehand
+
+        // MOVS.W R0, #0x00FF00FF
+        // Encoding: 0xF05F 0x10FF
+        // imm12 = 0x1FF (i=0, imm3=1, imm8=0xFF) -> pattern 00XY00XY
+        machine.bus.write_u16(0, 0xF05F).unwrap();
+        machine.bus.write_u16(2, 0x10FF).unwrap();
+        machine.step().unwrap();
+        assert_eq!(machine.cpu.r0, 0x00FF_00FF);
+        assert_eq!(machine.cpu.pc, 4);
+        assert_eq!(machine.cpu.xpsr & 0x4000_0000, 0, "Z should be clear");
+        assert_eq!(machine.cpu.xpsr & 0x8000_0000, 0, "N should be clear");
+
+        // MOVS.W R1, #0x80000000 (rotated constant, carry out = bit 31)
+        // Encoding: 0xF05F 0x4100
+        machine.bus.write_u16(4, 0xF05F).unwrap();
+        machine.bus.write_u16(6, 0x4100).unwrap();
+        machine.step().unwrap();
+        assert_eq!(machine.cpu.r1, 0x8000_0000);
+        assert_ne!(machine.cpu.xpsr & 0x8000_0000, 0, "N should be set");
+        assert_ne!(machine.cpu.xpsr & 0x2000_0000, 0, "C should be set");
+    }
+
     #[test]
     fn test_mvn_w_instruction() {
         let mut machine: Machine<CortexM> = create_machine();
@@ -995,76 +1931,392 @@ mod tests {
 
         machine.step().unwrap();
         assert_eq!(metrics.get_instructions(), 2);
-        assert_eq!(metrics.get_cycles(), 3); // 1 (MOV) + 2 (BL) = 3
+        assert_eq!(metrics.get_cycles(), 4); // 1 (MOV) + 1 (BL) + 2 (branch taken)
     }
 
     #[test]
-    fn test_peripheral_cycle_accounting_systick() {
-        use crate::metrics::PerformanceMetrics;
+    fn test_policy_denied_flash_write_is_dropped_and_stops() {
+        let mut machine = create_machine();
+        let mut policy = crate::policy::ExecutionPolicy::new(crate::Arch::Arm);
+        policy.deny_writes.push(0x0..0x1_0000);
+        machine.policy = Some(policy);
+
+        machine.bus.write_u16(0x0, 0x6001).unwrap(); // STR R1, [R0]
+        machine.bus.write_u16(0x2, 0x6011).unwrap(); // STR R1, [R2]
+        machine.cpu.r0 = 0x100;
+        machine.cpu.r1 = 0xDEAD_BEEF;
+        machine.cpu.r2 = 0x2000_0000;
+        machine.cpu.pc = 0x0;
 
+        assert!(matches!(
+            machine.step(),
+            Err(SimulationError::PolicyViolation(0x100))
+        ));
+        assert_eq!(machine.bus.read_u32(0x100).unwrap(), 0);
+        // Like any faulting store, the denied one is not retired
+        assert_eq!(machine.cpu.pc, 0x0);
+
+        // RAM is not restricted
+        machine.cpu.pc = 0x2;
+        machine.step().unwrap();
+        assert_eq!(machine.bus.read_u32(0x2000_0000).unwrap(), 0xDEAD_BEEF);
+    }
+
+    #[test]
+    fn test_policy_denied_instruction_is_not_executed() {
+        let mut machine = create_machine();
+        let mut policy = crate::policy::ExecutionPolicy::new(crate::Arch::Arm);
+        policy.deny_instructions.push("CPSID".to_string());
+        machine.policy = Some(policy);
+
+        machine.bus.write_u16(0x0, 0xB672).unwrap(); // CPSID i
+        machine.cpu.pc = 0x0;
+
+        assert!(matches!(
+            machine.step(),
+            Err(SimulationError::PolicyViolation(0x0))
+        ));
+        assert_eq!(machine.cpu.pc, 0x0);
+        assert!(!machine.cpu.primask);
+    }
+
+    #[test]
+    fn test_metrics_restart_on_reload() {
+        use crate::metrics::PerformanceMetrics;
         let mut machine = create_machine();
         let metrics = std::sync::Arc::new(PerformanceMetrics::new());
         machine.observers.push(metrics.clone());
 
-        // Enable SysTick so it incurs a tick cost each machine step.
-        machine.bus.write_u32(0xE000_E010, 1).unwrap(); // CSR = ENABLE
+        let mut text = Vec::new();
+        text.extend_from_slice(&0x2000_1000u32.to_le_bytes()); // Initial SP
+        text.extend_from_slice(&0x41u32.to_le_bytes()); // Reset vector (Thumb)
+        text.resize(0x40, 0);
+        text.extend_from_slice(&0xE7FEu16.to_le_bytes()); // B .
+        let mut image = crate::memory::ProgramImage::new(0x40, crate::Arch::Arm);
+        let text_len = text.len() as u64;
+        image.add_segment(0, text, text_len);
 
-        // MOV R0, #10 (16-bit)
-        machine.bus.write_u16(0x0, 0x200A).unwrap();
+        machine.load_firmware(&image).unwrap();
+        for _ in 0..10 {
+            machine.step().unwrap();
+        }
+        assert_eq!(metrics.get_instructions(), 10);
+
+        machine.reset().unwrap();
+        machine.load_firmware(&image).unwrap();
+        assert_eq!(metrics.get_instructions(), 0);
+        assert_eq!(metrics.get_cycles(), 0);
+
+        machine.step().unwrap();
+        assert_eq!(metrics.get_instructions(), 1);
+    }
+
+    #[test]
+    fn test_pc_relative_operands_use_aligned_pc_plus_4() {
+        let mut machine = create_machine();
+        let code: [u16; 6] = [
+            0x467C, // 0x0: MOV R4, PC        ; 0x0 + 4
+            0x4802, // 0x2: LDR R0, [PC, #8]  ; Align(0x6, 4) + 8 = 0xC
+            0x4901, // 0x4: LDR R1, [PC, #4]  ; Align(0x8, 4) + 4 = 0xC
+            0xA201, // 0x6: ADR R2, #4        ; Align(0xA, 4) + 4 = 0xC
+            0xA300, // 0x8: ADR R3, #0        ; Align(0xC, 4) + 0 = 0xC
+            0xE7FE, // 0xA: B .
+        ];
+        load_thumb(&mut machine, 0, &code);
+        machine.bus.write_u32(0xC, 0xCAFE_F00D).unwrap();
         machine.cpu.pc = 0x0;
+        for _ in 0..5 {
+            machine.step().unwrap();
+        }
+
+        assert_eq!(machine.cpu.r4, 0x4);
+        assert_eq!(machine.cpu.r0, 0xCAFE_F00D);
+        assert_eq!(machine.cpu.r1, 0xCAFE_F00D);
+        assert_eq!(machine.cpu.r2, 0xC);
+        assert_eq!(machine.cpu.r3, 0xC);
+        assert_eq!(machine.cpu.pc, 0xA);
+    }
+
+    #[test]
+    fn test_wide_pc_relative_operands_use_aligned_pc_plus_4() {
+        let mut machine = create_machine();
+        let code: [u16; 9] = [
+            0xBF00, 0xF85F, 0x0020, // 0x40: LDR.W R0, [PC, #-32] ; Align(0x46, 4) - 32 = 0x24
+            0xF8DF, 0x1018, // 0x46: LDR.W R1, [PC, #24]  ; Align(0x4A, 4) + 24 = 0x60
+            0xF20F, 0x0206, // 0x4A: ADDW R2, PC, #6      ; Align(0x4E, 4) + 6 = 0x52
+            0xF2AF, 0x0302, // 0x4E: SUBW R3, PC, #2      ; Align(0x52, 4) - 2 = 0x4E
+        ];
+        load_thumb(&mut machine, 0x40, &code);
+        machine.bus.write_u32(0x24, 0xCAFE_F00D).unwrap();
+        machine.bus.write_u32(0x60, 0x1234_5678).unwrap();
+        machine.cpu.pc = 0x40;
+        for _ in 0..5 {
+            machine.step().unwrap();
+        }
 
+        assert_eq!(machine.cpu.r0, 0xCAFE_F00D);
+        assert_eq!(machine.cpu.r1, 0x1234_5678);
+        assert_eq!(machine.cpu.r2, 0x52);
+        assert_eq!(machine.cpu.r3, 0x4E);
+        assert_eq!(machine.cpu.pc, 0x52);
+    }
+
+    #[test]
+    fn test_wide_add_and_cmp_immediate() {
+        let mut machine = create_machine();
+        let code: [u16; 4] = [
+            0xF111, 0x0001, // 0x0: ADDS.W R0, R1, #1
+            0xF1B0, 0x0F02, // 0x4: CMP.W R0, #2
+        ];
+        load_thumb(&mut machine, 0, &code);
+        machine.cpu.r1 = 1;
+        machine.cpu.pc = 0x0;
+        machine.step().unwrap();
         machine.step().unwrap();
 
-        assert_eq!(metrics.get_instructions(), 1);
-        assert_eq!(metrics.get_peripheral_cycles_total(), 1);
-        assert_eq!(metrics.get_peripheral_cycles("systick"), 1);
-        assert_eq!(metrics.get_cycles(), 2); // 1 (MOV) + 1 (SysTick tick)
+        assert_eq!(machine.cpu.r0, 2);
+        assert_eq!(machine.cpu.pc, 0x8, "CMP.W does not write the PC");
+        assert_eq!(machine.cpu.xpsr >> 28, 0b0110); // Z and C
     }
 
     #[test]
-    fn test_bit_field_instructions() {
-        let mut machine: Machine<CortexM> = create_machine();
+    fn test_unaligned_word_load_costs_extra_cycles() {
+        use crate::metrics::PerformanceMetrics;
+        let mut machine = create_machine();
+        let metrics = std::sync::Arc::new(PerformanceMetrics::new());
+        machine.observers.push(metrics.clone());
+        machine.cpu.unaligned_access_cycles = 3;
 
-        // Test UBFX (Unsigned Bit Field Extract)
-        // Extract bits [7:4] from 0xABCD1234
-        // UBFX R1, R0, #4, #4
-        // Encoding: h1 = 0xF3C0 (1111 0011 1100 0000)
-        // h2 = 0imm3 Rd imm2 widthm1
-        // lsb = 4 = (imm3<<2)|imm2 = (1<<2)|0 = 4
-        // widthm1 = 3 (width-1)
-        // h2 = 0001 0001 0000 0011 = 0x1103
-        machine.cpu.r0 = 0xABCD1234;
+        // LDR R1, [R0, #0] -> 0x6801
+        machine.bus.write_u16(0x0, 0x6801).unwrap();
+        machine.bus.write_u32(0x2000_0000, 0x1122_3344).unwrap();
+        machine.bus.write_u32(0x2000_0004, 0x5566_7788).unwrap();
+
+        machine.cpu.r0 = 0x2000_0000;
         machine.cpu.pc = 0x0;
-        machine.bus.write_u16(0x0, 0xF3C0).unwrap();
-        machine.bus.write_u16(0x2, 0x1103).unwrap();
         machine.step().unwrap();
-        assert_eq!(machine.cpu.r1, 0x3); // Bits [7:4] = 0x3
+        assert_eq!(machine.cpu.r1, 0x1122_3344);
+        let aligned = metrics.get_cycles();
+        assert_eq!(aligned, 2);
 
-        // Test SBFX (Signed Bit Field Extract)
-        // Extract bits [7:4] from 0xFFFFFFF0 (negative)
-        // SBFX R2, R0, #4, #4
-        // lsb=4: imm3=1, imm2=0 -> (1<<2)|0 = 4
-        // widthm1=3 (width-1)
-        // h1 = 0xF340, h2 = 0x1203
-        machine.cpu.r0 = 0xFFFFFFF0;
-        machine.cpu.pc = 0x4;
-        machine.bus.write_u16(0x4, 0xF340).unwrap();
-        machine.bus.write_u16(0x6, 0x1203).unwrap();
+        machine.cpu.r0 = 0x2000_0002;
+        machine.cpu.pc = 0x0;
         machine.step().unwrap();
-        assert_eq!(machine.cpu.r2, 0xFFFFFFFF); // Sign-extended 0xF
+        assert_eq!(machine.cpu.r1, 0x7788_1122);
+        assert_eq!(metrics.get_cycles() - aligned, aligned + 3);
+    }
 
-        // Test BFC (Bit Field Clear)
-        // Clear bits [7:4] in R3
-        // BFC R3, #4, #4
-        // lsb=4: imm3=1, imm2=0
-        // msb=7 (lsb+width-1)
-        // h1 = 0xF36F (Rn=0xF for BFC), h2 = 0x1307
-        machine.cpu.r3 = 0xFFFFFFFF;
-        machine.cpu.pc = 0x8;
-        machine.bus.write_u16(0x8, 0xF36F).unwrap();
-        machine.bus.write_u16(0xA, 0x1307).unwrap();
+    #[test]
+    fn test_unaligned_access_without_scb_leaves_last_fault_clear() {
+        // No SCB is mapped, so the CCR probe finds nothing
+        let mut machine = Machine::new(CortexM::new(), crate::bus::SystemBus::new());
+        // LDR R1, [R0, #0] -> 0x6801
+        machine.bus.write_u16(0x0, 0x6801).unwrap();
+        machine.bus.write_u32(0x2000_0000, 0x1122_3344).unwrap();
+        machine.bus.write_u32(0x2000_0004, 0x5566_7788).unwrap();
+
+        machine.cpu.r0 = 0x2000_0002;
+        machine.cpu.pc = 0x0;
         machine.step().unwrap();
-        assert_eq!(machine.cpu.r3, 0xFFFFFF0F); // Bits [7:4] cleared
+        assert_eq!(machine.cpu.r1, 0x7788_1122);
+        assert_eq!(machine.bus.last_fault(), None);
+    }
+
+    #[test]
+    fn test_loads_cost_more_cycles_than_alu_ops() {
+        use crate::metrics::PerformanceMetrics;
+
+        // Four instructions each, then a halt loop that is never reached.
+        // ALU: MOVS R1, #1; ADDS R1, #1; EORS R1, R1; MOVS R2, R1
+        // Loads: LDR R1, [R0]; LDR R2, [R0, #4]; LDRB R3, [R0]; LDRH R4, [R0]
+        let cycles_for = |code: [u16; 4]| {
+            let mut machine = create_machine();
+            let metrics = std::sync::Arc::new(PerformanceMetrics::new());
+            machine.observers.push(metrics.clone());
+            load_thumb(&mut machine, 0, &code);
+            machine.cpu.r0 = 0x2000_0000;
+            machine.cpu.pc = 0x0;
+            for _ in 0..code.len() {
+                machine.step().unwrap();
+            }
+            assert_eq!(metrics.get_instructions(), 4);
+            metrics.get_cycles()
+        };
+
+        let alu = cycles_for([0x2101, 0x3101, 0x4049, 0x000A]);
+        let loads = cycles_for([0x6801, 0x6842, 0x7803, 0x8804]);
+        let costs = crate::cpu::CycleCosts::default();
+        assert_eq!(alu, 4 * costs.alu as u64);
+        assert_eq!(loads, 4 * costs.load as u64);
+        assert!(loads > alu);
+    }
+
+    #[test]
+    fn test_trace_observer_writes_jsonl_per_instruction() {
+        let path = std::env::temp_dir().join("labwired-trace-observer.jsonl");
+        let trace = crate::trace::TraceObserver::create(crate::Arch::Arm, &path).unwrap();
+        let mut machine = create_machine();
+        machine.observers.push(Arc::new(trace));
+
+        // MOVS R0, #42; ADDS R1, R0, #1; B .
+        load_thumb(&mut machine, 0, &[0x202A, 0x1C41, 0xE7FE]);
+        machine.cpu.pc = 0x0;
+        for _ in 0..3 {
+            machine.step().unwrap();
+        }
+        for observer in &machine.observers {
+            observer.on_simulation_stop();
+        }
+
+        let log = std::fs::read_to_string(&path).unwrap();
+        let records: Vec<serde_json::Value> = log
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(records.len(), 3);
+        assert_eq!(
+            records[0],
+            serde_json::json!({
+                "pc": 0, "opcode": 0x202A, "mnemonic": "movs", "regs_changed": {"r0": 42},
+            })
+        );
+        assert_eq!(records[1]["pc"], 2);
+        assert_eq!(records[1]["mnemonic"], "adds");
+        assert_eq!(records[1]["regs_changed"], serde_json::json!({"r1": 43}));
+        assert_eq!(records[2]["pc"], 4);
+        assert_eq!(records[2]["regs_changed"], serde_json::json!({}));
+    }
+
+    #[test]
+    fn test_vcd_records_pc13_toggle() {
+        let path = std::env::temp_dir().join("labwired-vcd-pc13.vcd");
+        let _ = std::fs::remove_file(&path);
+        let vcd = Arc::new(crate::vcd::VcdRecorder::new(&path));
+        let mut machine = create_machine();
+        machine.observers.push(vcd.clone());
+
+        // STR R1, [R0]; STR R2, [R0]; STR R1, [R0] with R0 = GPIOC ODR
+        load_thumb(&mut machine, 0, &[0x6001, 0x6002, 0x6001]);
+        machine.cpu.r0 = 0x4001_100C;
+        machine.cpu.r1 = 1 << 13;
+        machine.cpu.r2 = 0;
+        machine.cpu.pc = 0x0;
+        for _ in 0..3 {
+            machine.step().unwrap();
+        }
+        for observer in &machine.observers {
+            observer.on_simulation_stop();
+        }
+
+        let dump = std::fs::read_to_string(&path).unwrap();
+        let var = dump
+            .lines()
+            .find(|l| l.ends_with(" gpioc_13 $end"))
+            .expect("gpioc_13 declared");
+        let id = var.split_whitespace().nth(3).unwrap();
+        let changes: Vec<&str> = dump
+            .lines()
+            .skip_while(|l| *l != "$end")
+            .filter(|l| l.starts_with('#') || l.ends_with(id))
+            .collect();
+        // Every store costs two cycles.
+        let high = format!("1{}", id);
+        let low = format!("0{}", id);
+        assert_eq!(
+            changes,
+            vec!["#2", high.as_str(), "#4", low.as_str(), "#6", high.as_str()]
+        );
+    }
+
+    #[test]
+    fn test_vcd_stops_recording_at_change_limit() {
+        let vcd = Arc::new(crate::vcd::VcdRecorder::with_max_changes("unused.vcd", 2));
+        let mut machine = create_machine();
+        machine.observers.push(vcd.clone());
+
+        // Toggle PC13 three times; only the first two changes fit
+        load_thumb(&mut machine, 0, &[0x6001, 0x6002, 0x6001]);
+        machine.cpu.r0 = 0x4001_100C;
+        machine.cpu.r1 = 1 << 13;
+        machine.cpu.r2 = 0;
+        machine.cpu.pc = 0x0;
+        for _ in 0..3 {
+            machine.step().unwrap();
+        }
+
+        let dump = vcd.render();
+        assert!(dump.contains("$comment change limit reached: nothing recorded from #6 on $end"));
+        let body: Vec<&str> = dump.lines().skip_while(|l| *l != "$end").skip(1).collect();
+        assert_eq!(body.len(), 4, "{}", dump);
+    }
+
+    #[test]
+    fn test_peripheral_cycle_accounting_systick() {
+        use crate::metrics::PerformanceMetrics;
+
+        let mut machine = create_machine();
+        let metrics = std::sync::Arc::new(PerformanceMetrics::new());
+        machine.observers.push(metrics.clone());
+
+        // Enable SysTick so it incurs a tick cost each machine step.
+        machine.bus.write_u32(0xE000_E010, 1).unwrap(); // CSR = ENABLE
+
+        // MOV R0, #10 (16-bit)
+        machine.bus.write_u16(0x0, 0x200A).unwrap();
+        machine.cpu.pc = 0x0;
+
+        machine.step().unwrap();
+
+        assert_eq!(metrics.get_instructions(), 1);
+        assert_eq!(metrics.get_peripheral_cycles_total(), 1);
+        assert_eq!(metrics.get_peripheral_cycles("systick"), 1);
+        assert_eq!(metrics.get_cycles(), 2); // 1 (MOV) + 1 (SysTick tick)
+    }
+
+    #[test]
+    fn test_bit_field_instructions() {
+        let mut machine: Machine<CortexM> = create_machine();
+
+        // Test UBFX (Unsigned Bit Field Extract)
+        // Extract bits [7:4] from 0xABCD1234
+        // UBFX R1, R0, #4, #4
+        // Encoding: h1 = 0xF3C0 (1111 0011 1100 0000)
+        // h2 = 0imm3 Rd imm2 widthm1
+        // lsb = 4 = (imm3<<2)|imm2 = (1<<2)|0 = 4
+        // widthm1 = 3 (width-1)
+        // h2 = 0001 0001 0000 0011 = 0x1103
+        machine.cpu.r0 = 0xABCD1234;
+        machine.cpu.pc = 0x0;
+        machine.bus.write_u16(0x0, 0xF3C0).unwrap();
+        machine.bus.write_u16(0x2, 0x1103).unwrap();
+        machine.step().unwrap();
+        assert_eq!(machine.cpu.r1, 0x3); // Bits [7:4] = 0x3
+
+        // Test SBFX (Signed Bit Field Extract)
+        // Extract bits [7:4] from 0xFFFFFFF0 (negative)
+        // SBFX R2, R0, #4, #4
+        // lsb=4: imm3=1, imm2=0 -> (1<<2)|0 = 4
+        // widthm1=3 (width-1)
+        // h1 = 0xF340, h2 = 0x1203
+        machine.cpu.r0 = 0xFFFFFFF0;
+        machine.cpu.pc = 0x4;
+        machine.bus.write_u16(0x4, 0xF340).unwrap();
+        machine.bus.write_u16(0x6, 0x1203).unwrap();
+        machine.step().unwrap();
+        assert_eq!(machine.cpu.r2, 0xFFFFFFFF); // Sign-extended 0xF
+
+        // Test BFC (Bit Field Clear)
+        // Clear bits [7:4] in R3
+        // BFC R3, #4, #4
+        // lsb=4: imm3=1, imm2=0
+        // msb=7 (lsb+width-1)
+        // h1 = 0xF36F (Rn=0xF for BFC), h2 = 0x1307
+        machine.cpu.r3 = 0xFFFFFFFF;
+        machine.cpu.pc = 0x8;
+        machine.bus.write_u16(0x8, 0xF36F).unwrap();
+        machine.bus.write_u16(0xA, 0x1307).unwrap();
+        machine.step().unwrap();
+        assert_eq!(machine.cpu.r3, 0xFFFFFF0F); // Bits [7:4] cleared
 
         // Test BFI (Bit Field Insert)
         // Insert bits [3:0] of R0 into bits [7:4] of R4
@@ -1166,7 +2418,7 @@ mod tests {
 
         // 5. Verify Result
         let dr_addr = adc_base + 0x4C;
-        let sr_addr = adc_base + 0x00;
+        let sr_addr = adc_base;
 
         let dr = machine.bus.read_u32(dr_addr).unwrap();
         let sr = machine.bus.read_u32(sr_addr).unwrap();
@@ -1227,4 +2479,1154 @@ mod tests {
         // Check deserialization
         let _snap_restored: MachineSnapshot = serde_json::from_str(&json_str).unwrap();
     }
+
+    #[test]
+    fn test_snapshot_serializes_cpu_and_peripherals() {
+        let mut machine = create_machine();
+        machine.cpu.r7 = 0x1234_5678;
+        machine.cpu.primask = true;
+        machine.cpu.set_vtor(0x0800_0000);
+        machine.bus.write_u32(0x4001_080C, 0x55).unwrap(); // GPIOA ODR
+
+        let json = serde_json::to_value(machine.snapshot()).unwrap();
+
+        let cpu = &json["cpu"];
+        assert_eq!(cpu["type"], "arm");
+        let registers = cpu["registers"].as_array().expect("registers array");
+        assert_eq!(registers.len(), 16);
+        assert_eq!(registers[7], 0x1234_5678);
+        assert_eq!(cpu["primask"], true);
+        assert_eq!(cpu["vtor"], 0x0800_0000);
+        assert!(cpu.get("xpsr").is_some());
+        assert!(cpu.get("pending_exceptions").is_some());
+
+        assert_eq!(json["peripherals"]["gpioa"]["odr"], 0x55);
+    }
+
+    #[test]
+    fn test_restore_resumes_with_identical_register_trace() {
+        // loop: ADDS R1, R1, R0; STR R1, [R2]; LDR R3, [R2]; ADDS R0, #2; B loop
+        let code: [u16; 6] = [0x2001, 0x1809, 0x6011, 0x6813, 0x3002, 0xE7FA];
+        let mut original = create_machine();
+        load_thumb(&mut original, 0, &code);
+        original.cpu.r2 = 0x2000_0000;
+        original.cpu.pc = 0x0;
+        original.bus.write_u32(0xE000_E014, 3).unwrap(); // SysTick RVR
+        original.bus.write_u32(0xE000_E010, 1).unwrap(); // SysTick CSR = ENABLE
+
+        for _ in 0..7 {
+            original.step().unwrap();
+        }
+        let snap = original.snapshot();
+        let json = serde_json::to_string(&snap).unwrap();
+
+        let mut restored = create_machine();
+        restored
+            .restore(&serde_json::from_str(&json).unwrap())
+            .unwrap();
+
+        let trace = |machine: &mut VariableMachine| {
+            (0..10)
+                .map(|_| {
+                    machine.step().unwrap();
+                    serde_json::to_value(machine.snapshot().cpu).unwrap()
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(trace(&mut restored), trace(&mut original));
+        assert_eq!(
+            restored.bus.read_u32(0x2000_0000).unwrap(),
+            original.bus.read_u32(0x2000_0000).unwrap()
+        );
+        assert_eq!(
+            restored.snapshot().peripherals["systick"],
+            original.snapshot().peripherals["systick"]
+        );
+    }
+
+    #[test]
+    fn test_load_firmware_zeroes_bss() {
+        let mut machine = create_machine();
+        // Stale RAM contents where .bss will live
+        machine.bus.write_u32(0x2000_0100, 0xAAAA_AAAA).unwrap();
+        machine.bus.write_u32(0x2000_0104, 0xAAAA_AAAA).unwrap();
+        machine.bus.write_u32(0x2000_0108, 0xAAAA_AAAA).unwrap();
+
+        let mut text = Vec::new();
+        text.extend_from_slice(&0x2000_1000u32.to_le_bytes()); // Initial SP
+        text.extend_from_slice(&0x41u32.to_le_bytes()); // Reset vector (Thumb)
+        text.resize(0x40, 0);
+        for op in [0x4801u16, 0x6801, 0xE7FE, 0x0000] {
+            // LDR R0, [PC, #4]; LDR R1, [R0]; B .; padding
+            text.extend_from_slice(&op.to_le_bytes());
+        }
+        text.extend_from_slice(&0x2000_0104u32.to_le_bytes()); // &bss_var
+
+        let mut image = crate::memory::ProgramImage::new(0x40, crate::Arch::Arm);
+        let text_len = text.len() as u64;
+        image.add_segment(0, text, text_len);
+        // .data (4 file bytes) followed by 4 bytes of .bss
+        image.add_segment(0x2000_0100, vec![0x11, 0x22, 0x33, 0x44], 8);
+        machine.load_firmware(&image).unwrap();
+
+        machine.step().unwrap();
+        machine.step().unwrap();
+        assert_eq!(machine.cpu.r1, 0, "bss variable should read as zero");
+        assert_eq!(machine.bus.read_u32(0x2000_0100).unwrap(), 0x4433_2211);
+        // Past mem_size is left alone
+        assert_eq!(machine.bus.read_u32(0x2000_0108).unwrap(), 0xAAAA_AAAA);
+    }
+
+    #[test]
+    fn test_reset_clears_ram_to_init_pattern() {
+        let mut machine = create_machine();
+        machine.reset_clears_ram = true;
+        machine.ram_init_pattern = 0xA5;
+        machine.bus.preload(0x2000_0200, &[1, 2, 3, 4]).unwrap();
+
+        let mut text = Vec::new();
+        text.extend_from_slice(&0x2000_1000u32.to_le_bytes()); // Initial SP
+        text.extend_from_slice(&0x41u32.to_le_bytes()); // Reset vector (Thumb)
+        text.resize(0x40, 0);
+        text.extend_from_slice(&0xE7FEu16.to_le_bytes()); // B .
+        let mut image = crate::memory::ProgramImage::new(0x40, crate::Arch::Arm);
+        let text_len = text.len() as u64;
+        image.add_segment(0, text, text_len);
+        // .data followed by 4 bytes of .bss
+        image.add_segment(0x2000_0100, vec![0x11, 0x22, 0x33, 0x44], 8);
+        machine.load_firmware(&image).unwrap();
+
+        let check = |machine: &VariableMachine| {
+            assert_eq!(machine.bus.read_u32(0x2000_0000).unwrap(), 0xA5A5_A5A5);
+            assert_eq!(machine.bus.read_u32(0x2000_0100).unwrap(), 0x4433_2211);
+            assert_eq!(machine.bus.read_u32(0x2000_0104).unwrap(), 0);
+            assert_eq!(machine.bus.read_u32(0x2000_0200).unwrap(), 0x0403_0201);
+            assert_eq!(machine.bus.read_u32(0x4).unwrap(), 0x41);
+            assert_eq!(machine.bus.read_u16(0x40).unwrap(), 0xE7FE);
+        };
+        check(&machine);
+
+        // Firmware-side writes during a run are gone after the next reset.
+        machine.bus.write_u32(0x2000_0000, 0x1234_5678).unwrap();
+        machine.bus.write_u32(0x2000_0104, 0xFFFF_FFFF).unwrap();
+        machine.bus.write_u32(0x2000_0200, 0).unwrap();
+        machine.reset().unwrap();
+        check(&machine);
+        assert_eq!(machine.cpu.pc & !1, 0x40);
+
+        // Without the flag RAM is left as is.
+        machine.reset_clears_ram = false;
+        machine.bus.write_u32(0x2000_0000, 0x1234_5678).unwrap();
+        machine.reset().unwrap();
+        assert_eq!(machine.bus.read_u32(0x2000_0000).unwrap(), 0x1234_5678);
+    }
+
+    #[test]
+    fn test_multi_core_mailbox_signals_core_reading_shared_flag() {
+        use crate::multi_core::{MultiCore, SharedMemory};
+
+        let core0: [u16; 11] = [
+            0x2021, // movs r0, #0x21
+            0x0600, // lsls r0, r0, #24   ; r0 = shared RAM
+            0x212A, // movs r1, #42
+            0x6001, // str r1, [r0]       ; flag
+            0x2250, // movs r2, #0x50
+            0x0612, // lsls r2, r2, #24   ; r2 = mailbox
+            0x2301, // movs r3, #1
+            0x041B, // lsls r3, r3, #16
+            0x330E, // adds r3, #14       ; core 1, PendSV
+            0x6013, // str r3, [r2]
+            0xE7FE, // b .
+        ];
+        let mut machine0 = create_machine();
+        load_thumb(&mut machine0, 0, &core0);
+        machine0.cpu.sp = 0x2000_1000;
+
+        let mut machine1 = create_machine();
+        machine1.bus.write_u32(0x38, 0x101).unwrap(); // PendSV vector
+        machine1.bus.write_u16(0x80, 0xE7FE).unwrap(); // b .
+                                                       // movs r0, #0x21; lsls r0, r0, #24; ldr r4, [r0]; bx lr
+        load_thumb(&mut machine1, 0x100, &[0x2021, 0x0600, 0x6804, 0x4770]);
+        machine1.cpu.pc = 0x80;
+        machine1.cpu.sp = 0x2000_1000;
+
+        let shared = SharedMemory::new(0x1000);
+        let mut cores = MultiCore::new(vec![machine0, machine1]);
+        cores.map_shared_memory("sram_shared", 0x2100_0000, &shared);
+        cores.map_mailbox(0x5000_0000);
+
+        for _ in 0..9 {
+            assert!(cores.step_all().iter().all(|r| r.is_ok()));
+        }
+        assert_eq!(shared.read_bytes(0, 4), [42, 0, 0, 0]);
+        assert_eq!(
+            cores.machines[1].cpu.pc & !1,
+            0x80,
+            "core 1 not signalled yet"
+        );
+
+        // Doorbell write; core 1 takes the IRQ in the same round
+        cores.step_all();
+        assert_eq!(cores.machines[1].cpu.pc, 0x100);
+        for _ in 0..4 {
+            cores.step_all();
+        }
+        assert_eq!(cores.machines[1].cpu.r4, 42);
+        assert_eq!(cores.machines[1].cpu.pc & !1, 0x80);
+        // Private RAM is not shared
+        let ram1 = cores.machines[1].bus.read_u32(0x2000_0000).unwrap();
+        assert_eq!(ram1, 0);
+
+        assert!(!cores.signal_core(2, 14));
+    }
+
+    #[test]
+    fn test_last_fault_classifies_store_and_fetch() {
+        use crate::bus::{FaultInfo, FaultKind};
+        let mut machine = create_machine();
+        machine.bus.write_u16(0x0, 0x2060).unwrap(); // movs r0, #0x60
+        machine.bus.write_u16(0x2, 0x0600).unwrap(); // lsls r0, r0, #24
+        machine.bus.write_u16(0x4, 0x6001).unwrap(); // str r1, [r0]
+        assert_eq!(machine.bus.last_fault(), None);
+
+        machine.step().unwrap();
+        machine.step().unwrap();
+        // No HardFault handler is installed, so the store stops the step
+        assert!(matches!(
+            machine.step(),
+            Err(crate::SimulationError::MemoryViolation(0x6000_0000))
+        ));
+        assert_eq!(
+            machine.bus.last_fault(),
+            Some(FaultInfo {
+                addr: 0x6000_0000,
+                kind: FaultKind::DataAccess,
+                is_write: true,
+            })
+        );
+
+        machine.cpu.pc = 0x7000_0000;
+        assert!(machine.step().is_err());
+        let fault = machine.bus.last_fault().unwrap();
+        assert_eq!(fault.kind, FaultKind::InstructionFetch);
+        assert_eq!(fault.to_string(), "instruction fetch at 0x70000000");
+
+        // Debugger accesses outside a step are data accesses
+        assert!(machine.bus.read_u32(0x7000_0000).is_err());
+        assert_eq!(
+            machine.bus.last_fault().unwrap().kind,
+            FaultKind::DataAccess
+        );
+        machine.bus.clear_last_fault();
+        assert_eq!(machine.bus.last_fault(), None);
+    }
+
+    #[test]
+    fn test_scheduled_gpio_input_is_seen_at_its_step() {
+        use crate::signals::{DigitalLevel, SignalEvent};
+
+        let mut machine = create_machine();
+        machine.cpu.pc = 0;
+        machine.cpu.r0 = 0x4001_0800; // GPIOA
+        for i in 0..8 {
+            machine.bus.write_u16(i * 2, 0x6881).unwrap(); // LDR R1, [R0, #8] (IDR)
+        }
+        machine.signals.push(
+            3,
+            SignalEvent::GpioInput {
+                port: "gpioa".to_string(),
+                pin: 0,
+                level: DigitalLevel::High,
+            },
+        );
+
+        for _ in 0..3 {
+            machine.step().unwrap();
+            assert_eq!(machine.cpu.r1, 0);
+        }
+        machine.step().unwrap();
+        assert_eq!(machine.cpu.r1, 1);
+        assert!(machine.signals.is_empty());
+    }
+
+    #[test]
+    fn test_nvic_observer_records_enable_then_pend() {
+        use crate::peripherals::nvic::NvicEvent;
+
+        #[derive(Debug, Default)]
+        struct NvicRecorder(Mutex<Vec<(u32, NvicEvent)>>);
+        impl crate::SimulationObserver for NvicRecorder {
+            fn on_nvic_change(&self, irq: u32, event: NvicEvent) {
+                self.0.lock().unwrap().push((irq, event));
+            }
+        }
+
+        let mut machine = create_machine();
+        let recorder = Arc::new(NvicRecorder::default());
+        machine.observers.push(recorder.clone());
+        machine.cpu.pc = 0;
+        machine.cpu.sp = 0x2002_0000;
+        machine.cpu.r0 = 0xE000_E100; // NVIC_ISER0
+        machine.cpu.r1 = 1; // IRQ 0 = exception 16
+        machine.cpu.r2 = 0xE000_E200; // NVIC_ISPR0
+        machine.bus.write_u16(0, 0x6001).unwrap(); // STR R1, [R0]
+        machine.bus.write_u16(2, 0x6011).unwrap(); // STR R1, [R2]
+
+        machine.step().unwrap();
+        machine.step().unwrap();
+        assert_eq!(
+            *recorder.0.lock().unwrap(),
+            vec![(16, NvicEvent::Enable), (16, NvicEvent::Pend)]
+        );
+
+        // The pended IRQ is taken on the next step
+        machine.step().unwrap();
+        assert_eq!(
+            recorder.0.lock().unwrap().last(),
+            Some(&(16, NvicEvent::Activate))
+        );
+    }
+
+    #[test]
+    fn test_register_changes_only_tracked_on_request() {
+        #[derive(Debug, Default)]
+        struct RegisterRecorder(Mutex<Vec<Vec<(u8, u32)>>>);
+        impl crate::SimulationObserver for RegisterRecorder {
+            fn wants_register_changes(&self) -> bool {
+                true
+            }
+            fn on_registers_changed(&self, changed: &[(u8, u32)]) {
+                self.0.lock().unwrap().push(changed.to_vec());
+            }
+        }
+
+        let mut machine = create_machine();
+        machine
+            .observers
+            .push(Arc::new(crate::metrics::PerformanceMetrics::new()));
+        machine.cpu.pc = 0;
+        load_thumb(&mut machine, 0, &[0x2005, 0x2005]); // MOVS R0, #5 (twice)
+        machine.step().unwrap();
+        assert!(machine.registers_before.is_empty());
+
+        let recorder = Arc::new(RegisterRecorder::default());
+        machine.observers.push(recorder.clone());
+        machine.step().unwrap();
+        assert_eq!(*recorder.0.lock().unwrap(), vec![vec![(15, 4)]]);
+    }
+
+    #[test]
+    fn test_undefined_32bit_instruction_is_reported() {
+        let mut machine = create_machine();
+        // Long multiply space with op1 = 0b111 is UNDEFINED
+        machine.bus.write_u16(0, 0xFBF0).unwrap();
+        machine.bus.write_u16(2, 0x0000).unwrap();
+        machine.cpu.pc = 0;
+        assert!(matches!(
+            machine.step(),
+            Err(SimulationError::UndefinedInstruction(0))
+        ));
+        assert_eq!(machine.cpu.pc, 0);
+    }
+
+    #[test]
+    fn test_misaligned_access_traps_when_enabled() {
+        let mut machine = create_machine();
+        machine.cpu.r0 = 0x2000_0001;
+        machine.bus.write_u16(0, 0x6801).unwrap(); // LDR R1, [R0]
+        machine.bus.write_u16(2, 0x6801).unwrap();
+
+        // Permitted by default
+        machine.cpu.pc = 0;
+        machine.step().unwrap();
+
+        machine.cpu.unaligned_trap = true;
+        machine.cpu.r1 = 0;
+        assert!(matches!(
+            machine.step(),
+            Err(SimulationError::UnalignedAccess(0x2000_0001))
+        ));
+        assert_eq!(machine.cpu.r1, 0);
+    }
+
+    #[test]
+    fn test_push_below_stack_memory_is_stack_overflow() {
+        let mut machine = create_machine();
+        // RAM starts at 0x2000_0000, so the push lands in unmapped memory
+        machine.cpu.sp = 0x2000_0000;
+        machine.bus.write_u16(0, 0xB401).unwrap(); // PUSH {R0}
+        machine.cpu.pc = 0;
+        assert!(matches!(
+            machine.step(),
+            Err(SimulationError::StackOverflow(0x1FFF_FFFC))
+        ));
+    }
+
+    #[test]
+    fn test_bkpt_stops_with_breakpoint_but_semihosting_is_undefined() {
+        let mut machine = create_machine();
+        load_thumb(&mut machine, 0, &[0xBE07, 0xBEAB]); // bkpt #7; bkpt #0xab
+        machine.cpu.pc = 0;
+        assert!(matches!(
+            machine.step(),
+            Err(SimulationError::Breakpoint(0, 7))
+        ));
+        assert_eq!(machine.cpu.pc, 0);
+
+        machine.cpu.pc = 2;
+        assert!(matches!(
+            machine.step(),
+            Err(SimulationError::UndefinedInstruction(2))
+        ));
+    }
+
+    #[test]
+    fn test_firmware_can_enable_the_unaligned_trap() {
+        let mut machine = create_machine();
+        machine.cpu.r0 = 0x2000_0001;
+        machine.bus.write_u16(0, 0x6801).unwrap(); // LDR R1, [R0]
+        machine.bus.write_u32(0xE000_ED14, 1 << 3).unwrap(); // CCR.UNALIGN_TRP
+        machine.cpu.pc = 0;
+        assert!(matches!(
+            machine.step(),
+            Err(SimulationError::UnalignedAccess(0x2000_0001))
+        ));
+    }
+
+    #[test]
+    fn test_wide_push_and_exception_stacking_detect_stack_overflow() {
+        let mut machine = create_machine();
+        machine.bus.write_u16(0, 0xE92D).unwrap(); // PUSH.W {R4, LR}
+        machine.bus.write_u16(2, 0x4010).unwrap();
+        machine.cpu.sp = 0x2000_0004;
+        machine.cpu.pc = 0;
+        // The first store, of LR, fits; R4 lands below RAM
+        assert!(matches!(
+            machine.step(),
+            Err(SimulationError::StackOverflow(0x1FFF_FFFC))
+        ));
+
+        machine.cpu.sp = 0x2000_0000;
+        machine.cpu.pending_exceptions = 1 << 15; // SysTick
+        assert!(matches!(
+            machine.step(),
+            Err(SimulationError::StackOverflow(0x1FFF_FFE0))
+        ));
+    }
+
+    #[test]
+    fn test_wide_push_and_pop_round_trip() {
+        let mut machine = create_machine();
+        let code: [u16; 6] = [
+            0xE92D, 0x4030, // 0x0: PUSH.W {R4, R5, LR}
+            0xE8BD, 0x8030, // 0x4: POP.W {R4, R5, PC}
+            0xE7FE, 0xE7FE,
+        ];
+        load_thumb(&mut machine, 0, &code);
+        machine.cpu.sp = 0x2000_1000;
+        machine.cpu.r4 = 4;
+        machine.cpu.r5 = 5;
+        machine.cpu.lr = 0x0A | 1;
+        machine.cpu.pc = 0;
+
+        machine.step().unwrap();
+        assert_eq!(machine.cpu.sp, 0x2000_0FF4);
+        assert_eq!(machine.bus.read_u32(0x2000_0FF4).unwrap(), 4);
+        assert_eq!(machine.bus.read_u32(0x2000_0FFC).unwrap(), 0x0B);
+
+        machine.cpu.r4 = 0;
+        machine.cpu.r5 = 0;
+        machine.step().unwrap();
+        assert_eq!((machine.cpu.r4, machine.cpu.r5), (4, 5));
+        assert_eq!(machine.cpu.sp, 0x2000_1000);
+        assert_eq!(machine.cpu.pc, 0x0A);
+    }
+
+    #[test]
+    fn test_vfp_instruction_raises_nocp_usage_fault() {
+        use crate::cpu::cortex_m::CFSR_NOCP;
+        const SHCSR: u64 = 0xE000_ED24;
+        const CFSR: u64 = 0xE000_ED28;
+
+        let mut machine = create_machine();
+        machine.cpu.sp = 0x2000_1000;
+        machine.cpu.pc = 0x40;
+        // VADD.F32 S0, S0, S1
+        machine.bus.write_u16(0x40, 0xEE30).unwrap();
+        machine.bus.write_u16(0x42, 0x0A20).unwrap();
+
+        // No handler to take the fault
+        assert!(matches!(
+            machine.step(),
+            Err(SimulationError::UndefinedInstruction(0x40))
+        ));
+
+        // UsageFault handler at 0x100, enabled in SHCSR
+        machine.bus.write_u32(6 * 4, 0x101).unwrap();
+        machine.bus.write_u32(SHCSR, 1 << 18).unwrap();
+        machine.bus.write_u32(CFSR, CFSR_NOCP).unwrap();
+        assert_eq!(machine.bus.read_u32(CFSR).unwrap(), 0);
+
+        machine.step().unwrap();
+        assert_eq!(machine.bus.read_u32(CFSR).unwrap(), CFSR_NOCP);
+        machine.step().unwrap();
+        assert_eq!(machine.cpu.pc, 0x100);
+        assert_eq!(machine.cpu.xpsr & 0x1FF, 6);
+        // The stacked return address is the VFP instruction
+        let sp = machine.cpu.sp as u64;
+        assert_eq!(machine.bus.read_u32(sp + 24).unwrap(), 0x40);
+    }
+
+    #[test]
+    fn test_tight_loop_halts_within_window() {
+        use crate::{DebugControl, StopReason};
+
+        let mut machine = create_machine();
+        machine.halt_window = Some(8);
+        machine.cpu.pc = 0;
+        machine.bus.write_u16(0, 0x3001).unwrap(); // ADDS R0, #1
+        machine.bus.write_u16(2, 0x2800).unwrap(); // CMP R0, #0
+        machine.bus.write_u16(4, 0xE7FE).unwrap(); // B .
+
+        assert_eq!(machine.run(Some(100)).unwrap(), StopReason::Halted);
+        assert_eq!(machine.cpu.pc, 4);
+        // The setup ran once before the spin
+        assert_eq!(machine.cpu.r0, 1);
+
+        // A loop that keeps changing registers is not a halt
+        machine.cpu.pc = 0;
+        machine.bus.write_u16(4, 0xE7FC).unwrap(); // B 0
+        assert_eq!(machine.run(Some(100)).unwrap(), StopReason::MaxStepsReached);
+    }
+
+    #[test]
+    fn test_add_and_remove_peripheral() {
+        let mut bus = crate::bus::SystemBus::new();
+        bus.add_peripheral(
+            "sensor",
+            0x6000_0000,
+            0x100,
+            None,
+            Box::new(crate::peripherals::stub::StubPeripheral::new(0x42)),
+        )
+        .unwrap();
+        assert_eq!(bus.read_u32(0x6000_0010).unwrap(), 0x42);
+
+        // Names are unique
+        assert!(bus
+            .add_peripheral(
+                "sensor",
+                0x6000_1000,
+                0x100,
+                None,
+                Box::new(crate::peripherals::stub::StubPeripheral::new(0)),
+            )
+            .is_err());
+
+        // Ranges must not overlap a peripheral or memory
+        let stub = || Box::new(crate::peripherals::stub::StubPeripheral::new(0));
+        let err = bus
+            .add_peripheral("other", 0x6000_00F0, 0x100, None, stub())
+            .unwrap_err();
+        assert!(err.to_string().contains("'sensor'"), "{}", err);
+        assert!(bus
+            .add_peripheral("in_ram", 0x2000_1000, 0x100, None, stub())
+            .is_err());
+        assert!(bus
+            .add_peripheral("in_flash", 0x0, 0x10, None, stub())
+            .is_err());
+        bus.add_peripheral("next", 0x6000_0100, 0x100, None, stub())
+            .unwrap();
+
+        assert!(bus.remove_peripheral("sensor").is_some());
+        assert!(bus.read_u32(0x6000_0010).is_err());
+        assert!(bus.remove_peripheral("sensor").is_none());
+    }
+
+    #[test]
+    fn test_vadd_f32_with_fpu() {
+        let mut machine = create_machine();
+        let mut fpu = crate::cpu::Fpu::default();
+        fpu.write_s(0, 1.5);
+        fpu.write_s(1, 2.25);
+        machine.cpu.fpu = Some(fpu);
+        machine.cpu.pc = 0x40;
+        // VADD.F32 S2, S0, S1; VMOV R1, S2
+        load_thumb(&mut machine, 0x40, &[0xEE30, 0x1A20, 0xEE11, 0x1A10]);
+
+        machine.step().unwrap();
+        assert_eq!(machine.cpu.fpu.as_ref().unwrap().read_s(2), 3.75);
+        machine.step().unwrap();
+        assert_eq!(machine.cpu.r1, 3.75f32.to_bits());
+        assert_eq!(machine.cpu.pc, 0x48);
+    }
+
+    #[test]
+    fn test_vldr_vstr_round_trip_on_fpu_chip() {
+        let chip = ChipDescriptor {
+            name: "test-chip-m4f".to_string(),
+            arch: Arch::Arm,
+            flash: MemoryRange {
+                base: 0x0,
+                size: "64KB".to_string(),
+            },
+            ram: MemoryRange {
+                base: 0x2000_0000,
+                size: "16KB".to_string(),
+            },
+            peripherals: Vec::new(),
+            fpu: true,
+        };
+        let manifest = SystemManifest {
+            name: "test-system-m4f".to_string(),
+            chip: "test-chip-m4f".to_string(),
+            memory_overrides: HashMap::new(),
+            external_devices: Vec::new(),
+            preload: Vec::new(),
+        };
+        let mut bus = crate::bus::SystemBus::from_config(&chip, &manifest).unwrap();
+        let (cpu, _nvic) = crate::system::cortex_m::configure_cortex_m(&mut bus);
+        let mut machine = Machine::new(cpu, bus);
+        assert!(machine.cpu.fpu.is_some());
+
+        machine
+            .bus
+            .write_u32(0x2000_0004, 2.5f32.to_bits())
+            .unwrap();
+        machine.cpu.r0 = 0x2000_0000;
+        machine.cpu.pc = 0x40;
+        // VLDR S0, [R0, #4]; VADD.F32 S0, S0, S0; VSTR S0, [R0, #8]
+        load_thumb(
+            &mut machine,
+            0x40,
+            &[0xED90, 0x0A01, 0xEE30, 0x0A00, 0xED80, 0x0A02],
+        );
+        for _ in 0..3 {
+            machine.step().unwrap();
+        }
+
+        let stored = machine.bus.read_u32(0x2000_0008).unwrap();
+        assert_eq!(f32::from_bits(stored), 5.0);
+    }
+
+    #[test]
+    fn test_exception_with_fpca_stacks_fp_context_lazily() {
+        use crate::cpu::fpu::FPCCR_LSPACT;
+        const FPCCR: u64 = 0xE000_EF34;
+        const FPCAR: u64 = 0xE000_EF38;
+
+        let mut bus = crate::bus::SystemBus::new();
+        bus.fpu = true;
+        let (cpu, _nvic) = crate::system::cortex_m::configure_cortex_m(&mut bus);
+        let mut machine = Machine::new(cpu, bus);
+        machine.cpu.fpu.as_mut().unwrap().write_s(0, 1.5);
+        machine.cpu.sp = 0x2000_1000;
+        machine.cpu.pc = 0x40;
+        // Thread: VADD.F32 S0, S0, S0; b .
+        machine.bus.write_u16(0x40, 0xEE30).unwrap();
+        machine.bus.write_u16(0x42, 0x0A00).unwrap();
+        machine.bus.write_u16(0x44, 0xE7FE).unwrap();
+        // PendSV handler at 0x100: VMOV S0, R1; BX LR
+        machine.bus.write_u32(14 * 4, 0x101).unwrap();
+        machine.bus.write_u16(0x100, 0xEE00).unwrap();
+        machine.bus.write_u16(0x102, 0x1A10).unwrap();
+        machine.bus.write_u16(0x104, 0x4770).unwrap();
+
+        machine.step().unwrap();
+        assert!(machine.cpu.fpu.as_ref().unwrap().fpca);
+        machine.cpu.set_exception_pending(14);
+        machine.step().unwrap();
+
+        // Extended frame, with the FP part only reserved so far
+        let frame = 0x2000_1000 - 0x68;
+        assert_eq!(machine.cpu.sp, frame);
+        assert_eq!(machine.cpu.lr, 0xFFFF_FFE9);
+        assert!(!machine.cpu.fpu.as_ref().unwrap().fpca);
+        assert_ne!(machine.bus.read_u32(FPCCR).unwrap() & FPCCR_LSPACT, 0);
+        assert_eq!(machine.bus.read_u32(FPCAR).unwrap(), frame + 0x20);
+        assert_eq!(machine.bus.read_u32(frame as u64 + 0x20).unwrap(), 0);
+
+        // The handler's first FP instruction stacks S0 before clobbering it
+        machine.step().unwrap();
+        assert_eq!(machine.bus.read_u32(FPCCR).unwrap() & FPCCR_LSPACT, 0);
+        let stacked = machine.bus.read_u32(frame as u64 + 0x20).unwrap();
+        assert_eq!(f32::from_bits(stacked), 3.0);
+        assert_eq!(machine.cpu.fpu.as_ref().unwrap().read_s(0), 0.0);
+
+        // Returning restores it
+        machine.step().unwrap();
+        assert_eq!(machine.cpu.pc, 0x44);
+        assert_eq!(machine.cpu.sp, 0x2000_1000);
+        let fpu = machine.cpu.fpu.as_ref().unwrap();
+        assert_eq!(fpu.read_s(0), 3.0);
+        assert!(fpu.fpca);
+    }
+
+    #[test]
+    fn test_eager_fp_stacking_fault_is_reported() {
+        use crate::cpu::fpu::FPCCR_ASPEN;
+
+        let mut bus = crate::bus::SystemBus::new();
+        bus.fpu = true;
+        let (cpu, _nvic) = crate::system::cortex_m::configure_cortex_m(&mut bus);
+        let mut machine = Machine::new(cpu, bus);
+        machine.bus.write_u32(0xE000_EF34, FPCCR_ASPEN).unwrap(); // No lazy stacking
+        machine.bus.write_u32(14 * 4, 0x101).unwrap();
+        machine.cpu.fpu.as_mut().unwrap().fpca = true;
+        // The basic frame fits at the top of RAM, the FP part runs past it
+        machine.cpu.sp = 0x2010_0040;
+        machine.cpu.set_exception_pending(14);
+        assert!(matches!(
+            machine.step(),
+            Err(SimulationError::StackOverflow(0x2010_0000))
+        ));
+    }
+
+    #[test]
+    fn test_vpush_and_vpop_round_trip_high_fp_registers() {
+        let mut bus = crate::bus::SystemBus::new();
+        bus.fpu = true;
+        let (cpu, _nvic) = crate::system::cortex_m::configure_cortex_m(&mut bus);
+        let mut machine = Machine::new(cpu, bus);
+        for n in 16..32 {
+            machine.cpu.fpu.as_mut().unwrap().write_s(n, n as f32);
+        }
+        machine.cpu.sp = 0x2000_1000;
+        machine.cpu.pc = 0x40;
+        // VPUSH {S16-S31}; VPOP {S16-S31}
+        load_thumb(&mut machine, 0x40, &[0xED2D, 0x8A10, 0xECBD, 0x8A10]);
+
+        machine.step().unwrap();
+        assert_eq!(machine.cpu.sp, 0x2000_0FC0);
+        assert_eq!(machine.bus.read_u32(0x2000_0FC0).unwrap(), 16f32.to_bits());
+        assert_eq!(machine.bus.read_u32(0x2000_0FFC).unwrap(), 31f32.to_bits());
+
+        machine.cpu.fpu.as_mut().unwrap().s = [0; 32];
+        machine.step().unwrap();
+        assert_eq!(machine.cpu.sp, 0x2000_1000);
+        let fpu = machine.cpu.fpu.as_ref().unwrap();
+        assert_eq!(fpu.read_s(16), 16.0);
+        assert_eq!(fpu.read_s(31), 31.0);
+    }
+
+    #[test]
+    fn test_batch_memory_read_matches_byte_reads() {
+        use crate::DebugControl;
+        let mut machine = create_machine();
+        let base = 0x2000_0400u32;
+        let data: Vec<u8> = (0..1024u32).map(|i| (i * 7 + 3) as u8).collect();
+        machine.write_memory_from(base, &data).unwrap();
+
+        let mut batch = vec![0; 1024];
+        machine.read_memory_into(base, &mut batch).unwrap();
+        let bytewise: Vec<u8> = (0..1024)
+            .map(|i| machine.bus.read_u8(base as u64 + i).unwrap())
+            .collect();
+        assert_eq!(batch, bytewise);
+        assert_eq!(batch, data);
+
+        // Ranges outside RAM and flash fall back to byte access
+        machine
+            .bus
+            .add_peripheral(
+                "stub",
+                0x6000_0000,
+                0x10,
+                None,
+                Box::new(crate::peripherals::stub::StubPeripheral::new(0x1122_3344)),
+            )
+            .unwrap();
+        let mut regs = [0; 8];
+        machine.read_memory_into(0x6000_0000, &mut regs).unwrap();
+        assert_eq!(regs, [0x44, 0x33, 0x22, 0x11, 0x44, 0x33, 0x22, 0x11]);
+        let mut past_end = [0; 8];
+        assert!(machine
+            .read_memory_into(0x6000_000C, &mut past_end)
+            .is_err());
+    }
+
+    #[test]
+    fn test_bulk_registers_round_trip() {
+        let mut cpu = CortexM::new();
+        let regs: [u32; 17] = std::array::from_fn(|i| 0x1000_0000 + i as u32 * 0x11);
+        cpu.set_registers(&regs);
+        assert_eq!(cpu.registers(), regs);
+        for (id, &val) in regs.iter().enumerate() {
+            assert_eq!(cpu.get_register(id as u8), val);
+        }
+        assert_eq!(cpu.xpsr(), regs[16]);
+
+        cpu.set_xpsr(0x0100_0000);
+        assert_eq!(cpu.get_register(16), 0x0100_0000);
+    }
+
+    #[test]
+    fn test_run_until_register_reaches_value() {
+        use crate::StopReason;
+
+        let mut machine = create_machine();
+        machine.cpu.pc = 0;
+        machine.cpu.r0 = 5;
+        machine.bus.write_u16(0, 0x3801).unwrap(); // SUBS R0, #1
+        machine.bus.write_u16(2, 0xE7FD).unwrap(); // B 0
+
+        let mut steps = 0;
+        let reason = machine
+            .run_until(
+                |m| {
+                    steps += 1;
+                    m.cpu.r0 == 2
+                },
+                Some(100),
+            )
+            .unwrap();
+        assert_eq!(reason, StopReason::PredicateMet);
+        // Three SUBS with a branch between each
+        assert_eq!(steps, 5);
+        assert_eq!(machine.cpu.pc, 2);
+
+        // The budget still applies when the predicate never holds
+        assert_eq!(
+            machine.run_until(|m| m.cpu.r0 == 0x100, Some(10)).unwrap(),
+            StopReason::MaxStepsReached
+        );
+    }
+
+    #[test]
+    fn test_run_until_reports_watchpoint_hit_in_the_same_step() {
+        use crate::{DebugControl, StopReason, WatchKind};
+
+        let mut machine = create_machine();
+        machine.cpu.pc = 0;
+        machine.cpu.r0 = 0x2000_0040;
+        machine.bus.write_u16(0, 0x6001).unwrap(); // STR R1, [R0]
+        machine.bus.write_u16(2, 0xE7FE).unwrap(); // B .
+        machine.add_watchpoint(0x2000_0040, 4, WatchKind::Write);
+
+        assert_eq!(
+            machine.run_until(|m| m.cpu.pc == 2, Some(10)).unwrap(),
+            StopReason::Watchpoint {
+                addr: 0x2000_0040,
+                kind: WatchKind::Write
+            }
+        );
+        // The hit is not left over for the next stop
+        assert_eq!(machine.step_single().unwrap(), StopReason::StepDone);
+    }
+
+    #[test]
+    fn test_itm_stimulus_port_output_is_captured() {
+        // Callers attach the sink before fitting the core peripherals
+        let mut bus = crate::bus::SystemBus::new();
+        let sink = Arc::new(Mutex::new(Vec::new()));
+        bus.attach_uart_tx_sink(sink.clone(), false);
+        let (cpu, _nvic) = crate::system::cortex_m::configure_cortex_m(&mut bus);
+        let mut machine = Machine::new(cpu, bus);
+
+        // Port 0 accepts byte, halfword and word writes
+        machine.bus.write_u8(0xE000_0000, b'I').unwrap();
+        machine.bus.write_u8(0xE000_0000, b'T').unwrap();
+        machine
+            .bus
+            .write_u16(0xE000_0000, u16::from_le_bytes(*b"M!"))
+            .unwrap();
+        assert_eq!(*sink.lock().unwrap(), b"ITM!");
+        assert_eq!(machine.bus.read_u32(0xE000_0000).unwrap(), 1); // FIFO ready
+
+        machine
+            .bus
+            .write_u32(0xE000_0000, u32::from_le_bytes(*b"ITM!"))
+            .unwrap();
+        assert_eq!(*sink.lock().unwrap(), b"ITM!ITM!");
+
+        // With its TER bit clear, port 0 drops writes; port 1 still sends
+        machine.bus.write_u32(0xE000_0E00, 0b10).unwrap();
+        machine.bus.write_u8(0xE000_0000, b'x').unwrap();
+        machine.bus.write_u8(0xE000_0004, b'y').unwrap();
+        assert_eq!(*sink.lock().unwrap(), b"ITM!ITM!y");
+    }
+
+    #[test]
+    fn test_rtt_up_channel_is_drained_to_sink() {
+        let mut machine = create_machine();
+        let sink = Arc::new(Mutex::new(Vec::new()));
+        machine.rtt = Some(crate::rtt::RttReader::new(Some(sink.clone()), false));
+
+        // Control block with one 16-byte up buffer, as SEGGER_RTT_Init leaves it
+        let cb = 0x2000_0000;
+        let buffer = 0x2000_0100;
+        for (i, byte) in b"SEGGER RTT".iter().enumerate() {
+            machine.bus.write_u8(cb + i as u64, *byte).unwrap();
+        }
+        machine.bus.write_u32(cb + 16, 1).unwrap(); // MaxNumUpBuffers
+        machine.bus.write_u32(cb + 28, buffer).unwrap(); // aUp[0].pBuffer
+        machine.bus.write_u32(cb + 32, 16).unwrap(); // aUp[0].SizeOfBuffer
+
+        // Write "Hi" into the buffer, then publish it through WrOff
+        machine.cpu.pc = 0;
+        machine.cpu.r0 = cb as u32;
+        machine.cpu.r1 = buffer;
+        let code = [
+            0x2348, // MOVS R3, #'H'
+            0x700B, // STRB R3, [R1, #0]
+            0x2369, // MOVS R3, #'i'
+            0x704B, // STRB R3, [R1, #1]
+            0x2302, // MOVS R3, #2
+            0x6243, // STR R3, [R0, #36] (aUp[0].WrOff)
+            0xE7FE, // B .
+        ];
+        load_thumb(&mut machine, 0, &code);
+
+        for _ in 0..5 {
+            machine.step().unwrap();
+        }
+        assert_eq!(machine.rtt.as_ref().unwrap().control_block(), Some(cb));
+        assert!(sink.lock().unwrap().is_empty());
+
+        machine.step().unwrap();
+        assert_eq!(*sink.lock().unwrap(), b"Hi");
+        // The reader consumed the bytes: RdOff caught up with WrOff
+        assert_eq!(machine.bus.read_u32(cb + 40).unwrap(), 2);
+
+        machine.step().unwrap();
+        assert_eq!(*sink.lock().unwrap(), b"Hi");
+    }
+
+    /// Lay out an RTT control block at 0x2000_0000 whose 16-byte up buffer
+    /// at 0x2000_0100 holds `data` starting at `rd_off`, wrapping at the end.
+    fn write_rtt_channel(bus: &mut crate::bus::SystemBus, rd_off: u32, data: &[u8]) {
+        let cb = 0x2000_0000;
+        let buffer = 0x2000_0100;
+        for (i, byte) in b"SEGGER RTT".iter().enumerate() {
+            bus.write_u8(cb + i as u64, *byte).unwrap();
+        }
+        bus.write_u32(cb + 16, 1).unwrap(); // MaxNumUpBuffers
+        bus.write_u32(cb + 28, buffer as u32).unwrap(); // aUp[0].pBuffer
+        bus.write_u32(cb + 32, 16).unwrap(); // aUp[0].SizeOfBuffer
+        let mut off = rd_off;
+        for byte in data {
+            bus.write_u8(buffer + off as u64, *byte).unwrap();
+            off = (off + 1) % 16;
+        }
+        bus.write_u32(cb + 36, off).unwrap(); // aUp[0].WrOff
+        bus.write_u32(cb + 40, rd_off).unwrap(); // aUp[0].RdOff
+    }
+
+    #[test]
+    fn test_rtt_drains_across_the_ring_wrap() {
+        let mut bus = crate::bus::SystemBus::new();
+        let sink = Arc::new(Mutex::new(Vec::new()));
+        let mut rtt = crate::rtt::RttReader::new(Some(sink.clone()), false);
+
+        // WrOff (2) is behind RdOff (13): the data runs off the end and on
+        // from the start of the buffer
+        write_rtt_channel(&mut bus, 13, b"wrap!");
+        assert_eq!(rtt.poll(&mut bus), 5);
+        assert_eq!(*sink.lock().unwrap(), b"wrap!");
+        assert_eq!(bus.read_u32(0x2000_0028).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_rtt_finish_drains_a_block_the_backoff_skipped() {
+        let mut bus = crate::bus::SystemBus::new();
+        let sink = Arc::new(Mutex::new(Vec::new()));
+        let mut rtt = crate::rtt::RttReader::new(Some(sink.clone()), false);
+
+        // The first poll finds nothing and backs off before searching again
+        assert_eq!(rtt.poll(&mut bus), 0);
+        write_rtt_channel(&mut bus, 0, b"late");
+        assert_eq!(rtt.poll(&mut bus), 0);
+        assert!(sink.lock().unwrap().is_empty());
+
+        assert_eq!(rtt.finish(&mut bus), 4);
+        assert_eq!(*sink.lock().unwrap(), b"late");
+    }
+
+    #[test]
+    fn test_coverage_reports_the_branch_not_taken() {
+        use crate::coverage::{BlockHits, BranchOutcomes, CoverageObserver};
+
+        let mut machine = create_machine();
+        let coverage = Arc::new(CoverageObserver::new(crate::Arch::Arm));
+        machine.observers.push(coverage.clone());
+        machine.cpu.pc = 0;
+        machine.cpu.r0 = 0;
+        let code = [
+            0x2800, // 0x0: CMP R0, #0
+            0xD000, // 0x2: BEQ 0x6
+            0x2101, // 0x4: MOVS R1, #1 (the `if` body)
+            0x2202, // 0x6: MOVS R2, #2
+            0xE7FE, // 0x8: B .
+        ];
+        load_thumb(&mut machine, 0, &code);
+        for _ in 0..5 {
+            machine.step().unwrap();
+        }
+
+        let report = coverage.report();
+        assert_eq!(report.executed, vec![0x0, 0x2, 0x6, 0x8]);
+        assert_eq!(
+            report.branches,
+            vec![BranchOutcomes {
+                addr: 0x2,
+                taken: 1,
+                not_taken: 0
+            }]
+        );
+        assert_eq!(
+            report.basic_blocks,
+            vec![
+                BlockHits {
+                    start: 0x0,
+                    end: 0x2,
+                    hits: 1
+                },
+                BlockHits {
+                    start: 0x6,
+                    end: 0x6,
+                    hits: 1
+                },
+                BlockHits {
+                    start: 0x8,
+                    end: 0x8,
+                    hits: 2
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_coverage_treats_wide_conditional_branch_as_conditional() {
+        use crate::coverage::{BranchOutcomes, CoverageObserver};
+
+        let mut machine = create_machine();
+        let coverage = Arc::new(CoverageObserver::new(crate::Arch::Arm));
+        machine.observers.push(coverage.clone());
+        machine.cpu.pc = 0;
+        machine.cpu.r0 = 0;
+        let code = [
+            0x2800, // 0x0: CMP R0, #0
+            0xF000, 0x8002, // 0x2: BEQ.W 0xA
+            0x2101, // 0x6: MOVS R1, #1 (the `if` body)
+            0x2101, // 0x8: MOVS R1, #1
+            0x2202, // 0xA: MOVS R2, #2
+            0xE7FE, // 0xC: B .
+        ];
+        load_thumb(&mut machine, 0, &code);
+        for _ in 0..5 {
+            machine.step().unwrap();
+        }
+
+        assert_eq!(machine.cpu.r1, 0);
+        assert_eq!(machine.cpu.r2, 2);
+        let report = coverage.report();
+        assert_eq!(report.executed, vec![0x0, 0x2, 0xA, 0xC]);
+        assert_eq!(
+            report.branches,
+            vec![BranchOutcomes {
+                addr: 0x2,
+                taken: 1,
+                not_taken: 0
+            }]
+        );
+    }
+
+    #[test]
+    fn test_wide_conditional_branch_backwards() {
+        let mut machine = create_machine();
+        machine.cpu.pc = 0;
+        let code = [
+            0x2003, // 0x0: MOVS R0, #3
+            0x3801, // 0x2: SUBS R0, #1
+            0xF47F, 0xAFFD, // 0x4: BNE.W 0x2
+            0xE7FE, // 0x8: B .
+        ];
+        load_thumb(&mut machine, 0, &code);
+        for _ in 0..7 {
+            machine.step().unwrap();
+        }
+        assert_eq!(machine.cpu.r0, 0);
+        assert_eq!(machine.cpu.pc, 0x8);
+    }
+
+    #[test]
+    fn test_realtime_throttle_paces_and_reanchors() {
+        use crate::metrics::RealtimeThrottle;
+        use std::time::{Duration, Instant};
+
+        // 1 kHz: one cycle per millisecond, checked every 10 cycles
+        let start = Instant::now();
+        let mut throttle = RealtimeThrottle::new(1000, 0);
+        throttle.pace(5);
+        assert!(start.elapsed() < Duration::from_millis(50));
+
+        // 100 simulated ms ahead of the wall clock: sleeps until it is not
+        throttle.pace(100);
+        assert!(start.elapsed() >= Duration::from_millis(100));
+
+        // Fall well over MAX_LAG behind: the throttle re-anchors at 110
+        // rather than letting the run race ahead to catch up
+        std::thread::sleep(RealtimeThrottle::MAX_LAG * 3);
+        throttle.pace(110);
+        let resumed = Instant::now();
+        throttle.pace(160);
+        let paced = resumed.elapsed();
+        assert!(paced >= Duration::from_millis(40), "{:?}", paced);
+        assert!(paced < Duration::from_millis(500), "{:?}", paced);
+    }
+
+    #[test]
+    fn test_instruction_mix_ranks_memory_ops_first() {
+        use crate::decoder::InstructionCategory;
+        use crate::metrics::InstructionMix;
+
+        let mut machine = create_machine();
+        let mix = Arc::new(InstructionMix::new());
+        machine.observers.push(mix.clone());
+        machine.cpu.pc = 0;
+        machine.cpu.r0 = 0x2000_0000;
+        let code = [
+            0x6801, // 0x0: LDR R1, [R0, #0]
+            0x6041, // 0x2: STR R1, [R0, #4]
+            0x6882, // 0x4: LDR R2, [R0, #8]
+            0xE7FB, // 0x6: B 0x0
+        ];
+        load_thumb(&mut machine, 0, &code);
+        for _ in 0..40 {
+            machine.step().unwrap();
+        }
+
+        assert_eq!(
+            mix.histogram(),
+            vec![
+                (InstructionCategory::Load, 20),
+                (InstructionCategory::Store, 10),
+                (InstructionCategory::Branch, 10),
+            ]
+        );
+        assert_eq!(mix.count(InstructionCategory::Alu), 0);
+    }
+
+    #[test]
+    fn test_thumb_32_categories() {
+        use crate::decoder::InstructionCategory;
+
+        let cases = [
+            (0xF8D0, 0x1000, InstructionCategory::Load), // LDR.W R1, [R0]
+            (0xE92D, 0x4010, InstructionCategory::Store), // PUSH.W {R4, LR}
+            (0xE920, 0x0030, InstructionCategory::Store), // STMDB R0!, {R4, R5}
+            (0xE8D0, 0xF001, InstructionCategory::Branch), // TBB [R0, R1]
+            (0xF380, 0x8800, InstructionCategory::System), // MSR APSR_nzcvq, R0
+            (0xEE30, 0x0A00, InstructionCategory::Float), // VADD.F32 S0, S0, S0
+        ];
+        for (h1, h2, expected) in cases {
+            assert_eq!(
+                InstructionCategory::thumb_32(h1, h2),
+                expected,
+                "{:#06x} {:#06x}",
+                h1,
+                h2
+            );
+        }
+    }
 }
@@ -0,0 +1,366 @@
+// LabWired - Firmware Simulation Platform
+// Copyright (C) 2026 Andrii Shylenko
+//
+// This software is released under the MIT License.
+// See the LICENSE file in the project root for full license information.
+
+use crate::peripherals::nvic::NvicEvent;
+use crate::{Arch, SimulationObserver};
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// One executed instruction as recorded by [`InstructionTrace`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TraceEntry {
+    pub pc: u32,
+    pub opcode: u32,
+    pub disasm: String,
+}
+
+/// Bounded ring buffer of the last N executed instructions.
+///
+/// Only the PC and opcode are recorded per step; disassembly happens when the
+/// buffer is read, so keeping the observer attached is cheap compared to full
+/// instruction tracing.
+#[derive(Debug)]
+pub struct InstructionTrace {
+    arch: Arch,
+    capacity: usize,
+    entries: Mutex<VecDeque<(u32, u32)>>,
+}
+
+impl InstructionTrace {
+    pub fn new(arch: Arch, capacity: usize) -> Self {
+        Self {
+            arch,
+            capacity,
+            entries: Mutex::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Recorded instructions, oldest first.
+    pub fn entries(&self) -> Vec<TraceEntry> {
+        let Ok(entries) = self.entries.lock() else {
+            return Vec::new();
+        };
+        entries
+            .iter()
+            .map(|&(pc, opcode)| TraceEntry {
+                pc,
+                opcode,
+                disasm: disassemble(self.arch, opcode),
+            })
+            .collect()
+    }
+
+    pub fn clear(&self) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.clear();
+        }
+    }
+}
+
+impl SimulationObserver for InstructionTrace {
+    fn on_step_start(&self, pc: u32, opcode: u32) {
+        if self.capacity == 0 {
+            return;
+        }
+        // Record the instruction address, not the Thumb state bit
+        let pc = if self.arch == Arch::Arm { pc & !1 } else { pc };
+        if let Ok(mut entries) = self.entries.lock() {
+            if entries.len() == self.capacity {
+                entries.pop_front();
+            }
+            entries.push_back((pc, opcode));
+        }
+    }
+}
+
+/// One line of the log written by [`TraceObserver`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TraceRecord {
+    pub pc: u32,
+    pub opcode: u32,
+    pub mnemonic: String,
+    /// Registers written by the instruction, by name, excluding the PC.
+    pub regs_changed: BTreeMap<String, u32>,
+}
+
+/// Writes every executed instruction to a JSONL file, one [`TraceRecord`]
+/// per line.
+///
+/// The record is completed by `on_registers_changed` after the step, so an
+/// exception entry (which executes no instruction) is not logged. The
+/// `opcode` of a 32-bit Thumb instruction holds both halfwords, first
+/// halfword low; its mnemonic is that of the first halfword.
+#[derive(Debug)]
+pub struct TraceObserver {
+    arch: Arch,
+    state: Mutex<TraceFile>,
+}
+
+#[derive(Debug)]
+struct TraceFile {
+    out: BufWriter<File>,
+    /// PC and opcode of the instruction being executed.
+    current: Option<(u32, u32)>,
+}
+
+impl TraceObserver {
+    /// Create (or truncate) the log at `path`.
+    pub fn create(arch: Arch, path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Self {
+            arch,
+            state: Mutex::new(TraceFile {
+                out: BufWriter::new(File::create(path)?),
+                current: None,
+            }),
+        })
+    }
+}
+
+impl SimulationObserver for TraceObserver {
+    fn on_simulation_stop(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            if let Err(e) = state.out.flush() {
+                tracing::warn!("Failed to write instruction trace: {}", e);
+            }
+        }
+    }
+
+    fn on_step_start(&self, pc: u32, opcode: u32) {
+        let pc = if self.arch == Arch::Arm { pc & !1 } else { pc };
+        if let Ok(mut state) = self.state.lock() {
+            state.current = Some((pc, opcode));
+        }
+    }
+
+    fn wants_register_changes(&self) -> bool {
+        true
+    }
+
+    fn on_registers_changed(&self, changed: &[(u8, u32)]) {
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+        let Some((pc, opcode)) = state.current.take() else {
+            return;
+        };
+        let record = TraceRecord {
+            pc,
+            opcode,
+            mnemonic: mnemonic(self.arch, opcode),
+            regs_changed: changed
+                .iter()
+                .filter_map(|&(id, value)| Some((register_name(self.arch, id)?, value)))
+                .collect(),
+        };
+        let line = serde_json::to_string(&record).unwrap_or_default();
+        if let Err(e) = writeln!(state.out, "{}", line) {
+            tracing::warn!("Failed to write instruction trace: {}", e);
+        }
+    }
+}
+
+/// Logs NVIC transitions as a timeline, one `info` line per
+/// `on_nvic_change` tagged with the (zero-based) step it happened in.
+#[derive(Debug, Default)]
+pub struct NvicTimeline {
+    steps: AtomicU64,
+}
+
+impl NvicTimeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl SimulationObserver for NvicTimeline {
+    fn on_nvic_change(&self, irq: u32, event: NvicEvent) {
+        let step = self.steps.load(Ordering::Relaxed);
+        tracing::info!("NVIC [step {}] exception {} {}", step, irq, event);
+    }
+
+    fn on_step_complete(&self) {
+        self.steps.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// One UART byte as recorded by [`UartTimeline`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UartByte {
+    /// Number of steps executed when the byte was emitted (one-based).
+    pub step: u64,
+    /// Cumulative cycle count at the end of that step.
+    pub cycle: u64,
+    pub byte: u8,
+}
+
+/// Timestamps UART output: every byte appended to the UART TX sink is
+/// tagged with the step and cycle count of the step that emitted it.
+#[derive(Debug)]
+pub struct UartTimeline {
+    sink: Arc<Mutex<Vec<u8>>>,
+    state: Mutex<UartTimelineState>,
+}
+
+#[derive(Debug, Default)]
+struct UartTimelineState {
+    steps: u64,
+    cycles: u64,
+    /// Length of the sink already attributed to a step.
+    seen: usize,
+    bytes: Vec<UartByte>,
+}
+
+impl UartTimeline {
+    /// Watch `sink`, the buffer passed to `SystemBus::attach_uart_tx_sink`.
+    pub fn new(sink: Arc<Mutex<Vec<u8>>>) -> Self {
+        let seen = sink.lock().map(|buf| buf.len()).unwrap_or(0);
+        Self {
+            sink,
+            state: Mutex::new(UartTimelineState {
+                seen,
+                ..Default::default()
+            }),
+        }
+    }
+
+    pub fn bytes(&self) -> Vec<UartByte> {
+        self.state
+            .lock()
+            .map(|state| state.bytes.clone())
+            .unwrap_or_default()
+    }
+
+    /// Write the timeline as CSV with a `step,cycle,byte,ascii` header, the
+    /// byte in hex and its printable (or escaped) character.
+    pub fn write_csv(&self, out: &mut impl Write) -> io::Result<()> {
+        writeln!(out, "step,cycle,byte,ascii")?;
+        for entry in self.bytes() {
+            let ascii = match entry.byte {
+                b',' => "\",\"".to_string(),
+                b'"' => "\"\"\"\"".to_string(),
+                b => (b as char).escape_default().to_string(),
+            };
+            writeln!(
+                out,
+                "{},{},0x{:02x},{}",
+                entry.step, entry.cycle, entry.byte, ascii
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl SimulationObserver for UartTimeline {
+    fn on_step_end(&self, cycles: u32) {
+        if let Ok(mut state) = self.state.lock() {
+            state.cycles += cycles as u64;
+        }
+    }
+
+    fn on_step_complete(&self) {
+        let (Ok(mut state), Ok(sink)) = (self.state.lock(), self.sink.lock()) else {
+            return;
+        };
+        state.steps += 1;
+        let (step, cycle) = (state.steps, state.cycles);
+        // The sink may have been cleared (e.g. on reset) since the last step
+        let start = state.seen.min(sink.len());
+        state.bytes.extend(
+            sink[start..]
+                .iter()
+                .map(|&byte| UartByte { step, cycle, byte }),
+        );
+        state.seen = sink.len();
+    }
+}
+
+/// Name of core register `id` for the trace log; `None` for the PC.
+fn register_name(arch: Arch, id: u8) -> Option<String> {
+    const ARM: [&str; 17] = [
+        "r0", "r1", "r2", "r3", "r4", "r5", "r6", "r7", "r8", "r9", "r10", "r11", "r12", "sp",
+        "lr", "pc", "xpsr",
+    ];
+    match arch {
+        Arch::Arm if id == 15 => None,
+        Arch::Arm => ARM.get(id as usize).map(|name| name.to_string()),
+        Arch::RiscV if id >= 32 => None,
+        Arch::RiscV | Arch::Unknown => Some(format!("x{}", id)),
+    }
+}
+
+pub(crate) fn mnemonic(arch: Arch, opcode: u32) -> String {
+    match arch {
+        Arch::Arm => crate::decoder::arm::decode_thumb_16(opcode as u16).to_string(),
+        _ => disassemble(arch, opcode),
+    }
+    .split([' ', '{', '(']) // operands, or the fields of a Debug variant
+    .next()
+    .unwrap_or_default()
+    .to_lowercase()
+}
+
+fn disassemble(arch: Arch, opcode: u32) -> String {
+    use crate::decoder::riscv;
+    match arch {
+        Arch::Arm => format!("{:?}", crate::decoder::arm::decode_thumb_16(opcode as u16)),
+        // Compressed instructions are reported as their halfword
+        Arch::RiscV if riscv::is_compressed(opcode as u16) => {
+            format!("{:?}", riscv::decode_rv32c(opcode as u16))
+        }
+        Arch::RiscV => format!("{:?}", riscv::decode_rv32(opcode)),
+        Arch::Unknown => String::from("?"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ring_buffer_keeps_last_n() {
+        let trace = InstructionTrace::new(Arch::Arm, 3);
+        for i in 0..5u32 {
+            trace.on_step_start(0x100 + i * 2, 0x2000 | i); // MOVS R0, #i
+        }
+
+        let entries = trace.entries();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].pc, 0x104);
+        assert_eq!(entries[2].pc, 0x108);
+        assert_eq!(entries[2].opcode, 0x2004);
+        assert_eq!(entries[2].disasm, "MovImm { rd: 0, imm: 4 }");
+
+        trace.clear();
+        assert!(trace.entries().is_empty());
+    }
+
+    #[test]
+    fn test_uart_timeline_tags_bytes_with_their_step() {
+        let sink = Arc::new(Mutex::new(Vec::new()));
+        let timeline = UartTimeline::new(sink.clone());
+        timeline.on_step_end(1);
+        timeline.on_step_complete();
+        sink.lock().unwrap().extend_from_slice(b"a,");
+        timeline.on_step_end(3);
+        timeline.on_step_complete();
+
+        let mut csv = Vec::new();
+        timeline.write_csv(&mut csv).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "step,cycle,byte,ascii\n2,4,0x61,a\n2,4,0x2c,\",\"\n"
+        );
+    }
+}
@@ -0,0 +1,201 @@
+// LabWired - Firmware Simulation Platform
+// Copyright (C) 2026 Andrii Shylenko
+//
+// This software is released under the MIT License.
+// See the LICENSE file in the project root for full license information.
+
+use crate::SimulationObserver;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// GPIO output pins per port.
+const GPIO_PINS: u8 = 16;
+/// Value changes a `VcdRecorder::new` recorder keeps before it stops
+/// recording.
+pub const DEFAULT_MAX_CHANGES: usize = 1 << 20;
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Signal {
+    /// Pin of a GPIO port's output data register, e.g. `gpioc` pin 13.
+    Gpio(String, u8),
+    /// Exception number; high for the step in which it was raised.
+    Irq(u32),
+}
+
+impl Signal {
+    fn name(&self) -> String {
+        match self {
+            Signal::Gpio(port, pin) => format!("{}_{}", port, pin),
+            Signal::Irq(irq) => format!("irq_{}", irq),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct VcdState {
+    /// Simulated time in CPU cycles.
+    time: u64,
+    odr: BTreeMap<String, u32>,
+    /// Interrupts raised since the last step, to be lowered when it ends.
+    raised: Vec<u32>,
+    /// Value changes in time order.
+    changes: Vec<(u64, Signal, bool)>,
+    /// Time of the first change dropped for lack of room.
+    dropped_from: Option<u64>,
+}
+
+impl VcdState {
+    fn push(&mut self, max_changes: usize, signal: Signal, value: bool) {
+        if self.changes.len() < max_changes {
+            self.changes.push((self.time, signal, value));
+        } else if self.dropped_from.is_none() {
+            tracing::warn!(
+                "VCD change limit ({}) reached at cycle {}; later changes are dropped",
+                max_changes,
+                self.time
+            );
+            self.dropped_from = Some(self.time);
+        }
+    }
+}
+
+/// Records GPIO output pins and interrupt requests over simulated time and
+/// writes them as a Value Change Dump on `on_simulation_stop`.
+///
+/// One time unit is one CPU cycle as reported through `on_step_end`.
+/// Every pin of each GPIO port seen is dumped; interrupts appear once raised.
+/// Changes are held in memory until then, up to a limit: once it is reached
+/// the dump ends there, with a comment saying so.
+#[derive(Debug)]
+pub struct VcdRecorder {
+    path: PathBuf,
+    max_changes: usize,
+    state: Mutex<VcdState>,
+}
+
+impl VcdRecorder {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self::with_max_changes(path, DEFAULT_MAX_CHANGES)
+    }
+
+    /// A recorder that keeps at most `max_changes` value changes.
+    pub fn with_max_changes(path: impl Into<PathBuf>, max_changes: usize) -> Self {
+        Self {
+            path: path.into(),
+            max_changes,
+            state: Mutex::new(VcdState::default()),
+        }
+    }
+
+    /// The dump as it would be written now.
+    pub fn render(&self) -> String {
+        let Ok(state) = self.state.lock() else {
+            return String::new();
+        };
+
+        let mut signals: Vec<Signal> = state
+            .odr
+            .keys()
+            .flat_map(|port| (0..GPIO_PINS).map(|pin| Signal::Gpio(port.clone(), pin)))
+            .collect();
+        for (_, signal, _) in &state.changes {
+            if matches!(signal, Signal::Irq(_)) && !signals.contains(signal) {
+                signals.push(signal.clone());
+            }
+        }
+        signals.sort();
+        let ids: BTreeMap<&Signal, String> = signals
+            .iter()
+            .enumerate()
+            .map(|(i, s)| (s, identifier(i)))
+            .collect();
+
+        let mut out = String::new();
+        out.push_str("$comment LabWired simulation; one time unit is one CPU cycle $end\n");
+        if let Some(time) = state.dropped_from {
+            let _ = writeln!(
+                out,
+                "$comment change limit reached: nothing recorded from #{} on $end",
+                time
+            );
+        }
+        out.push_str("$timescale 1ns $end\n");
+        out.push_str("$scope module labwired $end\n");
+        for signal in &signals {
+            let _ = writeln!(out, "$var wire 1 {} {} $end", ids[signal], signal.name());
+        }
+        out.push_str("$upscope $end\n$enddefinitions $end\n");
+
+        out.push_str("#0\n$dumpvars\n");
+        for signal in &signals {
+            let _ = writeln!(out, "0{}", ids[signal]);
+        }
+        out.push_str("$end\n");
+
+        let mut last_time = 0;
+        for (time, signal, value) in &state.changes {
+            if *time != last_time {
+                let _ = writeln!(out, "#{}", time);
+                last_time = *time;
+            }
+            let _ = writeln!(out, "{}{}", u8::from(*value), ids[signal]);
+        }
+        out
+    }
+}
+
+/// VCD identifier for the `index`th signal: printable ASCII `!`..`~`, base 94.
+fn identifier(mut index: usize) -> String {
+    let mut id = String::new();
+    loop {
+        id.push((b'!' + (index % 94) as u8) as char);
+        index /= 94;
+        if index == 0 {
+            return id;
+        }
+        index -= 1;
+    }
+}
+
+impl SimulationObserver for VcdRecorder {
+    fn on_simulation_stop(&self) {
+        if let Err(e) = std::fs::write(&self.path, self.render()) {
+            tracing::warn!("Failed to write VCD {:?}: {}", self.path, e);
+        }
+    }
+
+    fn on_step_end(&self, cycles: u32) {
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+        state.time += cycles as u64;
+        let raised = std::mem::take(&mut state.raised);
+        for irq in raised {
+            state.push(self.max_changes, Signal::Irq(irq), false);
+        }
+    }
+
+    fn on_gpio_change(&self, port: &str, odr: u32) {
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+        let old = state.odr.insert(port.to_string(), odr).unwrap_or(0);
+        for pin in (0..GPIO_PINS).filter(|pin| (old ^ odr) & (1 << pin) != 0) {
+            let value = odr & (1 << pin) != 0;
+            state.push(self.max_changes, Signal::Gpio(port.to_string(), pin), value);
+        }
+    }
+
+    fn on_interrupt(&self, irq: u32) {
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+        if state.raised.contains(&irq) {
+            return;
+        }
+        state.raised.push(irq);
+        state.push(self.max_changes, Signal::Irq(irq), true);
+    }
+}
@@ -0,0 +1,109 @@
+// LabWired - Firmware Simulation Platform
+// Copyright (C) 2026 Andrii Shylenko
+//
+// This software is released under the MIT License.
+// See the LICENSE file in the project root for full license information.
+
+use crate::{Bus, DmaRequest, SimResult};
+use std::cell::Cell;
+
+/// Accesses a watchpoint fires on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchKind {
+    Write,
+    Read,
+    ReadWrite,
+}
+
+/// Data watchpoint on `len` bytes starting at `addr`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Watchpoint {
+    pub addr: u32,
+    pub len: u32,
+    pub kind: WatchKind,
+}
+
+impl Watchpoint {
+    fn matches(&self, addr: u64, is_write: bool) -> bool {
+        let kind_matches = match self.kind {
+            WatchKind::Write => is_write,
+            WatchKind::Read => !is_write,
+            WatchKind::ReadWrite => true,
+        };
+        let start = self.addr as u64;
+        kind_matches && addr >= start && addr < start + self.len as u64
+    }
+}
+
+/// Bus wrapper that records the first access matching a watchpoint.
+///
+/// Only CPU accesses are routed through it: debugger reads/writes and DMA do
+/// not trigger watchpoints. Instruction fetches are bus reads too, so a read
+/// watchpoint over code also fires when that code executes.
+pub(crate) struct WatchedBus<'a> {
+    inner: &'a mut dyn Bus,
+    watchpoints: &'a [Watchpoint],
+    hit: Cell<Option<(u32, WatchKind)>>,
+}
+
+impl<'a> WatchedBus<'a> {
+    pub(crate) fn new(inner: &'a mut dyn Bus, watchpoints: &'a [Watchpoint]) -> Self {
+        Self {
+            inner,
+            watchpoints,
+            hit: Cell::new(None),
+        }
+    }
+
+    /// Accessed address and kind of the watchpoint that fired, if any.
+    pub(crate) fn hit(&self) -> Option<(u32, WatchKind)> {
+        self.hit.get()
+    }
+
+    fn check(&self, addr: u64, is_write: bool) {
+        if self.hit.get().is_some() {
+            return;
+        }
+        if let Some(wp) = self.watchpoints.iter().find(|w| w.matches(addr, is_write)) {
+            self.hit.set(Some((addr as u32, wp.kind)));
+        }
+    }
+}
+
+impl Bus for WatchedBus<'_> {
+    fn read_u8(&self, addr: u64) -> SimResult<u8> {
+        let value = self.inner.read_u8(addr)?;
+        self.check(addr, false);
+        Ok(value)
+    }
+
+    fn peek_u8(&self, addr: u64) -> SimResult<u8> {
+        self.inner.peek_u8(addr)
+    }
+
+    fn write_u8(&mut self, addr: u64, value: u8) -> SimResult<()> {
+        self.inner.write_u8(addr, value)?;
+        self.check(addr, true);
+        Ok(())
+    }
+
+    fn write_u16(&mut self, addr: u64, value: u16) -> SimResult<()> {
+        self.inner.write_u16(addr, value)?;
+        (0..2).for_each(|i| self.check(addr + i, true));
+        Ok(())
+    }
+
+    fn write_u32(&mut self, addr: u64, value: u32) -> SimResult<()> {
+        self.inner.write_u32(addr, value)?;
+        (0..4).for_each(|i| self.check(addr + i, true));
+        Ok(())
+    }
+
+    fn tick_peripherals(&mut self) -> Vec<u32> {
+        self.inner.tick_peripherals()
+    }
+
+    fn execute_dma(&mut self, requests: &[DmaRequest]) -> SimResult<()> {
+        self.inner.execute_dma(requests)
+    }
+}
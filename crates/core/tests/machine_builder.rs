@@ -0,0 +1,63 @@
+// LabWired - Firmware Simulation Platform
+// Copyright (C) 2026 Andrii Shylenko
+//
+// This software is released under the MIT License.
+// See the LICENSE file in the project root for full license information.
+
+use labwired_core::cpu::CortexM;
+use labwired_core::{
+    Arch, DebugControl, MachineBuilder, ProgramImage, SimulationObserver, StopReason,
+};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+#[derive(Debug, Default)]
+struct StepCounter {
+    started: AtomicU32,
+    steps: AtomicU32,
+}
+
+impl SimulationObserver for StepCounter {
+    fn on_simulation_start(&self) {
+        self.started.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn on_step_end(&self, _cycles: u32) {
+        self.steps.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn test_builder_runs_to_breakpoint_with_observer() {
+    let mut vectors = Vec::new();
+    vectors.extend_from_slice(&0x2000_1000u32.to_le_bytes()); // Initial SP
+    vectors.extend_from_slice(&0x41u32.to_le_bytes()); // Reset vector (Thumb)
+    let code: Vec<u8> = [
+        0x2002u16, // 0x40: movs r0, #2
+        0x2103,    // 0x42: movs r1, #3
+        0x1842,    // 0x44: adds r2, r0, r1
+        0xE7FE,    // 0x46: b .
+    ]
+    .iter()
+    .flat_map(|op| op.to_le_bytes())
+    .collect();
+    let image = ProgramImage::new(0x40, Arch::Arm)
+        .with_segment(0x0, vectors)
+        .with_segment(0x40, code);
+
+    let counter = Arc::new(StepCounter::default());
+    let mut machine = MachineBuilder::<CortexM>::new()
+        .firmware(image)
+        .observer(counter.clone())
+        .breakpoint(0x46)
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        machine.run(Some(100)).unwrap(),
+        StopReason::Breakpoint(0x46)
+    );
+    assert_eq!(machine.read_core_reg(2), 5);
+    assert_eq!(counter.started.load(Ordering::SeqCst), 1);
+    assert_eq!(counter.steps.load(Ordering::SeqCst), 3);
+}
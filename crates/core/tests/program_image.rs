@@ -0,0 +1,47 @@
+// LabWired - Firmware Simulation Platform
+// Copyright (C) 2026 Andrii Shylenko
+//
+// This software is released under the MIT License.
+// See the LICENSE file in the project root for full license information.
+
+use labwired_core::bus::SystemBus;
+use labwired_core::system::cortex_m::configure_cortex_m;
+use labwired_core::{Arch, DebugControl, Machine, ProgramImage, StopReason};
+
+fn thumb(code: &[u16]) -> Vec<u8> {
+    code.iter().flat_map(|op| op.to_le_bytes()).collect()
+}
+
+#[test]
+fn test_program_image_built_in_code_runs_to_completion() {
+    let mut vectors = Vec::new();
+    vectors.extend_from_slice(&0x2000_1000u32.to_le_bytes()); // Initial SP
+    vectors.extend_from_slice(&0x41u32.to_le_bytes()); // Reset vector (Thumb)
+    let image = ProgramImage::new(0x40, Arch::Arm)
+        .with_segment(0x0, vectors)
+        .with_segment(
+            0x40,
+            thumb(&[
+                0x2002, // 0x40: movs r0, #2
+                0x2103, // 0x42: movs r1, #3
+                0x1842, // 0x44: adds r2, r0, r1
+                0xE7FE, // 0x46: b .
+            ]),
+        )
+        .with_bss(0x2000_0000, 0x10);
+
+    let mut bus = SystemBus::new();
+    let (cpu, _nvic) = configure_cortex_m(&mut bus);
+    let mut machine = Machine::new(cpu, bus);
+    machine.load_program(&image).unwrap();
+    assert_eq!(machine.cpu.pc & !1, 0x40);
+
+    // The closing `b .` is where the program is done
+    machine.add_breakpoint(0x46);
+    assert_eq!(
+        machine.run(Some(100)).unwrap(),
+        StopReason::Breakpoint(0x46)
+    );
+    assert_eq!(machine.read_core_reg(2), 5);
+    assert_eq!(machine.read_core_reg(13), 0x2000_1000);
+}
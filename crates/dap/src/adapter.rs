@@ -5,14 +5,297 @@
 // See the LICENSE file in the project root for full license information.
 
 use anyhow::{anyhow, Result};
-use labwired_core::{cpu::CortexM, DebugControl, Machine, StopReason};
+use labwired_core::decoder::arm::{decode_thumb_16, decode_thumb_32, Instruction};
+use labwired_core::{
+    cpu::CortexM, DebugControl, Machine, PeripheralRegister, SimResult, StopReason,
+};
 use labwired_loader::SymbolProvider;
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 
+/// Instruction budget for one next/stepIn/stepOut request, so stepping over
+/// a call that never returns cannot hang the adapter.
+const STEP_LIMIT: u32 = 1_000_000;
+
+/// Instruction budget for one continue request.
+const CONTINUE_LIMIT: u32 = 100_000;
+
+/// Control flow of the instruction at PC, as far as stepping is concerned.
+enum Flow {
+    /// BL/BLX: the callee returns to PC + `len`.
+    Call {
+        len: u32,
+    },
+    /// BX LR or POP {.., PC} back to a caller (not an exception return).
+    Return,
+    Other,
+}
+
+fn classify(machine: &Machine<CortexM>) -> Flow {
+    let pc = machine.cpu.pc & !1;
+    let Ok(bytes) = machine.read_memory(pc, 4) else {
+        return Flow::Other;
+    };
+    let h1 = u16::from_le_bytes([bytes[0], bytes[1]]);
+    let h2 = u16::from_le_bytes([bytes[2], bytes[3]]);
+
+    if (h1 & 0xF800) == 0xF000 && (h2 & 0xD000) == 0xD000 {
+        return Flow::Call { len: 4 }; // BL
+    }
+    if (h1 & 0xFF87) == 0x4780 {
+        return Flow::Call { len: 2 }; // BLX Rm
+    }
+
+    let target = if h1 == 0x4770 {
+        Some(machine.cpu.lr) // BX LR
+    } else if (h1 & 0xFF00) == 0xBD00 {
+        // POP {.., PC}: PC is loaded from above the popped low registers
+        let offset = (h1 & 0xFF).count_ones() * 4;
+        machine
+            .read_memory(machine.cpu.sp.wrapping_add(offset), 4)
+            .ok()
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    } else {
+        None
+    };
+    match target {
+        Some(addr) if addr & 0xF000_0000 != 0xF000_0000 => Flow::Return,
+        _ => Flow::Other,
+    }
+}
+
+/// Single-step, charging the step budget.
+fn step_budgeted(machine: &mut Machine<CortexM>, budget: &mut u32) -> SimResult<StopReason> {
+    if *budget == 0 {
+        return Ok(StopReason::MaxStepsReached);
+    }
+    *budget -= 1;
+    machine.step_single()
+}
+
+/// Execute one instruction, running through the callee if it is a call.
+/// Breakpoints inside the callee still stop the step.
+fn step_over_instruction(
+    machine: &mut Machine<CortexM>,
+    budget: &mut u32,
+) -> SimResult<StopReason> {
+    let Flow::Call { len } = classify(machine) else {
+        return step_budgeted(machine, budget);
+    };
+    let return_addr = (machine.cpu.pc & !1).wrapping_add(len);
+    let sp = machine.cpu.sp;
+
+    let reason = step_budgeted(machine, budget)?;
+    if reason != StopReason::StepDone {
+        return Ok(reason);
+    }
+    // Recursive calls pass through the return address with a deeper stack.
+    while machine.cpu.pc & !1 != return_addr || machine.cpu.sp < sp {
+        let pc = machine.cpu.pc & !1;
+        if machine.breakpoints.contains(&pc) {
+            return Ok(StopReason::Breakpoint(pc));
+        }
+        let reason = step_budgeted(machine, budget)?;
+        if reason != StopReason::StepDone {
+            return Ok(reason);
+        }
+    }
+    Ok(StopReason::StepDone)
+}
+
+/// Result of evaluating a watch/hover expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Evaluation {
+    pub value: u32,
+    pub type_name: &'static str,
+    /// Address read for memory dereferences.
+    pub address: Option<u64>,
+}
+
+/// One decoded Thumb instruction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DisassembledLine {
+    pub address: u64,
+    /// Raw encoding as stored in memory (2 or 4 bytes).
+    pub bytes: Vec<u8>,
+    pub text: String,
+}
+
+/// A bus peripheral as listed in the Peripherals scope.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeripheralInfo {
+    pub name: String,
+    pub base: u64,
+}
+
+/// Integer literal: `0x`-prefixed hex or decimal, `_` separators allowed.
+fn parse_integer(text: &str) -> Option<u64> {
+    let text = text.trim().replace('_', "");
+    match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => text.parse().ok(),
+    }
+}
+
+/// Core register number for `r0`..`r15`, `sp`, `lr` or `pc`.
+fn parse_register(name: &str) -> Option<u8> {
+    match name.to_ascii_lowercase().as_str() {
+        "sp" => Some(13),
+        "lr" => Some(14),
+        "pc" => Some(15),
+        other => other
+            .strip_prefix('r')?
+            .parse()
+            .ok()
+            .filter(|&id: &u8| id < 16),
+    }
+}
+
+/// [`LabwiredAdapter::evaluate`] on an already locked machine.
+fn evaluate_expression(machine: &Machine<CortexM>, expression: &str) -> Result<Evaluation> {
+    let expr = expression.trim();
+    let invalid = || anyhow!("Cannot evaluate '{}'", expression);
+
+    if let Some(name) = expr.strip_prefix('$') {
+        let id = parse_register(name).ok_or_else(invalid)?;
+        return Ok(Evaluation {
+            value: machine.read_core_reg(id),
+            type_name: "uint32",
+            address: None,
+        });
+    }
+
+    if let Some(rest) = expr.strip_prefix('*') {
+        let (cast, addr) = rest
+            .trim_start()
+            .strip_prefix('(')
+            .and_then(|r| r.split_once(')'))
+            .ok_or_else(invalid)?;
+        let cast: String = cast.split_whitespace().collect();
+        let (size, type_name) = match cast.as_str() {
+            "u8*" => (1, "uint8"),
+            "u16*" => (2, "uint16"),
+            "u32*" => (4, "uint32"),
+            _ => return Err(invalid()),
+        };
+        let addr = parse_integer(addr).ok_or_else(invalid)?;
+        let bytes = machine
+            .read_memory(addr as u32, size)
+            .map_err(|e| anyhow!("Memory read failed: {:?}", e))?;
+        let value = bytes
+            .iter()
+            .rev()
+            .fold(0u32, |acc, &b| (acc << 8) | b as u32);
+        return Ok(Evaluation {
+            value,
+            type_name,
+            address: Some(addr),
+        });
+    }
+
+    let value = parse_integer(expr)
+        .and_then(|v| u32::try_from(v).ok())
+        .ok_or_else(invalid)?;
+    Ok(Evaluation {
+        value,
+        type_name: "uint32",
+        address: None,
+    })
+}
+
+/// Comparison operators accepted in breakpoint conditions, longest first.
+const COMPARISONS: [&str; 6] = ["==", "!=", "<=", ">=", "<", ">"];
+
+/// Evaluate a breakpoint condition: `LHS OP RHS` with a comparison operator,
+/// or a bare expression that is true when non-zero.
+fn evaluate_condition(machine: &Machine<CortexM>, condition: &str) -> Result<bool> {
+    for op in COMPARISONS {
+        if let Some((lhs, rhs)) = condition.split_once(op) {
+            let lhs = evaluate_expression(machine, lhs)?.value;
+            let rhs = evaluate_expression(machine, rhs)?.value;
+            return Ok(match op {
+                "==" => lhs == rhs,
+                "!=" => lhs != rhs,
+                "<=" => lhs <= rhs,
+                ">=" => lhs >= rhs,
+                "<" => lhs < rhs,
+                _ => lhs > rhs,
+            });
+        }
+    }
+    Ok(evaluate_expression(machine, condition)?.value != 0)
+}
+
+/// Whether the `hits`-th hit satisfies a DAP `hitCondition`: `N` or `== N`
+/// (exactly the Nth hit), `>= N`, `> N`, or `% N` (every Nth hit).
+fn hit_condition_met(hit_condition: &str, hits: u64) -> Result<bool> {
+    let text = hit_condition.trim();
+    let (op, count) = [">=", "==", ">", "%"]
+        .iter()
+        .find_map(|op| text.strip_prefix(op).map(|rest| (*op, rest)))
+        .unwrap_or(("==", text));
+    let n =
+        parse_integer(count).ok_or_else(|| anyhow!("Invalid hit condition '{}'", hit_condition))?;
+    Ok(match op {
+        ">=" => hits >= n,
+        ">" => hits > n,
+        "%" => n != 0 && hits.is_multiple_of(n),
+        _ => hits == n,
+    })
+}
+
+/// A source line breakpoint as sent in `setBreakpoints`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LineBreakpoint {
+    pub line: i64,
+    pub condition: Option<String>,
+    pub hit_condition: Option<String>,
+}
+
+/// Condition and hit count of the breakpoint at one address.
+#[derive(Debug, Default)]
+struct BreakpointState {
+    condition: Option<String>,
+    hit_condition: Option<String>,
+    hits: u64,
+}
+
+impl BreakpointState {
+    /// Count a hit and decide whether it stops execution. Hits only count
+    /// while the condition holds; a condition that fails to evaluate stops.
+    fn should_stop(&mut self, machine: &Machine<CortexM>) -> bool {
+        if let Some(condition) = &self.condition {
+            match evaluate_condition(machine, condition) {
+                Ok(false) => return false,
+                Ok(true) => {}
+                Err(e) => {
+                    tracing::warn!("Breakpoint condition '{}': {}", condition, e);
+                    return true;
+                }
+            }
+        }
+        self.hits += 1;
+        match &self.hit_condition {
+            Some(hit_condition) => {
+                hit_condition_met(hit_condition, self.hits).unwrap_or_else(|e| {
+                    tracing::warn!("{}", e);
+                    true
+                })
+            }
+            None => true,
+        }
+    }
+}
+
 pub struct LabwiredAdapter {
     pub machine: Arc<Mutex<Option<Machine<CortexM>>>>,
     pub symbols: Arc<Mutex<Option<SymbolProvider>>>,
+    /// UART TX bytes not yet forwarded to the client.
+    pub uart_tx: Arc<Mutex<Vec<u8>>>,
+    breakpoint_states: Mutex<HashMap<u32, BreakpointState>>,
+    /// Addresses of the current function breakpoints.
+    function_breakpoints: Mutex<Vec<u32>>,
 }
 
 impl Default for LabwiredAdapter {
@@ -26,6 +309,9 @@ impl LabwiredAdapter {
         Self {
             machine: Arc::new(Mutex::new(None)),
             symbols: Arc::new(Mutex::new(None)),
+            uart_tx: Arc::new(Mutex::new(Vec::new())),
+            breakpoint_states: Mutex::new(HashMap::new()),
+            function_breakpoints: Mutex::new(Vec::new()),
         }
     }
 
@@ -34,6 +320,8 @@ impl LabwiredAdapter {
         let image = labwired_loader::load_elf(&path)?;
 
         let mut bus = labwired_core::bus::SystemBus::new();
+        // stdout carries the protocol, so UART output is only captured
+        bus.attach_uart_tx_sink(self.uart_tx.clone(), false);
         let (cpu, _nvic) = labwired_core::system::cortex_m::configure_cortex_m(&mut bus);
         let mut machine = Machine::new(cpu, bus);
         machine
@@ -56,6 +344,11 @@ impl LabwiredAdapter {
         self.symbols.lock().unwrap().as_ref()?.lookup(addr)
     }
 
+    /// UART output captured since the last call.
+    pub fn take_uart_output(&self) -> Vec<u8> {
+        std::mem::take(&mut *self.uart_tx.lock().unwrap())
+    }
+
     pub fn get_pc(&self) -> Result<u32> {
         let guard = self.machine.lock().unwrap();
         if let Some(machine) = guard.as_ref() {
@@ -74,6 +367,26 @@ impl LabwiredAdapter {
         }
     }
 
+    pub fn set_register(&self, id: u8, val: u32) -> Result<()> {
+        let mut guard = self.machine.lock().unwrap();
+        if let Some(machine) = guard.as_mut() {
+            machine.write_core_reg(id, val);
+            Ok(())
+        } else {
+            Err(anyhow!("Machine not initialized"))
+        }
+    }
+
+    /// Evaluate `$r0`..`$pc` register reads, `*(u32*)0x2000_0000`-style
+    /// dereferences (`u8`, `u16` or `u32`) and integer literals.
+    pub fn evaluate(&self, expression: &str) -> Result<Evaluation> {
+        let guard = self.machine.lock().unwrap();
+        let machine = guard
+            .as_ref()
+            .ok_or_else(|| anyhow!("Machine not initialized"))?;
+        evaluate_expression(machine, expression)
+    }
+
     pub fn step(&self) -> Result<StopReason> {
         let mut guard = self.machine.lock().unwrap();
         if let Some(machine) = guard.as_mut() {
@@ -86,42 +399,142 @@ impl LabwiredAdapter {
         }
     }
 
-    pub fn continue_execution(&self) -> Result<StopReason> {
+    /// Source line (file, line) containing `pc`, if debug info covers it.
+    fn line_at(syms: Option<&SymbolProvider>, pc: u32) -> Option<(String, u32)> {
+        let loc = syms?.lookup(pc as u64)?;
+        Some((loc.file, loc.line?))
+    }
+
+    /// Step until PC leaves the current source line. Falls back to a single
+    /// instruction when there is no line information or `instruction` is set.
+    fn step_line(&self, over_calls: bool, instruction: bool) -> Result<StopReason> {
+        let syms_guard = self.symbols.lock().unwrap();
         let mut guard = self.machine.lock().unwrap();
-        if let Some(machine) = guard.as_mut() {
-            // Run for a chunk of steps or until breakpoint
-            // For interactivity, we might want to run in a loop and release lock?
-            // But DAP requests (pause) need to acquire lock.
-            // Simplified: Run 1000 steps, check if we should stop?
-            // Or just run(). usage of `run(None)` runs forever until breakpoint.
-            let reason = machine
-                .run(Some(100_000))
-                .map_err(|e| anyhow!("Run failed: {:?}", e))?;
-            Ok(reason)
+        let machine = guard
+            .as_mut()
+            .ok_or_else(|| anyhow!("Machine not initialized"))?;
+
+        let start = if instruction {
+            None
         } else {
-            Err(anyhow!("Machine not initialized"))
+            Self::line_at(syms_guard.as_ref(), machine.cpu.pc & !1)
+        };
+        let mut budget = STEP_LIMIT;
+        loop {
+            let reason = if over_calls {
+                step_over_instruction(machine, &mut budget)
+            } else {
+                step_budgeted(machine, &mut budget)
+            }
+            .map_err(|e| anyhow!("Step failed: {:?}", e))?;
+
+            if reason != StopReason::StepDone || start.is_none() {
+                return Ok(reason);
+            }
+            if Self::line_at(syms_guard.as_ref(), machine.cpu.pc & !1) != start {
+                return Ok(StopReason::StepDone);
+            }
+        }
+    }
+
+    /// DAP `next`: step to the next source line, stepping over calls.
+    pub fn next(&self, instruction: bool) -> Result<StopReason> {
+        self.step_line(true, instruction)
+    }
+
+    /// DAP `stepIn`: step to the next source line, entering calls.
+    pub fn step_in(&self, instruction: bool) -> Result<StopReason> {
+        self.step_line(false, instruction)
+    }
+
+    /// DAP `stepOut`: run until the current function returns to its caller.
+    /// Call depth is tracked from BL/BLX and BX LR/POP {PC}.
+    pub fn step_out(&self) -> Result<StopReason> {
+        let mut guard = self.machine.lock().unwrap();
+        let machine = guard
+            .as_mut()
+            .ok_or_else(|| anyhow!("Machine not initialized"))?;
+
+        let mut budget = STEP_LIMIT;
+        let mut depth = 0u32;
+        loop {
+            let flow = classify(machine);
+            let reason =
+                step_budgeted(machine, &mut budget).map_err(|e| anyhow!("Step failed: {:?}", e))?;
+            if reason != StopReason::StepDone {
+                return Ok(reason);
+            }
+            match flow {
+                Flow::Call { .. } => depth += 1,
+                Flow::Return if depth == 0 => return Ok(StopReason::StepDone),
+                Flow::Return => depth -= 1,
+                Flow::Other => {}
+            }
+            let pc = machine.cpu.pc & !1;
+            if machine.breakpoints.contains(&pc) {
+                return Ok(StopReason::Breakpoint(pc));
+            }
+        }
+    }
+
+    /// Run until a breakpoint whose condition and hit count are satisfied,
+    /// or for at most `CONTINUE_LIMIT` instructions. A breakpoint at the
+    /// starting PC is stepped over rather than reported again.
+    pub fn continue_execution(&self) -> Result<StopReason> {
+        let mut guard = self.machine.lock().unwrap();
+        let machine = guard
+            .as_mut()
+            .ok_or_else(|| anyhow!("Machine not initialized"))?;
+        let mut states = self.breakpoint_states.lock().unwrap();
+
+        let mut budget = CONTINUE_LIMIT;
+        loop {
+            let reason =
+                step_budgeted(machine, &mut budget).map_err(|e| anyhow!("Run failed: {:?}", e))?;
+            if reason != StopReason::StepDone {
+                return Ok(reason);
+            }
+            let pc = machine.cpu.pc & !1;
+            if machine.breakpoints.contains(&pc)
+                && states.entry(pc).or_default().should_stop(machine)
+            {
+                return Ok(StopReason::Breakpoint(pc));
+            }
         }
     }
 
-    pub fn set_breakpoints(&self, path: String, lines: Vec<i64>) -> Result<()> {
-        let mut addresses = Vec::new();
+    pub fn set_breakpoints(&self, path: String, breakpoints: Vec<LineBreakpoint>) -> Result<()> {
+        let mut resolved = Vec::new();
 
         let syms_guard = self.symbols.lock().unwrap();
         if let Some(syms) = syms_guard.as_ref() {
-            for line in lines {
-                if let Some(addr) = syms.location_to_pc(&path, line as u32) {
-                    addresses.push(addr as u32);
+            for bp in breakpoints {
+                if let Some(addr) = syms.location_to_pc(&path, bp.line as u32) {
+                    resolved.push((addr as u32, bp));
                 } else {
-                    tracing::warn!("Could not resolve breakpoint at {}:{}", path, line);
+                    tracing::warn!("Could not resolve breakpoint at {}:{}", path, bp.line);
                 }
             }
         }
 
         let mut machine_guard = self.machine.lock().unwrap();
         if let Some(machine) = machine_guard.as_mut() {
+            let mut states = self.breakpoint_states.lock().unwrap();
             machine.clear_breakpoints();
-            for addr in addresses {
+            states.clear();
+            for &addr in self.function_breakpoints.lock().unwrap().iter() {
+                machine.add_breakpoint(addr);
+            }
+            for (addr, bp) in resolved {
                 machine.add_breakpoint(addr);
+                states.insert(
+                    addr,
+                    BreakpointState {
+                        condition: bp.condition,
+                        hit_condition: bp.hit_condition,
+                        hits: 0,
+                    },
+                );
                 tracing::info!("Breakpoint set at {:#x}", addr);
             }
         }
@@ -129,12 +542,145 @@ impl LabwiredAdapter {
         Ok(())
     }
 
+    /// Replace the function breakpoints with breakpoints on `names`, returning
+    /// each name's resolved address (`None` if no such symbol).
+    pub fn set_function_breakpoints(&self, names: &[String]) -> Result<Vec<Option<u32>>> {
+        let syms_guard = self.symbols.lock().unwrap();
+        let resolved: Vec<Option<u32>> = names
+            .iter()
+            .map(|name| {
+                let addr = syms_guard.as_ref()?.symbol_address(name);
+                if addr.is_none() {
+                    tracing::warn!("Could not resolve function breakpoint '{}'", name);
+                }
+                addr.map(|a| a as u32)
+            })
+            .collect();
+
+        let mut machine_guard = self.machine.lock().unwrap();
+        if let Some(machine) = machine_guard.as_mut() {
+            let states = self.breakpoint_states.lock().unwrap();
+            let mut function_breakpoints = self.function_breakpoints.lock().unwrap();
+            // Line breakpoints on the same address stay in place
+            for addr in function_breakpoints.drain(..) {
+                if !states.contains_key(&addr) {
+                    machine.remove_breakpoint(addr);
+                }
+            }
+            for &addr in resolved.iter().flatten() {
+                machine.add_breakpoint(addr);
+                function_breakpoints.push(addr);
+                tracing::info!("Function breakpoint set at {:#x}", addr);
+            }
+        }
+
+        Ok(resolved)
+    }
+
+    /// Bus peripherals in bus order; indices match `peripheral_registers`.
+    pub fn peripherals(&self) -> Result<Vec<PeripheralInfo>> {
+        let guard = self.machine.lock().unwrap();
+        let machine = guard
+            .as_ref()
+            .ok_or_else(|| anyhow!("Machine not initialized"))?;
+        Ok(machine
+            .bus
+            .peripherals
+            .iter()
+            .map(|p| PeripheralInfo {
+                name: p.name.clone(),
+                base: p.base,
+            })
+            .collect())
+    }
+
+    /// Register values of the `index`-th bus peripheral.
+    pub fn peripheral_registers(&self, index: usize) -> Result<Vec<PeripheralRegister>> {
+        let guard = self.machine.lock().unwrap();
+        let machine = guard
+            .as_ref()
+            .ok_or_else(|| anyhow!("Machine not initialized"))?;
+        machine
+            .bus
+            .peripherals
+            .get(index)
+            .map(|p| p.dev.registers())
+            .ok_or_else(|| anyhow!("No peripheral with index {}", index))
+    }
+
     pub fn read_memory(&self, addr: u64, len: usize) -> Result<Vec<u8>> {
         let machine_guard = self.machine.lock().unwrap();
         if let Some(machine) = machine_guard.as_ref() {
+            let mut data = vec![0; len];
+            machine
+                .read_memory_into(addr as u32, &mut data)
+                .map_err(|e| anyhow!("Memory read failed: {:?}", e))?;
+            Ok(data)
+        } else {
+            Err(anyhow!("Machine not initialized"))
+        }
+    }
+
+    /// Decode `count` Thumb instructions starting at `addr`. Unreadable
+    /// memory yields an `<unreadable>` entry per halfword, so exactly `count`
+    /// lines are returned.
+    pub fn disassemble(&self, addr: u64, count: usize) -> Result<Vec<DisassembledLine>> {
+        let machine_guard = self.machine.lock().unwrap();
+        let machine = machine_guard
+            .as_ref()
+            .ok_or_else(|| anyhow!("Machine not initialized"))?;
+        let read_half = |at: u64| {
+            machine
+                .read_memory(at as u32, 2)
+                .ok()
+                .map(|b| u16::from_le_bytes([b[0], b[1]]))
+        };
+
+        let mut lines = Vec::with_capacity(count);
+        let mut address = addr & !1;
+        for _ in 0..count {
+            let Some(h1) = read_half(address) else {
+                lines.push(DisassembledLine {
+                    address,
+                    bytes: Vec::new(),
+                    text: "<unreadable>".to_string(),
+                });
+                address += 2;
+                continue;
+            };
+
+            let (bytes, text) = match decode_thumb_16(h1) {
+                Instruction::Prefix32(h1) => match read_half(address + 2) {
+                    Some(h2) => {
+                        let text = match decode_thumb_32(h1, h2) {
+                            Instruction::Unknown(_) => format!(".inst.w 0x{:04x}{:04x}", h1, h2),
+                            instruction => instruction.to_string(),
+                        };
+                        let mut bytes = h1.to_le_bytes().to_vec();
+                        bytes.extend(h2.to_le_bytes());
+                        (bytes, text)
+                    }
+                    None => (h1.to_le_bytes().to_vec(), format!(".inst 0x{:04x}", h1)),
+                },
+                instruction => (h1.to_le_bytes().to_vec(), instruction.to_string()),
+            };
+            let len = bytes.len() as u64;
+            lines.push(DisassembledLine {
+                address,
+                bytes,
+                text,
+            });
+            address += len;
+        }
+        Ok(lines)
+    }
+
+    pub fn write_memory(&self, addr: u64, data: &[u8]) -> Result<()> {
+        let mut machine_guard = self.machine.lock().unwrap();
+        if let Some(machine) = machine_guard.as_mut() {
             machine
-                .read_memory(addr as u32, len)
-                .map_err(|e| anyhow!("Memory read failed: {:?}", e))
+                .write_memory_from(addr as u32, data)
+                .map_err(|e| anyhow!("Memory write failed: {:?}", e))
         } else {
             Err(anyhow!("Machine not initialized"))
         }
@@ -160,7 +706,13 @@ mod tests {
 
         // Set breakpoint at main.rs:11
         adapter
-            .set_breakpoints("main.rs".to_string(), vec![11])
+            .set_breakpoints(
+                "main.rs".to_string(),
+                vec![LineBreakpoint {
+                    line: 11,
+                    ..Default::default()
+                }],
+            )
             .expect("Failed to set breakpoints");
     }
 
@@ -4,17 +4,33 @@
 // This software is released under the MIT License.
 // See the LICENSE file in the project root for full license information.
 
+use clap::Parser;
 use labwired_dap::server::DapServer;
 use std::io;
 
+/// LabWired debug adapter. Speaks DAP over stdin/stdout unless `--port` is
+/// given.
+#[derive(Parser, Debug)]
+#[command(version, about = "LabWired Debug Adapter", long_about = None)]
+struct Args {
+    /// Listen on 127.0.0.1:PORT and serve the first client to connect
+    #[arg(long, value_name = "PORT")]
+    port: Option<u16>,
+}
+
 fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
     tracing_subscriber::fmt::init();
 
-    let stdin = io::stdin();
-    let stdout = io::stdout();
-
     let mut server = DapServer::new();
-    server.run(stdin.lock(), stdout.lock())?;
+    match args.port {
+        Some(port) => server.serve_tcp(port)?,
+        None => {
+            let stdin = io::stdin();
+            let stdout = io::stdout();
+            server.run(stdin.lock(), stdout.lock())?;
+        }
+    }
 
     Ok(())
 }
@@ -4,16 +4,29 @@
 // This software is released under the MIT License.
 // See the LICENSE file in the project root for full license information.
 
-use crate::adapter::LabwiredAdapter;
+use crate::adapter::{LabwiredAdapter, LineBreakpoint};
 use anyhow::Result;
+use dap::events::{Event, OutputEventBody, StoppedEventBody};
 use dap::requests::Command;
 use dap::responses::ResponseBody;
-use dap::types::{Breakpoint, Capabilities, Scope, Source, StackFrame, Thread, Variable};
+use dap::types::{
+    Breakpoint, Capabilities, DisassembledInstruction, OutputEventCategory, Scope, Source,
+    StackFrame, SteppingGranularity, StoppedEventReason, Thread, Variable,
+};
+use labwired_core::StopReason;
 use serde::Serialize;
 use serde_json::Value;
 use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
 use std::sync::atomic::{AtomicI64, Ordering};
 
+/// `variablesReference` of the Registers scope.
+const REGISTERS_REF: i64 = 1;
+/// `variablesReference` of the Peripherals scope.
+const PERIPHERALS_REF: i64 = 2;
+/// Peripheral `i` (in bus order) expands via `PERIPHERAL_REF_BASE + i`.
+const PERIPHERAL_REF_BASE: i64 = 1000;
+
 pub struct DapServer {
     adapter: LabwiredAdapter,
     seq: AtomicI64,
@@ -26,31 +39,115 @@ struct DapResponse {
     type_: String,
     request_seq: i64,
     success: bool,
-    command: String,
     message: Option<String>,
-    body: Option<ResponseBody>,
+    /// Serialized as the `command` tag plus `body`, per the DAP wire format.
+    #[serde(flatten)]
+    body: ResponseBody,
+}
+
+#[derive(Serialize)]
+struct DapErrorResponse {
+    seq: i64,
+    #[serde(rename = "type")]
+    type_: String,
+    request_seq: i64,
+    success: bool,
+    command: String,
+    message: String,
+}
+
+#[derive(Serialize)]
+struct DapEvent {
+    seq: i64,
+    #[serde(rename = "type")]
+    type_: String,
+    #[serde(flatten)]
+    event: Event,
+}
+
+fn write_message<W: Write>(output: &mut W, message: &impl Serialize) -> Result<()> {
+    let json = serde_json::to_string(message)?;
+    write!(output, "Content-Length: {}\r\n\r\n{}", json.len(), json)?;
+    output.flush()?;
+    Ok(())
+}
+
+fn stopped_event(reason: &StopReason) -> Event {
+    let reason = match reason {
+        StopReason::Breakpoint(_) => StoppedEventReason::Breakpoint,
+        _ => StoppedEventReason::Step,
+    };
+    Event::Stopped(StoppedEventBody {
+        reason,
+        description: None,
+        thread_id: Some(1),
+        preserve_focus_hint: None,
+        text: None,
+        all_threads_stopped: Some(true),
+        hit_breakpoint_ids: None,
+    })
+}
+
+/// Debug console output for UART bytes written by the firmware.
+fn output_event(bytes: &[u8]) -> Event {
+    Event::Output(OutputEventBody {
+        category: Some(OutputEventCategory::Stdout),
+        output: String::from_utf8_lossy(bytes).into_owned(),
+        ..Default::default()
+    })
+}
+
+/// Resolve a `memoryReference` (usually a hex string) plus optional offset.
+fn memory_address(memory_reference: &str, offset: Option<i64>) -> u64 {
+    let addr = if let Some(hex) = memory_reference.strip_prefix("0x") {
+        u64::from_str_radix(hex, 16).unwrap_or(0)
+    } else {
+        memory_reference.parse().unwrap_or(0)
+    };
+    addr.wrapping_add_signed(offset.unwrap_or(0))
 }
 
-fn command_name(cmd: &Command) -> &'static str {
-    match cmd {
-        Command::Initialize(_) => "initialize",
-        Command::Launch(_) => "launch",
-        Command::Disconnect(_) => "disconnect",
-        Command::ConfigurationDone => "configurationDone",
-        Command::SetBreakpoints(_) => "setBreakpoints",
-        Command::SetFunctionBreakpoints(_) => "setFunctionBreakpoints",
-        Command::Threads => "threads",
-        Command::StackTrace(_) => "stackTrace",
-        Command::Scopes(_) => "scopes",
-        Command::Variables(_) => "variables",
-        Command::Continue(_) => "continue",
-        Command::Next(_) => "next",
-        Command::StepIn(_) => "stepIn",
-        Command::Pause(_) => "pause",
-        _ => "unknown",
+/// Name of core register `id` in the Registers scope.
+fn register_name(id: u8) -> String {
+    match id {
+        13 => "SP".to_string(),
+        14 => "LR".to_string(),
+        15 => "PC".to_string(),
+        n => format!("R{}", n),
     }
 }
 
+/// A `uint32` variable; `variables_reference` is non-zero for expandable entries.
+fn variable(name: String, value: String, variables_reference: i64) -> Variable {
+    Variable {
+        name,
+        value,
+        variables_reference,
+        evaluate_name: None,
+        indexed_variables: None,
+        named_variables: None,
+        presentation_hint: None,
+        type_field: (variables_reference == 0).then(|| "uint32".to_string()),
+        memory_reference: None,
+    }
+}
+
+/// Parse a hex (`0x`-prefixed) or decimal register value.
+fn parse_register_value(value: &str) -> Option<u32> {
+    let value = value.trim();
+    match value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+    {
+        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+        None => value.parse().ok(),
+    }
+}
+
+fn is_instruction(granularity: &Option<SteppingGranularity>) -> bool {
+    matches!(granularity, Some(SteppingGranularity::Instruction))
+}
+
 impl Default for DapServer {
     fn default() -> Self {
         Self::new()
@@ -65,6 +162,21 @@ impl DapServer {
         }
     }
 
+    /// Listen on `127.0.0.1:port` and serve the first client to connect,
+    /// for editors that start the adapter as a debug server.
+    pub fn serve_tcp(&mut self, port: u16) -> Result<()> {
+        let listener = TcpListener::bind(("127.0.0.1", port))?;
+        tracing::info!("DAP server listening on {}", listener.local_addr()?);
+        self.serve_listener(&listener)
+    }
+
+    /// Accept one connection on `listener` and `run` the session over it.
+    pub fn serve_listener(&mut self, listener: &TcpListener) -> Result<()> {
+        let (stream, addr) = listener.accept()?;
+        tracing::info!("DAP client connected from {}", addr);
+        self.run(stream.try_clone()?, stream)
+    }
+
     pub fn run<R: Read, W: Write>(&mut self, input: R, mut output: W) -> Result<()> {
         let mut reader = BufReader::new(input);
 
@@ -107,12 +219,24 @@ impl DapServer {
             // Parse as Value to access arbitrary args
             let request_value: Value = serde_json::from_slice(&body).unwrap_or(Value::Null);
 
+            // Events to send once the response is out
+            let mut events = Vec::new();
+            // Set instead of a body to fail the request
+            let mut failure: Option<String> = None;
+
             // Handle request
             let response_body = match &request.command {
                 // Fixed: No Some() wrapper around Capabilities
                 Command::Initialize(_) => Some(ResponseBody::Initialize(Capabilities {
                     supports_configuration_done_request: Some(true),
                     supports_function_breakpoints: Some(true),
+                    supports_read_memory_request: Some(true),
+                    supports_write_memory_request: Some(true),
+                    supports_set_variable: Some(true),
+                    supports_evaluate_for_hovers: Some(true),
+                    supports_disassemble_request: Some(true),
+                    supports_conditional_breakpoints: Some(true),
+                    supports_hit_conditional_breakpoints: Some(true),
                     ..Default::default()
                 })),
                 Command::Launch(_) => {
@@ -129,21 +253,60 @@ impl DapServer {
                     Some(ResponseBody::Launch)
                 }
                 Command::Disconnect(_) => return Ok(()),
-                Command::SetFunctionBreakpoints(_) => Some(ResponseBody::SetFunctionBreakpoints(
-                    dap::responses::SetFunctionBreakpointsResponse {
-                        breakpoints: vec![],
-                    },
-                )),
+                Command::SetFunctionBreakpoints(args) => {
+                    let names: Vec<String> =
+                        args.breakpoints.iter().map(|b| b.name.clone()).collect();
+                    let resolved = self
+                        .adapter
+                        .set_function_breakpoints(&names)
+                        .unwrap_or_else(|e| {
+                            tracing::error!("Failed to set function breakpoints: {}", e);
+                            vec![None; names.len()]
+                        });
+
+                    let breakpoints = names
+                        .iter()
+                        .zip(resolved)
+                        .map(|(name, addr)| Breakpoint {
+                            id: None,
+                            verified: addr.is_some(),
+                            message: addr
+                                .is_none()
+                                .then(|| format!("No symbol named '{}'", name)),
+                            source: None,
+                            line: None,
+                            column: None,
+                            end_column: None,
+                            end_line: None,
+                            instruction_reference: addr.map(|a| format!("{:#x}", a)),
+                            offset: None,
+                        })
+                        .collect();
+
+                    Some(ResponseBody::SetFunctionBreakpoints(
+                        dap::responses::SetFunctionBreakpointsResponse { breakpoints },
+                    ))
+                }
                 Command::ConfigurationDone => Some(ResponseBody::ConfigurationDone),
                 Command::SetBreakpoints(args) => {
                     let path = args.source.path.clone().unwrap_or_default();
-                    let lines: Vec<i64> = args
+                    // `dap::types::SourceBreakpoint` expects `hit_condition`
+                    // rather than the wire name, so read it from the raw JSON.
+                    let raw = &request_value["arguments"]["breakpoints"];
+                    let requested: Vec<LineBreakpoint> = args
                         .breakpoints
-                        .as_ref()
-                        .map(|bp| bp.iter().map(|b| b.line).collect())
-                        .unwrap_or_default();
+                        .iter()
+                        .flatten()
+                        .enumerate()
+                        .map(|(i, b)| LineBreakpoint {
+                            line: b.line,
+                            condition: b.condition.clone(),
+                            hit_condition: raw[i]["hitCondition"].as_str().map(String::from),
+                        })
+                        .collect();
+                    let lines: Vec<i64> = requested.iter().map(|b| b.line).collect();
 
-                    if let Err(e) = self.adapter.set_breakpoints(path, lines.clone()) {
+                    if let Err(e) = self.adapter.set_breakpoints(path, requested) {
                         tracing::error!("Failed to set breakpoints: {}", e);
                     }
 
@@ -168,14 +331,7 @@ impl DapServer {
                     ))
                 }
                 Command::ReadMemory(args) => {
-                    // Extract address from memoryReference (it's usually a string representation of hex)
-                    let addr = if args.memory_reference.starts_with("0x") {
-                        u64::from_str_radix(&args.memory_reference[2..], 16).unwrap_or(0)
-                    } else {
-                        args.memory_reference.parse().unwrap_or(0)
-                    };
-                    let offset = args.offset.unwrap_or(0);
-                    let final_addr = addr + offset as u64;
+                    let final_addr = memory_address(&args.memory_reference, args.offset);
                     let count = args.count as usize;
 
                     match self.adapter.read_memory(final_addr, count) {
@@ -192,7 +348,63 @@ impl DapServer {
                         }
                         Err(e) => {
                             tracing::error!("ReadMemory failed: {}", e);
-                            None // Or error response
+                            failure = Some(e.to_string());
+                            None
+                        }
+                    }
+                }
+                Command::WriteMemory(args) => {
+                    use base64::Engine;
+                    let addr = memory_address(&args.memory_reference, args.offset);
+                    match base64::engine::general_purpose::STANDARD
+                        .decode(&args.data)
+                        .map_err(anyhow::Error::from)
+                        .and_then(|data| {
+                            self.adapter.write_memory(addr, &data)?;
+                            Ok(data.len())
+                        }) {
+                        Ok(written) => Some(ResponseBody::WriteMemory(
+                            dap::responses::WriteMemoryResponse {
+                                offset: None,
+                                bytes_written: Some(written as i64),
+                            },
+                        )),
+                        Err(e) => {
+                            tracing::error!("WriteMemory failed: {}", e);
+                            failure = Some(e.to_string());
+                            None
+                        }
+                    }
+                }
+                Command::Disassemble(args) => {
+                    // Thumb instructions are variable length; an instruction
+                    // offset is approximated as whole halfwords.
+                    let addr = memory_address(&args.memory_reference, args.offset)
+                        .wrapping_add_signed(args.instruction_offset.unwrap_or(0) * 2);
+                    let count = args.instruction_count.max(0) as usize;
+                    match self.adapter.disassemble(addr, count) {
+                        Ok(lines) => Some(ResponseBody::Disassemble(
+                            dap::responses::DisassembleResponse {
+                                instructions: lines
+                                    .into_iter()
+                                    .map(|line| DisassembledInstruction {
+                                        address: format!("{:#x}", line.address),
+                                        instruction_bytes: Some(
+                                            line.bytes
+                                                .iter()
+                                                .map(|b| format!("{:02x}", b))
+                                                .collect(),
+                                        ),
+                                        instruction: line.text,
+                                        ..Default::default()
+                                    })
+                                    .collect(),
+                            },
+                        )),
+                        Err(e) => {
+                            tracing::error!("Disassemble failed: {}", e);
+                            failure = Some(e.to_string());
+                            None
                         }
                     }
                 }
@@ -252,64 +464,135 @@ impl DapServer {
                     ))
                 }
                 Command::Scopes(_) => {
+                    let scope = |name: &str, variables_reference, named_variables| Scope {
+                        name: name.to_string(),
+                        variables_reference,
+                        expensive: false,
+                        column: None,
+                        end_column: None,
+                        end_line: None,
+                        indexed_variables: None,
+                        line: None,
+                        named_variables,
+                        presentation_hint: None,
+                        source: None,
+                    };
+                    let peripherals = self.adapter.peripherals().map(|p| p.len() as i64).ok();
                     Some(ResponseBody::Scopes(dap::responses::ScopesResponse {
-                        scopes: vec![Scope {
-                            name: "Registers".to_string(),
-                            variables_reference: 1, // Reference for registers
-                            expensive: false,
-                            column: None,
-                            end_column: None,
-                            end_line: None,
-                            indexed_variables: None,
-                            line: None,
-                            named_variables: Some(16),
-                            presentation_hint: None,
-                            source: None,
-                        }],
+                        scopes: vec![
+                            scope("Registers", REGISTERS_REF, Some(16)),
+                            scope("Peripherals", PERIPHERALS_REF, peripherals),
+                        ],
                     }))
                 }
                 Command::Variables(args) => {
-                    if args.variables_reference == 1 {
-                        let mut variables = Vec::new();
-                        for i in 0..16 {
-                            let name = match i {
-                                13 => "SP".to_string(),
-                                14 => "LR".to_string(),
-                                15 => "PC".to_string(),
-                                n => format!("R{}", n),
-                            };
-                            let val = self.adapter.get_register(i as u8).unwrap_or(0);
-                            variables.push(Variable {
-                                name,
-                                value: format!("{:#x}", val),
-                                variables_reference: 0,
-                                evaluate_name: None,
-                                indexed_variables: None,
-                                named_variables: None,
-                                presentation_hint: None,
-                                type_field: Some("uint32".to_string()),
-                                memory_reference: None,
-                            });
+                    let variables = match args.variables_reference {
+                        REGISTERS_REF => (0..16)
+                            .map(|i| {
+                                let val = self.adapter.get_register(i).unwrap_or(0);
+                                variable(register_name(i), format!("{:#x}", val), 0)
+                            })
+                            .collect(),
+                        PERIPHERALS_REF => self
+                            .adapter
+                            .peripherals()
+                            .unwrap_or_default()
+                            .into_iter()
+                            .enumerate()
+                            .map(|(i, p)| {
+                                let reference = PERIPHERAL_REF_BASE + i as i64;
+                                variable(p.name, format!("@ {:#010x}", p.base), reference)
+                            })
+                            .collect(),
+                        reference if reference >= PERIPHERAL_REF_BASE => self
+                            .adapter
+                            .peripheral_registers((reference - PERIPHERAL_REF_BASE) as usize)
+                            .unwrap_or_default()
+                            .into_iter()
+                            .map(|r| variable(r.name.to_string(), format!("{:#010x}", r.value), 0))
+                            .collect(),
+                        _ => vec![],
+                    };
+                    Some(ResponseBody::Variables(dap::responses::VariablesResponse {
+                        variables,
+                    }))
+                }
+                Command::SetVariable(args) => {
+                    let id = (0..16).find(|&i| register_name(i).eq_ignore_ascii_case(&args.name));
+                    let value = parse_register_value(&args.value);
+                    match (args.variables_reference, id, value) {
+                        (REGISTERS_REF, Some(id), Some(value)) => {
+                            match self.adapter.set_register(id, value) {
+                                Ok(()) => Some(ResponseBody::SetVariable(
+                                    dap::responses::SetVariableResponse {
+                                        value: format!("{:#x}", value),
+                                        type_field: Some("uint32".to_string()),
+                                        variables_reference: Some(0),
+                                        named_variables: None,
+                                        indexed_variables: None,
+                                    },
+                                )),
+                                Err(e) => {
+                                    failure = Some(e.to_string());
+                                    None
+                                }
+                            }
+                        }
+                        (REGISTERS_REF, Some(_), None) => {
+                            failure = Some(format!("Invalid register value '{}'", args.value));
+                            None
+                        }
+                        _ => {
+                            failure = Some(format!("Unknown register '{}'", args.name));
+                            None
                         }
-                        Some(ResponseBody::Variables(dap::responses::VariablesResponse {
-                            variables,
-                        }))
-                    } else {
-                        Some(ResponseBody::Variables(dap::responses::VariablesResponse {
-                            variables: vec![],
-                        }))
                     }
                 }
+                Command::Evaluate(args) => match self.adapter.evaluate(&args.expression) {
+                    Ok(eval) => Some(ResponseBody::Evaluate(dap::responses::EvaluateResponse {
+                        result: format!("{:#x}", eval.value),
+                        type_field: Some(eval.type_name.to_string()),
+                        presentation_hint: None,
+                        variables_reference: 0,
+                        named_variables: None,
+                        indexed_variables: None,
+                        memory_reference: eval.address.map(|a| format!("{:#x}", a)),
+                    })),
+                    Err(e) => {
+                        failure = Some(e.to_string());
+                        None
+                    }
+                },
                 Command::Continue(_) => {
-                    let _ = self.adapter.continue_execution();
+                    match self.adapter.continue_execution() {
+                        Ok(reason) => events.push(stopped_event(&reason)),
+                        Err(e) => tracing::error!("Continue failed: {}", e),
+                    }
                     Some(ResponseBody::Continue(dap::responses::ContinueResponse {
                         all_threads_continued: Some(true),
                     }))
                 }
-                Command::Next(_) => {
-                    let _ = self.adapter.step();
+                Command::Next(args) => {
+                    match self.adapter.next(is_instruction(&args.granularity)) {
+                        Ok(reason) => events.push(stopped_event(&reason)),
+                        Err(e) => tracing::error!("Next failed: {}", e),
+                    }
                     Some(ResponseBody::Next)
                 }
+                Command::StepIn(args) => {
+                    match self.adapter.step_in(is_instruction(&args.granularity)) {
+                        Ok(reason) => events.push(stopped_event(&reason)),
+                        Err(e) => tracing::error!("StepIn failed: {}", e),
+                    }
+                    Some(ResponseBody::StepIn)
+                }
+                Command::StepOut(_) => {
+                    match self.adapter.step_out() {
+                        Ok(reason) => events.push(stopped_event(&reason)),
+                        Err(e) => tracing::error!("StepOut failed: {}", e),
+                    }
+                    Some(ResponseBody::StepOut)
+                }
                 _ => None,
             };
 
@@ -319,22 +602,49 @@ impl DapServer {
                     type_: "response".to_string(),
                     request_seq: request.seq,
                     success: true,
-                    command: command_name(&request.command).to_string(),
                     message: None,
-                    body: Some(body),
+                    body,
+                };
+                write_message(&mut output, &response)?;
+            }
+
+            if let Some(message) = failure {
+                let response = DapErrorResponse {
+                    seq: self.seq.fetch_add(1, Ordering::SeqCst),
+                    type_: "response".to_string(),
+                    request_seq: request.seq,
+                    success: false,
+                    command: request_value
+                        .get("command")
+                        .and_then(|c| c.as_str())
+                        .unwrap_or_default()
+                        .to_string(),
+                    message,
                 };
+                write_message(&mut output, &response)?;
+            }
+
+            // Forward UART output produced while handling the request ahead
+            // of any stop, so the console reads in execution order.
+            let uart = self.adapter.take_uart_output();
+            if !uart.is_empty() {
+                events.insert(0, output_event(&uart));
+            }
 
-                let resp_json = serde_json::to_string(&response)?;
-                write!(
-                    output,
-                    "Content-Length: {}\r\n\r\n{}",
-                    resp_json.len(),
-                    resp_json
-                )?;
-                output.flush()?;
+            for event in events {
+                self.send_event(&mut output, event)?;
             }
         }
     }
+
+    fn send_event<W: Write>(&self, output: &mut W, event: Event) -> Result<()> {
+        let event = DapEvent {
+            seq: self.seq.fetch_add(1, Ordering::SeqCst),
+            type_: "event".to_string(),
+            event,
+        };
+        write_message(output, &event)
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,102 @@
+// LabWired - Firmware Simulation Platform
+// Copyright (C) 2026 Andrii Shylenko
+//
+// This software is released under the MIT License.
+// See the LICENSE file in the project root for full license information.
+
+mod common;
+
+use serde_json::{json, Value};
+
+// `adds r0, #1` in tests/fixtures/dap-count-thumbv7m.s
+const LINE_COUNT: i64 = 21;
+
+/// Break on the counting line with `breakpoint` settings, continue
+/// `continues` times and evaluate R0 after each stop.
+fn run(breakpoint: Value, continues: usize) -> Vec<Value> {
+    let mut requests = vec![
+        json!({"command": "initialize", "arguments": {"adapterID": "labwired"}}),
+        json!({"command": "launch", "arguments": {"program": common::count_fixture()}}),
+        json!({"command": "setBreakpoints", "arguments": {
+            "source": {"path": "dap-count-thumbv7m.s"},
+            "breakpoints": [breakpoint],
+        }}),
+        json!({"command": "configurationDone"}),
+    ];
+    for _ in 0..continues {
+        requests.push(json!({"command": "continue", "arguments": {"threadId": 1}}));
+        requests.push(json!({"command": "evaluate", "arguments": {"expression": "$r0"}}));
+    }
+    common::run_session(requests)
+}
+
+/// (stop reason, R0) after each continue.
+fn stops(messages: &[Value]) -> Vec<(String, String)> {
+    let reasons = messages
+        .iter()
+        .filter(|m| m["event"] == "stopped")
+        .map(|m| m["body"]["reason"].as_str().unwrap().to_string());
+    let values = messages
+        .iter()
+        .filter(|m| m["command"] == "evaluate")
+        .map(|m| m["body"]["result"].as_str().unwrap().to_string());
+    reasons.zip(values).collect()
+}
+
+#[test]
+fn test_conditional_breakpoint_stops_only_when_condition_holds() {
+    let messages = run(json!({"line": LINE_COUNT, "condition": "$r0 == 5"}), 1);
+    assert_eq!(
+        stops(&messages),
+        vec![("breakpoint".to_string(), "0x5".to_string())]
+    );
+}
+
+#[test]
+fn test_unconditional_breakpoint_stops_every_iteration() {
+    let messages = run(json!({"line": LINE_COUNT}), 3);
+    assert_eq!(
+        stops(&messages),
+        vec![
+            ("breakpoint".to_string(), "0x0".to_string()),
+            ("breakpoint".to_string(), "0x1".to_string()),
+            ("breakpoint".to_string(), "0x2".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_hit_condition_counts_hits() {
+    let messages = run(json!({"line": LINE_COUNT, "hitCondition": "% 3"}), 2);
+    assert_eq!(
+        stops(&messages),
+        vec![
+            ("breakpoint".to_string(), "0x2".to_string()),
+            ("breakpoint".to_string(), "0x5".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_function_breakpoint_stops_at_symbol() {
+    let mut requests = common::launch_requests();
+    requests.extend([
+        json!({"command": "setFunctionBreakpoints", "arguments": {
+            "breakpoints": [{"name": "helper"}, {"name": "no_such_function"}],
+        }}),
+        json!({"command": "configurationDone"}),
+        json!({"command": "continue", "arguments": {"threadId": 1}}),
+        json!({"command": "stackTrace", "arguments": {"threadId": 1}}),
+    ]);
+    let messages = common::run_session(requests);
+
+    let breakpoints = &common::response(&messages, 3)["body"]["breakpoints"];
+    assert_eq!(breakpoints[0]["verified"], true);
+    assert_eq!(breakpoints[1]["verified"], false);
+
+    let stopped = messages.iter().find(|m| m["event"] == "stopped").unwrap();
+    assert_eq!(stopped["body"]["reason"], "breakpoint");
+    let trace = common::response(&messages, 6);
+    // First instruction of `helper` in tests/fixtures/dap-step-thumbv7m.s
+    assert_eq!(trace["body"]["stackFrames"][0]["line"], 26);
+}
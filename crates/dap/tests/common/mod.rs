@@ -0,0 +1,81 @@
+// LabWired - Firmware Simulation Platform
+// Copyright (C) 2026 Andrii Shylenko
+//
+// This software is released under the MIT License.
+// See the LICENSE file in the project root for full license information.
+
+//! Helpers shared by the DAP integration tests.
+
+#![allow(dead_code)]
+
+use labwired_dap::server::DapServer;
+use serde_json::{json, Value};
+use std::io::Cursor;
+
+fn fixture_path(name: &str) -> String {
+    std::fs::canonicalize(format!("../../tests/fixtures/{}", name))
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .to_string()
+}
+
+/// Absolute path of the stepping fixture (tests/fixtures/dap-step-thumbv7m.s).
+pub fn fixture() -> String {
+    fixture_path("dap-step-thumbv7m.elf")
+}
+
+/// Absolute path of the counting-loop fixture (tests/fixtures/dap-count-thumbv7m.s).
+pub fn count_fixture() -> String {
+    fixture_path("dap-count-thumbv7m.elf")
+}
+
+/// Absolute path of the firmware that prints "OK" on the UART.
+pub fn uart_fixture() -> String {
+    fixture_path("uart-ok-thumbv7m.elf")
+}
+
+/// `initialize` + `launch` of the stepping fixture.
+pub fn launch_requests() -> Vec<Value> {
+    vec![
+        json!({"command": "initialize", "arguments": {"adapterID": "labwired"}}),
+        json!({"command": "launch", "arguments": {"program": fixture()}}),
+    ]
+}
+
+/// Run `requests` (sequence numbers are filled in, a `disconnect` is appended)
+/// through a fresh server and return every message it sent.
+pub fn run_session(requests: Vec<Value>) -> Vec<Value> {
+    let mut input = Vec::new();
+    let disconnect = json!({"command": "disconnect", "arguments": {}});
+    for (seq, mut request) in requests.into_iter().chain([disconnect]).enumerate() {
+        request["seq"] = json!(seq + 1);
+        request["type"] = json!("request");
+        let body = request.to_string();
+        input.extend_from_slice(
+            format!("Content-Length: {}\r\n\r\n{}", body.len(), body).as_bytes(),
+        );
+    }
+
+    let mut output = Vec::new();
+    DapServer::new()
+        .run(Cursor::new(input), &mut output)
+        .unwrap();
+
+    let text = String::from_utf8(output).unwrap();
+    text.split("Content-Length: ")
+        .filter(|chunk| !chunk.is_empty())
+        .map(|chunk| {
+            let (_, body) = chunk.split_once("\r\n\r\n").unwrap();
+            serde_json::from_str(body).unwrap()
+        })
+        .collect()
+}
+
+/// Response to the request with sequence number `seq`.
+pub fn response(messages: &[Value], seq: i64) -> &Value {
+    messages
+        .iter()
+        .find(|m| m["type"] == "response" && m["request_seq"] == seq)
+        .unwrap_or_else(|| panic!("no response to request {}", seq))
+}
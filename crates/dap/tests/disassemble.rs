@@ -0,0 +1,49 @@
+// LabWired - Firmware Simulation Platform
+// Copyright (C) 2026 Andrii Shylenko
+//
+// This software is released under the MIT License.
+// See the LICENSE file in the project root for full license information.
+
+mod common;
+
+use serde_json::json;
+
+#[test]
+fn test_disassemble_fixture_reset_code() {
+    let mut requests = common::launch_requests();
+    requests.push(json!({"command": "disassemble", "arguments": {
+        "memoryReference": "0x40",
+        "instructionCount": 8,
+    }}));
+    let messages = common::run_session(requests);
+    let response = common::response(&messages, 3);
+    assert_eq!(response["success"], true);
+
+    let lines: Vec<(&str, &str)> = response["body"]["instructions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|i| {
+            (
+                i["address"].as_str().unwrap(),
+                i["instruction"].as_str().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        lines,
+        vec![
+            ("0x40", "movs r0, #1"),
+            ("0x42", "bl #6"),
+            ("0x46", "movs r1, #2"),
+            ("0x48", "movs r2, #3"),
+            ("0x4a", "b #-4"),
+            ("0x4c", "push {lr}"),
+            ("0x4e", "bl #4"),
+            ("0x52", "movs r3, #4"),
+        ]
+    );
+
+    let bl = &response["body"]["instructions"][1];
+    assert_eq!(bl["instructionBytes"], "00f003f8");
+}
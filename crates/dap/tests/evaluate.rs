@@ -0,0 +1,62 @@
+// LabWired - Firmware Simulation Platform
+// Copyright (C) 2026 Andrii Shylenko
+//
+// This software is released under the MIT License.
+// See the LICENSE file in the project root for full license information.
+
+mod common;
+
+use serde_json::{json, Value};
+
+/// Responses to evaluating each expression after launching the fixture,
+/// writing 0xDEADBEEF to 0x2000_0010 and setting R3 to 0x77.
+fn evaluate(expressions: &[&str]) -> Vec<Value> {
+    let mut requests = common::launch_requests();
+    requests.push(json!({"command": "writeMemory", "arguments": {
+        "memoryReference": "0x20000010",
+        "data": "776t3g==",
+    }}));
+    requests.push(json!({"command": "setVariable", "arguments": {
+        "variablesReference": 1,
+        "name": "R3",
+        "value": "0x77",
+    }}));
+    for expression in expressions {
+        requests.push(json!({"command": "evaluate", "arguments": {
+            "expression": expression,
+            "context": "watch",
+        }}));
+    }
+    let messages = common::run_session(requests);
+    (0..expressions.len())
+        .map(|i| common::response(&messages, i as i64 + 5).clone())
+        .collect()
+}
+
+#[test]
+fn test_evaluate_register() {
+    let results = evaluate(&["$r3", "$SP"]);
+    assert_eq!(results[0]["body"]["result"], "0x77");
+    // Initial SP from the fixture's vector table
+    assert_eq!(results[1]["body"]["result"], "0x20001000");
+}
+
+#[test]
+fn test_evaluate_memory_dereference() {
+    let results = evaluate(&["*(u32*)0x2000_0010", "*(u8 *)0x20000010", "0x1234"]);
+    assert_eq!(results[0]["body"]["result"], "0xdeadbeef");
+    assert_eq!(results[0]["body"]["memoryReference"], "0x20000010");
+    assert_eq!(results[1]["body"]["result"], "0xef");
+    assert_eq!(results[1]["body"]["type"], "uint8");
+    assert_eq!(results[2]["body"]["result"], "0x1234");
+}
+
+#[test]
+fn test_evaluate_invalid_expression_fails() {
+    let results = evaluate(&["$r99", "foo + 1"]);
+    for result in &results {
+        assert_eq!(result["success"], false);
+        assert_eq!(result["command"], "evaluate");
+    }
+    assert!(results[1]["message"].as_str().unwrap().contains("foo + 1"));
+}
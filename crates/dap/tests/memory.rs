@@ -0,0 +1,58 @@
+// LabWired - Firmware Simulation Platform
+// Copyright (C) 2026 Andrii Shylenko
+//
+// This software is released under the MIT License.
+// See the LICENSE file in the project root for full license information.
+
+mod common;
+
+use serde_json::json;
+
+#[test]
+fn test_write_memory_then_read_back() {
+    let mut requests = common::launch_requests();
+    requests.extend([
+        // 0xDEADBEEF
+        json!({"command": "writeMemory", "arguments": {
+            "memoryReference": "0x20000000",
+            "offset": 4,
+            "data": "3q2+7w==",
+        }}),
+        json!({"command": "readMemory", "arguments": {
+            "memoryReference": "0x20000004",
+            "count": 4,
+        }}),
+    ]);
+    let messages = common::run_session(requests);
+
+    let write = common::response(&messages, 3);
+    assert_eq!(write["command"], "writeMemory");
+    assert_eq!(write["body"]["bytesWritten"], 4);
+
+    let read = common::response(&messages, 4);
+    assert_eq!(read["body"]["address"], "0x20000004");
+    assert_eq!(read["body"]["data"], "3q2+7w==");
+}
+
+#[test]
+fn test_unmapped_memory_access_fails_the_request() {
+    let mut requests = common::launch_requests();
+    requests.extend([
+        json!({"command": "writeMemory", "arguments": {
+            "memoryReference": "0x60000000",
+            "data": "3q2+7w==",
+        }}),
+        json!({"command": "readMemory", "arguments": {
+            "memoryReference": "0x60000000",
+            "count": 4,
+        }}),
+    ]);
+    let messages = common::run_session(requests);
+
+    for (seq, command) in [(3, "writeMemory"), (4, "readMemory")] {
+        let response = common::response(&messages, seq);
+        assert_eq!(response["command"], command);
+        assert_eq!(response["success"], false);
+        assert!(!response["message"].as_str().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,34 @@
+// LabWired - Firmware Simulation Platform
+// Copyright (C) 2026 Andrii Shylenko
+//
+// This software is released under the MIT License.
+// See the LICENSE file in the project root for full license information.
+
+mod common;
+
+use serde_json::json;
+
+#[test]
+fn test_uart_output_forwarded_as_output_events() {
+    let messages = common::run_session(vec![
+        json!({"command": "initialize", "arguments": {"adapterID": "labwired"}}),
+        json!({"command": "launch", "arguments": {"program": common::uart_fixture()}}),
+        json!({"command": "configurationDone"}),
+        json!({"command": "continue", "arguments": {"threadId": 1}}),
+    ]);
+
+    let output: String = messages
+        .iter()
+        .filter(|m| m["type"] == "event" && m["event"] == "output")
+        .inspect(|m| assert_eq!(m["body"]["category"], "stdout"))
+        .map(|m| m["body"]["output"].as_str().unwrap())
+        .collect();
+    assert!(output.contains("OK"), "output events: {:?}", output);
+
+    // The event follows the response to the request that produced it
+    let continue_at = messages
+        .iter()
+        .position(|m| m["command"] == "continue")
+        .unwrap();
+    assert_eq!(messages[continue_at + 1]["event"], "output");
+}
@@ -0,0 +1,97 @@
+// LabWired - Firmware Simulation Platform
+// Copyright (C) 2026 Andrii Shylenko
+//
+// This software is released under the MIT License.
+// See the LICENSE file in the project root for full license information.
+
+mod common;
+
+use serde_json::{json, Value};
+
+// Source lines in tests/fixtures/dap-step-thumbv7m.s
+const LINE_BL_HELPER: i64 = 20;
+const LINE_AFTER_BL: i64 = 21;
+const LINE_HELPER_ENTRY: i64 = 26;
+const LINE_BL_LEAF: i64 = 27;
+const LINE_AFTER_BL_LEAF: i64 = 28;
+
+/// Launch the fixture, break on the `bl helper` line, continue to it, then
+/// issue `steps` followed by a stack trace after each.
+fn run(steps: &[&str]) -> Vec<Value> {
+    let mut requests = common::launch_requests();
+    requests.extend([
+        json!({"command": "setBreakpoints", "arguments": {
+            "source": {"path": "dap-step-thumbv7m.s"},
+            "breakpoints": [{"line": LINE_BL_HELPER}],
+        }}),
+        json!({"command": "configurationDone"}),
+        json!({"command": "continue", "arguments": {"threadId": 1}}),
+    ]);
+    for step in steps {
+        requests.push(json!({"command": step, "arguments": {"threadId": 1}}));
+        requests.push(json!({"command": "stackTrace", "arguments": {"threadId": 1}}));
+    }
+    common::run_session(requests)
+}
+
+/// Stopped-event reason and top frame line following each stepping request.
+fn step_results(messages: &[Value], command: &str) -> Vec<(String, i64)> {
+    let mut results = Vec::new();
+    for (i, msg) in messages.iter().enumerate() {
+        if msg["type"] != "response" || msg["command"] != command {
+            continue;
+        }
+        let stopped = &messages[i + 1];
+        assert_eq!(
+            stopped["event"], "stopped",
+            "after {}: {}",
+            command, stopped
+        );
+        let trace = messages[i + 2..]
+            .iter()
+            .find(|m| m["command"] == "stackTrace")
+            .unwrap();
+        results.push((
+            stopped["body"]["reason"].as_str().unwrap().to_string(),
+            trace["body"]["stackFrames"][0]["line"].as_i64().unwrap(),
+        ));
+    }
+    results
+}
+
+#[test]
+fn test_next_steps_over_bl_to_following_line() {
+    let messages = run(&["next"]);
+    let results = step_results(&messages, "next");
+    assert_eq!(results, vec![("step".to_string(), LINE_AFTER_BL)]);
+
+    let trace = messages
+        .iter()
+        .find(|m| m["command"] == "stackTrace")
+        .unwrap();
+    assert_eq!(
+        trace["body"]["stackFrames"][0]["instructionPointerReference"],
+        "0x46"
+    );
+}
+
+#[test]
+fn test_step_in_then_step_out_returns_to_caller() {
+    let messages = run(&["stepIn", "next", "next", "stepOut"]);
+
+    assert_eq!(
+        step_results(&messages, "stepIn"),
+        vec![("step".to_string(), LINE_HELPER_ENTRY)]
+    );
+    assert_eq!(
+        step_results(&messages, "next"),
+        vec![
+            ("step".to_string(), LINE_BL_LEAF),
+            ("step".to_string(), LINE_AFTER_BL_LEAF),
+        ]
+    );
+    assert_eq!(
+        step_results(&messages, "stepOut"),
+        vec![("step".to_string(), LINE_AFTER_BL)]
+    );
+}
@@ -0,0 +1,72 @@
+// LabWired - Firmware Simulation Platform
+// Copyright (C) 2026 Andrii Shylenko
+//
+// This software is released under the MIT License.
+// See the LICENSE file in the project root for full license information.
+
+use labwired_dap::server::DapServer;
+use serde_json::{json, Value};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+
+fn send(stream: &mut TcpStream, seq: i64, command: &str) {
+    let body = json!({
+        "seq": seq,
+        "type": "request",
+        "command": command,
+        "arguments": {"adapterID": "labwired"},
+    })
+    .to_string();
+    write!(stream, "Content-Length: {}\r\n\r\n{}", body.len(), body).unwrap();
+}
+
+fn receive(reader: &mut BufReader<TcpStream>) -> Value {
+    let mut content_length = 0;
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        let line = line.trim();
+        if line.is_empty() {
+            break;
+        }
+        if let Some(len) = line.strip_prefix("Content-Length: ") {
+            content_length = len.parse().unwrap();
+        }
+    }
+    let mut body = vec![0u8; content_length];
+    reader.read_exact(&mut body).unwrap();
+    serde_json::from_slice(&body).unwrap()
+}
+
+#[test]
+fn test_initialize_over_tcp() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = std::thread::spawn(move || DapServer::new().serve_listener(&listener));
+
+    let mut stream = TcpStream::connect(addr).unwrap();
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    send(&mut stream, 1, "initialize");
+
+    let response = receive(&mut reader);
+    assert_eq!(response["type"], "response");
+    assert_eq!(response["command"], "initialize");
+    assert_eq!(response["success"], true);
+
+    // Closing the connection ends the session
+    drop(reader);
+    drop(stream);
+    server.join().unwrap().unwrap();
+}
+
+#[test]
+fn test_adapter_rejects_unknown_arguments() {
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_labwired-dap"))
+        .args(["--prot", "4711"])
+        .stdin(std::process::Stdio::null())
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("--prot"), "{}", stderr);
+}
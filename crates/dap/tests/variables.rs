@@ -0,0 +1,103 @@
+// LabWired - Firmware Simulation Platform
+// Copyright (C) 2026 Andrii Shylenko
+//
+// This software is released under the MIT License.
+// See the LICENSE file in the project root for full license information.
+
+mod common;
+
+use serde_json::json;
+
+#[test]
+fn test_set_variable_writes_register() {
+    let mut requests = common::launch_requests();
+    requests.extend([
+        json!({"command": "setVariable", "arguments": {
+            "variablesReference": 1,
+            "name": "R0",
+            "value": "0xCAFE",
+        }}),
+        json!({"command": "variables", "arguments": {"variablesReference": 1}}),
+    ]);
+    let messages = common::run_session(requests);
+
+    let set = common::response(&messages, 3);
+    assert_eq!(set["success"], true);
+    assert_eq!(set["body"]["value"], "0xcafe");
+
+    let variables = common::response(&messages, 4);
+    let r0 = variables["body"]["variables"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|v| v["name"] == "R0")
+        .unwrap();
+    assert_eq!(r0["value"], "0xcafe");
+}
+
+#[test]
+fn test_set_variable_rejects_unknown_register() {
+    let mut requests = common::launch_requests();
+    requests.push(json!({"command": "setVariable", "arguments": {
+        "variablesReference": 1,
+        "name": "R42",
+        "value": "1",
+    }}));
+    let messages = common::run_session(requests);
+
+    let set = common::response(&messages, 3);
+    assert_eq!(set["success"], false);
+    assert_eq!(set["command"], "setVariable");
+    assert!(set["message"].as_str().unwrap().contains("R42"));
+}
+
+#[test]
+fn test_peripherals_scope_lists_uart_registers() {
+    let mut requests = common::launch_requests();
+    requests.extend([
+        json!({"command": "scopes", "arguments": {"frameId": 0}}),
+        json!({"command": "variables", "arguments": {"variablesReference": 2}}),
+    ]);
+    let messages = common::run_session(requests);
+
+    let scopes = common::response(&messages, 3)["body"]["scopes"]
+        .as_array()
+        .unwrap()
+        .clone();
+    let names: Vec<&str> = scopes.iter().map(|s| s["name"].as_str().unwrap()).collect();
+    assert_eq!(names, ["Registers", "Peripherals"]);
+    assert_eq!(scopes[1]["variablesReference"], 2);
+
+    let uart = common::response(&messages, 4)["body"]["variables"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|v| v["name"] == "uart1")
+        .cloned()
+        .expect("uart1 in Peripherals scope");
+    assert_eq!(uart["value"], "@ 0x4000c000");
+    let reference = uart["variablesReference"].as_i64().unwrap();
+    assert!(reference > 0);
+
+    let mut requests = common::launch_requests();
+    requests.push(json!({"command": "variables", "arguments": {"variablesReference": reference}}));
+    let messages = common::run_session(requests);
+    let registers: Vec<(String, String)> = common::response(&messages, 3)["body"]["variables"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|v| {
+            (
+                v["name"].as_str().unwrap().to_string(),
+                v["value"].as_str().unwrap().to_string(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        registers,
+        [
+            ("SR".to_string(), "0x000000c0".to_string()),
+            ("DR".to_string(), "0x00000000".to_string()),
+        ]
+    );
+}
@@ -0,0 +1,138 @@
+// LabWired - Firmware Simulation Platform
+// Copyright (C) 2026 Andrii Shylenko
+//
+// This software is released under the MIT License.
+// See the LICENSE file in the project root for full license information.
+
+//! Cortex-M (ARMv7-M, Thumb-only) architecture description for gdbstub.
+//!
+//! `gdbstub_arch` only ships classic ARM profiles, whose register file has a
+//! CPSR and FPA registers that M-profile cores do not have.
+
+use core::num::NonZeroUsize;
+use gdbstub::arch::{Arch, RegId, Registers};
+use gdbstub_arch::arm::ArmBreakpointKind;
+
+/// xPSR Thumb state bit. Always set on Cortex-M.
+pub const XPSR_T: u32 = 1 << 24;
+
+/// Implements `Arch` for Cortex-M (thumbv7m) cores.
+pub enum CortexMArch {}
+
+impl Arch for CortexMArch {
+    type Usize = u32;
+    type Registers = CortexMRegs;
+    type RegId = CortexMRegId;
+    type BreakpointKind = ArmBreakpointKind;
+
+    fn target_description_xml() -> Option<&'static str> {
+        Some(TARGET_XML)
+    }
+}
+
+const TARGET_XML: &str = r#"<?xml version="1.0"?>
+<!DOCTYPE target SYSTEM "gdb-target.dtd">
+<target version="1.0">
+  <architecture>arm</architecture>
+  <feature name="org.gnu.gdb.arm.m-profile">
+    <reg name="r0" bitsize="32"/>
+    <reg name="r1" bitsize="32"/>
+    <reg name="r2" bitsize="32"/>
+    <reg name="r3" bitsize="32"/>
+    <reg name="r4" bitsize="32"/>
+    <reg name="r5" bitsize="32"/>
+    <reg name="r6" bitsize="32"/>
+    <reg name="r7" bitsize="32"/>
+    <reg name="r8" bitsize="32"/>
+    <reg name="r9" bitsize="32"/>
+    <reg name="r10" bitsize="32"/>
+    <reg name="r11" bitsize="32"/>
+    <reg name="r12" bitsize="32"/>
+    <reg name="sp" bitsize="32" type="data_ptr"/>
+    <reg name="lr" bitsize="32"/>
+    <reg name="pc" bitsize="32" type="code_ptr"/>
+    <reg name="xpsr" bitsize="32"/>
+  </feature>
+</target>"#;
+
+/// Cortex-M core registers, in `g` packet order.
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct CortexMRegs {
+    /// General purpose registers (R0-R12)
+    pub r: [u32; 13],
+    /// Stack Pointer (R13)
+    pub sp: u32,
+    /// Link Register (R14)
+    pub lr: u32,
+    /// Program Counter (R15)
+    pub pc: u32,
+    /// Program Status Register
+    pub xpsr: u32,
+}
+
+impl Registers for CortexMRegs {
+    type ProgramCounter = u32;
+
+    fn pc(&self) -> Self::ProgramCounter {
+        self.pc
+    }
+
+    fn gdb_serialize(&self, mut write_byte: impl FnMut(Option<u8>)) {
+        let regs = self
+            .r
+            .iter()
+            .chain([&self.sp, &self.lr, &self.pc, &self.xpsr]);
+        for reg in regs {
+            for b in reg.to_le_bytes() {
+                write_byte(Some(b));
+            }
+        }
+    }
+
+    fn gdb_deserialize(&mut self, bytes: &[u8]) -> Result<(), ()> {
+        if bytes.len() != 17 * 4 {
+            return Err(());
+        }
+
+        let mut words = bytes
+            .chunks_exact(4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]));
+        for reg in self.r.iter_mut() {
+            *reg = words.next().ok_or(())?;
+        }
+        self.sp = words.next().ok_or(())?;
+        self.lr = words.next().ok_or(())?;
+        self.pc = words.next().ok_or(())?;
+        self.xpsr = words.next().ok_or(())?;
+        Ok(())
+    }
+}
+
+/// Cortex-M core register identifier, numbered as in the target description.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CortexMRegId {
+    /// General purpose registers (R0-R12)
+    Gpr(u8),
+    /// Stack Pointer (R13)
+    Sp,
+    /// Link Register (R14)
+    Lr,
+    /// Program Counter (R15)
+    Pc,
+    /// Program Status Register
+    Xpsr,
+}
+
+impl RegId for CortexMRegId {
+    fn from_raw_id(id: usize) -> Option<(Self, Option<NonZeroUsize>)> {
+        let reg = match id {
+            0..=12 => Self::Gpr(id as u8),
+            13 => Self::Sp,
+            14 => Self::Lr,
+            15 => Self::Pc,
+            16 => Self::Xpsr,
+            _ => return None,
+        };
+        Some((reg, Some(NonZeroUsize::new(4)?)))
+    }
+}
@@ -0,0 +1,84 @@
+// LabWired - Firmware Simulation Platform
+// Copyright (C) 2026 Andrii Shylenko
+//
+// This software is released under the MIT License.
+// See the LICENSE file in the project root for full license information.
+
+use gdbstub::conn::{Connection, ConnectionExt};
+use std::io::{self, Read, Write};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+
+/// A GDB connection over a plain reader/writer pair such as stdin/stdout.
+///
+/// The input is read on a background thread, so `peek` (polled while the
+/// target runs, to catch Ctrl-C) never blocks. End of input reads as
+/// `UnexpectedEof`, which ends the session.
+pub struct StreamConnection<W: Write> {
+    input: Receiver<u8>,
+    peeked: Option<u8>,
+    output: W,
+}
+
+impl<W: Write> StreamConnection<W> {
+    pub fn new<R: Read + Send + 'static>(mut input: R, output: W) -> Self {
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            let mut buf = [0u8; 256];
+            loop {
+                match input.read(&mut buf) {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => {
+                        if buf[..n].iter().any(|&byte| tx.send(byte).is_err()) {
+                            break;
+                        }
+                    }
+                }
+            }
+        });
+        Self {
+            input: rx,
+            peeked: None,
+            output,
+        }
+    }
+}
+
+fn eof() -> io::Error {
+    io::Error::new(io::ErrorKind::UnexpectedEof, "GDB input closed")
+}
+
+impl<W: Write> Connection for StreamConnection<W> {
+    type Error = io::Error;
+
+    fn write(&mut self, byte: u8) -> Result<(), Self::Error> {
+        self.output.write_all(&[byte])
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), Self::Error> {
+        self.output.write_all(buf)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.output.flush()
+    }
+}
+
+impl<W: Write> ConnectionExt for StreamConnection<W> {
+    fn read(&mut self) -> Result<u8, Self::Error> {
+        match self.peeked.take() {
+            Some(byte) => Ok(byte),
+            None => self.input.recv().map_err(|_| eof()),
+        }
+    }
+
+    fn peek(&mut self) -> Result<Option<u8>, Self::Error> {
+        if self.peeked.is_none() {
+            self.peeked = match self.input.try_recv() {
+                Ok(byte) => Some(byte),
+                Err(TryRecvError::Empty) => None,
+                Err(TryRecvError::Disconnected) => return Err(eof()),
+            };
+        }
+        Ok(self.peeked)
+    }
+}
@@ -4,30 +4,91 @@
 // This software is released under the MIT License.
 // See the LICENSE file in the project root for full license information.
 
+pub mod arch;
+pub mod conn;
+
+use arch::{CortexMArch, CortexMRegs, XPSR_T};
+use conn::StreamConnection;
 use core::convert::Infallible;
+use gdbstub::conn::ConnectionExt;
 use gdbstub::stub::{BaseStopReason, GdbStub};
 use gdbstub::target::ext::base::singlethread::{
     SingleThreadBase, SingleThreadResume, SingleThreadSingleStep,
 };
 use gdbstub::target::ext::base::BaseOps;
 use gdbstub::target::{Target, TargetError, TargetResult};
+use labwired_core::bus::{MemoryRegionKind, SystemBus};
 use labwired_core::cpu::{CortexM, RiscV};
-use labwired_core::{Cpu, DebugControl, Machine, StopReason};
+use labwired_core::{Cpu, DebugControl, Machine, StopReason, WatchKind};
+use labwired_loader::SymbolProvider;
+use std::collections::HashSet;
+use std::io::{Read, Write};
 use std::marker::PhantomData;
 use std::net::{TcpListener, TcpStream};
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::Path;
+
+/// Instructions executed per `run` call while continuing, between polls of the
+/// GDB connection for an interrupt.
+const CONTINUE_CHUNK: u32 = 1000;
+
+/// Execution requested by the last `resume`/`step` from GDB.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExecMode {
+    Continue,
+    Step,
+}
 
 pub struct LabwiredTarget<C: Cpu> {
     pub machine: Machine<C>,
+    pub exec_mode: ExecMode,
+    /// Breakpoints set via `hbreak`. They live in `machine.breakpoints` like
+    /// software ones; this set only decides which stop reason GDB is sent.
+    pub hw_breakpoints: HashSet<u32>,
+    /// Firmware symbols for `monitor break SYMBOL`.
+    pub symbols: Option<SymbolProvider>,
 }
 
 impl<C: Cpu> LabwiredTarget<C> {
     pub fn new(machine: Machine<C>) -> Self {
-        Self { machine }
+        Self {
+            machine,
+            exec_mode: ExecMode::Continue,
+            hw_breakpoints: HashSet::new(),
+            symbols: None,
+        }
+    }
+
+    fn breakpoint_stop(&self, pc: u32) -> BaseStopReason<(), u32> {
+        if self.hw_breakpoints.contains(&pc) {
+            BaseStopReason::HwBreak(())
+        } else {
+            BaseStopReason::SwBreak(())
+        }
+    }
+}
+
+fn to_gdb_watch_kind(kind: WatchKind) -> gdbstub::target::ext::breakpoints::WatchKind {
+    use gdbstub::target::ext::breakpoints::WatchKind as GdbWatchKind;
+    match kind {
+        WatchKind::Write => GdbWatchKind::Write,
+        WatchKind::Read => GdbWatchKind::Read,
+        WatchKind::ReadWrite => GdbWatchKind::ReadWrite,
+    }
+}
+
+fn from_gdb_watch_kind(kind: gdbstub::target::ext::breakpoints::WatchKind) -> WatchKind {
+    use gdbstub::target::ext::breakpoints::WatchKind as GdbWatchKind;
+    match kind {
+        GdbWatchKind::Write => WatchKind::Write,
+        GdbWatchKind::Read => WatchKind::Read,
+        GdbWatchKind::ReadWrite => WatchKind::ReadWrite,
     }
 }
 
 impl Target for LabwiredTarget<CortexM> {
-    type Arch = gdbstub_arch::arm::Armv4t;
+    type Arch = CortexMArch;
     type Error = Infallible;
 
     fn base_ops(&mut self) -> BaseOps<'_, Self::Arch, Self::Error> {
@@ -39,50 +100,60 @@ impl Target for LabwiredTarget<CortexM> {
     ) -> Option<gdbstub::target::ext::breakpoints::BreakpointsOps<'_, Self>> {
         Some(self)
     }
+
+    fn support_memory_map(
+        &mut self,
+    ) -> Option<gdbstub::target::ext::memory_map::MemoryMapOps<'_, Self>> {
+        Some(self)
+    }
+
+    fn support_flash_operations(
+        &mut self,
+    ) -> Option<gdbstub::target::ext::flash::FlashOps<'_, Self>> {
+        Some(self)
+    }
+
+    fn support_monitor_cmd(
+        &mut self,
+    ) -> Option<gdbstub::target::ext::monitor_cmd::MonitorCmdOps<'_, Self>> {
+        Some(self)
+    }
 }
 
 impl SingleThreadBase for LabwiredTarget<CortexM> {
-    fn read_registers(
-        &mut self,
-        regs: &mut gdbstub_arch::arm::reg::ArmCoreRegs,
-    ) -> TargetResult<(), Self> {
-        for i in 0..13 {
-            regs.r[i] = self.machine.read_core_reg(i as u8);
-        }
-        regs.sp = self.machine.read_core_reg(13);
-        regs.lr = self.machine.read_core_reg(14);
-        regs.pc = self.machine.read_core_reg(15);
-        regs.cpsr = self.machine.read_core_reg(16); // xPSR
+    fn read_registers(&mut self, regs: &mut CortexMRegs) -> TargetResult<(), Self> {
+        let r = self.machine.cpu.registers();
+        regs.r.copy_from_slice(&r[..13]);
+        regs.sp = r[13];
+        regs.lr = r[14];
+        // The simulator may carry the Thumb bit in PC; GDB expects the instruction address
+        regs.pc = r[15] & !1;
+        // Cortex-M only executes Thumb code, so T is always set
+        regs.xpsr = r[16] | XPSR_T;
         Ok(())
     }
 
-    fn write_registers(
-        &mut self,
-        regs: &gdbstub_arch::arm::reg::ArmCoreRegs,
-    ) -> TargetResult<(), Self> {
-        for i in 0..13 {
-            self.machine.write_core_reg(i as u8, regs.r[i]);
-        }
-        self.machine.write_core_reg(13, regs.sp);
-        self.machine.write_core_reg(14, regs.lr);
-        self.machine.write_core_reg(15, regs.pc);
-        self.machine.write_core_reg(16, regs.cpsr);
+    fn write_registers(&mut self, regs: &CortexMRegs) -> TargetResult<(), Self> {
+        let mut r = [0; 17];
+        r[..13].copy_from_slice(&regs.r);
+        r[13] = regs.sp;
+        r[14] = regs.lr;
+        r[15] = regs.pc & !1;
+        r[16] = regs.xpsr;
+        self.machine.cpu.set_registers(&r);
         Ok(())
     }
 
     fn read_addrs(&mut self, start_addr: u32, data: &mut [u8]) -> TargetResult<usize, Self> {
-        let mem = self
-            .machine
-            .read_memory(start_addr, data.len())
+        self.machine
+            .read_memory_into(start_addr, data)
             .map_err(|_| TargetError::NonFatal)?;
-        let len = mem.len().min(data.len());
-        data[..len].copy_from_slice(&mem[..len]);
-        Ok(len)
+        Ok(data.len())
     }
 
     fn write_addrs(&mut self, start_addr: u32, data: &[u8]) -> TargetResult<(), Self> {
         self.machine
-            .write_memory(start_addr, data)
+            .write_memory_from(start_addr, data)
             .map_err(|_| TargetError::NonFatal)?;
         Ok(())
     }
@@ -107,6 +178,24 @@ impl Target for LabwiredTarget<RiscV> {
     ) -> Option<gdbstub::target::ext::breakpoints::BreakpointsOps<'_, Self>> {
         Some(self)
     }
+
+    fn support_memory_map(
+        &mut self,
+    ) -> Option<gdbstub::target::ext::memory_map::MemoryMapOps<'_, Self>> {
+        Some(self)
+    }
+
+    fn support_flash_operations(
+        &mut self,
+    ) -> Option<gdbstub::target::ext::flash::FlashOps<'_, Self>> {
+        Some(self)
+    }
+
+    fn support_monitor_cmd(
+        &mut self,
+    ) -> Option<gdbstub::target::ext::monitor_cmd::MonitorCmdOps<'_, Self>> {
+        Some(self)
+    }
 }
 
 impl SingleThreadBase for LabwiredTarget<RiscV> {
@@ -137,13 +226,10 @@ impl SingleThreadBase for LabwiredTarget<RiscV> {
         start_addr: <Self::Arch as gdbstub::arch::Arch>::Usize,
         data: &mut [u8],
     ) -> TargetResult<usize, Self> {
-        let mem = self
-            .machine
-            .read_memory(start_addr, data.len())
+        self.machine
+            .read_memory_into(start_addr, data)
             .map_err(|_| TargetError::NonFatal)?;
-        let len = mem.len().min(data.len());
-        data[..len].copy_from_slice(&mem[..len]);
-        Ok(len)
+        Ok(data.len())
     }
 
     fn write_addrs(
@@ -152,7 +238,7 @@ impl SingleThreadBase for LabwiredTarget<RiscV> {
         data: &[u8],
     ) -> TargetResult<(), Self> {
         self.machine
-            .write_memory(start_addr, data)
+            .write_memory_from(start_addr, data)
             .map_err(|_| TargetError::NonFatal)?;
         Ok(())
     }
@@ -169,6 +255,7 @@ where
     LabwiredTarget<C>: Target<Arch: gdbstub::arch::Arch<Usize = u32>>,
 {
     fn resume(&mut self, _signal: Option<gdbstub::common::Signal>) -> Result<(), Self::Error> {
+        self.exec_mode = ExecMode::Continue;
         Ok(())
     }
 
@@ -184,6 +271,7 @@ where
     LabwiredTarget<C>: Target<Arch: gdbstub::arch::Arch<Usize = u32>>,
 {
     fn step(&mut self, _signal: Option<gdbstub::common::Signal>) -> Result<(), Self::Error> {
+        self.exec_mode = ExecMode::Step;
         Ok(())
     }
 }
@@ -197,6 +285,18 @@ where
     ) -> Option<gdbstub::target::ext::breakpoints::SwBreakpointOps<'_, Self>> {
         Some(self)
     }
+
+    fn support_hw_breakpoint(
+        &mut self,
+    ) -> Option<gdbstub::target::ext::breakpoints::HwBreakpointOps<'_, Self>> {
+        Some(self)
+    }
+
+    fn support_hw_watchpoint(
+        &mut self,
+    ) -> Option<gdbstub::target::ext::breakpoints::HwWatchpointOps<'_, Self>> {
+        Some(self)
+    }
 }
 
 impl<C: Cpu> gdbstub::target::ext::breakpoints::SwBreakpoint for LabwiredTarget<C>
@@ -222,19 +322,246 @@ where
     }
 }
 
+impl<C: Cpu> gdbstub::target::ext::breakpoints::HwBreakpoint for LabwiredTarget<C>
+where
+    LabwiredTarget<C>: Target<Arch: gdbstub::arch::Arch<Usize = u32>>,
+{
+    fn add_hw_breakpoint(
+        &mut self,
+        addr: u32,
+        _kind: <Self::Arch as gdbstub::arch::Arch>::BreakpointKind,
+    ) -> TargetResult<bool, Self> {
+        self.machine.add_breakpoint(addr);
+        self.hw_breakpoints.insert(addr);
+        Ok(true)
+    }
+
+    fn remove_hw_breakpoint(
+        &mut self,
+        addr: u32,
+        _kind: <Self::Arch as gdbstub::arch::Arch>::BreakpointKind,
+    ) -> TargetResult<bool, Self> {
+        self.machine.remove_breakpoint(addr);
+        Ok(self.hw_breakpoints.remove(&addr))
+    }
+}
+
+impl<C: Cpu> gdbstub::target::ext::breakpoints::HwWatchpoint for LabwiredTarget<C>
+where
+    LabwiredTarget<C>: Target<Arch: gdbstub::arch::Arch<Usize = u32>>,
+{
+    fn add_hw_watchpoint(
+        &mut self,
+        addr: u32,
+        len: u32,
+        kind: gdbstub::target::ext::breakpoints::WatchKind,
+    ) -> TargetResult<bool, Self> {
+        self.machine
+            .add_watchpoint(addr, len, from_gdb_watch_kind(kind));
+        Ok(true)
+    }
+
+    fn remove_hw_watchpoint(
+        &mut self,
+        addr: u32,
+        len: u32,
+        kind: gdbstub::target::ext::breakpoints::WatchKind,
+    ) -> TargetResult<bool, Self> {
+        Ok(self
+            .machine
+            .remove_watchpoint(addr, len, from_gdb_watch_kind(kind)))
+    }
+}
+
+/// Erase granularity advertised for flash regions.
+const FLASH_BLOCK_SIZE: u64 = 0x400;
+
+/// GDB memory-map XML for the bus. The memory-map DTD only knows `ram`, `rom`
+/// and `flash`, so peripheral (MMIO) windows are reported as `ram`, tagged with
+/// a comment. Regions overlapping an earlier one are left out, since GDB rejects
+/// maps with overlaps.
+pub fn memory_map_xml(bus: &SystemBus) -> String {
+    let mut regions = bus.memory_regions();
+    regions.retain(|r| r.size > 0);
+    regions.sort_by_key(|r| r.base);
+
+    let mut xml = String::from(
+        r#"<?xml version="1.0"?>
+<!DOCTYPE memory-map PUBLIC "+//IDN gnu.org//DTD GDB Memory Map V1.0//EN" "http://sourceware.org/gdb/gdb-memory-map.dtd">
+<memory-map>
+"#,
+    );
+    let mut end = 0u64;
+    for region in regions {
+        if region.base < end {
+            tracing::debug!("Memory map: skipping overlapping region '{}'", region.name);
+            continue;
+        }
+        end = region.base + region.size;
+
+        let (start, length) = (region.base, region.size);
+        match region.kind {
+            MemoryRegionKind::Flash => xml.push_str(&format!(
+                "  <memory type=\"flash\" start=\"{:#x}\" length=\"{:#x}\">\n    <property name=\"blocksize\">{:#x}</property>\n  </memory>\n",
+                start, length, FLASH_BLOCK_SIZE
+            )),
+            MemoryRegionKind::Ram => xml.push_str(&format!(
+                "  <memory type=\"ram\" start=\"{:#x}\" length=\"{:#x}\"/>\n",
+                start, length
+            )),
+            MemoryRegionKind::Mmio => xml.push_str(&format!(
+                "  <!-- mmio: {} -->\n  <memory type=\"ram\" start=\"{:#x}\" length=\"{:#x}\"/>\n",
+                region.name, start, length
+            )),
+        }
+    }
+    xml.push_str("</memory-map>\n");
+    xml
+}
+
+impl<C: Cpu> gdbstub::target::ext::memory_map::MemoryMap for LabwiredTarget<C>
+where
+    LabwiredTarget<C>: Target<Arch: gdbstub::arch::Arch<Usize = u32>>,
+{
+    fn memory_map_xml(
+        &self,
+        offset: u64,
+        length: usize,
+        buf: &mut [u8],
+    ) -> TargetResult<usize, Self> {
+        let xml = memory_map_xml(&self.machine.bus);
+        let data = xml.as_bytes();
+        let start = (offset as usize).min(data.len());
+        let end = start.saturating_add(length).min(data.len());
+        let chunk = &data[start..end];
+        let len = chunk.len().min(buf.len());
+        buf[..len].copy_from_slice(&chunk[..len]);
+        Ok(len)
+    }
+}
+
+impl<C: Cpu> gdbstub::target::ext::flash::Flash for LabwiredTarget<C>
+where
+    LabwiredTarget<C>: Target<Arch: gdbstub::arch::Arch<Usize = u32>>,
+{
+    fn flash_erase(&mut self, start_addr: u32, length: u32) -> TargetResult<(), Self> {
+        self.machine
+            .write_memory(start_addr, &vec![0xFF; length as usize])
+            .map_err(|_| TargetError::NonFatal)
+    }
+
+    fn flash_write(&mut self, start_addr: u32, data: &[u8]) -> TargetResult<(), Self> {
+        self.machine
+            .write_memory(start_addr, data)
+            .map_err(|_| TargetError::NonFatal)
+    }
+
+    fn flash_done(&mut self) -> TargetResult<(), Self> {
+        Ok(())
+    }
+}
+
+/// Register names for `monitor regs`, indexed like `DebugControl::read_core_reg`.
+pub trait CoreRegisterNames {
+    const REGISTER_NAMES: &'static [&'static str];
+}
+
+impl CoreRegisterNames for CortexM {
+    const REGISTER_NAMES: &'static [&'static str] = &[
+        "r0", "r1", "r2", "r3", "r4", "r5", "r6", "r7", "r8", "r9", "r10", "r11", "r12", "sp",
+        "lr", "pc", "xpsr",
+    ];
+}
+
+impl CoreRegisterNames for RiscV {
+    const REGISTER_NAMES: &'static [&'static str] = &[
+        "zero", "ra", "sp", "gp", "tp", "t0", "t1", "t2", "s0", "s1", "a0", "a1", "a2", "a3", "a4",
+        "a5", "a6", "a7", "s2", "s3", "s4", "s5", "s6", "s7", "s8", "s9", "s10", "s11", "t3", "t4",
+        "t5", "t6", "pc",
+    ];
+}
+
+impl<C: Cpu + CoreRegisterNames> gdbstub::target::ext::monitor_cmd::MonitorCmd for LabwiredTarget<C>
+where
+    LabwiredTarget<C>: Target<Arch: gdbstub::arch::Arch<Usize = u32>>,
+{
+    fn handle_monitor_cmd(
+        &mut self,
+        cmd: &[u8],
+        mut out: gdbstub::target::ext::monitor_cmd::ConsoleOutput<'_>,
+    ) -> Result<(), Self::Error> {
+        use gdbstub::outputln;
+
+        let cmd = String::from_utf8_lossy(cmd);
+        let words: Vec<&str> = cmd.split_whitespace().collect();
+        match words.as_slice() {
+            // GDB keeps the target stopped after a monitor command, so both
+            // forms leave the core halted at the reset vector.
+            ["reset"] | ["reset", "halt"] => match self.machine.reset() {
+                Ok(()) => outputln!(
+                    out,
+                    "Target reset, halted at {:#010x}",
+                    self.machine.cpu.get_pc() & !1
+                ),
+                Err(e) => outputln!(out, "Reset failed: {}", e),
+            },
+            ["regs"] => {
+                for (id, name) in C::REGISTER_NAMES.iter().enumerate() {
+                    outputln!(
+                        out,
+                        "{:>5} = {:#010x}",
+                        name,
+                        self.machine.read_core_reg(id as u8)
+                    );
+                }
+            }
+            ["break", name] => {
+                let addr = self.symbols.as_ref().and_then(|s| s.symbol_address(name));
+                match addr {
+                    Some(addr) => {
+                        let addr = addr as u32;
+                        self.machine.add_breakpoint(addr);
+                        outputln!(out, "Breakpoint at {:#010x} ({})", addr, name);
+                    }
+                    None if self.symbols.is_none() => {
+                        outputln!(out, "No symbols loaded")
+                    }
+                    None => outputln!(out, "No symbol named '{}'", name),
+                }
+            }
+            _ => outputln!(
+                out,
+                "Unknown monitor command '{}'. Supported: reset, reset halt, regs, break SYMBOL",
+                cmd.trim()
+            ),
+        }
+        Ok(())
+    }
+}
+
 pub struct GdbServer {
     port: u16,
+    symbols: Option<SymbolProvider>,
 }
 
 impl GdbServer {
     pub fn new(port: u16) -> Self {
-        Self { port }
+        Self {
+            port,
+            symbols: None,
+        }
     }
 
-    pub fn run<C: Cpu + 'static>(&self, machine: Machine<C>) -> anyhow::Result<()>
+    /// Resolve `monitor break SYMBOL` against `symbols`.
+    pub fn with_symbols(mut self, symbols: SymbolProvider) -> Self {
+        self.symbols = Some(symbols);
+        self
+    }
+
+    pub fn run<C: Cpu + 'static>(self, machine: Machine<C>) -> anyhow::Result<()>
     where
         LabwiredTarget<C>: Target<Error = Infallible, Arch: gdbstub::arch::Arch<Usize = u32>>,
-        GdbEventLoop<C>: gdbstub::stub::run_blocking::BlockingEventLoop<
+        GdbEventLoop<C, TcpStream>: gdbstub::stub::run_blocking::BlockingEventLoop<
             Target = LabwiredTarget<C>,
             Connection = TcpStream,
             StopReason = BaseStopReason<(), u32>,
@@ -245,27 +572,124 @@ impl GdbServer {
 
         let (stream, addr) = listener.accept()?;
         tracing::info!("GDB client connected from {}", addr);
+        self.serve(machine, stream);
+        Ok(())
+    }
+
+    /// Like `run`, listening on the Unix domain socket `path` instead of a
+    /// TCP port.
+    #[cfg(unix)]
+    pub fn run_unix<C: Cpu + 'static>(
+        self,
+        machine: Machine<C>,
+        path: impl AsRef<Path>,
+    ) -> anyhow::Result<()>
+    where
+        LabwiredTarget<C>: Target<Error = Infallible, Arch: gdbstub::arch::Arch<Usize = u32>>,
+        GdbEventLoop<C, UnixStream>: gdbstub::stub::run_blocking::BlockingEventLoop<
+            Target = LabwiredTarget<C>,
+            Connection = UnixStream,
+            StopReason = BaseStopReason<(), u32>,
+        >,
+    {
+        let path = path.as_ref();
+        // A socket file left behind by an earlier server would fail the bind
+        remove_socket(path)?;
+        let listener = UnixListener::bind(path)?;
+        tracing::info!("GDB server listening on {}", path.display());
 
+        let accepted = listener.accept().map(|(stream, _)| {
+            tracing::info!("GDB client connected");
+            self.serve(machine, stream);
+        });
+        drop(listener);
+        remove_socket(path)?;
+        Ok(accepted?)
+    }
+
+    /// Serve one session over stdin/stdout, for IDEs that launch the server
+    /// as a pipe (`target remote | labwired ...`).
+    ///
+    /// Stdout carries the protocol, so UART and ITM echo to stdout is turned
+    /// off; the caller must send its logs to stderr.
+    pub fn run_stdio<C: Cpu + 'static>(self, machine: Machine<C>) -> anyhow::Result<()>
+    where
+        LabwiredTarget<C>: Target<Error = Infallible, Arch: gdbstub::arch::Arch<Usize = u32>>,
+        GdbEventLoop<C, StreamConnection<std::io::Stdout>>:
+            gdbstub::stub::run_blocking::BlockingEventLoop<
+                Target = LabwiredTarget<C>,
+                Connection = StreamConnection<std::io::Stdout>,
+                StopReason = BaseStopReason<(), u32>,
+            >,
+    {
+        let mut machine = machine;
+        machine.bus.disable_stdout_echo();
+        self.run_stream(machine, std::io::stdin(), std::io::stdout())
+    }
+
+    /// Serve one session reading packets from `input` and writing replies
+    /// to `output`.
+    pub fn run_stream<C: Cpu + 'static, R: Read + Send + 'static, W: Write>(
+        self,
+        machine: Machine<C>,
+        input: R,
+        output: W,
+    ) -> anyhow::Result<()>
+    where
+        LabwiredTarget<C>: Target<Error = Infallible, Arch: gdbstub::arch::Arch<Usize = u32>>,
+        GdbEventLoop<C, StreamConnection<W>>: gdbstub::stub::run_blocking::BlockingEventLoop<
+            Target = LabwiredTarget<C>,
+            Connection = StreamConnection<W>,
+            StopReason = BaseStopReason<(), u32>,
+        >,
+    {
+        self.serve(machine, StreamConnection::new(input, output));
+        Ok(())
+    }
+
+    /// Run a GDB session on `conn` until the client detaches or disconnects;
+    /// shared by every transport.
+    fn serve<C: Cpu + 'static, Conn: ConnectionExt>(self, machine: Machine<C>, conn: Conn)
+    where
+        Conn::Error: std::fmt::Debug,
+        LabwiredTarget<C>: Target<Error = Infallible, Arch: gdbstub::arch::Arch<Usize = u32>>,
+        GdbEventLoop<C, Conn>: gdbstub::stub::run_blocking::BlockingEventLoop<
+            Target = LabwiredTarget<C>,
+            Connection = Conn,
+            StopReason = BaseStopReason<(), u32>,
+        >,
+    {
         let mut target = LabwiredTarget::new(machine);
-        let gdb = GdbStub::new(stream);
+        target.symbols = self.symbols;
+        let gdb = GdbStub::new(conn);
 
-        match gdb.run_blocking::<GdbEventLoop<C>>(&mut target) {
+        match gdb.run_blocking::<GdbEventLoop<C, Conn>>(&mut target) {
             Ok(reason) => tracing::info!("GDB session ended: {:?}", reason),
             Err(e) => tracing::error!("GDB session error: {:?}", e),
         }
+    }
+}
 
-        Ok(())
+/// Remove the Unix socket at `path`, if there is one; other files are left
+/// alone so the bind reports them.
+#[cfg(unix)]
+fn remove_socket(path: &Path) -> std::io::Result<()> {
+    use std::os::unix::fs::FileTypeExt;
+    match std::fs::symlink_metadata(path) {
+        Ok(meta) if meta.file_type().is_socket() => std::fs::remove_file(path),
+        _ => Ok(()),
     }
 }
 
-pub struct GdbEventLoop<C: Cpu>(PhantomData<C>);
+pub struct GdbEventLoop<C: Cpu, Conn = TcpStream>(PhantomData<(C, Conn)>);
 
-impl<C: Cpu> gdbstub::stub::run_blocking::BlockingEventLoop for GdbEventLoop<C>
+impl<C: Cpu, Conn: ConnectionExt> gdbstub::stub::run_blocking::BlockingEventLoop
+    for GdbEventLoop<C, Conn>
 where
     LabwiredTarget<C>: Target<Arch: gdbstub::arch::Arch<Usize = u32>>,
 {
     type Target = LabwiredTarget<C>;
-    type Connection = TcpStream;
+    type Connection = Conn;
     type StopReason = BaseStopReason<(), u32>;
 
     fn wait_for_stop_reason(
@@ -278,50 +702,58 @@ where
             <Self::Connection as gdbstub::conn::Connection>::Error,
         >,
     > {
-        use gdbstub::stub::run_blocking::Event;
-        use std::io::Read;
+        use gdbstub::stub::run_blocking::{Event, WaitForStopReasonError};
 
-        loop {
-            // Non-blocking peep at connection for interrupt
-            let mut byte = [0];
-            conn.set_nonblocking(true).ok();
-            let incoming = match conn.read(&mut byte) {
-                Ok(1) => {
-                    conn.set_nonblocking(false).ok();
-                    Some(byte[0])
-                }
-                _ => {
-                    conn.set_nonblocking(false).ok();
-                    None
-                }
-            };
+        let fault = |e: labwired_core::SimulationError| {
+            tracing::error!("GDB Simulation Error: {}", e);
+            Event::TargetStopped(BaseStopReason::Signal(gdbstub::common::Signal::SIGSEGV))
+        };
+
+        let watch = |addr: u32, kind: WatchKind| {
+            Event::TargetStopped(BaseStopReason::Watch {
+                tid: (),
+                kind: to_gdb_watch_kind(kind),
+                addr,
+            })
+        };
+
+        if target.exec_mode == ExecMode::Step {
+            return Ok(match target.machine.step_single() {
+                Ok(StopReason::Watchpoint { addr, kind }) => watch(addr, kind),
+                Ok(_) => Event::TargetStopped(BaseStopReason::DoneStep),
+                Err(e) => fault(e),
+            });
+        }
 
-            if let Some(b) = incoming {
-                return Ok(Event::IncomingData(b));
+        // Continuing from a breakpoint: execute it once so the run below doesn't
+        // stop on the same address again.
+        let pc = target.machine.read_core_reg(15) & !1;
+        if target.machine.breakpoints.contains(&pc) {
+            match target.machine.step_single() {
+                Ok(StopReason::Watchpoint { addr, kind }) => return Ok(watch(addr, kind)),
+                Ok(_) => {}
+                Err(e) => return Ok(fault(e)),
             }
+        }
 
-            // Run machine for a small chunk
-            match target.machine.run(Some(1000)) {
-                Ok(StopReason::Breakpoint(_)) => {
-                    return Ok(Event::TargetStopped(BaseStopReason::Signal(
-                        gdbstub::common::Signal::SIGTRAP,
-                    )))
-                }
-                Ok(StopReason::StepDone) => {
-                    return Ok(Event::TargetStopped(BaseStopReason::Signal(
-                        gdbstub::common::Signal::SIGTRAP,
-                    )))
-                }
-                Ok(_) => {
-                    // MaxSteps reached, continue loop and check for interrupt again
-                    continue;
-                }
-                Err(e) => {
-                    tracing::error!("GDB Simulation Error: {}", e);
-                    return Ok(Event::TargetStopped(BaseStopReason::Signal(
-                        gdbstub::common::Signal::SIGSEGV,
-                    )));
+        loop {
+            // Non-blocking peek at the connection for an interrupt
+            if conn
+                .peek()
+                .map_err(WaitForStopReasonError::Connection)?
+                .is_some()
+            {
+                let byte = conn.read().map_err(WaitForStopReasonError::Connection)?;
+                return Ok(Event::IncomingData(byte));
+            }
+
+            match target.machine.run(Some(CONTINUE_CHUNK)) {
+                Ok(StopReason::Breakpoint(pc)) => {
+                    return Ok(Event::TargetStopped(target.breakpoint_stop(pc)))
                 }
+                Ok(StopReason::Watchpoint { addr, kind }) => return Ok(watch(addr, kind)),
+                Ok(_) => continue, // Chunk done; poll for an interrupt again
+                Err(e) => return Ok(fault(e)),
             }
         }
     }
@@ -338,7 +770,6 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
-    use labwired_core::bus::SystemBus;
     use labwired_core::cpu::CortexM;
 
     #[test]
@@ -353,14 +784,14 @@ mod tests {
         target.machine.write_core_reg(15, 0x08000100);
         target.machine.write_core_reg(16, 0x60000000); // xPSR
 
-        let mut regs = gdbstub_arch::arm::reg::ArmCoreRegs::default();
+        let mut regs = CortexMRegs::default();
         target
             .read_registers(&mut regs)
             .unwrap_or_else(|_| panic!("Failed to read registers"));
 
         assert_eq!(regs.r[0], 0x12345678);
         assert_eq!(regs.pc, 0x08000100);
-        assert_eq!(regs.cpsr, 0x60000000);
+        assert_eq!(regs.xpsr, 0x60000000 | XPSR_T);
 
         // Test write
         regs.r[1] = 0xdeadbeef;
@@ -370,6 +801,123 @@ mod tests {
         assert_eq!(target.machine.read_core_reg(1), 0xdeadbeef);
     }
 
+    /// A `Write` handle onto a buffer the test keeps a reference to.
+    #[derive(Clone, Default)]
+    struct SharedOutput(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl Write for SharedOutput {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_stream_session_scripted_exchange() {
+        let mut bus = SystemBus::new();
+        let (cpu, _nvic) = labwired_core::system::cortex_m::configure_cortex_m(&mut bus);
+        let machine = Machine::new(cpu, bus);
+
+        // Ask for the stop reason, then detach
+        let input: &'static [u8] = b"+$?#3f+$D#44+";
+        let output = SharedOutput::default();
+        GdbServer::new(0)
+            .run_stream(machine, input, output.clone())
+            .unwrap();
+
+        let reply = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+        assert!(reply.starts_with("+$T05"), "no stop reply in {:?}", reply);
+        assert!(reply.contains("$OK#9a"), "no detach reply in {:?}", reply);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_unix_session_replaces_and_removes_the_socket() {
+        let path = std::env::temp_dir().join(format!("labwired-gdb-{}.sock", std::process::id()));
+        // A stale socket from an earlier server
+        drop(UnixListener::bind(&path).unwrap());
+        assert!(path.exists());
+
+        let mut bus = SystemBus::new();
+        let (cpu, _nvic) = labwired_core::system::cortex_m::configure_cortex_m(&mut bus);
+        let machine = Machine::new(cpu, bus);
+        let server_path = path.clone();
+        let server = std::thread::spawn(move || GdbServer::new(0).run_unix(machine, server_path));
+
+        let mut client = loop {
+            if let Ok(stream) = UnixStream::connect(&path) {
+                break stream;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        };
+        client.write_all(b"+$D#44").unwrap();
+        let mut reply = String::new();
+        client.read_to_string(&mut reply).unwrap();
+        server.join().unwrap().unwrap();
+
+        assert!(reply.contains("$OK#9a"), "no detach reply in {:?}", reply);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_cortex_m_register_roundtrip() {
+        use gdbstub::arch::Registers;
+
+        let mut bus = SystemBus::new();
+        let (cpu, _nvic) = labwired_core::system::cortex_m::configure_cortex_m(&mut bus);
+        let machine = Machine::new(cpu, bus);
+        let mut target = LabwiredTarget::<CortexM>::new(machine);
+
+        for i in 0..13u8 {
+            target.machine.write_core_reg(i, 0x1000 + i as u32);
+        }
+        target.machine.write_core_reg(13, 0x2000_1000);
+        target.machine.write_core_reg(14, 0x0000_0201);
+        target.machine.write_core_reg(15, 0x0000_0101); // Thumb bit from a vector fetch
+        target.machine.write_core_reg(16, 0x2000_0000);
+
+        let mut regs = CortexMRegs::default();
+        target
+            .read_registers(&mut regs)
+            .unwrap_or_else(|_| panic!("Failed to read registers"));
+        assert_eq!(regs.pc, 0x100);
+        assert_eq!(regs.xpsr, 0x2000_0000 | XPSR_T);
+
+        // 'g' packet: R0-R12, SP, LR, PC, xPSR with no gaps
+        let mut packet = Vec::new();
+        regs.gdb_serialize(|b| packet.push(b.unwrap()));
+        assert_eq!(packet.len(), 17 * 4);
+        assert_eq!(&packet[13 * 4..14 * 4], &0x2000_1000u32.to_le_bytes());
+        assert_eq!(&packet[16 * 4..], &(0x2000_0000 | XPSR_T).to_le_bytes());
+
+        let mut decoded = CortexMRegs::default();
+        decoded.gdb_deserialize(&packet).unwrap();
+        assert_eq!(decoded, regs);
+
+        // PC written by GDB with the Thumb bit set is stored as an address
+        decoded.pc = 0x0000_0301;
+        target
+            .write_registers(&decoded)
+            .unwrap_or_else(|_| panic!("Failed to write registers"));
+        assert_eq!(target.machine.read_core_reg(15), 0x300);
+        assert_eq!(target.machine.read_core_reg(12), 0x100C);
+        assert_eq!(target.machine.read_core_reg(14), 0x0000_0201);
+    }
+
+    #[test]
+    fn test_cortex_m_reg_ids() {
+        use arch::CortexMRegId;
+        use gdbstub::arch::RegId;
+
+        assert_eq!(CortexMRegId::from_raw_id(15).unwrap().0, CortexMRegId::Pc);
+        assert_eq!(CortexMRegId::from_raw_id(16).unwrap().0, CortexMRegId::Xpsr);
+        assert!(CortexMRegId::from_raw_id(25).is_none());
+    }
+
     #[test]
     fn test_riscv_target_register_access() {
         let bus = SystemBus::new();
@@ -397,6 +945,303 @@ mod tests {
         assert_eq!(target.machine.read_core_reg(2), 0xdeadbeef);
     }
 
+    /// In-memory GDB connection with no pending client data.
+    #[derive(Default)]
+    struct MockConnection {
+        incoming: Vec<u8>,
+        outgoing: Vec<u8>,
+    }
+
+    impl gdbstub::conn::Connection for MockConnection {
+        type Error = ();
+
+        fn write(&mut self, byte: u8) -> Result<(), Self::Error> {
+            self.outgoing.push(byte);
+            Ok(())
+        }
+
+        fn flush(&mut self) -> Result<(), Self::Error> {
+            Ok(())
+        }
+    }
+
+    impl ConnectionExt for MockConnection {
+        fn read(&mut self) -> Result<u8, Self::Error> {
+            if self.incoming.is_empty() {
+                return Err(());
+            }
+            Ok(self.incoming.remove(0))
+        }
+
+        fn peek(&mut self) -> Result<Option<u8>, Self::Error> {
+            Ok(self.incoming.first().copied())
+        }
+    }
+
+    fn movs_target() -> LabwiredTarget<CortexM> {
+        let mut bus = SystemBus::new();
+        let (cpu, _nvic) = labwired_core::system::cortex_m::configure_cortex_m(&mut bus);
+        let mut machine = Machine::new(cpu, bus);
+        // MOVS R0, #i at 0x100, 0x102, ...
+        for i in 0..8u32 {
+            machine
+                .write_memory(0x100 + i * 2, &[i as u8, 0x20])
+                .unwrap();
+        }
+        machine.write_core_reg(15, 0x100);
+        machine.write_core_reg(13, 0x2000_1000);
+        LabwiredTarget::new(machine)
+    }
+
+    fn wait(
+        target: &mut LabwiredTarget<CortexM>,
+        conn: &mut MockConnection,
+    ) -> gdbstub::stub::run_blocking::Event<BaseStopReason<(), u32>> {
+        use gdbstub::stub::run_blocking::BlockingEventLoop;
+        GdbEventLoop::<CortexM, MockConnection>::wait_for_stop_reason(target, conn)
+            .unwrap_or_else(|_| panic!("wait_for_stop_reason failed"))
+    }
+
+    #[test]
+    fn test_single_step_executes_one_instruction() {
+        use gdbstub::stub::run_blocking::Event;
+
+        let mut target = movs_target();
+        let mut conn = MockConnection::default();
+
+        SingleThreadSingleStep::step(&mut target, None).unwrap();
+        let event = wait(&mut target, &mut conn);
+        assert!(matches!(
+            event,
+            Event::TargetStopped(BaseStopReason::DoneStep)
+        ));
+        assert_eq!(target.machine.read_core_reg(15), 0x102);
+        assert_eq!(target.machine.read_core_reg(0), 0);
+
+        SingleThreadSingleStep::step(&mut target, None).unwrap();
+        wait(&mut target, &mut conn);
+        assert_eq!(target.machine.read_core_reg(15), 0x104);
+        assert_eq!(target.machine.read_core_reg(0), 1);
+    }
+
+    #[test]
+    fn test_continue_runs_to_breakpoint_and_steps_off_it() {
+        use gdbstub::stub::run_blocking::Event;
+
+        let mut target = movs_target();
+        let mut conn = MockConnection::default();
+        target.machine.add_breakpoint(0x106);
+
+        SingleThreadResume::resume(&mut target, None).unwrap();
+        let event = wait(&mut target, &mut conn);
+        assert!(matches!(
+            event,
+            Event::TargetStopped(BaseStopReason::SwBreak(()))
+        ));
+        assert_eq!(target.machine.read_core_reg(15), 0x106);
+
+        // Continuing again must not re-report the breakpoint we are stopped on
+        target.machine.add_breakpoint(0x10A);
+        SingleThreadResume::resume(&mut target, None).unwrap();
+        wait(&mut target, &mut conn);
+        assert_eq!(target.machine.read_core_reg(15), 0x10A);
+    }
+
+    #[test]
+    fn test_continue_yields_to_incoming_data() {
+        use gdbstub::stub::run_blocking::Event;
+
+        let mut target = movs_target();
+        let mut conn = MockConnection {
+            incoming: vec![0x03], // Ctrl-C
+            ..Default::default()
+        };
+
+        SingleThreadResume::resume(&mut target, None).unwrap();
+        let event = wait(&mut target, &mut conn);
+        assert!(matches!(event, Event::IncomingData(0x03)));
+        assert_eq!(target.machine.read_core_reg(15), 0x100);
+    }
+
+    #[test]
+    fn test_write_watchpoint_stops_on_store() {
+        use gdbstub::stub::run_blocking::Event;
+        use gdbstub::target::ext::breakpoints::{HwWatchpoint, WatchKind as GdbWatchKind};
+
+        let mut bus = SystemBus::new();
+        let (cpu, _nvic) = labwired_core::system::cortex_m::configure_cortex_m(&mut bus);
+        let mut machine = Machine::new(cpu, bus);
+        let program: [u16; 6] = [
+            0x4802, // 0x100: ldr r0, [pc, #8]   ; 0x2000_0010
+            0x2107, // 0x102: movs r1, #7
+            0x6801, // 0x104: ldr r1, [r0, #0]   ; read must not fire a write watchpoint
+            0x6001, // 0x106: str r1, [r0, #0]
+            0x2200, // 0x108: movs r2, #0
+            0xE7FE, // 0x10A: b .
+        ];
+        for (i, op) in program.iter().enumerate() {
+            machine
+                .write_memory(0x100 + i as u32 * 2, &op.to_le_bytes())
+                .unwrap();
+        }
+        machine
+            .write_memory(0x10C, &0x2000_0010u32.to_le_bytes())
+            .unwrap();
+        machine.write_core_reg(15, 0x100);
+        let mut target = LabwiredTarget::new(machine);
+        let mut conn = MockConnection::default();
+
+        assert!(target
+            .add_hw_watchpoint(0x2000_0010, 4, GdbWatchKind::Write)
+            .unwrap_or_else(|_| panic!("add_hw_watchpoint failed")));
+
+        SingleThreadResume::resume(&mut target, None).unwrap();
+        let event = wait(&mut target, &mut conn);
+        assert!(matches!(
+            event,
+            Event::TargetStopped(BaseStopReason::Watch {
+                kind: GdbWatchKind::Write,
+                addr: 0x2000_0010,
+                ..
+            })
+        ));
+        // Stopped right after the store retired
+        assert_eq!(target.machine.read_core_reg(15), 0x108);
+
+        assert!(target
+            .remove_hw_watchpoint(0x2000_0010, 4, GdbWatchKind::Write)
+            .unwrap_or_else(|_| panic!("remove_hw_watchpoint failed")));
+        assert!(target.machine.watchpoints.is_empty());
+    }
+
+    #[test]
+    fn test_hw_breakpoint_reports_hwbreak() {
+        use gdbstub::stub::run_blocking::Event;
+        use gdbstub::target::ext::breakpoints::HwBreakpoint;
+
+        let mut target = movs_target();
+        let mut conn = MockConnection::default();
+        let kind = gdbstub_arch::arm::ArmBreakpointKind::Thumb16;
+        assert!(target
+            .add_hw_breakpoint(0x104, kind)
+            .unwrap_or_else(|_| panic!("add_hw_breakpoint failed")));
+
+        SingleThreadResume::resume(&mut target, None).unwrap();
+        let event = wait(&mut target, &mut conn);
+        assert!(matches!(
+            event,
+            Event::TargetStopped(BaseStopReason::HwBreak(()))
+        ));
+        assert_eq!(target.machine.read_core_reg(15), 0x104);
+    }
+
+    /// Frame `payload` as a GDB remote serial protocol packet.
+    fn rsp_packet(payload: &str) -> Vec<u8> {
+        let checksum = payload.bytes().fold(0u8, |sum, b| sum.wrapping_add(b));
+        format!("${}#{:02x}", payload, checksum).into_bytes()
+    }
+
+    fn hex(s: &str) -> String {
+        s.bytes().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    #[test]
+    fn test_monitor_reset_returns_to_reset_vector() {
+        let mut target = movs_target();
+        // Vector table: SP, then the Thumb reset handler at 0x100
+        target
+            .machine
+            .write_memory(0x0, &0x2000_1000u32.to_le_bytes())
+            .unwrap();
+        target
+            .machine
+            .write_memory(0x4, &0x101u32.to_le_bytes())
+            .unwrap();
+        target.machine.step_single().unwrap();
+        target.machine.step_single().unwrap();
+        assert_eq!(target.machine.read_core_reg(15), 0x104);
+
+        let outgoing = monitor(&mut target, &["reset halt", "frobnicate"]);
+
+        assert_eq!(target.machine.read_core_reg(15) & !1, 0x100);
+        assert_eq!(target.machine.read_core_reg(13), 0x2000_1000);
+        assert!(outgoing.contains(&hex("Target reset, halted at 0x00000100")));
+        assert!(outgoing.contains(&hex("Unknown monitor command 'frobnicate'")));
+    }
+
+    #[test]
+    fn test_monitor_break_resolves_symbol() {
+        let mut target = movs_target();
+        let outgoing = monitor(&mut target, &["break main"]);
+        assert!(outgoing.contains(&hex("No symbols loaded")));
+
+        target.symbols = Some(
+            SymbolProvider::new(std::path::Path::new(
+                "../../tests/fixtures/uart-ok-thumbv7m.elf",
+            ))
+            .unwrap(),
+        );
+        let outgoing = monitor(&mut target, &["break main", "break nope"]);
+        assert!(target.machine.breakpoints.contains(&0x43c));
+        assert!(outgoing.contains(&hex("Breakpoint at 0x0000043c (main)")));
+        assert!(outgoing.contains(&hex("No symbol named 'nope'")));
+    }
+
+    /// Send each of `cmds` as `monitor` commands; returns the raw outgoing data.
+    fn monitor(target: &mut LabwiredTarget<CortexM>, cmds: &[&str]) -> String {
+        use gdbstub::stub::state_machine::GdbStubStateMachine;
+
+        let mut gdb = GdbStub::new(MockConnection::default())
+            .run_state_machine(target)
+            .unwrap_or_else(|_| panic!("Failed to start GDB stub"));
+        for cmd in cmds {
+            for byte in rsp_packet(&format!("qRcmd,{}", hex(cmd))) {
+                gdb = match gdb {
+                    GdbStubStateMachine::Idle(gdb) => gdb
+                        .incoming_data(target, byte)
+                        .unwrap_or_else(|_| panic!("GDB stub rejected packet")),
+                    _ => panic!("GDB stub left the idle state"),
+                };
+            }
+        }
+
+        let GdbStubStateMachine::Idle(mut gdb) = gdb else {
+            panic!("GDB stub left the idle state");
+        };
+        String::from_utf8(gdb.borrow_conn().outgoing.clone()).unwrap()
+    }
+
+    #[test]
+    fn test_memory_map_xml_lists_flash_and_ram() {
+        use gdbstub::target::ext::memory_map::MemoryMap;
+
+        let mut bus = SystemBus::new();
+        let (cpu, _nvic) = labwired_core::system::cortex_m::configure_cortex_m(&mut bus);
+        let target = LabwiredTarget::<CortexM>::new(Machine::new(cpu, bus));
+
+        let xml = memory_map_xml(&target.machine.bus);
+        assert!(xml.contains(r#"<memory type="flash" start="0x0" length="0x100000">"#));
+        assert!(xml.contains(r#"<property name="blocksize">0x400</property>"#));
+        assert!(xml.contains(r#"<memory type="ram" start="0x20000000" length="0x100000"/>"#));
+        assert!(xml.contains("<!-- mmio: uart1 -->"));
+        assert!(xml.contains(r#"<memory type="ram" start="0x4000c000" length="0x1000"/>"#));
+        assert!(xml.trim_end().ends_with("</memory-map>"));
+
+        // qXfer reads in chunks reassemble to the same document
+        let mut read = Vec::new();
+        let mut buf = [0u8; 64];
+        loop {
+            let n = target
+                .memory_map_xml(read.len() as u64, buf.len(), &mut buf)
+                .unwrap_or_else(|_| panic!("memory_map_xml failed"));
+            if n == 0 {
+                break;
+            }
+            read.extend_from_slice(&buf[..n]);
+        }
+        assert_eq!(String::from_utf8(read).unwrap(), xml);
+    }
+
     #[test]
     fn test_target_memory_access() {
         let mut bus = SystemBus::new();
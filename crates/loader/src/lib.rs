@@ -8,12 +8,39 @@ use anyhow::{anyhow, Context, Result};
 use goblin::elf::program_header::PT_LOAD;
 use goblin::elf::Elf;
 use labwired_core::memory::ProgramImage;
+use labwired_core::profile::FunctionSymbol;
+use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
 use std::sync::Arc;
 use tracing::{debug, info, warn};
 
+/// Caps applied while loading an ELF, so that a malformed or hostile file
+/// (e.g. thousands of overlapping segments) fails with an error instead of
+/// exhausting memory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LoadLimits {
+    /// Maximum number of non-empty `PT_LOAD` segments.
+    pub max_segments: usize,
+    /// Maximum total file bytes copied out of all segments.
+    pub max_total_size: u64,
+}
+
+impl Default for LoadLimits {
+    fn default() -> Self {
+        Self {
+            max_segments: 1024,
+            max_total_size: 256 * 1024 * 1024,
+        }
+    }
+}
+
+/// Load an ELF with the default [`LoadLimits`].
 pub fn load_elf(path: &Path) -> Result<ProgramImage> {
+    load_elf_with_limits(path, &LoadLimits::default())
+}
+
+pub fn load_elf_with_limits(path: &Path, limits: &LoadLimits) -> Result<ProgramImage> {
     let buffer = fs::read(path).with_context(|| format!("Failed to read ELF file: {:?}", path))?;
 
     let elf = Elf::parse(&buffer).context("Failed to parse ELF binary")?;
@@ -30,6 +57,7 @@ pub fn load_elf(path: &Path) -> Result<ProgramImage> {
     };
 
     let mut program_image = ProgramImage::new(elf.entry, arch);
+    let mut total_size = 0u64;
 
     for ph in elf.program_headers {
         if ph.p_type == PT_LOAD {
@@ -38,21 +66,35 @@ pub fn load_elf(path: &Path) -> Result<ProgramImage> {
             let size = ph.p_filesz as usize;
             let offset = ph.p_offset as usize;
 
-            if size == 0 {
+            // A segment with no file bytes can still be .bss to zero
+            if ph.p_memsz == 0 {
                 continue;
             }
 
             debug!(
-                "Found Loadable Segment: Addr={:#x}, Size={} bytes, Offset={:#x}",
-                start_addr, size, offset
+                "Found Loadable Segment: Addr={:#x}, Size={} bytes ({} in memory), Offset={:#x}",
+                start_addr, size, ph.p_memsz, offset
             );
 
             if offset + size > buffer.len() {
                 return Err(anyhow!("Segment out of bounds in ELF file"));
             }
+            if program_image.segments.len() >= limits.max_segments {
+                return Err(anyhow!(
+                    "ELF has more than {} loadable segments (loader limit)",
+                    limits.max_segments
+                ));
+            }
+            total_size += ph.p_filesz;
+            if total_size > limits.max_total_size {
+                return Err(anyhow!(
+                    "ELF loadable segments exceed {} bytes in total (loader limit)",
+                    limits.max_total_size
+                ));
+            }
 
             let segment_data = buffer[offset..offset + size].to_vec();
-            program_image.add_segment(start_addr, segment_data);
+            program_image.add_segment(start_addr, segment_data, ph.p_memsz);
         }
     }
 
@@ -63,6 +105,65 @@ pub fn load_elf(path: &Path) -> Result<ProgramImage> {
     Ok(program_image)
 }
 
+/// Load a flat binary as a single segment at `base_addr`, with the entry
+/// point at `base_addr`.
+///
+/// Raw images carry no machine type and are assumed to be ARM (Cortex-M).
+/// If the image has no usable vector table, `Machine::load_firmware` falls
+/// back to the entry point after reset.
+pub fn load_bin(path: &Path, base_addr: u64) -> Result<ProgramImage> {
+    let data = fs::read(path).with_context(|| format!("Failed to read binary file: {:?}", path))?;
+    if data.is_empty() {
+        warn!("Binary file {:?} is empty", path);
+    }
+    info!("Raw binary: {} bytes at {:#x}", data.len(), base_addr);
+
+    let mut program_image = ProgramImage::new(base_addr, labwired_core::Arch::Arm);
+    let len = data.len() as u64;
+    program_image.add_segment(base_addr, data, len);
+    Ok(program_image)
+}
+
+/// How a firmware file is laid out on disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FirmwareFormat {
+    #[default]
+    Elf,
+    /// Flat binary loaded at `base_addr`.
+    Bin { base_addr: u64 },
+}
+
+/// Load `path` with the loader for `format`.
+pub fn load_firmware(path: &Path, format: FirmwareFormat) -> Result<ProgramImage> {
+    match format {
+        FirmwareFormat::Elf => load_elf(path),
+        FirmwareFormat::Bin { base_addr } => load_bin(path, base_addr),
+    }
+}
+
+/// Named symbols in the ELF symbol table, mapped to their addresses, under
+/// both their raw and demangled names.
+///
+/// The Thumb bit is cleared from ARM function addresses so they compare equal
+/// to the PC. Undefined symbols are skipped.
+pub fn load_symbols(path: &Path) -> Result<HashMap<String, u64>> {
+    let buffer = fs::read(path).with_context(|| format!("Failed to read ELF file: {:?}", path))?;
+    let object = object::File::parse(&*buffer).context("Failed to parse ELF binary")?;
+    Ok(symbol_tables(&object).0)
+}
+
+/// GNU build-id from the ELF's `.note.gnu.build-id` section, as lowercase hex.
+///
+/// `Ok(None)` if the ELF has no build-id note.
+pub fn load_build_id(path: &Path) -> Result<Option<String>> {
+    use object::Object;
+
+    let buffer = fs::read(path).with_context(|| format!("Failed to read ELF file: {:?}", path))?;
+    let object = object::File::parse(&*buffer).context("Failed to parse ELF binary")?;
+    let id = object.build_id().context("Failed to read build-id note")?;
+    Ok(id.map(|id| id.iter().map(|b| format!("{:02x}", b)).collect()))
+}
+
 pub struct SourceLocation {
     pub file: String,
     pub line: Option<u32>,
@@ -75,8 +176,11 @@ pub struct SymbolProvider {
     context: addr2line::Context<
         addr2line::gimli::EndianReader<addr2line::gimli::RunTimeEndian, Arc<[u8]>>,
     >,
-    // Map of (file_name, line) -> address
+    // Map of (file_name, line) -> lowest statement address
     line_map: std::collections::HashMap<(String, u32), u64>,
+    // Symbol table names, mangled and demangled, -> address
+    symbols: HashMap<String, u64>,
+    functions: Vec<FunctionSymbol>,
 }
 
 impl SymbolProvider {
@@ -91,6 +195,7 @@ impl SymbolProvider {
 
         let object = object::File::parse(slice).context("Failed to parse ELF for symbols")?;
 
+        let (symbols, functions) = symbol_tables(&object);
         let mut line_map = std::collections::HashMap::new();
 
         // Build line map using gimli for reverse lookup
@@ -116,7 +221,7 @@ impl SymbolProvider {
                 if let Some(ref line_program) = unit.line_program {
                     let mut rows = line_program.clone().rows();
                     while let Ok(Some((_, row))) = rows.next_row() {
-                        if row.end_sequence() {
+                        if row.end_sequence() || !row.is_stmt() {
                             continue;
                         }
                         let file_idx = row.file_index();
@@ -130,10 +235,11 @@ impl SymbolProvider {
                                 });
 
                             if let (Some(f), Some(line)) = (file_name, row.line()) {
-                                // Store the first address seen for this file:line
+                                let addr = row.address();
                                 line_map
                                     .entry((f, line.get() as u32))
-                                    .or_insert(row.address());
+                                    .and_modify(|a: &mut u64| *a = (*a).min(addr))
+                                    .or_insert(addr);
                             }
                         }
                     }
@@ -148,6 +254,8 @@ impl SymbolProvider {
             data,
             context,
             line_map,
+            symbols,
+            functions,
         })
     }
 
@@ -181,25 +289,102 @@ impl SymbolProvider {
         None
     }
 
+    /// Lowest address of `file_path:line`. A line without code (blank,
+    /// comment, label) snaps to the next line that has a statement.
+    ///
+    /// The full path is tried first, then any file with the same base name.
     pub fn location_to_pc(&self, file_path: &str, line: u32) -> Option<u64> {
-        // Try exact match first
-        if let Some(addr) = self.line_map.get(&(file_path.to_string(), line)) {
-            return Some(*addr);
-        }
+        let requested = Path::new(file_path);
+        let requested_name = requested.file_name()?;
+        self.first_statement_from(line, |f| Path::new(f) == requested)
+            .or_else(|| {
+                self.first_statement_from(line, |f| {
+                    Path::new(f).file_name() == Some(requested_name)
+                })
+            })
+    }
 
-        // Try base name match if full path doesn't match
-        let requested_file = std::path::Path::new(file_path).file_name()?.to_str()?;
+    /// Address of the symbol `name`, given either as it appears in the symbol
+    /// table or demangled (e.g. `firmware::main`). ARM function addresses have
+    /// the Thumb bit cleared.
+    pub fn symbol_address(&self, name: &str) -> Option<u64> {
+        self.symbols.get(name).copied()
+    }
 
-        for ((f, l), addr) in &self.line_map {
-            if *l == line {
-                let current_file = std::path::Path::new(f).file_name()?.to_str()?;
-                if current_file == requested_file {
-                    return Some(*addr);
-                }
-            }
+    /// Every `(file, line)` with a statement in the line table, with its
+    /// lowest address.
+    pub fn lines(&self) -> impl Iterator<Item = (&str, u32, u64)> {
+        self.line_map
+            .iter()
+            .map(|((file, line), addr)| (file.as_str(), *line, *addr))
+    }
+
+    /// Code symbols (functions and labels in executable sections) with
+    /// demangled names, for attributing PCs to functions.
+    pub fn functions(&self) -> &[FunctionSymbol] {
+        &self.functions
+    }
+
+    /// Lowest address on the first line at or after `line` in files accepted by `matches`.
+    fn first_statement_from(&self, line: u32, matches: impl Fn(&str) -> bool) -> Option<u64> {
+        self.line_map
+            .iter()
+            .filter(|((f, l), _)| *l >= line && matches(f))
+            .min_by_key(|((_, l), addr)| (*l, **addr))
+            .map(|(_, addr)| *addr)
+    }
+}
+
+/// Defined, named symbols of `object`, keyed by both raw and demangled name,
+/// and the ones located in executable sections as functions. ARM mapping
+/// symbols (`$t`, `$d`, ...) are not functions.
+fn symbol_tables(object: &object::File) -> (HashMap<String, u64>, Vec<FunctionSymbol>) {
+    use object::{Object, ObjectSection, ObjectSymbol, SectionKind, SymbolKind};
+
+    let is_arm = object.architecture() == object::Architecture::Arm;
+    let clear_thumb = |addr: u64| if is_arm { addr & !1 } else { addr };
+    let mut symbols = HashMap::new();
+    let mut functions = Vec::new();
+    for sym in object.symbols() {
+        if sym.is_undefined() {
+            continue;
+        }
+        let Some(name) = sym.name().ok().filter(|n| !n.is_empty()) else {
+            continue;
+        };
+        let addr = match sym.kind() {
+            SymbolKind::Text => clear_thumb(sym.address()),
+            _ => sym.address(),
+        };
+        for key in symbol_names(name) {
+            symbols.entry(key).or_insert(addr);
         }
 
-        None
+        let in_text = sym
+            .section_index()
+            .and_then(|index| object.section_by_index(index).ok())
+            .is_some_and(|section| section.kind() == SectionKind::Text);
+        if in_text
+            && matches!(sym.kind(), SymbolKind::Text | SymbolKind::Unknown)
+            && !name.starts_with('$')
+        {
+            functions.push(FunctionSymbol {
+                name: addr2line::demangle_auto(name.into(), None).into_owned(),
+                addr: clear_thumb(sym.address()),
+                size: sym.size(),
+            });
+        }
+    }
+    (symbols, functions)
+}
+
+/// `name` followed by its demangled form, when that differs.
+fn symbol_names(name: &str) -> Vec<String> {
+    let demangled = addr2line::demangle_auto(name.into(), None);
+    if demangled == name {
+        vec![name.to_string()]
+    } else {
+        vec![name.to_string(), demangled.into_owned()]
     }
 }
 
@@ -207,6 +392,172 @@ impl SymbolProvider {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_load_symbols_clears_thumb_bit() {
+        let symbols = load_symbols(Path::new("../../tests/fixtures/uart-ok-thumbv7m.elf")).unwrap();
+        assert_eq!(symbols.get("main"), Some(&0x43c));
+        assert_eq!(symbols.get("HardFault"), Some(&0x462));
+    }
+
+    #[test]
+    fn test_load_build_id_absent() {
+        let path = Path::new("../../tests/fixtures/uart-ok-thumbv7m.elf");
+        assert_eq!(load_build_id(path).unwrap(), None);
+    }
+
+    /// Little-endian ELF32 for ARM whose `segments` PT_LOAD headers all map
+    /// the same 4 file bytes to consecutive words.
+    fn synthetic_elf(segments: u16) -> Vec<u8> {
+        let phoff = 52u32;
+        let data_offset = phoff + 32 * segments as u32;
+        let mut elf = vec![0x7F, b'E', b'L', b'F', 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        for half in [2u16, 40] {
+            elf.extend(half.to_le_bytes()); // e_type = EXEC, e_machine = ARM
+        }
+        for word in [1u32, 0, phoff, 0, 0] {
+            elf.extend(word.to_le_bytes()); // version, entry, phoff, shoff, flags
+        }
+        for half in [52u16, 32, segments, 40, 0, 0] {
+            elf.extend(half.to_le_bytes()); // ehsize, phentsize, phnum, sh*
+        }
+        for i in 0..segments as u32 {
+            for word in [PT_LOAD, data_offset, i * 4, i * 4, 4, 4, 5, 4] {
+                elf.extend(word.to_le_bytes());
+            }
+        }
+        elf.extend([0xFE, 0xE7, 0xFE, 0xE7]);
+        elf
+    }
+
+    #[test]
+    fn test_load_elf_rejects_too_many_segments() {
+        let path = std::env::temp_dir().join(format!("labwired-segs-{}.elf", std::process::id()));
+        fs::write(&path, synthetic_elf(5)).unwrap();
+        let limits = LoadLimits {
+            max_segments: 4,
+            ..LoadLimits::default()
+        };
+
+        let err = load_elf_with_limits(&path, &limits).unwrap_err();
+        let image = load_elf(&path).unwrap();
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(
+            err.to_string(),
+            "ELF has more than 4 loadable segments (loader limit)"
+        );
+        assert_eq!(image.segments.len(), 5);
+    }
+
+    #[test]
+    fn test_load_elf_rejects_oversized_total() {
+        let path = std::env::temp_dir().join(format!("labwired-size-{}.elf", std::process::id()));
+        fs::write(&path, synthetic_elf(3)).unwrap();
+        let limits = LoadLimits {
+            max_total_size: 8,
+            ..LoadLimits::default()
+        };
+
+        let err = load_elf_with_limits(&path, &limits).unwrap_err();
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(
+            err.to_string(),
+            "ELF loadable segments exceed 8 bytes in total (loader limit)"
+        );
+    }
+
+    #[test]
+    fn test_load_bin_places_single_segment_at_base() {
+        let path = std::env::temp_dir().join(format!("labwired-bin-{}.bin", std::process::id()));
+        let blob = [0x00, 0x10, 0x00, 0x20, 0x09, 0x00, 0x00, 0x08, 0xFE, 0xE7];
+        fs::write(&path, blob).unwrap();
+
+        let image = load_firmware(
+            &path,
+            FirmwareFormat::Bin {
+                base_addr: 0x0800_0000,
+            },
+        )
+        .unwrap();
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(image.entry_point, 0x0800_0000);
+        assert_eq!(image.arch, labwired_core::Arch::Arm);
+        assert_eq!(image.segments.len(), 1);
+        assert_eq!(image.segments[0].start_addr, 0x0800_0000);
+        assert_eq!(image.segments[0].data, blob);
+    }
+
+    #[test]
+    fn test_location_to_pc_snaps_to_next_statement() {
+        let provider =
+            SymbolProvider::new(Path::new("../../tests/fixtures/dap-step-thumbv7m.elf")).unwrap();
+
+        // Line numbers in tests/fixtures/dap-step-thumbv7m.s
+        assert_eq!(
+            provider.location_to_pc("dap-step-thumbv7m.s", 20),
+            Some(0x42)
+        );
+        assert_eq!(
+            provider.location_to_pc("/elsewhere/dap-step-thumbv7m.s", 19),
+            Some(0x40)
+        );
+        // `reset:` and `loop:` labels have no code of their own
+        assert_eq!(
+            provider.location_to_pc("dap-step-thumbv7m.s", 18),
+            Some(0x40)
+        );
+        assert_eq!(
+            provider.location_to_pc("dap-step-thumbv7m.s", 23),
+            Some(0x4a)
+        );
+        assert_eq!(provider.location_to_pc("dap-step-thumbv7m.s", 1000), None);
+        assert_eq!(provider.location_to_pc("other.s", 20), None);
+    }
+
+    #[test]
+    fn test_symbol_address_resolves_main() {
+        let path = Path::new("../../tests/fixtures/uart-ok-thumbv7m.elf");
+        let provider = SymbolProvider::new(path).unwrap();
+        let image = load_elf(path).unwrap();
+
+        let main = provider.symbol_address("main").unwrap();
+        assert_eq!(main, 0x43c);
+        let code = image
+            .segments
+            .iter()
+            .find(|s| {
+                (s.start_addr..s.start_addr + s.data.len() as u64).contains(&image.entry_point)
+            })
+            .unwrap();
+        assert!((code.start_addr..code.start_addr + code.data.len() as u64).contains(&main));
+        assert_eq!(provider.symbol_address("no_such_symbol"), None);
+    }
+
+    #[test]
+    fn test_functions_skip_mapping_symbols() {
+        let path = Path::new("../../tests/fixtures/uart-ok-thumbv7m.elf");
+        let provider = SymbolProvider::new(path).unwrap();
+        let functions = provider.functions();
+
+        let main = functions.iter().find(|f| f.name == "main").unwrap();
+        assert_eq!((main.addr, main.size), (0x43c, 26));
+        assert!(functions.iter().all(|f| !f.name.starts_with('$')));
+        // Vector table entries are data, not code
+        assert!(functions.iter().all(|f| f.name != "__RESET_VECTOR"));
+    }
+
+    #[test]
+    fn test_symbol_names_include_demangled_form() {
+        assert_eq!(symbol_names("main"), vec!["main"]);
+        assert_eq!(
+            symbol_names("_ZN8firmware4main17h0123456789abcdefE"),
+            vec!["_ZN8firmware4main17h0123456789abcdefE", "firmware::main"]
+        );
+        assert_eq!(symbol_names("_Z4initv"), vec!["_Z4initv", "init()"]);
+    }
+
     #[test]
     fn test_location_to_pc() {
         // This test requires the firmware to be built with debug symbols
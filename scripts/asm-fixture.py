@@ -0,0 +1,71 @@
+#!/usr/bin/env python3
+# LabWired - Firmware Simulation Platform
+# Copyright (C) 2026 Andrii Shylenko
+#
+# This software is released under the MIT License.
+# See the LICENSE file in the project root for full license information.
+
+"""Build a tiny Thumb ELF test fixture (with DWARF line info) from assembly.
+
+Only llvm-mc is needed, no ARM linker: the source is a single .text section
+placed at address 0 (vector table first), so the object's unrelocated values
+are already final. The object is turned into an executable by adding one
+PT_LOAD segment covering .text.
+
+Usage: scripts/asm-fixture.py tests/fixtures/foo.s tests/fixtures/foo.elf
+"""
+
+import os
+import struct
+import subprocess
+import sys
+import tempfile
+
+
+def main():
+    src, out = sys.argv[1], sys.argv[2]
+    with tempfile.TemporaryDirectory() as tmp:
+        obj = os.path.join(tmp, "fixture.o")
+        subprocess.run(
+            [
+                "llvm-mc",
+                "-triple=thumbv7m-none-eabi",
+                "-filetype=obj",
+                "-g",
+                "-fdebug-compilation-dir=.",
+                "-main-file-name=" + os.path.basename(src),
+                src,
+                "-o",
+                obj,
+            ],
+            check=True,
+        )
+        elf = bytearray(open(obj, "rb").read())
+
+    shoff, = struct.unpack_from("<I", elf, 0x20)
+    shentsize, shnum, shstrndx = struct.unpack_from("<HHH", elf, 0x2E)
+    strtab_off = struct.unpack_from("<I", elf, shoff + shstrndx * shentsize + 0x10)[0]
+
+    text = None
+    for i in range(shnum):
+        sh = shoff + i * shentsize
+        name_off, = struct.unpack_from("<I", elf, sh)
+        name = elf[strtab_off + name_off:].split(b"\0", 1)[0]
+        if name == b".text":
+            text = struct.unpack_from("<IIII", elf, sh + 0x0C)  # addr, offset, size
+    if text is None:
+        sys.exit("no .text section")
+    addr, offset, size = text[0], text[1], text[2]
+
+    phoff = len(elf)
+    elf += struct.pack("<IIIIIIII", 1, offset, addr, addr, size, size, 5, 4)
+    struct.pack_into("<H", elf, 0x10, 2)  # ET_EXEC
+    reset, = struct.unpack_from("<I", elf, offset + 4)
+    struct.pack_into("<I", elf, 0x18, reset)  # e_entry
+    struct.pack_into("<I", elf, 0x1C, phoff)
+    struct.pack_into("<HH", elf, 0x2A, 32, 1)  # e_phentsize, e_phnum
+    open(out, "wb").write(elf)
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,22 @@
+@ LabWired - Firmware Simulation Platform
+@ Copyright (C) 2026 Andrii Shylenko
+@
+@ This software is released under the MIT License.
+@ See the LICENSE file in the project root for full license information.
+@
+@ DAP conditional breakpoint fixture: R0 counts up forever.
+@ Rebuild with: scripts/asm-fixture.py tests/fixtures/dap-count-thumbv7m.s tests/fixtures/dap-count-thumbv7m.elf
+@ Tests refer to source lines below; keep them stable.
+
+    .syntax unified
+    .thumb
+    .text
+vectors:
+    .word 0x20001000
+    .word 0x41
+    .org 0x40
+reset:
+    movs r0, #0
+count:
+    adds r0, #1
+    b count
@@ -0,0 +1,32 @@
+@ LabWired - Firmware Simulation Platform
+@ Copyright (C) 2026 Andrii Shylenko
+@
+@ This software is released under the MIT License.
+@ See the LICENSE file in the project root for full license information.
+@
+@ DAP stepping fixture: one line per instruction, nested calls.
+@ Rebuild with: scripts/asm-fixture.py tests/fixtures/dap-step-thumbv7m.s tests/fixtures/dap-step-thumbv7m.elf
+@ Tests refer to source lines below; keep them stable.
+
+    .syntax unified
+    .thumb
+    .text
+vectors:
+    .word 0x20001000
+    .word 0x41
+    .org 0x40
+reset:
+    movs r0, #1
+    bl helper
+    movs r1, #2
+    movs r2, #3
+loop:
+    b loop
+helper:
+    push {lr}
+    bl leaf
+    movs r3, #4
+    pop {pc}
+leaf:
+    movs r4, #5
+    bx lr